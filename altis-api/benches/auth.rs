@@ -0,0 +1,38 @@
+//! Auth hot-path benchmarks. The middleware budget is 100µs per request;
+//! `cargo bench -p altis-api --bench auth` should stay well below it.
+use std::collections::HashMap;
+
+use altis_api::middleware::{key_cache::AuthKeyCache, CustomerClaims};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn bench_auth(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut api_keys = HashMap::new();
+    for i in 0..16 {
+        api_keys.insert(format!("partner-{}", i), format!("key-{}", i));
+    }
+    let cache = AuthKeyCache::from_secret("bench-secret", &api_keys);
+
+    let claims = CustomerClaims {
+        sub: "bench-customer".to_string(),
+        email: Some("bench@example.com".to_string()),
+        role: "CUSTOMER".to_string(),
+        exp: (chrono::Utc::now().timestamp() + 3600) as usize,
+        test: false,
+    };
+    let token = cache.encode(&claims).unwrap();
+
+    c.bench_function("decode_cached_jwt", |b| {
+        b.to_async(&runtime).iter(|| async {
+            cache.decode::<CustomerClaims>(&token).await.unwrap()
+        })
+    });
+
+    c.bench_function("verify_api_key_16_partners", |b| {
+        b.iter(|| cache.verify_api_key("key-15"))
+    });
+}
+
+criterion_group!(benches, bench_auth);
+criterion_main!(benches);
@@ -1,11 +1,18 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    Json,
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use crate::authz::authorize_product;
 use crate::state::AppState;
+use altis_core::tenant::TenantContext;
+use altis_catalog::product::{FlightProduct, FlightStatus};
+use altis_catalog::InventoryError;
+use altis_order::disruption::{BookingOutcome, ReaccommodationStats};
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::Money;
 
 // ============================================================================
 // Request/Response Types
@@ -21,6 +28,15 @@ pub struct CreateProductRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// A product's wording and media in one language
+#[derive(Debug, Deserialize)]
+pub struct ProductContentRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub media: Vec<altis_catalog::Media>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProductResponse {
     pub id: Uuid,
@@ -32,6 +48,50 @@ pub struct ProductResponse {
     pub base_price_nuc: i32,
     pub metadata: serde_json::Value,
     pub is_active: bool,
+    /// The product version in force; see GET /v1/admin/products/:id/versions
+    #[serde(default)]
+    pub version: i32,    /// Set while the product is soft-deleted; restore with POST /v1/admin/products/:id/restore
+    #[serde(default)]
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InventoryRuleRequest {
+    /// FLIGHT, SEAT, BAG, ...; fixed once the rule exists, so ignored on update
+    pub resource_type: Option<String>,
+    pub hold_duration_seconds: i32,
+    #[serde(default)]
+    pub overbooking_percentage: f64,
+    #[serde(default)]
+    pub min_availability_threshold: i32,
+    pub capacity: Option<i32>,
+    /// Unpaid orders a customer may hold with this resource at once; None is unlimited
+    pub max_holds_per_customer: Option<i32>,
+    /// Departure dates the resource can't be held for
+    #[serde(default)]
+    pub blackout_dates: Vec<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InventoryRuleResponse {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    pub resource_type: String,
+    pub hold_duration_seconds: i32,
+    pub overbooking_percentage: f64,
+    pub min_availability_threshold: i32,
+    pub capacity: Option<i32>,
+    pub max_holds_per_customer: Option<i32>,
+    pub blackout_dates: Vec<chrono::NaiveDate>,
+    pub is_active: bool,
+}
+
+/// An airline's business rules: what it overrides, and what it trades on
+#[derive(Debug, Serialize)]
+pub struct AirlineBusinessRulesResponse {
+    pub airline_id: Uuid,
+    pub overrides: serde_json::Value,
+    pub effective: altis_store::app_config::BusinessRules,
 }
 
 #[derive(Debug, Deserialize)]
@@ -81,12 +141,92 @@ pub struct BundleResponse {
 pub struct ListProductsQuery {
     pub product_type: Option<String>,
     pub is_active: Option<bool>,
+    /// Soft-deleted products are left out unless asked for
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteProductQuery {
+    /// Remove the row for good; refused while orders or offers reference it
+    #[serde(default)]
+    pub purge: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TriggerDisruptionRequest {
     pub flight_id: Uuid,
     pub new_status: String, // DELAYED, CANCELLED
+    /// Expected delay, used to decide whether protected self-connections are missed
+    pub delay_minutes: Option<i64>,
+    /// Reason for the disruption (WEATHER, TECHNICAL, ...), kept for compensation
+    pub cause: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDisruptionsQuery {
+    pub airline_id: Option<Uuid>,
+    pub flight_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// A disruption as the engine recorded it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DisruptionRecord {
+    pub id: Uuid,
+    pub flight_id: Uuid,
+    pub airline_id: Option<Uuid>,
+    pub new_status: String,
+    pub delay_minutes: Option<i32>,
+    pub cause: Option<String>,
+    pub reaccommodate: bool,
+    pub source: String,
+    pub reason: Option<String>,
+    pub affected_orders: i32,
+    pub proposals_made: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Unset while the engine is still working through affected orders
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisruptionSummaryResponse {
+    #[serde(flatten)]
+    pub disruption: DisruptionRecord,
+    pub reaccommodation: ReaccommodationStats,
+}
+
+/// A booking whose customer hasn't picked a replacement flight yet
+#[derive(Debug, Serialize)]
+pub struct PendingActionResponse {
+    pub order_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub open_proposals: i64,
+    pub hold_expires_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisruptionDetailResponse {
+    #[serde(flatten)]
+    pub summary: DisruptionSummaryResponse,
+    pub pending_actions: Vec<PendingActionResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DisruptedBooking {
+    disruption_id: Uuid,
+    order_id: Uuid,
+    item_id: Option<Uuid>,
+    order_status: Option<String>,
+    open_proposals: i64,
+    accepted: bool,
+    hold_expires_at: Option<String>,
+}
+
+impl DisruptedBooking {
+    fn outcome(&self) -> BookingOutcome {
+        BookingOutcome::classify(self.accepted, self.open_proposals, self.order_status.as_deref() == Some("CANCELLED"))
+    }
 }
 
 // ============================================================================
@@ -111,6 +251,7 @@ pub async fn create_product(
 
     let product_id = state.catalog_repo.create_product(&product_json).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    state.catalog_changed("product created").await;
     
     Ok(Json(ProductResponse {
         id: product_id,
@@ -122,6 +263,8 @@ pub async fn create_product(
         base_price_nuc: req.base_price_nuc,
         metadata: req.metadata.unwrap_or(serde_json::json!({})),
         is_active: true,
+        version: 1,
+        deleted_at: None,
     }))
 }
 
@@ -135,7 +278,8 @@ pub async fn list_products(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     let responses: Vec<ProductResponse> = products_json.into_iter()
-        .filter_map(|val| serde_json::from_value(val).ok())
+        .filter_map(|val| serde_json::from_value::<ProductResponse>(val).ok())
+        .filter(|product| query.include_deleted || product.deleted_at.is_none())
         .collect();
     
     Ok(Json(responses))
@@ -144,11 +288,10 @@ pub async fn list_products(
 /// GET /v1/admin/products/:id
 pub async fn get_product(
     State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
     Path(product_id): Path<Uuid>,
 ) -> Result<Json<ProductResponse>, StatusCode> {
-    let product_json = state.catalog_repo.get_product(product_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let product_json = authorize_product(&state, &tenant, product_id).await?;
 
     let response: ProductResponse = serde_json::from_value(product_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -159,9 +302,16 @@ pub async fn get_product(
 /// PUT /v1/admin/products/:id
 pub async fn update_product(
     State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
     Path(product_id): Path<Uuid>,
     Json(req): Json<CreateProductRequest>,
 ) -> Result<Json<ProductResponse>, StatusCode> {
+    let current = authorize_product(&state, &tenant, product_id).await?;
+    // Editing would quietly reactivate it; deleted products are restored first
+    if !current["deleted_at"].is_null() {
+        return Err(StatusCode::CONFLICT);
+    }
+
     let product_json = serde_json::json!({
         "product_type": req.product_type,
         "product_code": req.product_code,
@@ -173,11 +323,19 @@ pub async fn update_product(
 
     state.catalog_repo.update_product(product_id, &product_json).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    state.catalog_changed("product updated").await;
     
     let updated = state.catalog_repo.get_product(product_id).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
+    // A retimed flight carries its new times over to the bookings on it
+    if updated["product_type"].as_str() == Some("FLIGHT") {
+        if let Some(change) = altis_order::schedule_change::ScheduleChange::between(&current["metadata"], &updated["metadata"]) {
+            crate::schedule_changes::propagate(&state, &updated, &change, "ADMIN").await;
+        }
+    }
+
     let response: ProductResponse = serde_json::from_value(updated)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
@@ -185,27 +343,383 @@ pub async fn update_product(
 }
 
 /// DELETE /v1/admin/products/:id
+/// Soft-deletes the product, taking it off sale; `?purge=true` removes a product nothing has ever referenced
 pub async fn delete_product(
     State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(product_id): Path<Uuid>,
+    Query(query): Query<DeleteProductQuery>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_product(&state, &tenant, product_id).await?;
+    let removed = if query.purge {
+        let references = state.catalog_repo.count_product_references(product_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if references > 0 {
+            tracing::debug!("Refused to purge product {}: {} order or offer item(s) reference it", product_id, references);
+            return Err(StatusCode::CONFLICT);
+        }
+        state.catalog_repo.purge_product(product_id).await
+    } else {
+        state.catalog_repo.delete_product(product_id).await
+    }
+    .map_err(|e| {
+        tracing::error!("Failed to delete product {}: {:?}", product_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !removed {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.catalog_changed("product deleted").await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /v1/admin/products/:id/restore
+/// Puts a soft-deleted product back on sale
+pub async fn restore_product(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(product_id): Path<Uuid>,
+) -> Result<Json<ProductResponse>, StatusCode> {
+    authorize_product(&state, &tenant, product_id).await?;
+    let restored = state.catalog_repo.restore_product(product_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !restored {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.catalog_changed("product restored").await;
+
+    let product = state.catalog_repo.get_product(product_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(serde_json::from_value(product).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?))
+}
+
+/// GET /v1/admin/products/:id/content
+/// The product's translations, by locale
+pub async fn list_product_content(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
     Path(product_id): Path<Uuid>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    authorize_product(&state, &tenant, product_id).await?;
+    let content = state.catalog_repo.list_product_content(product_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(content))
+}
+
+/// PUT /v1/admin/products/:id/content/:locale
+/// Set the product's name, description and media in one language
+pub async fn put_product_content(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path((product_id, locale)): Path<(Uuid, String)>,
+    Json(req): Json<ProductContentRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let locale = altis_catalog::content::normalize_locale(&locale).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let blank = |text: &Option<String>| text.as_deref().is_some_and(|text| text.trim().is_empty());
+    if blank(&req.name) || blank(&req.description) || req.media.iter().any(|media| !media.url.starts_with("https://")) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    authorize_product(&state, &tenant, product_id).await?;
+
+    let content = serde_json::json!({"name": req.name, "description": req.description, "media": req.media});
+    let saved = state.catalog_repo.upsert_product_content(product_id, &locale, &content).await
+        .map_err(|e| {
+            tracing::error!("Failed to save {} content for product {}: {:?}", locale, product_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    state.catalog_changed("product content updated").await;
+    Ok(Json(saved))
+}
+
+/// DELETE /v1/admin/products/:id/content/:locale
+/// Drop a translation; offers in that language go back to the product's own wording
+pub async fn delete_product_content(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path((product_id, locale)): Path<(Uuid, String)>,
 ) -> Result<StatusCode, StatusCode> {
-    state.catalog_repo.delete_product(product_id).await
+    authorize_product(&state, &tenant, product_id).await?;
+    let locale = altis_catalog::content::normalize_locale(&locale).ok_or(StatusCode::NOT_FOUND)?;
+    let deleted = state.catalog_repo.delete_product_content(product_id, &locale).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.catalog_changed("product content deleted").await;
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// GET /v1/admin/inventory/:product_id
+/// Live counts and utilization of a tracked product
+pub async fn get_inventory(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(product_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    authorize_product(&state, &tenant, product_id).await?;
+    let item = state.inventory.get(product_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to read inventory for {}: {}", product_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(serde_json::json!({
+        "product_id": product_id,
+        "total_capacity": item.total_capacity,
+        "available_quantity": item.available_quantity,
+        "reserved_quantity": item.reserved_quantity,
+        "sold_quantity": item.sold_quantity(),
+        "utilization": item.utilization(),
+    })))
+}
+
+// ============================================================================
+// Inventory Rules Handlers
+// ============================================================================
+
+fn validate_inventory_rule(req: &InventoryRuleRequest) -> Result<(), &'static str> {
+    if req.hold_duration_seconds <= 0 {
+        return Err("hold_duration_seconds must be positive");
+    }
+    // Stored as NUMERIC(5,2)
+    if !(0.0..100.0).contains(&req.overbooking_percentage) {
+        return Err("overbooking_percentage is outside 0-100");
+    }
+    if req.min_availability_threshold < 0 || req.capacity.is_some_and(|capacity| capacity < 0) {
+        return Err("min_availability_threshold and capacity must not be negative");
+    }
+    if req.max_holds_per_customer.is_some_and(|max| max <= 0) {
+        return Err("max_holds_per_customer must be positive");
+    }
+    Ok(())
+}
+
+fn inventory_rule_json(req: &InventoryRuleRequest) -> serde_json::Value {
+    serde_json::json!({
+        "resource_type": req.resource_type,
+        "hold_duration_seconds": req.hold_duration_seconds,
+        "overbooking_percentage": req.overbooking_percentage,
+        "min_availability_threshold": req.min_availability_threshold,
+        "capacity": req.capacity,
+        "max_holds_per_customer": req.max_holds_per_customer,
+        "blackout_dates": req.blackout_dates,
+    })
+}
+
+fn inventory_rule_response(rule: serde_json::Value) -> Result<InventoryRuleResponse, StatusCode> {
+    serde_json::from_value(rule).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /v1/admin/airlines/:airline_id/inventory-rules
+/// One active rule per resource type; a second one is refused with 409
+pub async fn create_inventory_rule(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Json(req): Json<InventoryRuleRequest>,
+) -> Result<(StatusCode, Json<InventoryRuleResponse>), StatusCode> {
+    let known_type = req.resource_type.as_deref()
+        .is_some_and(|t| serde_json::from_value::<altis_catalog::ProductType>(serde_json::json!(t)).is_ok());
+    if let Err(reason) = validate_inventory_rule(&req).and(if known_type { Ok(()) } else { Err("resource_type is missing or unknown") }) {
+        tracing::debug!("Rejected inventory rule for airline {}: {}", airline_id, reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let created = state.catalog_repo.create_inventory_rule(airline_id, &inventory_rule_json(&req)).await
+        .map_err(|e| {
+            tracing::error!("Failed to create inventory rule for airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::CONFLICT)?;
+    state.catalog_changed("inventory rule created").await;
+
+    Ok((StatusCode::CREATED, Json(inventory_rule_response(created)?)))
+}
+
+/// GET /v1/admin/airlines/:airline_id/inventory-rules
+/// The rules in force, one per resource type
+pub async fn list_inventory_rules(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<Json<Vec<InventoryRuleResponse>>, StatusCode> {
+    let rules = state.catalog_repo.list_inventory_rules(airline_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(rules.into_iter().map(inventory_rule_response).collect::<Result<_, _>>()?))
+}
+
+/// GET /v1/admin/inventory-rules/:id
+pub async fn get_inventory_rule(
+    State(state): State<AppState>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<InventoryRuleResponse>, StatusCode> {
+    let rule = state.catalog_repo.get_inventory_rule_by_id(rule_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(inventory_rule_response(rule)?))
+}
+
+/// PUT /v1/admin/inventory-rules/:id
+pub async fn update_inventory_rule(
+    State(state): State<AppState>,
+    Path(rule_id): Path<Uuid>,
+    Json(req): Json<InventoryRuleRequest>,
+) -> Result<Json<InventoryRuleResponse>, StatusCode> {
+    if let Err(reason) = validate_inventory_rule(&req) {
+        tracing::debug!("Rejected update of inventory rule {}: {}", rule_id, reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let updated = state.catalog_repo.update_inventory_rule(rule_id, &inventory_rule_json(&req)).await
+        .map_err(|e| {
+            tracing::error!("Failed to update inventory rule {}: {:?}", rule_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    state.catalog_changed("inventory rule updated").await;
+
+    Ok(Json(inventory_rule_response(updated)?))
+}
+
+/// DELETE /v1/admin/inventory-rules/:id
+/// Retire a rule; its resource type falls back to the global hold and has no limits
+pub async fn delete_inventory_rule(
+    State(state): State<AppState>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let retired = state.catalog_repo.deactivate_inventory_rule(rule_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !retired {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.catalog_changed("inventory rule deleted").await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Airline Business Rules Handlers
+// ============================================================================
+
+/// The global rules with `overrides` applied, refused if a name is unknown,
+/// a value has the wrong type, or the result couldn't be traded on
+fn airline_business_rules(
+    defaults: &altis_store::app_config::BusinessRules,
+    overrides: &serde_json::Value,
+) -> Result<altis_store::app_config::BusinessRules, String> {
+    let rules = defaults.with_overrides(overrides)?;
+    match rules.problems().into_iter().next() {
+        Some(problem) => Err(problem.to_string()),
+        None => Ok(rules),
+    }
+}
+
+/// GET /v1/admin/airlines/:airline_id/business-rules
+/// The airline's overrides and the rules that result; no overrides is `{}`
+pub async fn get_airline_business_rules(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<Json<AirlineBusinessRulesResponse>, StatusCode> {
+    let overrides = state.catalog_repo.get_business_rule(airline_id, "CONFIG").await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .unwrap_or_else(|| serde_json::json!({}));
+    let effective = airline_business_rules(&state.business_rules, &overrides).map_err(|e| {
+        tracing::error!("Stored business rules of airline {} no longer apply: {}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(AirlineBusinessRulesResponse { airline_id, overrides, effective }))
+}
+
+/// PUT /v1/admin/airlines/:airline_id/business-rules
+/// Replace the airline's overrides; settings left out follow the global config
+pub async fn put_airline_business_rules(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Json(overrides): Json<serde_json::Value>,
+) -> Result<Json<AirlineBusinessRulesResponse>, StatusCode> {
+    let effective = match airline_business_rules(&state.business_rules, &overrides) {
+        Ok(rules) => rules,
+        Err(reason) => {
+            tracing::debug!("Rejected business rules for airline {}: {}", airline_id, reason);
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    };
+
+    state.catalog_repo.put_airline_business_rules(airline_id, &overrides).await
+        .map_err(|e| {
+            tracing::error!("Failed to save business rules for airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    state.catalog_changed("business rules updated").await;
+
+    Ok(Json(AirlineBusinessRulesResponse { airline_id, overrides, effective }))
+}
+
+/// DELETE /v1/admin/airlines/:airline_id/business-rules
+/// Drop the airline's overrides; it trades on the global config again
+pub async fn delete_airline_business_rules(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let cleared = state.catalog_repo.clear_airline_business_rules(airline_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !cleared {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.catalog_changed("business rules cleared").await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Tax Code Handlers
+// ============================================================================
+
+/// GET /v1/admin/tax-codes
+pub async fn list_tax_codes(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let codes = state.catalog_repo.list_tax_codes().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(codes))
+}
+
+/// POST /v1/admin/tax-codes
+pub async fn create_tax_code(
+    State(state): State<AppState>,
+    Json(req): Json<altis_catalog::TaxCode>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    if req.rate.is_none() && req.amount_nuc.is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut tax_code = serde_json::to_value(&req).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let id = state.catalog_repo.create_tax_code(&tax_code).await.map_err(|e| {
+        tracing::error!("Failed to create tax code {}: {:?}", req.code, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    // Cached searches were priced without it
+    state.catalog_changed("tax code created").await;
+
+    tax_code["id"] = serde_json::json!(id);
+    Ok((StatusCode::CREATED, Json(tax_code)))
+}
+
 // ============================================================================
 // Pricing Rules Handlers
 // ============================================================================
 
 /// POST /v1/admin/airlines/:airline_id/pricing-rules
 pub async fn create_pricing_rule(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(airline_id): Path<Uuid>,
     Json(req): Json<CreatePricingRuleRequest>,
 ) -> Result<Json<PricingRuleResponse>, StatusCode> {
+    // Conditions on the route must name one the airline flies
+    let airport = |field: &str| req.conditions[field].as_str();
+    crate::markets::check_scope(&state, airline_id, airport("origin"), airport("destination")).await?;
+
     // Create pricing rule
     let rule_id = Uuid::new_v4();
+    state.catalog_changed("pricing rule created").await;
     
     Ok(Json(PricingRuleResponse {
         id: rule_id,
@@ -221,26 +735,19 @@ pub async fn create_pricing_rule(
 
 /// GET /v1/admin/airlines/:airline_id/pricing-rules
 pub async fn list_pricing_rules(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(airline_id): Path<Uuid>,
 ) -> Result<Json<Vec<PricingRuleResponse>>, StatusCode> {
-    // Mock pricing rules
-    let rules = vec![
-        PricingRuleResponse {
-            id: Uuid::new_v4(),
-            airline_id,
-            rule_name: "Continuous Pricing - Economy".to_string(),
-            rule_type: "DEMAND".to_string(),
-            conditions: serde_json::json!({"cabin_class": "ECONOMY"}),
-            adjustments: serde_json::json!({
-                "type": "FORMULA",
-                "formula": "1.0 + (utilization^2 * 2.0)"
-            }),
-            priority: 10,
-            is_active: true,
-        },
-    ];
-    
+    let rules = state.catalog_cache.pricing_rules(airline_id).await.map_err(|e| {
+        tracing::error!("Failed to load pricing rules for airline {}: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let rules = rules.iter()
+        .map(|rule| serde_json::from_value(rule.clone()))
+        .collect::<Result<Vec<PricingRuleResponse>, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
     Ok(Json(rules))
 }
 
@@ -265,10 +772,11 @@ pub async fn update_pricing_rule(
 
 /// DELETE /v1/admin/pricing-rules/:id
 pub async fn delete_pricing_rule(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(_rule_id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
     // TODO: Implement pricing rule deletion
+    state.catalog_changed("pricing rule deleted").await;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -350,10 +858,88 @@ pub async fn delete_bundle(
     // TODO: Implement bundle deletion
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// POST /v1/admin/disruptions
+/// Apply a flight status change by hand; passengers are always re-accommodated
 pub async fn trigger_disruption(
     State(state): State<AppState>,
     Json(req): Json<TriggerDisruptionRequest>,
 ) -> Result<StatusCode, StatusCode> {
+    apply_disruption(&state, &req, true, "ADMIN", "Flight disruption triggered by admin").await?;
+    Ok(StatusCode::OK)
+}
+
+/// GET /v1/admin/disruptions
+/// Recent disruptions, newest first, with how passengers responded to the proposals
+pub async fn list_disruptions(
+    State(state): State<AppState>,
+    Query(query): Query<ListDisruptionsQuery>,
+) -> Result<Json<Vec<DisruptionSummaryResponse>>, StatusCode> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let disruptions: Vec<DisruptionRecord> = state.disruption_repo.list_disruptions(query.airline_id, query.flight_id, limit).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect();
+    let ids: Vec<Uuid> = disruptions.iter().map(|d| d.id).collect();
+    let bookings = disrupted_bookings(&state, &ids).await?;
+
+    Ok(Json(disruptions.into_iter().map(|disruption| {
+        let outcomes = bookings.iter().filter(|b| b.disruption_id == disruption.id).map(DisruptedBooking::outcome);
+        DisruptionSummaryResponse { reaccommodation: ReaccommodationStats::from_outcomes(outcomes), disruption }
+    }).collect()))
+}
+
+/// GET /v1/admin/disruptions/:id
+/// One disruption with its response rates and the bookings still awaiting the customer
+pub async fn get_disruption(
+    State(state): State<AppState>,
+    Path(disruption_id): Path<Uuid>,
+) -> Result<Json<DisruptionDetailResponse>, StatusCode> {
+    let disruption: DisruptionRecord = state.disruption_repo.get_disruption(disruption_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(serde_json::from_value)
+        .ok_or(StatusCode::NOT_FOUND)?
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let bookings = disrupted_bookings(&state, &[disruption_id]).await?;
+
+    let pending_actions = bookings.iter()
+        .filter(|b| b.outcome() == BookingOutcome::Pending)
+        .map(|b| PendingActionResponse {
+            order_id: b.order_id,
+            item_id: b.item_id,
+            open_proposals: b.open_proposals,
+            hold_expires_at: b.hold_expires_at.clone(),
+        })
+        .collect();
+    Ok(Json(DisruptionDetailResponse {
+        summary: DisruptionSummaryResponse {
+            reaccommodation: ReaccommodationStats::from_outcomes(bookings.iter().map(DisruptedBooking::outcome)),
+            disruption,
+        },
+        pending_actions,
+    }))
+}
+
+async fn disrupted_bookings(state: &AppState, ids: &[Uuid]) -> Result<Vec<DisruptedBooking>, StatusCode> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows = state.disruption_repo.list_disrupted_bookings(ids).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(rows.into_iter().filter_map(|row| serde_json::from_value(row).ok()).collect())
+}
+
+/// Records the disruption on every affected order, moves passengers to an
+/// alternative flight when `reaccommodate` is set, and settles missed
+/// connection protection
+pub(crate) async fn apply_disruption(
+    state: &AppState,
+    req: &TriggerDisruptionRequest,
+    reaccommodate: bool,
+    actor: &str,
+    reason: &str,
+) -> Result<(), StatusCode> {
     // 1. Fetch flight details to know origin/destination
     let flight_json = state.catalog_repo.get_product(req.flight_id).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
@@ -369,18 +955,40 @@ pub async fn trigger_disruption(
 
     tracing::info!("Found {} affected orders for flight {} ({}-{})", affected_orders.len(), req.flight_id, origin, destination);
 
-    // 3. Search for alternative flight (same route, different ID)
+    let disruption_id = state.disruption_repo.create_disruption(&serde_json::json!({
+        "flight_id": req.flight_id,
+        "airline_id": (!airline_id.is_nil()).then_some(airline_id),
+        "new_status": req.new_status,
+        "delay_minutes": req.delay_minutes,
+        "cause": req.cause,
+        "reaccommodate": reaccommodate,
+        "source": actor,
+        "reason": reason,
+    })).await.map_err(|e| {
+        tracing::error!("Failed to record disruption of flight {}: {:?}", req.flight_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // A cancelled flight stops selling, including seats its passengers give back
+    if req.new_status == "CANCELLED" {
+        match state.inventory.close(req.flight_id).await {
+            Ok(()) | Err(InventoryError::NotFound(_)) => {}
+            Err(e) => tracing::error!("Failed to take cancelled flight {} off sale: {}", req.flight_id, e),
+        }
+    }
+
+    // 3. Candidate replacement flights, with live seat counts
     let alt_flights = state.catalog_repo.list_products(airline_id, Some("FLIGHT")).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    let alternative = alt_flights.iter()
-        .find(|f| {
-            f["metadata"]["origin"] == origin && 
-            f["metadata"]["destination"] == destination && 
-            f["id"] != req.flight_id.to_string()
-        });
+    let candidates = if reaccommodate {
+        crate::reaccommodation::candidate_flights(state, &alt_flights).await
+    } else {
+        Vec::new()
+    };
 
     // 4. Update orders
+    let affected_count = affected_orders.len() as i32;
+    let mut proposals_made = 0;
     for order_val in affected_orders {
         let order_id = Uuid::parse_str(order_val["id"].as_str().unwrap_or_default()).unwrap_or_default();
         
@@ -389,28 +997,169 @@ pub async fn trigger_disruption(
             order_id,
             "FLIGHT_DISRUPTION",
             None,
-            Some(serde_json::json!({"flight_id": req.flight_id, "new_status": req.new_status})),
-            "ADMIN",
-            Some("Flight disruption triggered by admin")
+            Some(serde_json::json!({"flight_id": req.flight_id, "new_status": req.new_status, "delay_minutes": req.delay_minutes, "cause": req.cause, "disruption_id": disruption_id})),
+            actor,
+            Some(reason)
         ).await;
 
-        // Add Re-accommodation if alternative found
-        if let Some(alt) = alternative {
-            let mut metadata = alt["metadata"].clone();
-            metadata["disrupted_flight_id"] = serde_json::json!(req.flight_id.to_string());
-            
-            let reac_item = serde_json::json!({
-                "product_type": "FLIGHT",
-                "product_id": alt["id"],
-                "name": alt["name"],
-                "price_nuc": 0, // Involuntary re-accommodation is free
-                "status": "REACCOMMODATED",
-                "metadata": metadata
-            });
-
-            let _ = state.order_repo.add_order_item(order_id, &reac_item).await;
+        // Propose ranked alternatives, holding seats for the customer to choose
+        let proposed = if reaccommodate {
+            let proposed = crate::reaccommodation::propose_alternatives(state, disruption_id, &order_val, &flight_json, &candidates).await;
+            tracing::info!("Proposed {} alternative(s) on order {}", proposed, order_id);
+            proposals_made += proposed as i32;
+            proposed
+        } else {
+            0
+        };
+
+        // Tell the passenger on their alert channels, with the proposals ready
+        let alert = crate::travel_alerts::disruption_alert(req, disruption_id, &flight_json, proposed);
+        crate::travel_alerts::send_alert(state, &order_val, &alert).await;
+
+        // 5. Missed connection protection on separately ticketed onward flights
+        if let Ok(order) = serde_json::from_value::<altis_order::Order>(order_val) {
+            apply_connection_protection(state, req, &order, &alt_flights).await;
         }
     }
 
-    Ok(StatusCode::OK)
+    if let Err(e) = state.disruption_repo.complete_disruption(disruption_id, affected_count, proposals_made).await {
+        tracing::error!("Failed to complete disruption {}: {:?}", disruption_id, e);
+    }
+    Ok(())
+}
+
+async fn apply_connection_protection(
+    state: &AppState,
+    req: &TriggerDisruptionRequest,
+    order: &altis_order::Order,
+    catalog_flights: &[serde_json::Value],
+) {
+    use altis_order::protection::ProtectionOutcome;
+
+    let Ok(status) = serde_json::from_value::<FlightStatus>(serde_json::json!(req.new_status)) else {
+        return;
+    };
+
+    let mut alternatives: Vec<FlightProduct> = catalog_flights.iter().filter_map(flight_product).collect();
+    // Live counts beat the catalog's static seat numbers
+    for alternative in &mut alternatives {
+        if let Ok(Some(inventory)) = state.inventory.get(alternative.product.id).await {
+            alternative.available_seats = inventory.available_quantity;
+        }
+    }
+    let outcomes = altis_order::disruption::DisruptionManager::new().resolve_protected_connections(
+        &req.flight_id.to_string(),
+        status,
+        req.delay_minutes.unwrap_or(0),
+        order,
+        &alternatives,
+    );
+
+    for outcome in outcomes {
+        match &outcome {
+            ProtectionOutcome::Rebook { order_id, protection_item_id, downstream_item_id, replacement } => {
+                if let Some(flight) = replacement.product_id {
+                    if !take_seat(state, flight).await {
+                        tracing::warn!("Protected connection on order {} not rebooked: flight {} filled up", order_id, flight);
+                        continue;
+                    }
+                }
+                let Ok(item_json) = serde_json::to_value(replacement) else { continue };
+                if state.order_repo.add_order_item(*order_id, &item_json).await.is_err() {
+                    tracing::error!("Failed to rebook protected connection on order {}", order_id);
+                    continue;
+                }
+                let _ = state.order_repo.update_item_status(*downstream_item_id, "MODIFIED").await;
+                let _ = state.order_repo.update_item_revenue_status(*protection_item_id, "EARNED").await;
+            }
+            ProtectionOutcome::Refund { order_id, protection_item_id, downstream_item_id, amount } => {
+                let Ok(refund_nuc) = amount.to_i32() else {
+                    tracing::error!("Protection refund on order {} is out of range", order_id);
+                    continue;
+                };
+                if state.order_repo.update_item_status(*downstream_item_id, "REFUNDED").await.is_err() {
+                    tracing::error!("Failed to refund protected connection on order {}", order_id);
+                    continue;
+                }
+                let _ = state.order_repo.update_item_revenue_status(*downstream_item_id, "REFUNDED").await;
+                let _ = state.order_repo.add_order_ledger_entry(
+                    *order_id,
+                    *downstream_item_id,
+                    "REFUND",
+                    -refund_nuc,
+                    Some("Missed connection protection refund"),
+                ).await;
+                crate::finance::post_journal(
+                    state,
+                    JournalTransaction::refund(*order_id, Some(*downstream_item_id), *amount, Money::zero(amount.currency()), "Missed connection protection refund"),
+                ).await;
+                let _ = state.order_repo.update_item_revenue_status(*protection_item_id, "EARNED").await;
+            }
+        }
+
+        let (change_type, order_id) = match &outcome {
+            ProtectionOutcome::Rebook { order_id, .. } => ("CONNECTION_PROTECTION_REBOOK", *order_id),
+            ProtectionOutcome::Refund { order_id, .. } => ("CONNECTION_PROTECTION_REFUND", *order_id),
+        };
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            change_type,
+            None,
+            serde_json::to_value(&outcome).ok(),
+            "SYSTEM",
+            Some("Inbound flight disrupted on a protected self-connection"),
+        ).await;
+    }
+}
+
+/// Sells a seat on `flight` to a passenger moved onto it; false when the
+/// flight is full. Untracked flights always have room.
+async fn take_seat(state: &AppState, flight: Uuid) -> bool {
+    match state.inventory.reserve(flight, 1).await {
+        Ok(()) => {
+            if let Err(e) = state.inventory.commit(flight, 1).await {
+                tracing::error!("Re-accommodation seat on flight {} held but not sold: {}", flight, e);
+            }
+            true
+        }
+        Err(InventoryError::NotFound(_)) => true,
+        Err(InventoryError::InsufficientInventory { .. }) => false,
+        Err(e) => {
+            // Moving the passenger matters more than the count being exact
+            tracing::error!("Failed to take a seat on flight {} for re-accommodation: {}", flight, e);
+            true
+        }
+    }
+}
+
+/// Catalog flights carry their schedule in metadata; flights without one can't be
+/// matched against a connection and are skipped.
+pub(crate) fn flight_product(product: &serde_json::Value) -> Option<FlightProduct> {
+    let metadata = &product["metadata"];
+    let time = |field: &str| {
+        metadata[field].as_str()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&chrono::Utc))
+    };
+
+    Some(FlightProduct {
+        product: altis_catalog::Product {
+            id: Uuid::parse_str(product["id"].as_str()?).ok()?,
+            product_type: altis_catalog::ProductType::Flight,
+            product_code: product["product_code"].as_str().unwrap_or_default().to_string(),
+            name: product["name"].as_str().unwrap_or_default().to_string(),
+            description: product["description"].as_str().map(|s| s.to_string()),
+            base_price_nuc: product["base_price_nuc"].as_i64().unwrap_or(0) as i32,
+            margin_percentage: product["margin_percentage"].as_f64().unwrap_or(0.15),
+            is_active: product["is_active"].as_bool().unwrap_or(true),
+            metadata: metadata.clone(),
+        },
+        flight_id: Uuid::parse_str(metadata["flight_id"].as_str().or(product["id"].as_str())?).ok()?,
+        origin: metadata["origin"].as_str()?.to_string(),
+        destination: metadata["destination"].as_str()?.to_string(),
+        departure_time: time("departure_time")?,
+        arrival_time: time("arrival_time")?,
+        available_seats: metadata["available_seats"].as_i64().unwrap_or(1) as i32,
+        status: FlightStatus::Scheduled,
+    })
 }
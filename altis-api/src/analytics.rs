@@ -0,0 +1,188 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Longest utm_* value kept; longer ones are cut
+const MAX_CAMPAIGN_FIELD_LEN: usize = 255;
+
+/// Marketing parameters a search arrived with (from the landing page URL)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CampaignParams {
+    pub utm_source: Option<String>,
+    pub utm_medium: Option<String>,
+    pub utm_campaign: Option<String>,
+    pub utm_term: Option<String>,
+    pub utm_content: Option<String>,
+}
+
+impl CampaignParams {
+    pub fn is_empty(&self) -> bool {
+        [&self.utm_source, &self.utm_medium, &self.utm_campaign, &self.utm_term, &self.utm_content]
+            .iter()
+            .all(|field| field.as_deref().is_none_or(|v| v.trim().is_empty()))
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let clean = |field: &Option<String>| {
+            field.as_deref().map(|v| v.trim().chars().take(MAX_CAMPAIGN_FIELD_LEN).collect::<String>())
+        };
+        serde_json::json!({
+            "utm_source": clean(&self.utm_source),
+            "utm_medium": clean(&self.utm_medium),
+            "utm_campaign": clean(&self.utm_campaign),
+            "utm_term": clean(&self.utm_term),
+            "utm_content": clean(&self.utm_content),
+        })
+    }
+}
+
+/// Keyed hash standing in for an identifier in attribution tables. `kind`
+/// separates namespaces, so a customer id and an order id that happen to be
+/// equal still hash differently.
+pub fn hash_identifier(salt: &str, kind: &str, id: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(kind.as_bytes());
+    mac.update(b":");
+    mac.update(id.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Records a campaign touch for the searching customer, off the request path
+pub fn record_touch(state: &AppState, customer_id: &str, campaign: &CampaignParams) {
+    if campaign.is_empty() {
+        return;
+    }
+    let subject_hash = hash_identifier(&state.attribution.salt, "customer", customer_id);
+    let campaign = campaign.to_json();
+    let repo = state.attribution_repo.clone();
+    tokio::spawn(async move {
+        if let Err(e) = repo.record_touch(&subject_hash, &campaign).await {
+            tracing::warn!("Failed to record campaign touch: {:?}", e);
+        }
+    });
+}
+
+/// Records a paid order for attribution. Best effort: a miss only
+/// undercounts a campaign.
+pub async fn record_conversion(state: &AppState, order_id: Uuid, customer_id: &str, value_nuc: i32) {
+    let order_hash = hash_identifier(&state.attribution.salt, "order", &order_id.to_string());
+    let subject_hash = hash_identifier(&state.attribution.salt, "customer", customer_id);
+    if let Err(e) = state.attribution_repo.record_conversion(&order_hash, &subject_hash, value_nuc).await {
+        tracing::warn!("Failed to record conversion for order {}: {:?}", order_id, e);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttributionReportQuery {
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// "csv" for a download; JSON otherwise
+    pub format: Option<String>,
+}
+
+/// Campaign totals; there are no identifiers, hashed or otherwise, at this level
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CampaignAttribution {
+    pub utm_source: String,
+    pub utm_medium: String,
+    pub utm_campaign: String,
+    /// Distinct customers who searched from the campaign in the period
+    pub reached: i64,
+    pub conversions: i64,
+    pub revenue_nuc: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttributionReportResponse {
+    pub from: String,
+    pub to: String,
+    pub lookback_days: i32,
+    pub campaigns: Vec<CampaignAttribution>,
+}
+
+/// GET /v1/admin/analytics/attribution
+/// Reach, last-touch conversions and revenue per campaign over a date range
+pub async fn get_attribution_report(
+    State(state): State<AppState>,
+    Query(query): Query<AttributionReportQuery>,
+) -> Result<Response, StatusCode> {
+    let to = query.to.unwrap_or_else(chrono::Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(30));
+    if from >= to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let lookback_days = state.attribution.lookback_days;
+    let campaigns: Vec<CampaignAttribution> = state.attribution_repo.campaign_report(from, to, lookback_days).await
+        .map_err(|e| {
+            tracing::error!("Attribution report failed: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if query.format.as_deref() == Some("csv") {
+        let filename = format!("attribution-{}-{}.csv", from.format("%Y%m%d"), to.format("%Y%m%d"));
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            ],
+            attribution_csv(&campaigns),
+        ).into_response());
+    }
+
+    Ok(Json(AttributionReportResponse {
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        lookback_days,
+        campaigns,
+    }).into_response())
+}
+
+fn attribution_csv(campaigns: &[CampaignAttribution]) -> String {
+    // utm values come from landing page URLs; keep spreadsheets from running them as formulas
+    let quote = |v: &str| {
+        let v = if v.starts_with(['=', '+', '-', '@']) { format!("'{}", v) } else { v.to_string() };
+        format!("\"{}\"", v.replace('"', "\"\""))
+    };
+    let mut csv = String::from("utm_source,utm_medium,utm_campaign,reached,conversions,revenue_nuc\n");
+    for c in campaigns {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            quote(&c.utm_source), quote(&c.utm_medium), quote(&c.utm_campaign),
+            c.reached, c.conversions, c.revenue_nuc,
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_identifier_is_keyed_and_namespaced() {
+        let hash = hash_identifier("salt-a", "customer", "cust-1");
+        assert_eq!(hash.len(), 64);
+        assert!(!hash.contains("cust-1"));
+        assert_eq!(hash, hash_identifier("salt-a", "customer", "cust-1"));
+        assert_ne!(hash, hash_identifier("salt-b", "customer", "cust-1"));
+        assert_ne!(hash, hash_identifier("salt-a", "order", "cust-1"));
+    }
+}
@@ -0,0 +1,330 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use altis_core::payment::PaymentStatus;
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::{Currency, Money};
+
+use crate::middleware::auth::CustomerClaims;
+use crate::offers::OfferResponse;
+use crate::orders::{OrderItemResponse, OrderResponse};
+use crate::state::AppState;
+
+// ============================================================================
+// Models
+// ============================================================================
+
+/// An existing booking, as the traveler or a partner knows it
+#[derive(Debug, Deserialize)]
+pub struct AncillarySearchRequest {
+    /// The six characters on the itinerary, in any case
+    pub booking_reference: String,
+    /// Last name of the contact or of any traveler on the booking
+    pub last_name: String,
+    /// Date of a flight on the booking the extras are for
+    pub departure_date: NaiveDate,
+}
+
+/// The order an ancillary-only offer was made for, if it was
+pub(crate) fn attached_order(offer: &altis_offer::Offer) -> Option<Uuid> {
+    offer.search_context["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// The active flight on `date` that has yet to leave
+fn booked_flight(order: &OrderResponse, date: NaiveDate, now: DateTime<Utc>) -> Result<&OrderItemResponse, StatusCode> {
+    let departure = |item: &OrderItemResponse| item.metadata["departure_time"].as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc));
+    let flight = order.items.iter()
+        .filter(|item| item.product_type.eq_ignore_ascii_case("FLIGHT") && item.status == "ACTIVE")
+        .find(|item| {
+            let booked_date = item.metadata["departure_date"].as_str()
+                .and_then(|d| d.parse::<NaiveDate>().ok())
+                .or_else(|| departure(item).map(|t| t.date_naive()));
+            booked_date == Some(date)
+        })
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    // Without a time, the flight is taken to leave at the end of its day
+    let leaves = departure(flight).unwrap_or_else(|| date.and_hms_opt(23, 59, 59).unwrap_or_default().and_utc());
+    if leaves <= now {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    Ok(flight)
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/offers/ancillaries
+/// Ancillary-only offers for an existing booking, found by booking reference
+pub async fn search_ancillary_offers(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Json(req): Json<AncillarySearchRequest>,
+) -> Result<Json<Vec<OfferResponse>>, StatusCode> {
+    let reference = req.booking_reference.trim();
+    let last_name = req.last_name.trim();
+    if last_name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if reference.len() != 6 || !reference.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    // Sandbox bookings are only found with sandbox keys, and live ones never
+    let bookings: Vec<OrderResponse> = state.order_repo.find_orders_by_reference(reference, last_name).await
+        .map_err(|e| {
+            tracing::error!("Failed to look up booking {}: {:?}", reference, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .filter_map(|order| serde_json::from_value::<OrderResponse>(order).ok())
+        .filter(|order| order.test == claims.test)
+        .collect();
+    if bookings.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    // Two bookings may share a reference; the flight date tells them apart
+    let now = Utc::now();
+    let (order, flight) = bookings.iter()
+        .find_map(|order| booked_flight(order, req.departure_date, now).ok().map(|flight| (order, flight)))
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    if !matches!(order.status.as_str(), "PAID" | "FULFILLED") {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let offers = price_ancillaries(&state, order, flight).await?;
+    let offer_values = offers.iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    state.offer_repo.save_offers_batch(&offer_values).await.map_err(|e| {
+        tracing::error!("Failed to persist ancillary offers for order {}: {:?}", order.id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let content = altis_catalog::ProductContent::default();
+    Ok(Json(offers.iter().map(|offer| crate::offers::offer_response(offer, &content, &[])).collect()))
+}
+
+/// The airline's sellable ancillaries, each priced as an offer on the booked flight
+async fn price_ancillaries(state: &AppState, order: &OrderResponse, flight: &OrderItemResponse) -> Result<Vec<altis_offer::Offer>, StatusCode> {
+    let order_json = state.order_repo.get_order(order.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let airline_id = order_json["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or(StatusCode::CONFLICT)?;
+    let (products, tax_engine, campaigns, routes) = tokio::try_join!(
+        state.catalog_cache.products(airline_id),
+        state.catalog_cache.tax_engine(),
+        state.catalog_cache.campaigns(airline_id),
+        state.catalog_cache.routes(airline_id),
+    ).map_err(|e| {
+        tracing::error!("Failed to load the catalog of airline {} for ancillary offers: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // The booked trip, with the segment it was shopped under when the offer is still around
+    let search = match order.offer_id {
+        Some(offer_id) => state.offer_repo.get_offer(offer_id).await.ok().flatten()
+            .map(|offer| offer["search_context"].clone())
+            .unwrap_or_default(),
+        None => serde_json::Value::Null,
+    };
+    let passengers = order.travelers.as_ref().map(|t| t.len() as i64).filter(|n| *n > 0)
+        .or(order.group_size.map(i64::from))
+        .unwrap_or(1);
+    let context = serde_json::json!({
+        "origin": flight.metadata["origin"],
+        "destination": flight.metadata["destination"],
+        "departure_date": flight.metadata["departure_date"],
+        "departure_time": flight.metadata["departure_time"],
+        "flight_id": flight.metadata["flight_id"],
+        "passengers": passengers,
+        "booking_reference": crate::itinerary_email::booking_reference(order.id),
+    });
+
+    let ancillaries: Vec<altis_catalog::Product> = products.iter()
+        .filter(|p| p["is_active"].as_bool().unwrap_or(true))
+        .map(crate::offers::catalog_product)
+        .filter(|p| p.product_type != altis_catalog::ProductType::Flight)
+        .collect();
+    let ancillaries = crate::offers::sellable_ancillaries(state, airline_id, ancillaries, &context).await;
+
+    let generator = altis_offer::generator::OfferGenerator::new(
+        altis_catalog::pricing::PricingEngine::new(crate::offers::pricing_config(state, airline_id).await)
+            .with_campaigns((*campaigns).clone())
+    ).with_tax_engine((*tax_engine).clone())
+        .with_routes((*routes).clone());
+    let user_segment = search["user_segment"].as_str().map(str::to_string);
+    generator.generate_ancillary_offers(order.id, Some(airline_id), user_segment, context, &ancillaries)
+        .map_err(|e| {
+            tracing::error!("Ancillary offers for order {} failed: {:?}", order.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Accepting an ancillary-only offer: its items are charged to
+/// `payment_reference` and added to the booking it was made for, each for
+/// the traveler on it `item_travelers` names, if any. The offer
+/// is claimed first, so it can only be bought once; it is put back on sale
+/// if the booking no longer takes extras, stock has run out or the card is
+/// declined.
+pub(crate) async fn attach_offer(
+    state: &AppState,
+    claims: &CustomerClaims,
+    offer: &altis_offer::Offer,
+    order_id: Uuid,
+    req: &crate::offers::AcceptOfferRequest,
+) -> Result<serde_json::Value, StatusCode> {
+    let token = req.payment_reference.as_deref().ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let order_json = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let order: OrderResponse = serde_json::from_value(order_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if order.test != claims.test {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let date = offer.search_context["departure_date"].as_str()
+        .or_else(|| offer.search_context["departure_time"].as_str().and_then(|t| t.get(..10)))
+        .and_then(|d| d.parse::<NaiveDate>().ok())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !matches!(order.status.as_str(), "PAID" | "FULFILLED") || booked_flight(&order, date, Utc::now()).is_err() {
+        return Err(StatusCode::CONFLICT);
+    }
+    let mut items = offer.items.clone();
+    if let Err(errors) = crate::offers::assign_travelers(&mut items, &req.item_travelers, &crate::offers::traveler_indices(order.travelers.as_deref())) {
+        tracing::debug!("Rejected item travelers for ancillary offer {} on order {}: {}", offer.id, order_id, errors.join("; "));
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    // Only the first acceptance gets past here
+    let claimed = state.offer_repo.claim_offer(offer.id).await.map_err(|e| {
+        tracing::error!("Failed to claim ancillary offer {}: {:?}", offer.id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !claimed {
+        return Err(StatusCode::GONE);
+    }
+    if let Err(status) = crate::offers::reserve_inventory(state, &offer.items).await {
+        let _ = state.offer_repo.release_offer(offer.id).await;
+        return Err(status);
+    }
+    let give_back = || async {
+        crate::offers::release_offer_inventory(state, &offer.items).await;
+        let _ = state.offer_repo.release_offer(offer.id).await;
+    };
+
+    let charged = state.payments(order.test).charge_upsell(order_id, offer.id, Money::new(offer.total.minor_units(), Currency::NUC), token).await;
+    match charged {
+        Ok(PaymentStatus::Succeeded) => {}
+        Ok(status) => {
+            give_back().await;
+            let _ = state.order_repo.add_order_change(
+                order_id,
+                "PAYMENT_FAILED",
+                None,
+                Some(serde_json::json!({"status": status, "offer_id": offer.id})),
+                &claims.sub,
+                Some("Ancillary charge declined"),
+            ).await;
+            return Err(StatusCode::PAYMENT_REQUIRED);
+        }
+        Err(e) => {
+            // The intent id is stable, so accepting again can't charge twice
+            give_back().await;
+            tracing::error!("Ancillary charge on order {} failed: {:?}", order_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    let mut item_ids = Vec::with_capacity(items.len());
+    for item in &items {
+        let mut item_json = serde_json::to_value(item).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        item_json["status"] = serde_json::json!("ACTIVE");
+        item_json["metadata"]["ancillary_offer_id"] = serde_json::json!(offer.id);
+        let item_id = state.order_repo.add_order_item(order_id, &item_json).await.map_err(|e| {
+            tracing::error!("Charged ancillary offer {} on order {} but failed to add its items: {:?}", offer.id, order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        item_ids.push(item_id);
+    }
+    let total_nuc = offer.total.to_i32().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Err(e) = state.order_repo.adjust_order_total(order_id, total_nuc).await {
+        tracing::error!("Failed to add ancillary offer {} to the total of order {}: {:?}", offer.id, order_id, e);
+    }
+    let tax = offer.items.iter().try_fold(Money::zero(Currency::NUC), |sum, item| sum.checked_add(item.tax))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "ANCILLARY_ADDED",
+        Some(serde_json::json!({"total_nuc": order.total_nuc})),
+        Some(serde_json::json!({"total_nuc": order.total_nuc + total_nuc, "order_item_ids": item_ids, "offer_id": offer.id})),
+        &claims.sub,
+        Some("Ancillaries bought by booking reference"),
+    ).await;
+    crate::finance::post_journal(state, JournalTransaction::sale(order_id, offer.total, tax)).await;
+    crate::finance::post_journal(state, JournalTransaction::payment(order_id, offer.total, Some(token))).await;
+
+    // Sold units, supplier bookings and barcodes, as for items paid at checkout
+    let updated = state.order_repo.get_order(order_id).await.ok().flatten()
+        .and_then(|order| serde_json::from_value::<OrderResponse>(order).ok());
+    if let Some(updated) = &updated {
+        let added: Vec<OrderItemResponse> = updated.items.iter().filter(|i| item_ids.contains(&i.id)).cloned().collect();
+        crate::orders::commit_inventory(state, &added).await;
+        crate::suppliers::confirm_supplier_items(state, order_id, &added).await;
+        crate::orders::fulfill_added_items(state, updated, &item_ids).await;
+    }
+
+    Ok(serde_json::json!({
+        "order_id": order_id,
+        "status": updated.map_or(order.status, |o| o.status),
+        "order_item_ids": item_ids,
+        "message": "Ancillaries added to the booking.",
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_a_flight_on_the_date_still_ahead_takes_extras() {
+        let now = Utc::now();
+        let tomorrow = now + chrono::Duration::days(1);
+        let order: OrderResponse = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "offer_id": null,
+            "customer_id": "cust-1",
+            "customer_email": null,
+            "customer_did": null,
+            "status": "PAID",
+            "items": [{
+                "id": Uuid::new_v4(), "product_id": null, "product_type": "Flight", "name": "SQ318",
+                "price_nuc": 50000, "status": "ACTIVE", "revenue_status": "UNEARNED",
+                "operating_carrier_id": null, "net_rate_nuc": null, "commission_nuc": null,
+                "metadata": {"departure_time": tomorrow.to_rfc3339()},
+            }],
+            "travelers": null,
+            "contact_info": null,
+            "total_nuc": 50000,
+            "currency": "NUC",
+            "expires_at": null,
+            "group_size": null,
+            "names_due_at": null,
+            "created_at": now,
+        })).unwrap();
+
+        assert!(booked_flight(&order, tomorrow.date_naive(), now).is_ok());
+        assert_eq!(booked_flight(&order, now.date_naive() - chrono::Duration::days(3), now).unwrap_err(), StatusCode::UNPROCESSABLE_ENTITY);
+        // Once it has left, the same flight takes nothing more
+        assert!(booked_flight(&order, tomorrow.date_naive(), tomorrow + chrono::Duration::hours(1)).is_err());
+    }
+}
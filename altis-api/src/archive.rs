@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use altis_core::tenant::TenantContext;
+use altis_store::app_config::ArchivalConfig;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Where an archived order's document is kept in the blob store
+fn blob_key(order_id: Uuid) -> String {
+    format!("archive/orders/{}.json", order_id)
+}
+
+/// A booking reference as we issue them: six letters and digits, matched
+/// in upper case
+fn booking_reference(reference: &str) -> Option<String> {
+    let reference = reference.trim();
+    (reference.len() == 6 && reference.chars().all(|c| c.is_ascii_alphanumeric())).then(|| reference.to_ascii_uppercase())
+}
+
+// ============================================================================
+// Archival
+// ============================================================================
+
+/// Background loop moving FULFILLED and CANCELLED orders untouched for
+/// `archival.retention_days` to the blob store. The document is stored
+/// before any row is deleted, and an order that changes in between is left
+/// live for the next pass.
+pub async fn run_order_archival_worker(state: AppState, config: ArchivalConfig) {
+    if !config.enabled {
+        return;
+    }
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let before = chrono::Utc::now() - chrono::Duration::days(config.retention_days);
+        let due = match state.archive_repo.archivable_orders(before, config.batch_size).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to find orders due for archival: {:?}", e);
+                continue;
+            }
+        };
+        let mut archived = 0;
+        for order_id in due {
+            match archive_order(&state, order_id).await {
+                Ok(true) => archived += 1,
+                Ok(false) => tracing::debug!("Order {} changed while being archived; left for the next pass", order_id),
+                Err(e) => tracing::error!("Failed to archive order {}: {:?}", order_id, e),
+            }
+        }
+        if archived > 0 {
+            tracing::info!("Archived {} closed order(s) to cold storage", archived);
+        }
+    }
+}
+
+async fn archive_order(state: &AppState, order_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(document) = state.archive_repo.export_order(order_id).await? else { return Ok(false) };
+    let key = blob_key(order_id);
+    state.blob_store.put(&key, serde_json::to_vec(&document)?, "application/json").await?;
+    state.archive_repo.archive_order(&document, &key).await
+}
+
+// ============================================================================
+// Lookup and Restore
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ArchivedOrdersQuery {
+    pub reference: String,
+    pub last_name: Option<String>,
+}
+
+/// GET /v1/admin/archived-orders?reference=&last_name=
+/// Finds archived orders by booking reference, optionally narrowed to a passenger surname
+pub async fn find_archived_orders(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Query(query): Query<ArchivedOrdersQuery>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let Some(reference) = booking_reference(&query.reference) else {
+        tracing::debug!("Rejected archive lookup by reference {:?}", query.reference);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    };
+    let last_name = query.last_name.as_deref().map(str::trim).filter(|name| !name.is_empty());
+
+    let found = state.archive_repo.find_archived_orders(&tenant, &reference, last_name).await
+        .map_err(|e| {
+            tracing::error!("Failed to search archived orders: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(found))
+}
+
+/// GET /v1/admin/archived-orders/{id}
+/// The index entry of one archived order
+pub async fn get_archived_order(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let archived = state.archive_repo.get_archived_order(&tenant, order_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to load archived order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(archived))
+}
+
+/// POST /v1/admin/archived-orders/{id}/restore
+/// Brings an archived order back into the live tables and returns it
+pub async fn restore_archived_order(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let archived = state.archive_repo.get_archived_order(&tenant, order_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to load archived order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let key = archived["blob_key"].as_str().unwrap_or_default();
+
+    let bytes = state.blob_store.get(key).await
+        .map_err(|e| {
+            tracing::error!("Failed to read archive {} of order {}: {:?}", key, order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Archive {} of order {} is missing from the blob store", key, order_id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let document: serde_json::Value = serde_json::from_slice(&bytes).map_err(|e| {
+        tracing::error!("Archive {} of order {} is unreadable: {:?}", key, order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let restored = state.archive_repo.restore_order(&document).await
+        .map_err(|e| {
+            tracing::error!("Failed to restore order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !restored {
+        // Restored meanwhile by another request
+        return Err(StatusCode::CONFLICT);
+    }
+    tracing::info!("Restored archived order {} from {}", order_id, key);
+
+    let order = state.order_repo.get_order(order_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to load restored order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(order))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_booking_reference() {
+        assert_eq!(booking_reference(" 3f2a9c ").as_deref(), Some("3F2A9C"));
+        assert_eq!(booking_reference("3F2A9C"), Some("3F2A9C".to_string()));
+        assert_eq!(booking_reference("3F2A9"), None);
+        assert_eq!(booking_reference("3F2-9C"), None);
+        assert_eq!(booking_reference("3F2A9CD"), None);
+    }
+}
@@ -6,7 +6,6 @@ use axum::{
     Router,
 };
 use serde::Serialize;
-use jsonwebtoken::{encode, Header, EncodingKey};
 use chrono::{Utc, Duration};
 use uuid::Uuid;
 use crate::{state::AppState, error::AppError, middleware::auth::CustomerClaims};
@@ -28,10 +27,10 @@ async fn login_guest(State(state): State<AppState>) -> Result<Json<AuthResponse>
         email: None,
         role: "GUEST".to_owned(),
         exp: (Utc::now() + Duration::seconds(state.auth.expiration as i64)).timestamp() as usize,
+        test: false,
     };
 
-    let token = encode(&Header::default(), &my_claims, &EncodingKey::from_secret(state.auth.secret.as_bytes()))
-        .map_err(|e| AppError::InternalServerError(format!("Token encoding failed: {}", e)))?;
+    let token = state.auth.keys.encode(&my_claims)?;
 
     Ok(Json(AuthResponse { token }))
 }
@@ -50,10 +49,10 @@ async fn login_one_id(
         email: None,
         role: "CUSTOMER".to_owned(),
         exp: (Utc::now() + Duration::seconds(state.auth.expiration as i64)).timestamp() as usize,
+        test: false,
     };
 
-    let token = encode(&Header::default(), &my_claims, &EncodingKey::from_secret(state.auth.secret.as_bytes()))
-        .map_err(|e| AppError::InternalServerError(format!("Token encoding failed: {}", e)))?;
+    let token = state.auth.keys.encode(&my_claims)?;
 
     Ok(Json(AuthResponse { token }))
 }
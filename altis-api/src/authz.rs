@@ -0,0 +1,220 @@
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_core::tenant::TenantContext;
+
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+// ============================================================================
+// Principal Mapping
+// ============================================================================
+
+/// Maps the token subject to the `customer_id` stored on orders.
+/// One ID subjects (`did:...`) are stored under a short `DID-` alias with the
+/// full DID kept in `customer_did`.
+pub fn customer_id_for(claims: &CustomerClaims) -> (String, Option<String>) {
+    if claims.sub.starts_with("did:") {
+        (format!("DID-{}", &claims.sub.chars().take(12).collect::<String>()), Some(claims.sub.clone()))
+    } else {
+        (claims.sub.clone(), None)
+    }
+}
+
+/// Who the caller's saved cards and profile are kept under. Guests have no
+/// lasting identity to keep them under. One ID holders are keyed on the full
+/// DID, not its alias.
+pub fn registered_customer(claims: &CustomerClaims) -> Result<&str, StatusCode> {
+    if claims.role == "GUEST" {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(&claims.sub)
+}
+
+/// True when the order JSON belongs to the caller.
+/// DID holders must match on the full DID: the `DID-` alias is a prefix and
+/// can collide between identities.
+pub fn owns_order(claims: &CustomerClaims, order: &serde_json::Value) -> bool {
+    match customer_id_for(claims) {
+        (_, Some(did)) => order["customer_did"].as_str() == Some(did.as_str()),
+        (customer_id, None) => order["customer_id"].as_str() == Some(customer_id.as_str()),
+    }
+}
+
+// ============================================================================
+// Resource Entitlements
+// ============================================================================
+
+/// Loads an order and checks the caller owns it.
+/// Orders owned by someone else are reported as missing so ids can't be probed.
+pub async fn authorize_order(
+    state: &AppState,
+    claims: &CustomerClaims,
+    order_id: Uuid,
+) -> Result<serde_json::Value, StatusCode> {
+    let order_json = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if !owns_order(claims, &order_json) {
+        tracing::warn!("Customer {} denied access to order {}", claims.sub, order_id);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(order_json)
+}
+
+/// Checks the caller holds an order with an item on the given flight.
+pub async fn authorize_flight(
+    state: &AppState,
+    claims: &CustomerClaims,
+    flight_id: &str,
+) -> Result<(), StatusCode> {
+    let (customer_id, customer_did) = customer_id_for(claims);
+    let owner = customer_did.unwrap_or(customer_id);
+    let entitled = state.order_repo.customer_has_flight_order(&owner, flight_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !entitled {
+        tracing::warn!("Customer {} denied access to flight {}", claims.sub, flight_id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Tenant Entitlements (back office)
+// ============================================================================
+
+/// Checks an airline named in an admin route is one the caller acts for.
+pub fn authorize_airline(tenant: &TenantContext, airline_id: Uuid) -> Result<(), StatusCode> {
+    if !tenant.permits(Some(airline_id)) {
+        tracing::warn!("Tenant {:?} denied access to airline {}", tenant.airline_id(), airline_id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(())
+}
+
+/// Loads a product the caller's airline may manage. Other airlines'
+/// products are reported as missing, the same as absent ones.
+pub async fn authorize_product(
+    state: &AppState,
+    tenant: &TenantContext,
+    product_id: Uuid,
+) -> Result<serde_json::Value, StatusCode> {
+    state.catalog_repo.get_tenant_product(tenant, product_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Loads an order for back-office staff, confined to their airline's orders.
+pub async fn authorize_tenant_order(
+    state: &AppState,
+    tenant: &TenantContext,
+    order_id: Uuid,
+) -> Result<serde_json::Value, StatusCode> {
+    state.order_repo.get_tenant_order(tenant, order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+// ============================================================================
+// Fulfillment Grants (QR links)
+// ============================================================================
+
+/// Signed, expiring grant embedded in QR links so a barcode can be rendered
+/// without a session. Deliberately has no `sub`/`role`, so it can never pass
+/// as a session token.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FulfillmentGrant {
+    pub order_id: Uuid,
+    pub barcode: String,
+    pub exp: usize,
+}
+
+pub fn issue_fulfillment_grant(state: &AppState, order_id: Uuid, barcode: &str) -> Result<String, StatusCode> {
+    let grant = FulfillmentGrant {
+        order_id,
+        barcode: barcode.to_string(),
+        exp: (chrono::Utc::now() + chrono::Duration::seconds(state.auth.qr_grant_ttl as i64)).timestamp() as usize,
+    };
+
+    state.auth.keys.encode(&grant).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Validates a grant and checks it was issued for this barcode.
+pub async fn verify_fulfillment_grant(
+    state: &AppState,
+    barcode: &str,
+    grant: &str,
+) -> Result<FulfillmentGrant, StatusCode> {
+    let data = state.auth.keys.decode::<FulfillmentGrant>(grant).await
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    if data.claims.barcode != barcode {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(data.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::auth::AdminClaims;
+    use serde_json::json;
+
+    fn claims(sub: &str) -> CustomerClaims {
+        CustomerClaims {
+            sub: sub.to_string(),
+            email: None,
+            role: "CUSTOMER".to_string(),
+            exp: 0,
+            test: false,
+        }
+    }
+
+    #[test]
+    fn test_owns_order_by_customer_id() {
+        let order = json!({ "customer_id": "guest-123", "customer_did": null });
+        assert!(owns_order(&claims("guest-123"), &order));
+        assert!(!owns_order(&claims("guest-456"), &order));
+    }
+
+    #[test]
+    fn test_airline_staff_are_confined_to_their_airline() {
+        let ours = Uuid::new_v4();
+        let theirs = Uuid::new_v4();
+        let staff = |airline_id| AdminClaims {
+            sub: "ops@example.com".to_string(),
+            email: "ops@example.com".to_string(),
+            role: "ADMIN".to_string(),
+            airline_id,
+            permissions: vec![],
+            exp: 0,
+        };
+
+        let tenant = staff(Some(ours)).tenant();
+        assert_eq!(authorize_airline(&tenant, ours), Ok(()));
+        assert_eq!(authorize_airline(&tenant, theirs), Err(StatusCode::FORBIDDEN));
+
+        // Staff without an airline work across the platform
+        let platform = staff(None).tenant();
+        assert_eq!(platform, TenantContext::platform());
+        assert_eq!(authorize_airline(&platform, theirs), Ok(()));
+    }
+
+    #[test]
+    fn test_owns_order_by_did() {
+        let did = "did:altis:user-000000000001";
+        let (customer_id, _) = customer_id_for(&claims(did));
+        let order = json!({ "customer_id": customer_id, "customer_did": did });
+
+        assert!(owns_order(&claims(did), &order));
+        // Same 12-char alias, different identity
+        let order = json!({ "customer_id": customer_id, "customer_did": "did:altis:user-999" });
+        assert!(!owns_order(&claims(did), &order));
+    }
+}
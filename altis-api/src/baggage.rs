@@ -0,0 +1,198 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+    Json,
+};
+use altis_core::tenant::TenantContext;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_order::baggage::{bag_tag_number, BagEvent};
+
+use crate::authz::{authorize_order, authorize_tenant_order};
+use crate::middleware::auth::CustomerClaims;
+use crate::orders::OrderItemResponse;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CheckInBagsRequest {
+    /// IATA code of the airport where the bags are dropped
+    pub station: String,
+    /// Agent or bag drop unit, for the custody trail
+    pub scanned_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScanBagRequest {
+    pub event: BagEvent,
+    pub station: String,
+    /// The flight the bag was loaded onto or arrived on
+    pub flight_id: Option<Uuid>,
+    pub scanned_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BagScanResponse {
+    pub event_type: BagEvent,
+    pub station: Option<String>,
+    pub flight_id: Option<Uuid>,
+    pub scanned_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BagResponse {
+    pub tag_number: String,
+    pub order_item_id: Uuid,
+    /// The latest scan, and so where the bag was last seen
+    #[serde(skip_deserializing)]
+    pub last_event: Option<BagEvent>,
+    #[serde(skip_deserializing)]
+    pub last_station: Option<String>,
+    pub events: Vec<BagScanResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderBaggageResponse {
+    pub order_id: Uuid,
+    pub bags: Vec<BagResponse>,
+}
+
+fn bag_response(tag: serde_json::Value) -> Result<BagResponse, StatusCode> {
+    let mut bag: BagResponse = serde_json::from_value(tag).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(last) = bag.events.last() {
+        bag.last_event = Some(last.event_type);
+        bag.last_station = last.station.clone();
+    }
+    Ok(bag)
+}
+
+async fn order_baggage(state: &AppState, order_id: Uuid) -> Result<OrderBaggageResponse, StatusCode> {
+    let tags = state.baggage_repo.list_order_bag_tags(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let bags = tags.into_iter().map(bag_response).collect::<Result<_, _>>()?;
+    Ok(OrderBaggageResponse { order_id, bags })
+}
+
+// ============================================================================
+// Customer Handlers
+// ============================================================================
+
+/// GET /v1/orders/:id/baggage
+/// Where each of the order's checked bags was last scanned, with its journey so far
+pub async fn get_order_baggage(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<OrderBaggageResponse>, StatusCode> {
+    authorize_order(&state, &claims, order_id).await?;
+    Ok(Json(order_baggage(&state, order_id).await?))
+}
+
+// ============================================================================
+// Airport Handlers
+// ============================================================================
+
+/// POST /v1/admin/orders/:id/baggage/check-in
+/// Tag every paid bag on the order that isn't tagged yet, one tag per bag
+pub async fn check_in_bags(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+    Json(req): Json<CheckInBagsRequest>,
+) -> Result<Json<OrderBaggageResponse>, StatusCode> {
+    let order = authorize_tenant_order(&state, &tenant, order_id).await?;
+    if order["status"].as_str() != Some("PAID") {
+        return Err(StatusCode::CONFLICT);
+    }
+    let airline_id = order["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let accounting_code = state.catalog_repo.list_active_airlines().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .find(|airline| airline["id"].as_str() == Some(airline_id.to_string().as_str()))
+        .and_then(|airline| airline["accounting_code"].as_str().map(str::to_string))
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    let tagged = state.baggage_repo.list_order_bag_tags(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let items: Vec<OrderItemResponse> = serde_json::from_value(order["items"].clone()).unwrap_or_default();
+    let station = req.station.to_uppercase();
+
+    let mut issued = 0;
+    for item in items.iter().filter(|item| item.product_type == "BAG" && item.status == "ACTIVE") {
+        let already = tagged.iter().filter(|tag| tag["order_item_id"].as_str() == Some(item.id.to_string().as_str())).count() as i64;
+        let missing = item.quantity.unwrap_or(1) as i64 - already;
+        if missing <= 0 {
+            continue;
+        }
+        let serials = state.baggage_repo.next_bag_tag_serials(airline_id, missing).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        for serial in serials {
+            let tag_number = bag_tag_number(&accounting_code, serial).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+            let tag = serde_json::json!({
+                "tag_number": tag_number,
+                "order_id": order_id,
+                "order_item_id": item.id,
+                "airline_id": airline_id,
+                "station": station,
+                "scanned_by": req.scanned_by,
+            });
+            state.baggage_repo.create_bag_tag(&tag).await.map_err(|e| {
+                tracing::error!("Failed to tag bag {} on order {}: {:?}", tag_number, order_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            issued += 1;
+        }
+    }
+
+    if issued > 0 {
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            "BAGS_CHECKED_IN",
+            None,
+            Some(serde_json::json!({"bags": issued, "station": station})),
+            req.scanned_by.as_deref().unwrap_or("AIRPORT"),
+            Some("Bags tagged at check-in"),
+        ).await;
+    }
+    // Passengers still without a seat get one as they check in, before
+    // departure control is sent their entitlements
+    crate::seating::assign_order_seats(&state, &order).await;
+    crate::dcs::mark_checked_in(&state, &order).await;
+    Ok(Json(order_baggage(&state, order_id).await?))
+}
+
+/// POST /v1/admin/baggage/:tag/scan
+/// Record a custody scan; scans out of order (arrived before loaded) are refused
+pub async fn scan_bag(
+    State(state): State<AppState>,
+    Path(tag_number): Path<String>,
+    Json(req): Json<ScanBagRequest>,
+) -> Result<Json<BagResponse>, StatusCode> {
+    let tag = state.baggage_repo.get_bag_tag(&tag_number).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let bag_tag_id = tag["id"].as_str().and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let bag = bag_response(tag)?;
+    if let Some(last) = bag.last_event {
+        if !req.event.can_follow(last) {
+            return Err(StatusCode::CONFLICT);
+        }
+    }
+
+    let event = serde_json::json!({
+        "event_type": req.event.as_str(),
+        "station": req.station.to_uppercase(),
+        "flight_id": req.flight_id,
+        "scanned_by": req.scanned_by,
+    });
+    state.baggage_repo.record_bag_event(bag_tag_id, &event).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let updated = state.baggage_repo.get_bag_tag(&tag_number).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(bag_response(updated)?))
+}
@@ -0,0 +1,196 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BulkPriceFilters {
+    /// FLIGHT, BAG, SEAT, ...
+    pub product_type: Option<String>,
+    #[serde(default)]
+    pub product_codes: Vec<String>,
+    pub min_price_nuc: Option<i32>,
+    pub max_price_nuc: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PriceAdjustment {
+    /// e.g. 5.0 raises prices 5%, -10.0 cuts them 10%
+    Percent(f64),
+    /// NUC added to every price; negative to lower them
+    Fixed(i32),
+}
+
+impl PriceAdjustment {
+    /// The adjusted price, rounded to the nearest NUC; None if it would be negative
+    fn apply(&self, price_nuc: i32) -> Option<i32> {
+        let adjusted = match *self {
+            PriceAdjustment::Percent(percent) => (price_nuc as f64 * (1.0 + percent / 100.0)).round() as i64,
+            PriceAdjustment::Fixed(amount) => price_nuc as i64 + amount as i64,
+        };
+        i32::try_from(adjusted).ok().filter(|price| *price >= 0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkPriceUpdateRequest {
+    #[serde(default)]
+    pub filters: BulkPriceFilters,
+    pub adjustment: PriceAdjustment,
+    /// Only preview the new prices
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceChange {
+    pub product_id: Uuid,
+    pub product_code: String,
+    pub name: String,
+    pub old_price_nuc: i32,
+    pub new_price_nuc: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkPriceUpdateResponse {
+    pub dry_run: bool,
+    /// Rolls the update back through POST /v1/admin/bulk-price-updates/:token/rollback; None on a dry run
+    pub undo_token: Option<Uuid>,
+    pub products: Vec<PriceChange>,
+}
+
+/// New prices for the products the filters select. Deleted products and
+/// those the adjustment leaves unchanged are passed over; Err if any price
+/// would go negative.
+fn price_changes(products: &[serde_json::Value], req: &BulkPriceUpdateRequest) -> Result<Vec<PriceChange>, &'static str> {
+    let filters = &req.filters;
+    let mut changes = Vec::new();
+    for product in products {
+        let old_price_nuc = product["base_price_nuc"].as_i64().unwrap_or_default() as i32;
+        let code = product["product_code"].as_str().unwrap_or_default();
+        let selected = product["deleted_at"].is_null()
+            && filters.product_type.as_deref().is_none_or(|t| product["product_type"].as_str() == Some(t))
+            && (filters.product_codes.is_empty() || filters.product_codes.iter().any(|c| c == code))
+            && filters.min_price_nuc.is_none_or(|min| old_price_nuc >= min)
+            && filters.max_price_nuc.is_none_or(|max| old_price_nuc <= max);
+        if !selected {
+            continue;
+        }
+
+        let new_price_nuc = req.adjustment.apply(old_price_nuc).ok_or("the adjustment would make a price negative")?;
+        if new_price_nuc == old_price_nuc {
+            continue;
+        }
+        let Some(product_id) = product["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+        changes.push(PriceChange {
+            product_id,
+            product_code: code.to_string(),
+            name: product["name"].as_str().unwrap_or_default().to_string(),
+            old_price_nuc,
+            new_price_nuc,
+        });
+    }
+    Ok(changes)
+}
+
+/// POST /v1/admin/airlines/:airline_id/products/bulk-price-update
+/// Reprice every product matching the filters at once, or preview it with `dry_run`
+pub async fn bulk_price_update(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Json(req): Json<BulkPriceUpdateRequest>,
+) -> Result<Json<BulkPriceUpdateResponse>, StatusCode> {
+    if let PriceAdjustment::Percent(percent) = req.adjustment {
+        if !percent.is_finite() || percent <= -100.0 {
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    let products = state.catalog_repo.list_products(airline_id, req.filters.product_type.as_deref()).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let changes = price_changes(&products, &req).map_err(|reason| {
+        tracing::debug!("Rejected bulk price update for airline {}: {}", airline_id, reason);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+    if req.dry_run {
+        return Ok(Json(BulkPriceUpdateResponse { dry_run: true, undo_token: None, products: changes }));
+    }
+    if changes.is_empty() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let request = serde_json::to_value(&req).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let prices: Vec<(Uuid, i32, i32)> = changes.iter().map(|c| (c.product_id, c.old_price_nuc, c.new_price_nuc)).collect();
+    let undo_token = state.catalog_repo.apply_bulk_price_update(airline_id, &request, &prices).await
+        .map_err(|e| {
+            tracing::error!("Failed to apply bulk price update for airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        // A product was repriced or deleted while this ran
+        .ok_or(StatusCode::CONFLICT)?;
+    state.catalog_changed("bulk price update").await;
+
+    tracing::info!("Bulk price update {} repriced {} product(s) of airline {}", undo_token, changes.len(), airline_id);
+    Ok(Json(BulkPriceUpdateResponse { dry_run: false, undo_token: Some(undo_token), products: changes }))
+}
+
+/// POST /v1/admin/bulk-price-updates/:token/rollback
+/// Restore the prices a bulk update replaced, except where they've been changed again since
+pub async fn rollback_bulk_price_update(
+    State(state): State<AppState>,
+    Path(undo_token): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let outcome = state.catalog_repo.rollback_bulk_price_update(undo_token).await
+        .map_err(|e| {
+            tracing::error!("Failed to roll back bulk price update {}: {:?}", undo_token, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    state.catalog_changed("bulk price update rolled back").await;
+    Ok(Json(outcome))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_changes() {
+        let product = |code: &str, product_type: &str, price: i32| serde_json::json!({
+            "id": Uuid::new_v4(),
+            "product_code": code,
+            "product_type": product_type,
+            "name": code,
+            "base_price_nuc": price,
+            "deleted_at": null,
+        });
+        let mut deleted = product("BAG_OLD", "BAG", 3000);
+        deleted["deleted_at"] = serde_json::json!("2026-10-01T00:00:00Z");
+        let products = vec![product("BAG_20", "BAG", 3000), product("BAG_32", "BAG", 4500), product("SEAT_XL", "SEAT", 2000), deleted];
+
+        let request = |adjustment, product_type: Option<&str>| BulkPriceUpdateRequest {
+            filters: BulkPriceFilters { product_type: product_type.map(str::to_string), ..Default::default() },
+            adjustment,
+            dry_run: true,
+        };
+
+        let bags = price_changes(&products, &request(PriceAdjustment::Percent(5.0), Some("BAG"))).unwrap();
+        assert_eq!(bags.iter().map(|c| (c.product_code.as_str(), c.new_price_nuc)).collect::<Vec<_>>(), vec![("BAG_20", 3150), ("BAG_32", 4725)]);
+
+        let cheap = BulkPriceUpdateRequest {
+            filters: BulkPriceFilters { max_price_nuc: Some(3000), ..Default::default() },
+            adjustment: PriceAdjustment::Fixed(-500),
+            dry_run: true,
+        };
+        assert_eq!(price_changes(&products, &cheap).unwrap().len(), 2, "BAG_20 and SEAT_XL");
+
+        assert!(price_changes(&products, &request(PriceAdjustment::Fixed(-2500), None)).is_err(), "SEAT_XL would go negative");
+        assert!(price_changes(&products, &request(PriceAdjustment::Fixed(0), None)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::{Currency, Money};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Orders claimed by a worker that died are picked up again after this long.
+const CLAIM_LEASE_SECONDS: i64 = 600;
+
+#[derive(Debug, Deserialize)]
+pub struct BulkRefundRequest {
+    /// Catalog flight being removed; matched against order item `flight_id`
+    pub flight_id: String,
+    pub departure_from: chrono::DateTime<chrono::Utc>,
+    /// Exclusive upper bound on item departure time
+    pub departure_to: chrono::DateTime<chrono::Utc>,
+    /// Shown to customers and recorded on every order
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkRefundFailuresResponse {
+    pub job_id: Uuid,
+    pub failures: Vec<serde_json::Value>,
+}
+
+/// Outcome of one order within a job
+enum ItemOutcome {
+    Refunded(i64),
+    /// Unpaid orders are cancelled with nothing to refund
+    Cancelled,
+    /// Already cancelled or refunded by other means
+    Skipped,
+}
+
+/// POST /v1/admin/bulk-refunds
+/// Cancel and refund every live order on a flight within a departure range
+pub async fn create_bulk_refund(
+    State(state): State<AppState>,
+    Json(req): Json<BulkRefundRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    if req.flight_id.trim().is_empty() || req.reason.trim().is_empty() || req.departure_from >= req.departure_to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let job = state.bulk_refund_repo.create_job(
+        &req.flight_id,
+        req.departure_from,
+        req.departure_to,
+        &req.reason,
+        "ADMIN",
+    ).await.map_err(|e| {
+        tracing::error!("Failed to create bulk refund job for flight {}: {:?}", req.flight_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let job_id = job["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    tracing::info!("Bulk refund job {} queued {} order(s) on flight {}", job_id, job["progress"]["total"], req.flight_id);
+    tokio::spawn(run_job(state.clone(), job_id));
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// GET /v1/admin/bulk-refunds/:id
+/// Job progress: per-status order counts and amount refunded so far
+pub async fn get_bulk_refund(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let job = state.bulk_refund_repo.get_job(job_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(job))
+}
+
+/// GET /v1/admin/bulk-refunds/:id/failures
+pub async fn list_bulk_refund_failures(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<BulkRefundFailuresResponse>, StatusCode> {
+    let failures = state.bulk_refund_repo.list_failures(job_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(BulkRefundFailuresResponse { job_id, failures }))
+}
+
+/// POST /v1/admin/bulk-refunds/:id/retry
+/// Re-queue the job's failed orders
+pub async fn retry_bulk_refund(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let requeued = state.bulk_refund_repo.retry_failures(job_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if requeued > 0 {
+        tracing::info!("Bulk refund job {}: retrying {} failed order(s)", job_id, requeued);
+        tokio::spawn(run_job(state.clone(), job_id));
+    }
+
+    get_bulk_refund(State(state), Path(job_id)).await
+}
+
+/// Picks up jobs interrupted by a restart.
+pub async fn resume_bulk_refunds(state: AppState) {
+    match state.bulk_refund_repo.list_running_jobs().await {
+        Ok(jobs) => {
+            for job_id in jobs {
+                tracing::info!("Resuming bulk refund job {}", job_id);
+                tokio::spawn(run_job(state.clone(), job_id));
+            }
+        }
+        Err(e) => tracing::error!("Failed to list running bulk refund jobs: {:?}", e),
+    }
+}
+
+/// Works through the job in batches, pausing between them so thousands of
+/// refunds don't trip the PSP's rate limits.
+async fn run_job(state: AppState, job_id: Uuid) {
+    let Ok(Some(job)) = state.bulk_refund_repo.get_job(job_id).await else {
+        tracing::error!("Bulk refund job {} not found", job_id);
+        return;
+    };
+    let reason = job["reason"].as_str().unwrap_or_default().to_string();
+    let actor = job["created_by"].as_str().unwrap_or("ADMIN").to_string();
+    let config = &state.refunds;
+
+    loop {
+        let items = match state.bulk_refund_repo.claim_items(job_id, config.bulk_batch_size.max(1), CLAIM_LEASE_SECONDS).await {
+            Ok(items) => items,
+            Err(e) => {
+                tracing::error!("Bulk refund job {} failed to claim orders: {:?}", job_id, e);
+                return;
+            }
+        };
+        if items.is_empty() {
+            break;
+        }
+
+        for item in items {
+            let Some(order_id) = item["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+            let attempts = item["attempts"].as_i64().unwrap_or(1) as i32;
+
+            let result = match process_order(&state, job_id, order_id, &reason, &actor).await {
+                Ok(ItemOutcome::Refunded(amount_nuc)) => state.bulk_refund_repo.complete_item(job_id, order_id, "REFUNDED", amount_nuc, None).await,
+                Ok(ItemOutcome::Cancelled) => state.bulk_refund_repo.complete_item(job_id, order_id, "CANCELLED", 0, None).await,
+                Ok(ItemOutcome::Skipped) => state.bulk_refund_repo.complete_item(job_id, order_id, "SKIPPED", 0, None).await,
+                Err(e) => {
+                    tracing::warn!("Bulk refund job {}: order {} failed (attempt {}): {}", job_id, order_id, attempts, e);
+                    let status = if attempts >= config.bulk_max_attempts { "FAILED" } else { "PENDING" };
+                    state.bulk_refund_repo.complete_item(job_id, order_id, status, 0, Some(&e)).await
+                }
+            };
+            if let Err(e) = result {
+                tracing::error!("Bulk refund job {}: failed to record order {}: {:?}", job_id, order_id, e);
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(config.bulk_batch_interval_ms)).await;
+    }
+
+    match state.bulk_refund_repo.finish_job(job_id).await {
+        Ok(Some(status)) => tracing::info!("Bulk refund job {} finished: {}", job_id, status),
+        Ok(None) => {}
+        Err(e) => tracing::error!("Bulk refund job {} failed to close: {:?}", job_id, e),
+    }
+}
+
+/// Refunds (when paid) and cancels one order, then tells the customer.
+async fn process_order(state: &AppState, job_id: Uuid, order_id: Uuid, reason: &str, actor: &str) -> Result<ItemOutcome, String> {
+    let order = state.order_repo.get_order(order_id).await
+        .map_err(|e| e.to_string())?
+        .ok_or("Order not found")?;
+
+    let status = order["status"].as_str().unwrap_or_default();
+    if matches!(status, "CANCELLED" | "REFUNDED" | "EXPIRED") {
+        return Ok(ItemOutcome::Skipped);
+    }
+
+    let paid = status == "PAID";
+    let total_nuc = order["total_nuc"].as_i64().unwrap_or(0);
+    let currency = Currency::new(order["currency"].as_str().unwrap_or("NUC")).map_err(|e| e.to_string())?;
+
+    if paid && total_nuc > 0 {
+        let key = format!("bulk-refund-{}-{}", job_id.simple(), order_id.simple());
+        let refund_status = state.payments(order["test"].as_bool().unwrap_or(false)).refund_payment(order_id, Money::new(total_nuc, currency), &key).await
+            .map_err(|e| format!("Refund failed: {}", e))?;
+        if refund_status != altis_core::payment::PaymentStatus::Succeeded {
+            return Err(format!("Refund not completed: {:?}", refund_status));
+        }
+    }
+
+    state.order_repo.update_order_status(order_id, "CANCELLED").await
+        .map_err(|e| format!("Refunded but failed to cancel: {}", e))?;
+    let items: Vec<crate::orders::OrderItemResponse> = serde_json::from_value(order["items"].clone()).unwrap_or_default();
+    crate::orders::return_inventory(state, &items, paid).await;
+    crate::suppliers::cancel_supplier_items(state, &items).await;
+
+    let refunded_nuc = if paid { total_nuc } else { 0 };
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "BULK_CANCELLED",
+        Some(serde_json::json!({ "status": status })),
+        Some(serde_json::json!({ "status": "CANCELLED", "job_id": job_id, "refunded_nuc": refunded_nuc })),
+        actor,
+        Some(reason),
+    ).await;
+
+    if refunded_nuc > 0 {
+        crate::documents::issue_for_order(state, order_id, "CREDIT_NOTE", refunded_nuc).await;
+        crate::finance::post_journal(state, JournalTransaction::refund(order_id, None, Money::nuc(refunded_nuc), crate::finance::order_tax(&order), reason)).await;
+    }
+
+    let event = serde_json::json!({
+        "event_type": "ORDER_CANCELLED",
+        "order_id": order_id,
+        "customer_id": order["customer_id"],
+        "customer_email": order["customer_email"],
+        "reason": reason,
+        "refunded_nuc": refunded_nuc,
+        "currency": currency,
+        "timestamp": chrono::Utc::now().timestamp(),
+    });
+    if let Err(e) = crate::notifier::notify(state, &order_id.to_string(), &event).await {
+        // The refund stands; re-queuing the order would refund nothing new
+        tracing::warn!("Cancelled order {} but failed to notify the customer: {}", order_id, e);
+    }
+
+    Ok(if paid { ItemOutcome::Refunded(refunded_nuc) } else { ItemOutcome::Cancelled })
+}
@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::price_watch::is_airport_code;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CampaignRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Percentage off, e.g. 15.0
+    pub discount_percentage: f64,
+    /// FLIGHT, BAG, SEAT, ...; empty covers everything
+    #[serde(default)]
+    pub product_types: Vec<String>,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Promo banner for the storefront (headline, image URL, ...)
+    pub banner: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CampaignResponse {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub discount_percentage: f64,
+    pub product_types: Vec<String>,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub banner: serde_json::Value,
+    /// SCHEDULED, ACTIVE, ENDED or CANCELLED
+    pub status: String,
+}
+
+fn validate_campaign(req: &CampaignRequest, now: DateTime<Utc>) -> Result<(), &'static str> {
+    if req.name.trim().is_empty() {
+        return Err("name is required");
+    }
+    if !(req.discount_percentage > 0.0 && req.discount_percentage <= 100.0) {
+        return Err("discount_percentage must be above 0 and at most 100");
+    }
+    if req.ends_at <= req.starts_at || req.ends_at <= now {
+        return Err("ends_at must be after starts_at and in the future");
+    }
+    let known_type = |t: &String| serde_json::from_value::<altis_catalog::ProductType>(serde_json::json!(t)).is_ok();
+    if !req.product_types.iter().all(known_type) {
+        return Err("unknown product type");
+    }
+    if ![&req.origin, &req.destination].into_iter().flatten().all(|airport| is_airport_code(airport)) {
+        return Err("origin and destination must be airport codes");
+    }
+    Ok(())
+}
+
+fn campaign_json(req: &CampaignRequest) -> serde_json::Value {
+    serde_json::json!({
+        "name": req.name,
+        "description": req.description,
+        "discount_percentage": req.discount_percentage,
+        "product_types": req.product_types,
+        "origin": req.origin.as_deref().map(str::to_uppercase),
+        "destination": req.destination.as_deref().map(str::to_uppercase),
+        "starts_at": req.starts_at.to_rfc3339(),
+        "ends_at": req.ends_at.to_rfc3339(),
+        "banner": req.banner,
+    })
+}
+
+fn to_response(campaign: serde_json::Value) -> Result<CampaignResponse, StatusCode> {
+    serde_json::from_value(campaign).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /v1/admin/airlines/:airline_id/campaigns
+/// Schedule a sale; it starts and ends on its own dates
+pub async fn create_campaign(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Json(req): Json<CampaignRequest>,
+) -> Result<(StatusCode, Json<CampaignResponse>), StatusCode> {
+    if let Err(reason) = validate_campaign(&req, Utc::now()) {
+        tracing::debug!("Rejected campaign for airline {}: {}", airline_id, reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    crate::markets::check_scope(&state, airline_id, req.origin.as_deref(), req.destination.as_deref()).await?;
+
+    let created = state.catalog_repo.create_campaign(airline_id, &campaign_json(&req)).await
+        .map_err(|e| {
+            tracing::error!("Failed to create campaign for airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    state.catalog_changed("campaign created").await;
+
+    Ok((StatusCode::CREATED, Json(to_response(created)?)))
+}
+
+/// GET /v1/admin/airlines/:airline_id/campaigns
+pub async fn list_campaigns(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<Json<Vec<CampaignResponse>>, StatusCode> {
+    let campaigns = state.catalog_repo.list_campaigns(airline_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(campaigns.into_iter().map(to_response).collect::<Result<_, _>>()?))
+}
+
+/// GET /v1/admin/campaigns/:id
+pub async fn get_campaign(
+    State(state): State<AppState>,
+    Path(campaign_id): Path<Uuid>,
+) -> Result<Json<CampaignResponse>, StatusCode> {
+    let campaign = state.catalog_repo.get_campaign(campaign_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(to_response(campaign)?))
+}
+
+/// PUT /v1/admin/campaigns/:id
+/// Change a scheduled or running campaign; ended and cancelled ones are kept as they were
+pub async fn update_campaign(
+    State(state): State<AppState>,
+    Path(campaign_id): Path<Uuid>,
+    Json(req): Json<CampaignRequest>,
+) -> Result<Json<CampaignResponse>, StatusCode> {
+    if let Err(reason) = validate_campaign(&req, Utc::now()) {
+        tracing::debug!("Rejected update of campaign {}: {}", campaign_id, reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let campaign = state.catalog_repo.get_campaign(campaign_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let airline_id = campaign["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    crate::markets::check_scope(&state, airline_id, req.origin.as_deref(), req.destination.as_deref()).await?;
+
+    let updated = state.catalog_repo.update_campaign(campaign_id, &campaign_json(&req)).await
+        .map_err(|e| {
+            tracing::error!("Failed to update campaign {}: {:?}", campaign_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    state.catalog_changed("campaign updated").await;
+
+    Ok(Json(to_response(updated)?))
+}
+
+/// DELETE /v1/admin/campaigns/:id
+/// Cancel a campaign; prices go back to normal at once
+pub async fn cancel_campaign(
+    State(state): State<AppState>,
+    Path(campaign_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let cancelled = state.catalog_repo.cancel_campaign(campaign_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !cancelled {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.catalog_changed("campaign cancelled").await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Background loop starting and ending campaigns on their dates
+pub async fn run_campaign_scheduler(state: AppState) {
+    let mut interval = tokio::time::interval(Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+        match state.catalog_repo.sync_campaign_statuses().await {
+            Ok(0) => {}
+            Ok(changed) => {
+                tracing::info!("{} campaign(s) started or ended", changed);
+                state.catalog_changed("campaigns started or ended").await;
+            }
+            Err(e) => tracing::error!("Failed to update campaign statuses: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_campaign() {
+        let now = Utc::now();
+        let request = |discount_percentage, product_types: &[&str], origin: Option<&str>| CampaignRequest {
+            name: "Year-end sale".to_string(),
+            description: None,
+            discount_percentage,
+            product_types: product_types.iter().map(|t| t.to_string()).collect(),
+            origin: origin.map(str::to_string),
+            destination: None,
+            starts_at: now + chrono::Duration::days(1),
+            ends_at: now + chrono::Duration::days(8),
+            banner: None,
+        };
+
+        assert!(validate_campaign(&request(15.0, &["BAG", "SEAT"], Some("SIN")), now).is_ok());
+        assert!(validate_campaign(&request(0.0, &[], None), now).is_err());
+        assert!(validate_campaign(&request(15.0, &["LUGGAGE"], None), now).is_err());
+        assert!(validate_campaign(&request(15.0, &[], Some("Singapore")), now).is_err());
+
+        let mut ended = request(15.0, &[], None);
+        ended.ends_at = now - chrono::Duration::hours(1);
+        assert!(validate_campaign(&ended, now).is_err());
+    }
+}
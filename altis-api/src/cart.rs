@@ -0,0 +1,434 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_offer::models::{Offer, OfferItem};
+use altis_order::cart::{BundleDiscounts, CartLine, CartTotals};
+use altis_shared::money::{self, Currency, Money, MoneyError};
+
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct AddCartOfferRequest {
+    pub offer_id: Uuid,
+    /// Items of the offer to add; all of them when omitted
+    pub item_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckoutCartRequest {
+    pub customer_email: String,
+    pub travelers: Option<Vec<altis_core::iata::Traveler>>,
+    pub contact_info: Option<altis_core::iata::ContactInfo>,
+    /// Which traveler each ancillary is for, as offer item id to traveler index
+    #[serde(default)]
+    pub item_travelers: std::collections::HashMap<Uuid, i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CartItemResponse {
+    pub id: Uuid,
+    pub product_type: String,
+    pub name: String,
+    #[serde(rename = "price_nuc", serialize_with = "money::nuc::serialize")]
+    pub price: Money,
+    #[serde(rename = "discount_nuc", serialize_with = "money::nuc::serialize")]
+    pub discount: Money,
+    #[serde(rename = "tax_nuc", serialize_with = "money::nuc::serialize")]
+    pub tax: Money,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CartOfferResponse {
+    pub offer_id: Uuid,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// Expired offers must be removed before checkout
+    pub expired: bool,
+    pub items: Vec<CartItemResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CartResponse {
+    pub id: Uuid,
+    /// OPEN or CHECKED_OUT
+    pub status: String,
+    pub order_id: Option<Uuid>,
+    pub offers: Vec<CartOfferResponse>,
+    #[serde(rename = "subtotal_nuc", serialize_with = "money::nuc::serialize")]
+    pub subtotal: Money,
+    #[serde(rename = "discount_nuc", serialize_with = "money::nuc::serialize")]
+    pub discount: Money,
+    #[serde(rename = "tax_nuc", serialize_with = "money::nuc::serialize")]
+    pub tax: Money,
+    #[serde(rename = "total_nuc", serialize_with = "money::nuc::serialize")]
+    pub total: Money,
+    pub currency: String,
+}
+
+/// A cart with its offers loaded
+struct LoadedCart {
+    id: Uuid,
+    status: String,
+    order_id: Option<Uuid>,
+    /// Offers in the order first added, each with its items in the cart
+    offers: Vec<(Offer, Vec<OfferItem>)>,
+}
+
+impl LoadedCart {
+    fn lines(&self) -> Result<Vec<CartLine>, MoneyError> {
+        self.offers.iter()
+            .flat_map(|(offer, items)| items.iter().map(|item| Ok(CartLine {
+                offer_id: offer.id,
+                item_id: item.id,
+                product_type: item.product_type.clone(),
+                price: item.line_price()?,
+                tax: item.tax,
+            })))
+            .collect()
+    }
+}
+
+fn bundle_discounts(state: &AppState) -> BundleDiscounts {
+    BundleDiscounts {
+        multi_offer: state.cart.multi_offer_discount,
+        ancillary_with_flight: state.cart.ancillary_bundle_discount,
+    }
+}
+
+/// Loads the caller's cart; someone else's cart is reported as missing
+async fn load_cart(state: &AppState, claims: &CustomerClaims, cart_id: Uuid) -> Result<LoadedCart, StatusCode> {
+    let (customer_id, _) = crate::authz::customer_id_for(claims);
+    let cart = state.cart_repo.get_cart(cart_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to load cart {}: {:?}", cart_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .filter(|c| c["customer_id"].as_str() == Some(customer_id.as_str()))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut offers: Vec<(Offer, Vec<OfferItem>)> = Vec::new();
+    for entry in cart["items"].as_array().into_iter().flatten() {
+        let (Some(offer_id), Some(item_id)) = (
+            entry["offer_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+            entry["offer_item_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+        ) else {
+            continue;
+        };
+
+        if !offers.iter().any(|(offer, _)| offer.id == offer_id) {
+            let Some(offer_json) = state.offer_repo.get_offer(offer_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else {
+                // Purged offers simply drop out of the cart
+                continue;
+            };
+            let offer: Offer = serde_json::from_value(offer_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            offers.push((offer, Vec::new()));
+        }
+        if let Some((offer, items)) = offers.iter_mut().find(|(offer, _)| offer.id == offer_id) {
+            items.extend(offer.items.iter().find(|item| item.id == item_id).cloned());
+        }
+    }
+
+    Ok(LoadedCart {
+        id: cart_id,
+        status: cart["status"].as_str().unwrap_or("OPEN").to_string(),
+        order_id: cart["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+        offers,
+    })
+}
+
+fn to_response(cart: &LoadedCart, totals: &CartTotals) -> CartResponse {
+    let discount_of = |item_id: Uuid| totals.lines.iter().find(|l| l.item_id == item_id).map_or(Money::zero(Currency::NUC), |l| l.discount);
+    CartResponse {
+        id: cart.id,
+        status: cart.status.clone(),
+        order_id: cart.order_id,
+        offers: cart.offers.iter().map(|(offer, items)| CartOfferResponse {
+            offer_id: offer.id,
+            expires_at: offer.expires_at,
+            expired: offer.is_expired(),
+            items: items.iter().map(|item| CartItemResponse {
+                id: item.id,
+                product_type: item.product_type.clone(),
+                name: item.name.clone(),
+                price: item.price,
+                discount: discount_of(item.id),
+                tax: item.tax,
+            }).collect(),
+        }).collect(),
+        subtotal: totals.subtotal,
+        discount: totals.discount,
+        tax: totals.tax,
+        total: totals.total,
+        currency: cart.offers.first().map_or_else(|| "NUC".to_string(), |(offer, _)| offer.currency.clone()),
+    }
+}
+
+async fn priced_response(state: &AppState, claims: &CustomerClaims, cart_id: Uuid) -> Result<Json<CartResponse>, StatusCode> {
+    let cart = load_cart(state, claims, cart_id).await?;
+    let totals = altis_order::cart::price_cart(&cart.lines().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?, &bundle_discounts(state))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(to_response(&cart, &totals)))
+}
+
+/// POST /v1/carts
+/// Start an empty trip builder cart
+pub async fn create_cart(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+) -> Result<(StatusCode, Json<CartResponse>), StatusCode> {
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let cart = state.cart_repo.create_cart(&customer_id).await.map_err(|e| {
+        tracing::error!("Failed to create cart: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let cart_id = cart["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, priced_response(&state, &claims, cart_id).await?))
+}
+
+/// GET /v1/carts/{id}
+/// The cart's items with combined totals and bundle discounts
+pub async fn get_cart(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path(cart_id): Path<Uuid>,
+) -> Result<Json<CartResponse>, StatusCode> {
+    priced_response(&state, &claims, cart_id).await
+}
+
+/// POST /v1/carts/{id}/offers
+/// Add an offer, or some of its items, from any search
+pub async fn add_cart_offer(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path(cart_id): Path<Uuid>,
+    Json(req): Json<AddCartOfferRequest>,
+) -> Result<Json<CartResponse>, StatusCode> {
+    let cart = load_cart(&state, &claims, cart_id).await?;
+    if cart.status != "OPEN" {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let offer_json = state.offer_repo.get_offer(req.offer_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let offer: Offer = serde_json::from_value(offer_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if offer.is_expired() {
+        return Err(StatusCode::GONE);
+    }
+    // Interline offers are booked with the partner as a whole, on accept
+    if offer.metadata["partner"].is_string() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    if crate::sandbox::is_sandbox_offer(&state, &offer) != claims.test {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let item_ids: Vec<Uuid> = match req.item_ids {
+        Some(ids) if ids.is_empty() || ids.iter().any(|id| !offer.items.iter().any(|item| item.id == *id)) => {
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        Some(ids) => ids,
+        None => offer.items.iter().map(|item| item.id).collect(),
+    };
+    let new_offer = !cart.offers.iter().any(|(o, _)| o.id == offer.id);
+    if new_offer && cart.offers.len() >= state.cart.max_offers {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    if cart.offers.first().is_some_and(|(first, _)| first.currency != offer.currency) {
+        // One order, one payment: everything must be priced in the same currency
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    if !state.cart_repo.add_cart_items(cart_id, offer.id, &item_ids).await.map_err(|e| {
+        tracing::error!("Failed to add offer {} to cart {}: {:?}", offer.id, cart_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })? {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    priced_response(&state, &claims, cart_id).await
+}
+
+/// DELETE /v1/carts/{id}/offers/{offer_id}
+/// Take an offer's items out of the cart
+pub async fn remove_cart_offer(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path((cart_id, offer_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<CartResponse>, StatusCode> {
+    load_cart(&state, &claims, cart_id).await?;
+    if !state.cart_repo.remove_cart_offer(cart_id, offer_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    priced_response(&state, &claims, cart_id).await
+}
+
+/// POST /v1/carts/{id}/checkout
+/// Turn the whole cart into one PROPOSED order, paid through /v1/orders/{id}/pay
+pub async fn checkout_cart(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path(cart_id): Path<Uuid>,
+    Json(mut req): Json<CheckoutCartRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    if req.contact_info.as_mut().is_some_and(|contact| !contact.normalize_phone()) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let cart = load_cart(&state, &claims, cart_id).await?;
+    if cart.status != "OPEN" {
+        return Err(StatusCode::CONFLICT);
+    }
+    if cart.offers.iter().all(|(_, items)| items.is_empty()) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    if cart.offers.iter().any(|(offer, _)| offer.is_expired()) {
+        return Err(StatusCode::GONE);
+    }
+    if let Some(travelers) = req.travelers.as_mut() {
+        for (offer, items) in &cart.offers {
+            if let Err(errors) = crate::offers::check_travelers(items, travelers) {
+                tracing::debug!("Rejected travelers for offer {} in cart {}: {}", offer.id, cart_id, errors.join("; "));
+                return Err(StatusCode::UNPROCESSABLE_ENTITY);
+            }
+        }
+    }
+    let mut items: Vec<OfferItem> = cart.offers.iter().flat_map(|(_, items)| items.iter().cloned()).collect();
+    if let Err(errors) = crate::offers::assign_travelers(&mut items, &req.item_travelers, &crate::offers::traveler_indices(req.travelers.as_deref())) {
+        tracing::debug!("Rejected item travelers for cart {}: {}", cart_id, errors.join("; "));
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let totals = altis_order::cart::price_cart(&cart.lines().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?, &bundle_discounts(&state))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Claim the cart first so a double-submitted checkout can't create two orders
+    let order_id = Uuid::new_v4();
+    if !state.cart_repo.checkout_cart(cart_id, order_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    match create_cart_order(&state, &claims, &req, &cart, &items, &totals, order_id).await {
+        Ok(()) => {}
+        Err(status) => {
+            if let Err(e) = state.cart_repo.reopen_cart(cart_id).await {
+                tracing::error!("Checkout of cart {} failed and the cart could not be reopened: {:?}", cart_id, e);
+            }
+            return Err(status);
+        }
+    }
+
+    for (offer, _) in &cart.offers {
+        let _ = state.telemetry.log_offer_accepted(altis_shared::models::events::OfferAcceptedEvent {
+            offer_id: offer.id,
+            customer_id: Some(req.customer_email.clone()),
+            timestamp: chrono::Utc::now().timestamp(),
+        }).await;
+        let _ = state.telemetry.log_training(&altis_offer::training::TrainingRecord::label(
+            altis_offer::training::TrainingLabel::Accepted,
+            offer,
+        )).await;
+    }
+
+    Ok(Json(serde_json::json!({
+        "order_id": order_id,
+        "cart_id": cart_id,
+        "status": "PROPOSED",
+        "total_nuc": totals.total.minor_units(),
+        "discount_nuc": totals.discount.minor_units(),
+        "message": "Order created successfully. Proceed to payment.",
+        "customer_email": req.customer_email,
+    })))
+}
+
+async fn create_cart_order(
+    state: &AppState,
+    claims: &CustomerClaims,
+    req: &CheckoutCartRequest,
+    cart: &LoadedCart,
+    items: &[OfferItem],
+    totals: &CartTotals,
+    order_id: Uuid,
+) -> Result<(), StatusCode> {
+    let (customer_id, customer_did) = crate::authz::customer_id_for(claims);
+
+    // Each airline's inventory rules apply to its own offers; the order is
+    // held as long as the strictest of them allows
+    let mut hold_seconds: Option<u64> = None;
+    for (offer, offer_items) in &cart.offers {
+        if let Some(airline_id) = offer.airline_id {
+            let airline_hold = crate::offers::hold_policy(state, airline_id, &customer_id, offer_items).await?;
+            hold_seconds = Some(hold_seconds.map_or(airline_hold, |h| h.min(airline_hold)));
+        }
+    }
+    let hold_seconds = hold_seconds.unwrap_or(state.business_rules.trip_hold_seconds);
+    let expires_at = (chrono::Utc::now() + chrono::Duration::seconds(hold_seconds as i64)).to_rfc3339();
+
+    crate::offers::reserve_inventory(state, items).await?;
+
+    let (first_offer, _) = cart.offers.first().ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let order = state.order_repo.create_order(&serde_json::json!({
+        "id": order_id,
+        "customer_id": customer_id,
+        "customer_email": req.customer_email,
+        "customer_did": customer_did,
+        "offer_id": first_offer.id,
+        "airline_id": first_offer.airline_id,
+        "status": "PROPOSED",
+        "total_nuc": totals.total.minor_units(),
+        "currency": first_offer.currency,
+        "contact_phone": req.contact_info.as_ref().and_then(|c| c.phone.clone()),
+        "contact_first_name": req.contact_info.as_ref().and_then(|c| c.first_name.clone()),
+        "contact_last_name": req.contact_info.as_ref().and_then(|c| c.last_name.clone()),
+        "travelers": req.travelers,
+        "expires_at": expires_at,
+        "test": claims.test,
+    })).await.map_err(|e| {
+        tracing::error!("Failed to create order for cart {}: {:?}", cart.id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    });
+    if let Err(status) = order {
+        crate::offers::release_offer_inventory(state, items).await;
+        return Err(status);
+    }
+
+    // Items carry their discounted price, so refunds and settlement see what was paid
+    for (item, line) in items.iter().zip(&totals.lines) {
+        let mut item_json = serde_json::to_value(item).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let paid = line.price.checked_sub(line.discount).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        item_json["price_nuc"] = paid.minor_units().into();
+        if !item_json["metadata"].is_object() {
+            item_json["metadata"] = serde_json::json!({});
+        }
+        item_json["metadata"]["cart_id"] = cart.id.to_string().into();
+        item_json["metadata"]["offer_id"] = line.offer_id.to_string().into();
+        item_json["metadata"]["bundle_discount_nuc"] = line.discount.minor_units().into();
+        let _ = state.order_repo.add_order_item(order_id, &item_json).await;
+    }
+    let offers: Vec<serde_json::Value> = cart.offers.iter()
+        .map(|(offer, _)| serde_json::to_value(offer))
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    crate::snapshots::record_order_snapshot(state, order_id, &offers).await;
+
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "CART_CHECKOUT",
+        None,
+        Some(serde_json::json!({
+            "cart_id": cart.id,
+            "offer_ids": cart.offers.iter().map(|(offer, _)| offer.id).collect::<Vec<_>>(),
+            "discount_nuc": totals.discount.minor_units(),
+        })),
+        "CUSTOMER",
+        None,
+    ).await;
+
+    Ok(())
+}
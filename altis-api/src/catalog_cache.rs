@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use altis_catalog::{Campaign, LocalizedContent, ProductContent, Route, RouteNetwork, TaxCode, TaxEngine};
+use altis_core::catalog::{CatalogChangedEvent, CATALOG_CHANGED_TOPIC};
+use altis_core::events::registry;
+use altis_core::repository::ProductRepository;
+use altis_store::app_config::{BusinessRules, CatalogEventsConfig, KafkaConfig};
+use altis_store::consumer::{EventConsumer, EventHandler, HandlerError};
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+type CacheResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// An airline, its products and the tax engine: what a search prices from
+pub type PricingCatalog = (Arc<Value>, Arc<Vec<Value>>, Arc<TaxEngine>);
+
+/// Values stamped with when they were loaded
+struct TtlMap<K, V> {
+    entries: RwLock<HashMap<K, (Instant, Arc<V>)>>,
+}
+
+impl<K: Eq + Hash, V> TtlMap<K, V> {
+    fn new() -> Self {
+        Self { entries: RwLock::new(HashMap::new()) }
+    }
+
+    fn get(&self, key: &K, ttl: Duration) -> Option<Arc<V>> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries.get(key)
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// The last value loaded, however long ago
+    fn get_stale(&self, key: &K) -> Option<Arc<V>> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries.get(key).map(|(_, value)| value.clone())
+    }
+
+    fn insert(&self, key: K, value: V) -> Arc<V> {
+        let value = Arc::new(value);
+        self.entries.write().unwrap_or_else(|e| e.into_inner()).insert(key, (Instant::now(), value.clone()));
+        value
+    }
+
+    fn clear(&self) {
+        self.entries.write().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// In-process copy of the catalog data every search reads: airlines,
+/// products and their localized content, tax codes, running campaigns,
+/// route networks, active pricing and inventory rules, and each airline's
+/// business rules. Entries live for the configured TTL
+/// and are dropped whenever an admin edits the catalog, so a search only
+/// reaches Postgres when the cache is cold. Edits made through another
+/// instance show up here once the TTL runs out. A TTL of 0 disables caching.
+pub struct CatalogCache {
+    repo: Arc<dyn ProductRepository>,
+    ttl: Duration,
+    airlines: TtlMap<String, Value>,
+    products: TtlMap<Uuid, Vec<Value>>,
+    content: TtlMap<Uuid, ProductContent>,
+    pricing_rules: TtlMap<Uuid, Vec<Value>>,
+    inventory_rules: TtlMap<Uuid, Vec<Value>>,
+    campaigns: TtlMap<Uuid, Vec<Campaign>>,
+    routes: TtlMap<Uuid, RouteNetwork>,
+    business_rules: TtlMap<Uuid, BusinessRules>,
+    tax_engine: TtlMap<(), TaxEngine>,
+}
+
+impl CatalogCache {
+    pub fn new(repo: Arc<dyn ProductRepository>, ttl_seconds: u64) -> Self {
+        Self {
+            repo,
+            ttl: Duration::from_secs(ttl_seconds),
+            airlines: TtlMap::new(),
+            products: TtlMap::new(),
+            content: TtlMap::new(),
+            pricing_rules: TtlMap::new(),
+            inventory_rules: TtlMap::new(),
+            campaigns: TtlMap::new(),
+            routes: TtlMap::new(),
+            business_rules: TtlMap::new(),
+            tax_engine: TtlMap::new(),
+        }
+    }
+
+    /// Unknown codes aren't cached, so a newly onboarded airline shows up at once.
+    pub async fn airline(&self, code: &str) -> CacheResult<Option<Arc<Value>>> {
+        if let Some(airline) = self.airlines.get(&code.to_string(), self.ttl) {
+            return Ok(Some(airline));
+        }
+        Ok(self.repo.get_airline_by_code(code).await?
+            .map(|airline| self.airlines.insert(code.to_string(), airline)))
+    }
+
+    pub async fn products(&self, airline_id: Uuid) -> CacheResult<Arc<Vec<Value>>> {
+        if let Some(products) = self.products.get(&airline_id, self.ttl) {
+            return Ok(products);
+        }
+        let products = self.repo.list_products(airline_id, None).await?;
+        Ok(self.products.insert(airline_id, products))
+    }
+
+    pub async fn content(&self, airline_id: Uuid) -> CacheResult<Arc<ProductContent>> {
+        if let Some(content) = self.content.get(&airline_id, self.ttl) {
+            return Ok(content);
+        }
+        let entries = self.repo.list_airline_product_content(airline_id).await?
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<LocalizedContent>, _>>()?;
+        Ok(self.content.insert(airline_id, ProductContent::new(entries)))
+    }
+
+    pub async fn pricing_rules(&self, airline_id: Uuid) -> CacheResult<Arc<Vec<Value>>> {
+        if let Some(rules) = self.pricing_rules.get(&airline_id, self.ttl) {
+            return Ok(rules);
+        }
+        let rules = self.repo.list_pricing_rules(airline_id).await?;
+        Ok(self.pricing_rules.insert(airline_id, rules))
+    }
+
+    pub async fn inventory_rules(&self, airline_id: Uuid) -> CacheResult<Arc<Vec<Value>>> {
+        if let Some(rules) = self.inventory_rules.get(&airline_id, self.ttl) {
+            return Ok(rules);
+        }
+        let rules = self.repo.list_inventory_rules(airline_id).await?;
+        Ok(self.inventory_rules.insert(airline_id, rules))
+    }
+
+    /// ACTIVE campaigns; pricing still checks each one's window, so one that
+    /// ends while cached stops applying on time
+    pub async fn campaigns(&self, airline_id: Uuid) -> CacheResult<Arc<Vec<Campaign>>> {
+        if let Some(campaigns) = self.campaigns.get(&airline_id, self.ttl) {
+            return Ok(campaigns);
+        }
+        let campaigns = self.repo.list_active_campaigns(airline_id).await?
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<Campaign>, _>>()?;
+        Ok(self.campaigns.insert(airline_id, campaigns))
+    }
+
+    pub async fn routes(&self, airline_id: Uuid) -> CacheResult<Arc<RouteNetwork>> {
+        if let Some(routes) = self.routes.get(&airline_id, self.ttl) {
+            return Ok(routes);
+        }
+        let routes = self.repo.list_routes(airline_id).await?
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<Route>, _>>()?;
+        Ok(self.routes.insert(airline_id, RouteNetwork::new(routes)))
+    }
+
+    /// `defaults` with the airline's overrides laid over them
+    pub async fn business_rules(&self, airline_id: Uuid, defaults: &BusinessRules) -> CacheResult<Arc<BusinessRules>> {
+        if let Some(rules) = self.business_rules.get(&airline_id, self.ttl) {
+            return Ok(rules);
+        }
+        let rules = match self.repo.get_business_rule(airline_id, "CONFIG").await? {
+            Some(overrides) => defaults.with_overrides(&overrides)?,
+            None => defaults.clone(),
+        };
+        Ok(self.business_rules.insert(airline_id, rules))
+    }
+
+    pub async fn tax_engine(&self) -> CacheResult<Arc<TaxEngine>> {
+        if let Some(engine) = self.tax_engine.get(&(), self.ttl) {
+            return Ok(engine);
+        }
+        let codes = self.repo.list_tax_codes().await?
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<TaxCode>, _>>()?;
+        let airports = self.repo.list_airport_countries().await?;
+        Ok(self.tax_engine.insert((), TaxEngine::new(codes, airports)))
+    }
+
+    /// The pricing catalog as last loaded, past its TTL or not; what a
+    /// search prices from when the catalog can't be read in time. None unless all three were loaded since the last edit.
+    pub fn last_known(&self, code: &str) -> Option<PricingCatalog> {
+        let airline = self.airlines.get_stale(&code.to_string())?;
+        let airline_id = airline["id"].as_str().and_then(|id| Uuid::parse_str(id).ok())?;
+        Some((airline, self.products.get_stale(&airline_id)?, self.tax_engine.get_stale(&())?))
+    }
+
+    /// Drops everything; called on any catalog, tax or pricing rule change.
+    pub fn invalidate(&self) {
+        self.airlines.clear();
+        self.products.clear();
+        self.content.clear();
+        self.pricing_rules.clear();
+        self.inventory_rules.clear();
+        self.campaigns.clear();
+        self.routes.clear();
+        self.business_rules.clear();
+        self.tax_engine.clear();
+    }
+}
+
+// ============================================================================
+// Change Events
+// ============================================================================
+
+/// Publishes `catalog.changed` off the request path. If it's lost, other
+/// instances catch up when their cached entries expire.
+pub(crate) fn announce_change(state: &AppState, reason: &str) {
+    let event = CatalogChangedEvent { instance_id: state.instance_id, reason: reason.to_string() };
+    let payload = match registry().seal(&event) {
+        Ok(envelope) => serde_json::json!(envelope).to_string(),
+        Err(e) => {
+            tracing::error!("Catalog change event rejected by its schema: {}", e);
+            return;
+        }
+    };
+    let kafka = state.kafka.clone();
+    let key = state.instance_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = kafka.publish(CATALOG_CHANGED_TOPIC, &key, &payload).await {
+            tracing::warn!("Failed to announce catalog change: {}", e);
+        }
+    });
+}
+
+/// Drops the cached catalog whenever another instance changes it. Each
+/// instance reads in a group of its own, since every one needs every change.
+pub async fn run_catalog_change_consumer(state: AppState, kafka: KafkaConfig, config: CatalogEventsConfig, shutdown: watch::Receiver<bool>) {
+    if !config.consume {
+        return;
+    }
+    let group = format!("{}-{}", config.consumer_group, state.instance_id);
+    let consumer = match EventConsumer::new(&kafka, &group, CATALOG_CHANGED_TOPIC, state.kafka.clone()) {
+        Ok(consumer) => consumer,
+        Err(e) => {
+            tracing::error!("Failed to start catalog change consumer, edits from other instances wait out the TTL: {}", e);
+            return;
+        }
+    };
+    consumer.run(CatalogChangeHandler { state }, shutdown).await;
+}
+
+struct CatalogChangeHandler {
+    state: AppState,
+}
+
+#[async_trait]
+impl EventHandler for CatalogChangeHandler {
+    type Event = CatalogChangedEvent;
+
+    async fn handle(&self, event: &CatalogChangedEvent) -> Result<(), HandlerError> {
+        if event.instance_id != self.state.instance_id {
+            tracing::debug!("Catalog changed on instance {} ({}), dropping cached copy", event.instance_id, event.reason);
+            self.state.catalog_cache.invalidate();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_and_clear() {
+        let map: TtlMap<u8, &str> = TtlMap::new();
+        map.insert(1, "AL");
+
+        assert_eq!(map.get(&1, Duration::from_secs(60)).as_deref(), Some(&"AL"));
+        // A zero TTL means nothing is ever served from cache
+        assert!(map.get(&1, Duration::ZERO).is_none());
+        // ...but stays on hand for when the catalog can't be reached
+        assert_eq!(map.get_stale(&1).as_deref(), Some(&"AL"));
+
+        map.clear();
+        assert!(map.get(&1, Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn test_catalog_changes_travel_in_a_registered_envelope() {
+        let event = CatalogChangedEvent { instance_id: Uuid::new_v4(), reason: "product updated".to_string() };
+        let envelope = registry().seal(&event).unwrap();
+        assert_eq!(envelope.event_type, "catalog_changed");
+
+        let opened: CatalogChangedEvent = registry().open(serde_json::json!(envelope).to_string().as_bytes()).unwrap();
+        assert_eq!(opened, event);
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::{BTreeSet, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use altis_catalog::ProductType;
+use altis_store::app_config::ConfigProblem;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::preflight::check_rule_terms;
+use crate::state::AppState;
+
+// ============================================================================
+// Bundle Format
+// ============================================================================
+
+/// An airline's catalog as moved between environments. Entries refer to each
+/// other by natural key (product code, rule name, bundle name, resource
+/// type), never by id, since ids differ from one environment to the next.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CatalogBundle {
+    /// Base URL of the environment the bundle was exported from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub products: Vec<BundleProduct>,
+    #[serde(default)]
+    pub pricing_rules: Vec<BundlePricingRule>,
+    #[serde(default)]
+    pub bundles: Vec<BundleTemplate>,
+    #[serde(default)]
+    pub inventory_rules: Vec<BundleInventoryRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleProduct {
+    pub product_type: String,
+    pub product_code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub base_price_nuc: i32,
+    #[serde(default = "empty_object")]
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundlePricingRule {
+    pub rule_name: String,
+    pub rule_type: String,
+    /// Product the rule prices; None applies it by its conditions alone
+    pub product_code: Option<String>,
+    pub conditions: Value,
+    pub adjustments: Value,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleTemplate {
+    pub bundle_name: String,
+    pub bundle_type: String,
+    pub product_types: Value,
+    #[serde(default)]
+    pub discount_percentage: f64,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleInventoryRule {
+    pub resource_type: String,
+    pub hold_duration_seconds: i32,
+    #[serde(default)]
+    pub overbooking_percentage: f64,
+    #[serde(default)]
+    pub min_availability_threshold: i32,
+    #[serde(default = "default_true")]
+    pub auto_release_on_expiry: bool,
+    #[serde(default)]
+    pub notify_on_low_inventory: bool,
+    /// Units of each product of this type on sale; None is unlimited
+    #[serde(default)]
+    pub capacity: Option<i32>,
+    /// Unpaid orders a customer may hold with this type at once; None is unlimited
+    #[serde(default)]
+    pub max_holds_per_customer: Option<i32>,
+    #[serde(default)]
+    pub blackout_dates: Vec<chrono::NaiveDate>,
+}
+
+fn empty_object() -> Value {
+    serde_json::json!({})
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// ============================================================================
+// Diff
+// ============================================================================
+
+/// What applying a bundle would do to one section, by natural key
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct SectionDiff {
+    pub added: Vec<String>,
+    pub changed: Vec<ChangedEntry>,
+    /// Deactivated on apply; existing orders keep their references
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ChangedEntry {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct CatalogDiff {
+    pub products: SectionDiff,
+    pub pricing_rules: SectionDiff,
+    pub bundles: SectionDiff,
+    pub inventory_rules: SectionDiff,
+}
+
+impl CatalogDiff {
+    pub fn is_empty(&self) -> bool {
+        [&self.products, &self.pricing_rules, &self.bundles, &self.inventory_rules]
+            .iter()
+            .all(|s| s.added.is_empty() && s.changed.is_empty() && s.removed.is_empty())
+    }
+}
+
+/// Changes that turn `current` into `incoming`
+pub fn diff(current: &CatalogBundle, incoming: &CatalogBundle) -> CatalogDiff {
+    CatalogDiff {
+        products: diff_section(&current.products, &incoming.products, |p| &p.product_code),
+        pricing_rules: diff_section(&current.pricing_rules, &incoming.pricing_rules, |r| &r.rule_name),
+        bundles: diff_section(&current.bundles, &incoming.bundles, |b| &b.bundle_name),
+        inventory_rules: diff_section(&current.inventory_rules, &incoming.inventory_rules, |r| &r.resource_type),
+    }
+}
+
+fn diff_section<T: Serialize>(current: &[T], incoming: &[T], key: impl Fn(&T) -> &str) -> SectionDiff {
+    let mut section = SectionDiff::default();
+
+    for entry in incoming {
+        let Some(existing) = current.iter().find(|c| key(c) == key(entry)) else {
+            section.added.push(key(entry).to_string());
+            continue;
+        };
+        let (before, after) = (serde_json::to_value(existing).unwrap_or_default(), serde_json::to_value(entry).unwrap_or_default());
+        let names: BTreeSet<&String> = before.as_object().into_iter().chain(after.as_object()).flat_map(|fields| fields.keys()).collect();
+        let fields: Vec<String> = names.into_iter().filter(|name| before[name.as_str()] != after[name.as_str()]).cloned().collect();
+        if !fields.is_empty() {
+            section.changed.push(ChangedEntry { key: key(entry).to_string(), fields });
+        }
+    }
+    section.removed = current.iter()
+        .filter(|c| !incoming.iter().any(|entry| key(entry) == key(c)))
+        .map(|c| key(c).to_string())
+        .collect();
+
+    section
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+/// Everything in the bundle the target environment couldn't serve: unknown
+/// product types, duplicate keys, rules pointing at products the bundle
+/// doesn't carry, and values outside what the engine and schema accept.
+pub fn validate(bundle: &CatalogBundle) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |setting: String, message: String| problems.push(ConfigProblem { setting, message });
+    let known_type = |product_type: &str| serde_json::from_value::<ProductType>(Value::String(product_type.to_string())).is_ok();
+
+    let mut codes = HashSet::new();
+    for product in &bundle.products {
+        let setting = format!("products[{}]", product.product_code);
+        if product.product_code.trim().is_empty() {
+            problem(setting.clone(), "product_code is empty".to_string());
+        }
+        if !codes.insert(product.product_code.as_str()) {
+            problem(setting.clone(), "appears more than once".to_string());
+        }
+        if !known_type(&product.product_type) {
+            problem(setting.clone(), format!("product type '{}' is unknown", product.product_type));
+        }
+        if product.base_price_nuc < 0 {
+            problem(setting.clone(), "base_price_nuc is negative".to_string());
+        }
+        if !product.metadata.is_object() {
+            problem(setting, "metadata must be a JSON object".to_string());
+        }
+    }
+
+    let mut rule_names = HashSet::new();
+    for rule in &bundle.pricing_rules {
+        let setting = format!("pricing_rules[{}]", rule.rule_name);
+        if !rule_names.insert(rule.rule_name.as_str()) {
+            problem(setting.clone(), "appears more than once".to_string());
+        }
+        if let Some(code) = &rule.product_code {
+            if !codes.contains(code.as_str()) {
+                problem(setting.clone(), format!("references product {}, which the bundle doesn't include", code));
+            }
+        }
+        let terms = serde_json::json!({ "conditions": rule.conditions, "adjustments": rule.adjustments });
+        for found in check_rule_terms(&setting, &terms) {
+            problem(found.setting, found.message);
+        }
+    }
+
+    let mut bundle_names = HashSet::new();
+    for template in &bundle.bundles {
+        let setting = format!("bundles[{}]", template.bundle_name);
+        if !bundle_names.insert(template.bundle_name.as_str()) {
+            problem(setting.clone(), "appears more than once".to_string());
+        }
+        if !(0.0..=100.0).contains(&template.discount_percentage) {
+            problem(setting.clone(), format!("discount_percentage {} is outside 0-100", template.discount_percentage));
+        }
+        match template.product_types.as_array() {
+            Some(entries) => {
+                for entry in entries {
+                    match entry["type"].as_str() {
+                        Some(product_type) if known_type(product_type) => {}
+                        Some(product_type) => problem(setting.clone(), format!("includes unknown product type '{}'", product_type)),
+                        None => problem(setting.clone(), "has a product_types entry without a \"type\"".to_string()),
+                    }
+                }
+            }
+            None => problem(setting, "product_types must be a JSON array".to_string()),
+        }
+    }
+
+    let mut resource_types = HashSet::new();
+    for rule in &bundle.inventory_rules {
+        let setting = format!("inventory_rules[{}]", rule.resource_type);
+        if !resource_types.insert(rule.resource_type.as_str()) {
+            problem(setting.clone(), "appears more than once".to_string());
+        }
+        if !known_type(&rule.resource_type) {
+            problem(setting.clone(), format!("resource type '{}' is unknown", rule.resource_type));
+        }
+        if rule.hold_duration_seconds <= 0 {
+            problem(setting.clone(), "hold_duration_seconds must be positive".to_string());
+        }
+        // Stored as NUMERIC(5,2)
+        if !(0.0..100.0).contains(&rule.overbooking_percentage) {
+            problem(setting.clone(), format!("overbooking_percentage {} is outside 0-100", rule.overbooking_percentage));
+        }
+        if rule.min_availability_threshold < 0 {
+            problem(setting.clone(), "min_availability_threshold is negative".to_string());
+        }
+        if rule.capacity.is_some_and(|capacity| capacity < 0) {
+            problem(setting.clone(), "capacity is negative".to_string());
+        }
+        if rule.max_holds_per_customer.is_some_and(|max| max <= 0) {
+            problem(setting, "max_holds_per_customer must be positive".to_string());
+        }
+    }
+
+    problems
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CatalogPreview {
+    pub diff: CatalogDiff,
+    pub problems: Vec<ConfigProblem>,
+    pub applied: bool,
+}
+
+async fn current_catalog(state: &AppState, airline_id: Uuid) -> Result<CatalogBundle, StatusCode> {
+    let exported = state.catalog_repo.export_catalog(airline_id).await.map_err(|e| {
+        tracing::error!("Failed to export catalog for airline {}: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    serde_json::from_value(exported).map_err(|e| {
+        tracing::error!("Catalog for airline {} doesn't fit the bundle format: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// GET /v1/admin/airlines/:airline_id/catalog/export
+/// The airline's active catalog, ready to import into another environment
+pub async fn export_catalog(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<Json<CatalogBundle>, StatusCode> {
+    let mut bundle = current_catalog(&state, airline_id).await?;
+    bundle.exported_from = Some(state.api_base_url.clone());
+    bundle.exported_at = Some(chrono::Utc::now());
+    Ok(Json(bundle))
+}
+
+/// POST /v1/admin/airlines/:airline_id/catalog/diff
+/// Preview what importing the bundle here would add, change and remove
+pub async fn diff_catalog(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Json(bundle): Json<CatalogBundle>,
+) -> Result<Json<CatalogPreview>, StatusCode> {
+    let current = current_catalog(&state, airline_id).await?;
+    Ok(Json(CatalogPreview { diff: diff(&current, &bundle), problems: validate(&bundle), applied: false }))
+}
+
+/// POST /v1/admin/airlines/:airline_id/catalog/import
+/// Replace the airline's catalog with the bundle; `?dry_run=true` validates
+/// and diffs without writing. Bundles with problems are rejected with 422.
+pub async fn import_catalog(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Query(query): Query<ImportQuery>,
+    Json(bundle): Json<CatalogBundle>,
+) -> Result<(StatusCode, Json<CatalogPreview>), StatusCode> {
+    let current = current_catalog(&state, airline_id).await?;
+    let mut preview = CatalogPreview { diff: diff(&current, &bundle), problems: validate(&bundle), applied: false };
+
+    if !preview.problems.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(preview)));
+    }
+    if query.dry_run || preview.diff.is_empty() {
+        return Ok((StatusCode::OK, Json(preview)));
+    }
+
+    let payload = serde_json::to_value(&bundle).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    state.catalog_repo.apply_catalog(airline_id, &payload).await.map_err(|e| {
+        tracing::error!("Failed to import catalog for airline {}: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    // Cached searches were priced with the old catalog
+    state.catalog_changed("catalog imported").await;
+    // Ancillaries the bundle gives a capacity to start being tracked now
+    if let Err(e) = crate::warmup::seed_ancillary_inventory(&state, &[airline_id]).await {
+        tracing::warn!("Imported catalog for airline {} but failed to track ancillary stock: {}", airline_id, e);
+    }
+
+    tracing::info!(
+        "Imported catalog for airline {} from {}",
+        airline_id,
+        bundle.exported_from.as_deref().unwrap_or("an unnamed environment")
+    );
+    preview.applied = true;
+    Ok((StatusCode::OK, Json(preview)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(code: &str, price: i32) -> BundleProduct {
+        BundleProduct {
+            product_type: "BAG".to_string(),
+            product_code: code.to_string(),
+            name: code.to_string(),
+            description: None,
+            base_price_nuc: price,
+            metadata: empty_object(),
+        }
+    }
+
+    #[test]
+    fn test_diff_and_validate() {
+        let current = CatalogBundle { products: vec![product("BAG-20", 3500), product("BAG-32", 5000)], ..Default::default() };
+        let incoming = CatalogBundle {
+            exported_from: Some("https://staging.example".to_string()),
+            products: vec![product("BAG-20", 3900), product("BAG-10", 2000)],
+            pricing_rules: vec![BundlePricingRule {
+                rule_name: "Peak bags".to_string(),
+                rule_type: "DEMAND".to_string(),
+                product_code: Some("BAG-32".to_string()),
+                conditions: serde_json::json!({}),
+                adjustments: serde_json::json!({"type": "MULTIPLIER", "value": 1.2}),
+                priority: 10,
+            }],
+            ..Default::default()
+        };
+
+        let diff = diff(&current, &incoming);
+        assert_eq!(diff.products.added, vec!["BAG-10"]);
+        assert_eq!(diff.products.changed, vec![ChangedEntry { key: "BAG-20".to_string(), fields: vec!["base_price_nuc".to_string()] }]);
+        assert_eq!(diff.products.removed, vec!["BAG-32"]);
+        assert_eq!(diff.pricing_rules.added, vec!["Peak bags"]);
+
+        let problems = validate(&incoming);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].setting, "pricing_rules[Peak bags]");
+        assert!(problems[0].message.contains("BAG-32"));
+
+        assert!(super::diff(&current, &current).is_empty());
+    }
+}
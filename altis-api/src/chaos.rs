@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use altis_store::chaos::{ChaosFault, ChaosTarget};
+use serde::Serialize;
+
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct ChaosStateResponse {
+    pub enabled: bool,
+    /// Active faults by target (redis, postgres, payment, ml)
+    pub faults: BTreeMap<&'static str, ChaosFault>,
+}
+
+fn snapshot(state: &AppState) -> ChaosStateResponse {
+    ChaosStateResponse {
+        enabled: state.chaos.is_enabled(),
+        faults: state.chaos.faults().into_iter().map(|(target, fault)| (target.as_str(), fault)).collect(),
+    }
+}
+
+/// GET /v1/admin/chaos
+pub async fn get_chaos(State(state): State<AppState>) -> Json<ChaosStateResponse> {
+    Json(snapshot(&state))
+}
+
+/// PUT /v1/admin/chaos/:target
+/// Sets the latency/error probabilities injected into one dependency
+pub async fn set_chaos_fault(
+    State(state): State<AppState>,
+    Path(target): Path<String>,
+    Json(fault): Json<ChaosFault>,
+) -> Result<Json<ChaosStateResponse>, StatusCode> {
+    let target = ChaosTarget::parse(&target).ok_or(StatusCode::NOT_FOUND)?;
+    if !state.chaos.is_enabled() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    state.chaos.set_fault(target, fault.clone()).map_err(|_| StatusCode::BAD_REQUEST)?;
+    tracing::warn!("Chaos fault set on {}: {:?}", target.as_str(), fault);
+
+    Ok(Json(snapshot(&state)))
+}
+
+/// DELETE /v1/admin/chaos/:target
+pub async fn clear_chaos_fault(
+    State(state): State<AppState>,
+    Path(target): Path<String>,
+) -> Result<Json<ChaosStateResponse>, StatusCode> {
+    let target = ChaosTarget::parse(&target).ok_or(StatusCode::NOT_FOUND)?;
+    state.chaos.clear(Some(target));
+    tracing::warn!("Chaos fault cleared on {}", target.as_str());
+    Ok(Json(snapshot(&state)))
+}
+
+/// DELETE /v1/admin/chaos
+/// Stops all fault injection
+pub async fn clear_all_chaos(State(state): State<AppState>) -> Json<ChaosStateResponse> {
+    state.chaos.clear(None);
+    tracing::warn!("All chaos faults cleared");
+    Json(snapshot(&state))
+}
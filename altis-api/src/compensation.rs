@@ -0,0 +1,246 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_order::compensation::{self, CompensationAssessment, CompensationTerms, DisruptionFacts, Scheme};
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::Money;
+
+use crate::authz::authorize_order;
+use crate::middleware::auth::CustomerClaims;
+use crate::orders::OrderItemResponse;
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct CompensationEligibilityResponse {
+    pub order_id: Uuid,
+    pub items: Vec<ItemCompensation>,
+}
+
+/// What one disrupted booking is owed
+#[derive(Debug, Serialize)]
+pub struct ItemCompensation {
+    pub item_id: Uuid,
+    pub flight_id: Uuid,
+    #[serde(flatten)]
+    pub assessment: CompensationAssessment,
+    /// Offered instead of `amount_nuc` for taking a voucher
+    pub voucher_amount_nuc: i32,
+    /// The payout already made, if any
+    pub payout: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimCompensationRequest {
+    pub item_id: Uuid,
+    /// CASH or VOUCHER
+    pub payout_method: String,
+}
+
+fn scheme_name(scheme: Scheme) -> &'static str {
+    match scheme {
+        Scheme::Eu261 => "EU261",
+        Scheme::UsDot => "US_DOT",
+    }
+}
+
+fn uuid_of(value: &serde_json::Value) -> Option<Uuid> {
+    value.as_str().and_then(|id| Uuid::parse_str(id).ok())
+}
+
+// ============================================================================
+// Assessment
+// ============================================================================
+
+/// Assesses every booking on a disrupted flight of the order, using the last
+/// disruption recorded for each flight and, when the passenger moved to a
+/// replacement flight, how late that one arrives
+pub(crate) async fn assess_order(state: &AppState, order: &serde_json::Value) -> Result<Vec<ItemCompensation>, StatusCode> {
+    let order_id = uuid_of(&order["id"]).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let items: Vec<OrderItemResponse> = serde_json::from_value(order["items"].clone()).unwrap_or_default();
+    let changes = state.order_repo.get_order_changes(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let payouts = state.order_repo.list_compensation_payouts(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut disruptions: Vec<serde_json::Value> = Vec::new();
+    for change in changes.into_iter().filter(|c| c["change_type"] == "FLIGHT_DISRUPTION") {
+        let disruption = change["new_value"].clone();
+        disruptions.retain(|d| d["flight_id"] != disruption["flight_id"]);
+        disruptions.push(disruption);
+    }
+
+    let carrier_country = match uuid_of(&order["airline_id"]) {
+        Some(airline_id) => state.catalog_repo.list_active_airlines().await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .into_iter()
+            .find(|airline| uuid_of(&airline["id"]) == Some(airline_id))
+            .and_then(|airline| airline["country"].as_str().map(str::to_string)),
+        None => None,
+    };
+    let terms = CompensationTerms { eur_to_nuc: state.compensation.eur_to_nuc };
+
+    let mut assessed = Vec::new();
+    for disruption in &disruptions {
+        let Some(flight_id) = uuid_of(&disruption["flight_id"]) else { continue };
+        let Some(flight) = state.catalog_repo.get_product(flight_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else { continue };
+        let origin = airport(state, &flight["metadata"]["origin"]).await?;
+        let destination = airport(state, &flight["metadata"]["destination"]).await?;
+
+        // Proposals carry their own flight id, so these are the original bookings
+        let booked = items.iter().filter(|item| {
+            item.product_type == "FLIGHT"
+                && item.status != "REACCOMMODATED"
+                && uuid_of(&item.metadata["flight_id"]) == Some(flight_id)
+        });
+        for item in booked {
+            let replacement = items.iter().find(|other| {
+                other.status == "ACTIVE" && uuid_of(&other.metadata["disrupted_item_id"]) == Some(item.id)
+            });
+            let assessment = match (&origin, &destination) {
+                (Some(origin), Some(destination)) => compensation::assess(&DisruptionFacts {
+                    origin_country: origin.0.clone(),
+                    destination_country: destination.0.clone(),
+                    carrier_country: carrier_country.clone(),
+                    distance_km: compensation::distance_km(origin.1, destination.1),
+                    cancelled: disruption["new_status"] == "CANCELLED",
+                    arrival_delay_minutes: replacement
+                        .and_then(|r| r.metadata["arrival_delay_minutes"].as_i64())
+                        .or_else(|| disruption["delay_minutes"].as_i64())
+                        .unwrap_or(0),
+                    reaccommodated: replacement.is_some(),
+                    cause: disruption["cause"].as_str().map(str::to_string),
+                    passengers: item.quantity.unwrap_or(1),
+                    fare_nuc: item.price_nuc,
+                }, &terms),
+                _ => CompensationAssessment {
+                    scheme: None,
+                    eligible: false,
+                    amount_nuc: 0,
+                    reason: "Route distance unknown".to_string(),
+                },
+            };
+
+            let payout = payouts.iter()
+                .find(|p| uuid_of(&p["order_item_id"]) == Some(item.id)
+                    && assessment.scheme.is_some_and(|scheme| p["scheme"] == scheme_name(scheme)))
+                .cloned();
+            assessed.push(ItemCompensation {
+                item_id: item.id,
+                flight_id,
+                voucher_amount_nuc: voucher_amount(state, &assessment),
+                assessment,
+                payout,
+            });
+        }
+    }
+    Ok(assessed)
+}
+
+/// Country and (latitude, longitude) of the airport, when known
+async fn airport(state: &AppState, code: &serde_json::Value) -> Result<Option<(String, (f64, f64))>, StatusCode> {
+    let Some(code) = code.as_str() else { return Ok(None) };
+    let airport = state.catalog_repo.get_airport(code).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(airport.and_then(|a| Some((
+        a["country"].as_str()?.to_string(),
+        (a["latitude"].as_f64()?, a["longitude"].as_f64()?),
+    ))))
+}
+
+fn voucher_amount(state: &AppState, assessment: &CompensationAssessment) -> i32 {
+    (assessment.amount_nuc as f64 * state.compensation.voucher_uplift).round() as i32
+}
+
+// ============================================================================
+// Payout
+// ============================================================================
+
+/// Pays an eligible assessment once: records the payout, writes it to the
+/// order ledger and the journal. None when the booking was already paid
+/// under the scheme.
+pub(crate) async fn pay(
+    state: &AppState,
+    order_id: Uuid,
+    item: &ItemCompensation,
+    voucher: bool,
+) -> Result<Option<serde_json::Value>, StatusCode> {
+    let scheme = item.assessment.scheme.filter(|_| item.assessment.eligible)
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let amount_nuc = if voucher { item.voucher_amount_nuc } else { item.assessment.amount_nuc };
+    let payout = serde_json::json!({
+        "order_id": order_id,
+        "order_item_id": item.item_id,
+        "scheme": scheme_name(scheme),
+        "amount_nuc": amount_nuc,
+        "payout_method": if voucher { "VOUCHER" } else { "CASH" },
+        "voucher_code": voucher.then(|| format!("CMP{}", &Uuid::new_v4().simple().to_string()[..12].to_uppercase())),
+    });
+    let Some(created) = state.order_repo.create_compensation_payout(&payout).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else {
+        return Ok(None);
+    };
+
+    let description = format!("{} compensation ({})", scheme_name(scheme), payout["payout_method"].as_str().unwrap_or_default());
+    let _ = state.order_repo.add_order_ledger_entry(order_id, item.item_id, "COMPENSATION", -amount_nuc, Some(&description)).await;
+    crate::finance::post_journal(
+        state,
+        JournalTransaction::compensation(order_id, item.item_id, Money::nuc(amount_nuc as i64), voucher, scheme_name(scheme)),
+    ).await;
+    tracing::info!("Paid {} NUC {} compensation on item {} of order {}", amount_nuc, scheme_name(scheme), item.item_id, order_id);
+    Ok(Some(created))
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/orders/:id/compensation-eligibility
+/// What each disrupted booking on the order is owed under EU261 or US DOT rules
+pub async fn get_compensation_eligibility(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<CompensationEligibilityResponse>, StatusCode> {
+    let order = authorize_order(&state, &claims, order_id).await?;
+    let items = assess_order(&state, &order).await?;
+    Ok(Json(CompensationEligibilityResponse { order_id, items }))
+}
+
+/// POST /v1/orders/:id/compensation
+/// Claim the compensation for one booking, in cash or as a larger voucher
+pub async fn claim_compensation(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+    Json(req): Json<ClaimCompensationRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let voucher = match req.payout_method.as_str() {
+        "CASH" => false,
+        "VOUCHER" => true,
+        _ => return Err(StatusCode::BAD_REQUEST),
+    };
+    let order = authorize_order(&state, &claims, order_id).await?;
+    let assessed = assess_order(&state, &order).await?;
+    let item = assessed.iter().find(|item| item.item_id == req.item_id).ok_or(StatusCode::NOT_FOUND)?;
+    if item.payout.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let payout = pay(&state, order_id, item, voucher).await?.ok_or(StatusCode::CONFLICT)?;
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "COMPENSATION_PAID",
+        None,
+        Some(payout.clone()),
+        "CUSTOMER",
+        Some("Customer claimed disruption compensation"),
+    ).await;
+    Ok(Json(payout))
+}
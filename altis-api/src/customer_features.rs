@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use altis_offer::features::CustomerFeatures;
+use altis_store::app_config::PersonalizationConfig;
+
+use crate::state::AppState;
+
+/// Orders updated while a refresh runs may be missed by its snapshot, so each
+/// run looks back this far before the previous one started.
+const REFRESH_OVERLAP_SECONDS: i64 = 60;
+
+/// Features for ranking this customer's searches. Lookup failures only cost
+/// personalization, so they are logged and the search goes ahead without.
+pub async fn for_customer(state: &AppState, customer_id: &str) -> Option<CustomerFeatures> {
+    match state.customer_feature_repo.get_customer_features(customer_id).await {
+        Ok(features) => features.and_then(|value| serde_json::from_value(value).ok()),
+        Err(e) => {
+            tracing::warn!("Failed to load customer features for {}: {:?}", customer_id, e);
+            None
+        }
+    }
+}
+
+/// Background loop keeping `customer_features` in step with orders. The first
+/// run rebuilds every customer; later runs only revisit customers whose
+/// orders changed since.
+pub async fn run_customer_features_refresher(state: AppState, config: PersonalizationConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.refresh_interval_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut since = None;
+
+    loop {
+        interval.tick().await;
+
+        let started = chrono::Utc::now();
+        match state.customer_feature_repo.refresh_customer_features(since, config.lookback_days).await {
+            Ok(refreshed) => {
+                tracing::info!("Refreshed purchase-history features for {} customers", refreshed);
+                since = Some(started - chrono::Duration::seconds(REFRESH_OVERLAP_SECONDS));
+            }
+            Err(e) => tracing::error!("Failed to refresh customer features: {:?}", e),
+        }
+    }
+}
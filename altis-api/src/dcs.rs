@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use altis_core::dcs::{ServiceDeliveredEvent, SERVICE_DELIVERED_TOPIC, SERVICE_DELIVERY_TOPIC};
+use altis_core::events::registry;
+use altis_store::app_config::{DcsConfig, KafkaConfig};
+use altis_store::consumer::{EventConsumer, EventHandler, HandlerError};
+
+use crate::state::AppState;
+use crate::v1::oneorder::{self, DeliveryMappingError};
+
+/// Records check-in on the order's booked flights; the export worker then
+/// sends the passengers on to departure control
+pub(crate) async fn mark_checked_in(state: &AppState, order: &serde_json::Value) {
+    let items = order["items"].as_array().cloned().unwrap_or_default();
+    let now = chrono::Utc::now();
+    for item in &items {
+        let flight = item["product_type"].as_str() == Some("FLIGHT") && item["status"].as_str() == Some("ACTIVE");
+        if !flight || !item["metadata"]["checked_in_at"].is_null() {
+            continue;
+        }
+        let Some(item_id) = item["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+        if let Err(e) = state.order_repo.merge_item_metadata(item_id, &serde_json::json!({"checked_in_at": now})).await {
+            tracing::error!("Failed to record check-in of item {}: {:?}", item_id, e);
+        }
+    }
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Background loop sending each checked-in booking to `dcs.service.delivery`,
+/// one record per passenger. Mapping that fails for a passing reason is
+/// tried again next pass; an item that can never be mapped is marked with
+/// the reason and left for the airport.
+pub async fn run_dcs_export_worker(state: AppState, config: DcsConfig) {
+    if !config.export {
+        return;
+    }
+    let mut interval = tokio::time::interval(Duration::from_secs(config.export_poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let due = match state.order_repo.due_dcs_exports(config.export_batch_size).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to find check-ins due for departure control: {:?}", e);
+                continue;
+            }
+        };
+        for booking in &due {
+            let id_of = |field: &str| booking[field].as_str().and_then(|id| Uuid::parse_str(id).ok());
+            let (Some(order_id), Some(item_id)) = (id_of("order_id"), id_of("order_item_id")) else { continue };
+            export_item(&state, order_id, item_id).await;
+        }
+    }
+}
+
+async fn export_item(state: &AppState, order_id: Uuid, item_id: Uuid) {
+    let records = match oneorder::checked_in_records(state, order_id, item_id).await {
+        Ok(records) => records,
+        Err(DeliveryMappingError::Retryable(reason)) => {
+            tracing::warn!("Check-in of item {} on order {} not sent to departure control yet: {}", item_id, order_id, reason);
+            return;
+        }
+        Err(DeliveryMappingError::Rejected(reason)) => {
+            tracing::error!("Check-in of item {} on order {} can't be sent to departure control: {}", item_id, order_id, reason);
+            let patch = serde_json::json!({"dcs_exported_at": chrono::Utc::now(), "dcs_export_error": reason});
+            let _ = state.order_repo.merge_item_metadata(item_id, &patch).await;
+            return;
+        }
+    };
+
+    for record in &records {
+        let payload = match registry().seal(record) {
+            Ok(envelope) => serde_json::json!(envelope).to_string(),
+            Err(e) => {
+                tracing::error!("Service delivery record for order {} rejected by its schema: {}", order_id, e);
+                return;
+            }
+        };
+        // Keyed by order, so the DCS sees one booking's passengers in order
+        if let Err(e) = state.kafka.publish(SERVICE_DELIVERY_TOPIC, &order_id.to_string(), &payload).await {
+            tracing::warn!("Failed to send order {} to departure control, retrying next pass: {}", order_id, e);
+            return;
+        }
+    }
+    if let Err(e) = state.order_repo.merge_item_metadata(item_id, &serde_json::json!({"dcs_exported_at": chrono::Utc::now()})).await {
+        tracing::error!("Failed to mark item {} of order {} sent to departure control: {:?}", item_id, order_id, e);
+    }
+    tracing::info!("Sent {} passenger(s) of order {} to departure control", records.len(), order_id);
+}
+
+// ============================================================================
+// Delivery Confirmations
+// ============================================================================
+
+/// Marks fulfillment delivered as departure control confirms each service
+pub async fn run_dcs_delivery_consumer(state: AppState, kafka: KafkaConfig, config: DcsConfig, shutdown: watch::Receiver<bool>) {
+    if !config.consume {
+        return;
+    }
+    let consumer = match EventConsumer::new(&kafka, &config.consumer_group, SERVICE_DELIVERED_TOPIC, state.kafka.clone()) {
+        Ok(consumer) => consumer,
+        Err(e) => {
+            tracing::error!("Failed to start DCS delivery consumer, fulfillment won't show what was delivered: {}", e);
+            return;
+        }
+    };
+    consumer.run(ServiceDeliveredHandler { state }, shutdown).await;
+}
+
+struct ServiceDeliveredHandler {
+    state: AppState,
+}
+
+#[async_trait]
+impl EventHandler for ServiceDeliveredHandler {
+    type Event = ServiceDeliveredEvent;
+
+    async fn handle(&self, event: &ServiceDeliveredEvent) -> Result<(), HandlerError> {
+        oneorder::record_delivery(&self.state, event).await.map_err(|e| match e {
+            DeliveryMappingError::Retryable(reason) => HandlerError::Retryable(reason),
+            DeliveryMappingError::Rejected(reason) => HandlerError::Permanent(reason),
+        })
+    }
+}
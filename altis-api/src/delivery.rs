@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use altis_core::iata::Traveler;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::orders::OrderItemResponse;
+use crate::state::AppState;
+
+/// Kafka topic consumed by the notification service (email/SMS/app push).
+const DELIVERY_TOPIC: &str = "fulfillment";
+
+/// Claimed deliveries are retried after this long if the worker dies mid-send.
+const CLAIM_LEASE_SECONDS: i64 = 300;
+
+pub fn barcode_for(order_id: Uuid, item_id: Uuid, traveler_index: Option<i32>, unit_index: i32) -> String {
+    let barcode = match traveler_index {
+        Some(index) => format!("ALTIS-{}-{}-T{}", order_id.simple(), item_id.simple(), index),
+        None => format!("ALTIS-{}-{}", order_id.simple(), item_id.simple()),
+    };
+    match unit_index {
+        0 => barcode,
+        unit => format!("{}-U{}", barcode, unit),
+    }
+}
+
+/// Who one fulfillment record is for: a traveler on a flight segment, a
+/// traveler's own ancillary, or (no traveler) the whole booking; and which
+/// of the item's units it is
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FulfillmentHolder {
+    pub traveler_id: Option<Uuid>,
+    pub traveler_index: Option<i32>,
+    pub segment: Option<serde_json::Value>,
+    /// From 0; each unit is consumed, and earns, on its own
+    pub unit_index: i32,
+}
+
+/// The segment a flight item's barcode boards, from the item's metadata
+fn flight_segment(metadata: &serde_json::Value) -> serde_json::Value {
+    let segment: serde_json::Map<String, serde_json::Value> = ["flight_id", "flight_number", "origin", "destination", "departure_time"]
+        .into_iter()
+        .filter(|field| !metadata[*field].is_null())
+        .map(|field| (field.to_string(), metadata[field].clone()))
+        .collect();
+    serde_json::Value::Object(segment)
+}
+
+/// The records an item is fulfilled with: a flight gets one per traveler of
+/// the passenger type it was priced for (per passenger booked, while names
+/// aren't in), an ancillary one per unit held, for the traveler it was
+/// bought for or else the booking
+pub fn fulfillment_holders(item: &OrderItemResponse, travelers: &[Traveler]) -> Vec<FulfillmentHolder> {
+    let traveler_id = |index: i32| travelers.iter().find(|t| t.traveler_index == index).and_then(|t| t.id);
+
+    if item.product_type.eq_ignore_ascii_case("FLIGHT") {
+        let segment = Some(flight_segment(&item.metadata));
+        let ptc = item.metadata["ptc"].as_str();
+        let indices: Vec<i32> = if travelers.is_empty() {
+            (0..item.quantity.unwrap_or(1).max(1)).collect()
+        } else {
+            travelers.iter()
+                .filter(|t| ptc.is_none_or(|ptc| t.ptc.eq_ignore_ascii_case(ptc)))
+                .map(|t| t.traveler_index)
+                .collect()
+        };
+        return indices.into_iter()
+            .zip(0..)
+            .map(|(index, unit_index)| FulfillmentHolder { traveler_id: traveler_id(index), traveler_index: Some(index), segment: segment.clone(), unit_index })
+            .collect();
+    }
+    (0..item.active_units())
+        .map(|unit_index| FulfillmentHolder {
+            traveler_id: item.traveler_id.or_else(|| item.traveler_index.and_then(traveler_id)),
+            traveler_index: item.traveler_index,
+            segment: None,
+            unit_index,
+        })
+        .collect()
+}
+
+/// Background loop delivering scheduled fulfillment (wifi codes, duty-free
+/// pickup vouchers) once their delivery time arrives.
+pub async fn run_delivery_worker(state: AppState, config: altis_store::app_config::FulfillmentConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.delivery_poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let due = match state.order_repo.claim_due_deliveries(
+            config.delivery_batch_size,
+            CLAIM_LEASE_SECONDS,
+            config.delivery_max_attempts,
+        ).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to claim scheduled deliveries: {:?}", e);
+                continue;
+            }
+        };
+
+        for delivery in due {
+            deliver(&state, &delivery).await;
+        }
+    }
+}
+
+/// Issues the artifact and hands it to the notification pipeline. The barcode
+/// is only recorded once the event is published, so a failed send is retried.
+async fn deliver(state: &AppState, delivery: &serde_json::Value) {
+    let parse = |field: &str| delivery[field].as_str().and_then(|id| Uuid::parse_str(id).ok());
+    let (Some(fulfillment_id), Some(order_id), Some(item_id)) = (parse("id"), parse("order_id"), parse("order_item_id")) else {
+        return;
+    };
+
+    let traveler_index = delivery["traveler_index"].as_i64().map(|index| index as i32);
+    let unit_index = delivery["unit_index"].as_i64().unwrap_or(0) as i32;
+    let barcode = barcode_for(order_id, item_id, traveler_index, unit_index);
+    let event = serde_json::json!({
+        "event_type": "FULFILLMENT_DELIVERED",
+        "order_id": order_id,
+        "order_item_id": item_id,
+        "traveler_index": traveler_index,
+        "unit_index": unit_index,
+        "fulfillment_type": delivery["fulfillment_type"],
+        "barcode": barcode,
+        "timestamp": chrono::Utc::now().timestamp(),
+    });
+
+    if let Err(e) = state.kafka.publish(DELIVERY_TOPIC, &order_id.to_string(), &event.to_string()).await {
+        tracing::warn!("Delivery {} failed (attempt {}): {}", fulfillment_id, delivery["delivery_attempts"], e);
+        let _ = state.order_repo.fail_delivery(fulfillment_id, &e.to_string()).await;
+        return;
+    }
+
+    if let Err(e) = state.order_repo.complete_delivery(fulfillment_id, &barcode, "APP").await {
+        tracing::error!("Delivered {} but failed to record it: {:?}", fulfillment_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(product_type: &str, quantity: i32, metadata: serde_json::Value) -> OrderItemResponse {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(), "product_id": null, "product_type": product_type, "name": product_type, "price_nuc": 100,
+            "quantity": quantity, "status": "ACTIVE", "revenue_status": "UNEARNED", "operating_carrier_id": null,
+            "net_rate_nuc": null, "commission_nuc": null, "metadata": metadata,
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_fulfillment_holders() {
+        let traveler = |index: i32, ptc: &str| -> Traveler {
+            serde_json::from_value(serde_json::json!({
+                "id": Uuid::new_v4(), "traveler_index": index, "ptc": ptc, "first_name": "Ana", "last_name": "Lim",
+                "date_of_birth": null, "gender": null, "traveler_did": null, "metadata": null,
+            })).unwrap()
+        };
+        let travelers = vec![traveler(0, "ADT"), traveler(1, "ADT"), traveler(2, "ADT")];
+        let flight = item("FLIGHT", 3, serde_json::json!({"flight_id": "f1", "origin": "SIN", "destination": "BKK", "price": 1}));
+
+        // Every passenger boards the segment on their own barcode
+        let boarding = fulfillment_holders(&flight, &travelers);
+        assert_eq!(boarding.len(), 3);
+        assert_eq!(boarding[2].traveler_index, Some(2));
+        assert_eq!(boarding[2].traveler_id, travelers[2].id);
+        assert_eq!(boarding[0].segment, Some(serde_json::json!({"flight_id": "f1", "origin": "SIN", "destination": "BKK"})));
+
+        // Before names are in, one per seat booked
+        let unnamed = fulfillment_holders(&flight, &[]);
+        assert_eq!(unnamed.iter().map(|h| h.traveler_index).collect::<Vec<_>>(), vec![Some(0), Some(1), Some(2)]);
+        assert!(unnamed.iter().all(|h| h.traveler_id.is_none()));
+
+        // Fares priced per passenger type board only the travelers of that type
+        let family = vec![traveler(0, "ADT"), traveler(1, "CHD"), traveler(2, "INF")];
+        let child_fare = item("FLIGHT", 1, serde_json::json!({"flight_id": "f1", "ptc": "CHD"}));
+        let boarding = fulfillment_holders(&child_fare, &family);
+        assert_eq!(boarding.iter().map(|h| (h.traveler_index, h.unit_index)).collect::<Vec<_>>(), vec![(Some(1), 0)]);
+
+        let mut bag = item("BAG", 1, serde_json::json!({}));
+        bag.traveler_index = Some(1);
+        assert_eq!(fulfillment_holders(&bag, &travelers), vec![FulfillmentHolder { traveler_id: travelers[1].id, traveler_index: Some(1), segment: None, unit_index: 0 }]);
+        let wifi = item("WIFI", 1, serde_json::json!({}));
+        assert_eq!(fulfillment_holders(&wifi, &travelers), vec![FulfillmentHolder::default()]);
+
+        // Three lounge passes, one already refunded: a record for each of the other two
+        let mut lounge = item("LOUNGE", 3, serde_json::json!({}));
+        (lounge.traveler_index, lounge.refunded_quantity) = (Some(0), 1);
+        let passes = fulfillment_holders(&lounge, &travelers);
+        assert_eq!(passes.iter().map(|h| (h.traveler_index, h.unit_index)).collect::<Vec<_>>(), vec![(Some(0), 0), (Some(0), 1)]);
+
+        assert_ne!(barcode_for(Uuid::nil(), Uuid::nil(), Some(0), 0), barcode_for(Uuid::nil(), Uuid::nil(), Some(1), 0));
+        assert_eq!(barcode_for(Uuid::nil(), Uuid::nil(), None, 2), format!("ALTIS-{}-{}-U2", Uuid::nil().simple(), Uuid::nil().simple()));
+    }
+}
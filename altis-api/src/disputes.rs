@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use altis_order::disputes::DisputeStatus;
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::Money;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::middleware::auth::AdminClaims;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct DisputeListQuery {
+    /// OPEN, EVIDENCE_SUBMITTED, WON or LOST; all disputes when left out
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDisputeRequest {
+    pub status: DisputeStatus,
+    pub note: Option<String>,
+}
+
+/// A dispute as the payment provider reports it
+#[derive(Debug)]
+pub(crate) struct ReportedDispute {
+    pub provider_dispute_id: String,
+    pub payment_intent_id: String,
+    pub amount_nuc: i64,
+    pub currency: String,
+    pub reason: Option<String>,
+    pub status: DisputeStatus,
+    pub evidence_due_by: Option<DateTime<Utc>>,
+}
+
+/// GET /v1/admin/finance/disputes
+/// Chargebacks, soonest evidence deadline first
+pub async fn list_disputes(
+    State(state): State<AppState>,
+    Query(query): Query<DisputeListQuery>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let status = match query.status.as_deref() {
+        Some(status) => Some(DisputeStatus::parse(status).ok_or(StatusCode::BAD_REQUEST)?.as_str()),
+        None => None,
+    };
+    let disputes = state.dispute_repo.list_disputes(status, query.limit.unwrap_or(100).clamp(1, 500)).await
+        .map_err(|e| {
+            tracing::error!("Failed to list disputes: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(disputes))
+}
+
+/// GET /v1/admin/finance/disputes/:id
+pub async fn get_dispute(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let dispute = state.dispute_repo.get_dispute(id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(dispute))
+}
+
+/// POST /v1/admin/finance/disputes/:id/status
+/// Record evidence sent to the provider or the network's ruling
+pub async fn update_dispute_status(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: Option<Extension<AdminClaims>>,
+    Json(req): Json<UpdateDisputeRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let actor = claims.map(|Extension(c)| c.sub).unwrap_or_else(|| "ADMIN".to_string());
+    let dispute = state.dispute_repo.get_dispute(id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    transition_dispute(&state, &dispute, req.status, &actor, req.note.as_deref()).await.map(Json)
+}
+
+/// Moves a dispute on and records it against its order. Losing books the
+/// chargeback; only the caller that makes the move posts it, so a webhook
+/// and a finance user closing the same dispute can't book it twice.
+async fn transition_dispute(
+    state: &AppState,
+    dispute: &serde_json::Value,
+    next: DisputeStatus,
+    actor: &str,
+    note: Option<&str>,
+) -> Result<serde_json::Value, StatusCode> {
+    let id = dispute["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let order_id = dispute["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let current = dispute["status"].as_str().and_then(DisputeStatus::parse).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    if current == next {
+        return Ok(dispute.clone());
+    }
+    if !current.can_transition_to(next) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let moved = state.dispute_repo.update_dispute_status(id, current.as_str(), next.as_str(), note).await
+        .map_err(|e| {
+            tracing::error!("Failed to update dispute {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !moved {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        &format!("DISPUTE_{}", next.as_str()),
+        Some(serde_json::json!({"dispute_id": id, "status": current})),
+        Some(serde_json::json!({"dispute_id": id, "status": next})),
+        actor,
+        note,
+    ).await;
+
+    if next == DisputeStatus::Lost {
+        let amount = Money::nuc(dispute["amount_nuc"].as_i64().unwrap_or(0));
+        let dispute_ref = dispute["provider_dispute_id"].as_str().unwrap_or_default();
+        crate::finance::post_journal(state, JournalTransaction::chargeback(order_id, amount, dispute_ref)).await;
+    }
+
+    state.dispute_repo.get_dispute(id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Records a dispute from a provider webhook against the order its payment
+/// was for, then brings its status up to date. Events the provider sends
+/// out of order are logged and dropped rather than retried forever.
+pub(crate) async fn record_reported_dispute(state: &AppState, reported: ReportedDispute) -> Result<(), StatusCode> {
+    let intent = state.payment_orchestrator.process_status_update(&reported.payment_intent_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to look up disputed payment {}: {:?}", reported.payment_intent_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if state.order_repo.get_order(intent.order_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?.is_none() {
+        tracing::warn!("Dispute {} is for payment {} of unknown order {}", reported.provider_dispute_id, intent.id, intent.order_id);
+        return Ok(());
+    }
+
+    let dispute = state.dispute_repo.record_dispute(&serde_json::json!({
+        "order_id": intent.order_id,
+        "provider": state.payment_orchestrator.provider(),
+        "provider_dispute_id": reported.provider_dispute_id,
+        "payment_intent_id": reported.payment_intent_id,
+        "amount_nuc": reported.amount_nuc,
+        "currency": reported.currency,
+        "reason": reported.reason,
+        "evidence_due_by": reported.evidence_due_by,
+    })).await.map_err(|e| {
+        tracing::error!("Failed to record dispute {}: {:?}", reported.provider_dispute_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if dispute["inserted"] == true {
+        let _ = state.order_repo.add_order_change(
+            intent.order_id,
+            "DISPUTE_OPENED",
+            None,
+            Some(serde_json::json!({"dispute_id": dispute["id"], "amount_nuc": reported.amount_nuc, "reason": reported.reason})),
+            "PSP",
+            Some("Cardholder disputed the payment"),
+        ).await;
+        tracing::warn!("Dispute {} opened on order {} for {} {}", reported.provider_dispute_id, intent.order_id, reported.amount_nuc, reported.currency);
+    }
+
+    match transition_dispute(state, &dispute, reported.status, "PSP", None).await {
+        Err(StatusCode::CONFLICT) => {
+            tracing::warn!("Ignoring {:?} for dispute {}, which is already {}", reported.status, reported.provider_dispute_id, dispute["status"]);
+            Ok(())
+        }
+        result => result.map(|_| ()),
+    }
+}
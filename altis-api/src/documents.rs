@@ -0,0 +1,187 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use altis_order::invoice::Invoice;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::authz::authorize_order;
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+/// Issues an invoice or credit note for an order. Failures are logged rather
+/// than surfaced: the customer-facing action has already happened, and the
+/// missing document shows up in the order's audit trail review.
+pub async fn issue_for_order(state: &AppState, order_id: Uuid, document_type: &str, amount_nuc: i64) {
+    match state.document_repo.issue_order_document(order_id, document_type, amount_nuc).await {
+        Ok(Some(document)) => {
+            let _ = state.order_repo.add_order_change(
+                order_id,
+                "DOCUMENT_ISSUED",
+                None,
+                Some(document),
+                "SYSTEM",
+                None,
+            ).await;
+        }
+        Ok(None) => {}
+        Err(e) => tracing::error!("Failed to issue {} for order {}: {:?}", document_type, order_id, e),
+    }
+}
+
+/// A rendered invoice: the breakdown shown as JSON and the PDF bytes
+pub struct RenderedInvoice {
+    pub invoice: Invoice,
+    pub sha256: String,
+    pub pdf: Vec<u8>,
+}
+
+/// Renders the order's invoice to PDF and stores it, or loads the stored copy.
+/// None when no invoice has been issued for the order.
+pub async fn render_invoice(state: &AppState, order_id: Uuid) -> Result<Option<RenderedInvoice>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(document) = state.document_repo.get_order_invoice(order_id).await? else {
+        return Ok(None);
+    };
+
+    // Re-rendering would drift from what the customer was sent, so a stored
+    // file is always served as-is
+    if let Some(file) = document.get("file").filter(|f| !f.is_null()) {
+        let key = file["blob_key"].as_str().unwrap_or_default();
+        let pdf = state.blob_store.get(key).await?.ok_or_else(|| format!("Invoice blob {} is missing", key))?;
+        return Ok(Some(RenderedInvoice {
+            invoice: serde_json::from_value(file["breakdown"].clone())?,
+            sha256: file["sha256"].as_str().unwrap_or_default().to_string(),
+            pdf,
+        }));
+    }
+
+    let document_id = document["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or("Invoice without id")?;
+    let document_number = document["document_number"].as_str().unwrap_or_default();
+    let issued_at = document["issued_at"].as_str()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .unwrap_or_else(chrono::Utc::now);
+
+    let order = state.order_repo.get_order(order_id).await?.ok_or("Order not found")?;
+    let order: altis_order::Order = serde_json::from_value(order)?;
+
+    let invoice = Invoice::from_order(&order, document_number, document["seller"].as_str().unwrap_or_default(), issued_at)?;
+    let pdf = invoice.render_pdf();
+    let sha256 = format!("{:x}", Sha256::digest(&pdf));
+
+    let key = format!("invoices/{}/{}.pdf", order_id, document_number);
+    state.blob_store.put(&key, pdf.clone(), "application/pdf").await?;
+    state.document_repo.save_document_file(document_id, &key, "application/pdf", &sha256, &serde_json::to_value(&invoice)?).await?;
+
+    Ok(Some(RenderedInvoice { invoice, sha256, pdf }))
+}
+
+/// Issues the invoice for a freshly paid order and renders it straight away,
+/// so the customer's receipt is ready before they ask for it.
+pub async fn invoice_paid_order(state: &AppState, order_id: Uuid, amount_nuc: i64) {
+    issue_for_order(state, order_id, "INVOICE", amount_nuc).await;
+    if let Err(e) = render_invoice(state, order_id).await {
+        tracing::error!("Failed to render invoice for order {}: {:?}", order_id, e);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvoiceResponse {
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    /// Checksum of the PDF, which is served from the same URL with `Accept: application/pdf`
+    pub pdf_sha256: String,
+}
+
+/// GET /v1/orders/:id/invoice
+/// The order's invoice as a JSON line-item breakdown, or as a PDF when the
+/// client accepts `application/pdf`
+pub async fn get_order_invoice(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    authorize_order(&state, &claims, order_id).await?;
+
+    let rendered = render_invoice(&state, order_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to render invoice for order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let wants_pdf = headers.get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/pdf"));
+
+    if wants_pdf {
+        let filename = format!("{}.pdf", rendered.invoice.document_number);
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (header::CONTENT_DISPOSITION, format!("inline; filename=\"{}\"", filename)),
+            ],
+            rendered.pdf,
+        ).into_response());
+    }
+
+    Ok(Json(InvoiceResponse { invoice: rendered.invoice, pdf_sha256: rendered.sha256 }).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocumentQuery {
+    pub document_type: Option<String>,
+    pub fiscal_year: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GapQuery {
+    pub document_type: String,
+    pub fiscal_year: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SequenceGapResponse {
+    pub airline_id: Uuid,
+    pub document_type: String,
+    pub fiscal_year: i32,
+    pub missing: Vec<i64>,
+}
+
+/// GET /v1/admin/airlines/:airline_id/documents
+pub async fn list_documents(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Query(query): Query<DocumentQuery>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let documents = state.document_repo.list_documents(airline_id, query.document_type.as_deref(), query.fiscal_year).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(documents))
+}
+
+/// GET /v1/admin/airlines/:airline_id/documents/gaps
+/// Sequence numbers allocated without a document (should always be empty)
+pub async fn get_sequence_gaps(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Query(query): Query<GapQuery>,
+) -> Result<Json<SequenceGapResponse>, StatusCode> {
+    let missing = state.document_repo.find_sequence_gaps(airline_id, &query.document_type, query.fiscal_year).await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    if !missing.is_empty() {
+        tracing::warn!("{} gap(s) in {} {} sequence for airline {}", missing.len(), query.document_type, query.fiscal_year, airline_id);
+    }
+
+    Ok(Json(SequenceGapResponse {
+        airline_id,
+        document_type: query.document_type,
+        fiscal_year: query.fiscal_year,
+        missing,
+    }))
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use uuid::Uuid;
+
+use altis_catalog::InventoryError;
+use altis_core::edifact::{
+    self, AvailabilityError, AvailabilityRequest, AvailabilityResponse, AvailableFlight, EdifactTransport, Envelope,
+};
+use altis_store::app_config::EdifactConfig;
+
+use crate::middleware::auth::Principal;
+use crate::state::AppState;
+
+const CONTENT_TYPE: &str = "application/edifact";
+
+/// Posts replies to the channel's configured endpoint
+pub struct HttpTransport {
+    client: reqwest::Client,
+    reply_urls: HashMap<String, String>,
+}
+
+impl HttpTransport {
+    pub fn new(config: &EdifactConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("EDIFACT HTTP client builds with static settings");
+        Self { client, reply_urls: config.reply_urls.clone() }
+    }
+}
+
+#[async_trait]
+impl EdifactTransport for HttpTransport {
+    async fn send(&self, channel: &str, interchange: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let url = self.reply_urls.get(channel).ok_or_else(|| format!("No EDIFACT reply URL for {}", channel))?;
+        self.client.post(url)
+            .header(header::CONTENT_TYPE, CONTENT_TYPE)
+            .body(interchange.to_string())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// The EDIFACT interface as configured: which airlines answer, and how
+/// replies leave
+pub struct EdifactGateway {
+    config: EdifactConfig,
+    /// None replies inline, in the HTTP response
+    transport: Option<Arc<dyn EdifactTransport>>,
+}
+
+impl EdifactGateway {
+    pub fn new(config: EdifactConfig) -> Self {
+        let transport: Option<Arc<dyn EdifactTransport>> = match config.transport.as_str() {
+            "http" => Some(Arc::new(HttpTransport::new(&config))),
+            _ => None,
+        };
+        Self { config, transport }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.config.airlines.is_empty()
+    }
+
+    pub fn serves(&self, carrier: &str) -> bool {
+        self.config.airlines.iter().any(|code| code.eq_ignore_ascii_case(carrier))
+    }
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/edifact/availability
+/// Answer a PAOREQ with a PAORES, for GDS partners still on EDIFACT
+pub async fn availability(
+    State(state): State<AppState>,
+    Extension(principal): Extension<Principal>,
+    body: String,
+) -> Result<Response, StatusCode> {
+    let gateway = &state.edifact;
+    if !gateway.is_enabled() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let Principal::Partner(channel) = principal else {
+        return Err(StatusCode::FORBIDDEN);
+    };
+
+    // Without an envelope there is nobody to address a reply to
+    let segments = edifact::parse(&body).map_err(|e| {
+        tracing::warn!("Unreadable EDIFACT interchange from {}: {}", channel, e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let envelope = Envelope::read(&segments, "PAOREQ").map_err(|e| {
+        tracing::warn!("Rejected EDIFACT interchange from {}: {}", channel, e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let response = match AvailabilityRequest::from_segments(&segments) {
+        Ok(request) if !gateway.serves(&request.carrier) => AvailabilityResponse::Error(AvailabilityError::CarrierNotOffered),
+        Ok(request) => AvailabilityResponse::Flights(available_flights(&state, &request).await?),
+        Err(e) => {
+            tracing::warn!("Invalid PAOREQ {} from {}: {}", envelope.control_ref, channel, e);
+            AvailabilityResponse::Error(AvailabilityError::InvalidRequest)
+        }
+    };
+    let reply = response.encode(&envelope.reply(&gateway.config.sender_id), chrono::Utc::now());
+
+    // Channels without a reply URL are answered inline even on the http transport
+    match &gateway.transport {
+        Some(transport) if gateway.config.reply_urls.contains_key(&channel) => {
+            transport.send(&channel, &reply).await.map_err(|e| {
+                tracing::error!("Failed to deliver PAORES {} to {}: {}", envelope.control_ref, channel, e);
+                StatusCode::BAD_GATEWAY
+            })?;
+            Ok(StatusCode::ACCEPTED.into_response())
+        }
+        _ => Ok(([(header::CONTENT_TYPE, CONTENT_TYPE)], reply).into_response()),
+    }
+}
+
+/// The carrier's catalog flights on the city pair and local departure date,
+/// earliest first. Seats come from live inventory, or the catalog count for
+/// flights it doesn't track.
+async fn available_flights(state: &AppState, request: &AvailabilityRequest) -> Result<Vec<AvailableFlight>, StatusCode> {
+    let Some(airline) = state.catalog_cache.airline(&request.carrier).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else {
+        return Ok(Vec::new());
+    };
+    let airline_id = airline["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let products = state.catalog_cache.products(airline_id).await.map_err(|e| {
+        tracing::error!("Failed to fetch products for airline {}: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut flights = Vec::new();
+    for product in products.iter() {
+        if product["product_type"].as_str() != Some("FLIGHT") || product["is_active"].as_bool() == Some(false) {
+            continue;
+        }
+        let metadata = &product["metadata"];
+        if metadata["origin"].as_str() != Some(&request.origin) || metadata["destination"].as_str() != Some(&request.destination) {
+            continue;
+        }
+        // Schedules are shown in local time, as the timestamps' offsets give it
+        let time = |field: &str| metadata[field].as_str()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.naive_local());
+        let (Some(departure), Some(arrival)) = (time("departure_time"), time("arrival_time")) else { continue };
+        if departure.date() != request.date {
+            continue;
+        }
+        let Some(product_id) = product["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+
+        let catalog_seats = metadata["available_seats"].as_i64().unwrap_or(0) as i32;
+        let seats = match state.inventory.get(product_id).await {
+            Ok(Some(item)) => item.available_quantity,
+            Ok(None) | Err(InventoryError::NotFound(_)) => catalog_seats,
+            Err(e) => {
+                tracing::warn!("Failed to read inventory for flight {}: {}", product_id, e);
+                catalog_seats
+            }
+        };
+        let product_code = product["product_code"].as_str().unwrap_or_default();
+        let flight_number = metadata["flight_number"].as_str()
+            .unwrap_or_else(|| product_code.strip_prefix(request.carrier.as_str()).unwrap_or(product_code));
+
+        flights.push(AvailableFlight {
+            carrier: request.carrier.clone(),
+            flight_number: flight_number.to_string(),
+            origin: request.origin.clone(),
+            destination: request.destination.clone(),
+            departure,
+            arrival,
+            booking_class: metadata["booking_class"].as_str().unwrap_or("Y").to_string(),
+            seats,
+            fare: product["base_price_nuc"].as_i64().map(|amount| (amount, "NUC".to_string())),
+        });
+    }
+
+    flights.sort_by_key(|flight| flight.departure);
+    Ok(flights)
+}
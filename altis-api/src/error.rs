@@ -3,6 +3,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
 
@@ -49,3 +50,65 @@ impl IntoResponse for AppError {
         (status, body).into_response()
     }
 }
+
+/// A request field that can't be served, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+}
+
+/// What handlers that explain their refusals return: a bare status, as
+/// everywhere else, or a 422 naming each field at fault
+#[derive(Debug)]
+pub enum ApiError {
+    Status(StatusCode),
+    Invalid(Vec<FieldError>),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Status(code) => *code,
+            ApiError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    /// One line for callers that can't carry the field list, e.g. gRPC
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::Status(code) => code.canonical_reason().unwrap_or_default().to_string(),
+            ApiError::Invalid(fields) => fields.iter()
+                .map(|f| format!("{}: {}", f.field, f.message))
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(code: StatusCode) -> Self {
+        ApiError::Status(code)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Status(code) => code.into_response(),
+            ApiError::Invalid(fields) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "error": "Validation failed",
+                    "fields": fields,
+                })),
+            ).into_response(),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::Write;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use altis_core::tenant::TenantContext;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::authz::authorize_tenant_order;
+use crate::middleware::auth::AdminClaims;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct EvidenceQuery {
+    /// Case reference or reason (e.g. chargeback id, legal hold ticket)
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    name: String,
+    sha256: String,
+    bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    order_id: Uuid,
+    generated_at: String,
+    generated_by: String,
+    reason: Option<String>,
+    files: Vec<ManifestEntry>,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// GET /v1/admin/orders/:id/evidence-bundle
+/// Zip of everything we know about an order, with a SHA-256 manifest
+pub async fn get_evidence_bundle(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    Query(query): Query<EvidenceQuery>,
+    Extension(tenant): Extension<TenantContext>,
+    claims: Option<Extension<AdminClaims>>,
+) -> Result<Response, StatusCode> {
+    let actor = claims.map(|Extension(c)| c.sub).unwrap_or_else(|| "ADMIN".to_string());
+
+    let order = authorize_tenant_order(&state, &tenant, order_id).await?;
+
+    let changes = state.order_repo.get_order_changes(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let fulfillment = state.order_repo.get_fulfillment_history(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let ledger = state.order_repo.get_order_ledger(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // The snapshot outlives the offer, which expires from the offer store
+    let snapshot = state.order_repo.get_order_snapshot(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let offer = match (&snapshot, order["offer_id"].as_str().and_then(|id| Uuid::parse_str(id).ok())) {
+        (Some(snapshot), _) => snapshot["offers"].as_array()
+            .and_then(|offers| offers.iter().find(|o| o["id"] == order["offer_id"]).cloned()),
+        (None, Some(offer_id)) => state.offer_repo.get_tenant_offer(&tenant, offer_id).await.ok().flatten(),
+        (None, None) => None,
+    };
+
+    let files = vec![
+        ("order.json", order.clone()),
+        ("pricing_trace.json", pricing_trace(&order, offer)),
+        ("payment_attempts.json", payment_attempts(&order, &changes)),
+        ("fulfillment_scans.json", serde_json::json!(fulfillment)),
+        ("communications.json", communications(&order, &fulfillment)),
+        ("ledger.json", serde_json::json!(ledger)),
+        ("audit_trail.json", serde_json::json!(changes)),
+        ("snapshot.json", serde_json::json!(snapshot)),
+    ];
+
+    let generated_at = chrono::Utc::now();
+    let bundle = build_zip(order_id, files, &actor, query.reason.clone(), generated_at)
+        .map_err(|e| {
+            tracing::error!("Failed to build evidence bundle for {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let bundle_sha256 = sha256_hex(&bundle);
+
+    let key = format!("evidence/{}/{}.zip", order_id, generated_at.format("%Y%m%dT%H%M%SZ"));
+    let uri = state.blob_store.put(&key, bundle.clone(), "application/zip").await
+        .map_err(|e| {
+            tracing::error!("Failed to store evidence bundle {}: {:?}", key, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Every export is recorded against the order, with who pulled it and why
+    state.order_repo.add_order_change(
+        order_id,
+        "EVIDENCE_EXPORTED",
+        None,
+        Some(serde_json::json!({ "blob": uri, "sha256": bundle_sha256 })),
+        &actor,
+        query.reason.as_deref(),
+    ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let filename = format!("evidence-{}.zip", order_id.simple());
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            (header::HeaderName::from_static("x-bundle-sha256"), bundle_sha256),
+        ],
+        bundle,
+    ).into_response())
+}
+
+fn build_zip(
+    order_id: Uuid,
+    files: Vec<(&str, serde_json::Value)>,
+    actor: &str,
+    reason: Option<String>,
+    generated_at: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut manifest = Manifest {
+        order_id,
+        generated_at: generated_at.to_rfc3339(),
+        generated_by: actor.to_string(),
+        reason,
+        files: Vec::new(),
+    };
+
+    for (name, content) in files {
+        let bytes = serde_json::to_vec_pretty(&content)?;
+        zip.start_file(name, options)?;
+        zip.write_all(&bytes)?;
+        manifest.files.push(ManifestEntry { name: name.to_string(), sha256: sha256_hex(&bytes), bytes: bytes.len() });
+    }
+
+    zip.start_file("manifest.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// What the customer was quoted and how each item's price splits between
+/// supplier and retailer.
+fn pricing_trace(order: &serde_json::Value, offer: Option<serde_json::Value>) -> serde_json::Value {
+    let items: Vec<serde_json::Value> = order["items"].as_array().into_iter().flatten().map(|item| {
+        serde_json::json!({
+            "item_id": item["id"],
+            "product_type": item["product_type"],
+            "product_code": item["product_code"],
+            "price_nuc": item["price_nuc"],
+            "quantity": item["quantity"],
+            "net_rate_nuc": item["net_rate_nuc"],
+            "commission_nuc": item["commission_nuc"],
+        })
+    }).collect();
+
+    serde_json::json!({
+        "offer": offer,
+        "items": items,
+        "total_nuc": order["total_nuc"],
+        "currency": order["currency"],
+    })
+}
+
+fn payment_attempts(order: &serde_json::Value, changes: &[serde_json::Value]) -> serde_json::Value {
+    let attempts: Vec<&serde_json::Value> = changes.iter()
+        .filter(|c| c["change_type"].as_str().is_some_and(|t| t.starts_with("PAYMENT")))
+        .collect();
+
+    serde_json::json!({
+        "payment_method": order["payment_method"],
+        "payment_reference": order["payment_reference"],
+        "attempts": attempts,
+    })
+}
+
+/// Messages sent to the customer: every fulfillment delivery and where it went.
+fn communications(order: &serde_json::Value, fulfillment: &[serde_json::Value]) -> serde_json::Value {
+    let deliveries: Vec<serde_json::Value> = fulfillment.iter()
+        .filter(|f| !f["delivered_at"].is_null() || !f["deliver_at"].is_null())
+        .map(|f| serde_json::json!({
+            "fulfillment_id": f["id"],
+            "order_item_id": f["order_item_id"],
+            "channel": f["delivery_method"],
+            "scheduled_for": f["deliver_at"],
+            "delivered_at": f["delivered_at"],
+            "attempts": f["delivery_attempts"],
+            "last_error": f["last_delivery_error"],
+        }))
+        .collect();
+
+    serde_json::json!({
+        "contact": order["contact_info"],
+        "deliveries": deliveries,
+    })
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use altis_offer::experiments::Experiment;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::middleware::auth::AdminClaims;
+use crate::state::AppState;
+
+/// Definitions are re-read this often so experiments started or ended
+/// through another instance take effect here too.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+pub struct CreateExperimentRequest {
+    pub name: String,
+    /// Registered ranking strategy served to the treatment, e.g. "ml"
+    pub strategy: String,
+    /// Share of subjects (0..=1) in the treatment
+    pub traffic_percentage: f64,
+    /// Defaults to now
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// GET /v1/admin/experiments
+pub async fn list_experiments(State(state): State<AppState>) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let experiments = state.experiment_repo.list_experiments().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(experiments))
+}
+
+/// POST /v1/admin/experiments
+/// Define a ranking experiment; it takes effect at `starts_at`
+pub async fn create_experiment(
+    State(state): State<AppState>,
+    claims: Option<Extension<AdminClaims>>,
+    Json(req): Json<CreateExperimentRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    let starts_at = req.starts_at.unwrap_or_else(Utc::now);
+    if req.name.trim().is_empty()
+        || !state.ranker.has_strategy(&req.strategy)
+        || !(0.0..=1.0).contains(&req.traffic_percentage)
+        || req.ends_at.is_some_and(|end| end <= starts_at)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let existing = state.experiment_repo.list_experiments().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if existing.iter().any(|e| e["name"].as_str() == Some(req.name.trim())) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let actor = claims.map(|Extension(c)| c.sub).unwrap_or_else(|| "ADMIN".to_string());
+    let experiment = state.experiment_repo.create_experiment(&serde_json::json!({
+        "name": req.name.trim(),
+        "strategy": req.strategy,
+        "traffic_percentage": req.traffic_percentage,
+        "starts_at": starts_at,
+        "ends_at": req.ends_at,
+    }), &actor).await.map_err(|e| {
+        tracing::error!("Failed to create experiment {}: {:?}", req.name, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    tracing::info!("Experiment {} created by {}: {:.1}% on {}", req.name, actor, req.traffic_percentage * 100.0, req.strategy);
+    reload(&state).await;
+    Ok((StatusCode::CREATED, Json(experiment)))
+}
+
+/// POST /v1/admin/experiments/:id/end
+/// Stop an experiment now; its treatment subjects return to control
+pub async fn end_experiment(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let experiment = state.experiment_repo.end_experiment(id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    tracing::info!("Experiment {} ended", experiment["name"]);
+    reload(&state).await;
+    Ok(Json(experiment))
+}
+
+/// Loads the definitions into the ranker; returns how many there are
+pub async fn load_experiments(state: &AppState) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let experiments = state.experiment_repo.list_experiments().await?
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<Vec<Experiment>, _>>()?;
+
+    let count = experiments.len();
+    state.ranker.set_experiments(experiments);
+    Ok(count)
+}
+
+async fn reload(state: &AppState) {
+    if let Err(e) = load_experiments(state).await {
+        tracing::error!("Failed to reload experiments: {}", e);
+    }
+}
+
+/// Keeps the ranker's experiments in step with the database. The initial
+/// load is part of the startup warmup.
+pub async fn run_experiment_refresher(state: AppState) {
+    let start = tokio::time::Instant::now() + REFRESH_INTERVAL;
+    let mut interval = tokio::time::interval_at(start, REFRESH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        reload(&state).await;
+    }
+}
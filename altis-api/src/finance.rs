@@ -1,10 +1,21 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
+use altis_core::tenant::TenantContext;
+use altis_order::ledger::{JournalTransaction, LedgerError};
+use altis_shared::money::{self, Money};
+use altis_order::models::LedgerEntry;
+use altis_order::settlement::{BatchStatus, HotFile, SettlementAdaptor, SettlementBatch};
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
+use crate::authz::authorize_tenant_order;
 use crate::state::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,23 +28,50 @@ pub struct LedgerResponse {
 pub struct SettlementReportResponse {
     pub airline_id: Uuid,
     pub report_date: String,
+    pub from: String,
+    pub to: String,
+    pub group_by: String,
     pub metrics: SettlementMetrics,
+    pub groups: Vec<SettlementGroup>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct SettlementMetrics {
-    pub total_earned_nuc: i32,
-    pub total_unearned_nuc: i32,
-    pub total_payable_nuc: i32,    // Amount owed to suppliers
-    pub total_commission_nuc: i32, // Amount kept as retailer
-    pub processed_items: i32,
+    pub total_earned_nuc: i64,
+    pub total_unearned_nuc: i64,
+    pub total_payable_nuc: i64,    // Amount owed to suppliers
+    pub total_commission_nuc: i64, // Amount kept as retailer
+    pub total_refunded_nuc: i64,
+    /// Tax collected on the revenue earned in the period
+    pub total_tax_nuc: i64,
+    /// Part of the earned revenue kept from units never used
+    #[serde(default)]
+    pub total_breakage_nuc: i64,
+    pub processed_items: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementGroup {
+    pub group: String,
+    pub earned_nuc: i64,
+    pub unearned_nuc: i64,
+    pub payable_nuc: i64,
+    pub commission_nuc: i64,
+    pub refunded_nuc: i64,
+    #[serde(default)]
+    pub tax_nuc: i64,
+    #[serde(default)]
+    pub breakage_nuc: i64,
+    pub processed_items: i64,
 }
 
 /// GET /v1/admin/finance/orders/:id/ledger
 pub async fn get_order_ledger(
     State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
     Path(order_id): Path<Uuid>,
 ) -> Result<Json<LedgerResponse>, StatusCode> {
+    authorize_tenant_order(&state, &tenant, order_id).await?;
     let entries = state.order_repo.get_order_ledger(order_id).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -43,82 +81,731 @@ pub async fn get_order_ledger(
     }))
 }
 
-/// GET /v1/admin/finance/airlines/:id/settlement
+#[derive(Debug, Serialize)]
+pub struct ItemProrationResponse {
+    pub item_id: Uuid,
+    pub name: String,
+    #[serde(rename = "price_nuc", serialize_with = "money::nuc::serialize")]
+    pub price: Money,
+    pub schedule: Vec<altis_order::models::LedgerEntry>,
+    pub interline_splits: Vec<altis_order::finance::InterlineSplit>,
+}
+
+/// GET /v1/admin/finance/orders/:id/proration
+/// Segment-level revenue schedule and interline splits for each flight item
+pub async fn get_order_proration(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<Vec<ItemProrationResponse>>, StatusCode> {
+    let order_json = authorize_tenant_order(&state, &tenant, order_id).await?;
+
+    let order: altis_order::Order = serde_json::from_value(order_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let financial_mgr = altis_order::finance::FinancialManager::new();
+    let items = order.items.iter()
+        .filter(|item| item.product_type.eq_ignore_ascii_case("flight"))
+        .map(|item| ItemProrationResponse {
+            item_id: item.id,
+            name: item.name.clone(),
+            price: item.price,
+            schedule: financial_mgr.segment_revenue_schedule(&order, item.id),
+            interline_splits: financial_mgr.interline_splits(&order, item.id),
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SettlementReportQuery {
+    /// Inclusive start; defaults to 30 days before `to`
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    /// Exclusive end; defaults to now
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// `product_type`, `day` or `none`
+    pub group_by: Option<String>,
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+/// GET /v1/admin/finance/airlines/:airline_id/settlement
+/// Earned/unearned, breakage and payable/commission totals over a date range
 pub async fn get_airline_settlement(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(airline_id): Path<Uuid>,
-) -> Result<Json<SettlementReportResponse>, StatusCode> {
-    // 1. Fetch all orders for this airline (Mock filtering or repo query)
-    // For now, we'll fetch a sample or just return a mock based on the logic
-    
-    // In a real repo, we'd have state.order_repo.get_airline_summary(airline_id)
-    
+    Query(query): Query<SettlementReportQuery>,
+) -> Result<Response, StatusCode> {
+    let to = query.to.unwrap_or_else(chrono::Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(30));
+    if from >= to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let group_by = query.group_by.as_deref().unwrap_or("none");
+    if !matches!(group_by, "none" | "product_type" | "day") {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let rows = state.settlement_repo.settlement_report(airline_id, from, to, group_by).await
+        .map_err(|e| {
+            tracing::error!("Settlement report failed for airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let groups: Vec<SettlementGroup> = rows.into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let metrics = groups.iter().fold(SettlementMetrics::default(), |mut totals, g| {
+        totals.total_earned_nuc += g.earned_nuc;
+        totals.total_unearned_nuc += g.unearned_nuc;
+        totals.total_payable_nuc += g.payable_nuc;
+        totals.total_commission_nuc += g.commission_nuc;
+        totals.total_refunded_nuc += g.refunded_nuc;
+        totals.total_tax_nuc += g.tax_nuc;
+        totals.total_breakage_nuc += g.breakage_nuc;
+        totals.processed_items += g.processed_items;
+        totals
+    });
+
+    if query.format.as_deref() == Some("csv") {
+        let filename = format!("settlement-{}-{}-{}.csv", airline_id.simple(), from.format("%Y%m%d"), to.format("%Y%m%d"));
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            ],
+            settlement_csv(&groups),
+        ).into_response());
+    }
+
     Ok(Json(SettlementReportResponse {
         airline_id,
         report_date: chrono::Utc::now().to_rfc3339(),
-        metrics: SettlementMetrics {
-            total_earned_nuc: 125000,
-            total_unearned_nuc: 45000,
-            total_payable_nuc: 30000,
-            total_commission_nuc: 5000,
-            processed_items: 128,
-        },
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        group_by: group_by.to_string(),
+        metrics,
+        groups,
+    }).into_response())
+}
+
+fn settlement_csv(groups: &[SettlementGroup]) -> String {
+    let mut csv = String::from("group,earned_nuc,unearned_nuc,payable_nuc,commission_nuc,refunded_nuc,tax_nuc,breakage_nuc,processed_items\n");
+    for g in groups {
+        csv.push_str(&format!(
+            "\"{}\",{},{},{},{},{},{},{},{}\n",
+            g.group.replace('"', "\"\""),
+            g.earned_nuc, g.unearned_nuc, g.payable_nuc, g.commission_nuc, g.refunded_nuc, g.tax_nuc, g.breakage_nuc, g.processed_items,
+        ));
+    }
+    csv
+}
+
+// ============================================================================
+// Interline Settlement
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct InterlineReportQuery {
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// Limit to one operating carrier
+    pub carrier_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InterlineReportResponse {
+    pub from: String,
+    pub to: String,
+    pub total_payable_nuc: i64,
+    pub total_commission_nuc: i64,
+    pub carriers: Vec<serde_json::Value>,
+}
+
+/// GET /v1/admin/finance/interline
+/// Amounts owed to operating carriers and commissions kept, per carrier pair
+pub async fn get_interline_report(
+    State(state): State<AppState>,
+    Query(query): Query<InterlineReportQuery>,
+) -> Result<Json<InterlineReportResponse>, StatusCode> {
+    let to = query.to.unwrap_or_else(chrono::Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(30));
+    if from >= to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let carriers = state.settlement_repo.interline_report(from, to, query.carrier_id).await
+        .map_err(|e| {
+            tracing::error!("Interline report failed: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let total = |field: &str| carriers.iter().filter_map(|c| c[field].as_i64()).sum();
+    Ok(Json(InterlineReportResponse {
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        total_payable_nuc: total("payable_nuc"),
+        total_commission_nuc: total("commission_nuc"),
+        carriers,
     }))
 }
 
-/// GET /v1/admin/finance/airlines/:id/export/swo
-pub async fn export_swo(
-    State(_state): State<AppState>,
-    Path(_airline_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // 1. Fetch relevant ledger entries (Mocked for now)
-    // 2. Use altis_order::settlement::IataSwoAdaptor to adapt
-    
-    let order_mock = altis_order::Order::new("test@example.com".to_string());
-    let ledger_mock = vec![altis_order::models::LedgerEntry {
-        id: Uuid::new_v4(),
-        order_id: order_mock.id,
-        order_item_id: Uuid::new_v4(),
-        transaction_type: "REVENUE_RECOGNITION".to_string(),
-        amount_nuc: 5000,
-        currency: "NUC".to_string(),
-        description: Some("Settlement Export Test".to_string()),
-        created_at: chrono::Utc::now(),
-    }];
-
-    let adaptor = altis_order::settlement::IataSwoAdaptor;
-    let payload = altis_order::settlement::SettlementAdaptor::adapt(&adaptor, &order_mock, ledger_mock).await
+// ============================================================================
+// Double-entry Journal
+// ============================================================================
+
+/// Records a journal transaction. Unbalanced transactions are a bug in the
+/// caller and are refused here before the database trigger sees them.
+pub async fn post_journal(state: &AppState, transaction: Result<JournalTransaction, LedgerError>) {
+    let transaction = match transaction {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            tracing::error!("Could not build journal transaction: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = transaction.validate() {
+        tracing::error!("Refusing {} journal transaction for order {}: {}", transaction.kind, transaction.order_id, e);
+        return;
+    }
+    let Ok(value) = serde_json::to_value(&transaction) else { return };
+    if let Err(e) = state.ledger_repo.post_transaction(&value).await {
+        tracing::error!("Failed to post {} journal transaction for order {}: {:?}", transaction.kind, transaction.order_id, e);
+    }
+}
+
+/// Units of the item consumed so far, from the order's stored JSON
+pub fn consumed_units(order: &serde_json::Value, item_id: Uuid) -> i32 {
+    let item_id = item_id.to_string();
+    order["fulfillment"].as_array()
+        .map(|records| records.iter()
+            .filter(|f| f["order_item_id"].as_str() == Some(item_id.as_str()) && !f["consumed_at"].is_null())
+            .count() as i32)
+        .unwrap_or(0)
+}
+
+/// Books one recognized unit: the ledger entry (with its tax, for settlement
+/// reporting), the journal, what a partner carrier is owed, and the
+/// settlement event
+pub(crate) async fn post_recognition(state: &AppState, order: &altis_order::Order, entry: &LedgerEntry) {
+    let item_id = entry.order_item_id;
+    if let Ok(value) = serde_json::to_value(entry) {
+        if let Err(e) = state.order_repo.add_ledger_entries(&[value]).await {
+            tracing::error!("Failed to record revenue recognition for item {}: {:?}", item_id, e);
+        }
+    }
+
+    let journal = if entry.transaction_type == "BREAKAGE" {
+        JournalTransaction::breakage(entry.order_id, item_id, entry.amount)
+    } else {
+        JournalTransaction::revenue_recognition(entry.order_id, item_id, entry.amount)
+    };
+    post_journal(state, journal).await;
+
+    // Partner-operated items: post what is owed to the operating carrier
+    let interline = altis_order::interline::recognition_postings(order, item_id);
+    for payable in interline.iter().filter(|p| p.transaction_type == altis_order::interline::INTERLINE_PAYABLE) {
+        if let Some(carrier_id) = payable.counterparty_id {
+            post_journal(state, JournalTransaction::carrier_payable(payable.order_id, item_id, carrier_id, payable.amount)).await;
+        }
+    }
+    let postings: Vec<serde_json::Value> = interline
+        .iter()
+        .filter_map(|p| serde_json::to_value(p).ok())
+        .collect();
+    if !postings.is_empty() {
+        if let Err(e) = state.order_repo.add_ledger_entries(&postings).await {
+            tracing::error!("Failed to post interline settlement for item {}: {:?}", item_id, e);
+        }
+    }
+
+    if let Ok(amount_nuc) = entry.amount.to_i32() {
+        let _ = state.telemetry.log_settlement(altis_shared::models::events::SettlementEvent {
+            order_id: order.id,
+            amount_nuc,
+            currency: order.currency.clone(),
+            event_type: entry.transaction_type.clone(),
+            timestamp: chrono::Utc::now().timestamp(),
+        }).await;
+    }
+}
+
+/// Taxes included in an order's total, from its stored JSON. An item's
+/// refunded units took their share of its tax with them.
+pub fn order_tax(order: &serde_json::Value) -> Money {
+    let held_tax = |item: &serde_json::Value| -> Option<i64> {
+        let units = item["quantity"].as_i64().unwrap_or(1).max(1);
+        let held = (units - item["refunded_quantity"].as_i64().unwrap_or(0)).max(0);
+        let shares = Money::nuc(item["tax_nuc"].as_i64()?).split(units as usize).ok()?;
+        Some(shares.iter().take(held as usize).map(Money::minor_units).sum())
+    };
+    Money::nuc(order["items"].as_array()
+        .map(|items| items.iter().filter_map(held_tax).sum())
+        .unwrap_or(0))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrialBalanceQuery {
+    pub as_of: Option<chrono::DateTime<chrono::Utc>>,
+    pub airline_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrialBalanceResponse {
+    pub as_of: String,
+    pub airline_id: Option<Uuid>,
+    pub accounts: Vec<serde_json::Value>,
+    pub total_debit_nuc: i64,
+    pub total_credit_nuc: i64,
+    pub balanced: bool,
+}
+
+/// GET /v1/admin/finance/trial-balance
+/// Debit and credit totals per account; debits must equal credits
+pub async fn get_trial_balance(
+    State(state): State<AppState>,
+    Query(query): Query<TrialBalanceQuery>,
+) -> Result<Json<TrialBalanceResponse>, StatusCode> {
+    let as_of = query.as_of.unwrap_or_else(chrono::Utc::now);
+    let accounts = state.ledger_repo.trial_balance(as_of, query.airline_id).await
         .map_err(|e| {
-            tracing::error!("Swo Adaptor failed: {:?}", e);
+            tracing::error!("Trial balance failed: {:?}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    Ok(Json(payload))
+    let total = |field: &str| accounts.iter().filter_map(|a| a[field].as_i64()).sum::<i64>();
+    let (total_debit_nuc, total_credit_nuc) = (total("debit_nuc"), total("credit_nuc"));
+    if total_debit_nuc != total_credit_nuc {
+        tracing::error!("Trial balance out of balance as of {}: debits {} credits {}", as_of, total_debit_nuc, total_credit_nuc);
+    }
+
+    Ok(Json(TrialBalanceResponse {
+        as_of: as_of.to_rfc3339(),
+        airline_id: query.airline_id,
+        accounts,
+        total_debit_nuc,
+        total_credit_nuc,
+        balanced: total_debit_nuc == total_credit_nuc,
+    }))
+}
+
+/// GET /v1/admin/finance/orders/:id/journal
+pub async fn get_order_journal(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<LedgerResponse>, StatusCode> {
+    authorize_tenant_order(&state, &tenant, order_id).await?;
+    let entries = state.ledger_repo.get_order_journal(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(LedgerResponse {
+        order_id,
+        entries,
+    }))
+}
+
+// ============================================================================
+// Settlement Batches
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct RunBatchesQuery {
+    /// Entries created before this instant are swept; defaults to today 00:00 UTC
+    pub period_end: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-/// GET /v1/admin/finance/airlines/:id/export/legacy
-pub async fn export_legacy(
-    State(_state): State<AppState>,
-    Path(_airline_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let order_mock = altis_order::Order::new("test@example.com".to_string());
-    let ledger_mock = vec![altis_order::models::LedgerEntry {
-        id: Uuid::new_v4(),
-        order_id: order_mock.id,
-        order_item_id: Uuid::new_v4(),
-        transaction_type: "REVENUE_RECOGNITION".to_string(),
-        amount_nuc: 5000,
-        currency: "NUC".to_string(),
-        description: Some("Legacy Export Test".to_string()),
-        created_at: chrono::Utc::now(),
-    }];
-
-    let adaptor = altis_order::settlement::LegacyHotAdaptor;
-    let payload = altis_order::settlement::SettlementAdaptor::adapt(&adaptor, &order_mock, ledger_mock).await
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Batch to export; defaults to the airline's most recent batch
+    pub batch_id: Option<Uuid>,
+}
+
+/// POST /v1/admin/finance/settlement/batches
+/// Sweep unbatched ledger entries into per-airline DRAFT batches now
+pub async fn run_settlement_batches(
+    State(state): State<AppState>,
+    Query(query): Query<RunBatchesQuery>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let period_end = query.period_end.unwrap_or_else(start_of_today);
+    let batches = state.settlement_repo.create_batches(period_end).await
         .map_err(|e| {
-            tracing::error!("Legacy Adaptor failed: {:?}", e);
+            tracing::error!("Settlement batch run failed: {:?}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    Ok(Json(payload))
+    Ok(Json(batches))
+}
+
+/// GET /v1/admin/finance/airlines/:airline_id/settlement/batches
+pub async fn list_settlement_batches(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let batches = state.settlement_repo.list_batches(airline_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(batches))
+}
+
+/// GET /v1/admin/finance/settlement/batches/:id
+pub async fn get_settlement_batch(
+    State(state): State<AppState>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let batch = state.settlement_repo.get_batch(batch_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(batch))
+}
+
+/// POST /v1/admin/finance/settlement/batches/:id/submit
+pub async fn submit_settlement_batch(
+    State(state): State<AppState>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    transition_batch(&state, batch_id, BatchStatus::Submitted).await
+}
+
+/// POST /v1/admin/finance/settlement/batches/:id/confirm
+pub async fn confirm_settlement_batch(
+    State(state): State<AppState>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    transition_batch(&state, batch_id, BatchStatus::Confirmed).await
+}
+
+async fn transition_batch(state: &AppState, batch_id: Uuid, next: BatchStatus) -> Result<Json<serde_json::Value>, StatusCode> {
+    let batch = load_batch(state, batch_id).await?;
+    if !batch.status.can_transition_to(next) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // Compare-and-set: a concurrent transition makes this one a conflict
+    let moved = state.settlement_repo.update_batch_status(batch_id, batch.status.as_str(), next.as_str()).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !moved {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let updated = state.settlement_repo.get_batch(batch_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(updated))
+}
+
+async fn load_batch(state: &AppState, batch_id: Uuid) -> Result<SettlementBatch, StatusCode> {
+    let batch = state.settlement_repo.get_batch(batch_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    serde_json::from_value(batch).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Resolves the batch to export, checking it belongs to the airline.
+async fn export_batch(state: &AppState, airline_id: Uuid, batch_id: Option<Uuid>) -> Result<SettlementBatch, StatusCode> {
+    let batch = match batch_id {
+        Some(id) => load_batch(state, id).await?,
+        None => {
+            let latest = state.settlement_repo.list_batches(airline_id).await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .into_iter()
+                .next()
+                .ok_or(StatusCode::NOT_FOUND)?;
+            serde_json::from_value(latest).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        }
+    };
+
+    if batch.airline_id != airline_id {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(batch)
+}
+
+/// Batch entries grouped per order (entries come back ordered by order).
+async fn entries_by_order(state: &AppState, batch_id: Uuid) -> Result<Vec<(Uuid, Vec<LedgerEntry>)>, StatusCode> {
+    let entries = state.settlement_repo.get_batch_entries(batch_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut grouped: Vec<(Uuid, Vec<LedgerEntry>)> = Vec::new();
+    for value in entries {
+        let entry: LedgerEntry = serde_json::from_value(value).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        match grouped.last_mut() {
+            Some((order_id, group)) if *order_id == entry.order_id => group.push(entry),
+            _ => grouped.push((entry.order_id, vec![entry])),
+        }
+    }
+    Ok(grouped)
+}
+
+fn file_response(content_type: &'static str, filename: String, rx: mpsc::Receiver<String>) -> Response {
+    let body = Body::from_stream(ReceiverStream::new(rx).map(Ok::<_, std::convert::Infallible>));
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    ).into_response()
+}
+
+/// GET /v1/admin/finance/airlines/:airline_id/export/swo
+/// Stream a settlement batch as an IATA SwO document
+pub async fn export_swo(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, StatusCode> {
+    let batch = export_batch(&state, airline_id, query.batch_id).await?;
+    let grouped = entries_by_order(&state, batch.id).await?;
+    let header_json = serde_json::to_string(&batch).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        let _ = tx.send(format!("{{\"@context\":\"https://iata.org/swo/v1\",\"batch\":{},\"settlementData\":[", header_json)).await;
+
+        let adaptor = altis_order::settlement::IataSwoAdaptor;
+        for (i, (order_id, ledger)) in grouped.into_iter().enumerate() {
+            let Some(order) = load_order(&state, order_id).await else { continue };
+            match SettlementAdaptor::adapt(&adaptor, &order, ledger).await {
+                Ok(adapted) => {
+                    let separator = if i == 0 { "" } else { "," };
+                    if tx.send(format!("{}{}", separator, adapted["settlementData"])).await.is_err() {
+                        return; // Client went away
+                    }
+                }
+                Err(e) => tracing::error!("SwO adaptor failed for order {}: {:?}", order_id, e),
+            }
+        }
+
+        let _ = tx.send("]}".to_string()).await;
+    });
+
+    Ok(file_response("application/ld+json", format!("swo-{}.json", batch.id.simple()), rx))
+}
+
+/// GET /v1/admin/finance/airlines/:airline_id/export/legacy
+/// Stream a settlement batch as a fixed-width HOT file
+pub async fn export_legacy(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, StatusCode> {
+    let batch = export_batch(&state, airline_id, query.batch_id).await?;
+    let grouped = entries_by_order(&state, batch.id).await?;
+
+    let (tx, rx) = mpsc::channel(64);
+    let filename = format!("hot-{}-{}.txt", batch.period_end.format("%Y%m%d"), batch.id.simple());
+    tokio::spawn(async move {
+        let _ = tx.send(HotFile::header(&batch)).await;
+
+        let mut count = 0;
+        let mut total: i64 = 0;
+        for (order_id, ledger) in grouped {
+            for entry in ledger {
+                count += 1;
+                total += entry.amount.minor_units();
+                if tx.send(HotFile::record(count, order_id, &entry)).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        let _ = tx.send(HotFile::trailer(count, total)).await;
+    });
+
+    Ok(file_response("text/plain; charset=utf-8", filename, rx))
+}
+
+async fn load_order(state: &AppState, order_id: Uuid) -> Option<altis_order::Order> {
+    let order_json = state.order_repo.get_order(order_id).await.ok()??;
+    serde_json::from_value(order_json)
+        .map_err(|e| tracing::error!("Unreadable order {} in settlement batch: {:?}", order_id, e))
+        .ok()
+}
+
+// ============================================================================
+// Nightly Batch Job
+// ============================================================================
+
+fn start_of_today() -> chrono::DateTime<chrono::Utc> {
+    chrono::Utc::now().date_naive().and_time(chrono::NaiveTime::MIN).and_utc()
+}
+
+/// Sweeps everything up to midnight UTC into batches once a day at
+/// `batch_hour_utc`. Runs are idempotent: entries already batched are skipped.
+pub async fn run_settlement_scheduler(state: AppState, config: altis_store::app_config::SettlementConfig) {
+    loop {
+        let now = chrono::Utc::now();
+        let today_run = start_of_today() + chrono::Duration::hours(config.batch_hour_utc.min(23) as i64);
+        let next_run = if today_run > now { today_run } else { today_run + chrono::Duration::days(1) };
+
+        tokio::time::sleep((next_run - now).to_std().unwrap_or_default()).await;
+
+        match state.settlement_repo.create_batches(start_of_today()).await {
+            Ok(batches) => tracing::info!("Nightly settlement created {} batch(es)", batches.len()),
+            Err(e) => tracing::error!("Nightly settlement run failed: {:?}", e),
+        }
+    }
+}
+
+// ============================================================================
+// Scheduled Revenue Recognition
+// ============================================================================
+
+/// Claimed items are retried after this long if the worker dies mid-post.
+const RECOGNITION_LEASE_SECONDS: i64 = 300;
+
+/// Background loop earning the revenue of items whose recognition policy
+/// fires on time rather than a scan. Insurance is earned at cover start.
+/// Ancillaries still unused at departure expire, and the airline's breakage
+/// treatment either keeps their revenue or refunds them.
+pub async fn run_revenue_recognition_worker(state: AppState, config: altis_store::app_config::RevenueRecognitionConfig) {
+    if !config.enabled {
+        return;
+    }
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(config.poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let due = match state.order_repo.claim_due_recognitions(config.batch_size, RECOGNITION_LEASE_SECONDS).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to claim items due for revenue recognition: {:?}", e);
+                continue;
+            }
+        };
+        for item in due {
+            let parse = |field: &str| item[field].as_str().and_then(|id| Uuid::parse_str(id).ok());
+            let (Some(item_id), Some(order_id)) = (parse("id"), parse("order_id")) else { continue };
+            match recognize_scheduled(&state, &config, order_id, item_id).await {
+                Ok(Some(Breakage::Recognized(units))) => tracing::info!("Recognized {} unused unit(s) of item {} as breakage", units, item_id),
+                Ok(Some(Breakage::Refunded(units))) => tracing::info!("Refunded {} unused unit(s) of item {} after departure", units, item_id),
+                Ok(None) => {}
+                Err(e) => tracing::error!("Failed to recognize revenue of item {}: {:?}", item_id, e),
+            }
+        }
+    }
+}
+
+/// Units of an item nobody used by the time their service was over
+#[derive(Debug)]
+enum Breakage {
+    Recognized(i32),
+    Refunded(i32),
+}
+
+/// The airline's BREAKAGE rule treatment (`RECOGNIZE` or `REFUND`), or the configured default
+async fn breakage_treatment(state: &AppState, config: &altis_store::app_config::RevenueRecognitionConfig, airline_id: Option<Uuid>) -> String {
+    let Some(airline_id) = airline_id else { return config.breakage.clone() };
+    match state.catalog_repo.get_business_rule(airline_id, "BREAKAGE").await {
+        Ok(Some(rule)) => rule["treatment"].as_str()
+            .map(str::to_ascii_uppercase)
+            .filter(|treatment| altis_store::app_config::BREAKAGE_TREATMENTS.contains(&treatment.as_str()))
+            .unwrap_or_else(|| config.breakage.clone()),
+        Ok(None) => config.breakage.clone(),
+        Err(e) => {
+            tracing::warn!("Failed to load breakage rule for airline {}, using the default: {:?}", airline_id, e);
+            config.breakage.clone()
+        }
+    }
+}
+
+/// Earns what no scan has of an item due. For an ancillary those units are
+/// breakage: their barcodes expire first, so no scan lands while they're
+/// counted.
+async fn recognize_scheduled(
+    state: &AppState,
+    config: &altis_store::app_config::RevenueRecognitionConfig,
+    order_id: Uuid,
+    item_id: Uuid,
+) -> Result<Option<Breakage>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(order_json) = state.order_repo.get_order(order_id).await? else { return Ok(None) };
+    let order: altis_order::Order = serde_json::from_value(order_json.clone())?;
+    let Some(item) = order.items.iter().find(|i| i.id == item_id) else { return Ok(None) };
+    let policy = altis_catalog::RecognitionPolicy::for_item(&item.product_type, &item.metadata);
+    let breakage = matches!(policy, altis_catalog::RecognitionPolicy::AtDeparture { .. });
+
+    let order_json = if breakage {
+        state.order_repo.expire_unconsumed_fulfillment(item_id).await?;
+        state.order_repo.get_order(order_id).await?.unwrap_or(order_json)
+    } else {
+        order_json
+    };
+    let consumed = consumed_units(&order_json, item_id);
+
+    // Taken off the schedule first, so a scan landing meanwhile finds it EARNED
+    if !state.order_repo.complete_scheduled_recognition(item_id).await? {
+        return Ok(None);
+    }
+    if !breakage {
+        for entry in unearned_units(&order, item_id, consumed, false) {
+            post_recognition(state, &order, &entry).await;
+        }
+        return Ok(None);
+    }
+
+    let unused = item.active_units() - consumed;
+    if unused <= 0 {
+        return Ok(None);
+    }
+    let airline_id = order_json["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok());
+    if breakage_treatment(state, config, airline_id).await == "REFUND" {
+        crate::orders::refund_units(state, &order_json, item_id, unused, "Unused at departure", "SYSTEM").await
+            .map_err(|status| format!("refund of unused units failed with {}", status))?;
+        return Ok(Some(Breakage::Refunded(unused)));
+    }
+    let entries = unearned_units(&order, item_id, consumed, true);
+    for entry in &entries {
+        post_recognition(state, &order, entry).await;
+    }
+    Ok(Some(Breakage::Recognized(entries.len() as i32)))
+}
+
+/// One entry per held unit from `consumed` on, as breakage or plain
+/// recognition; those before it were earned as they were scanned
+fn unearned_units(order: &altis_order::Order, item_id: Uuid, consumed: i32, breakage: bool) -> Vec<LedgerEntry> {
+    let manager = altis_order::finance::FinancialManager::new();
+    (consumed.max(0)..)
+        .map_while(|unit| if breakage {
+            manager.recognize_breakage(order, item_id, unit)
+        } else {
+            manager.recognize_revenue(order, item_id, unit)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altis_catalog::RecognitionPolicy;
+
+    #[test]
+    fn test_scheduled_recognition() {
+        let departure = "2026-03-01T08:00:00Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+        let insurance = serde_json::json!({"coverage_start": "2026-02-28T00:00:00Z"});
+        let at = |product_type: &str, metadata: &serde_json::Value| RecognitionPolicy::for_item(product_type, metadata).recognize_at(metadata, Some(departure));
+        assert_eq!(at("INSURANCE", &insurance), Some("2026-02-28T00:00:00Z".parse().unwrap()));
+        assert_eq!(at("LOUNGE", &serde_json::json!({})), Some(departure));
+        assert_eq!(at("FLIGHT", &serde_json::json!({})), None);
+        let late = serde_json::json!({"recognition_policy": {"trigger": "AT_DEPARTURE", "hours": 24}});
+        assert_eq!(at("MEAL", &late), Some(departure + chrono::Duration::hours(24)));
+
+        // Three passes, one scanned: the other two are earned on schedule
+        let mut order = altis_order::Order::new("customer".to_string());
+        let item = altis_order::OrderItem::new("LOUNGE".to_string(), None, None, "Lounge Pass".to_string(), None, Money::nuc(45), 3, serde_json::json!({}));
+        let item_id = item.id;
+        order.add_item(item).unwrap();
+        let entries = unearned_units(&order, item_id, 1, false);
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.amount == Money::nuc(45) && e.transaction_type == "REVENUE_RECOGNITION"));
+        assert!(unearned_units(&order, item_id, 3, false).is_empty());
+        // Unused at departure, the same units are breakage
+        let breakage = unearned_units(&order, item_id, 1, true);
+        assert_eq!(breakage.len(), 2);
+        assert!(breakage.iter().all(|e| e.transaction_type == "BREAKAGE"));
+    }
 }
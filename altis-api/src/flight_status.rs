@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use serde::Deserialize;
+use tokio::sync::{watch, Mutex};
+use uuid::Uuid;
+
+use altis_catalog::product::FlightStatus;
+use altis_core::events::registry;
+use altis_core::flight_status::{FlightStatusEvent, FlightStatusFeed, FLIGHT_STATUS_TOPIC};
+use altis_order::disruption::DisruptionManager;
+use altis_store::app_config::{FlightStatusConfig, KafkaConfig};
+use altis_store::consumer::{EventConsumer, EventHandler, HandlerError};
+
+use crate::admin::TriggerDisruptionRequest;
+use crate::state::AppState;
+
+/// How long a handled change is remembered, comfortably past any redelivery
+const CLAIM_TTL_SECONDS: u64 = 2 * 24 * 3600;
+
+// ============================================================================
+// Consumer
+// ============================================================================
+
+/// Applies status changes from `flight.status.changed` as they arrive. A
+/// change that fails for a transient reason is retried with backoff; one
+/// that keeps failing, or can't be read, goes to the dead-letter topic.
+pub async fn run_flight_status_consumer(state: AppState, kafka: KafkaConfig, config: FlightStatusConfig, shutdown: watch::Receiver<bool>) {
+    if !config.consume {
+        return;
+    }
+    let consumer = match EventConsumer::new(&kafka, &config.consumer_group, FLIGHT_STATUS_TOPIC, state.kafka.clone()) {
+        Ok(consumer) => consumer,
+        Err(e) => {
+            tracing::error!("Failed to start flight status consumer, disruptions need the admin API: {}", e);
+            return;
+        }
+    };
+    consumer.run(FlightStatusHandler { state, config }, shutdown).await;
+}
+
+struct FlightStatusHandler {
+    state: AppState,
+    config: FlightStatusConfig,
+}
+
+#[async_trait]
+impl EventHandler for FlightStatusHandler {
+    type Event = FlightStatusEvent;
+
+    async fn handle(&self, event: &FlightStatusEvent) -> Result<(), HandlerError> {
+        handle_status_event(&self.state, &self.config, event).await.map_err(|status| {
+            HandlerError::Retryable(format!("status {} of {}{}: {}", event.status, event.carrier, event.flight_number, status))
+        })
+    }
+}
+
+/// Runs the disruption for a delay or cancellation, once per change.
+/// Passengers are only moved when the airline's delay threshold is passed.
+pub(crate) async fn handle_status_event(state: &AppState, config: &FlightStatusConfig, event: &FlightStatusEvent) -> Result<(), StatusCode> {
+    let status_name = event.status.to_uppercase();
+    let Ok(status) = serde_json::from_value::<FlightStatus>(serde_json::json!(status_name)) else {
+        tracing::warn!("Ignoring unknown status '{}' for {}{}", event.status, event.carrier, event.flight_number);
+        return Ok(());
+    };
+    if status != FlightStatus::Delayed && status != FlightStatus::Cancelled {
+        return Ok(());
+    }
+
+    let claim = format!("flight_status:{}", event.dedup_key());
+    match state.redis.try_claim(&claim, CLAIM_TTL_SECONDS).await {
+        Ok(true) => {}
+        Ok(false) => return Ok(()),
+        Err(e) => {
+            tracing::error!("Failed to claim flight status change {}: {:?}", claim, e);
+            return Err(StatusCode::SERVICE_UNAVAILABLE);
+        }
+    }
+
+    let result = apply_status(state, config, event, status, status_name).await;
+    if result.is_err() {
+        let _ = state.redis.release_claim(&claim).await;
+    }
+    result
+}
+
+async fn apply_status(
+    state: &AppState,
+    config: &FlightStatusConfig,
+    event: &FlightStatusEvent,
+    status: FlightStatus,
+    status_name: String,
+) -> Result<(), StatusCode> {
+    let Some((flight_id, airline_id)) = resolve_flight(state, event).await? else {
+        tracing::info!("No catalog flight for {}{} on {}, status change ignored", event.carrier, event.flight_number, event.departure_date);
+        return Ok(());
+    };
+
+    let threshold = reaccommodation_threshold(state, config, airline_id).await;
+    let delay_minutes = event.delay_minutes.unwrap_or(0);
+    let reaccommodate = DisruptionManager::new().requires_reaccommodation(&status, delay_minutes, threshold);
+    tracing::info!(
+        "{}{} on {} is {} ({} min); re-accommodating: {}",
+        event.carrier, event.flight_number, event.departure_date, status_name, delay_minutes, reaccommodate,
+    );
+
+    let req = TriggerDisruptionRequest {
+        flight_id,
+        new_status: status_name,
+        delay_minutes: event.delay_minutes,
+        cause: event.cause.as_deref().map(str::to_uppercase),
+    };
+    crate::admin::apply_disruption(state, &req, reaccommodate, "FLIGHT_STATUS", "Flight status change reported by operations").await
+}
+
+/// The catalog flight and its airline: by id when the event gives one,
+/// otherwise by flight number and local departure date
+async fn resolve_flight(state: &AppState, event: &FlightStatusEvent) -> Result<Option<(Uuid, Uuid)>, StatusCode> {
+    let id_of = |value: &serde_json::Value| value.as_str().and_then(|id| Uuid::parse_str(id).ok());
+
+    if let Some(flight_id) = event.flight_id {
+        let product = state.catalog_repo.get_product(flight_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        return Ok(product.and_then(|p| id_of(&p["airline_id"])).map(|airline_id| (flight_id, airline_id)));
+    }
+
+    let Some(airline) = state.catalog_cache.airline(&event.carrier.to_uppercase()).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else {
+        return Ok(None);
+    };
+    let Some(airline_id) = id_of(&airline["id"]) else { return Ok(None) };
+    let products = state.catalog_cache.products(airline_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let product_code = format!("{}{}", event.carrier.to_uppercase(), event.flight_number);
+
+    let flight = products.iter().find(|product| {
+        let metadata = &product["metadata"];
+        let number_matches = metadata["flight_number"].as_str() == Some(event.flight_number.as_str())
+            || product["product_code"].as_str() == Some(product_code.as_str());
+        let departs_that_day = metadata["departure_time"].as_str()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|t| t.naive_local().date() == event.departure_date);
+        product["product_type"].as_str() == Some("FLIGHT") && number_matches && departs_that_day
+    });
+    Ok(flight.and_then(|f| id_of(&f["id"])).map(|flight_id| (flight_id, airline_id)))
+}
+
+/// The airline's DISRUPTION rule threshold, or the configured default
+async fn reaccommodation_threshold(state: &AppState, config: &FlightStatusConfig, airline_id: Uuid) -> i64 {
+    match state.catalog_repo.get_business_rule(airline_id, "DISRUPTION").await {
+        Ok(Some(rule)) => rule["reaccommodate_after_delay_minutes"].as_i64().unwrap_or(config.reaccommodate_after_delay_minutes),
+        Ok(None) => config.reaccommodate_after_delay_minutes,
+        Err(e) => {
+            tracing::warn!("Failed to load disruption rule for airline {}, using the default: {:?}", airline_id, e);
+            config.reaccommodate_after_delay_minutes
+        }
+    }
+}
+
+// ============================================================================
+// External Feed
+// ============================================================================
+
+/// A change as the status feed reports it
+#[derive(Debug, Deserialize)]
+struct FeedChange {
+    carrier: String,
+    flight_number: String,
+    scheduled_departure: chrono::DateTime<chrono::FixedOffset>,
+    estimated_departure: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// The feed's own status; only cancellations and diversions are taken
+    /// from it, delays come from the departure estimate
+    status: String,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeedPage {
+    cursor: Option<String>,
+    changes: Vec<FeedChange>,
+}
+
+impl FeedChange {
+    fn into_event(self) -> FlightStatusEvent {
+        let delay_minutes = self.estimated_departure
+            .map(|estimate| (estimate - self.scheduled_departure).num_minutes())
+            .filter(|minutes| *minutes > 0);
+        let status = match self.status.to_uppercase().as_str() {
+            "CANCELLED" | "CANCELED" => "CANCELLED",
+            "DIVERTED" => "DIVERTED",
+            _ if delay_minutes.is_some() => "DELAYED",
+            _ => "SCHEDULED",
+        };
+        FlightStatusEvent {
+            flight_id: None,
+            carrier: self.carrier.to_uppercase(),
+            flight_number: self.flight_number,
+            departure_date: self.scheduled_departure.naive_local().date(),
+            status: status.to_string(),
+            delay_minutes,
+            cause: self.reason.map(|reason| reason.to_uppercase()),
+        }
+    }
+}
+
+/// A status feed answering `GET {feed_url}?since={cursor}` with the changes
+/// after the cursor and the cursor to send next
+pub struct HttpFlightStatusFeed {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    cursor: Mutex<Option<String>>,
+}
+
+impl HttpFlightStatusFeed {
+    pub fn new(url: &str, api_key: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("flight status HTTP client builds with static settings");
+        Self { client, url: url.to_string(), api_key: api_key.to_string(), cursor: Mutex::new(None) }
+    }
+}
+
+#[async_trait]
+impl FlightStatusFeed for HttpFlightStatusFeed {
+    async fn poll(&self) -> Result<Vec<FlightStatusEvent>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut cursor = self.cursor.lock().await;
+        let mut request = self.client.get(&self.url).bearer_auth(&self.api_key);
+        if let Some(since) = cursor.as_deref() {
+            request = request.query(&[("since", since)]);
+        }
+        let body = request.send().await?.error_for_status()?.bytes().await?;
+        let page: FeedPage = serde_json::from_slice(&body)?;
+        if page.cursor.is_some() {
+            *cursor = page.cursor;
+        }
+        Ok(page.changes.into_iter().map(FeedChange::into_event).collect())
+    }
+}
+
+/// Polls the external feed and republishes its changes onto
+/// `flight.status.changed`, where the consumer picks them up like any other
+pub async fn run_flight_status_feed(state: AppState, config: FlightStatusConfig) {
+    let Some(url) = &config.feed_url else { return };
+    let feed = HttpFlightStatusFeed::new(url, &config.feed_api_key);
+    let mut interval = tokio::time::interval(Duration::from_secs(config.feed_poll_seconds));
+
+    loop {
+        interval.tick().await;
+        let events = match feed.poll().await {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::warn!("Flight status feed poll failed: {}", e);
+                continue;
+            }
+        };
+        for event in events.iter().filter(|e| e.status != "SCHEDULED") {
+            let key = format!("{}{}", event.carrier, event.flight_number);
+            let payload = match registry().seal(event) {
+                Ok(envelope) => serde_json::json!(envelope).to_string(),
+                Err(e) => {
+                    tracing::warn!("Dropping status of {} from the feed: {}", key, e);
+                    continue;
+                }
+            };
+            if let Err(e) = state.kafka.publish(FLIGHT_STATUS_TOPIC, &key, &payload).await {
+                tracing::error!("Failed to publish status of {} from the feed: {}", key, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(status: &str, estimated: Option<&str>) -> FeedChange {
+        FeedChange {
+            carrier: "al".to_string(),
+            flight_number: "101".to_string(),
+            scheduled_departure: chrono::DateTime::parse_from_rfc3339("2026-03-15T23:30:00+08:00").unwrap(),
+            estimated_departure: estimated.map(|t| chrono::DateTime::parse_from_rfc3339(t).unwrap()),
+            status: status.to_string(),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_feed_change_into_event() {
+        let delayed = change("ACTIVE", Some("2026-03-16T03:00:00+08:00")).into_event();
+        assert_eq!(delayed.status, "DELAYED");
+        assert_eq!(delayed.delay_minutes, Some(210));
+        assert_eq!(delayed.carrier, "AL");
+        // The scheduled local date, not the estimate's
+        assert_eq!(delayed.departure_date, chrono::NaiveDate::from_ymd_opt(2026, 3, 15).unwrap());
+
+        assert_eq!(change("Canceled", None).into_event().status, "CANCELLED");
+        assert_eq!(change("ACTIVE", Some("2026-03-15T23:30:00+08:00")).into_event().status, "SCHEDULED");
+    }
+}
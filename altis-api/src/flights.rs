@@ -0,0 +1,50 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures_util::stream::{self, Stream};
+use uuid::Uuid;
+
+use crate::authz::authorize_flight;
+use crate::middleware::auth::CustomerClaims;
+use crate::seat_events::{event_token, Delivery};
+use crate::state::AppState;
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/flights/:id/stream
+/// Live seat-hold updates for a flight the customer holds an order on; reconnects resume from `Last-Event-ID`
+pub async fn stream_flight(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(flight_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    authorize_flight(&state, &claims, &flight_id.to_string()).await?;
+
+    let last_event_id = headers.get("last-event-id").and_then(|value| value.to_str().ok());
+    let subscription = state.seat_events.subscribe(flight_id, last_event_id);
+    let stream = stream::unfold(subscription, |mut subscription| async move {
+        let event = match subscription.next().await? {
+            Delivery::Event(item) => Event::default()
+                .id(event_token(subscription.generation(), item.seq))
+                .event("seat_held")
+                .json_data(&item.event)
+                .unwrap_or_default(),
+            // Held seats were missed; the client reloads the seat map and carries on from here
+            Delivery::Resync(seq) => Event::default()
+                .id(event_token(subscription.generation(), seq))
+                .event("resync")
+                .data("missed seat updates; reload the seat map"),
+        };
+        Some((Ok(event), subscription))
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
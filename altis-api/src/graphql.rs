@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{
+    ComplexObject, Context, EmptySubscription, ErrorExtensions, InputObject, Json as GqlJson, Object, Schema,
+    SimpleObject,
+};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    Extension, Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::authz::owns_order;
+use crate::middleware::auth::CustomerClaims;
+use crate::error::ApiError;
+use crate::offers::{AcceptOfferRequest, OfferResponse, SearchOffersRequest};
+use crate::orders::PayOrderRequest;
+use crate::state::AppState;
+
+pub type AltisSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// Deep enough for order → items → metadata, shallow enough that a single
+/// request can't fan out without bound
+const MAX_DEPTH: usize = 8;
+
+pub fn schema() -> AltisSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .finish()
+}
+
+/// POST /v1/graphql
+/// Run a GraphQL query or mutation as the authenticated customer
+pub async fn execute(
+    State(state): State<AppState>,
+    Extension(schema): Extension<AltisSchema>,
+    Extension(claims): Extension<CustomerClaims>,
+    headers: HeaderMap,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    // Loaders live for one request, so nothing cached leaks to another caller
+    let request = request
+        .data(DataLoader::new(OrderLoader { state: state.clone() }, tokio::spawn))
+        .data(DataLoader::new(OrderItemsLoader { state: state.clone() }, tokio::spawn))
+        .data(DataLoader::new(TravelersLoader { state: state.clone() }, tokio::spawn))
+        .data(state)
+        .data(claims)
+        .data(headers);
+    Json(schema.execute(request).await)
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// A REST handler's failure as a GraphQL error, with the status in
+/// `extensions.code` so clients can branch on it
+fn error(code: StatusCode) -> async_graphql::Error {
+    let message = code.canonical_reason().unwrap_or("Request failed");
+    let name = match code {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => "BAD_REQUEST",
+        StatusCode::UNAUTHORIZED => "UNAUTHENTICATED",
+        StatusCode::FORBIDDEN => "FORBIDDEN",
+        StatusCode::NOT_FOUND | StatusCode::GONE => "NOT_FOUND",
+        StatusCode::CONFLICT => "CONFLICT",
+        StatusCode::PAYMENT_REQUIRED => "PAYMENT_REQUIRED",
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE | StatusCode::BAD_GATEWAY => "UNAVAILABLE",
+        StatusCode::GATEWAY_TIMEOUT => "DEADLINE_EXCEEDED",
+        _ => "INTERNAL",
+    };
+    async_graphql::Error::new(message).extend_with(|_, e| {
+        e.set("code", name);
+        e.set("status", code.as_u16());
+    })
+}
+
+/// As `error`, with a refused request's field errors under `fields`
+fn api_error(e: ApiError) -> async_graphql::Error {
+    match e {
+        ApiError::Status(code) => error(code),
+        ApiError::Invalid(fields) => async_graphql::Error::new("Validation failed").extend_with(|_, e| {
+            e.set("code", "BAD_REQUEST");
+            e.set("status", StatusCode::UNPROCESSABLE_ENTITY.as_u16());
+            e.set("fields", fields.iter()
+                .map(|f| async_graphql::Value::from_json(serde_json::json!(f)).unwrap_or_default())
+                .collect::<Vec<_>>());
+        }),
+    }
+}
+
+fn repo_error(e: Box<dyn std::error::Error + Send + Sync>) -> Arc<String> {
+    tracing::error!("GraphQL batch load failed: {}", e);
+    Arc::new(e.to_string())
+}
+
+// ============================================================================
+// Loaders
+// ============================================================================
+
+/// Orders by id without what hangs off them; ownership is checked by the caller
+pub struct OrderLoader {
+    state: AppState,
+}
+
+impl Loader<Uuid> for OrderLoader {
+    type Value = Order;
+    type Error = Arc<String>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Order>, Self::Error> {
+        let headers = self.state.order_repo.get_order_headers(keys).await.map_err(repo_error)?;
+        Ok(headers.into_iter()
+            .filter_map(|header| serde_json::from_value::<Order>(header).ok())
+            .map(|order| (order.id, order))
+            .collect())
+    }
+}
+
+/// Every requested order's items in one query
+pub struct OrderItemsLoader {
+    state: AppState,
+}
+
+impl Loader<Uuid> for OrderItemsLoader {
+    type Value = Vec<OrderItem>;
+    type Error = Arc<String>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<OrderItem>>, Self::Error> {
+        let items = self.state.order_repo.list_order_items(keys).await.map_err(repo_error)?;
+        Ok(group_by_order(items))
+    }
+}
+
+/// Every requested order's travelers in one query
+pub struct TravelersLoader {
+    state: AppState,
+}
+
+impl Loader<Uuid> for TravelersLoader {
+    type Value = Vec<Traveler>;
+    type Error = Arc<String>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<Traveler>>, Self::Error> {
+        let travelers = self.state.order_repo.list_order_travelers(keys).await.map_err(repo_error)?;
+        Ok(group_by_order(travelers))
+    }
+}
+
+/// Splits rows tagged with `order_id` back out per order, keeping their order
+fn group_by_order<T: serde::de::DeserializeOwned>(rows: Vec<serde_json::Value>) -> HashMap<Uuid, Vec<T>> {
+    let mut grouped: HashMap<Uuid, Vec<T>> = HashMap::new();
+    for row in rows {
+        let Some(order_id) = row["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else {
+            continue;
+        };
+        if let Ok(value) = serde_json::from_value(row) {
+            grouped.entry(order_id).or_default().push(value);
+        }
+    }
+    grouped
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(SimpleObject)]
+pub struct Offer {
+    id: Uuid,
+    items: Vec<OfferItem>,
+    total_nuc: i64,
+    currency: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    /// Airline code of the carrier selling the offer
+    owner: Option<String>,
+    /// Built without part of the search because it ran out of time
+    degraded: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct OfferItem {
+    id: Uuid,
+    product_type: String,
+    name: String,
+    description: Option<String>,
+    price_nuc: i64,
+    tax_nuc: i64,
+    metadata: GqlJson<serde_json::Value>,
+}
+
+impl From<OfferResponse> for Offer {
+    fn from(offer: OfferResponse) -> Self {
+        Self {
+            id: offer.id,
+            items: offer.items.into_iter().map(|item| OfferItem {
+                id: item.id,
+                product_type: item.product_type,
+                name: item.name,
+                description: item.description,
+                price_nuc: item.price.minor_units(),
+                tax_nuc: item.tax.minor_units(),
+                metadata: GqlJson(item.metadata),
+            }).collect(),
+            total_nuc: offer.total.minor_units(),
+            currency: offer.currency,
+            expires_at: offer.expires_at,
+            owner: offer.owner,
+            degraded: offer.degraded,
+        }
+    }
+}
+
+/// An order's own fields; items and travelers are batch-loaded on demand
+#[derive(SimpleObject, Deserialize, Clone)]
+#[graphql(complex)]
+pub struct Order {
+    id: Uuid,
+    offer_id: Option<Uuid>,
+    status: String,
+    total_nuc: i32,
+    #[serde(default)]
+    currency: Option<String>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    group_size: Option<i32>,
+    names_due_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    test: bool,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// What the customer must do to finish paying, e.g. a 3-D Secure
+    /// challenge; only set on the result of `payOrder`
+    #[serde(default)]
+    payment_action: Option<GqlJson<serde_json::Value>>,
+    #[graphql(skip)]
+    customer_id: String,
+    #[graphql(skip)]
+    customer_did: Option<String>,
+}
+
+#[ComplexObject]
+impl Order {
+    async fn items(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<OrderItem>> {
+        let loader = ctx.data_unchecked::<DataLoader<OrderItemsLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+
+    async fn travelers(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Traveler>> {
+        let loader = ctx.data_unchecked::<DataLoader<TravelersLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+}
+
+#[derive(SimpleObject, Deserialize, Clone)]
+pub struct OrderItem {
+    id: Uuid,
+    product_id: Option<Uuid>,
+    product_type: String,
+    name: String,
+    description: Option<String>,
+    /// Per unit
+    price_nuc: i32,
+    #[serde(default)]
+    tax_nuc: i32,
+    quantity: Option<i32>,
+    #[serde(default)]
+    refunded_quantity: i32,
+    status: Option<String>,
+    #[serde(default)]
+    metadata: GqlJson<serde_json::Value>,
+    /// The traveler it's for; none when it covers the booking
+    #[serde(default)]
+    traveler_index: Option<i32>,
+    #[serde(default)]
+    traveler_id: Option<Uuid>,
+}
+
+#[derive(SimpleObject, Deserialize, Clone)]
+pub struct Traveler {
+    id: Uuid,
+    traveler_index: i32,
+    ptc: Option<String>,
+    first_name: String,
+    last_name: String,
+    date_of_birth: Option<String>,
+    gender: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct Fulfillment {
+    order_id: Uuid,
+    barcodes: Vec<Barcode>,
+    /// Items whose barcode is issued later
+    scheduled: Vec<ScheduledDelivery>,
+}
+
+#[derive(SimpleObject)]
+pub struct Barcode {
+    item_id: Uuid,
+    /// The traveler it's for; none when it covers the booking
+    traveler_id: Option<Uuid>,
+    traveler_index: Option<i32>,
+    /// Flight segment it boards
+    segment: Option<GqlJson<serde_json::Value>>,
+    /// Which of the item's units it is
+    unit_index: i32,
+    barcode: String,
+    qr_code_url: Option<String>,
+    /// Signed pass scanners can check offline
+    signed_payload: Option<String>,
+    /// When the unit went unused past departure and stopped scanning
+    expired_at: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ScheduledDelivery {
+    item_id: Uuid,
+    traveler_index: Option<i32>,
+    deliver_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(InputObject)]
+pub struct OfferSearchInput {
+    origin: String,
+    destination: String,
+    departure_date: String,
+    return_date: Option<String>,
+    passengers: u32,
+    #[graphql(default)]
+    children: u32,
+    #[graphql(default)]
+    infants: u32,
+    cabin_class: Option<String>,
+    user_segment: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct PaymentInput {
+    payment_method: String,
+    #[graphql(default)]
+    payment_token: String,
+    payment_reference: Option<String>,
+    /// A saved card to charge instead of a new payment reference
+    payment_method_id: Option<Uuid>,
+    /// Where the provider sends the customer back after a 3-D Secure challenge
+    return_url: Option<String>,
+}
+
+// ============================================================================
+// Resolvers
+// ============================================================================
+
+/// Loads an order through the batch loader, hiding other customers' orders
+/// as missing just like the REST API does
+async fn owned_order(ctx: &Context<'_>, order_id: Uuid) -> async_graphql::Result<Order> {
+    let claims = ctx.data_unchecked::<CustomerClaims>();
+    let order = ctx.data_unchecked::<DataLoader<OrderLoader>>().load_one(order_id).await?
+        .ok_or_else(|| error(StatusCode::NOT_FOUND))?;
+
+    let owner = serde_json::json!({ "customer_id": order.customer_id, "customer_did": order.customer_did });
+    if !owns_order(claims, &owner) {
+        tracing::warn!("Customer {} denied access to order {}", claims.sub, order_id);
+        return Err(error(StatusCode::NOT_FOUND));
+    }
+    Ok(order)
+}
+
+/// Reads an order after a mutation changed it, skipping the loader's cache
+async fn reload_order(state: &AppState, order_id: Uuid) -> async_graphql::Result<Order> {
+    let header = state.order_repo.get_order_headers(&[order_id]).await
+        .map_err(|_| error(StatusCode::INTERNAL_SERVER_ERROR))?
+        .into_iter().next()
+        .ok_or_else(|| error(StatusCode::NOT_FOUND))?;
+    serde_json::from_value(header).map_err(|_| error(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+/// Only the language preference travels with a GraphQL call
+fn language_headers(ctx: &Context<'_>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(languages) = ctx.data_unchecked::<HeaderMap>().get(header::ACCEPT_LANGUAGE) {
+        headers.insert(header::ACCEPT_LANGUAGE, languages.clone());
+    }
+    headers
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Priced and ranked offers, as POST /v1/offers/search
+    async fn offers(&self, ctx: &Context<'_>, search: OfferSearchInput) -> async_graphql::Result<Vec<Offer>> {
+        let state = ctx.data_unchecked::<AppState>().clone();
+        let claims = ctx.data_unchecked::<CustomerClaims>().clone();
+        let req = SearchOffersRequest {
+            origin: search.origin,
+            destination: search.destination,
+            departure_date: search.departure_date,
+            return_date: search.return_date,
+            passengers: search.passengers,
+            children: search.children,
+            infants: search.infants,
+            cabin_class: search.cabin_class,
+            user_segment: search.user_segment,
+            campaign: Default::default(),
+        };
+        let (_, Json(offers)) = crate::offers::search_offers(State(state), Extension(claims), language_headers(ctx), Json(req)).await
+            .map_err(api_error)?;
+        Ok(offers.into_iter().map(Offer::from).collect())
+    }
+
+    async fn offer(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Offer> {
+        let state = ctx.data_unchecked::<AppState>().clone();
+        let Json(offer) = crate::offers::get_offer(State(state), Path(id), language_headers(ctx)).await.map_err(error)?;
+        Ok(offer.into())
+    }
+
+    async fn order(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Order> {
+        owned_order(ctx, id).await
+    }
+
+    /// Barcodes for an order's issued items, as GET /v1/orders/:id/fulfillment
+    async fn fulfillment(&self, ctx: &Context<'_>, order_id: Uuid) -> async_graphql::Result<Fulfillment> {
+        let state = ctx.data_unchecked::<AppState>().clone();
+        let claims = ctx.data_unchecked::<CustomerClaims>().clone();
+        let Json(fulfillment) = crate::orders::get_fulfillment(State(state), Extension(claims), Path(order_id)).await
+            .map_err(error)?;
+        Ok(Fulfillment {
+            order_id: fulfillment.order_id,
+            barcodes: fulfillment.barcodes.into_iter().map(|b| Barcode {
+                item_id: b.item_id,
+                traveler_id: b.traveler_id,
+                traveler_index: b.traveler_index,
+                segment: b.segment.map(GqlJson),
+                unit_index: b.unit_index,
+                barcode: b.barcode,
+                qr_code_url: b.qr_code_url,
+                signed_payload: b.signed_payload,
+                expired_at: b.expired_at,
+            }).collect(),
+            scheduled: fulfillment.scheduled.into_iter().map(|s| ScheduledDelivery {
+                item_id: s.item_id,
+                traveler_index: s.traveler_index,
+                deliver_at: s.deliver_at,
+            }).collect(),
+        })
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Create an order from an offer, with travelers saved on the profile or
+    /// added over REST later
+    async fn accept_offer(
+        &self,
+        ctx: &Context<'_>,
+        offer_id: Uuid,
+        customer_email: String,
+        group_size: Option<i32>,
+        #[graphql(desc = "Travelers saved on the customer's profile")] saved_traveler_ids: Option<Vec<Uuid>>,
+    ) -> async_graphql::Result<Order> {
+        let state = ctx.data_unchecked::<AppState>();
+        let claims = ctx.data_unchecked::<CustomerClaims>().clone();
+        let req = AcceptOfferRequest {
+            customer_email,
+            travelers: None,
+            saved_traveler_ids: saved_traveler_ids.unwrap_or_default(),
+            contact_info: None,
+            group_size,
+            payment_reference: None,
+            item_travelers: Default::default(),
+        };
+        let Json(accepted) = crate::offers::accept_offer(State(state.clone()), Extension(claims), Path(offer_id), Json(req)).await
+            .map_err(error)?;
+        let order_id = accepted["order_id"].as_str()
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .ok_or_else(|| error(StatusCode::INTERNAL_SERVER_ERROR))?;
+        reload_order(state, order_id).await
+    }
+
+    async fn pay_order(&self, ctx: &Context<'_>, order_id: Uuid, payment: PaymentInput) -> async_graphql::Result<Order> {
+        let state = ctx.data_unchecked::<AppState>();
+        let claims = ctx.data_unchecked::<CustomerClaims>().clone();
+        let req = PayOrderRequest {
+            payment_method: payment.payment_method,
+            payment_token: payment.payment_token,
+            payment_reference: payment.payment_reference,
+            payment_method_id: payment.payment_method_id,
+            return_url: payment.return_url,
+        };
+        let Json(order) = crate::orders::pay_order(State(state.clone()), Extension(claims), Path(order_id), Json(req)).await
+            .map_err(error)?;
+        serde_json::to_value(order)
+            .and_then(serde_json::from_value)
+            .map_err(|_| error(StatusCode::INTERNAL_SERVER_ERROR))
+    }
+
+    async fn cancel_order(&self, ctx: &Context<'_>, order_id: Uuid) -> async_graphql::Result<Order> {
+        let state = ctx.data_unchecked::<AppState>();
+        let claims = ctx.data_unchecked::<CustomerClaims>().clone();
+        crate::orders::cancel_order(State(state.clone()), Extension(claims), Path(order_id)).await
+            .map_err(error)?;
+        reload_order(state, order_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_are_grouped_per_order_in_row_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let traveler = |order_id: Uuid, index: i32, name: &str| serde_json::json!({
+            "id": Uuid::new_v4(), "order_id": order_id, "traveler_index": index,
+            "ptc": "ADT", "first_name": name, "last_name": "Tan",
+        });
+        let rows = vec![traveler(a, 0, "Mei"), traveler(b, 0, "Wei"), traveler(a, 1, "Lin"), serde_json::json!({ "order_id": null })];
+
+        let grouped: HashMap<Uuid, Vec<Traveler>> = group_by_order(rows);
+        assert_eq!(grouped.len(), 2);
+        let names: Vec<_> = grouped[&a].iter().map(|t| t.first_name.as_str()).collect();
+        assert_eq!(names, ["Mei", "Lin"]);
+        assert_eq!(grouped[&b][0].first_name, "Wei");
+
+        assert_eq!(error(StatusCode::GONE).extensions.unwrap().get("code"), Some(&async_graphql::Value::from("NOT_FOUND")));
+    }
+
+    #[test]
+    fn test_schema_exposes_shopping_and_order_operations() {
+        let sdl = schema().sdl();
+        for field in ["offers(search: OfferSearchInput!)", "order(id: UUID!)", "fulfillment(orderId: UUID!)", "acceptOffer(", "payOrder(", "cancelOrder("] {
+            assert!(sdl.contains(field), "schema is missing {}", field);
+        }
+    }
+}
@@ -0,0 +1,318 @@
+// tonic::Status is the error type every service method must return
+#![allow(clippy::result_large_err)]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    Extension, Json,
+};
+use altis_store::app_config::GrpcConfig;
+use altis_store::deadline::Deadline;
+use tokio::sync::watch;
+use tonic::{metadata::MetadataMap, Request, Response, Status};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::middleware::auth::{customer_claims, CustomerClaims, API_KEY_HEADER};
+use crate::error::ApiError;
+use crate::offers::{AcceptOfferRequest, OfferResponse, SearchOffersRequest};
+use crate::orders::{OrderResponse, PayOrderRequest};
+use crate::state::AppState;
+
+pub mod proto {
+    tonic::include_proto!("altis.v1");
+}
+
+use proto::offer_service_server::{OfferService, OfferServiceServer};
+use proto::order_service_server::{OrderService, OrderServiceServer};
+
+/// Serves the gRPC API until `shutdown` turns true. Calls run the same
+/// handlers as their REST counterparts, so they behave alike.
+pub async fn run_grpc_server(state: AppState, config: GrpcConfig, mut shutdown: watch::Receiver<bool>) {
+    if !config.enabled {
+        return;
+    }
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    tracing::info!("gRPC listening on {}", addr);
+
+    let served = tonic::transport::Server::builder()
+        .add_service(OfferServiceServer::new(GrpcOffers { state: state.clone() }))
+        .add_service(OrderServiceServer::new(GrpcOrders { state }))
+        .serve_with_shutdown(addr, async move {
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        })
+        .await;
+    if let Err(e) = served {
+        tracing::error!("gRPC server failed: {}", e);
+    }
+}
+
+// ============================================================================
+// Auth and Errors
+// ============================================================================
+
+/// The caller, from `authorization` or `x-api-key` metadata
+async fn authenticate(state: &AppState, metadata: &MetadataMap) -> Result<CustomerClaims, Status> {
+    let api_key = metadata.get(API_KEY_HEADER.to_lowercase().as_str()).and_then(|v| v.to_str().ok());
+    let token = metadata.get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    customer_claims(state, api_key, token).await
+        .map(|(_, claims)| claims)
+        .map_err(|e| match e {
+            AppError::AuthenticationError(msg) => Status::unauthenticated(msg),
+            AppError::AuthorizationError(msg) => Status::permission_denied(msg),
+            e => Status::internal(e.to_string()),
+        })
+}
+
+/// The gRPC status for what a REST handler answered
+fn status(code: StatusCode) -> Status {
+    let message = code.canonical_reason().unwrap_or_default();
+    match code {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Status::invalid_argument(message),
+        StatusCode::UNAUTHORIZED => Status::unauthenticated(message),
+        StatusCode::FORBIDDEN => Status::permission_denied(message),
+        StatusCode::NOT_FOUND | StatusCode::GONE => Status::not_found(message),
+        StatusCode::CONFLICT => Status::aborted(message),
+        StatusCode::PAYMENT_REQUIRED | StatusCode::PRECONDITION_FAILED => Status::failed_precondition(message),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE | StatusCode::BAD_GATEWAY => Status::unavailable(message),
+        StatusCode::GATEWAY_TIMEOUT => Status::deadline_exceeded(message),
+        _ => Status::internal(message),
+    }
+}
+
+/// As `status`, with a refused request's field errors as the message
+fn api_status(e: ApiError) -> Status {
+    match e {
+        ApiError::Status(code) => status(code),
+        ApiError::Invalid(_) => Status::invalid_argument(e.message()),
+    }
+}
+
+/// `accept-language` metadata as the header REST handlers read it, so
+/// offers are worded for the caller's languages
+fn language_headers(metadata: &MetadataMap) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(languages) = metadata.get("accept-language").and_then(|v| HeaderValue::from_bytes(v.as_bytes()).ok()) {
+        headers.insert(header::ACCEPT_LANGUAGE, languages);
+    }
+    headers
+}
+
+fn parse_id(id: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(id).map_err(|_| Status::invalid_argument(format!("'{}' is not a valid id", id)))
+}
+
+/// Runs a call under the same deadline a REST request without
+/// `X-Request-Timeout` gets
+async fn within_deadline<T>(state: &AppState, call: impl std::future::Future<Output = Result<T, Status>>) -> Result<T, Status> {
+    let budget = Duration::from_millis(state.deadlines.default_ms);
+    let deadline = Deadline::after(budget);
+    tokio::time::timeout_at(deadline.at(), deadline.scope(call)).await
+        .unwrap_or_else(|_| Err(Status::deadline_exceeded("Request deadline exceeded")))
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+fn offer_message(offer: OfferResponse) -> proto::Offer {
+    proto::Offer {
+        id: offer.id.to_string(),
+        items: offer.items.into_iter().map(|item| proto::OfferItem {
+            id: item.id.to_string(),
+            product_type: item.product_type,
+            name: item.name,
+            description: item.description,
+            price_nuc: item.price.minor_units(),
+            tax_nuc: item.tax.minor_units(),
+            metadata_json: item.metadata.to_string(),
+        }).collect(),
+        total_nuc: offer.total.minor_units(),
+        currency: offer.currency,
+        expires_at: offer.expires_at.to_rfc3339(),
+        owner: offer.owner,
+        degraded: offer.degraded,
+    }
+}
+
+fn order_message(order: OrderResponse) -> proto::Order {
+    proto::Order {
+        id: order.id.to_string(),
+        offer_id: order.offer_id.map(|id| id.to_string()),
+        status: order.status,
+        items: order.items.into_iter().map(|item| proto::OrderItem {
+            id: item.id.to_string(),
+            product_id: item.product_id.map(|id| id.to_string()),
+            product_type: item.product_type,
+            name: item.name,
+            price_nuc: i64::from(item.price_nuc),
+            quantity: item.quantity.unwrap_or(1),
+            status: item.status,
+            traveler_index: item.traveler_index,
+            traveler_id: item.traveler_id.map(|id| id.to_string()),
+        }).collect(),
+        total_nuc: i64::from(order.total_nuc),
+        currency: order.currency,
+        expires_at: order.expires_at.map(|at| at.to_rfc3339()),
+        test: order.test,
+        created_at: order.created_at.to_rfc3339(),
+        payment_action_json: order.payment_action.and_then(|action| serde_json::to_string(&action).ok()),
+    }
+}
+
+// ============================================================================
+// Offers
+// ============================================================================
+
+struct GrpcOffers {
+    state: AppState,
+}
+
+#[tonic::async_trait]
+impl OfferService for GrpcOffers {
+    async fn search(&self, request: Request<proto::SearchOffersRequest>) -> Result<Response<proto::SearchOffersResponse>, Status> {
+        let state = &self.state;
+        let claims = authenticate(state, request.metadata()).await?;
+        let headers = language_headers(request.metadata());
+        let search = request.into_inner();
+        let req = SearchOffersRequest {
+            origin: search.origin,
+            destination: search.destination,
+            departure_date: search.departure_date,
+            return_date: search.return_date,
+            passengers: search.passengers,
+            children: 0,
+            infants: 0,
+            cabin_class: search.cabin_class,
+            user_segment: search.user_segment,
+            campaign: Default::default(),
+        };
+
+        within_deadline(state, async {
+            let (_, Json(offers)) = crate::offers::search_offers(State(state.clone()), Extension(claims), headers, Json(req)).await
+                .map_err(api_status)?;
+            let degraded = offers.iter().any(|offer| offer.degraded);
+            Ok(Response::new(proto::SearchOffersResponse {
+                offers: offers.into_iter().map(offer_message).collect(),
+                degraded,
+            }))
+        }).await
+    }
+
+    async fn get(&self, request: Request<proto::GetOfferRequest>) -> Result<Response<proto::Offer>, Status> {
+        let state = &self.state;
+        authenticate(state, request.metadata()).await?;
+        let headers = language_headers(request.metadata());
+        let offer_id = parse_id(&request.into_inner().offer_id)?;
+
+        within_deadline(state, async {
+            let Json(offer) = crate::offers::get_offer(State(state.clone()), Path(offer_id), headers).await.map_err(status)?;
+            Ok(Response::new(offer_message(offer)))
+        }).await
+    }
+
+    async fn accept(&self, request: Request<proto::AcceptOfferRequest>) -> Result<Response<proto::AcceptOfferResponse>, Status> {
+        let state = &self.state;
+        let claims = authenticate(state, request.metadata()).await?;
+        let accept = request.into_inner();
+        let offer_id = parse_id(&accept.offer_id)?;
+        let req = AcceptOfferRequest {
+            customer_email: accept.customer_email,
+            travelers: None,
+            saved_traveler_ids: accept.saved_traveler_ids.iter().map(|id| parse_id(id)).collect::<Result<_, _>>()?,
+            contact_info: None,
+            group_size: accept.group_size,
+            payment_reference: None,
+            item_travelers: Default::default(),
+        };
+
+        within_deadline(state, async {
+            let Json(accepted) = crate::offers::accept_offer(State(state.clone()), Extension(claims), Path(offer_id), Json(req)).await
+                .map_err(status)?;
+            Ok(Response::new(proto::AcceptOfferResponse {
+                order_id: accepted["order_id"].as_str().unwrap_or_default().to_string(),
+                status: accepted["status"].as_str().unwrap_or_default().to_string(),
+            }))
+        }).await
+    }
+}
+
+// ============================================================================
+// Orders
+// ============================================================================
+
+struct GrpcOrders {
+    state: AppState,
+}
+
+#[tonic::async_trait]
+impl OrderService for GrpcOrders {
+    async fn get(&self, request: Request<proto::GetOrderRequest>) -> Result<Response<proto::Order>, Status> {
+        let state = &self.state;
+        let claims = authenticate(state, request.metadata()).await?;
+        let order_id = parse_id(&request.into_inner().order_id)?;
+
+        within_deadline(state, async {
+            let Json(order) = crate::orders::get_order(State(state.clone()), Extension(claims), Path(order_id)).await.map_err(status)?;
+            Ok(Response::new(order_message(order)))
+        }).await
+    }
+
+    async fn pay(&self, request: Request<proto::PayOrderRequest>) -> Result<Response<proto::Order>, Status> {
+        let state = &self.state;
+        let claims = authenticate(state, request.metadata()).await?;
+        let pay = request.into_inner();
+        let order_id = parse_id(&pay.order_id)?;
+        let req = PayOrderRequest {
+            payment_method: pay.payment_method,
+            payment_token: pay.payment_token,
+            payment_reference: pay.payment_reference,
+            payment_method_id: pay.payment_method_id.as_deref().map(parse_id).transpose()?,
+            return_url: pay.return_url,
+        };
+
+        within_deadline(state, async {
+            let Json(order) = crate::orders::pay_order(State(state.clone()), Extension(claims), Path(order_id), Json(req)).await
+                .map_err(status)?;
+            Ok(Response::new(order_message(order)))
+        }).await
+    }
+
+    async fn cancel(&self, request: Request<proto::CancelOrderRequest>) -> Result<Response<proto::CancelOrderResponse>, Status> {
+        let state = &self.state;
+        let claims = authenticate(state, request.metadata()).await?;
+        let order_id = parse_id(&request.into_inner().order_id)?;
+
+        within_deadline(state, async {
+            crate::orders::cancel_order(State(state.clone()), Extension(claims), Path(order_id)).await.map_err(status)?;
+            Ok(Response::new(proto::CancelOrderResponse {
+                order_id: order_id.to_string(),
+                status: "CANCELLED".to_string(),
+            }))
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rest_outcomes_map_to_grpc_codes() {
+        assert_eq!(status(StatusCode::NOT_FOUND).code(), tonic::Code::NotFound);
+        // An expired offer is as good as gone
+        assert_eq!(status(StatusCode::GONE).code(), tonic::Code::NotFound);
+        assert_eq!(status(StatusCode::CONFLICT).code(), tonic::Code::Aborted);
+        assert_eq!(status(StatusCode::PAYMENT_REQUIRED).code(), tonic::Code::FailedPrecondition);
+        assert_eq!(status(StatusCode::GATEWAY_TIMEOUT).code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(status(StatusCode::INTERNAL_SERVER_ERROR).code(), tonic::Code::Internal);
+
+        assert!(parse_id("not-a-uuid").is_err());
+        assert_eq!(parse_id("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap().to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+    }
+}
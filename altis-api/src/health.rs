@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::state::AppState;
+
+/// Each dependency probe gets this long before it is reported down.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// GET /health/live
+/// Liveness: the process is up and serving. Never touches dependencies, so a
+/// database outage doesn't get healthy pods restarted.
+pub async fn live() -> impl IntoResponse {
+    Json(json!({
+        "status": "alive",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+/// GET /health/ready
+/// Readiness: probes every dependency concurrently. Postgres (primary) and
+/// Redis are required; a down replica or Kafka only marks the service degraded,
+/// as does a schema that no longer matches this build's migrations.
+/// Until startup cache warming finishes the instance reports "warming".
+pub async fn ready(State(state): State<AppState>) -> impl IntoResponse {
+    let (database, redis, kafka, schema) = tokio::join!(
+        state.db.health(PROBE_TIMEOUT),
+        altis_store::health::probe(PROBE_TIMEOUT, state.redis.ping()),
+        altis_store::health::probe(PROBE_TIMEOUT, state.kafka.ping(PROBE_TIMEOUT)),
+        tokio::time::timeout(PROBE_TIMEOUT, state.db.schema_status()),
+    );
+    // Unreadable is already a down primary
+    let schema = schema.ok().and_then(Result::ok);
+
+    let ready = database.is_healthy() && redis.is_up();
+    let degraded = database.replica.as_ref().is_some_and(|r| r.error.is_some())
+        || !kafka.is_up()
+        || schema.as_ref().is_some_and(|schema| !schema.is_current());
+
+    let warmed = state.warmup.is_complete();
+
+    let (code, status) = match (ready, warmed, degraded) {
+        (false, _, _) => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+        (true, false, _) => (StatusCode::SERVICE_UNAVAILABLE, "warming"),
+        (true, true, true) => (StatusCode::OK, "degraded"),
+        (true, true, false) => (StatusCode::OK, "ready"),
+    };
+
+    (code, Json(json!({
+        "status": status,
+        "version": env!("CARGO_PKG_VERSION"),
+        "components": {
+            "postgres": database,
+            "redis": redis,
+            "kafka": kafka,
+        },
+        "schema": schema,
+        "warmup": {
+            "complete": warmed,
+            "steps": state.warmup.steps(),
+        },
+    })))
+}
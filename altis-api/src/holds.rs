@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Extension, Json,
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseSeatRequest {
+    pub trip_id: String,
+    pub flight_id: Uuid,
+    pub seat_number: String,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// DELETE /v1/holds/seat
+/// Give up a seat the caller's trip has locked, before the lock runs out
+pub async fn release_seat(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Json(req): Json<ReleaseSeatRequest>,
+) -> Result<StatusCode, StatusCode> {
+    // Trips that record an owner can only be touched by that customer; a
+    // stranger's trip is reported as missing, like other people's orders
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let owner = state.redis.hget_trip_field(&req.trip_id, "customer_id").await.map_err(|e| {
+        tracing::error!("Failed to load trip {}: {:?}", req.trip_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if owner.is_some_and(|owner| owner != customer_id) {
+        tracing::warn!("Customer {} denied release of a seat held by trip {}", claims.sub, req.trip_id);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let released = state.redis
+        .release_seat_lock(&req.flight_id.to_string(), &req.seat_number, &req.trip_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to release seat {} on flight {}: {:?}", req.seat_number, req.flight_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !released {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    tracing::info!("Trip {} released seat {} on flight {}", req.trip_id, req.seat_number, req.flight_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Trip Expiry
+// ============================================================================
+
+/// Releases a trip's seat locks as soon as its session key expires, rather
+/// than leaving the seats blocked until each lock's own TTL runs out.
+/// Expiry events are fire-and-forget, so any missed while disconnected fall
+/// back to those TTLs.
+pub async fn run_trip_expiry_listener(state: AppState, enable_notifications: bool) {
+    if enable_notifications {
+        if let Err(e) = state.redis.enable_expiry_notifications().await {
+            tracing::warn!("Could not enable Redis expiry notifications, seat locks will only lapse by TTL: {:?}", e);
+        }
+    }
+
+    loop {
+        match state.redis.subscribe_expired_keys().await {
+            Ok(pubsub) => {
+                let mut messages = pubsub.into_on_message();
+                while let Some(msg) = messages.next().await {
+                    let Ok(key) = msg.get_payload::<String>() else { continue };
+                    let Some(trip_id) = altis_store::redis_repo::expired_trip_id(&key) else { continue };
+                    match state.redis.release_trip_seats(trip_id).await {
+                        Ok(0) => {}
+                        Ok(released) => tracing::info!("Trip {} expired, released {} seat lock(s)", trip_id, released),
+                        Err(e) => tracing::error!("Failed to release seats of expired trip {}: {:?}", trip_id, e),
+                    }
+                }
+                tracing::warn!("Redis expiry subscription closed, resubscribing");
+            }
+            Err(e) => tracing::error!("Failed to subscribe to Redis expiry events: {:?}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
@@ -0,0 +1,443 @@
+use std::time::Duration as StdDuration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use altis_core::payment::PaymentStatus;
+use altis_order::installments;
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::{Currency, Money};
+
+use crate::authz::authorize_order;
+use crate::middleware::auth::CustomerClaims;
+use crate::orders::OrderResponse;
+use crate::state::AppState;
+
+/// How long a claimed installment is left alone before another instance may
+/// pick it up, e.g. after a gateway error
+const CLAIM_LEASE_SECONDS: i64 = 900;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInstallmentPlanRequest {
+    /// Number of payments, the first of which is taken now
+    pub installments: u32,
+    /// Days between payments; `installments.interval_days` when omitted
+    pub interval_days: Option<i64>,
+    /// Payment method on file, charged for every installment
+    pub payment_reference: Option<String>,
+    /// A saved card to charge instead of `payment_reference`
+    pub payment_method_id: Option<Uuid>,
+}
+
+/// Latest date the plan's last installment may fall due: the configured
+/// number of days before the order's first departure
+fn payment_deadline(order: &OrderResponse, final_due_days: i64) -> Option<DateTime<Utc>> {
+    order.items.iter()
+        .filter(|item| item.product_type.eq_ignore_ascii_case("FLIGHT") && item.status != "CANCELLED")
+        .filter_map(|item| item.metadata["departure_time"].as_str())
+        .filter_map(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .min()
+        .map(|departure| departure - Duration::days(final_due_days))
+}
+
+fn uuid_of(value: &Value) -> Option<Uuid> {
+    value.as_str().and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// POST /v1/orders/:id/installments
+/// Pay for a held order in installments, charging the first one now
+pub async fn create_installment_plan(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+    Json(req): Json<CreateInstallmentPlanRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let order: OrderResponse = serde_json::from_value(order_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if order.expires_at.is_some_and(|expires_at| Utc::now() > expires_at) {
+        return Err(StatusCode::GONE);
+    }
+    if order.status != "PROPOSED" {
+        return Err(StatusCode::CONFLICT);
+    }
+    let existing = state.payment_schedule_repo.get_schedule(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if existing.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let payment_reference = match req.payment_method_id {
+        Some(id) => Some(crate::payment_methods::stored_token(&state, &claims, id, order.test).await?),
+        None => req.payment_reference.clone(),
+    };
+
+    // 1. Lay out the payments; the last must be in well before departure
+    let config = &state.installments;
+    let plan = installments::schedule(
+        Money::nuc(order.total_nuc as i64),
+        req.installments,
+        config.max_installments,
+        req.interval_days.unwrap_or(config.interval_days),
+        Utc::now(),
+        payment_deadline(&order, config.final_due_days),
+    )
+    .map_err(|e| {
+        tracing::info!("Rejected installment plan for order {}: {}", order_id, e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+    let planned: Vec<Value> = plan.iter()
+        .map(serde_json::to_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let schedule = state.payment_schedule_repo.create_schedule(order_id, payment_reference.as_deref(), &planned).await
+        .map_err(|e| {
+            tracing::error!("Failed to create installment plan for order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let schedule_id = uuid_of(&schedule["id"]).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let first_id = uuid_of(&schedule["installments"][0]["id"]).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 2. Take the first payment; if it doesn't go through the order is left as it was
+    let currency = Currency::new(&order.currency).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let deposit = plan[0].amount;
+    let charged = state.payments(order.test)
+        .charge_installment(order_id, 1, Money::new(deposit.minor_units(), currency), payment_reference.as_deref())
+        .await;
+    if !matches!(charged, Ok(PaymentStatus::Succeeded)) {
+        let _ = state.payment_schedule_repo.close_schedule(schedule_id, "CANCELLED").await;
+        let status = charged.map_err(|e| {
+            tracing::error!("Installment plan deposit failed for order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            "PAYMENT_FAILED",
+            None,
+            Some(serde_json::json!({"status": status, "reference": req.payment_reference, "payment_method_id": req.payment_method_id, "installment": 1})),
+            "SYSTEM",
+            Some("Installment plan deposit declined"),
+        ).await;
+        return Err(StatusCode::PAYMENT_REQUIRED);
+    }
+    state.payment_schedule_repo.mark_installment_paid(first_id).await
+        .map_err(|e| {
+            tracing::error!("Charged the deposit of order {} but failed to record it: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // 3. The seats are the customer's from now on, even before the plan is paid off
+    state.order_repo.update_order_status(order_id, "HOLD_PARTIALLY_PAID").await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    crate::orders::commit_inventory(&state, &order.items).await;
+    crate::suppliers::confirm_supplier_items(&state, order_id, &order.items).await;
+
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "INSTALLMENT_PLAN_CREATED",
+        Some(serde_json::json!({"status": order.status})),
+        Some(serde_json::json!({"status": "HOLD_PARTIALLY_PAID", "schedule_id": schedule_id, "installments": plan})),
+        "CUSTOMER",
+        Some("Order put on an installment plan"),
+    ).await;
+
+    let amount = Money::nuc(order.total_nuc as i64);
+    let tax = Money::nuc(order.items.iter().map(|item| item.tax_nuc as i64).sum());
+    crate::finance::post_journal(&state, JournalTransaction::sale(order_id, amount, tax)).await;
+    crate::finance::post_journal(&state, JournalTransaction::payment(order_id, deposit, payment_reference.as_deref())).await;
+
+    let schedule = state.payment_schedule_repo.get_schedule(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(schedule))
+}
+
+/// GET /v1/orders/:id/installments
+/// The order's installment plan and where each payment stands
+pub async fn get_installment_plan(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<Value>, StatusCode> {
+    authorize_order(&state, &claims, order_id).await?;
+    let schedule = state.payment_schedule_repo.get_schedule(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(schedule))
+}
+
+/// Stops the plan of an order cancelled by other means
+pub(crate) async fn cancel_plan(state: &AppState, order_id: Uuid) {
+    let schedule = match state.payment_schedule_repo.get_schedule(order_id).await {
+        Ok(Some(schedule)) if schedule["status"] == "ACTIVE" => schedule,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::error!("Failed to look up installment plan of order {}: {:?}", order_id, e);
+            return;
+        }
+    };
+    if let Some(schedule_id) = uuid_of(&schedule["id"]) {
+        if let Err(e) = state.payment_schedule_repo.close_schedule(schedule_id, "CANCELLED").await {
+            tracing::error!("Failed to cancel installment plan {}: {:?}", schedule_id, e);
+        }
+    }
+}
+
+/// Charges installments as they fall due
+pub async fn run_installment_worker(state: AppState) {
+    let config = state.installments.clone();
+    let mut interval = tokio::time::interval(StdDuration::from_secs(config.poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let due = match state.payment_schedule_repo.claim_due_installments(config.batch_size, CLAIM_LEASE_SECONDS).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to claim due installments: {:?}", e);
+                continue;
+            }
+        };
+        for installment in due {
+            charge_due(&state, &installment).await;
+        }
+    }
+}
+
+async fn charge_due(state: &AppState, installment: &Value) {
+    let (Some(id), Some(schedule_id), Some(order_id)) =
+        (uuid_of(&installment["id"]), uuid_of(&installment["schedule_id"]), uuid_of(&installment["order_id"]))
+    else {
+        tracing::error!("Unreadable installment {}", installment);
+        return;
+    };
+    // Left to the lease on errors, so it is tried again later
+    let order = match state.order_repo.get_order(order_id).await {
+        Ok(Some(order)) => order,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Failed to load order {} for its installment: {:?}", order_id, e);
+            return;
+        }
+    };
+    if order["status"] != "HOLD_PARTIALLY_PAID" {
+        let _ = state.payment_schedule_repo.close_schedule(schedule_id, "CANCELLED").await;
+        return;
+    }
+
+    let Ok(currency) = Currency::new(order["currency"].as_str().unwrap_or("NUC")) else { return };
+    let sequence = installment["sequence"].as_u64().unwrap_or_default() as u32;
+    let amount_nuc = installment["amount_nuc"].as_i64().unwrap_or_default();
+    let reference = installment["payment_reference"].as_str();
+    let charged = state.payments(order["test"].as_bool().unwrap_or(false))
+        .charge_installment(order_id, sequence, Money::new(amount_nuc, currency), reference)
+        .await;
+
+    match charged {
+        Ok(PaymentStatus::Succeeded) => installment_paid(state, &order, id, sequence, Money::nuc(amount_nuc), reference).await,
+        // Gateway trouble and pending charges aren't the customer's doing.
+        // The intent id is stable, so trying again can't charge twice.
+        Ok(PaymentStatus::Processing) => {}
+        Err(e) => tracing::warn!("Installment {} of order {} not charged: {}", sequence, order_id, e),
+        Ok(status) => installment_declined(state, &order, installment, id, schedule_id, &format!("{:?}", status)).await,
+    }
+}
+
+async fn installment_paid(state: &AppState, order: &Value, id: Uuid, sequence: u32, amount: Money, reference: Option<&str>) {
+    let order_id = uuid_of(&order["id"]).unwrap_or_default();
+    let remaining = match state.payment_schedule_repo.mark_installment_paid(id).await {
+        Ok(Some(remaining)) => remaining,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Charged installment {} of order {} but failed to record it: {:?}", sequence, order_id, e);
+            return;
+        }
+    };
+    crate::finance::post_journal(state, JournalTransaction::payment(order_id, amount, reference)).await;
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "INSTALLMENT_PAID",
+        None,
+        Some(serde_json::json!({"installment": sequence, "amount_nuc": amount.minor_units(), "remaining": remaining})),
+        "SYSTEM",
+        None,
+    ).await;
+    if remaining > 0 {
+        return;
+    }
+
+    if let Err(e) = state.order_repo.update_order_status(order_id, "PAID").await {
+        tracing::error!("Order {} paid off its plan but could not be marked PAID: {:?}", order_id, e);
+        return;
+    }
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "PAYMENT_RECEIVED",
+        Some(serde_json::json!({"status": "HOLD_PARTIALLY_PAID"})),
+        Some(serde_json::json!({"status": "PAID"})),
+        "SYSTEM",
+        Some("Installment plan paid off"),
+    ).await;
+    match serde_json::from_value::<OrderResponse>(order.clone()) {
+        Ok(order) => crate::orders::finish_paid_order(state, &order).await,
+        Err(e) => tracing::error!("Unreadable order {} after its last installment: {}", order_id, e),
+    }
+}
+
+async fn installment_declined(state: &AppState, order: &Value, installment: &Value, id: Uuid, schedule_id: Uuid, reason: &str) {
+    let config = &state.installments;
+    let order_id = uuid_of(&order["id"]).unwrap_or_default();
+    let attempts = installment["attempts"].as_i64().unwrap_or(0) + 1;
+    let retry_at = (attempts < config.max_attempts as i64).then(|| Utc::now() + Duration::hours(config.retry_hours));
+
+    if let Err(e) = state.payment_schedule_repo.mark_installment_failed(id, reason, retry_at).await {
+        tracing::error!("Failed to record declined installment {} of order {}: {:?}", id, order_id, e);
+        return;
+    }
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "PAYMENT_FAILED",
+        None,
+        Some(serde_json::json!({"status": reason, "installment": installment["sequence"], "attempts": attempts, "retry_at": retry_at})),
+        "SYSTEM",
+        Some("Installment declined"),
+    ).await;
+    if retry_at.is_none() {
+        default_plan(state, order, schedule_id).await;
+    }
+}
+
+/// The plan is given up on: the order is cancelled and what was paid is
+/// refunded, less the cancellation fee on it
+async fn default_plan(state: &AppState, order: &Value, schedule_id: Uuid) {
+    let order_id = uuid_of(&order["id"]).unwrap_or_default();
+    match state.payment_schedule_repo.close_schedule(schedule_id, "DEFAULTED").await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::error!("Failed to default installment plan {}: {:?}", schedule_id, e);
+            return;
+        }
+    }
+    let schedule = match state.payment_schedule_repo.get_schedule(order_id).await {
+        Ok(Some(schedule)) => schedule,
+        other => {
+            tracing::error!("Defaulted installment plan {} but could not reload it: {:?}", schedule_id, other.err());
+            return;
+        }
+    };
+
+    let paid_nuc: i64 = schedule["installments"].as_array().into_iter().flatten()
+        .filter(|i| i["status"] == "PAID")
+        .filter_map(|i| i["amount_nuc"].as_i64())
+        .sum();
+    let total = Money::nuc(order["total_nuc"].as_i64().unwrap_or(0));
+    let paid = Money::nuc(paid_nuc);
+    let Ok(settlement) = installments::settle_default(paid, state.installments.cancellation_fee_percent) else { return };
+    let unpaid = total.checked_sub(paid).unwrap_or(total);
+    let reason = "Installment plan defaulted";
+
+    // 1. Pay back the customer's share first; the order is cancelled either way
+    let refund_status = if settlement.refund.is_positive() {
+        let currency = Currency::new(order["currency"].as_str().unwrap_or("NUC")).unwrap_or(Currency::NUC);
+        let key = format!("installment-default-{}", order_id.simple());
+        let refund = state.payments(order["test"].as_bool().unwrap_or(false))
+            .refund_payment(order_id, Money::new(settlement.refund.minor_units(), currency), &key)
+            .await;
+        Some(refund.unwrap_or_else(|e| {
+            tracing::error!("Refund of defaulted order {} failed: {}", order_id, e);
+            PaymentStatus::Failed
+        }))
+    } else {
+        None
+    };
+    let refunded = refund_status.as_ref().is_none_or(|status| *status == PaymentStatus::Succeeded);
+
+    // 2. Cancel the order and put its seats back on sale
+    if let Err(e) = state.order_repo.update_order_status(order_id, "CANCELLED").await {
+        tracing::error!("Failed to cancel defaulted order {}: {:?}", order_id, e);
+    }
+    let items: Vec<crate::orders::OrderItemResponse> = serde_json::from_value(order["items"].clone()).unwrap_or_default();
+    crate::orders::return_inventory(state, &items, true).await;
+    crate::suppliers::cancel_supplier_items(state, &items).await;
+
+    // 3. Unwind the receivable and book the fee, splitting the tax the same way
+    let weights = [unpaid, settlement.refund, settlement.retained].map(|amount| amount.minor_units().max(0) as u64);
+    if let Ok(tax) = crate::finance::order_tax(order).allocate(&weights) {
+        crate::finance::post_journal(state, JournalTransaction::installment_default(order_id, unpaid, tax[0], settlement.retained, tax[2])).await;
+        if settlement.refund.is_positive() && refunded {
+            crate::finance::post_journal(state, JournalTransaction::refund(order_id, None, settlement.refund, tax[1], reason)).await;
+            crate::documents::issue_for_order(state, order_id, "CREDIT_NOTE", settlement.refund.minor_units()).await;
+        }
+    }
+
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "INSTALLMENT_DEFAULTED",
+        Some(serde_json::json!({"status": "HOLD_PARTIALLY_PAID"})),
+        Some(serde_json::json!({
+            "status": "CANCELLED",
+            "schedule_id": schedule_id,
+            "paid_nuc": paid.minor_units(),
+            "refund_nuc": settlement.refund.minor_units(),
+            "retained_nuc": settlement.retained.minor_units(),
+            "refund_status": refund_status,
+        })),
+        "SYSTEM",
+        Some(reason),
+    ).await;
+
+    let event = serde_json::json!({
+        "event_type": "ORDER_CANCELLED",
+        "order_id": order_id,
+        "customer_id": order["customer_id"],
+        "customer_email": order["customer_email"],
+        "reason": reason,
+        "refunded_nuc": if refunded { settlement.refund.minor_units() } else { 0 },
+        "currency": order["currency"],
+        "timestamp": Utc::now().timestamp(),
+    });
+    if let Err(e) = crate::notifier::notify(state, &order_id.to_string(), &event).await {
+        tracing::warn!("Cancelled defaulted order {} but failed to notify the customer: {}", order_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payment_deadline_is_counted_back_from_the_first_departure() {
+        let flight = |departs: &str, status: &str| serde_json::json!({
+            "id": Uuid::new_v4(), "product_id": null, "product_type": "FLIGHT", "name": "SIN-BKK", "price_nuc": 25000,
+            "status": status, "revenue_status": "UNEARNED", "operating_carrier_id": null, "net_rate_nuc": null,
+            "commission_nuc": null, "metadata": {"departure_time": departs},
+        });
+        let order: OrderResponse = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(), "offer_id": null, "customer_id": "c1", "customer_email": null, "customer_did": null,
+            "status": "PROPOSED", "travelers": null, "contact_info": null, "total_nuc": 75000, "currency": "NUC",
+            "expires_at": null, "group_size": null, "names_due_at": null, "created_at": "2026-03-01T00:00:00Z",
+            "items": [
+                flight("2026-06-20T08:30:00Z", "ACTIVE"),
+                flight("2026-06-10T08:30:00Z", "ACTIVE"),
+                flight("2026-05-01T08:30:00Z", "CANCELLED"),
+            ],
+        })).unwrap();
+
+        let deadline = payment_deadline(&order, 14).unwrap();
+        assert_eq!(deadline.to_rfc3339(), "2026-05-27T08:30:00+00:00");
+
+        let no_flights = OrderResponse { items: vec![], ..order };
+        assert_eq!(payment_deadline(&no_flights, 14), None);
+    }
+}
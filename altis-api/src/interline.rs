@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use altis_core::iata::{
+    AirShoppingRequest, AirShoppingResponse, NdcOffer, NdcPrice, OneOrder, OneOrderResponse, OrderCreateRequest, Party, Sender,
+    ShoppingCriteria,
+};
+use altis_core::supplier::ndc::{self, NdcPartner};
+use altis_core::supplier::SupplierError;
+use altis_offer::models::{Offer, OfferItem};
+use altis_shared::money::{Currency, Money, MoneyError};
+use altis_store::app_config::{InterlineConfig, InterlinePartnerConfig};
+
+use crate::offers::AcceptOfferRequest;
+use crate::state::AppState;
+
+// ============================================================================
+// HTTP Partner
+// ============================================================================
+
+/// A partner carrier's NDC API, reached as JSON over HTTP
+pub struct HttpNdcPartner {
+    name: String,
+    carrier: String,
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+}
+
+impl HttpNdcPartner {
+    pub fn new(name: &str, config: &InterlinePartnerConfig, timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("NDC HTTP client builds with static settings");
+        Self {
+            name: name.to_string(),
+            carrier: config.carrier.to_uppercase(),
+            client,
+            url: config.url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+        }
+    }
+
+    async fn call<B: serde::Serialize, T: serde::de::DeserializeOwned>(&self, message: &str, body: &B) -> Result<T, SupplierError> {
+        let body = serde_json::to_string(body).map_err(|e| SupplierError::Rejected(e.to_string()))?;
+        let response = self.client.post(format!("{}/{}", self.url, message))
+            .bearer_auth(&self.api_key)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| SupplierError::Unavailable(e.to_string()))?;
+        let status = response.status();
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SupplierError::Unavailable(format!("HTTP {}", status)));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(SupplierError::Rejected(format!("HTTP {}: {}", status, body.chars().take(200).collect::<String>())));
+        }
+        let body = response.bytes().await.map_err(|e| SupplierError::Unavailable(e.to_string()))?;
+        serde_json::from_slice(&body).map_err(|e| SupplierError::Unavailable(format!("unreadable {} response: {}", message, e)))
+    }
+}
+
+#[async_trait]
+impl NdcPartner for HttpNdcPartner {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn carrier(&self) -> &str {
+        &self.carrier
+    }
+
+    async fn air_shopping(&self, request: &AirShoppingRequest) -> Result<AirShoppingResponse, SupplierError> {
+        self.call("airshopping", request).await
+    }
+
+    async fn order_create(&self, request: &OrderCreateRequest) -> Result<OneOrderResponse, SupplierError> {
+        self.call("ordercreate", request).await
+    }
+}
+
+// ============================================================================
+// Gateway
+// ============================================================================
+
+/// The configured partner carriers
+pub struct InterlineGateway {
+    partners: Vec<Box<dyn NdcPartner>>,
+    timeout: Duration,
+}
+
+impl InterlineGateway {
+    pub fn new(config: &InterlineConfig) -> Self {
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let partners = config.partners.iter()
+            .map(|(name, partner)| Box::new(HttpNdcPartner::new(name, partner, timeout)) as Box<dyn NdcPartner>)
+            .collect();
+        Self { partners, timeout }
+    }
+
+    fn partner(&self, name: &str) -> Option<&dyn NdcPartner> {
+        self.partners.iter().find(|p| p.name() == name).map(|p| p.as_ref())
+    }
+
+    /// Asks every partner at once. Partners that fail or miss the timeout
+    /// are left out rather than holding up the search.
+    pub async fn shop(&self, criteria: &ShoppingCriteria, search_context: &serde_json::Value) -> Vec<Offer> {
+        let request = AirShoppingRequest {
+            party: Party { sender: Sender { travel_agency: None } },
+            shopping_criteria: criteria.clone(),
+        };
+        let calls = self.partners.iter().map(|partner| {
+            let request = &request;
+            async move {
+                match tokio::time::timeout(self.timeout, partner.air_shopping(request)).await {
+                    Ok(Ok(response)) => {
+                        let response_id = response.response_id.clone();
+                        ndc::owned_offers(partner.carrier(), response).into_iter()
+                            .filter_map(|offer| {
+                                let offer_id = offer.offer_id.clone();
+                                partner_offer(partner.name(), &response_id, offer, search_context)
+                                    .map_err(|e| tracing::warn!("Skipping offer {} from interline partner {}: {}", offer_id, partner.name(), e))
+                                    .ok()
+                            })
+                            .collect()
+                    }
+                    Ok(Err(e)) => {
+                        tracing::warn!("Interline partner {} failed AirShopping: {}", partner.name(), e);
+                        Vec::new()
+                    }
+                    Err(_) => {
+                        tracing::warn!("Interline partner {} timed out on AirShopping", partner.name());
+                        Vec::new()
+                    }
+                }
+            }
+        });
+        futures_util::future::join_all(calls).await.into_iter().flatten().collect()
+    }
+}
+
+/// Our copy of a partner's offer. Items aren't catalog products, so they
+/// take no inventory; the partner's ids are kept for OrderCreate. Offers
+/// priced in anything but NUC are refused rather than read as NUC.
+fn partner_offer(partner: &str, response_id: &str, ndc_offer: NdcOffer, search_context: &serde_json::Value) -> Result<Offer, MoneyError> {
+    let nuc = |price: &NdcPrice| -> Result<Money, MoneyError> {
+        Money::new(price.amount as i64, Currency::new(&price.currency)?).require(Currency::NUC)
+    };
+
+    let mut offer = Offer::new(None, None, search_context.clone());
+    offer.currency = ndc_offer.total_price.currency.clone();
+    offer.metadata = serde_json::json!({
+        "owner": ndc_offer.owner,
+        "partner": partner,
+        "partner_offer_id": ndc_offer.offer_id,
+        "partner_response_id": response_id,
+    });
+    for item in &ndc_offer.items {
+        offer.add_item(OfferItem::new(
+            "FLIGHT".to_string(),
+            None,
+            None,
+            item.service_name.clone(),
+            None,
+            nuc(&item.price)?,
+            1,
+            serde_json::json!({
+                "owner": ndc_offer.owner,
+                "partner": partner,
+                "partner_item_id": item.item_id,
+            }),
+        ))?;
+    }
+    // The partner's total is what it will charge
+    offer.total = nuc(&ndc_offer.total_price)?;
+    Ok(offer)
+}
+
+/// Books an accepted partner offer with the partner that made it
+pub(crate) async fn create_partner_order(state: &AppState, offer: &Offer, req: &AcceptOfferRequest) -> Result<OneOrder, SupplierError> {
+    let partner_name = offer.metadata["partner"].as_str().unwrap_or_default();
+    let partner = state.interline.partner(partner_name)
+        .ok_or_else(|| SupplierError::Unavailable(format!("interline partner '{}' is not configured", partner_name)))?;
+    let request = OrderCreateRequest {
+        party: Party { sender: Sender { travel_agency: None } },
+        offer_id: offer.metadata["partner_offer_id"].as_str().unwrap_or_default().to_string(),
+        owner: partner.carrier().to_string(),
+        offer_item_ids: offer.items.iter()
+            .filter_map(|item| item.metadata["partner_item_id"].as_str().map(str::to_string))
+            .collect(),
+        travelers: req.travelers.clone(),
+        contact_info: req.contact_info.clone(),
+    };
+    Ok(partner.order_create(&request).await?.order)
+}
@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::middleware::auth::Principal;
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct OrderStatusResponse {
+    pub id: Uuid,
+    pub status: String,
+    pub total_nuc: i64,
+    pub currency: String,
+}
+
+/// POST /v1/internal/caches/invalidate
+/// Drop cached catalog data and search results after a change made outside the API
+pub async fn invalidate_caches(
+    State(state): State<AppState>,
+    Extension(principal): Extension<Principal>,
+) -> StatusCode {
+    tracing::info!("Caches invalidated by {:?}", principal);
+    state.catalog_changed("caches invalidated").await;
+    StatusCode::NO_CONTENT
+}
+
+/// GET /v1/internal/orders/{id}/status
+/// Order state for services that act on it; carries no passenger data
+pub async fn get_order_status(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<OrderStatusResponse>, StatusCode> {
+    let order = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(OrderStatusResponse {
+        id: order_id,
+        status: order["status"].as_str().unwrap_or_default().to_string(),
+        total_nuc: order["total_nuc"].as_i64().unwrap_or(0),
+        currency: order["currency"].as_str().unwrap_or("NUC").to_string(),
+    }))
+}
@@ -0,0 +1,331 @@
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use altis_core::email::{EmailError, EmailMessage};
+use altis_core::events::ORDER_PAID_TOPIC;
+use altis_core::tenant::TenantContext;
+use altis_order::invoice::format_amount;
+use altis_shared::models::events::OrderPaidEvent;
+use altis_shared::money::Money;
+use altis_store::app_config::{EmailConfig, KafkaConfig};
+use altis_store::consumer::{EventConsumer, EventHandler, HandlerError};
+
+use crate::authz::authorize_tenant_order;
+use crate::middleware::auth::AdminClaims;
+use crate::orders::OrderResponse;
+use crate::state::AppState;
+
+/// Kind of the confirmation sent once an order is paid
+const ITINERARY: &str = "ITINERARY";
+
+// ============================================================================
+// Consumer
+// ============================================================================
+
+/// Emails the itinerary of each order announced on `order.paid`. A provider
+/// that is down is retried with backoff, then the event is dead-lettered;
+/// either way the outcome is recorded on the order.
+pub async fn run_itinerary_email_consumer(state: AppState, kafka: KafkaConfig, config: EmailConfig, shutdown: watch::Receiver<bool>) {
+    if !config.consume {
+        return;
+    }
+    let consumer = match EventConsumer::new(&kafka, &config.consumer_group, ORDER_PAID_TOPIC, state.kafka.clone()) {
+        Ok(consumer) => consumer,
+        Err(e) => {
+            tracing::error!("Failed to start itinerary email consumer, paid orders get no confirmation: {}", e);
+            return;
+        }
+    };
+    consumer.run(ItineraryEmailHandler { state, provider: config.provider }, shutdown).await;
+}
+
+struct ItineraryEmailHandler {
+    state: AppState,
+    provider: String,
+}
+
+#[async_trait]
+impl EventHandler for ItineraryEmailHandler {
+    type Event = OrderPaidEvent;
+
+    async fn handle(&self, event: &OrderPaidEvent) -> Result<(), HandlerError> {
+        send_itinerary(&self.state, &self.provider, event.order_id).await
+    }
+}
+
+/// Sends the order's itinerary unless it already went out. Orders without
+/// an email address and sandbox orders are recorded as SKIPPED.
+pub(crate) async fn send_itinerary(state: &AppState, provider: &str, order_id: Uuid) -> Result<(), HandlerError> {
+    let retryable = |what: &str, e: Box<dyn std::error::Error + Send + Sync>| {
+        HandlerError::Retryable(format!("{} for order {}: {}", what, order_id, e))
+    };
+
+    let previous = state.email_repo.get_order_email(order_id, ITINERARY).await
+        .map_err(|e| retryable("email record", e))?;
+    if previous.is_some_and(|email| email["status"] == "SENT") {
+        return Ok(());
+    }
+
+    let order_json = state.order_repo.get_order(order_id).await
+        .map_err(|e| retryable("order", e))?
+        .ok_or_else(|| HandlerError::Permanent(format!("order {} not found", order_id)))?;
+    let order: OrderResponse = serde_json::from_value(order_json.clone())
+        .map_err(|e| HandlerError::Permanent(format!("unreadable order {}: {}", order_id, e)))?;
+
+    let recipient = recipient(&order);
+    let mut record = serde_json::json!({
+        "order_id": order_id,
+        "kind": ITINERARY,
+        "recipient": recipient,
+        "provider": provider,
+    });
+    let outcome = match &recipient {
+        _ if order.test => Err(EmailError::Rejected("sandbox order".to_string())),
+        None => Err(EmailError::Rejected("no email address on the order".to_string())),
+        Some(to) => state.email_sender.send(&render_itinerary(&order, &barcodes(&order_json), to)).await,
+    };
+    match &outcome {
+        Ok(message_id) => {
+            record["status"] = "SENT".into();
+            record["provider_message_id"] = message_id.as_str().into();
+        }
+        Err(_) if order.test || recipient.is_none() => record["status"] = "SKIPPED".into(),
+        Err(_) => record["status"] = "FAILED".into(),
+    }
+    if let Err(e) = &outcome {
+        record["error"] = e.to_string().into();
+    }
+    state.email_repo.record_order_email(&record).await.map_err(|e| retryable("recording email", e))?;
+
+    match outcome {
+        Ok(_) => {
+            tracing::info!("Itinerary for order {} emailed", order_id);
+            Ok(())
+        }
+        Err(EmailError::Unavailable(reason)) => Err(HandlerError::Retryable(format!("itinerary for order {}: {}", order_id, reason))),
+        // Sending again won't help; the failure is on the order for support
+        Err(EmailError::Rejected(reason)) => {
+            tracing::warn!("Itinerary for order {} not emailed: {}", order_id, reason);
+            Ok(())
+        }
+    }
+}
+
+/// The order's contact address, or the customer's own
+fn recipient(order: &OrderResponse) -> Option<String> {
+    order.contact_info.as_ref().map(|contact| contact.email.0.clone())
+        .or_else(|| order.customer_email.as_ref().map(|email| email.0.clone()))
+        .filter(|email| email.contains('@'))
+}
+
+/// Issued barcodes by order item, each traveler's labelled with their name;
+/// scheduled ones are still to come
+fn barcodes(order_json: &serde_json::Value) -> Vec<(Uuid, String)> {
+    let travelers = order_json["travelers"].as_array().cloned().unwrap_or_default();
+    let name_of = |index: i64| travelers.iter()
+        .find(|t| t["traveler_index"].as_i64() == Some(index))
+        .map(|t| format!("{} {}", t["first_name"].as_str().unwrap_or_default(), t["last_name"].as_str().unwrap_or_default()));
+    order_json["fulfillment"].as_array().into_iter().flatten()
+        .filter_map(|f| {
+            let item_id = f["order_item_id"].as_str().and_then(|id| Uuid::parse_str(id).ok())?;
+            let code = f["barcode"].as_str()?;
+            Some((item_id, match f["traveler_index"].as_i64().and_then(name_of) {
+                Some(name) => format!("{} ({})", code, name.trim()),
+                None => code.to_string(),
+            }))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Template
+// ============================================================================
+
+/// Short reference printed on the confirmation; the full order id below it
+/// remains what the order is looked up by
+pub fn booking_reference(order_id: Uuid) -> String {
+    order_id.simple().to_string()[..6].to_uppercase()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The itinerary confirmation: booking reference, travelers, flights and
+/// the barcodes issued so far, as plain text and HTML
+pub fn render_itinerary(order: &OrderResponse, barcodes: &[(Uuid, String)], to: &str) -> EmailMessage {
+    let reference = booking_reference(order.id);
+    // Every traveler's code for the item, when it has any
+    let barcode = |item_id: Uuid| {
+        let codes: Vec<&str> = barcodes.iter().filter(|(id, _)| *id == item_id).map(|(_, code)| code.as_str()).collect();
+        (!codes.is_empty()).then(|| codes.join(", "))
+    };
+    let text_field = |item: &crate::orders::OrderItemResponse, field: &str| item.metadata[field].as_str().unwrap_or("").to_string();
+
+    let travelers: Vec<String> = order.travelers.iter().flatten()
+        .map(|t| format!("{} {} ({})", t.first_name.0, t.last_name.0, t.ptc))
+        .collect();
+    let (flights, extras): (Vec<_>, Vec<_>) = order.items.iter()
+        .filter(|item| item.status != "CANCELLED")
+        .partition(|item| item.product_type.eq_ignore_ascii_case("FLIGHT"));
+    let flights: Vec<(String, String, Option<String>)> = flights.iter()
+        .map(|item| {
+            let route = format!("{} {} to {}", text_field(item, "flight_number"), text_field(item, "origin"), text_field(item, "destination"));
+            let times = format!("departs {}, arrives {}", text_field(item, "departure_time"), text_field(item, "arrival_time"));
+            (route.trim().to_string(), times, barcode(item.id))
+        })
+        .collect();
+    let extras: Vec<(String, Option<String>)> = extras.iter().map(|item| (item.name.clone(), barcode(item.id))).collect();
+    let total = format!("{} {}", format_amount(Money::from_nuc_i32(order.total_nuc)), order.currency);
+    let pending = "issued closer to departure";
+
+    let mut text = format!("Your booking is confirmed and paid.\n\nBooking reference: {}\nOrder: {}\n\nTravelers\n", reference, order.id);
+    for traveler in &travelers {
+        text.push_str(&format!("  {}\n", traveler));
+    }
+    text.push_str("\nFlights\n");
+    for (route, times, code) in &flights {
+        text.push_str(&format!("  {}, {}\n    Barcode: {}\n", route, times, code.as_deref().unwrap_or(pending)));
+    }
+    if !extras.is_empty() {
+        text.push_str("\nExtras\n");
+        for (name, code) in &extras {
+            text.push_str(&format!("  {}\n    Barcode: {}\n", name, code.as_deref().unwrap_or(pending)));
+        }
+    }
+    text.push_str(&format!("\nTotal paid: {}\n", total));
+
+    let mut html = format!(
+        "<h1>Your booking is confirmed</h1><p>Booking reference: <strong>{}</strong><br>Order: {}</p><h2>Travelers</h2><ul>",
+        reference, order.id,
+    );
+    for traveler in &travelers {
+        html.push_str(&format!("<li>{}</li>", escape(traveler)));
+    }
+    html.push_str("</ul><h2>Flights</h2><table>");
+    for (route, times, code) in &flights {
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td><td><code>{}</code></td></tr>", escape(route), escape(times), escape(code.as_deref().unwrap_or(pending))));
+    }
+    html.push_str("</table>");
+    if !extras.is_empty() {
+        html.push_str("<h2>Extras</h2><table>");
+        for (name, code) in &extras {
+            html.push_str(&format!("<tr><td>{}</td><td><code>{}</code></td></tr>", escape(name), escape(code.as_deref().unwrap_or(pending))));
+        }
+        html.push_str("</table>");
+    }
+    html.push_str(&format!("<p>Total paid: {}</p>", escape(&total)));
+
+    EmailMessage {
+        to: to.to_string(),
+        subject: format!("Your trip is confirmed: booking {}", reference),
+        text,
+        html,
+    }
+}
+
+// ============================================================================
+// Delivery Status
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderEmailResponse {
+    pub kind: String,
+    #[serde(serialize_with = "altis_shared::pii::email")]
+    pub recipient: Option<altis_shared::pii::Masked<String>>,
+    pub status: String,
+    pub provider: String,
+    pub provider_message_id: Option<String>,
+    pub error: Option<String>,
+    pub attempts: i32,
+    pub sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// GET /v1/admin/orders/:id/emails
+/// Emails sent about an order and how each delivery went, masked for the caller's role
+pub async fn list_order_emails(
+    State(state): State<AppState>,
+    Extension(claims): Extension<AdminClaims>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    authorize_tenant_order(&state, &tenant, order_id).await?;
+    let emails = state.email_repo.list_order_emails(order_id).await.map_err(|e| {
+        tracing::error!("Failed to list emails of order {}: {:?}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let emails: Vec<OrderEmailResponse> = emails.into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    altis_shared::pii::with_masking(&state.pii_policy.tier(&claims.role), || serde_json::to_value(&emails))
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_itinerary_lists_travelers_flights_and_barcodes() {
+        let (order_id, flight_id, bag_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let order_json = serde_json::json!({
+            "id": order_id,
+            "offer_id": null,
+            "customer_id": "cust-1",
+            "customer_email": "ada@example.com",
+            "customer_did": null,
+            "status": "PAID",
+            "items": [
+                {
+                    "id": flight_id, "product_id": null, "product_type": "FLIGHT", "name": "SQ318",
+                    "price_nuc": 45000, "status": "ACTIVE", "revenue_status": "UNEARNED",
+                    "operating_carrier_id": null, "net_rate_nuc": null, "commission_nuc": null,
+                    "metadata": {"flight_number": "SQ318", "origin": "SIN", "destination": "LHR",
+                                 "departure_time": "2026-11-02T23:35:00Z", "arrival_time": "2026-11-03T06:10:00Z"},
+                },
+                {
+                    "id": bag_id, "product_id": null, "product_type": "BAGGAGE", "name": "Extra bag <23kg>",
+                    "price_nuc": 5000, "status": "ACTIVE", "revenue_status": "UNEARNED",
+                    "operating_carrier_id": null, "net_rate_nuc": null, "commission_nuc": null, "metadata": {},
+                },
+            ],
+            "travelers": [{"id": null, "traveler_index": 0, "ptc": "ADT", "first_name": "Ada", "last_name": "Lovelace",
+                           "date_of_birth": null, "gender": null, "traveler_did": null, "metadata": null}],
+            "contact_info": null,
+            "total_nuc": 50000,
+            "currency": "NUC",
+            "expires_at": null,
+            "group_size": null,
+            "names_due_at": null,
+            "created_at": "2026-10-16T08:00:00Z",
+            "fulfillment": [
+                {"order_item_id": flight_id, "barcode": "ALTIS-FLIGHT", "traveler_index": 0},
+                {"order_item_id": bag_id, "barcode": null, "deliver_at": "2026-11-01T23:35:00Z"},
+            ],
+        });
+        let order: OrderResponse = serde_json::from_value(order_json.clone()).unwrap();
+        assert_eq!(recipient(&order).as_deref(), Some("ada@example.com"));
+
+        let email = render_itinerary(&order, &barcodes(&order_json), "ada@example.com");
+        let reference = booking_reference(order_id);
+        assert_eq!(reference.len(), 6);
+        assert!(email.subject.contains(&reference));
+        assert!(email.text.contains("Ada Lovelace (ADT)"));
+        assert!(email.text.contains("SQ318 SIN to LHR, departs 2026-11-02T23:35:00Z"));
+        assert!(email.text.contains("Barcode: ALTIS-FLIGHT (Ada Lovelace)"));
+        // The bag's code is scheduled, not issued yet
+        assert!(email.text.contains("Barcode: issued closer to departure"));
+        assert!(email.text.contains("Total paid: 500.00 NUC"));
+        assert!(email.html.contains("Extra bag &lt;23kg&gt;"));
+    }
+}
@@ -1,6 +1,6 @@
 extern crate altis_core;
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
     http::Method,
     extract::State,
@@ -11,13 +11,71 @@ use tower_http::trace::TraceLayer;
 use std::net::SocketAddr;
 
 pub mod auth;
+pub mod authz;
 pub mod state;
 pub mod search;
 pub mod error;
 pub mod offers;
+pub mod ancillary_offers;
+pub mod search_budget;
+pub mod search_validation;
+pub mod flights;
+pub mod seat_events;
+pub mod holds;
+pub mod health;
 pub mod orders;
+pub mod delivery;
+pub mod edifact;
 pub mod admin;
 pub mod finance;
+pub mod evidence;
+pub mod support;
+pub mod notes;
+pub mod itinerary_email;
+pub mod travel_alerts;
+pub mod upsell;
+pub mod documents;
+pub mod bulk_refund;
+pub mod bulk_pricing;
+pub mod campaigns;
+pub mod markets;
+pub mod chaos;
+pub mod catalog_cache;
+pub mod catalog_sync;
+pub mod warmup;
+pub mod experiments;
+pub mod customer_features;
+pub mod analytics;
+pub mod notifier;
+pub mod partner_webhooks;
+pub mod price_watch;
+pub mod low_fares;
+pub mod cart;
+pub mod suppliers;
+pub mod interline;
+pub mod flight_status;
+pub mod reaccommodation;
+pub mod schedule_changes;
+pub mod waitlist;
+pub mod seating;
+pub mod dcs;
+pub mod compensation;
+pub mod baggage;
+pub mod product_versions;
+pub mod snapshots;
+pub mod archive;
+pub mod scanning;
+pub mod sandbox;
+pub mod installments;
+pub mod payment_methods;
+pub mod profiles;
+pub mod payment_actions;
+pub mod disputes;
+pub mod retail_analytics;
+pub mod internal;
+pub mod grpc;
+pub mod graphql;
+pub mod preflight;
 pub mod middleware;
 use crate::middleware::resiliency::circuit_breaker_middleware;
 pub mod webhooks;
@@ -38,12 +96,23 @@ fn customer_routes(state: AppState) -> Router<AppState> {
     
     Router::new()
         .merge(auth_routes)
+        // Fulfillment / Service Delivery (gate and lounge scanners only)
+        .merge(
+            Router::new()
+                .route("/fulfillment/{barcode}/consume", post(orders::consume_fulfillment))
+                .route("/fulfillment/scans/batch", post(scanning::reconcile_scans))
+                .route("/fulfillment/scan-keys", get(scanning::get_scan_keys))
+                .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::auth::scanner_auth_middleware))
+        )
         // Protected Routes (Offers, Orders, Search)
         .merge(
             Router::new()
                 .merge(public_search) // Request: "this token use to search offers". So search MUST be protected.
                 // Offers
-                .route("/offers/search", post(offers::search_offers))
+                .route("/offers/search", post(offers::search_offers)
+                    .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::admission::search_admission_middleware)))
+                .route("/offers/lowfares/batch", post(low_fares::batch_low_fares))
+                .route("/offers/ancillaries", post(ancillary_offers::search_ancillary_offers))
                 .route("/offers/{id}", get(offers::get_offer).delete(offers::expire_offer))
                 .route("/offers/{id}/accept", post(offers::accept_offer))
                 
@@ -51,16 +120,68 @@ fn customer_routes(state: AppState) -> Router<AppState> {
                 .route("/orders", get(orders::list_orders))
                 .route("/orders/{id}", get(orders::get_order))
                 .route("/orders/{id}/pay", post(orders::pay_order))
+                .route("/orders/{id}/pay/confirm", post(payment_actions::confirm_payment))
+                .route("/orders/{id}/invoice", get(documents::get_order_invoice))
                 .route("/orders/{id}/payment-intent", post(orders::initialize_payment_intent))
+                .route("/orders/{id}/installments", get(installments::get_installment_plan).post(installments::create_installment_plan))
                 .route("/orders/{id}/reshop", post(orders::reshop_order))
+                .route("/orders/{id}/changes/preview", post(orders::preview_order_changes))
                 .route("/orders/{id}/customize", post(orders::customize_order))
+                .route("/orders/{id}/travelers/import", post(orders::import_travelers))
+                .route("/orders/{id}/protection", get(orders::get_protection_quote).post(orders::purchase_protection))
+                .route("/orders/{id}/upsell", get(upsell::get_upsell))
+                .route("/orders/{id}/upsell/{offer_item_id}/accept", post(upsell::accept_upsell))
+                .route("/orders/{id}/items/{item_id}/transfer", post(orders::transfer_item))
+                .route("/orders/{id}/items/{item_id}/refund", post(orders::refund_item_units))
                 .route("/orders/{id}/fulfillment", get(orders::get_fulfillment))
                 .route("/orders/{id}/cancel", post(orders::cancel_order))
                 .route("/orders/{id}/accept-reaccommodation", post(orders::accept_reaccommodation))
                 .route("/orders/{id}/involuntary-refund", post(orders::involuntary_refund))
+                .route("/orders/{id}/schedule-changes/{item_id}/accept", post(schedule_changes::accept_schedule_change))
+                .route("/orders/{id}/schedule-changes/{item_id}/decline", post(schedule_changes::decline_schedule_change))
+                .route("/orders/{id}/compensation-eligibility", get(compensation::get_compensation_eligibility))
+                .route("/orders/{id}/compensation", post(compensation::claim_compensation))
+                .route("/orders/{id}/baggage", get(baggage::get_order_baggage))
+                .route("/orders/{id}/notes", get(notes::list_customer_notes))
 
-                // Fulfillment / Service Delivery
-                .route("/fulfillment/{barcode}/consume", post(orders::consume_fulfillment))
+                // Trip builder carts
+                .route("/carts", post(cart::create_cart))
+                .route("/carts/{id}", get(cart::get_cart))
+                .route("/carts/{id}/offers", post(cart::add_cart_offer))
+                .route("/carts/{id}/offers/{offer_id}", delete(cart::remove_cart_offer))
+                .route("/carts/{id}/checkout", post(cart::checkout_cart))
+
+                // Profile and saved travelers
+                .route("/customers/me", get(profiles::get_profile).put(profiles::update_profile))
+                .route("/customers/me/travelers", get(profiles::list_saved_travelers).post(profiles::create_saved_traveler))
+                .route("/customers/me/travelers/{id}", put(profiles::update_saved_traveler).delete(profiles::delete_saved_traveler))
+
+                // Saved cards
+                .route("/customers/me/payment-methods", get(payment_methods::list_payment_methods).post(payment_methods::save_payment_method))
+                .route("/customers/me/payment-methods/{id}", delete(payment_methods::remove_payment_method))
+
+                // Fare alerts
+                .route("/price-watches", get(price_watch::list_price_watches).post(price_watch::create_price_watch))
+                .route("/price-watches/{id}", get(price_watch::get_price_watch).delete(price_watch::cancel_price_watch))
+
+                // Partner webhook test console (API-key callers only)
+                .route("/partners/me/webhooks/test", get(partner_webhooks::list_test_events).post(partner_webhooks::send_test_webhook))
+                .route("/partners/me/webhooks/deliveries", get(partner_webhooks::list_deliveries))
+                .route("/partners/me/webhooks/deliveries/{id}", get(partner_webhooks::get_delivery))
+                .route("/partners/me/webhooks/deliveries/{id}/redeliver", post(partner_webhooks::redeliver))
+
+                // Legacy GDS availability over EDIFACT (API-key callers only)
+                .route("/edifact/availability", post(edifact::availability))
+
+                // Flights
+                .route("/flights/{id}/stream", get(flights::stream_flight))
+                .route("/flights/{id}/waitlist", get(waitlist::get_waitlist_entry).post(waitlist::join_waitlist).delete(waitlist::leave_waitlist))
+
+                // Seat holds
+                .route("/holds/seat", delete(holds::release_seat))
+
+                // GraphQL over the offer and order handlers above
+                .route("/graphql", post(graphql::execute).layer(axum::Extension(graphql::schema())))
                 .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::auth::customer_auth_middleware))
         )
 }
@@ -69,13 +190,36 @@ fn customer_routes(state: AppState) -> Router<AppState> {
 // Admin Routes (/v1/admin/*)
 // ============================================================================
 
-fn admin_routes() -> Router<AppState> {
+fn admin_routes(state: AppState) -> Router<AppState> {
     Router::new()
         // Product Management
         .route("/airlines/{airline_id}/products", get(admin::list_products).post(admin::create_product))
+        .route("/airlines/{airline_id}/products/bulk-price-update", post(bulk_pricing::bulk_price_update))
+        .route("/bulk-price-updates/{token}/rollback", post(bulk_pricing::rollback_bulk_price_update))
         .route("/products/{id}", get(admin::get_product).put(admin::update_product).delete(admin::delete_product))
+        .route("/products/{id}/restore", post(admin::restore_product))
+        .route("/products/{id}/content", get(admin::list_product_content))
+        .route("/products/{id}/content/{locale}", put(admin::put_product_content).delete(admin::delete_product_content))
+        .route("/products/{id}/versions", get(product_versions::list_product_versions).post(product_versions::schedule_product_version))
+        .route("/products/{id}/versions/{version}", delete(product_versions::cancel_product_version))
+        .route("/inventory/{product_id}", get(admin::get_inventory))
+        .route("/airlines/{airline_id}/waitlists", get(waitlist::list_waitlists))
+        .route("/flights/{id}/waitlist", get(waitlist::get_flight_waitlist))
+        .route("/airlines/{airline_id}/inventory-rules", get(admin::list_inventory_rules).post(admin::create_inventory_rule))
+        .route("/inventory-rules/{id}", get(admin::get_inventory_rule).put(admin::update_inventory_rule).delete(admin::delete_inventory_rule))
+        .route("/airlines/{airline_id}/business-rules", get(admin::get_airline_business_rules).put(admin::put_airline_business_rules).delete(admin::delete_airline_business_rules))
         
+        // Tax Codes
+        .route("/tax-codes", get(admin::list_tax_codes).post(admin::create_tax_code))
+
+        // Routes and Markets
+        .route("/airports", get(markets::list_airports))
+        .route("/airlines/{airline_id}/routes", get(markets::list_routes).post(markets::create_route))
+        .route("/routes/{id}", get(markets::get_route).put(markets::update_route).delete(markets::delete_route))
+
         // Pricing Rules
+        .route("/airlines/{airline_id}/campaigns", get(campaigns::list_campaigns).post(campaigns::create_campaign))
+        .route("/campaigns/{id}", get(campaigns::get_campaign).put(campaigns::update_campaign).delete(campaigns::cancel_campaign))
         .route("/airlines/{airline_id}/pricing-rules", get(admin::list_pricing_rules).post(admin::create_pricing_rule))
         .route("/pricing-rules/{id}", get(admin::get_pricing_rule).put(admin::update_pricing_rule).delete(admin::delete_pricing_rule))
         
@@ -83,14 +227,96 @@ fn admin_routes() -> Router<AppState> {
         .route("/airlines/{airline_id}/bundles", get(admin::list_bundles).post(admin::create_bundle))
         .route("/bundles/{id}", get(admin::get_bundle).put(admin::update_bundle).delete(admin::delete_bundle))
 
+        // Catalog promotion between environments
+        .route("/airlines/{airline_id}/catalog/export", get(catalog_sync::export_catalog))
+        .route("/airlines/{airline_id}/catalog/diff", post(catalog_sync::diff_catalog))
+        .route("/airlines/{airline_id}/catalog/import", post(catalog_sync::import_catalog))
+
         // Disruption Management
-        .route("/disruptions", post(admin::trigger_disruption))
+        .route("/disruptions", get(admin::list_disruptions).post(admin::trigger_disruption))
+        .route("/disruptions/{id}", get(admin::get_disruption))
+
+        // Baggage (check-in and custody scans)
+        .route("/orders/{id}/baggage/check-in", post(baggage::check_in_bags))
+        .route("/baggage/{tag}/scan", post(baggage::scan_bag))
+
+        // Flight Removals (bulk cancel-and-refund)
+        .route("/bulk-refunds", post(bulk_refund::create_bulk_refund))
+        .route("/bulk-refunds/{id}", get(bulk_refund::get_bulk_refund))
+        .route("/bulk-refunds/{id}/failures", get(bulk_refund::list_bulk_refund_failures))
+        .route("/bulk-refunds/{id}/retry", post(bulk_refund::retry_bulk_refund))
+
+        // Ranking Experiments
+        .route("/experiments", get(experiments::list_experiments).post(experiments::create_experiment))
+        .route("/experiments/{id}/end", post(experiments::end_experiment))
+
+        // Chaos Injection (only effective where enabled in config)
+        .route("/chaos", get(chaos::get_chaos).delete(chaos::clear_all_chaos))
+        .route("/chaos/{target}", put(chaos::set_chaos_fault).delete(chaos::clear_chaos_fault))
+
+        // Sandbox (synthetic airline booked against with test API keys)
+        .route("/sandbox/reset", post(sandbox::reset_sandbox))
+
+        // Legal / Chargeback Evidence
+        .route("/orders/{id}/evidence-bundle", get(evidence::get_evidence_bundle))
+        .route("/orders/{id}/snapshot", get(snapshots::get_order_snapshot))
+
+        // Order Archive (closed orders in cold storage)
+        .route("/archived-orders", get(archive::find_archived_orders))
+        .route("/archived-orders/{id}", get(archive::get_archived_order))
+        .route("/archived-orders/{id}/restore", post(archive::restore_archived_order))
+
+        // Support Desk (role-masked order views)
+        .merge(
+            Router::new()
+                .route("/orders/{id}", get(support::get_order))
+                .route("/orders/{id}/unmask", post(support::unmask_order))
+                .route("/orders/{id}/notes", get(notes::list_notes).post(notes::add_note))
+                .route("/orders/{id}/emails", get(itinerary_email::list_order_emails))
+                .route("/orders/{id}/messages", get(travel_alerts::list_order_messages))
+                .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::auth::admin_auth_middleware))
+        )
         
+        // Marketing Analytics (aggregates only; identifiers are stored hashed)
+        .route("/analytics/attribution", get(analytics::get_attribution_report))
+
+        // Retail Analytics (daily rollups of offers and orders)
+        .route("/airlines/{airline_id}/analytics/funnel", get(retail_analytics::get_funnel))
+        .route("/airlines/{airline_id}/analytics/attach-rate", get(retail_analytics::get_attach_rates))
+
         // Finance / Settlement
         .route("/finance/orders/{id}/ledger", get(finance::get_order_ledger))
-        .route("/finance/airlines/{id}/settlement", get(finance::get_airline_settlement))
-        .route("/finance/airlines/{id}/export/swo", get(finance::export_swo))
-        .route("/finance/airlines/{id}/export/legacy", get(finance::export_legacy))
+        .route("/finance/orders/{id}/proration", get(finance::get_order_proration))
+        .route("/finance/airlines/{airline_id}/settlement", get(finance::get_airline_settlement))
+        .route("/finance/interline", get(finance::get_interline_report))
+        .route("/finance/orders/{id}/journal", get(finance::get_order_journal))
+        .route("/finance/trial-balance", get(finance::get_trial_balance))
+        .route("/finance/disputes", get(disputes::list_disputes))
+        .route("/finance/disputes/{id}", get(disputes::get_dispute))
+        .route("/finance/disputes/{id}/status", post(disputes::update_dispute_status))
+        .route("/airlines/{airline_id}/documents", get(documents::list_documents))
+        .route("/airlines/{airline_id}/documents/gaps", get(documents::get_sequence_gaps))
+        .route("/finance/airlines/{airline_id}/settlement/batches", get(finance::list_settlement_batches))
+        .route("/finance/settlement/batches", post(finance::run_settlement_batches))
+        .route("/finance/settlement/batches/{id}", get(finance::get_settlement_batch))
+        .route("/finance/settlement/batches/{id}/submit", post(finance::submit_settlement_batch))
+        .route("/finance/settlement/batches/{id}/confirm", post(finance::confirm_settlement_batch))
+        .route("/finance/airlines/{airline_id}/export/swo", get(finance::export_swo))
+        .route("/finance/airlines/{airline_id}/export/legacy", get(finance::export_legacy))
+        // Confines every admin request to the caller's airline
+        .route_layer(axum::middleware::from_fn_with_state(state, middleware::auth::tenant_middleware))
+}
+
+// ============================================================================
+// Internal Service Routes (/v1/internal/*)
+// ============================================================================
+
+fn internal_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/caches/invalidate", post(internal::invalidate_caches))
+        .route("/orders/{id}/status", get(internal::get_order_status))
+        // Only requests signed with a service key get through
+        .route_layer(axum::middleware::from_fn_with_state(state, middleware::service_auth_middleware))
 }
 
 // ============================================================================
@@ -106,6 +332,12 @@ pub fn app(state: AppState) -> Router {
             axum::http::header::AUTHORIZATION,
             axum::http::header::CONTENT_TYPE,
             axum::http::header::USER_AGENT,
+            axum::http::HeaderName::from_static("x-api-key"),
+            axum::http::HeaderName::from_static(middleware::deadline::REQUEST_TIMEOUT_HEADER),
+        ])
+        .expose_headers([
+            axum::http::HeaderName::from_static(middleware::deadline::PARTIAL_RESULT_HEADER),
+            axum::http::header::RETRY_AFTER,
         ]);
 
     Router::new()
@@ -113,7 +345,10 @@ pub fn app(state: AppState) -> Router {
         .nest("/v1", customer_routes(state.clone()))
         
         // Admin routes at /v1/admin/*
-        .nest("/v1/admin", admin_routes())
+        .nest("/v1/admin", admin_routes(state.clone()))
+
+        // Service-to-service routes at /v1/internal/*
+        .nest("/v1/internal", internal_routes(state.clone()))
         
         // Webhooks
         .route("/v1/webhooks/payments/stripe", post(webhooks::handle_stripe_webhook))
@@ -122,13 +357,20 @@ pub fn app(state: AppState) -> Router {
         .route("/v1/ndc/airshopping", post(v1::ndc::air_shopping))
         .route("/v1/oneorder/{id}", get(v1::oneorder::order_retrieve))
 
-        // Health check
-        .route("/health", get(health_check))
+        // QR links (authorized by the signed grant in the query string)
+        .route("/qr/{barcode}", get(orders::get_fulfillment_qr))
+
+        // Health checks (liveness / readiness; /health kept for existing probes)
+        .route("/health", get(health::ready))
+        .route("/health/live", get(health::live))
+        .route("/health/ready", get(health::ready))
         .route("/metrics", get(metrics_handler))
         
         // Middleware
         .layer(cors)
         .layer(TraceLayer::new_for_http())
+        // Inside the breakers, so requests that run out of time count as failures
+        .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::deadline::deadline_middleware))
         .layer(axum::middleware::from_fn_with_state(state.clone(), circuit_breaker_middleware))
         .layer(axum::middleware::from_fn_with_state(state.clone(), rate_limit_middleware))
         .with_state(state)
@@ -154,17 +396,6 @@ async fn rate_limit_middleware(
     }
 }
 
-// ============================================================================
-// Health Check
-// ============================================================================
-
-async fn health_check() -> impl IntoResponse {
-    axum::Json(serde_json::json!({
-        "status": "healthy",
-        "version": env!("CARGO_PKG_VERSION")
-    }))
-}
-
 async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
     use prometheus::{Encoder, TextEncoder, Registry, Gauge, Opts};
     
@@ -182,6 +413,19 @@ async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
 
     registry.register(Box::new(payment_failures.clone())).unwrap();
     registry.register(Box::new(ndc_failures.clone())).unwrap();
+
+    // Redis latency / error counters (shared collectors, registered per scrape)
+    registry.register(Box::new(state.redis.metrics().latency.clone())).unwrap();
+    registry.register(Box::new(state.redis.metrics().errors.clone())).unwrap();
+    registry.register(Box::new(state.search_cache.metrics().clone())).unwrap();
+    let (queued, in_flight, shed) = state.search_admission.metrics();
+    registry.register(Box::new(queued.clone())).unwrap();
+    registry.register(Box::new(in_flight.clone())).unwrap();
+    registry.register(Box::new(shed.clone())).unwrap();
+    let (channels, replayed, dropped) = state.seat_events.metrics();
+    registry.register(Box::new(channels.clone())).unwrap();
+    registry.register(Box::new(replayed.clone())).unwrap();
+    registry.register(Box::new(dropped.clone())).unwrap();
     
     encoder.encode(&registry.gather(), &mut buffer).unwrap();
     
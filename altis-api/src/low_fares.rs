@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use altis_store::app_config::LowFaresConfig;
+
+use crate::price_watch::is_airport_code;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct LowFareRoute {
+    pub origin: String,
+    pub destination: String,
+    /// Departure month, as YYYY-MM
+    pub month: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchLowFaresRequest {
+    pub routes: Vec<LowFareRoute>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LowFareResponse {
+    pub origin: String,
+    pub destination: String,
+    pub month: String,
+    /// Cheapest one-passenger offer departing in the month; None until the
+    /// aggregation job has priced it, or when nothing in the month is for sale
+    pub lowest_price_nuc: Option<i32>,
+    pub lowest_price_date: Option<NaiveDate>,
+    pub priced_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchLowFaresResponse {
+    pub fares: Vec<LowFareResponse>,
+}
+
+/// First day of a YYYY-MM month, if it's between this month and
+/// `max_months_ahead` months from now
+fn parse_month(month: &str, today: NaiveDate, max_months_ahead: u32) -> Option<NaiveDate> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    let this_month = today.with_day(1)?;
+    let last = this_month.checked_add_months(chrono::Months::new(max_months_ahead))?;
+    (this_month..=last).contains(&first).then_some(first)
+}
+
+/// The month's days still open for departure, from `today` on
+fn bookable_days(month: NaiveDate, today: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    month.max(today).iter_days().take_while(move |day| day.month() == month.month() && day.year() == month.year())
+}
+
+/// POST /v1/offers/lowfares/batch
+/// Cached "from" prices for many route-months at once; tuples not priced yet come back empty and are priced shortly
+pub async fn batch_low_fares(
+    State(state): State<AppState>,
+    Json(req): Json<BatchLowFaresRequest>,
+) -> Result<Json<BatchLowFaresResponse>, StatusCode> {
+    if req.routes.is_empty() || req.routes.len() > state.low_fares.max_batch {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let mut keys = Vec::with_capacity(req.routes.len());
+    for route in &req.routes {
+        if !is_airport_code(&route.origin) || !is_airport_code(&route.destination) || route.origin.eq_ignore_ascii_case(&route.destination) {
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        let month = parse_month(&route.month, today, state.low_fares.max_months_ahead)
+            .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+        keys.push((route.origin.to_uppercase(), route.destination.to_uppercase(), month));
+    }
+
+    let rows = state.low_fare_repo.request_low_fares(&keys).await.map_err(|e| {
+        tracing::error!("Failed to read low fares: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let cached: HashMap<(String, String, String), serde_json::Value> = rows.into_iter()
+        .map(|row| {
+            let key = (
+                row["origin"].as_str().unwrap_or_default().to_string(),
+                row["destination"].as_str().unwrap_or_default().to_string(),
+                row["month"].as_str().unwrap_or_default().to_string(),
+            );
+            (key, row)
+        })
+        .collect();
+
+    let fares = keys.into_iter().map(|(origin, destination, month)| {
+        let row = cached.get(&(origin.clone(), destination.clone(), month.to_string()));
+        let field = |key: &str| row.map(|r| r[key].clone()).unwrap_or_default();
+        LowFareResponse {
+            lowest_price_nuc: field("lowest_price_nuc").as_i64().map(|p| p as i32),
+            lowest_price_date: serde_json::from_value(field("lowest_price_date")).unwrap_or(None),
+            priced_at: serde_json::from_value(field("priced_at")).unwrap_or(None),
+            month: month.format("%Y-%m").to_string(),
+            origin,
+            destination,
+        }
+    }).collect();
+
+    Ok(Json(BatchLowFaresResponse { fares }))
+}
+
+/// Background job keeping the cached "from" prices fresh: each stale
+/// route-month is priced day by day through the offer pipeline.
+pub async fn run_low_fare_aggregator(state: AppState, config: LowFaresConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let stale = match state.low_fare_repo.claim_stale_low_fares(config.batch_size, config.refresh_seconds, config.retain_days).await {
+            Ok(stale) => stale,
+            Err(e) => {
+                tracing::error!("Failed to claim low fares: {:?}", e);
+                continue;
+            }
+        };
+        for route in stale {
+            let (Some(origin), Some(destination), Some(month)) = (
+                route["origin"].as_str(),
+                route["destination"].as_str(),
+                route["month"].as_str().and_then(|m| m.parse::<NaiveDate>().ok()),
+            ) else {
+                tracing::error!("Unreadable low fare route {}", route);
+                continue;
+            };
+            let lowest = lowest_fare(&state, origin, destination, month).await;
+            if let Err(e) = state.low_fare_repo.record_low_fare(origin, destination, month, lowest).await {
+                tracing::warn!("Failed to record low fare {}-{} {}: {:?}", origin, destination, month, e);
+            }
+        }
+    }
+}
+
+/// Cheapest one-passenger offer, and its day, over the month's remaining days
+async fn lowest_fare(state: &AppState, origin: &str, destination: &str, month: NaiveDate) -> Option<(i32, NaiveDate)> {
+    let mut lowest: Option<(i32, NaiveDate)> = None;
+    for date in bookable_days(month, chrono::Utc::now().date_naive()) {
+        let context = altis_offer::features::SearchContext {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            departure_date: date.to_string(),
+            passengers: 1,
+            children: 0,
+            infants: 0,
+            cabin_class: None,
+            user_segment: None,
+            customer: None,
+        };
+        let offers = match crate::offers::generate_offers(state, &context, crate::offers::CATALOG_AIRLINE).await {
+            Ok(offers) => offers,
+            Err(status) => {
+                tracing::warn!("Could not price {}-{} on {}: {}", origin, destination, date, status);
+                continue;
+            }
+        };
+        if let Some(cheapest) = offers.iter().filter_map(|o| o.total.to_i32().ok()).min() {
+            if lowest.is_none_or(|(price, _)| cheapest < price) {
+                lowest = Some((cheapest, date));
+            }
+        }
+    }
+    lowest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_months_and_remaining_days() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let first = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+
+        assert_eq!(parse_month("2026-10", today, 12), Some(first(2026, 10)));
+        assert_eq!(parse_month("2027-10", today, 12), Some(first(2027, 10)));
+        assert_eq!(parse_month("2027-11", today, 12), None, "beyond max_months_ahead");
+        assert_eq!(parse_month("2026-09", today, 12), None, "already over");
+        assert_eq!(parse_month("2026-13", today, 12), None);
+
+        let october: Vec<_> = bookable_days(first(2026, 10), today).collect();
+        assert_eq!(october.len(), 16);
+        assert_eq!(october.last(), Some(&NaiveDate::from_ymd_opt(2026, 10, 31).unwrap()));
+        assert_eq!(bookable_days(first(2027, 2), today).count(), 28);
+    }
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 use std::net::SocketAddr;
 use altis_api::{app, state::{AppState, AuthConfig, ResiliencyState}};
 use altis_api::middleware::resiliency::CircuitBreaker;
+use altis_api::middleware::key_cache::AuthKeyCache;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
@@ -14,55 +15,87 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let config = altis_store::app_config::Config::load().expect("Failed to load config");
-    tracing::info!("Starting Altis API on port {}", config.server.port);
-
-    // Redis Connection
-    let redis_client = altis_store::RedisClient::new(&config.redis.url)
-        .await
-        .expect("Failed to connect to Redis");
-    let redis_arc = Arc::new(redis_client);
+    // `--check` (or `--check-config`) runs the pre-flight checks, schema
+    // included, and exits instead of serving
+    let check_only = std::env::args().any(|arg| arg == "--check" || arg == "--check-config");
 
-    // Kafka Connection
-    let kafka_producer = altis_store::EventProducer::new(&config.kafka.brokers)
-        .expect("Failed to create Kafka producer");
-    let kafka_arc = Arc::new(kafka_producer);
+    let config = altis_store::app_config::Config::load().expect("Failed to load config");
+    if check_only {
+        tracing::info!("Checking configuration");
+    } else {
+        tracing::info!("Starting Altis API on port {}", config.server.port);
+    }
 
-    // SSE Broadcast Channel
-    let (sse_tx, _) = tokio::sync::broadcast::channel(100);
+    // Fault injection for resiliency testing (off unless enabled for this environment)
+    let chaos = Arc::new(altis_store::ChaosInjector::new(&config.chaos));
+    if chaos.is_enabled() {
+        tracing::warn!("Chaos injection is ENABLED; admins can inject faults into Redis, Postgres, payment and ML calls");
+    }
 
-    // Database Pool
-    let pool = sqlx::PgPool::connect(&config.database.url)
+    // Database Pools (primary + optional read replica)
+    let db = altis_store::DbClient::new(&config.database, chaos.clone())
         .await
         .expect("Failed to connect to Postgres");
 
-    // Run Migrations
-    sqlx::migrate!("../migrations")
-        .run(&pool)
-        .await
-        .expect("Failed to run database migrations");
+    // Migrations: applied unless this is a check or database.migrations is
+    // "verify", which only compare the schema. A schema that has drifted
+    // from this build stops startup before anything touches it; a check
+    // carries on to report the other problems too.
+    let schema = if check_only || config.database.migrations == "verify" {
+        db.schema_status().await
+    } else {
+        db.migrate().await
+    }
+    .expect("Failed to run database migrations");
+    let schema_problems = schema.problems();
+    if !check_only && !altis_api::preflight::report(&schema_problems) {
+        std::process::exit(1);
+    }
 
     // Repositories
-    let offer_repo = Arc::new(altis_store::StoreOfferRepository::new(pool.clone(), Arc::new(redis_arc.get_client())));
-    let order_repo = Arc::new(altis_store::StoreOrderRepository::new(pool.clone()));
-    let catalog_repo = Arc::new(altis_store::StoreProductRepository::new(pool.clone()));
+    let catalog_repo = Arc::new(altis_store::StoreProductRepository::new(db.clone()));
+    let experiment_repo = Arc::new(altis_store::StoreExperimentRepository::new(db.clone()));
+    let sequences = Arc::new(altis_store::SequenceAllocator::new(&config.documents));
+    let settlement_repo = Arc::new(altis_store::StoreSettlementRepository::new(db.clone(), sequences.clone()));
+    let document_repo = Arc::new(altis_store::StoreDocumentRepository::new(db.clone(), sequences));
+    let ledger_repo = Arc::new(altis_store::StoreLedgerRepository::new(db.clone()));
+    let bulk_refund_repo = Arc::new(altis_store::StoreBulkRefundRepository::new(db.clone()));
+    let customer_feature_repo = Arc::new(altis_store::StoreCustomerFeatureRepository::new(db.clone()));
+    let attribution_repo = Arc::new(altis_store::StoreAttributionRepository::new(db.clone()));
+    let price_watch_repo = Arc::new(altis_store::StorePriceWatchRepository::new(db.clone()));
+    let webhook_delivery_repo = Arc::new(altis_store::StoreWebhookDeliveryRepository::new(db.clone()));
+    let cart_repo = Arc::new(altis_store::StoreCartRepository::new(db.clone()));
+    let baggage_repo = Arc::new(altis_store::StoreBaggageRepository::new(db.clone()));
+    let disruption_repo = Arc::new(altis_store::StoreDisruptionRepository::new(db.clone()));
+    let low_fare_repo = Arc::new(altis_store::StoreLowFareRepository::new(db.clone()));
+    let payment_schedule_repo = Arc::new(altis_store::StorePaymentScheduleRepository::new(db.clone()));
+    let payment_method_repo = Arc::new(altis_store::StorePaymentMethodRepository::new(db.clone()));
+    let profile_repo = Arc::new(altis_store::StoreProfileRepository::new(db.clone()));
+    let note_repo = Arc::new(altis_store::StoreNoteRepository::new(db.clone()));
+    let dispute_repo = Arc::new(altis_store::StoreDisputeRepository::new(db.clone()));
+    let analytics_repo = Arc::new(altis_store::StoreAnalyticsRepository::new(db.clone()));
+    let email_repo = Arc::new(altis_store::StoreEmailRepository::new(db.clone()));
+    let message_repo = Arc::new(altis_store::StoreMessageRepository::new(db.clone()));
+    let upsell_repo = Arc::new(altis_store::StoreUpsellRepository::new(db.clone()));
+    let waitlist_repo = Arc::new(altis_store::StoreWaitlistRepository::new(db.clone()));
+    let archive_repo = Arc::new(altis_store::StoreArchiveRepository::new(db.clone()));
+    let blob_store = Arc::new(altis_store::FsBlobStore::new(&config.blob.root_dir));
+    let email_sender = altis_store::email::email_sender(&config.email).expect("Failed to set up the email provider");
 
     // AI/Telemetry
-    let telemetry = Arc::new(altis_offer::events::OfferTelemetry::new(&config.kafka.brokers, "offers"));
+    let telemetry = Arc::new(
+        altis_offer::events::OfferTelemetry::new(&config.kafka.brokers, "offers")
+            .with_training_topic(&config.ranking.training_topic)
+    );
     
+    // Connects lazily and reconnects on its own, so an ML service that is down
+    // at startup is picked up once it's back (the ranker falls back to rules meanwhile)
     let ml_client = if let Some(url) = &config.ranking.ml_service_url {
         match tonic::transport::Endpoint::from_shared(url.clone()) {
             Ok(endpoint) => {
-                match endpoint.connect().await {
-                    Ok(channel) => {
-                        tracing::info!("Connected to ML Ranking service at {}", url);
-                        Some(altis_offer::ai_ranker::ranking::ranking_service_client::RankingServiceClient::new(channel))
-                    },
-                    Err(e) => {
-                        tracing::error!("Failed to connect to ML service at {}: {}", url, e);
-                        None
-                    }
-                }
+                let channel = endpoint.connect_timeout(std::time::Duration::from_secs(2)).connect_lazy();
+                tracing::info!("ML Ranking service configured at {}", url);
+                Some(altis_offer::ai_ranker::ranking::ranking_service_client::RankingServiceClient::new(channel))
             },
             Err(e) => {
                 tracing::error!("Invalid ML service URL {}: {}", url, e);
@@ -73,15 +106,55 @@ async fn main() {
         None
     };
 
-    let ranker = Arc::new(tokio::sync::Mutex::new(altis_offer::ai_ranker::OfferRanker::new(
-        config.ranking.clone(),
-        Some(telemetry.clone()),
-        ml_client,
-    )));
+    let ml_strategy = altis_offer::strategy::MlStrategy::new(&config.ranking, ml_client).with_chaos(chaos.clone());
+    let ranker = Arc::new(
+        altis_offer::ai_ranker::OfferRanker::new(config.ranking.clone(), Some(telemetry.clone()))
+            .with_strategy(Arc::new(ml_strategy))
+    );
+
+    // Pre-flight: catch bad settings and dangling catalog references now rather than per request
+    let mut problems = schema_problems;
+    problems.extend(altis_api::preflight::run(&config, catalog_repo.as_ref(), experiment_repo.as_ref(), &ranker).await);
+    let config_ok = altis_api::preflight::report(&problems);
+    if check_only {
+        if config_ok {
+            tracing::info!("Configuration OK");
+        }
+        std::process::exit(if config_ok { 0 } else { 1 });
+    }
+    if !config_ok {
+        std::process::exit(1);
+    }
+
+    // Redis Connection
+    let redis_client = altis_store::RedisClient::new(&config.redis.url)
+        .await
+        .expect("Failed to connect to Redis")
+        .with_chaos(chaos.clone());
+    let redis_arc = Arc::new(redis_client);
+
+    // Kafka Connection
+    let kafka_producer = altis_store::EventProducer::new(&config.kafka.brokers)
+        .expect("Failed to create Kafka producer");
+    let kafka_arc = Arc::new(kafka_producer);
+
+    // Seat-hold SSE fan-out, a channel per flight
+    let seat_events = Arc::new(altis_api::seat_events::SeatEventHub::new(config.sse.clone()));
+
+    let offer_repo = Arc::new(altis_store::StoreOfferRepository::new(db.clone(), (*redis_arc).clone()));
+    let order_repo = Arc::new(altis_store::StoreOrderRepository::new(db.clone()));
+
+    // Search result cache
+    let search_cache = Arc::new(altis_store::SearchCache::new((*redis_arc).clone(), config.search.cache_ttl_seconds));
+    let inventory = Arc::new(altis_store::InventoryManager::new((*redis_arc).clone()));
+    let catalog_cache = Arc::new(altis_api::catalog_cache::CatalogCache::new(catalog_repo.clone(), config.search.catalog_ttl_seconds));
 
     // Payment Orchestration
-    let payment_adapter = Arc::new(altis_order::orchestrator::MockPaymentAdapter);
-    let payment_orchestrator = Arc::new(altis_order::orchestrator::PaymentOrchestrator::new(payment_adapter));
+    // "mock" is the only adapter in `PAYMENT_ADAPTERS`; pre-flight rejects anything else
+    let payment_adapter = Arc::new(altis_order::orchestrator::MockPaymentAdapter::default());
+    let payment_orchestrator = Arc::new(altis_order::orchestrator::PaymentOrchestrator::new(payment_adapter).with_chaos(chaos.clone()));
+    // Test orders always pay through the mock, whatever adapter is live
+    let sandbox_payments = Arc::new(altis_order::orchestrator::PaymentOrchestrator::new(Arc::new(altis_order::orchestrator::MockPaymentAdapter::default())).with_chaos(chaos.clone()));
 
     // One Identity
     let one_id_resolver = Arc::new(altis_core::identity::MockOneIdResolver);
@@ -93,25 +166,170 @@ async fn main() {
     });
 
     let app_state = AppState {
+        db,
         redis: redis_arc,
         kafka: kafka_arc,
-        sse_tx,
+        instance_id: uuid::Uuid::new_v4(),
+        seat_events,
         business_rules: config.business_rules.clone(),
+        refunds: config.refunds.clone(),
+        analytics: config.analytics.clone(),
+        attribution: config.attribution.clone(),
+        price_watch: config.price_watch.clone(),
+        low_fares: config.low_fares.clone(),
+        cart: config.cart.clone(),
+        reaccommodation: config.reaccommodation.clone(),
+        compensation: config.compensation.clone(),
+        deadlines: config.deadlines.clone(),
+        search: config.search.clone(),
+        sandbox: config.sandbox.clone(),
+        installments: config.installments.clone(),
+        upsell: config.upsell.clone(),
+        markets: config.markets.clone(),
+        schedule_changes: config.schedule_changes.clone(),
+        waitlist: config.waitlist.clone(),
+        payment: config.payment.clone(),
+        scanning: config.scanning.clone(),
         auth: AuthConfig {
-            secret: config.auth.jwt_secret.clone(),
+            keys: Arc::new(AuthKeyCache::from_secret(&config.auth.jwt_secret, &config.auth.api_keys).with_test_api_keys(&config.auth.test_api_keys)),
             expiration: config.auth.jwt_expiration_seconds,
+            qr_grant_ttl: config.auth.qr_grant_ttl_seconds,
+            service_keys: Arc::new(altis_api::middleware::service_auth::ServiceKeys::new(
+                config.auth.service_keys.clone(),
+                config.auth.service_signature_tolerance_seconds,
+            )),
+            scan_signer: altis_api::scanning::ScanSigner::from_config(&config.scanning)
+                .expect("Invalid scanning key pair")
+                .map(Arc::new),
         },
         offer_repo,
         order_repo,
         catalog_repo,
+        settlement_repo,
+        document_repo,
+        ledger_repo,
+        bulk_refund_repo,
+        experiment_repo,
+        customer_feature_repo,
+        attribution_repo,
+        price_watch_repo,
+        webhook_delivery_repo,
+        cart_repo,
+        baggage_repo,
+        disruption_repo,
+        low_fare_repo,
+        payment_schedule_repo,
+        payment_method_repo,
+        profile_repo,
+        note_repo,
+        dispute_repo,
+        analytics_repo,
+        email_repo,
+        message_repo,
+        upsell_repo,
+        waitlist_repo,
+        archive_repo,
+        blob_store,
+        email_sender,
+        pii_policy: Arc::new(altis_shared::pii::MaskingPolicy::default().with_overrides(config.pii.roles.clone())),
         telemetry,
         ranker,
+        search_cache,
+        search_admission: Arc::new(altis_api::middleware::admission::SearchAdmission::new(config.search_admission.clone())),
+        inventory,
+        catalog_cache,
+        webhooks: Arc::new(altis_api::partner_webhooks::WebhookSender::new(config.webhooks.clone())),
+        alerts: Arc::new(altis_api::travel_alerts::TravelAlerts::new(&config.messaging)),
+        edifact: Arc::new(altis_api::edifact::EdifactGateway::new(config.edifact.clone())),
+        suppliers: Arc::new(altis_api::suppliers::SupplierRegistry::new(&config.suppliers)),
+        interline: Arc::new(altis_api::interline::InterlineGateway::new(&config.interline)),
+        warmup: Arc::new(altis_api::warmup::Warmup::new()),
         payment_orchestrator,
+        sandbox_payments,
         one_id_resolver,
         resiliency,
+        chaos,
         api_base_url: config.server.base_url.clone(),
     };
 
+    // Fill caches before reporting ready, so a fresh deploy doesn't refill them from Postgres under load
+    tokio::spawn(altis_api::warmup::run_warmup(app_state.clone(), config.warmup.clone()));
+
+    // Scheduled fulfillment delivery
+    tokio::spawn(altis_api::delivery::run_delivery_worker(app_state.clone(), config.fulfillment.clone()));
+
+    // Nightly settlement batches
+    tokio::spawn(altis_api::finance::run_settlement_scheduler(app_state.clone(), config.settlement.clone()));
+
+    // Revenue of insurance and unused ancillaries, earned as cover starts or flights leave
+    tokio::spawn(altis_api::finance::run_revenue_recognition_worker(app_state.clone(), config.revenue_recognition.clone()));
+
+    // Ranking experiments started or ended through other instances
+    tokio::spawn(altis_api::experiments::run_experiment_refresher(app_state.clone()));
+
+    // Purchase-history features for personalized ranking
+    tokio::spawn(altis_api::customer_features::run_customer_features_refresher(app_state.clone(), config.personalization.clone()));
+
+    // Fare alerts on customer price watches
+    tokio::spawn(altis_api::price_watch::run_price_watch_worker(app_state.clone(), config.price_watch.clone()));
+    tokio::spawn(altis_api::waitlist::run_waitlist_worker(app_state.clone(), config.waitlist.clone()));
+
+    // "From" prices for the marketing site's route grid
+    tokio::spawn(altis_api::low_fares::run_low_fare_aggregator(app_state.clone(), config.low_fares.clone()));
+
+    // Scheduled product price changes coming into force
+    tokio::spawn(altis_api::product_versions::run_product_version_scheduler(app_state.clone()));
+
+    // Campaigns starting and ending on their dates
+    tokio::spawn(altis_api::campaigns::run_campaign_scheduler(app_state.clone()));
+
+    // Seat locks released as their trips expire
+    tokio::spawn(altis_api::holds::run_trip_expiry_listener(app_state.clone(), config.redis.expiry_notifications));
+
+    // Bulk cancel-and-refund jobs interrupted by the last shutdown
+    tokio::spawn(altis_api::bulk_refund::resume_bulk_refunds(app_state.clone()));
+
+    // Re-accommodation proposals nobody chose in time give their seats back
+    tokio::spawn(altis_api::reaccommodation::run_proposal_expiry_worker(app_state.clone()));
+
+    // Installments falling due are charged; a plan that keeps failing cancels its order
+    tokio::spawn(altis_api::installments::run_installment_worker(app_state.clone()));
+
+    // Payments left waiting on a 3-D Secure challenge past the deadline release their orders
+    tokio::spawn(altis_api::payment_actions::run_payment_action_timeout_worker(app_state.clone()));
+
+    // Check-in alerts by SMS, WhatsApp or email as flights come up for check-in
+    tokio::spawn(altis_api::travel_alerts::run_check_in_alert_worker(app_state.clone(), config.messaging.clone()));
+
+    // Seats for passengers who didn't choose one, ahead of departure
+    tokio::spawn(altis_api::seating::run_seat_assignment_worker(app_state.clone(), config.seating.clone()));
+
+    // Daily funnel and attach-rate stats for the admin analytics endpoints
+    tokio::spawn(altis_api::retail_analytics::run_analytics_rollup(app_state.clone()));
+
+    // Set on Ctrl-C / SIGTERM; Kafka consumers finish the event in hand and stop
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+    // Disruptions driven by flight status changes, from Kafka and the external feed
+    let flight_status_consumer = tokio::spawn(altis_api::flight_status::run_flight_status_consumer(app_state.clone(), config.kafka.clone(), config.flight_status.clone(), shutdown_rx.clone()));
+    tokio::spawn(altis_api::flight_status::run_flight_status_feed(app_state.clone(), config.flight_status.clone()));
+
+    // Catalog edits made through other instances drop our cached copy too
+    tokio::spawn(altis_api::catalog_cache::run_catalog_change_consumer(app_state.clone(), config.kafka.clone(), config.catalog_events.clone(), shutdown_rx.clone()));
+
+    // Checked-in passengers out to departure control, delivery confirmations back
+    tokio::spawn(altis_api::dcs::run_dcs_export_worker(app_state.clone(), config.dcs.clone()));
+    tokio::spawn(altis_api::dcs::run_dcs_delivery_consumer(app_state.clone(), config.kafka.clone(), config.dcs.clone(), shutdown_rx.clone()));
+
+    // Closed orders past retention out to cold storage
+    tokio::spawn(altis_api::archive::run_order_archival_worker(app_state.clone(), config.archival.clone()));
+
+    // Itinerary confirmations emailed once an order is paid
+    tokio::spawn(altis_api::itinerary_email::run_itinerary_email_consumer(app_state.clone(), config.kafka.clone(), config.email.clone(), shutdown_rx.clone()));
+
+    // gRPC API for internal clients, when enabled
+    tokio::spawn(altis_api::grpc::run_grpc_server(app_state.clone(), config.grpc.clone(), shutdown_rx.clone()));
+
     let app = app(app_state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
@@ -121,5 +339,34 @@ async fn main() {
     axum::serve(
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>()
-    ).await.unwrap();
+    )
+    .with_graceful_shutdown(shutdown_signal(shutdown_tx))
+    .await
+    .unwrap();
+
+    if tokio::time::timeout(std::time::Duration::from_secs(30), flight_status_consumer).await.is_err() {
+        tracing::warn!("Flight status consumer did not stop in time");
+    }
+}
+
+async fn shutdown_signal(shutdown: tokio::sync::watch::Sender<bool>) {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => { signal.recv().await; }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down");
+    let _ = shutdown.send(true);
 }
@@ -0,0 +1,255 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_catalog::{MarketType, RouteScopeError};
+use altis_shared::money::Currency;
+
+use crate::price_watch::is_airport_code;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct RouteRequest {
+    /// IATA airport codes; fixed once the route exists, so ignored on update
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    /// Worked out from the airports' coordinates when left out
+    pub distance_km: Option<i32>,
+    /// ISO 4217 code fares on the route are sold in
+    pub sale_currency: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RouteResponse {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    pub origin: String,
+    pub destination: String,
+    pub origin_city: Option<String>,
+    pub destination_city: Option<String>,
+    pub origin_country: String,
+    pub destination_country: String,
+    pub distance_km: i32,
+    pub market: MarketType,
+    pub sale_currency: String,
+    pub is_active: bool,
+}
+
+fn validate_route(req: &RouteRequest) -> Result<(), &'static str> {
+    if Currency::new(&req.sale_currency).is_err() {
+        return Err("sale_currency must be an ISO 4217 code");
+    }
+    if req.distance_km.is_some_and(|km| km <= 0) {
+        return Err("distance_km must be positive");
+    }
+    Ok(())
+}
+
+/// The route's distance as given, or else the great-circle distance between its airports
+fn route_distance(req: &RouteRequest, origin: &serde_json::Value, destination: &serde_json::Value) -> Option<i32> {
+    let coordinates = |airport: &serde_json::Value| Some((airport["latitude"].as_f64()?, airport["longitude"].as_f64()?));
+    req.distance_km.or_else(|| {
+        let km = altis_order::compensation::distance_km(coordinates(origin)?, coordinates(destination)?);
+        Some(km.round() as i32).filter(|km| *km > 0)
+    })
+}
+
+fn to_response(route: serde_json::Value) -> Result<RouteResponse, StatusCode> {
+    serde_json::from_value(route).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn airport(state: &AppState, code: &str) -> Result<Option<serde_json::Value>, StatusCode> {
+    state.catalog_repo.get_airport(code).await.map_err(|e| {
+        tracing::error!("Failed to look up airport {}: {:?}", code, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Checks the origin and destination a campaign or pricing rule is limited
+/// to against the airline's routes. An airline with no routes set up yet can
+/// still scope to any airport or city the catalog knows.
+pub(crate) async fn check_scope(
+    state: &AppState,
+    airline_id: Uuid,
+    origin: Option<&str>,
+    destination: Option<&str>,
+) -> Result<(), StatusCode> {
+    let network = state.catalog_cache.routes(airline_id).await.map_err(|e| {
+        tracing::error!("Failed to load routes for airline {}: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let outcome = if network.is_empty() {
+        let airports = state.catalog_repo.list_airports().await.map_err(|e| {
+            tracing::error!("Failed to list airports: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let known = |code: &str| airports.iter().any(|airport| {
+            ["iata_code", "city_code"].iter().any(|field| airport[field].as_str().is_some_and(|c| c.eq_ignore_ascii_case(code)))
+        });
+        match [origin, destination].into_iter().flatten().find(|code| !known(code)) {
+            Some(code) => Err(RouteScopeError::UnknownAirport(code.to_uppercase())),
+            None => Ok(()),
+        }
+    } else {
+        network.check_scope(origin, destination)
+    };
+    outcome.map_err(|reason| {
+        tracing::debug!("Rejected scope for airline {}: {}", airline_id, reason);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })
+}
+
+/// GET /v1/admin/airports
+/// Airports routes can be opened between, with their city and country
+pub async fn list_airports(State(state): State<AppState>) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let airports = state.catalog_repo.list_airports().await.map_err(|e| {
+        tracing::error!("Failed to list airports: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(airports))
+}
+
+/// POST /v1/admin/airlines/:airline_id/routes
+/// Open a route; its market follows from the countries of its airports
+pub async fn create_route(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Json(req): Json<RouteRequest>,
+) -> Result<(StatusCode, Json<RouteResponse>), StatusCode> {
+    let (Some(origin), Some(destination)) = (req.origin.as_deref(), req.destination.as_deref()) else {
+        tracing::debug!("Rejected route for airline {}: origin and destination are required", airline_id);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    };
+    let (origin, destination) = (origin.to_uppercase(), destination.to_uppercase());
+    let checked = validate_route(&req).and(if is_airport_code(&origin) && is_airport_code(&destination) && origin != destination {
+        Ok(())
+    } else {
+        Err("origin and destination must be two different airport codes")
+    });
+    if let Err(reason) = checked {
+        tracing::debug!("Rejected route for airline {}: {}", airline_id, reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let (Some(from), Some(to)) = (airport(&state, &origin).await?, airport(&state, &destination).await?) else {
+        tracing::debug!("Rejected route {}-{} for airline {}: unknown airport", origin, destination, airline_id);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    };
+    let Some(distance_km) = route_distance(&req, &from, &to) else {
+        tracing::debug!("Rejected route {}-{} for airline {}: no distance given or known", origin, destination, airline_id);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    };
+
+    let route = serde_json::json!({
+        "origin": origin,
+        "destination": destination,
+        "distance_km": distance_km,
+        "sale_currency": req.sale_currency.to_uppercase(),
+    });
+    let created = state.catalog_repo.create_route(airline_id, &route).await
+        .map_err(|e| {
+            tracing::error!("Failed to create route for airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::CONFLICT)?;
+    state.catalog_changed("route created").await;
+
+    Ok((StatusCode::CREATED, Json(to_response(created)?)))
+}
+
+/// GET /v1/admin/airlines/:airline_id/routes
+pub async fn list_routes(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<Json<Vec<RouteResponse>>, StatusCode> {
+    let routes = state.catalog_repo.list_routes(airline_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(routes.into_iter().map(to_response).collect::<Result<_, _>>()?))
+}
+
+/// GET /v1/admin/routes/:id
+pub async fn get_route(
+    State(state): State<AppState>,
+    Path(route_id): Path<Uuid>,
+) -> Result<Json<RouteResponse>, StatusCode> {
+    let route = state.catalog_repo.get_route(route_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(to_response(route)?))
+}
+
+/// PUT /v1/admin/routes/:id
+/// Change an active route's distance or currency of sale
+pub async fn update_route(
+    State(state): State<AppState>,
+    Path(route_id): Path<Uuid>,
+    Json(req): Json<RouteRequest>,
+) -> Result<Json<RouteResponse>, StatusCode> {
+    if let Err(reason) = validate_route(&req) {
+        tracing::debug!("Rejected update of route {}: {}", route_id, reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let current = state.catalog_repo.get_route(route_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let route = serde_json::json!({
+        "distance_km": req.distance_km.or(current["distance_km"].as_i64().map(|km| km as i32)),
+        "sale_currency": req.sale_currency.to_uppercase(),
+    });
+    let updated = state.catalog_repo.update_route(route_id, &route).await
+        .map_err(|e| {
+            tracing::error!("Failed to update route {}: {:?}", route_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    state.catalog_changed("route updated").await;
+
+    Ok(Json(to_response(updated)?))
+}
+
+/// DELETE /v1/admin/routes/:id
+/// Withdraw a route; searches on it price without a market from then on
+pub async fn delete_route(
+    State(state): State<AppState>,
+    Path(route_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let withdrawn = state.catalog_repo.deactivate_route(route_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !withdrawn {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.catalog_changed("route withdrawn").await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_distance() {
+        let request = |distance_km, sale_currency: &str| RouteRequest {
+            origin: Some("LHR".to_string()),
+            destination: Some("JFK".to_string()),
+            distance_km,
+            sale_currency: sale_currency.to_string(),
+        };
+        let lhr = serde_json::json!({"latitude": 51.47, "longitude": -0.4543});
+        let jfk = serde_json::json!({"latitude": 40.6413, "longitude": -73.7781});
+
+        assert!(validate_route(&request(None, "GBP")).is_ok());
+        assert!(validate_route(&request(None, "pounds")).is_err());
+        assert!(validate_route(&request(Some(0), "GBP")).is_err());
+
+        let km = route_distance(&request(None, "GBP"), &lhr, &jfk).unwrap();
+        assert!((5500..5600).contains(&km), "{}", km);
+        assert_eq!(route_distance(&request(Some(5555), "GBP"), &lhr, &jfk), Some(5555));
+        // Without coordinates the distance has to be given
+        assert_eq!(route_distance(&request(None, "GBP"), &lhr, &serde_json::json!({})), None);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use prometheus::{IntCounterVec, IntGauge, Opts};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use altis_store::app_config::SearchAdmissionConfig;
+use super::auth::Principal;
+use crate::state::AppState;
+
+/// Tenant shared by every signed-in customer and guest
+pub const PUBLIC_TENANT: &str = "public";
+
+/// Why a search was turned away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shed {
+    QueueFull,
+    Timeout,
+}
+
+impl Shed {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Shed::QueueFull => "queue_full",
+            Shed::Timeout => "timeout",
+        }
+    }
+}
+
+/// Held while a search runs; gives back the tenant's and the global slot
+pub struct AdmissionPermit {
+    _tenant: OwnedSemaphorePermit,
+    _global: OwnedSemaphorePermit,
+    in_flight: IntGauge,
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        self.in_flight.dec();
+    }
+}
+
+/// Bounded, per-tenant admission for offer search. A search first takes one
+/// of its tenant's slots, then a global one; both queues are FIFO, so a
+/// tenant can never have more searches competing for global capacity than
+/// its own limit, and a burst from one partner can't starve the others.
+pub struct SearchAdmission {
+    config: SearchAdmissionConfig,
+    global: Arc<Semaphore>,
+    tenants: Mutex<HashMap<String, Arc<Semaphore>>>,
+    queued: AtomicUsize,
+    queue_depth: IntGauge,
+    in_flight: IntGauge,
+    shed: IntCounterVec,
+}
+
+impl SearchAdmission {
+    pub fn new(config: SearchAdmissionConfig) -> Self {
+        let queue_depth = IntGauge::new("altis_search_admission_queued", "Searches waiting for a slot")
+            .expect("valid gauge definition");
+        let in_flight = IntGauge::new("altis_search_admission_in_flight", "Searches running under admission control")
+            .expect("valid gauge definition");
+        let shed = IntCounterVec::new(
+            Opts::new("altis_search_admission_shed_total", "Searches turned away, by reason and tenant"),
+            &["reason", "tenant"],
+        ).expect("valid counter definition");
+
+        Self {
+            global: Arc::new(Semaphore::new(config.max_concurrent)),
+            tenants: Mutex::new(HashMap::new()),
+            queued: AtomicUsize::new(0),
+            queue_depth,
+            in_flight,
+            shed,
+            config,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn retry_after_seconds(&self) -> u64 {
+        self.config.retry_after_seconds
+    }
+
+    pub fn metrics(&self) -> (&IntGauge, &IntGauge, &IntCounterVec) {
+        (&self.queue_depth, &self.in_flight, &self.shed)
+    }
+
+    fn tenant_slots(&self, tenant: &str) -> Arc<Semaphore> {
+        let mut tenants = self.tenants.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        tenants.entry(tenant.to_string())
+            .or_insert_with(|| {
+                let limit = self.config.partner_concurrency.get(tenant).copied().unwrap_or(self.config.tenant_concurrency);
+                Arc::new(Semaphore::new(limit))
+            })
+            .clone()
+    }
+
+    /// Waits up to `max_wait_ms` for a slot. Searches arriving while the queue
+    /// is full are shed straight away.
+    pub async fn admit(&self, tenant: &str) -> Result<AdmissionPermit, Shed> {
+        let tenant_slots = self.tenant_slots(tenant);
+
+        // Free capacity needs no queue slot
+        if let (Ok(tenant_permit), Ok(global_permit)) = (tenant_slots.clone().try_acquire_owned(), self.global.clone().try_acquire_owned()) {
+            return Ok(self.permit(tenant_permit, global_permit));
+        }
+
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.config.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            self.shed.with_label_values(&[Shed::QueueFull.as_str(), tenant]).inc();
+            return Err(Shed::QueueFull);
+        }
+        self.queue_depth.inc();
+
+        let wait = async {
+            let tenant_permit = tenant_slots.acquire_owned().await.expect("admission semaphores are never closed");
+            let global_permit = self.global.clone().acquire_owned().await.expect("admission semaphores are never closed");
+            (tenant_permit, global_permit)
+        };
+        let admitted = tokio::time::timeout(Duration::from_millis(self.config.max_wait_ms), wait).await;
+
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        self.queue_depth.dec();
+        match admitted {
+            Ok((tenant_permit, global_permit)) => Ok(self.permit(tenant_permit, global_permit)),
+            Err(_) => {
+                self.shed.with_label_values(&[Shed::Timeout.as_str(), tenant]).inc();
+                Err(Shed::Timeout)
+            }
+        }
+    }
+
+    fn permit(&self, tenant: OwnedSemaphorePermit, global: OwnedSemaphorePermit) -> AdmissionPermit {
+        self.in_flight.inc();
+        AdmissionPermit { _tenant: tenant, _global: global, in_flight: self.in_flight.clone() }
+    }
+}
+
+/// Runs offer search under admission control when it's enabled; a shed search
+/// gets a 503 with Retry-After
+pub async fn search_admission_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let admission = &state.search_admission;
+    if !admission.enabled() {
+        return next.run(req).await;
+    }
+
+    let tenant = match req.extensions().get::<Principal>() {
+        Some(Principal::Partner(partner)) => partner.clone(),
+        _ => PUBLIC_TENANT.to_string(),
+    };
+    match admission.admit(&tenant).await {
+        Ok(_permit) => next.run(req).await,
+        Err(shed) => {
+            tracing::warn!("Shed search from {} ({:?})", tenant, shed);
+            let retry_after = admission.retry_after_seconds();
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(serde_json::json!({
+                    "error": "Search capacity exhausted, retry shortly",
+                    "retry_after_seconds": retry_after,
+                })),
+            ).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tenants_are_limited_and_bursts_shed() {
+        let admission = SearchAdmission::new(SearchAdmissionConfig {
+            enabled: true,
+            max_concurrent: 3,
+            tenant_concurrency: 1,
+            partner_concurrency: HashMap::from([("acme".to_string(), 2)]),
+            max_queued: 1,
+            max_wait_ms: 20,
+            retry_after_seconds: 1,
+        });
+
+        let _a = admission.admit("acme").await.unwrap();
+        let _b = admission.admit("acme").await.unwrap();
+        // acme is at its limit, but another tenant still gets in
+        let public = admission.admit(PUBLIC_TENANT).await.unwrap();
+        assert_eq!(admission.in_flight.get(), 3);
+
+        // One search may queue; it times out since nothing frees up
+        let queued = admission.admit("acme");
+        let shed = admission.admit(PUBLIC_TENANT);
+        let (queued, shed) = tokio::join!(queued, shed);
+        assert_eq!(queued.err(), Some(Shed::Timeout));
+        assert_eq!(shed.err(), Some(Shed::QueueFull));
+        assert_eq!(admission.queue_depth.get(), 0);
+
+        drop(public);
+        assert!(admission.admit(PUBLIC_TENANT).await.is_ok());
+    }
+}
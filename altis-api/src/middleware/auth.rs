@@ -1,9 +1,9 @@
+use altis_core::tenant::TenantContext;
 use axum::{
-    extract::{Request, State},
+    extract::{rejection::RawPathParamsRejection, RawPathParams, Request, State},
     middleware::Next,
     response::Response,
 };
-use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -19,6 +19,9 @@ pub struct CustomerClaims {
     pub email: Option<String>,
     pub role: String,
     pub exp: usize,
+    /// Sandbox caller: books the sandbox airline and pays through the mock adapter
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub test: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -31,6 +34,39 @@ pub struct AdminClaims {
     pub exp: usize,
 }
 
+impl AdminClaims {
+    /// Staff of an airline are confined to it; staff without one work across the platform
+    pub fn tenant(&self) -> TenantContext {
+        self.airline_id.map_or(TenantContext::platform(), TenantContext::airline)
+    }
+}
+
+/// Header carrying partner API keys on customer routes.
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// Who a request acts for, as established by the auth middlewares. Customer
+/// routes see customers and partners; internal routes only see services.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Principal {
+    /// Signed-in customer or guest, by token subject
+    Customer(String),
+    /// Partner integration, by its `auth.api_keys` name
+    Partner(String),
+    /// Internal service, by its `auth.service_keys` name
+    Service(String),
+}
+
+fn bearer_token(req: &Request) -> Result<&str, AppError> {
+    let auth_header = req.headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .ok_or(AppError::AuthenticationError("Missing or invalid Authorization header".to_string()))?;
+
+    auth_header
+        .strip_prefix("Bearer ")
+        .ok_or(AppError::AuthenticationError("Invalid token format".to_string()))
+}
+
 // ============================================================================
 // Customer Authentication Middleware
 // ============================================================================
@@ -40,68 +76,153 @@ pub async fn customer_auth_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    // 1. Extract token from Authorization header
-    let auth_header = req.headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .ok_or(AppError::AuthenticationError("Missing or invalid Authorization header".to_string()))?;
-    
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .ok_or(AppError::AuthenticationError("Invalid token format".to_string()))?;
+    // 0. Service credentials are scoped to internal routes
+    if req.headers().contains_key(super::service_auth::SERVICE_SIGNATURE_HEADER) {
+        return Err(AppError::AuthorizationError("Service credentials are not accepted here".to_string()));
+    }
+
+    let api_key = req.headers().get(API_KEY_HEADER).and_then(|h| h.to_str().ok()).map(str::to_string);
+    let token = match api_key {
+        Some(_) => None,
+        None => Some(bearer_token(&req)?.to_string()),
+    };
+    let (principal, claims) = customer_claims(&state, api_key.as_deref(), token.as_deref()).await?;
+
+    // Inject claims into request extensions
+    req.extensions_mut().insert(principal);
+    req.extensions_mut().insert(claims);
     
-    // 2. Decode and validate JWT
-    let token_data = decode::<CustomerClaims>(
-        token,
-        &DecodingKey::from_secret(state.auth.secret.as_bytes()),
-        &Validation::default(),
-    ).map_err(|_| AppError::AuthenticationError("Invalid or expired token".to_string()))?;
+    Ok(next.run(req).await)
+}
+
+/// Who a customer-facing call acts for: a partner by API key, or a customer
+/// or guest by bearer token. Shared by the REST middleware and the gRPC API.
+pub(crate) async fn customer_claims(state: &AppState, api_key: Option<&str>, token: Option<&str>) -> Result<(Principal, CustomerClaims), AppError> {
+    // 1. Partner integrations authenticate with an API key instead of a JWT;
+    //    sandbox keys act for the same partner in test mode
+    if let Some(api_key) = api_key {
+        let (partner, test) = match state.auth.keys.verify_api_key(api_key) {
+            Some(partner) => (partner, false),
+            None => state.auth.keys.verify_test_api_key(api_key)
+                .map(|partner| (partner, true))
+                .ok_or(AppError::AuthenticationError("Invalid API key".to_string()))?,
+        };
+
+        let claims = CustomerClaims {
+            // Sandbox bookings are kept apart from the partner's live ones
+            sub: if test { format!("partner:{}:test", partner) } else { format!("partner:{}", partner) },
+            email: None,
+            role: "PARTNER".to_string(),
+            exp: 0, // API keys don't expire; they are revoked through config
+            test,
+        };
+        return Ok((Principal::Partner(partner.to_string()), claims));
+    }
+
+    // 2. Decode and validate JWT against the cached keys
+    let token = token.ok_or(AppError::AuthenticationError("Missing or invalid Authorization header".to_string()))?;
+    let token_data = state.auth.keys.decode::<CustomerClaims>(token).await?;
     
     // 3. Check role is CUSTOMER or GUEST
     if token_data.claims.role != "CUSTOMER" && token_data.claims.role != "GUEST" {
         return Err(AppError::AuthorizationError("Insufficient permissions".to_string()));
     }
     
-    // 4. Inject claims into request extensions
-    req.extensions_mut().insert(token_data.claims);
-    
-    Ok(next.run(req).await)
+    Ok((Principal::Customer(token_data.claims.sub.clone()), token_data.claims))
 }
 
 // ============================================================================
 // Admin Authentication Middleware
 // ============================================================================
 
+/// Decodes a back-office token; other roles are refused
+async fn admin_claims(state: &AppState, token: &str) -> Result<AdminClaims, AppError> {
+    let token_data = state.auth.keys.decode::<AdminClaims>(token).await?;
+    
+    // Support agents count too; they see masked data
+    if !matches!(token_data.claims.role.as_str(), "ADMIN" | "SUPER_ADMIN" | "SUPPORT") {
+        return Err(AppError::AuthorizationError("Insufficient permissions".to_string()));
+    }
+    
+    Ok(token_data.claims)
+}
+
 pub async fn admin_auth_middleware(
     State(state): State<AppState>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, AppError> {
     // 1. Extract token
-    let auth_header = req.headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .ok_or(AppError::AuthenticationError("Missing or invalid Authorization header".to_string()))?;
+    let token = bearer_token(&req)?.to_string();
     
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .ok_or(AppError::AuthenticationError("Invalid token format".to_string()))?;
+    // 2. Decode JWT and check the role
+    let claims = admin_claims(&state, &token).await?;
     
-    // 2. Decode JWT
-    let token_data = decode::<AdminClaims>(
-        token,
-        &DecodingKey::from_secret(state.auth.secret.as_bytes()),
-        &Validation::default(),
-    ).map_err(|_| AppError::AuthenticationError("Invalid or expired token".to_string()))?;
+    // 3. Inject claims and the tenant they confine the caller to
+    req.extensions_mut().insert(claims.tenant());
+    req.extensions_mut().insert(claims);
     
-    // 3. Check role is ADMIN or SUPER_ADMIN
-    if token_data.claims.role != "ADMIN" && token_data.claims.role != "SUPER_ADMIN" {
-        return Err(AppError::AuthorizationError("Insufficient permissions".to_string()));
+    Ok(next.run(req).await)
+}
+
+// ============================================================================
+// Scanner Authentication Middleware
+// ============================================================================
+
+/// Gate and lounge scanners: back-office tokens with the SCANNER role, one
+/// per device, or staff. Customer tokens and partner API keys are refused.
+pub async fn scanner_auth_middleware(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let token = bearer_token(&req)?.to_string();
+    let token_data = state.auth.keys.decode::<AdminClaims>(&token).await?;
+    if !matches!(token_data.claims.role.as_str(), "SCANNER" | "ADMIN" | "SUPER_ADMIN") {
+        return Err(AppError::AuthorizationError("Scanner credentials required".to_string()));
     }
-    
-    // 4. Inject claims
+
+    req.extensions_mut().insert(token_data.claims.tenant());
     req.extensions_mut().insert(token_data.claims);
-    
+    Ok(next.run(req).await)
+}
+
+// ============================================================================
+// Tenant Scoping Middleware
+// ============================================================================
+
+/// Establishes the `TenantContext` of every admin request. A back-office
+/// token tied to an airline confines the request to that airline, and a
+/// route naming any other `airline_id` is refused outright. Requests without
+/// a token are left to whatever guards the admin API upstream and act for
+/// the platform.
+pub async fn tenant_middleware(
+    State(state): State<AppState>,
+    params: Result<RawPathParams, RawPathParamsRejection>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let tenant = if req.headers().contains_key("Authorization") {
+        let token = bearer_token(&req)?.to_string();
+        let claims = admin_claims(&state, &token).await?;
+        let tenant = claims.tenant();
+        req.extensions_mut().insert(claims);
+        tenant
+    } else {
+        TenantContext::platform()
+    };
+
+    let named_airline = params.ok().and_then(|params| {
+        params.iter().find(|(key, _)| *key == "airline_id").map(|(_, value)| value.to_string())
+    });
+    if let Some(airline_id) = named_airline {
+        let airline_id = Uuid::parse_str(&airline_id)
+            .map_err(|_| AppError::ValidationError("Invalid airline_id".to_string()))?;
+        crate::authz::authorize_airline(&tenant, airline_id)
+            .map_err(|_| AppError::AuthorizationError("Airline is outside the caller's tenant".to_string()))?;
+    }
+
+    req.extensions_mut().insert(tenant);
     Ok(next.run(req).await)
 }
 
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use altis_store::app_config::DeadlinesConfig;
+use altis_store::deadline::Deadline;
+use crate::state::AppState;
+
+/// Client-requested budget in milliseconds ("2500", "2500ms" or "3s")
+pub const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout";
+
+/// Set on successful responses assembled without part of their work, e.g.
+/// offers ranked by rules because the ML call ran out of time
+pub const PARTIAL_RESULT_HEADER: &str = "x-partial-result";
+
+/// The request's budget: the header if it parses, capped at `max_ms`,
+/// otherwise the configured default.
+pub fn budget(headers: &HeaderMap, config: &DeadlinesConfig) -> Duration {
+    let requested = headers.get(REQUEST_TIMEOUT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_timeout);
+    let ms = requested.unwrap_or(config.default_ms).min(config.max_ms);
+    Duration::from_millis(ms)
+}
+
+fn parse_timeout(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let ms = match value.strip_suffix("ms") {
+        Some(ms) => ms.trim().parse().ok()?,
+        None => match value.strip_suffix('s') {
+            Some(secs) => secs.trim().parse::<u64>().ok()?.checked_mul(1000)?,
+            None => value.parse().ok()?,
+        },
+    };
+    (ms > 0).then_some(ms)
+}
+
+/// Runs the request under its deadline. Postgres, Redis and gRPC calls made
+/// on its behalf stop waiting when it passes; a request that overruns, or
+/// fails because a dependency was cut off, is answered with a 504.
+pub async fn deadline_middleware(
+    State(state): State<AppState>,
+    req: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let budget = budget(req.headers(), &state.deadlines);
+    let deadline = Deadline::after(budget);
+
+    match tokio::time::timeout_at(deadline.at(), deadline.scope(next.run(req))).await {
+        Ok(response) if response.status() == StatusCode::INTERNAL_SERVER_ERROR && deadline.is_expired() => {
+            deadline_exceeded(budget)
+        }
+        Ok(response) => response,
+        Err(_) => deadline_exceeded(budget),
+    }
+}
+
+fn deadline_exceeded(budget: Duration) -> Response {
+    (
+        StatusCode::GATEWAY_TIMEOUT,
+        Json(serde_json::json!({
+            "error": "Request deadline exceeded",
+            "timeout_ms": budget.as_millis() as u64,
+            // Nothing was returned; work already committed (e.g. an order) stands
+            "partial": false,
+        })),
+    ).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_is_bounded() {
+        let config = DeadlinesConfig { default_ms: 10_000, max_ms: 30_000 };
+        let with = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(REQUEST_TIMEOUT_HEADER, value.parse().unwrap());
+            budget(&headers, &config)
+        };
+
+        assert_eq!(budget(&HeaderMap::new(), &config), Duration::from_secs(10));
+        assert_eq!(with("2500"), Duration::from_millis(2500));
+        assert_eq!(with("750ms"), Duration::from_millis(750));
+        assert_eq!(with("3s"), Duration::from_secs(3));
+        assert_eq!(with("120s"), Duration::from_secs(30), "capped at max_ms");
+        assert_eq!(with("0"), Duration::from_secs(10));
+        assert_eq!(with("soon"), Duration::from_secs(10));
+    }
+}
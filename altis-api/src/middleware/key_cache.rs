@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tokio::sync::Mutex;
+
+use crate::error::AppError;
+
+/// Key id used for tokens signed with the configured HMAC secret.
+pub const DEFAULT_KID: &str = "altis-hs256";
+
+/// Minimum gap between two refreshes triggered by unknown `kid`s, so a flood of
+/// forged tokens cannot turn into a flood of JWKS fetches.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// Key Sources
+// ============================================================================
+
+/// Supplies the verification keys, indexed by `kid`.
+/// The static HMAC secret is one source; a JWKS endpoint is another.
+#[async_trait]
+pub trait KeySource: Send + Sync {
+    async fn fetch_keys(&self) -> Result<HashMap<String, DecodingKey>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+pub struct StaticSecretSource {
+    secret: String,
+}
+
+impl StaticSecretSource {
+    pub fn new(secret: &str) -> Self {
+        Self { secret: secret.to_string() }
+    }
+}
+
+#[async_trait]
+impl KeySource for StaticSecretSource {
+    async fn fetch_keys(&self) -> Result<HashMap<String, DecodingKey>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut keys = HashMap::new();
+        keys.insert(DEFAULT_KID.to_string(), DecodingKey::from_secret(self.secret.as_bytes()));
+        Ok(keys)
+    }
+}
+
+// ============================================================================
+// Auth Key Cache
+// ============================================================================
+
+/// Holds decoded verification keys and the signing key in memory so the auth
+/// middleware never rebuilds them per request. Unknown `kid`s trigger a
+/// throttled refresh from the `KeySource` (key rotation).
+pub struct AuthKeyCache {
+    source: Arc<dyn KeySource>,
+    keys: RwLock<HashMap<String, Arc<DecodingKey>>>,
+    last_refresh: Mutex<Option<Instant>>,
+    validation: Validation,
+    encoding_key: EncodingKey,
+    api_keys: Vec<(String, [u8; 32])>,
+    test_api_keys: Vec<(String, [u8; 32])>,
+}
+
+fn key_digests(keys: &HashMap<String, String>) -> Vec<(String, [u8; 32])> {
+    keys.iter()
+        .map(|(name, key)| (name.clone(), Sha256::digest(key.as_bytes()).into()))
+        .collect()
+}
+
+/// Every key is compared in constant time over its SHA-256 digest, so neither
+/// the match position nor the key length leaks through timing
+fn find_key<'a>(keys: &'a [(String, [u8; 32])], presented: &str) -> Option<&'a str> {
+    let digest: [u8; 32] = Sha256::digest(presented.as_bytes()).into();
+    let mut matched = None;
+
+    for (name, expected) in keys {
+        if bool::from(expected.ct_eq(&digest)) {
+            matched = Some(name.as_str());
+        }
+    }
+
+    matched
+}
+
+impl AuthKeyCache {
+    /// Builds a cache backed by the static HMAC secret, with the keys pre-loaded.
+    pub fn from_secret(secret: &str, api_keys: &HashMap<String, String>) -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(DEFAULT_KID.to_string(), Arc::new(DecodingKey::from_secret(secret.as_bytes())));
+
+        Self {
+            source: Arc::new(StaticSecretSource::new(secret)),
+            keys: RwLock::new(keys),
+            last_refresh: Mutex::new(Some(Instant::now())),
+            validation: Validation::default(),
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            api_keys: key_digests(api_keys),
+            test_api_keys: Vec::new(),
+        }
+    }
+
+    /// Adds the partners' sandbox keys
+    pub fn with_test_api_keys(mut self, test_api_keys: &HashMap<String, String>) -> Self {
+        self.test_api_keys = key_digests(test_api_keys);
+        self
+    }
+
+    /// Swaps in a different key source (e.g. JWKS). Keys are loaded lazily on the
+    /// first unknown `kid`.
+    pub fn with_source(mut self, source: Arc<dyn KeySource>) -> Self {
+        self.source = source;
+        self.last_refresh = Mutex::new(None);
+        self
+    }
+
+    /// Signs claims with the cached encoding key, tagging the header with our `kid`.
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, AppError> {
+        let mut header = Header::default();
+        header.kid = Some(DEFAULT_KID.to_string());
+
+        encode(&header, claims, &self.encoding_key)
+            .map_err(|e| AppError::InternalServerError(format!("Token encoding failed: {}", e)))
+    }
+
+    /// Verifies a token against the cached keys, refreshing once on a `kid` miss.
+    pub async fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<TokenData<T>, AppError> {
+        let header = decode_header(token)
+            .map_err(|_| AppError::AuthenticationError("Invalid or expired token".to_string()))?;
+        // Tokens issued before kids were stamped carry none; they were signed with the default key
+        let kid = header.kid.unwrap_or_else(|| DEFAULT_KID.to_string());
+
+        let key = match self.cached_key(&kid) {
+            Some(key) => key,
+            None => {
+                self.refresh().await;
+                self.cached_key(&kid)
+                    .ok_or(AppError::AuthenticationError("Unknown signing key".to_string()))?
+            }
+        };
+
+        decode::<T>(token, &key, &self.validation)
+            .map_err(|_| AppError::AuthenticationError("Invalid or expired token".to_string()))
+    }
+
+    /// Returns the name of the matching API key.
+    pub fn verify_api_key(&self, presented: &str) -> Option<&str> {
+        find_key(&self.api_keys, presented)
+    }
+
+    /// Returns the name of the partner whose sandbox key this is.
+    pub fn verify_test_api_key(&self, presented: &str) -> Option<&str> {
+        find_key(&self.test_api_keys, presented)
+    }
+
+    fn cached_key(&self, kid: &str) -> Option<Arc<DecodingKey>> {
+        self.keys.read().ok()?.get(kid).cloned()
+    }
+
+    /// Fetches the keys now regardless of the refresh throttle, so a JWKS-backed
+    /// cache is populated before the first request. Returns the number of keys.
+    pub async fn warm(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut last_refresh = self.last_refresh.lock().await;
+        let fetched = self.source.fetch_keys().await;
+        *last_refresh = Some(Instant::now());
+        Ok(self.replace_keys(fetched?))
+    }
+
+    async fn refresh(&self) {
+        // Held across the fetch so concurrent misses share a single refresh
+        let mut last_refresh = self.last_refresh.lock().await;
+        if last_refresh.is_some_and(|at| at.elapsed() < MIN_REFRESH_INTERVAL) {
+            return;
+        }
+
+        if let Err(e) = self.source.fetch_keys().await.map(|fetched| self.replace_keys(fetched)) {
+            tracing::warn!("Failed to refresh auth keys: {}", e);
+        }
+        *last_refresh = Some(Instant::now());
+    }
+
+    fn replace_keys(&self, fetched: HashMap<String, DecodingKey>) -> usize {
+        let count = fetched.len();
+        let fetched = fetched.into_iter().map(|(kid, key)| (kid, Arc::new(key))).collect();
+        if let Ok(mut keys) = self.keys.write() {
+            *keys = fetched;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::middleware::auth::CustomerClaims;
+
+    struct RotatingSource {
+        fetches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl KeySource for RotatingSource {
+        async fn fetch_keys(&self) -> Result<HashMap<String, DecodingKey>, Box<dyn std::error::Error + Send + Sync>> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            let mut keys = HashMap::new();
+            keys.insert("rotated".to_string(), DecodingKey::from_secret(b"rotated-secret"));
+            Ok(keys)
+        }
+    }
+
+    fn claims() -> CustomerClaims {
+        CustomerClaims {
+            sub: "cust-1".to_string(),
+            email: None,
+            role: "CUSTOMER".to_string(),
+            exp: (chrono::Utc::now().timestamp() + 600) as usize,
+            test: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_roundtrip_with_default_key() {
+        let cache = AuthKeyCache::from_secret("secret", &HashMap::new());
+        let token = cache.encode(&claims()).unwrap();
+
+        let decoded = cache.decode::<CustomerClaims>(&token).await.unwrap();
+        assert_eq!(decoded.claims.sub, "cust-1");
+    }
+
+    #[tokio::test]
+    async fn test_kid_miss_refreshes_once() {
+        let source = Arc::new(RotatingSource { fetches: AtomicUsize::new(0) });
+        let cache = AuthKeyCache::from_secret("secret", &HashMap::new()).with_source(source.clone());
+
+        let mut header = Header::default();
+        header.kid = Some("rotated".to_string());
+        let token = encode(&header, &claims(), &EncodingKey::from_secret(b"rotated-secret")).unwrap();
+
+        assert!(cache.decode::<CustomerClaims>(&token).await.is_ok());
+        assert!(cache.decode::<CustomerClaims>(&token).await.is_ok());
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+
+        // Unknown kids within the refresh interval don't hit the source again
+        header.kid = Some("forged".to_string());
+        let forged = encode(&header, &claims(), &EncodingKey::from_secret(b"x")).unwrap();
+        assert!(cache.decode::<CustomerClaims>(&forged).await.is_err());
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_warm_ignores_refresh_throttle() {
+        let source = Arc::new(RotatingSource { fetches: AtomicUsize::new(0) });
+        let cache = AuthKeyCache::from_secret("secret", &HashMap::new()).with_source(source.clone());
+
+        assert_eq!(cache.warm().await.unwrap(), 1);
+        assert_eq!(cache.warm().await.unwrap(), 1);
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 2);
+        assert!(cache.cached_key("rotated").is_some());
+    }
+
+    #[test]
+    fn test_verify_api_key() {
+        let mut api_keys = HashMap::new();
+        api_keys.insert("acme-travel".to_string(), "k3y-acme".to_string());
+        let cache = AuthKeyCache::from_secret("secret", &api_keys);
+
+        assert_eq!(cache.verify_api_key("k3y-acme"), Some("acme-travel"));
+        assert_eq!(cache.verify_api_key("k3y-acm"), None);
+        assert_eq!(cache.verify_api_key(""), None);
+    }
+
+    #[test]
+    fn test_sandbox_keys_are_not_live_keys() {
+        let mut api_keys = HashMap::new();
+        api_keys.insert("acme-travel".to_string(), "k3y-acme".to_string());
+        let mut test_api_keys = HashMap::new();
+        test_api_keys.insert("acme-travel".to_string(), "t3st-acme".to_string());
+        let cache = AuthKeyCache::from_secret("secret", &api_keys).with_test_api_keys(&test_api_keys);
+
+        assert_eq!(cache.verify_test_api_key("t3st-acme"), Some("acme-travel"));
+        assert_eq!(cache.verify_api_key("t3st-acme"), None);
+        assert_eq!(cache.verify_test_api_key("k3y-acme"), None);
+    }
+}
@@ -1,4 +1,9 @@
+pub mod admission;
 pub mod auth;
+pub mod deadline;
+pub mod key_cache;
 pub mod resiliency;
+pub mod service_auth;
 
-pub use auth::{customer_auth_middleware, admin_auth_middleware, CustomerClaims, AdminClaims};
+pub use auth::{customer_auth_middleware, admin_auth_middleware, CustomerClaims, AdminClaims, Principal};
+pub use service_auth::service_auth_middleware;
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::{error::AppError, state::AppState};
+use super::auth::Principal;
+
+/// Header carrying `svc=<service>,t=<unix seconds>,v1=<hex HMAC-SHA256>`.
+/// Kept out of `Authorization`, which gateways in front of the API often
+/// rewrite or strip.
+pub const SERVICE_SIGNATURE_HEADER: &str = "Altis-Service-Signature";
+
+/// Internal request bodies are small; anything bigger is refused unsigned
+const MAX_SIGNED_BODY_BYTES: usize = 1024 * 1024;
+
+/// Service name and HMAC key pairs, plus the clock skew a signature may carry
+pub struct ServiceKeys {
+    keys: HashMap<String, String>,
+    tolerance_seconds: i64,
+}
+
+/// What a signature covers: timestamp, method, path with query, and body digest.
+/// The host is left out because gateways rewrite it.
+fn signing_payload(timestamp: i64, method: &str, path_and_query: &str, body: &[u8]) -> String {
+    let body_digest: String = Sha256::digest(body).iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}.{}.{}.{}", timestamp, method.to_uppercase(), path_and_query, body_digest)
+}
+
+fn mac(key: &str, payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Value of [`SERVICE_SIGNATURE_HEADER`] for a request from `service`
+pub fn sign_request(service: &str, key: &str, timestamp: i64, method: &str, path_and_query: &str, body: &[u8]) -> String {
+    let digest: String = mac(key, &signing_payload(timestamp, method, path_and_query, body))
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("svc={},t={},v1={}", service, timestamp, digest)
+}
+
+impl ServiceKeys {
+    pub fn new(keys: HashMap<String, String>, tolerance_seconds: i64) -> Self {
+        Self { keys, tolerance_seconds }
+    }
+
+    /// Checks a signature header against the request, returning the calling
+    /// service's name.
+    pub fn verify(&self, header: &str, method: &str, path_and_query: &str, body: &[u8], now: i64) -> Result<&str, &'static str> {
+        let mut service = None;
+        let mut timestamp = None;
+        let mut signature = None;
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("svc", value)) => service = Some(value),
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("v1", value)) => signature = Some(value),
+                _ => {}
+            }
+        }
+        let (Some(service), Some(timestamp), Some(signature)) = (service, timestamp, signature) else {
+            return Err("malformed signature header");
+        };
+
+        let (name, key) = self.keys.get_key_value(service).ok_or("unknown service")?;
+        if (now - timestamp).abs() > self.tolerance_seconds {
+            return Err("signature timestamp outside the allowed window");
+        }
+        let signature = decode_hex(signature).ok_or("malformed signature")?;
+        // verify_slice compares in constant time
+        mac(key, &signing_payload(timestamp, method, path_and_query, body))
+            .verify_slice(&signature)
+            .map_err(|_| "signature mismatch")?;
+
+        Ok(name)
+    }
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// ============================================================================
+// Internal Service Authentication Middleware
+// ============================================================================
+
+/// Admits only requests signed with a configured service key. Customer
+/// tokens and partner API keys are not accepted on internal routes.
+pub async fn service_auth_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let header = req.headers()
+        .get(SERVICE_SIGNATURE_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .ok_or(AppError::AuthenticationError("Missing service signature".to_string()))?
+        .to_string();
+
+    // The body is part of the signature, so it has to be read up front
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, MAX_SIGNED_BODY_BYTES).await
+        .map_err(|_| AppError::ValidationError("Request body too large for a signed request".to_string()))?;
+    // Signed as sent, not with the /v1/internal prefix the router has stripped
+    let uri = parts.extensions.get::<OriginalUri>().map_or_else(|| parts.uri.clone(), |original| original.0.clone());
+    let path_and_query = uri.path_and_query().map_or("/", |pq| pq.as_str());
+
+    let service = state.auth.service_keys
+        .verify(&header, parts.method.as_str(), path_and_query, &body, chrono::Utc::now().timestamp())
+        .map_err(|reason| {
+            tracing::warn!("Rejected internal request to {}: {}", path_and_query, reason);
+            AppError::AuthenticationError("Invalid service signature".to_string())
+        })?
+        .to_string();
+
+    let mut req = Request::from_parts(parts, Body::from(body));
+    req.extensions_mut().insert(Principal::Service(service));
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> ServiceKeys {
+        let mut keys = HashMap::new();
+        keys.insert("fulfillment-worker".to_string(), "k".repeat(32));
+        ServiceKeys::new(keys, 300)
+    }
+
+    #[test]
+    fn test_signed_request_verifies() {
+        let keys = keys();
+        let body = br#"{"reason":"catalog import"}"#;
+        let header = sign_request("fulfillment-worker", &"k".repeat(32), 1_000, "post", "/v1/internal/caches/invalidate", body);
+
+        assert_eq!(keys.verify(&header, "POST", "/v1/internal/caches/invalidate", body, 1_100), Ok("fulfillment-worker"));
+        assert_eq!(keys.verify(&header, "POST", "/v1/internal/caches/invalidate", b"{}", 1_100), Err("signature mismatch"));
+        assert_eq!(keys.verify(&header, "GET", "/v1/internal/caches/invalidate", body, 1_100), Err("signature mismatch"));
+        assert!(keys.verify(&header, "POST", "/v1/internal/caches/invalidate", body, 1_400).is_err(), "stale");
+
+        let forged = sign_request("fulfillment-worker", &"x".repeat(32), 1_000, "POST", "/v1/internal/caches/invalidate", body);
+        assert_eq!(keys.verify(&forged, "POST", "/v1/internal/caches/invalidate", body, 1_000), Err("signature mismatch"));
+        let unknown = sign_request("someone", &"k".repeat(32), 1_000, "POST", "/v1/internal/caches/invalidate", body);
+        assert_eq!(keys.verify(&unknown, "POST", "/v1/internal/caches/invalidate", body, 1_000), Err("unknown service"));
+        assert_eq!(keys.verify("t=1000", "POST", "/", body, 1_000), Err("malformed signature header"));
+    }
+}
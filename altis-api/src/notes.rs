@@ -0,0 +1,211 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use altis_core::tenant::TenantContext;
+use altis_order::waivers::Waiver;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authz::{authorize_order, authorize_tenant_order};
+use crate::middleware::auth::{has_permission, AdminClaims, CustomerClaims};
+use crate::state::AppState;
+
+/// Permission needed to record a waiver; it credits money back on the order
+pub const WAIVE_PERMISSION: &str = "orders:waive";
+
+/// Longest note body accepted
+const MAX_NOTE_LENGTH: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NoteVisibility {
+    /// Seen by back-office staff only
+    #[default]
+    Internal,
+    /// Also shown to the customer with their order
+    Customer,
+}
+
+impl NoteVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteVisibility::Internal => "INTERNAL",
+            NoteVisibility::Customer => "CUSTOMER",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddNoteRequest {
+    pub body: String,
+    #[serde(default)]
+    pub visibility: NoteVisibility,
+    /// A charge waived for the customer, explained by this note
+    pub waiver: Option<Waiver>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteResponse {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub author: String,
+    pub author_role: String,
+    pub visibility: NoteVisibility,
+    pub body: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub waiver: Option<WaiverRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WaiverRecord {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub waiver: Waiver,
+    /// The adjustment that credited the amount; none when nothing was charged
+    pub ledger_entry_id: Option<Uuid>,
+}
+
+/// A note as the customer sees it, without who wrote it
+#[derive(Debug, Serialize)]
+pub struct CustomerNoteResponse {
+    pub id: Uuid,
+    pub body: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn to_response(note: serde_json::Value) -> Result<NoteResponse, StatusCode> {
+    serde_json::from_value(note).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /v1/admin/orders/:id/notes
+/// Annotate an order, optionally recording a waiver; waivers need `orders:waive`
+pub async fn add_note(
+    State(state): State<AppState>,
+    Extension(claims): Extension<AdminClaims>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+    Json(req): Json<AddNoteRequest>,
+) -> Result<(StatusCode, Json<NoteResponse>), StatusCode> {
+    let body = req.body.trim();
+    if body.is_empty() || body.chars().count() > MAX_NOTE_LENGTH {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let order = authorize_tenant_order(&state, &tenant, order_id).await?;
+
+    let waiver = match &req.waiver {
+        Some(waiver) => {
+            if !has_permission(&claims, WAIVE_PERMISSION) {
+                tracing::warn!("{} ({}) denied a waiver on order {}", claims.sub, claims.role, order_id);
+                return Err(StatusCode::FORBIDDEN);
+            }
+            waiver.validate().map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+            let item_id = waiver.order_item_id.to_string();
+            let on_order = order["items"].as_array().into_iter().flatten()
+                .any(|item| item["id"].as_str() == Some(item_id.as_str()));
+            if !on_order {
+                return Err(StatusCode::UNPROCESSABLE_ENTITY);
+            }
+
+            let mut record = serde_json::to_value(waiver).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            record["ledger_entry"] = serde_json::to_value(waiver.ledger_entry(order_id))
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            Some(record)
+        }
+        None => None,
+    };
+
+    let saved = state.note_repo.add_note(&serde_json::json!({
+        "order_id": order_id,
+        "author": claims.sub,
+        "author_role": claims.role,
+        "visibility": req.visibility.as_str(),
+        "body": body,
+        "waiver": waiver,
+    })).await.map_err(|e| {
+        tracing::error!("Failed to add note to order {}: {:?}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Some(waiver) = &req.waiver {
+        tracing::info!("{} waived {} NUC {} on order {}", claims.sub, waiver.amount.minor_units(), waiver.kind.as_str(), order_id);
+    }
+    Ok((StatusCode::CREATED, Json(to_response(saved)?)))
+}
+
+/// GET /v1/admin/orders/:id/notes
+/// Every note on an order with its waiver, oldest first
+pub async fn list_notes(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<Vec<NoteResponse>>, StatusCode> {
+    authorize_tenant_order(&state, &tenant, order_id).await?;
+    let notes = state.note_repo.list_notes(order_id, false).await.map_err(|e| {
+        tracing::error!("Failed to list notes of order {}: {:?}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(notes.into_iter().map(to_response).collect::<Result<_, _>>()?))
+}
+
+/// GET /v1/orders/:id/notes
+/// Notes agents left for the customer on their order
+pub async fn list_customer_notes(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<Vec<CustomerNoteResponse>>, StatusCode> {
+    authorize_order(&state, &claims, order_id).await?;
+    let notes = state.note_repo.list_notes(order_id, true).await.map_err(|e| {
+        tracing::error!("Failed to list notes of order {}: {:?}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let notes = notes.into_iter().map(to_response).collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(notes.into_iter()
+        .filter(|note| note.visibility == NoteVisibility::Customer)
+        .map(|note| CustomerNoteResponse { id: note.id, body: note.body, created_at: note.created_at })
+        .collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_note_reads_back_with_its_waiver() {
+        let (order_id, item_id, entry_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let stored = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "order_id": order_id,
+            "author": "agent-7",
+            "author_role": "SUPPORT",
+            "visibility": "CUSTOMER",
+            "body": "Change fee waived after the schedule change",
+            "created_at": "2026-10-16T08:00:00.123456+00:00",
+            "waiver": {
+                "id": Uuid::new_v4(),
+                "note_id": Uuid::new_v4(),
+                "order_id": order_id,
+                "order_item_id": item_id,
+                "kind": "CHANGE_FEE",
+                "amount_nuc": 5000,
+                "reason": "Schedule change",
+                "ledger_entry_id": entry_id,
+                "created_at": "2026-10-16T08:00:00.123456+00:00",
+            },
+        });
+
+        let note = to_response(stored).unwrap();
+        assert_eq!(note.visibility, NoteVisibility::Customer);
+        let waiver = note.waiver.unwrap();
+        assert_eq!(waiver.waiver.order_item_id, item_id);
+        assert_eq!(waiver.waiver.amount.minor_units(), 5000);
+        assert_eq!(waiver.ledger_entry_id, Some(entry_id));
+
+        // Notes default to staff-only
+        let req: AddNoteRequest = serde_json::from_value(serde_json::json!({ "body": "Called the customer" })).unwrap();
+        assert_eq!(req.visibility, NoteVisibility::Internal);
+        assert!(req.waiver.is_none());
+    }
+}
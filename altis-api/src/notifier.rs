@@ -0,0 +1,11 @@
+use crate::state::AppState;
+
+/// Kafka topic consumed by the notification service for customer messages.
+const NOTIFICATION_TOPIC: &str = "notifications";
+
+/// Hands a customer message to the notification service, which picks the
+/// template from `event_type` and the channel from the customer's contact
+/// details in the event. `key` keeps messages about one subject in order.
+pub async fn notify(state: &AppState, key: &str, event: &serde_json::Value) -> Result<(), rdkafka::error::KafkaError> {
+    state.kafka.publish(NOTIFICATION_TOPIC, key, &event.to_string()).await
+}
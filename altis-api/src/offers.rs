@@ -1,11 +1,18 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use crate::error::ApiError;
 use crate::state::AppState;
+use std::collections::HashMap;
+use std::sync::Arc;
+use altis_catalog::{InventoryError, ProductContent};
+use altis_shared::money::{self, Money};
+use crate::search_budget::{Degraded, SearchBudget, SearchStage};
+use futures_util::StreamExt;
 
 // ============================================================================
 // Request/Response Types
@@ -17,9 +24,18 @@ pub struct SearchOffersRequest {
     pub destination: String,
     pub departure_date: String,
     pub return_date: Option<String>,
+    /// The whole party, children and infants included
     pub passengers: u32,
+    /// How many of `passengers` are children (CHD, 2-11) and lap infants (INF, under 2)
+    #[serde(default)]
+    pub children: u32,
+    #[serde(default)]
+    pub infants: u32,
     pub cabin_class: Option<String>,
     pub user_segment: Option<String>,
+    /// utm_* parameters, for campaign attribution
+    #[serde(flatten)]
+    pub campaign: crate::analytics::CampaignParams,
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,30 +52,66 @@ pub struct ReshopOrderResponse {
     pub expires_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OfferResponse {
     pub id: Uuid,
     pub items: Vec<OfferItemResponse>,
-    pub total_nuc: i32,
+    #[serde(rename = "total_nuc", with = "money::nuc")]
+    pub total: Money,
     pub currency: String,
     pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// Airline code of the carrier operating and selling the offer; ours or
+    /// an interline partner's
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Built without part of the search (an up-to-date catalog, ancillaries
+    /// and campaigns, or model ranking) because it ran out of time
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OfferItemResponse {
     pub id: Uuid,
     pub product_type: String,
     pub name: String,
     pub description: Option<String>,
-    pub price_nuc: i32,
+    #[serde(rename = "price_nuc", with = "money::nuc")]
+    pub price: Money,
     pub metadata: serde_json::Value,
+    #[serde(rename = "tax_nuc", with = "money::nuc", default = "money::nuc::zero")]
+    pub tax: Money,
+    #[serde(default)]
+    pub taxes: Vec<altis_catalog::TaxLine>,
+    /// Images and video from the airline's content for the customer's language
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<altis_catalog::Media>,
+    /// Language of `name` and `description` when the airline's content was used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// The traveler the ancillary is for, by index; none for the booking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traveler_index: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AcceptOfferRequest {
     pub customer_email: String,
     pub travelers: Option<Vec<altis_core::iata::Traveler>>,
+    /// Travelers saved on the customer's profile, in place of `travelers`
+    #[serde(default)]
+    pub saved_traveler_ids: Vec<Uuid>,
+    /// Defaults to the contact details on the customer's profile
     pub contact_info: Option<altis_core::iata::ContactInfo>,
+    /// Passenger count for group bookings whose names are submitted later
+    pub group_size: Option<i32>,
+    /// Card token paying for an ancillary offer on an existing booking,
+    /// which is charged on acceptance
+    pub payment_reference: Option<String>,
+    /// Which traveler each ancillary is for, as offer item id to traveler
+    /// index; the ones left out serve the whole booking
+    #[serde(default)]
+    pub item_travelers: HashMap<Uuid, i32>,
 }
 
 // ============================================================================
@@ -70,102 +122,357 @@ pub struct AcceptOfferRequest {
 /// Generate offers based on search criteria
 pub async fn search_offers(
     State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<crate::middleware::auth::CustomerClaims>,
+    request_headers: HeaderMap,
     Json(req): Json<SearchOffersRequest>,
-) -> Result<Json<Vec<OfferResponse>>, StatusCode> {
+) -> Result<(HeaderMap, Json<Vec<OfferResponse>>), ApiError> {
+    let catalog_airline = crate::sandbox::catalog_airline(&state, claims.test);
+    crate::search_validation::validate_search(&state, &req, catalog_airline).await?;
+
+    let languages = accepted_languages(&request_headers);
+    // Same customer, same experiment variant, for the whole experiment
+    let (subject, _) = crate::authz::customer_id_for(&claims);
+    let assignment = state.ranker.assign(&subject);
+    let customer = crate::customer_features::for_customer(&state, &subject).await;
+    crate::analytics::record_touch(&state, &subject, &req.campaign);
+
+    // 0. Identical searches within the cache window reuse the stored offers,
+    //    as long as they were ranked by the same strategy for a similar customer
+    let cache_key = format!("{}:{}:{}", altis_store::SearchCache::key(
+        &req.origin,
+        &req.destination,
+        &req.departure_date,
+        req.return_date.as_deref(),
+        req.passengers,
+        req.cabin_class.as_deref(),
+        req.user_segment.as_deref(),
+    ), assignment.cache_label(), customer.as_ref().map_or_else(|| "new".to_string(), |c| c.cache_label()));
+    // Fares differ by passenger type
+    let cache_key = if req.children + req.infants == 0 { cache_key } else { format!("{}:{}c{}i", cache_key, req.children, req.infants) };
+    // Sandbox searches price another catalog
+    let cache_key = if claims.test { format!("sandbox:{}", cache_key) } else { cache_key };
+    // Offers are cached as worded for the customer's languages
+    let cache_key = if languages.is_empty() { cache_key } else { format!("{}:{}", cache_key, languages.join(",")) };
+    if let Some(cached) = state.search_cache.get(&cache_key).await {
+        if let Ok(responses) = serde_json::from_value::<Vec<OfferResponse>>(cached) {
+            state.ranker.log_exposure(&assignment, &subject, responses.iter().map(|r| r.id).collect(), true);
+            return Ok((HeaderMap::new(), Json(responses)));
+        }
+    }
+
     // 1. Build search context
     let search_context = altis_offer::features::SearchContext {
         origin: req.origin.clone(),
         destination: req.destination.clone(),
         departure_date: req.departure_date.clone(),
         passengers: req.passengers as i32, // Assuming SearchContext still expects i32
+        children: req.children as i32,
+        infants: req.infants as i32,
         cabin_class: None, // TODO: Pull from request if available
         user_segment: req.user_segment.clone(),
+        customer,
     };
 
+    // 2-3. Price the catalog into offers, while interline partners shop
+    let criteria = altis_core::iata::ShoppingCriteria {
+        origin: req.origin.clone(),
+        destination: req.destination.clone(),
+        travel_date: req.departure_date.clone(),
+    };
     let search_context_json = serde_json::to_value(&search_context).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Stages that run out of time settle for less rather than fail the search
+    let budget = SearchBudget::start(&state.search);
+    let mut degraded = Degraded::default();
+    let (offers, partner_offers) = tokio::join!(
+        generate_offers_within(&state, &search_context, catalog_airline, &budget, &mut degraded),
+        state.interline.shop(&criteria, &search_context_json),
+    );
+    let mut offers = offers?;
+    
+    // 4. AI Ranking, on rule scores for whatever the strategy can't score in time
+    budget.scope(SearchStage::Ranking, state.ranker.rank_offers_with_context(&search_context, &assignment, &mut offers)).await;
+    state.ranker.log_exposure(&assignment, &subject, offers.iter().map(|o| o.id).collect(), false);
+    if offers.iter().any(|o| o.metadata["score_source"] == "rules_fallback") {
+        degraded.mark(SearchStage::Ranking);
+    }
+    // Kept on the offer, so it reads the same when fetched again
+    if degraded.is_degraded() {
+        for offer in &mut offers {
+            offer.metadata["degraded"] = serde_json::json!(true);
+        }
+    }
+
+    // Partner offers follow our own, unranked
+    offers.extend(partner_offers);
+    
+    // 5. Save generated offers to repository (for retrieval on accept)
+    let offer_values = offers.iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    state.offer_repo.save_offers_batch(&offer_values).await.map_err(|e| {
+        tracing::error!("Failed to persist offers: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // 6. Convert to response format, in the customer's language where the airline has content for it
+    let content = airline_content(&state, catalog_airline, &languages).await;
+    let responses: Vec<OfferResponse> = offers.iter()
+        .map(|offer| offer_response(offer, &content, &languages))
+        .collect();
+
+    // Degraded results are still worth returning, but not worth caching
+    let mut headers = HeaderMap::new();
+    if degraded.is_degraded() {
+        if let Ok(value) = HeaderValue::from_str(&degraded.header_value()) {
+            headers.insert(crate::middleware::deadline::PARTIAL_RESULT_HEADER, value);
+        }
+    } else if let Ok(value) = serde_json::to_value(&responses) {
+        state.search_cache.put(&cache_key, &value).await;
+    }
+
+    Ok((headers, Json(responses)))
+}
+
+/// Languages from the request's `Accept-Language`, most wanted first
+fn accepted_languages(headers: &HeaderMap) -> Vec<String> {
+    headers.get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| altis_catalog::content::parse_accept_language(value).into_iter().take(5).collect())
+        .unwrap_or_default()
+}
+
+/// The airline's localized product content; none when the customer named
+/// no language or it can't be loaded, leaving the catalog's own wording
+async fn airline_content(state: &AppState, airline_code: &str, languages: &[String]) -> Arc<ProductContent> {
+    if languages.is_empty() {
+        return Arc::default();
+    }
+    let airline_id = match state.catalog_cache.airline(airline_code).await {
+        Ok(Some(airline)) => airline["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+        _ => None,
+    };
+    let Some(airline_id) = airline_id else { return Arc::default() };
+    state.catalog_cache.content(airline_id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load product content for airline {}: {:?}", airline_code, e);
+        Arc::default()
+    })
+}
+
+pub(crate) fn offer_response(offer: &altis_offer::Offer, content: &ProductContent, languages: &[String]) -> OfferResponse {
+    OfferResponse {
+        id: offer.id,
+        items: offer.items.iter().map(|item| {
+            let localized = item.product_id.and_then(|product_id| content.resolve(product_id, languages));
+            OfferItemResponse {
+                id: item.id,
+                product_type: item.product_type.clone(),
+                name: localized.and_then(|c| c.name.clone()).unwrap_or_else(|| item.name.clone()),
+                description: localized.and_then(|c| c.description.clone()).or_else(|| item.description.clone()),
+                price: item.price,
+                metadata: item.metadata.clone(),
+                tax: item.tax,
+                taxes: item.taxes.clone(),
+                media: localized.map(|c| c.media.clone()).unwrap_or_default(),
+                locale: localized.map(|c| c.locale.clone()),
+                traveler_index: item.traveler_index,
+            }
+        }).collect(),
+        total: offer.total,
+        currency: offer.currency.clone(),
+        expires_at: offer.expires_at,
+        owner: offer.metadata["owner"].as_str().map(str::to_string),
+        degraded: offer.metadata["degraded"].as_bool().unwrap_or(false),
+    }
+}
+
+/// The airline whose catalog live searches are priced from
+pub(crate) const CATALOG_AIRLINE: &str = "AL";
+
+/// Builds unranked offers for a search from the airline's catalog, pricing
+/// rules and taxes. Shared by search, the price watch worker, which
+/// re-prices watched routes the same way a customer search would, and the
+/// waitlist, which prices seats that come back on a sold-out flight.
+pub(crate) async fn generate_offers(
+    state: &AppState,
+    search_context: &altis_offer::features::SearchContext,
+    airline_code: &str,
+) -> Result<Vec<altis_offer::models::Offer>, StatusCode> {
+    let mut degraded = Degraded::default();
+    generate_offers_within(state, search_context, airline_code, &SearchBudget::unbounded(), &mut degraded).await
+}
+
+/// [`generate_offers`] with each stage held to `budget`. A catalog that
+/// can't be loaded in time is replaced by the last one held in memory, and
+/// pricing that runs out of time by baseline offers: the flights alone, at
+/// full fare. Stages that were cut short are marked in `degraded`.
+pub(crate) async fn generate_offers_within(
+    state: &AppState,
+    search_context: &altis_offer::features::SearchContext,
+    airline_code: &str,
+    budget: &SearchBudget,
+    degraded: &mut Degraded,
+) -> Result<Vec<altis_offer::models::Offer>, StatusCode> {
+    let search_context_json = serde_json::to_value(search_context).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     // 2. Fetch products from catalog
-    // Dynamically find AirAltis LCC (AL) ID
-    let airline = state.catalog_repo.get_airline_by_code("AL").await
+    let catalog = match budget.run(SearchStage::Catalog, load_catalog(state, airline_code)).await {
+        Ok(catalog) => catalog,
+        Err(_) => {
+            tracing::warn!("Catalog of airline {} not loaded within the search budget", airline_code);
+            Err(StatusCode::GATEWAY_TIMEOUT)
+        }
+    };
+    let (airline, products, tax_engine) = match catalog {
+        Ok(catalog) => catalog,
+        Err(status) => match state.catalog_cache.last_known(airline_code) {
+            Some(catalog) => {
+                degraded.mark(SearchStage::Catalog);
+                catalog
+            }
+            None => return Err(status),
+        },
+    };
+    
+    let airline_id = Uuid::parse_str(airline["id"].as_str().unwrap_or_default()).unwrap_or_default();
+
+    // Convert catalog products to domain Products
+    // Inactive and soft-deleted products are off sale
+    let domain_products: Vec<altis_catalog::Product> = products.iter()
+        .filter(|p| p["is_active"].as_bool().unwrap_or(true))
+        .map(catalog_product)
+        .collect();
+
+    let (mut flights, ancillaries): (Vec<_>, Vec<_>) = domain_products.into_iter()
+        .partition(|p| p.product_type == altis_catalog::ProductType::Flight);
+    // Flights that state their route or date (e.g. the sandbox schedule) only answer matching searches
+    flights.retain(|flight| {
+        let matches = |field: &str, wanted: &str| flight.metadata[field].as_str().is_none_or(|value| value == wanted);
+        matches("origin", &search_context.origin)
+            && matches("destination", &search_context.destination)
+            && matches("departure_date", &search_context.departure_date)
+    });
+
+    // 3. Generate offers using dynamic OfferGenerator
+    let pricing = pricing_config(state, airline_id).await;
+    let generation = async {
+        // Campaigns and routes load while ancillaries are checked
+        let (campaigns, routes, ancillaries) = tokio::join!(
+            state.catalog_cache.campaigns(airline_id),
+            state.catalog_cache.routes(airline_id),
+            sellable_ancillaries(state, airline_id, ancillaries, &search_context_json),
+        );
+        // A search still prices, at full fare, when campaigns can't be loaded
+        let campaigns = campaigns.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load campaigns for airline {}: {:?}", airline_id, e);
+            Default::default()
+        });
+        // ...and without market pricing when routes can't be
+        let routes = routes.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load routes for airline {}: {:?}", airline_id, e);
+            Default::default()
+        });
+        let generator = altis_offer::generator::OfferGenerator::new(
+            altis_catalog::pricing::PricingEngine::new(pricing.clone())
+                .with_campaigns((*campaigns).clone())
+        ).with_tax_engine((*tax_engine).clone())
+            .with_routes((*routes).clone());
+        price_offers(&generator, search_context, &search_context_json, flights.clone(), ancillaries).await
+    };
+    let mut offers = match budget.run(SearchStage::Generation, generation).await {
+        Ok(offers) => offers?,
+        Err(_) => {
+            tracing::warn!("Offers of airline {} not priced within the search budget; offering flights only", airline_code);
+            degraded.mark(SearchStage::Generation);
+            let baseline = altis_offer::generator::OfferGenerator::new(
+                altis_catalog::pricing::PricingEngine::new(pricing)
+            ).with_tax_engine((*tax_engine).clone());
+            price_offers(&baseline, search_context, &search_context_json, flights, Vec::new()).await?
+        }
+    };
+    for offer in &mut offers {
+        offer.metadata["owner"] = serde_json::json!(airline["code"].as_str().unwrap_or(airline_code));
+    }
+    Ok(offers)
+}
+
+/// Continuous pricing with the configured market multipliers, and child
+/// and infant fares as the airline's business rules set them
+pub(crate) async fn pricing_config(state: &AppState, airline_id: Uuid) -> altis_catalog::pricing::PricingConfig {
+    let rules = state.business_rules_for(airline_id).await;
+    altis_catalog::pricing::PricingConfig {
+        domestic_multiplier: state.markets.domestic_multiplier,
+        international_multiplier: state.markets.international_multiplier,
+        child_fare_percentage: rules.child_fare_percentage,
+        infant_fare_percentage: rules.infant_fare_percentage,
+        ..Default::default()
+    }
+}
+
+/// A catalog product as pricing sees it. Its metadata records the product
+/// version it was priced from, which follows the item into the order.
+pub(crate) fn catalog_product(p: &serde_json::Value) -> altis_catalog::Product {
+    let mut metadata = p["metadata"].clone();
+    if let Some(fields) = metadata.as_object_mut() {
+        fields.insert("product_version".to_string(), p["version"].clone());
+    } else if metadata.is_null() {
+        metadata = serde_json::json!({"product_version": p["version"]});
+    }
+    altis_catalog::Product {
+        id: Uuid::parse_str(p["id"].as_str().unwrap_or_default()).unwrap_or_default(),
+        product_type: serde_json::from_value(p["product_type"].clone()).unwrap_or(altis_catalog::ProductType::Flight),
+        product_code: p["product_code"].as_str().unwrap_or_default().to_string(),
+        name: p["name"].as_str().unwrap_or_default().to_string(),
+        description: p["description"].as_str().map(|s| s.to_string()),
+        base_price_nuc: p["base_price_nuc"].as_i64().unwrap_or(0) as i32,
+        margin_percentage: p["margin_percentage"].as_f64().unwrap_or(0.15),
+        is_active: p["is_active"].as_bool().unwrap_or(true),
+        metadata,
+    }
+}
+
+/// The airline, its products and tax codes: everything pricing can't do without
+async fn load_catalog(state: &AppState, airline_code: &str) -> Result<crate::catalog_cache::PricingCatalog, StatusCode> {
+    // AL must exist from migration; the sandbox airline once it has been reset
+    let airline = state.catalog_cache.airline(airline_code).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?; // AL must exist from migration
+        .ok_or_else(|| {
+            tracing::error!("Airline {} is not in the catalog", airline_code);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
     
     let airline_id = Uuid::parse_str(airline["id"].as_str().unwrap_or_default()).unwrap_or_default();
     
-    let products = state.catalog_repo.list_products(airline_id, None).await
+    let products = state.catalog_cache.products(airline_id).await
         .map_err(|e| {
             tracing::error!("Failed to fetch products for airline {}: {:?}", airline_id, e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    // Helper to find product by code
-    let _find_product = |code: &str| {
-        products.iter().find(|p| p["product_code"].as_str() == Some(code))
-    };
-
-    // 3. Generate offers using dynamic OfferGenerator
-    let generator = altis_offer::generator::OfferGenerator::new(
-        altis_catalog::pricing::PricingEngine::new(altis_catalog::pricing::PricingConfig::default())
-    );
-
-    // Convert catalog products to domain Products
-    let domain_products: Vec<altis_catalog::Product> = products.into_iter().map(|p| {
-        altis_catalog::Product {
-            id: Uuid::parse_str(p["id"].as_str().unwrap_or_default()).unwrap_or_default(),
-            product_type: serde_json::from_value(p["product_type"].clone()).unwrap_or(altis_catalog::ProductType::Flight),
-            product_code: p["product_code"].as_str().unwrap_or_default().to_string(),
-            name: p["name"].as_str().unwrap_or_default().to_string(),
-            description: p["description"].as_str().map(|s| s.to_string()),
-            base_price_nuc: p["base_price_nuc"].as_i64().unwrap_or(0) as i32,
-            margin_percentage: p["margin_percentage"].as_f64().unwrap_or(0.15),
-            is_active: p["is_active"].as_bool().unwrap_or(true),
-            metadata: p["metadata"].clone(),
-        }
-    }).collect();
-
-    let (flights, ancillaries): (Vec<_>, Vec<_>) = domain_products.into_iter()
-        .partition(|p| p.product_type == altis_catalog::ProductType::Flight);
+    let tax_engine = state.catalog_cache.tax_engine().await.map_err(|e| {
+        tracing::error!("Failed to load tax codes: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok((airline, products, tax_engine))
+}
 
-    let mut offers = generator.generate_offers(
+async fn price_offers(
+    generator: &altis_offer::generator::OfferGenerator,
+    search_context: &altis_offer::features::SearchContext,
+    search_context_json: &serde_json::Value,
+    flights: Vec<altis_catalog::Product>,
+    ancillaries: Vec<altis_catalog::Product>,
+) -> Result<Vec<altis_offer::models::Offer>, StatusCode> {
+    generator.generate_offers(
         None, // customer_id
-        req.user_segment.clone(),
+        search_context.user_segment.clone(),
         search_context_json.clone(),
         flights,
         ancillaries,
     ).await.map_err(|e| {
         tracing::error!("Offer generation failed: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    
-    // 4. AI Ranking
-    let mut ranker = state.ranker.lock().await;
-    ranker.rank_offers_with_context(&search_context, &mut offers).await;
-    
-    // 5. Save generated offers to repository (for retrieval on accept)
-    for offer in &offers {
-        let val = serde_json::to_value(offer).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        state.offer_repo.save_offer(&val).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    }
-
-    // 6. Convert to response format
-    let responses: Vec<OfferResponse> = offers.into_iter()
-        .map(|offer| OfferResponse {
-            id: offer.id,
-            items: offer.items.iter().map(|item| OfferItemResponse {
-                id: item.id,
-                product_type: item.product_type.clone(),
-                name: item.name.clone(),
-                description: item.description.clone(),
-                price_nuc: item.price_nuc,
-                metadata: item.metadata.clone(),
-            }).collect(),
-            total_nuc: offer.total_nuc,
-            currency: offer.currency.clone(),
-            expires_at: offer.expires_at,
-        })
-        .collect();
-    
-    Ok(Json(responses))
+    })
 }
 
 /// GET /v1/offers/:id
@@ -173,6 +480,7 @@ pub async fn search_offers(
 pub async fn get_offer(
     State(state): State<AppState>,
     Path(offer_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<Json<OfferResponse>, StatusCode> {
     let offer_json = state.offer_repo.get_offer(offer_id).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
@@ -185,20 +493,12 @@ pub async fn get_offer(
         return Err(StatusCode::GONE);
     }
 
-    let response = OfferResponse {
-        id: offer.id,
-        items: offer.items.iter().map(|item| OfferItemResponse {
-            id: item.id,
-            product_type: item.product_type.clone(),
-            name: item.name.clone(),
-            description: item.description.clone(),
-            price_nuc: item.price_nuc,
-            metadata: item.metadata.clone(),
-        }).collect(),
-        total_nuc: offer.total_nuc,
-        currency: offer.currency.clone(),
-        expires_at: offer.expires_at,
+    let languages = accepted_languages(&headers);
+    let content = match offer.metadata["owner"].as_str() {
+        Some(owner) => airline_content(&state, owner, &languages).await,
+        None => Arc::default(),
     };
+    let response = offer_response(&offer, &content, &languages);
     
     Ok(Json(response))
 }
@@ -209,7 +509,7 @@ pub async fn accept_offer(
     State(state): State<AppState>,
     axum::Extension(claims): axum::Extension<crate::middleware::auth::CustomerClaims>,
     Path(offer_id): Path<Uuid>,
-    Json(req): Json<AcceptOfferRequest>,
+    Json(mut req): Json<AcceptOfferRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // 1. Get offer to verify and log
     let offer_json = state.offer_repo.get_offer(offer_id).await
@@ -223,6 +523,30 @@ pub async fn accept_offer(
     if offer.is_expired() {
         return Err(StatusCode::GONE);
     }
+    // Ancillaries for an existing booking join its order instead of making one
+    if let Some(order_id) = crate::ancillary_offers::attached_order(&offer) {
+        return crate::ancillary_offers::attach_offer(&state, &claims, &offer, order_id, &req).await.map(Json);
+    }
+    // Sandbox keys only book sandbox offers, and live callers never do
+    if crate::sandbox::is_sandbox_offer(&state, &offer) != claims.test {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    crate::profiles::apply_profile(&state, &claims, &mut req).await?;
+    if let Some(travelers) = req.travelers.as_mut() {
+        if let Err(errors) = check_travelers(&offer.items, travelers) {
+            tracing::debug!("Rejected travelers for offer {}: {}", offer_id, errors.join("; "));
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+    let mut items = offer.items.clone();
+    if let Err(errors) = assign_travelers(&mut items, &req.item_travelers, &traveler_indices(req.travelers.as_deref())) {
+        tracing::debug!("Rejected item travelers for offer {}: {}", offer_id, errors.join("; "));
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    // A number alerts can't be texted to is caught now, not on the day of travel
+    if req.contact_info.as_mut().is_some_and(|contact| !contact.normalize_phone()) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
 
     // 2. Log Telemetry
     let _ = state.telemetry.log_offer_accepted(altis_shared::models::events::OfferAcceptedEvent {
@@ -230,62 +554,82 @@ pub async fn accept_offer(
         customer_id: Some(req.customer_email.clone()),
         timestamp: chrono::Utc::now().timestamp(),
     }).await;
+    let _ = state.telemetry.log_training(&altis_offer::training::TrainingRecord::label(
+        altis_offer::training::TrainingLabel::Accepted,
+        &offer,
+    )).await;
 
     // 3. Create Order
     // If sub starts with did:, use it as customer_did
-    let (customer_id, customer_did) = if claims.sub.starts_with("did:") {
-        (format!("DID-{}", &claims.sub.chars().take(12).collect::<String>()), Some(claims.sub.clone()))
-    } else {
-        (claims.sub.clone(), None)
-    };
+    let (customer_id, customer_did) = crate::authz::customer_id_for(&claims);
 
-    // Calculate expiration based on airline rules or global default
-    let airline_id = offer.airline_id.ok_or(StatusCode::INTERNAL_SERVER_ERROR)?; 
-    let hold_seconds = if let Ok(Some(rule)) = state.catalog_repo.get_inventory_rule(airline_id, "FLIGHT").await {
-        rule["hold_duration_seconds"].as_u64().unwrap_or(state.business_rules.trip_hold_seconds)
-    } else {
-        state.business_rules.trip_hold_seconds
+    // Calculate expiration based on airline rules or global default.
+    // Partner offers are held on the partner's side, for our default.
+    let partner = offer.metadata["partner"].as_str().map(str::to_string);
+    let rules = match offer.airline_id {
+        Some(airline_id) => state.business_rules_for(airline_id).await,
+        None => Arc::new(state.business_rules.clone()),
+    };
+    let hold_seconds = match partner {
+        Some(_) => rules.trip_hold_seconds,
+        None => {
+            let airline_id = offer.airline_id.ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+            hold_policy(&state, airline_id, &customer_id, &offer.items).await?
+        }
     };
 
     let expires_at = (chrono::Utc::now() + chrono::Duration::seconds(hold_seconds as i64)).to_rfc3339();
 
-    // 4. Reserve Inventory (Hard Hold)
-    for item in &offer.items {
-        if item.product_type == "Flight" {
-            if let Some(product_id) = item.product_id {
-                let pid_str = product_id.to_string();
-                match state.redis.decr_flight_availability(&pid_str).await {
-                    Ok(Some(remaining)) if remaining < 0 => {
-                        // Rollback: increment back (simple version for now)
-                        let _ = state.redis.set_flight_availability(&pid_str, 0).await;
-                        return Err(StatusCode::CONFLICT); // Seat just taken
-                    }
-                    Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-                    _ => {}
-                }
-            }
+    // Group bookings get a deadline for submitting traveler names
+    let names_due_at = req.group_size
+        .filter(|size| *size >= altis_order::travelers::GROUP_BOOKING_MIN_PAX)
+        .map(|_| (chrono::Utc::now() + chrono::Duration::seconds(rules.group_name_deadline_seconds as i64)).to_rfc3339());
+
+    // 4. Reserve Inventory (Hard Hold); a partner's flights are booked with the partner
+    if let Some(partner) = &partner {
+        let partner_order = crate::interline::create_partner_order(&state, &offer, &req).await.map_err(|e| {
+            tracing::error!("OrderCreate for offer {} with interline partner {} failed: {}", offer_id, partner, e);
+            StatusCode::BAD_GATEWAY
+        })?;
+        for item in &mut items {
+            item.metadata["partner_order_id"] = serde_json::json!(partner_order.order_id);
+            item.metadata["partner_status"] = serde_json::json!(partner_order.status);
         }
     }
+    // Seats held for a waitlisted customer pass to this order
+    crate::waitlist::redeem_offer(&state, &claims, &offer).await?;
+    reserve_inventory(&state, &items).await?;
 
-    let order_id = state.order_repo.create_order(&serde_json::json!({
+    let order_id = match state.order_repo.create_order(&serde_json::json!({
         "customer_id": customer_id,
         "customer_email": req.customer_email,
         "customer_did": customer_did,
         "offer_id": offer_id,
+        "airline_id": offer.airline_id,
         "status": "PROPOSED",
-        "total_nuc": offer.total_nuc,
+        "total_nuc": offer.total.minor_units(),
         "currency": offer.currency,
         "contact_phone": req.contact_info.as_ref().and_then(|c| c.phone.clone()),
         "contact_first_name": req.contact_info.as_ref().and_then(|c| c.first_name.clone()),
         "contact_last_name": req.contact_info.as_ref().and_then(|c| c.last_name.clone()),
         "travelers": req.travelers,
         "expires_at": expires_at,
-    })).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        "group_size": req.group_size,
+        "names_due_at": names_due_at,
+        "test": claims.test,
+    })).await {
+        Ok(order_id) => order_id,
+        Err(_) => {
+            release_offer_inventory(&state, &offer.items).await;
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
 
     // 4. Add Order Items
-    for item in &offer.items {
+    for item in &items {
         let _ = state.order_repo.add_order_item(order_id, &serde_json::to_value(item).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?).await;
     }
+    crate::snapshots::record_order_snapshot(&state, order_id, std::slice::from_ref(&offer_json)).await;
     
     // 5. Release/Update offer status (optional, usually done by expiry or order link)
     
@@ -297,6 +641,248 @@ pub async fn accept_offer(
     })))
 }
 
+/// Checks the travelers of a new order: each one's details, their PTC
+/// against their date of birth, and together against the passenger types
+/// the offer's first flight was priced for. PTCs come back upper-cased.
+pub(crate) fn check_travelers(
+    items: &[altis_offer::models::OfferItem],
+    travelers: &mut [altis_core::iata::Traveler],
+) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    let mut records = Vec::new();
+    for traveler in travelers.iter_mut() {
+        match altis_order::travelers::validate_traveler(
+            Some(&traveler.ptc),
+            Some(traveler.first_name.0.trim()),
+            Some(traveler.last_name.0.trim()),
+            traveler.date_of_birth.as_ref().map(|dob| dob.0.trim()),
+            traveler.gender.as_deref(),
+        ) {
+            Ok(record) => {
+                traveler.ptc = record.ptc.clone();
+                records.push(record);
+            }
+            Err(problems) => errors.extend(problems.into_iter().map(|p| format!("traveler {}: {}", traveler.traveler_index, p))),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let first_flight = items.iter().find(|item| item.product_type.eq_ignore_ascii_case("FLIGHT")).and_then(|item| item.product_id);
+    let mut fares = std::collections::BTreeMap::new();
+    for item in items.iter().filter(|item| first_flight.is_some() && item.product_id == first_flight) {
+        if let Some(ptc) = item.metadata["ptc"].as_str() {
+            *fares.entry(ptc.to_string()).or_default() += item.quantity.max(1);
+        }
+    }
+    let errors = altis_order::travelers::check_party(&records, &fares);
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Indices of the travelers given for a new order
+pub(crate) fn traveler_indices(travelers: Option<&[altis_core::iata::Traveler]>) -> Vec<i32> {
+    travelers.unwrap_or_default().iter().map(|t| t.traveler_index).collect()
+}
+
+/// Gives ancillaries to the travelers `assignments` names, by item id, then
+/// checks every item given to someone is an ancillary for one of
+/// `travelers` (indices on the order). Flights are priced per passenger
+/// type and belong to no one traveler.
+pub(crate) fn assign_travelers(
+    items: &mut [altis_offer::models::OfferItem],
+    assignments: &HashMap<Uuid, i32>,
+    travelers: &[i32],
+) -> Result<(), Vec<String>> {
+    let mut errors: Vec<String> = assignments.keys()
+        .filter(|id| !items.iter().any(|item| item.id == **id))
+        .map(|id| format!("item {} is not on the offer", id))
+        .collect();
+    for item in items.iter_mut() {
+        if let Some(index) = assignments.get(&item.id) {
+            item.traveler_index = Some(*index);
+        }
+        let Some(index) = item.traveler_index else { continue };
+        if item.product_type.eq_ignore_ascii_case("FLIGHT") {
+            errors.push(format!("{}: a flight isn't for one traveler", item.name));
+        } else if !travelers.contains(&index) {
+            errors.push(format!("{}: no traveler {} on the order", item.name, index));
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Whether an inventory rule's resource type (`CARBON_OFFSET`) covers an
+/// offer item's product type (`CarbonOffset`)
+fn rule_covers(resource_type: &str, product_type: &str) -> bool {
+    let normalize = |t: &str| t.replace('_', "").to_ascii_uppercase();
+    normalize(resource_type) == normalize(product_type)
+}
+
+/// Departure date an item is held for: its own, else the offer's flight's
+fn departure_date(item: &altis_offer::models::OfferItem) -> Option<chrono::NaiveDate> {
+    let metadata = &item.metadata;
+    metadata["departure_date"].as_str()
+        .or_else(|| metadata["departure_time"].as_str().and_then(|t| t.get(..10)))
+        .and_then(|date| date.parse().ok())
+}
+
+/// Applies the airline's inventory rules to a new hold on `items`. The hold is
+/// refused on a blackout date (422) or when the customer already holds as
+/// many unpaid orders as a rule allows (409); otherwise the order may hold
+/// its inventory for the strictest matching rule's duration, or the global
+/// default when no rule matches.
+pub(crate) async fn hold_policy(
+    state: &AppState,
+    airline_id: Uuid,
+    customer_id: &str,
+    items: &[altis_offer::models::OfferItem],
+) -> Result<u64, StatusCode> {
+    let rules = state.catalog_cache.inventory_rules(airline_id).await.map_err(|e| {
+        tracing::error!("Failed to load inventory rules for airline {}: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let trip_date = items.iter().find_map(departure_date);
+
+    let mut hold_seconds: Option<u64> = None;
+    for raw in rules.iter() {
+        let rule: altis_catalog::InventoryRule = match serde_json::from_value(raw.clone()) {
+            Ok(rule) => rule,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable inventory rule {}: {}", raw["id"], e);
+                continue;
+            }
+        };
+        let Some(item) = items.iter().find(|item| rule_covers(&rule.resource_type, &item.product_type)) else {
+            continue;
+        };
+
+        let holds = match rule.max_holds_per_customer {
+            Some(_) => state.order_repo.count_customer_holds(customer_id, airline_id, &item.product_type).await.map_err(|e| {
+                tracing::error!("Failed to count holds of customer {}: {:?}", customer_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+            None => 0,
+        };
+        if let Err(refusal) = rule.check_hold(departure_date(item).or(trip_date), holds) {
+            tracing::info!("Hold refused for customer {}: {}", customer_id, refusal);
+            return Err(match refusal {
+                altis_catalog::HoldRefusal::Blackout { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+                altis_catalog::HoldRefusal::TooManyHolds { .. } => StatusCode::CONFLICT,
+            });
+        }
+        hold_seconds = Some(hold_seconds.map_or(rule.hold_duration_seconds, |h| h.min(rule.hold_duration_seconds)));
+    }
+    match hold_seconds {
+        Some(hold_seconds) => Ok(hold_seconds),
+        None => Ok(state.business_rules_for(airline_id).await.trip_hold_seconds),
+    }
+}
+
+/// Whether a flight item is for infants on a lap, who take no seat
+pub(crate) fn is_lap_infant(metadata: &serde_json::Value) -> bool {
+    metadata["lap_infant"].as_bool().unwrap_or(false)
+}
+
+/// Units each item takes from inventory. Every catalog product is listed;
+/// the ones without tracked inventory are skipped when reserving.
+fn offer_inventory(items: &[altis_offer::models::OfferItem]) -> Vec<(Uuid, i32)> {
+    items.iter()
+        .filter(|item| !is_lap_infant(&item.metadata))
+        .filter_map(|item| item.product_id.map(|product_id| (product_id, item.quantity.max(1))))
+        .collect()
+}
+
+/// Holds the inventory for every item (a seat per flight, units of any
+/// capacity-limited ancillary), all or nothing. Products without tracked
+/// inventory aren't limited.
+pub(crate) async fn reserve_inventory(state: &AppState, items: &[altis_offer::models::OfferItem]) -> Result<(), StatusCode> {
+    let units = offer_inventory(items);
+    for (reserved, (product_id, quantity)) in units.iter().enumerate() {
+        match state.inventory.reserve(*product_id, *quantity).await {
+            Ok(()) | Err(InventoryError::NotFound(_)) => {}
+            Err(e) => {
+                release_units(state, &units[..reserved]).await;
+                return Err(match e {
+                    InventoryError::InsufficientInventory { .. } => StatusCode::CONFLICT, // Sold out since the offer was made
+                    e => {
+                        tracing::error!("Failed to reserve {} unit(s) of product {}: {}", quantity, product_id, e);
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    }
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Gives back inventory held by [`reserve_inventory`] when the order it was
+/// for never got created
+pub(crate) async fn release_offer_inventory(state: &AppState, items: &[altis_offer::models::OfferItem]) {
+    release_units(state, &offer_inventory(items)).await;
+}
+
+pub(crate) async fn release_units(state: &AppState, units: &[(Uuid, i32)]) {
+    for (product_id, quantity) in units {
+        match state.inventory.release(*product_id, *quantity).await {
+            Ok(()) | Err(InventoryError::NotFound(_)) => {}
+            Err(e) => tracing::error!("Failed to release {} held unit(s) of product {}: {}", quantity, product_id, e),
+        }
+    }
+}
+
+/// Ancillaries whose stock and supplier are checked at once
+const AVAILABILITY_CONCURRENCY: usize = 16;
+
+/// Drops ancillaries that are sold out, or down to the stock their inventory
+/// rule keeps back (`min_availability_threshold`), so search doesn't offer
+/// what accept would refuse. Untracked products always stay; if inventory
+/// can't be read the product stays too and accept has the final say.
+/// Supplier-sourced products stay only if their supplier confirms it can
+/// sell them. Products are checked concurrently, in catalog order.
+pub(crate) async fn sellable_ancillaries(
+    state: &AppState,
+    airline_id: Uuid,
+    ancillaries: Vec<altis_catalog::Product>,
+    search_context: &serde_json::Value,
+) -> Vec<altis_catalog::Product> {
+    let rules = state.catalog_cache.inventory_rules(airline_id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load inventory rules for airline {}: {:?}", airline_id, e);
+        Default::default()
+    });
+
+    let sellable = |product: altis_catalog::Product| {
+        let rules = rules.clone();
+        async move {
+            let resource_type = serde_json::to_value(&product.product_type).unwrap_or_default();
+            let threshold = rules.iter()
+                .find(|rule| rule["resource_type"] == resource_type)
+                .and_then(|rule| rule["min_availability_threshold"].as_i64())
+                .unwrap_or(0);
+            match state.inventory.get(product.id).await {
+                Ok(Some(item)) if i64::from(item.available_quantity) <= threshold => {
+                    tracing::debug!("Ancillary {} withheld: {} left", product.product_code, item.available_quantity);
+                    return None;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to read inventory for ancillary {}: {}", product.product_code, e),
+            }
+            let confirmed = match altis_core::supplier::SupplierProduct::from_metadata(&product.metadata) {
+                Some(supplier_product) => state.suppliers.is_available(&supplier_product, 1, search_context).await,
+                None => true,
+            };
+            confirmed.then_some(product)
+        }
+    };
+
+    futures_util::stream::iter(ancillaries)
+        .map(sellable)
+        .buffered(AVAILABILITY_CONCURRENCY)
+        .filter_map(std::future::ready)
+        .collect()
+        .await
+}
+
 /// DELETE /v1/offers/:id
 /// Expire an offer (customer cancels)
 pub async fn expire_offer(
@@ -310,3 +896,32 @@ pub async fn expire_offer(
     
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altis_offer::models::OfferItem;
+
+    fn item(product_type: &str, name: &str) -> OfferItem {
+        OfferItem::new(product_type.to_string(), None, None, name.to_string(), None, Money::nuc(1000), 1, serde_json::json!({}))
+    }
+
+    #[test]
+    fn test_assign_travelers() {
+        let mut items = vec![item("FLIGHT", "AL101"), item("BAG", "Checked bag"), item("LOUNGE", "Lounge pass")];
+        let (flight, bag, lounge) = (items[0].id, items[1].id, items[2].id);
+
+        assert!(assign_travelers(&mut items, &HashMap::from([(bag, 1)]), &[0, 1]).is_ok());
+        assert_eq!(items.iter().map(|i| i.traveler_index).collect::<Vec<_>>(), vec![None, Some(1), None]);
+
+        // Nobody by that index, a flight given to one traveler, an item from elsewhere
+        let stranger = Uuid::new_v4();
+        let errors = assign_travelers(&mut items, &HashMap::from([(lounge, 2), (flight, 0), (stranger, 0)]), &[0, 1]).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], format!("item {} is not on the offer", stranger));
+        assert!(errors.contains(&"Lounge pass: no traveler 2 on the order".to_string()));
+
+        // An item already given to someone needs them on the order too
+        assert_eq!(assign_travelers(&mut items[1..2], &HashMap::new(), &[]).unwrap_err(), vec!["Checked bag: no traveler 1 on the order"]);
+    }
+}
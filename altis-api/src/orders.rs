@@ -1,11 +1,20 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    Extension,
     Json,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use crate::state::AppState;
+use crate::authz::{authorize_order, issue_fulfillment_grant, owns_order, verify_fulfillment_grant};
+use crate::middleware::auth::{AdminClaims, CustomerClaims};
+use altis_core::tenant::TenantContext;
+use altis_order::changes::{diff, DiffLine, OrderDiff};
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::{self, Currency, Money, MoneyError};
+use altis_catalog::InventoryError;
 
 // ============================================================================
 // Request/Response Types
@@ -24,6 +33,7 @@ pub struct OrderResponse {
     pub id: Uuid,
     pub offer_id: Option<Uuid>,
     pub customer_id: String,
+    #[serde(serialize_with = "altis_shared::pii::email")]
     pub customer_email: Option<altis_shared::pii::Masked<String>>,
     pub customer_did: Option<String>,
     pub status: String,
@@ -33,29 +43,77 @@ pub struct OrderResponse {
     pub total_nuc: i32,
     pub currency: String,
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub group_size: Option<i32>,
+    pub names_due_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Placed with a sandbox key
+    #[serde(default)]
+    pub test: bool,
+    /// When a payment waiting on the customer's authentication is given up
+    #[serde(default)]
+    pub payment_action_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// What the customer must do to finish paying, e.g. a 3-D Secure challenge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payment_action: Option<altis_core::payment::NextAction>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderItemResponse {
     pub id: Uuid,
     pub product_id: Option<Uuid>,
     pub product_type: String,
     pub name: String,
+    /// Price of one unit
     pub price_nuc: i32,
+    #[serde(default)]
+    pub quantity: Option<i32>,
+    /// Units of `quantity` refunded since purchase
+    #[serde(default)]
+    pub refunded_quantity: i32,
     pub status: String,
     pub revenue_status: String,
     pub operating_carrier_id: Option<Uuid>,
     pub net_rate_nuc: Option<i32>,
     pub commission_nuc: Option<i32>,
     pub metadata: serde_json::Value,
+    #[serde(default)]
+    pub tax_nuc: i32,
+    #[serde(default)]
+    pub taxes: Vec<altis_catalog::TaxLine>,
+    /// Whose item it is, by index on the order; none when it's the booking's
+    #[serde(default)]
+    pub traveler_index: Option<i32>,
+    /// That traveler's id, once their name is on the order
+    #[serde(default)]
+    pub traveler_id: Option<Uuid>,
+}
+
+impl OrderItemResponse {
+    /// Units still held: bought and not refunded
+    pub fn active_units(&self) -> i32 {
+        (self.quantity.unwrap_or(1).max(1) - self.refunded_quantity).max(0)
+    }
+
+    /// What the units still held cost, with their share of the tax (refunds
+    /// give back the last units' share first, as `altis_order` splits it)
+    pub fn held_amount(&self) -> Result<Money, MoneyError> {
+        let tax = Money::from_nuc_i32(self.tax_nuc).split(self.quantity.unwrap_or(1).max(1) as usize)?;
+        let units = self.active_units();
+        Money::from_nuc_i32(self.price_nuc).checked_mul(units as i64)?
+            .checked_add(Money::sum(Currency::NUC, tax.into_iter().take(units as usize))?)
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct PayOrderRequest {
     pub payment_method: String,
+    #[serde(default)]
     pub payment_token: String,
     pub payment_reference: Option<String>,
+    /// A saved card to charge instead of a new payment reference
+    pub payment_method_id: Option<Uuid>,
+    /// Where the provider sends the customer back after a 3-D Secure challenge
+    pub return_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -87,13 +145,89 @@ pub struct MealSelection {
 pub struct FulfillmentResponse {
     pub order_id: Uuid,
     pub barcodes: Vec<BarcodeResponse>,
+    #[serde(default)]
+    pub scheduled: Vec<ScheduledDeliveryResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledDeliveryResponse {
+    pub item_id: Uuid,
+    #[serde(default)]
+    pub traveler_index: Option<i32>,
+    pub deliver_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// One barcode: a traveler's on a flight segment or for their own
+/// ancillary, or the booking's when no traveler is set
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BarcodeResponse {
     pub item_id: Uuid,
+    #[serde(default)]
+    pub traveler_id: Option<Uuid>,
+    #[serde(default)]
+    pub traveler_index: Option<i32>,
+    /// Flight, origin, destination and departure of the segment it boards
+    #[serde(default)]
+    pub segment: Option<serde_json::Value>,
+    /// Which of the item's units it is, from 0
+    #[serde(default)]
+    pub unit_index: i32,
     pub barcode: String,
     pub qr_code_url: Option<String>,
+    /// Signed pass for the QR code, which scanners can check offline
+    #[serde(default)]
+    pub signed_payload: Option<String>,
+    /// Set once the unit went unused past departure; it no longer scans
+    #[serde(default)]
+    pub expired_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TravelerImportResponse {
+    pub order_id: Uuid,
+    pub accepted: usize,
+    pub rejected: Vec<altis_order::travelers::RowError>,
+    pub over_capacity: Vec<String>,
+    pub travelers_total: usize,
+    pub names_complete: bool,
+    pub fulfillment_generated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProtectionQuote {
+    #[serde(flatten)]
+    pub connection: altis_order::protection::SelfConnection,
+    #[serde(rename = "price_nuc", serialize_with = "money::nuc::serialize")]
+    pub price: Money,
+    pub protected: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferItemRequest {
+    /// Recipient order; defaults to the current order (reassigning a traveler)
+    pub target_order_id: Option<Uuid>,
+    pub traveler_index: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferItemResponse {
+    pub item_id: Uuid,
+    pub from_order_id: Uuid,
+    pub to_order_id: Uuid,
+    pub traveler_index: Option<i32>,
+    pub barcode: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefundItemUnitsRequest {
+    /// How many of the item's unused units to refund
+    pub units: i32,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QrGrantQuery {
+    pub grant: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -105,6 +239,9 @@ pub struct ConsumeFulfillmentRequest {
 #[derive(Debug, Deserialize)]
 pub struct ReshopOrderRequest {
     pub add_products: Vec<Uuid>,
+    /// Items the customer would give up
+    #[serde(default)]
+    pub remove_items: Vec<Uuid>,
 }
 
 #[derive(Debug, Serialize)]
@@ -113,6 +250,22 @@ pub struct ReshopOrderResponse {
     pub new_total_nuc: i32,
     pub additional_nuc: i32,
     pub items_to_add: Vec<OrderItemResponse>,
+    pub changes: OrderDiff,
+}
+
+/// A change to preview, in the body its own endpoint takes
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangePreviewRequest {
+    Reshop(ReshopOrderRequest),
+    Reaccommodation(AcceptReaccommodationRequest),
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangePreviewResponse {
+    pub order_id: Uuid,
+    #[serde(flatten)]
+    pub diff: OrderDiff,
 }
 
 // ============================================================================
@@ -123,11 +276,10 @@ pub struct ReshopOrderResponse {
 /// Retrieve order details
 pub async fn get_order(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
 ) -> Result<Json<OrderResponse>, StatusCode> {
-    let order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
     let response: OrderResponse = serde_json::from_value(order_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -135,17 +287,54 @@ pub async fn get_order(
     Ok(Json(response))
 }
 
+/// Units each item holds in inventory: a seat per flight passenger but lap
+/// infants, the quantity of an ancillary less any units refunded.
+/// Re-accommodation proposals hold their seats until accepted, and give them
+/// back themselves when declined or expired.
+fn order_inventory(items: &[OrderItemResponse]) -> Vec<(Uuid, i32)> {
+    items.iter()
+        .filter(|item| item.status != "REACCOMMODATED" && item.status != "CANCELLED")
+        .filter(|item| item.active_units() > 0 && !crate::offers::is_lap_infant(&item.metadata))
+        .filter_map(|item| item.product_id.map(|product_id| (product_id, item.active_units())))
+        .collect()
+}
+
+/// Turns the order's held inventory into sold units once it is paid
+pub(crate) async fn commit_inventory(state: &AppState, items: &[OrderItemResponse]) {
+    for (product_id, quantity) in order_inventory(items) {
+        match state.inventory.commit(product_id, quantity).await {
+            Ok(()) | Err(InventoryError::NotFound(_)) => {}
+            Err(e) => tracing::warn!("Paid order's units of product {} were not held: {}", product_id, e),
+        }
+    }
+}
+
+/// Puts a cancelled order's inventory back on sale: sold units if it had
+/// been paid, held ones otherwise
+pub(crate) async fn return_inventory(state: &AppState, items: &[OrderItemResponse], was_paid: bool) {
+    for (product_id, quantity) in order_inventory(items) {
+        let returned = if was_paid {
+            state.inventory.restock(product_id, quantity).await
+        } else {
+            state.inventory.release(product_id, quantity).await
+        };
+        match returned {
+            Ok(()) | Err(InventoryError::NotFound(_)) => {}
+            Err(e) => tracing::error!("Failed to return {} unit(s) of product {}: {}", quantity, product_id, e),
+        }
+    }
+}
+
 /// POST /v1/orders/:id/pay
 /// Pay for an order
 pub async fn pay_order(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
     Json(req): Json<PayOrderRequest>,
 ) -> Result<Json<OrderResponse>, StatusCode> {
     // 1. Get order to verify exists
-    let order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
     let mut order: OrderResponse = serde_json::from_value(order_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -164,6 +353,16 @@ pub async fn pay_order(
         }
     }
 
+    // Orders on an installment plan are paid off by the plan
+    if order.status == "HOLD_PARTIALLY_PAID" {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let payment_reference = match req.payment_method_id {
+        Some(id) => Some(crate::payment_methods::stored_token(&state, &claims, id, order.test).await?),
+        None => req.payment_reference.clone(),
+    };
+
     // 2. Lock-in: Transition to PAYMENT_PENDING
     // This prevents the background cleanup worker from releasing inventory
     state.order_repo.update_order_status(order_id, "PAYMENT_PENDING").await
@@ -176,12 +375,14 @@ pub async fn pay_order(
         amount: order.total_nuc,
         currency: order.currency.clone(),
         status: altis_core::payment::PaymentStatus::RequiresPaymentMethod,
-        reference: req.payment_reference.clone(),
+        reference: payment_reference.clone(),
         client_secret: None,
+        return_url: req.return_url.clone(),
+        next_action: None,
         created_at: chrono::Utc::now(),
     };
 
-    let payment_status = state.payment_orchestrator.process_payment(&intent).await
+    let payment_status = state.payments(order.test).process_payment(&intent).await
         .map_err(|e| {
             tracing::error!("Payment Orchestration Failed: {:?}", e);
             StatusCode::INTERNAL_SERVER_ERROR // This will be caught by CB middleware
@@ -192,30 +393,92 @@ pub async fn pay_order(
         if payment_status == altis_core::payment::PaymentStatus::Processing {
              return Ok(Json(order));
         }
+        // The card issuer wants the customer to authenticate first
+        if payment_status == altis_core::payment::PaymentStatus::RequiresAction {
+            return crate::payment_actions::await_payment_action(&state, order, &intent.id).await.map(Json);
+        }
+        // Declines are kept in the audit trail as chargeback evidence
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            "PAYMENT_FAILED",
+            None,
+            Some(serde_json::json!({"status": payment_status, "reference": req.payment_reference, "payment_method_id": req.payment_method_id})),
+            "SYSTEM",
+            Some("Payment declined"),
+        ).await;
         return Err(StatusCode::PAYMENT_REQUIRED);
     }
 
-    state.order_repo.update_order_status(order_id, "PAID").await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !complete_payment(&state, &order, &["PAYMENT_PENDING"], payment_reference.as_deref(), "Order paid via API").await? {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // 4. Return updated order
+    order.status = "PAID".to_string();
+    Ok(Json(order))
+}
+
+/// Books a payment the provider has taken: the order becomes PAID, its held
+/// inventory is sold and the sale journaled. Only the caller that moves the
+/// order out of `from` does this; false for the others, e.g. a webhook
+/// arriving just after the customer's own confirmation.
+pub(crate) async fn complete_payment(
+    state: &AppState,
+    order: &OrderResponse,
+    from: &[&str],
+    payment_reference: Option<&str>,
+    reason: &str,
+) -> Result<bool, StatusCode> {
+    let order_id = order.id;
+    let moved = state.order_repo.transition_order_status(order_id, from, "PAID").await.map_err(|e| {
+        tracing::error!("Failed to mark order {} paid: {:?}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !moved {
+        return Ok(false);
+    }
+    commit_inventory(state, &order.items).await;
+    crate::suppliers::confirm_supplier_items(state, order_id, &order.items).await;
 
     // Log Audit Change
     let _ = state.order_repo.add_order_change(
-        order_id, 
-        "PAYMENT_RECEIVED", 
-        Some(serde_json::json!({"status": "PROPOSED"})), 
-        Some(serde_json::json!({"status": "PAID"})), 
-        "SYSTEM", 
-        Some("Order paid via API")
+        order_id,
+        "PAYMENT_RECEIVED",
+        Some(serde_json::json!({"status": order.status})),
+        Some(serde_json::json!({"status": "PAID"})),
+        "SYSTEM",
+        Some(reason)
     ).await;
 
+    let amount = Money::nuc(order.total_nuc as i64);
+    let tax = Money::nuc(order.items.iter().map(|item| item.tax_nuc as i64).sum());
+    crate::finance::post_journal(state, JournalTransaction::sale(order_id, amount, tax)).await;
+    crate::finance::post_journal(state, JournalTransaction::payment(order_id, amount, payment_reference)).await;
+
+    finish_paid_order(state, order).await;
+    Ok(true)
+}
+
+/// What follows an order being paid in full: its invoice, telemetry and
+/// fulfillment. Reached from checkout and from the last installment of a plan.
+pub(crate) async fn finish_paid_order(state: &AppState, order: &OrderResponse) {
+    let order_id = order.id;
+    crate::documents::invoice_paid_order(state, order_id, order.total_nuc as i64).await;
+
     // Log Telemetry
-    let _ = state.telemetry.log_order_paid(altis_shared::models::events::OrderPaidEvent {
+    let paid = altis_shared::models::events::OrderPaidEvent {
         order_id,
         offer_id: order.offer_id, // Need to add to OrderResponse or fetch
         customer_id: order.customer_id.clone(),
         total_nuc: order.total_nuc,
+        currency: "NUC".to_string(),
         timestamp: chrono::Utc::now().timestamp(),
-    }).await;
+    };
+    let _ = state.telemetry.log_order_paid(paid.clone()).await;
+    crate::analytics::record_conversion(state, order_id, &order.customer_id, order.total_nuc).await;
+    if let Some(offer_id) = order.offer_id {
+        let _ = state.telemetry.log_training(&altis_offer::training::TrainingRecord::paid(offer_id, order_id, order.total_nuc)).await;
+    }
 
     let _ = state.telemetry.log_settlement(altis_shared::models::events::SettlementEvent {
         order_id,
@@ -225,26 +488,213 @@ pub async fn pay_order(
         timestamp: chrono::Utc::now().timestamp(),
     }).await;
 
-    // 3. Generate fulfillment records (barcodes) for each item
-    for item in &order.items {
-        let barcode = format!("ALTIS-{}-{}", order_id.simple(), item.id.simple());
-        let _ = state.order_repo.create_fulfillment(order_id, item.id, "BARCODE", &barcode).await;
+    // Fulfillment records (barcodes) for each item; group orders wait
+    // until every traveler name is in
+    if names_complete(order) {
+        generate_fulfillment(state, order).await;
+    } else {
+        tracing::info!("Order {} paid; fulfillment deferred until traveler names are complete", order_id);
     }
+    announce_paid(state, &paid).await;
+}
 
-    // 4. Return updated order
-    order.status = "PAID".to_string();
-    Ok(Json(order))
+/// Publishes `order.paid` once the order's barcodes exist, so consumers
+/// such as the itinerary email find them
+async fn announce_paid(state: &AppState, paid: &altis_shared::models::events::OrderPaidEvent) {
+    use altis_core::events::{registry, ORDER_PAID_TOPIC};
+
+    let payload = match registry().seal(paid) {
+        Ok(envelope) => serde_json::json!(envelope).to_string(),
+        Err(e) => {
+            tracing::error!("Order paid event rejected by its schema: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = state.kafka.publish(ORDER_PAID_TOPIC, &paid.order_id.to_string(), &payload).await {
+        tracing::error!("Failed to publish order.paid for order {}: {}", paid.order_id, e);
+    }
+}
+
+/// POST /v1/orders/:id/travelers/import
+/// Bulk upload travelers for a group order (CSV or XLSX)
+pub async fn import_travelers(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<TravelerImportResponse>, StatusCode> {
+    use altis_order::travelers;
+
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let order: OrderResponse = serde_json::from_value(order_json.clone())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 1. Names can only be submitted on live orders before the deadline
+    if order.status == "CANCELLED" || order.status == "EXPIRED" {
+        return Err(StatusCode::CONFLICT);
+    }
+    if let Some(names_due_at) = order.names_due_at {
+        if chrono::Utc::now() > names_due_at {
+            return Err(StatusCode::GONE);
+        }
+    }
+
+    // 2. Parse and validate rows
+    let content_type = headers.get(header::CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or_default();
+
+    let parsed = if content_type.contains("spreadsheetml") {
+        travelers::parse_xlsx(&body)
+    } else if content_type.starts_with("text/csv") || content_type.starts_with("text/plain") {
+        travelers::parse_csv(&body)
+    } else {
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+    .map_err(|e| {
+        tracing::warn!("Traveler import for order {} rejected: {}", order_id, e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    // 3. Fit accepted rows into the free traveler slots
+    let existing: Vec<i32> = order_json["travelers"].as_array().into_iter().flatten()
+        .filter_map(|t| t["traveler_index"].as_i64().map(|i| i as i32))
+        .collect();
+    let (records, over_capacity) = travelers::assign_indices(parsed.accepted, &existing, order.group_size);
+
+    let rows: Vec<serde_json::Value> = records.iter()
+        .map(serde_json::to_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !rows.is_empty() {
+        state.order_repo.save_travelers(order_id, &rows).await
+            .map_err(|e| {
+                tracing::error!("Failed to save travelers for order {}: {:?}", order_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            "TRAVELERS_IMPORTED",
+            None,
+            Some(serde_json::json!({"accepted": rows.len(), "rejected": parsed.rejected.len()})),
+            "CUSTOMER",
+            Some("Bulk traveler upload")
+        ).await;
+    }
+
+    // 4. Paid orders get their fulfillment as soon as the names are complete
+    let updated_json = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let updated: OrderResponse = serde_json::from_value(updated_json.clone())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let complete = names_complete(&updated);
+    let already_fulfilled = updated_json["fulfillment"].as_array().is_some_and(|f| !f.is_empty());
+    let fulfillment_generated = complete && updated.status == "PAID" && !already_fulfilled;
+    if fulfillment_generated {
+        generate_fulfillment(&state, &updated).await;
+    }
+
+    Ok(Json(TravelerImportResponse {
+        order_id,
+        accepted: rows.len(),
+        rejected: parsed.rejected,
+        over_capacity,
+        travelers_total: updated.travelers.as_ref().map_or(0, |t| t.len()),
+        names_complete: complete,
+        fulfillment_generated,
+    }))
+}
+
+/// Group orders are complete once every seat has a traveler; others always are.
+fn names_complete(order: &OrderResponse) -> bool {
+    match order.group_size {
+        Some(size) => order.travelers.as_ref().map_or(0, |t| t.len()) as i32 >= size,
+        None => true,
+    }
+}
+
+/// Issues barcodes for products delivered on payment and schedules the rest
+/// (per the product's delivery policy) for the delivery worker.
+async fn generate_fulfillment(state: &AppState, order: &OrderResponse) {
+    fulfill_items(state, order, order.items.iter()).await;
+}
+
+/// Fulfillment for items added to an order after it was paid, e.g. an
+/// upsell. Group orders still short of names get it with the rest.
+pub(crate) async fn fulfill_added_items(state: &AppState, order: &OrderResponse, added: &[Uuid]) {
+    if !names_complete(order) {
+        return;
+    }
+    let items = order.items.iter().filter(|item| added.contains(&item.id));
+    fulfill_items(state, order, items).await;
+}
+
+/// One record per traveler or unit an item covers (see `delivery::fulfillment_holders`).
+/// Delivery is timed from the order's first departure.
+async fn fulfill_items<'a>(
+    state: &AppState,
+    order: &OrderResponse,
+    to_fulfill: impl Iterator<Item = &'a OrderItemResponse>,
+) {
+    let order_id = order.id;
+    let travelers = order.travelers.as_deref().unwrap_or_default();
+    let now = chrono::Utc::now();
+    let departure = order.items.iter()
+        .filter(|i| i.product_type.eq_ignore_ascii_case("FLIGHT"))
+        .filter_map(|i| i.metadata["departure_time"].as_str())
+        .filter_map(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .min();
+
+    for item in to_fulfill {
+        // Earned at cover start or departure if no scan earns it first
+        let item_departure = item.metadata["departure_time"].as_str()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .or(departure);
+        let recognition = altis_catalog::RecognitionPolicy::for_item(&item.product_type, &item.metadata);
+        let mut recognize_at = recognition.recognize_at(&item.metadata, item_departure);
+        if matches!(recognition, altis_catalog::RecognitionPolicy::AtDeparture { .. }) {
+            // Units unused by then expire, so not while their passes still scan
+            recognize_at = recognize_at.max(item_departure.map(|d| d + chrono::Duration::hours(state.scanning.pass_closes_hours)));
+        }
+        if let Some(recognize_at) = recognize_at {
+            if let Err(e) = state.order_repo.schedule_revenue_recognition(item.id, recognize_at).await {
+                tracing::error!("Failed to schedule revenue recognition for item {} of order {}: {:?}", item.id, order_id, e);
+            }
+        }
+
+        let policy = altis_catalog::DeliveryPolicy::from_metadata(&item.metadata);
+        let deliver_at = policy.deliver_at(departure, now);
+        for holder in crate::delivery::fulfillment_holders(item, travelers) {
+            let record = serde_json::json!(holder);
+            let issued = match deliver_at {
+                Some(deliver_at) => state.order_repo.schedule_fulfillment(order_id, item.id, &record, "BARCODE", deliver_at).await,
+                None => {
+                    let barcode = crate::delivery::barcode_for(order_id, item.id, holder.traveler_index, holder.unit_index);
+                    state.order_repo.create_fulfillment(order_id, item.id, &record, "BARCODE", &barcode).await
+                }
+            };
+            if let Err(e) = issued {
+                tracing::error!("Failed to issue fulfillment for item {} of order {}: {:?}", item.id, order_id, e);
+            }
+        }
+    }
 }
 
 /// POST /v1/orders/:id/payment-intent
 /// Initialize a payment intent for the order
 pub async fn initialize_payment_intent(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
 ) -> Result<Json<PaymentIntentResponse>, StatusCode> {
-    let order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
     let order: OrderResponse = serde_json::from_value(order_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -256,10 +706,13 @@ pub async fn initialize_payment_intent(
         }
     }
 
-    let intent = state.payment_orchestrator.initialize_payment(
+    let currency = Currency::new(&order.currency).map_err(|e| {
+        tracing::error!("Order {} has an unusable currency: {}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let intent = state.payments(order.test).initialize_payment(
         order_id, 
-        order.total_nuc, 
-        &order.currency
+        Money::new(order.total_nuc as i64, currency),
     ).await.map_err(|e| {
         tracing::error!("Failed to initialize payment: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
@@ -277,15 +730,14 @@ pub async fn initialize_payment_intent(
 /// Customize order (select seats, meals)
 pub async fn customize_order(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
     Json(_req): Json<CustomizeOrderRequest>,
 ) -> Result<Json<OrderResponse>, StatusCode> {
     // Mock customization logic (metadata updates)
     // In production, this would update item metadata in order_items table
     
-    let order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
     let response: OrderResponse = serde_json::from_value(order_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -293,30 +745,337 @@ pub async fn customize_order(
     Ok(Json(response))
 }
 
+/// GET /v1/orders/:id/protection
+/// Quote missed connection protection for self-connections in the order
+pub async fn get_protection_quote(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<Vec<ProtectionQuote>>, StatusCode> {
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let order: altis_order::Order = serde_json::from_value(order_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(protection_quotes(&order)?))
+}
+
+/// POST /v1/orders/:id/protection
+/// Add protection for every unprotected self-connection (before payment)
+pub async fn purchase_protection(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let order: altis_order::Order = serde_json::from_value(order_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if order.status != altis_order::OrderStatus::Proposed {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let terms = altis_order::protection::ProtectionTerms::default();
+    let mut added_nuc = 0;
+    for quote in protection_quotes(&order)?.into_iter().filter(|q| !q.protected) {
+        let item = altis_order::protection::protection_item(&quote.connection, &terms)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let item_json = serde_json::to_value(&item).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        state.order_repo.add_order_item(order_id, &item_json).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        added_nuc += item.price.to_i32().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    if added_nuc > 0 {
+        state.order_repo.adjust_order_total(order_id, added_nuc).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            "PROTECTION_ADDED",
+            Some(serde_json::json!({"total_nuc": order.total.minor_units()})),
+            Some(serde_json::json!({"total_nuc": order.total.minor_units() + added_nuc as i64})),
+            "CUSTOMER",
+            Some("Missed connection protection purchased"),
+        ).await;
+    }
+
+    let updated = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let response: OrderResponse = serde_json::from_value(updated)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(response))
+}
+
+fn protection_quotes(order: &altis_order::Order) -> Result<Vec<ProtectionQuote>, StatusCode> {
+    let terms = altis_order::protection::ProtectionTerms::default();
+    let protected: std::collections::HashSet<String> = order.items.iter()
+        .filter(|i| i.product_type == altis_order::protection::PROTECTION_PRODUCT_TYPE)
+        .filter_map(|i| i.metadata["outbound_item_id"].as_str().map(|s| s.to_string()))
+        .collect();
+
+    altis_order::protection::detect_self_connections(&order.items, &terms)
+        .into_iter()
+        .map(|connection| Ok(ProtectionQuote {
+            price: altis_order::protection::price_protection(&connection, &terms)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+            protected: protected.contains(&connection.outbound_item_id.to_string()),
+            connection,
+        }))
+        .collect()
+}
+
+/// POST /v1/orders/:id/items/:item_id/transfer
+/// Gift or reassign an unused ancillary; its barcode is reissued to the recipient
+pub async fn transfer_item(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path((order_id, item_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<TransferItemRequest>,
+) -> Result<Json<TransferItemResponse>, StatusCode> {
+    authorize_order(&state, &claims, order_id).await?;
+
+    let target_order_id = req.target_order_id.unwrap_or(order_id);
+    if target_order_id == order_id && req.traveler_index.is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if target_order_id != order_id {
+        let target = state.order_repo.get_order(target_order_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        if matches!(target["status"].as_str(), Some("CANCELLED" | "EXPIRED" | "ARCHIVED")) {
+            return Err(StatusCode::CONFLICT);
+        }
+    }
+
+    // Fresh barcode: the old one stays on record but is voided
+    let barcode = format!(
+        "ALTIS-{}-{}-{}",
+        target_order_id.simple(),
+        item_id.simple(),
+        &Uuid::new_v4().simple().to_string()[..8],
+    );
+
+    let transferred = state.order_repo.transfer_order_item(item_id, order_id, target_order_id, req.traveler_index, &barcode).await
+        .map_err(|e| {
+            tracing::error!("Failed to transfer item {} from order {}: {:?}", item_id, order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !transferred {
+        // Flights, consumed, refunded or foreign items can't be transferred
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let change = serde_json::json!({
+        "item_id": item_id,
+        "from_order_id": order_id,
+        "to_order_id": target_order_id,
+        "traveler_index": req.traveler_index,
+    });
+    let _ = state.order_repo.add_order_change(order_id, "ITEM_TRANSFERRED_OUT", None, Some(change.clone()), &claims.sub, None).await;
+    if target_order_id != order_id {
+        let _ = state.order_repo.add_order_change(target_order_id, "ITEM_TRANSFERRED_IN", None, Some(change), &claims.sub, None).await;
+    }
+
+    Ok(Json(TransferItemResponse {
+        item_id,
+        from_order_id: order_id,
+        to_order_id: target_order_id,
+        traveler_index: req.traveler_index,
+        barcode,
+    }))
+}
+
+/// POST /v1/orders/:id/items/:item_id/refund
+/// Refund some unused units of an item, keeping the rest on the order
+pub async fn refund_item_units(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path((order_id, item_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<RefundItemUnitsRequest>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let reason = req.reason.as_deref().unwrap_or("Customer refunded unused units");
+    refund_units(&state, &order_json, item_id, req.units, reason, &claims.sub).await?;
+
+    let updated = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let response: OrderResponse = serde_json::from_value(updated)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(response))
+}
+
+/// Refunds `units` unused units of an item of `order_json` (as from
+/// `get_order`): they go back on sale, their barcodes are voided, and a paid
+/// order gets the money back with a credit note. The refund's status, if
+/// there was money to return.
+pub(crate) async fn refund_units(
+    state: &AppState,
+    order_json: &serde_json::Value,
+    item_id: Uuid,
+    units: i32,
+    reason: &str,
+    changed_by: &str,
+) -> Result<Option<altis_core::payment::PaymentStatus>, StatusCode> {
+    let mut order: altis_order::Order = serde_json::from_value(order_json.clone())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let order_id = order.id;
+
+    let paid = match order.status {
+        altis_order::OrderStatus::Paid | altis_order::OrderStatus::Fulfilled => true,
+        altis_order::OrderStatus::Proposed | altis_order::OrderStatus::Locked => false,
+        _ => return Err(StatusCode::CONFLICT),
+    };
+    let item = order.items.iter().find(|i| i.id == item_id).ok_or(StatusCode::NOT_FOUND)?;
+    // Lap infants gave up no seat to put back on sale
+    let product_id = item.product_id.filter(|_| !crate::offers::is_lap_infant(&item.metadata));
+    let (price, tax) = altis_order::ChangeHandler::refund_units(&mut order, &item_id, units)
+        .map_err(|e| match e {
+            altis_order::changes::ChangeError::ValidationFailed(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::CONFLICT,
+        })?;
+    let amount = price.checked_add(tax).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let amount_nuc = amount.to_i32().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Claimed first, so the same units are never paid back twice
+    let refunded = state.order_repo.refund_item_units(item_id, units).await
+        .map_err(|e| {
+            tracing::error!("Failed to refund {} unit(s) of item {}: {:?}", units, item_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !refunded {
+        // Used or refunded since the order was read
+        return Err(StatusCode::CONFLICT);
+    }
+    state.order_repo.adjust_order_total(order_id, -amount_nuc).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if let Some(product_id) = product_id {
+        let returned = if paid {
+            state.inventory.restock(product_id, units).await
+        } else {
+            state.inventory.release(product_id, units).await
+        };
+        match returned {
+            Ok(()) | Err(InventoryError::NotFound(_)) => {}
+            Err(e) => tracing::error!("Failed to return {} unit(s) of product {}: {}", units, product_id, e),
+        }
+    }
+
+    let refund_status = if paid && amount.is_positive() {
+        let refunded_units = order.items.iter().find(|i| i.id == item_id).map_or(0, |i| i.refunded_quantity);
+        let key = format!("item-refund-{}-{}", item_id.simple(), refunded_units);
+        let status = state.payments(order_json["test"].as_bool().unwrap_or(false))
+            .refund_payment(order_id, Money::new(amount.minor_units(), Currency::new(&order.currency).unwrap_or(Currency::NUC)), &key)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("Refund of {} unit(s) of item {} failed: {}", units, item_id, e);
+                altis_core::payment::PaymentStatus::Failed
+            });
+        if status == altis_core::payment::PaymentStatus::Succeeded {
+            crate::documents::issue_for_order(state, order_id, "CREDIT_NOTE", amount.minor_units()).await;
+            crate::finance::post_journal(state, JournalTransaction::refund(order_id, Some(item_id), amount, tax, reason)).await;
+        }
+        Some(status)
+    } else {
+        None
+    };
+
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "ITEM_UNITS_REFUNDED",
+        Some(serde_json::json!({"total_nuc": order_json["total_nuc"]})),
+        Some(serde_json::json!({
+            "item_id": item_id,
+            "units": units,
+            "refund_nuc": amount.minor_units(),
+            "refund_status": refund_status,
+            "total_nuc": order_json["total_nuc"].as_i64().unwrap_or(0) - amount.minor_units(),
+        })),
+        changed_by,
+        Some(reason),
+    ).await;
+
+
+    Ok(refund_status)
+}
+
 /// GET /v1/orders/:id/fulfillment
 /// Get fulfillment details (barcodes, QR codes)
 pub async fn get_fulfillment(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
 ) -> Result<Json<FulfillmentResponse>, StatusCode> {
-    let order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
     // Extraction: In the real repo, get_order returns fulfillment as a field
-    let barcodes = if let Some(fulfillment) = order_json["fulfillment"].as_array() {
-        fulfillment.iter().map(|f| BarcodeResponse {
-            item_id: Uuid::parse_str(f["order_item_id"].as_str().unwrap_or_default()).unwrap_or_default(),
-            barcode: f["barcode"].as_str().unwrap_or_default().to_string(),
-            qr_code_url: Some(format!("{}/qr/{}", state.api_base_url, f["barcode"].as_str().unwrap_or_default())),
-        }).collect()
-    } else {
-        vec![]
-    };
+    // QR links carry a signed, expiring grant so they work without a session
+    let mut barcodes = Vec::new();
+    let mut scheduled = Vec::new();
+    for f in order_json["fulfillment"].as_array().into_iter().flatten() {
+        let item_id = Uuid::parse_str(f["order_item_id"].as_str().unwrap_or_default()).unwrap_or_default();
+        let traveler_index = f["traveler_index"].as_i64().map(|index| index as i32);
+
+        // Not issued yet: the delivery worker generates it at `deliver_at`
+        let Some(barcode) = f["barcode"].as_str().map(|b| b.to_string()) else {
+            if let Some(deliver_at) = f["deliver_at"].as_str().and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok()) {
+                scheduled.push(ScheduledDeliveryResponse { item_id, traveler_index, deliver_at: deliver_at.with_timezone(&chrono::Utc) });
+            }
+            continue;
+        };
+        let grant = issue_fulfillment_grant(&state, order_id, &barcode)?;
+
+        barcodes.push(BarcodeResponse {
+            item_id,
+            traveler_id: f["traveler_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+            traveler_index,
+            segment: Some(f["segment"].clone()).filter(|segment| !segment.is_null()),
+            unit_index: f["unit_index"].as_i64().unwrap_or(0) as i32,
+            qr_code_url: Some(format!("{}/qr/{}?grant={}", state.api_base_url, barcode, grant)),
+            signed_payload: crate::scanning::signed_pass(&state, &order_json, f),
+            expired_at: f["expired_at"].as_str().map(str::to_string),
+            barcode,
+        });
+    }
     
     Ok(Json(FulfillmentResponse {
         order_id,
         barcodes,
+        scheduled,
+    }))
+}
+
+/// GET /qr/:barcode?grant=
+/// Resolve a QR link; the signed grant stands in for a session
+pub async fn get_fulfillment_qr(
+    State(state): State<AppState>,
+    Path(barcode): Path<String>,
+    Query(query): Query<QrGrantQuery>,
+) -> Result<Json<BarcodeResponse>, StatusCode> {
+    let grant = verify_fulfillment_grant(&state, &barcode, &query.grant).await?;
+
+    let order_json = state.order_repo.get_order(grant.order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let fulfillment = order_json["fulfillment"].as_array()
+        .and_then(|f| f.iter().find(|f| f["barcode"].as_str() == Some(barcode.as_str())))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(BarcodeResponse {
+        item_id: Uuid::parse_str(fulfillment["order_item_id"].as_str().unwrap_or_default()).unwrap_or_default(),
+        traveler_id: fulfillment["traveler_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+        traveler_index: fulfillment["traveler_index"].as_i64().map(|index| index as i32),
+        segment: Some(fulfillment["segment"].clone()).filter(|segment| !segment.is_null()),
+        unit_index: fulfillment["unit_index"].as_i64().unwrap_or(0) as i32,
+        signed_payload: crate::scanning::signed_pass(&state, &order_json, fulfillment),
+        expired_at: fulfillment["expired_at"].as_str().map(str::to_string),
+        barcode,
+        qr_code_url: None,
     }))
 }
 
@@ -324,12 +1083,11 @@ pub async fn get_fulfillment(
 /// Cancel an order
 pub async fn cancel_order(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
     // 1. Get order to verify exists and check status
-    let order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
     let order: OrderResponse = serde_json::from_value(order_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -342,18 +1100,13 @@ pub async fn cancel_order(
     state.order_repo.update_order_status(order_id, "CANCELLED").await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // 3. Release inventory
-    for item in &order.items {
-        if item.product_type == "Flight" {
-            if let Some(product_id) = item.product_id {
-                let pid_str = product_id.to_string();
-                let current = state.redis.get_flight_availability(&pid_str).await
-                    .unwrap_or(Some(0))
-                    .unwrap_or(0);
-                let _ = state.redis.set_flight_availability(&pid_str, current + 1).await;
-            }
-        }
+    // 3. Release inventory and any supplier bookings, and stop any installments
+    let sold = matches!(order.status.as_str(), "PAID" | "HOLD_PARTIALLY_PAID");
+    return_inventory(&state, &order.items, sold).await;
+    if order.status == "HOLD_PARTIALLY_PAID" {
+        crate::installments::cancel_plan(&state, order_id).await;
     }
+    crate::suppliers::cancel_supplier_items(&state, &order.items).await;
 
     // 4. Log Audit Change
     let _ = state.order_repo.add_order_change(
@@ -372,13 +1125,15 @@ pub async fn cancel_order(
 /// List customer's orders
 pub async fn list_orders(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
 ) -> Result<Json<Vec<OrderResponse>>, StatusCode> {
-    // For now, list all orders since we don't have full JWT user context yet
-    // In production, this would use customer_id from token
-    let orders_json = state.order_repo.list_orders("").await
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let orders_json = state.order_repo.list_orders(&customer_id).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
+    // DID aliases can collide, so re-check ownership on the full identity
     let responses: Vec<OrderResponse> = orders_json.into_iter()
+        .filter(|val| owns_order(&claims, val))
         .filter_map(|val| serde_json::from_value(val).ok())
         .collect();
     
@@ -386,19 +1141,39 @@ pub async fn list_orders(
 }
 
 /// POST /v1/fulfillment/:barcode/consume
-/// Consume a barcode (Service Delivery)
+/// Consume a barcode (Service Delivery); scanner credentials only
 pub async fn consume_fulfillment(
     State(state): State<AppState>,
+    Extension(scanner): Extension<AdminClaims>,
+    Extension(tenant): Extension<TenantContext>,
     Path(barcode): Path<String>,
     Json(req): Json<ConsumeFulfillmentRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    // 1. Consume fulfillment and get IDs
-    let (order_id, item_id) = state.order_repo.consume_fulfillment(&barcode, &req.location).await
+    // 1. Check the scan, then consume fulfillment and get IDs
+    crate::scanning::admit_scan(&state, &scanner.sub).await?;
+    let fulfillment = state.order_repo.find_fulfillment(&barcode).await
+        .map_err(|e| {
+            tracing::error!("Failed to look up fulfillment for barcode {}: {:?}", barcode, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    crate::scanning::check_scan(&tenant, &fulfillment, &req.location)?;
+
+    let (order_id, item_id) = state.order_repo.consume_fulfillment(&barcode, &req.location, &scanner.sub, chrono::Utc::now()).await
         .map_err(|e| {
             tracing::error!("Failed to consume fulfillment for barcode {}: {:?}", barcode, e);
             StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        })?
+        // Another scan of the same barcode got there first
+        .ok_or(StatusCode::CONFLICT)?;
 
+    recognize_consumption(&state, order_id, item_id).await?;
+    Ok(StatusCode::OK)
+}
+
+/// Earns a consumed item's revenue, with its journal, interline postings and
+/// settlement event. Shared by live scans and offline ones reconciled later.
+pub(crate) async fn recognize_consumption(state: &AppState, order_id: Uuid, item_id: Uuid) -> Result<(), StatusCode> {
     // 2. Fetch order to get price and current status
     let order_json = state.order_repo.get_order(order_id).await
         .map_err(|e| {
@@ -416,104 +1191,158 @@ pub async fn consume_fulfillment(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    // 3. Recognize Revenue
+    // 3. Recognize Revenue, one unit per barcode used. The item is EARNED
+    // once every unit still held has been; scans past that earn nothing
+    let consumed = crate::finance::consumed_units(&order_json, item_id);
+    let held = order.items.iter().find(|i| i.id == item_id).map_or(0, |i| i.active_units());
     let financial_mgr = altis_order::finance::FinancialManager::new();
-    if let Some(entry) = financial_mgr.recognize_revenue(&order, item_id) {
-        // Save Ledger Entry
-        let _ = state.order_repo.add_order_ledger_entry(
-            entry.order_id,
-            entry.order_item_id,
-            &entry.transaction_type,
-            entry.amount_nuc,
-            entry.description.as_deref(),
-        ).await;
-
-        // Update Revenue Status to EARNED
-        let _ = state.order_repo.update_item_revenue_status(item_id, "EARNED").await;
-
-        // 4. Log Settlement (Consumption)
-        let _ = state.telemetry.log_settlement(altis_shared::models::events::SettlementEvent {
-            order_id,
-            amount_nuc: entry.amount_nuc,
-            currency: order.currency.clone(),
-            event_type: "REVENUE_RECOGNITION".to_string(),
-            timestamp: chrono::Utc::now().timestamp(),
-        }).await;
+    if let Some(entry) = financial_mgr.recognize_revenue(&order, item_id, consumed - 1) {
+        if consumed >= held {
+            let _ = state.order_repo.update_item_revenue_status(item_id, "EARNED").await;
+        }
+        crate::finance::post_recognition(state, &order, &entry).await;
     }
-    
-    Ok(StatusCode::OK)
+
+    Ok(())
 }
 
 /// POST /v1/orders/:id/reshop
 /// Initial skeleton for post-booking modifications
 pub async fn reshop_order(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
     Json(req): Json<ReshopOrderRequest>,
 ) -> Result<Json<ReshopOrderResponse>, StatusCode> {
     // 1. Fetch current order
-    let order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
     let order: OrderResponse = serde_json::from_value(order_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // 2. Fetch products to add
-    let mut items_to_add = Vec::new();
-    let mut additional_nuc = 0;
+    // 2. Price the products to add against what the order holds
+    let (items_to_add, changes) = reshop_changes(&state, &order, &req).await?;
+    let additional_nuc = items_to_add.iter().map(|item| item.price_nuc).sum();
+    let delta = changes.total_delta_nuc.to_i32().map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    // 3. Return proposal
+    Ok(Json(ReshopOrderResponse {
+        order_id,
+        new_total_nuc: order.total_nuc + delta,
+        additional_nuc,
+        items_to_add,
+        changes,
+    }))
+}
+
+/// The order's lines the customer holds now, as the diff engine reads them
+pub(crate) fn held_lines(items: &[OrderItemResponse]) -> Result<Vec<DiffLine>, StatusCode> {
+    items.iter()
+        .filter(|item| matches!(item.status.as_str(), "ACTIVE" | "PROTECTED"))
+        .map(|item| Ok(DiffLine {
+            item_id: item.id,
+            product_id: item.product_id,
+            product_type: item.product_type.clone(),
+            name: item.name.clone(),
+            amount: item.held_amount().map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?,
+            replaces: None,
+        }))
+        .collect()
+}
 
-    for product_id in req.add_products {
-        let product = state.catalog_repo.get_product(product_id).await
+/// The items a reshop would add and how the order would change. Items to
+/// remove must be ones the order holds (422 otherwise).
+async fn reshop_changes(
+    state: &AppState,
+    order: &OrderResponse,
+    req: &ReshopOrderRequest,
+) -> Result<(Vec<OrderItemResponse>, OrderDiff), StatusCode> {
+    let mut items_to_add = Vec::new();
+    for product_id in &req.add_products {
+        let product = state.catalog_repo.get_product(*product_id).await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
             .ok_or(StatusCode::BAD_REQUEST)?;
 
-        let price = product["base_price_nuc"].as_i64().unwrap_or(0) as i32;
-        additional_nuc += price;
-
         items_to_add.push(OrderItemResponse {
             id: Uuid::new_v4(),
-            product_id: Some(product_id),
+            product_id: Some(*product_id),
             product_type: product["product_type"].as_str().unwrap_or("EXTRA").to_string(),
             name: product["name"].as_str().unwrap_or("Extra Product").to_string(),
-            price_nuc: price,
+            price_nuc: product["base_price_nuc"].as_i64().unwrap_or(0) as i32,
+            quantity: Some(1),
+            refunded_quantity: 0,
             status: "CONFIRMED".to_string(),
             revenue_status: "UNEARNED".to_string(),
             operating_carrier_id: None,
             net_rate_nuc: None,
             commission_nuc: None,
             metadata: product["metadata"].clone(),
+            tax_nuc: 0,
+            taxes: Vec::new(),
+            traveler_index: None,
+            traveler_id: None,
         });
     }
 
-    // 3. Return proposal
-    Ok(Json(ReshopOrderResponse {
-        order_id,
-        new_total_nuc: order.total_nuc + additional_nuc,
-        additional_nuc,
-        items_to_add,
-    }))
+    let before = held_lines(&order.items)?;
+    if req.remove_items.iter().any(|id| !before.iter().any(|line| line.item_id == *id)) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let mut after: Vec<DiffLine> = before.iter().filter(|line| !req.remove_items.contains(&line.item_id)).cloned().collect();
+    after.extend(items_to_add.iter().map(|item| DiffLine {
+        item_id: item.id,
+        product_id: item.product_id,
+        product_type: item.product_type.clone(),
+        name: item.name.clone(),
+        amount: Money::nuc(item.price_nuc as i64),
+        replaces: None,
+    }));
+    let changes = diff::compare(&before, &after).map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    Ok((items_to_add, changes))
+}
+
+/// POST /v1/orders/:id/changes/preview
+/// What a reshop or re-accommodation would change on the order, without changing it
+pub async fn preview_order_changes(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+    Json(req): Json<ChangePreviewRequest>,
+) -> Result<Json<ChangePreviewResponse>, StatusCode> {
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+
+    let diff = match req {
+        ChangePreviewRequest::Reshop(reshop) => {
+            let order: OrderResponse = serde_json::from_value(order_json)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            reshop_changes(&state, &order, &reshop).await?.1
+        }
+        ChangePreviewRequest::Reaccommodation(accept) => {
+            crate::reaccommodation::reaccommodation_changes(&order_json, &accept.selected_item_ids)?
+        }
+    };
+    Ok(Json(ChangePreviewResponse { order_id, diff }))
 }
 
 /// POST /v1/orders/:id/accept-reaccommodation
 /// Accept proposed re-accommodation items
 pub async fn accept_reaccommodation(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
     Json(req): Json<AcceptReaccommodationRequest>,
 ) -> Result<Json<OrderResponse>, StatusCode> {
     // 1. Fetch current order
-    let _order_json = state.order_repo.get_order(order_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let order_json = authorize_order(&state, &claims, order_id).await?;
 
-    // 2. Process acceptance (Mock logic)
-    // In a real repo, we'd update specific item statuses
+    // 2. Confirm the chosen flights; the other proposals are declined
+    let changes = crate::reaccommodation::reaccommodation_changes(&order_json, &req.selected_item_ids)?;
+    crate::reaccommodation::accept_proposals(&state, &order_json, &req.selected_item_ids).await?;
     let _ = state.order_repo.add_order_change(
         order_id,
         "REACCOMMODATION_ACCEPTED",
         None,
-        Some(serde_json::json!({"accepted_items": req.selected_item_ids})),
+        Some(serde_json::json!({"accepted_items": req.selected_item_ids, "changes": changes})),
         "CUSTOMER",
         Some("Customer accepted alternative flight")
     ).await;
@@ -533,8 +1362,11 @@ pub async fn accept_reaccommodation(
 /// Process a full refund for a disrupted flight (zero fees)
 pub async fn involuntary_refund(
     State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
     Path(order_id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
+    let order = authorize_order(&state, &claims, order_id).await?;
+
     // 1. Update order status to CANCELLED
     state.order_repo.update_order_status(order_id, "CANCELLED").await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -549,5 +1381,20 @@ pub async fn involuntary_refund(
         Some("Full refund processed due to flight disruption")
     ).await;
 
+    let refund_nuc = order["total_nuc"].as_i64().unwrap_or(0);
+    crate::documents::issue_for_order(&state, order_id, "CREDIT_NOTE", refund_nuc).await;
+    crate::finance::post_journal(
+        &state,
+        JournalTransaction::refund(order_id, None, Money::nuc(refund_nuc), crate::finance::order_tax(&order), "Involuntary refund after flight disruption"),
+    ).await;
+
+    // EU261 compensation is owed on top of the refund; under US DOT rules the
+    // refund is the remedy
+    for item in crate::compensation::assess_order(&state, &order).await? {
+        if item.assessment.eligible && item.payout.is_none() && item.assessment.scheme == Some(altis_order::compensation::Scheme::Eu261) {
+            crate::compensation::pay(&state, order_id, &item, false).await?;
+        }
+    }
+
     Ok(StatusCode::OK)
 }
@@ -0,0 +1,374 @@
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use altis_store::app_config::{WebhookEndpoint, WebhooksConfig};
+
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+/// Header carrying `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`
+pub const SIGNATURE_HEADER: &str = "Altis-Signature";
+
+/// Events partners can subscribe to, in the order the test console lists them
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[
+    "offer.created",
+    "offer.accepted",
+    "order.created",
+    "order.paid",
+    "order.cancelled",
+    "order.refunded",
+    "order.fulfilled",
+];
+
+const MAX_LISTED_DELIVERIES: i64 = 100;
+
+/// Sends signed webhook payloads to partner endpoints
+pub struct WebhookSender {
+    client: reqwest::Client,
+    config: WebhooksConfig,
+}
+
+impl WebhookSender {
+    pub fn new(config: WebhooksConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            // A redirect would re-send the payload somewhere the partner never registered
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("webhook HTTP client builds with static settings");
+        Self { client, config }
+    }
+
+    pub fn endpoint(&self, partner: &str) -> Option<&WebhookEndpoint> {
+        self.config.endpoints.get(partner)
+    }
+}
+
+/// Value of the signature header for `body` sent at `timestamp`
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let digest: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("t={},v1={}", timestamp, digest)
+}
+
+/// Example `data` for an event type, shaped like the real payload
+pub fn sample_data(event_type: &str) -> Option<serde_json::Value> {
+    let offer_id = "00000000-0000-4000-8000-000000000001";
+    let order_id = "00000000-0000-4000-8000-000000000002";
+    let order = |status: &str| serde_json::json!({
+        "order_id": order_id,
+        "offer_id": offer_id,
+        "status": status,
+        "total_nuc": 24500,
+        "currency": "NUC",
+    });
+
+    Some(match event_type {
+        "offer.created" => serde_json::json!({
+            "offer_id": offer_id,
+            "origin": "SIN",
+            "destination": "BKK",
+            "departure_date": "2026-03-14",
+            "total_nuc": 24500,
+            "currency": "NUC",
+            "expires_at": "2026-03-01T10:15:00Z",
+        }),
+        "offer.accepted" => serde_json::json!({ "offer_id": offer_id, "order_id": order_id }),
+        "order.created" => order("PROPOSED"),
+        "order.paid" => order("PAID"),
+        "order.cancelled" => serde_json::json!({ "order_id": order_id, "status": "CANCELLED", "reason": "Customer request" }),
+        "order.refunded" => serde_json::json!({ "order_id": order_id, "refunded_nuc": 24500, "currency": "NUC" }),
+        "order.fulfilled" => serde_json::json!({
+            "order_id": order_id,
+            "status": "FULFILLED",
+            "items": [{ "product_type": "FLIGHT", "barcode": "ALT-SAMPLE-0001" }],
+        }),
+        _ => return None,
+    })
+}
+
+fn envelope(event_id: Uuid, event_type: &str, data: serde_json::Value, test: bool) -> serde_json::Value {
+    serde_json::json!({
+        "id": event_id,
+        "type": event_type,
+        "created_at": chrono::Utc::now(),
+        "test": test,
+        "data": data,
+    })
+}
+
+/// Cuts `body` to at most `max` bytes without splitting a character
+fn truncate_utf8(body: &str, max: usize) -> &str {
+    if body.len() <= max {
+        return body;
+    }
+    let mut end = max;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    &body[..end]
+}
+
+/// Partner name behind an API-key caller; other callers have no webhooks
+fn partner_of(claims: &CustomerClaims) -> Result<&str, StatusCode> {
+    if claims.role != "PARTNER" {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    claims.sub.strip_prefix("partner:").ok_or(StatusCode::FORBIDDEN)
+}
+
+/// One outbound attempt, as shown in the delivery inspector
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub is_test: bool,
+    pub redelivery_of: Option<Uuid>,
+    pub url: String,
+    pub request_headers: serde_json::Value,
+    pub request_body: String,
+    /// None when the endpoint could not be reached
+    pub response_status: Option<i32>,
+    pub response_headers: Option<serde_json::Value>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An event body ready to send; the same body is re-sent on redelivery
+struct OutboundEvent {
+    event_id: Uuid,
+    event_type: String,
+    body: String,
+    is_test: bool,
+    redelivery_of: Option<Uuid>,
+}
+
+/// Signs and posts the event to the partner's endpoint, recording the
+/// attempt whatever the outcome.
+async fn deliver(
+    state: &AppState,
+    partner: &str,
+    endpoint: &WebhookEndpoint,
+    event: OutboundEvent,
+) -> Result<WebhookDeliveryResponse, StatusCode> {
+    let OutboundEvent { event_id, event_type, body, is_test, redelivery_of } = event;
+    let signature = sign(&endpoint.secret, chrono::Utc::now().timestamp(), &body);
+    let request_headers = [
+        ("Content-Type", "application/json".to_string()),
+        ("Altis-Event-Id", event_id.to_string()),
+        ("Altis-Event-Type", event_type.clone()),
+        (SIGNATURE_HEADER, signature),
+    ];
+
+    let mut request = state.webhooks.client.post(&endpoint.url).body(body.clone());
+    for (name, value) in &request_headers {
+        request = request.header(*name, value);
+    }
+
+    let started = Instant::now();
+    let (response_status, response_headers, response_body, error) = match request.send().await {
+        Ok(response) => {
+            let status = response.status().as_u16() as i32;
+            let headers: serde_json::Value = response.headers().iter()
+                .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into()))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+            match response.text().await {
+                Ok(text) => {
+                    let captured = truncate_utf8(&text, state.webhooks.config.max_captured_body_bytes).to_string();
+                    (Some(status), Some(headers), Some(captured), None)
+                }
+                Err(e) => (Some(status), Some(headers), None, Some(format!("reading response: {}", e))),
+            }
+        }
+        Err(e) if e.is_timeout() => (None, None, None, Some("timed out".to_string())),
+        Err(e) => (None, None, None, Some(e.to_string())),
+    };
+    let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let delivery = serde_json::json!({
+        "partner": partner,
+        "event_id": event_id,
+        "event_type": event_type,
+        "is_test": is_test,
+        "redelivery_of": redelivery_of,
+        "url": endpoint.url,
+        "request_headers": request_headers.iter()
+            .map(|(name, value)| (name.to_string(), serde_json::Value::from(value.as_str())))
+            .collect::<serde_json::Map<_, _>>(),
+        "request_body": body,
+        "response_status": response_status,
+        "response_headers": response_headers,
+        "response_body": response_body,
+        "error": error,
+        "duration_ms": duration_ms,
+    });
+    let recorded = state.webhook_delivery_repo.record_delivery(&delivery).await.map_err(|e| {
+        tracing::error!("Failed to record webhook delivery for {}: {:?}", partner, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    serde_json::from_value(recorded).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookEventSample {
+    pub event_type: String,
+    pub payload: serde_json::Value,
+}
+
+/// GET /v1/partners/me/webhooks/test
+/// Event types that can be test-sent, with an example payload for each
+pub async fn list_test_events(
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+) -> Result<Json<Vec<WebhookEventSample>>, StatusCode> {
+    partner_of(&claims)?;
+
+    Ok(Json(WEBHOOK_EVENT_TYPES.iter()
+        .filter_map(|event_type| sample_data(event_type).map(|data| WebhookEventSample {
+            event_type: event_type.to_string(),
+            payload: envelope(Uuid::nil(), event_type, data, true),
+        }))
+        .collect()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendTestWebhookRequest {
+    pub event_type: String,
+}
+
+/// POST /v1/partners/me/webhooks/test
+/// Send a signed sample event to the partner's endpoint and return the attempt
+pub async fn send_test_webhook(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Json(req): Json<SendTestWebhookRequest>,
+) -> Result<Json<WebhookDeliveryResponse>, StatusCode> {
+    let partner = partner_of(&claims)?;
+    let endpoint = state.webhooks.endpoint(partner).ok_or(StatusCode::NOT_FOUND)?;
+    let data = sample_data(&req.event_type).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    let event_id = Uuid::new_v4();
+    let body = envelope(event_id, &req.event_type, data, true).to_string();
+    let delivery = deliver(&state, partner, endpoint, OutboundEvent {
+        event_id,
+        event_type: req.event_type,
+        body,
+        is_test: true,
+        redelivery_of: None,
+    }).await?;
+
+    Ok(Json(delivery))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /v1/partners/me/webhooks/deliveries
+/// Recent delivery attempts with captured requests and responses
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Query(query): Query<ListDeliveriesQuery>,
+) -> Result<Json<Vec<WebhookDeliveryResponse>>, StatusCode> {
+    let partner = partner_of(&claims)?;
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_LISTED_DELIVERIES);
+
+    let deliveries = state.webhook_delivery_repo.list_deliveries(partner, limit).await.map_err(|e| {
+        tracing::error!("Failed to list webhook deliveries: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(deliveries.into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?))
+}
+
+async fn own_delivery(state: &AppState, partner: &str, id: Uuid) -> Result<WebhookDeliveryResponse, StatusCode> {
+    let delivery = state.webhook_delivery_repo.get_delivery(id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|d| d["partner"].as_str() == Some(partner))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    serde_json::from_value(delivery).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /v1/partners/me/webhooks/deliveries/{id}
+pub async fn get_delivery(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebhookDeliveryResponse>, StatusCode> {
+    let partner = partner_of(&claims)?;
+    Ok(Json(own_delivery(&state, partner, id).await?))
+}
+
+/// POST /v1/partners/me/webhooks/deliveries/{id}/redeliver
+/// Re-send the same event (same id and body, fresh signature) to the current endpoint
+pub async fn redeliver(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebhookDeliveryResponse>, StatusCode> {
+    let partner = partner_of(&claims)?;
+    let original = own_delivery(&state, partner, id).await?;
+    let endpoint = state.webhooks.endpoint(partner).ok_or(StatusCode::NOT_FOUND)?;
+
+    let delivery = deliver(&state, partner, endpoint, OutboundEvent {
+        event_id: original.event_id,
+        event_type: original.event_type,
+        body: original.request_body,
+        is_test: original.is_test,
+        redelivery_of: Some(original.id),
+    }).await?;
+
+    Ok(Json(delivery))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_event_type_has_a_sample() {
+        for event_type in WEBHOOK_EVENT_TYPES {
+            assert!(sample_data(event_type).is_some(), "{} has no sample", event_type);
+        }
+        assert!(sample_data("order.unknown").is_none());
+    }
+
+    #[test]
+    fn test_sign_covers_timestamp_and_body() {
+        let signature = sign("whsec", 1_700_000_000, r#"{"id":1}"#);
+        assert!(signature.starts_with("t=1700000000,v1="));
+        assert_eq!(signature.len(), "t=1700000000,v1=".len() + 64);
+        assert_ne!(signature, sign("whsec", 1_700_000_001, r#"{"id":1}"#));
+        assert_ne!(signature, sign("whsec", 1_700_000_000, r#"{"id":2}"#));
+        assert_ne!(signature, sign("other", 1_700_000_000, r#"{"id":1}"#));
+    }
+
+    #[test]
+    fn test_truncate_utf8_keeps_whole_characters() {
+        assert_eq!(truncate_utf8("short", 10), "short");
+        assert_eq!(truncate_utf8("héllo", 2), "h");
+        assert_eq!(truncate_utf8("héllo", 3), "hé");
+    }
+}
@@ -0,0 +1,189 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use altis_core::payment::PaymentStatus;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::authz::authorize_order;
+use crate::middleware::auth::CustomerClaims;
+use crate::orders::{complete_payment, return_inventory, OrderResponse};
+use crate::state::AppState;
+
+/// How long a claimed timeout is left to one instance before another retries it
+const TIMEOUT_LEASE_SECONDS: i64 = 300;
+
+/// Parks a PAYMENT_PENDING order while the customer authenticates with the
+/// card issuer and returns it with the provider's `next_action`, which the
+/// client follows before calling `/pay/confirm`.
+pub(crate) async fn await_payment_action(state: &AppState, mut order: OrderResponse, intent_id: &str) -> Result<OrderResponse, StatusCode> {
+    let intent = state.payments(order.test).process_status_update(intent_id).await.map_err(|e| {
+        tracing::error!("Failed to fetch next action for payment {}: {:?}", intent_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let expires_at = Utc::now() + chrono::Duration::seconds(state.payment.action_timeout_seconds as i64);
+    state.order_repo.await_payment_action(order.id, intent_id, expires_at).await.map_err(|e| {
+        tracing::error!("Failed to record payment action for order {}: {:?}", order.id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let _ = state.order_repo.add_order_change(
+        order.id,
+        "PAYMENT_ACTION_REQUIRED",
+        Some(serde_json::json!({"status": order.status})),
+        Some(serde_json::json!({"status": "PAYMENT_PENDING", "intent_id": intent_id, "expires_at": expires_at})),
+        "SYSTEM",
+        Some("Card issuer asked the customer to authenticate"),
+    ).await;
+
+    order.status = "PAYMENT_PENDING".to_string();
+    order.payment_action_expires_at = Some(expires_at);
+    order.payment_action = intent.next_action;
+    Ok(order)
+}
+
+/// POST /v1/orders/:id/pay/confirm
+/// Finish paying once the customer is back from a 3-D Secure challenge
+pub async fn confirm_payment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let intent_id = order_json["payment_intent_id"].as_str().map(str::to_string);
+    let mut order: OrderResponse = serde_json::from_value(order_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // The provider's webhook may have got there first
+    if order.status == "PAID" {
+        return Ok(Json(order));
+    }
+    let Some(intent_id) = intent_id.filter(|_| order.status == "PAYMENT_PENDING") else {
+        return Err(StatusCode::CONFLICT);
+    };
+
+    let intent = state.payments(order.test).confirm_payment(&intent_id).await.map_err(|e| {
+        tracing::error!("Failed to confirm payment {} for order {}: {:?}", intent_id, order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    match intent.status {
+        PaymentStatus::Succeeded => {
+            if !complete_payment(&state, &order, &["PAYMENT_PENDING"], intent.reference.as_deref(), "Order paid after customer authentication").await? {
+                let current = state.order_repo.get_order(order_id).await.ok().flatten();
+                if current.as_ref().and_then(|o| o["status"].as_str()) != Some("PAID") {
+                    return Err(StatusCode::CONFLICT);
+                }
+            }
+            order.status = "PAID".to_string();
+            order.payment_action_expires_at = None;
+        }
+        // Not done yet; the client carries on with the same challenge
+        PaymentStatus::RequiresAction => order.payment_action = intent.next_action,
+        PaymentStatus::Processing => {}
+        status => {
+            release_payment_action(&state, &order, &format!("{:?}", status)).await;
+            return Err(StatusCode::PAYMENT_REQUIRED);
+        }
+    }
+    Ok(Json(order))
+}
+
+/// Where an order whose payment was never authenticated goes: back to
+/// PROPOSED for another attempt while its hold lasts, EXPIRED after
+fn released_status(hold_expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> &'static str {
+    if hold_expires_at.is_none_or(|expires_at| now < expires_at) { "PROPOSED" } else { "EXPIRED" }
+}
+
+async fn release_payment_action(state: &AppState, order: &OrderResponse, outcome: &str) {
+    let status = released_status(order.expires_at, Utc::now());
+    match state.order_repo.transition_order_status(order.id, &["PAYMENT_PENDING"], status).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::error!("Failed to release order {} from its payment action: {:?}", order.id, e);
+            return;
+        }
+    }
+    if status == "EXPIRED" {
+        return_inventory(state, &order.items, false).await;
+    }
+    let _ = state.order_repo.add_order_change(
+        order.id,
+        "PAYMENT_FAILED",
+        Some(serde_json::json!({"status": "PAYMENT_PENDING"})),
+        Some(serde_json::json!({"status": status, "outcome": outcome})),
+        "SYSTEM",
+        Some("Customer authentication not completed"),
+    ).await;
+    tracing::info!("Order {} released to {} after payment authentication ended {}", order.id, status, outcome);
+}
+
+/// Releases orders whose customers never finished authenticating. The
+/// provider is asked first, so a challenge completed just before the
+/// deadline whose webhook went missing still pays the order.
+pub async fn run_payment_action_timeout_worker(state: AppState) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        let claimed = match state.order_repo.claim_expired_payment_actions(100, TIMEOUT_LEASE_SECONDS).await {
+            Ok(claimed) => claimed,
+            Err(e) => {
+                tracing::error!("Failed to claim timed-out payment actions: {:?}", e);
+                continue;
+            }
+        };
+        for claim in &claimed {
+            let Some(order_id) = claim["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+            time_out_payment_action(&state, order_id, claim["payment_intent_id"].as_str()).await;
+        }
+    }
+}
+
+async fn time_out_payment_action(state: &AppState, order_id: Uuid, intent_id: Option<&str>) {
+    let Ok(Some(order_json)) = state.order_repo.get_order(order_id).await else { return };
+    let Ok(order) = serde_json::from_value::<OrderResponse>(order_json) else { return };
+
+    let intent = match intent_id {
+        Some(intent_id) => match state.payments(order.test).process_status_update(intent_id).await {
+            Ok(intent) => Some(intent),
+            // Left to the lease; the provider is asked again next time round
+            Err(e) => {
+                tracing::warn!("Payment {} of order {} not checked before timing out: {}", intent_id, order_id, e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    match intent {
+        Some(intent) if intent.status == PaymentStatus::Succeeded => {
+            let reason = "Order paid; provider confirmed the payment after its deadline";
+            if let Err(status) = complete_payment(state, &order, &["PAYMENT_PENDING"], intent.reference.as_deref(), reason).await {
+                tracing::error!("Order {} was paid but could not be completed: {}", order_id, status);
+            }
+        }
+        Some(intent) if intent.status == PaymentStatus::Processing => {}
+        intent => {
+            let outcome = intent.map_or("TIMED_OUT".to_string(), |intent| format!("{:?}", intent.status));
+            release_payment_action(state, &order, &outcome).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_released_orders_keep_their_hold_until_it_runs_out() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(released_status(Some(now + chrono::Duration::minutes(5)), now), "PROPOSED");
+        assert_eq!(released_status(None, now), "PROPOSED");
+        assert_eq!(released_status(Some(now), now), "EXPIRED");
+        assert_eq!(released_status(Some(now - chrono::Duration::minutes(1)), now), "EXPIRED");
+    }
+}
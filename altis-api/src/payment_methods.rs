@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authz::registered_customer;
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SavePaymentMethodRequest {
+    /// Single-use token from the payment provider's client SDK
+    pub token: String,
+}
+
+/// A saved card as the customer sees it; the provider token stays server-side
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentMethodResponse {
+    pub id: Uuid,
+    pub brand: Option<String>,
+    pub last4: Option<String>,
+    pub exp_month: Option<i16>,
+    pub exp_year: Option<i16>,
+    pub is_default: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn to_response(method: serde_json::Value) -> Result<PaymentMethodResponse, StatusCode> {
+    serde_json::from_value(method).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// A raw card number sent where a provider token belongs. It is refused
+/// before it can reach a log line or the database.
+fn looks_like_card_number(token: &str) -> bool {
+    let digits: String = token.chars().filter(|c| !matches!(c, ' ' | '-')).collect();
+    (12..=19).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// POST /v1/customers/me/payment-methods
+/// Save a card for later checkouts from a single-use provider token
+pub async fn save_payment_method(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Json(req): Json<SavePaymentMethodRequest>,
+) -> Result<(StatusCode, Json<PaymentMethodResponse>), StatusCode> {
+    let owner = registered_customer(&claims)?;
+    if req.token.trim().is_empty() || looks_like_card_number(&req.token) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let payments = state.payments(claims.test);
+    let vaulted = payments.vault_payment_method(owner, req.token.trim()).await.map_err(|e| {
+        tracing::warn!("Payment provider refused to vault a payment method: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+    let method = serde_json::json!({
+        "customer_id": owner,
+        "provider": payments.provider(),
+        "provider_token": vaulted.provider_token,
+        "brand": vaulted.brand,
+        "last4": vaulted.last4,
+        "exp_month": vaulted.exp_month,
+        "exp_year": vaulted.exp_year,
+        "fingerprint": vaulted.fingerprint,
+        "test": claims.test,
+    });
+    let saved = state.payment_method_repo.create_payment_method(&method).await.map_err(|e| {
+        tracing::error!("Failed to save payment method: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // The same card saved again keeps its original entry; drop the new token
+    if saved["provider_token"].as_str() != Some(vaulted.provider_token.as_str()) {
+        if let Err(e) = payments.remove_payment_method(&vaulted.provider_token).await {
+            tracing::warn!("Failed to detach duplicate payment method token: {}", e);
+        }
+    }
+
+    Ok((StatusCode::CREATED, Json(to_response(saved)?)))
+}
+
+/// GET /v1/customers/me/payment-methods
+/// The customer's saved cards, default first
+pub async fn list_payment_methods(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+) -> Result<Json<Vec<PaymentMethodResponse>>, StatusCode> {
+    let owner = registered_customer(&claims)?;
+    let provider = state.payments(claims.test).provider();
+    let methods = state.payment_method_repo.list_payment_methods(owner, provider, claims.test).await.map_err(|e| {
+        tracing::error!("Failed to list payment methods: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(methods.into_iter().map(to_response).collect::<Result<_, _>>()?))
+}
+
+/// DELETE /v1/customers/me/payment-methods/:id
+/// Forget a saved card
+pub async fn remove_payment_method(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let owner = registered_customer(&claims)?;
+    let removed = state.payment_method_repo.remove_payment_method(id, owner).await
+        .map_err(|e| {
+            tracing::error!("Failed to remove payment method {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // It is never charged again either way; detaching also clears it at the provider
+    if let Some(token) = removed["provider_token"].as_str() {
+        let payments = state.payments(removed["test"].as_bool().unwrap_or(false));
+        if let Err(e) = payments.remove_payment_method(token).await {
+            tracing::warn!("Removed payment method {} but the provider did not detach it: {}", id, e);
+        }
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Provider token of one of the caller's saved cards, for paying an order
+/// placed with the same kind of key. Unknown ids are the request's fault.
+pub(crate) async fn stored_token(state: &AppState, claims: &CustomerClaims, id: Uuid, test: bool) -> Result<String, StatusCode> {
+    let owner = registered_customer(claims)?;
+    let method = state.payment_method_repo.get_payment_method(id, owner).await
+        .map_err(|e| {
+            tracing::error!("Failed to load payment method {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    let usable = method["test"].as_bool() == Some(test) && method["provider"].as_str() == Some(state.payments(test).provider());
+    match method["provider_token"].as_str() {
+        Some(token) if usable => Ok(token.to_string()),
+        _ => Err(StatusCode::UNPROCESSABLE_ENTITY),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_card_numbers_are_told_apart_from_tokens() {
+        assert!(looks_like_card_number("4242424242424242"));
+        assert!(looks_like_card_number("4242 4242 4242 4242"));
+        assert!(looks_like_card_number("3782-822463-10005"));
+        assert!(!looks_like_card_number("tok_visa_4242"));
+        assert!(!looks_like_card_number("pm_1NqK2a2eZvKYlo2C"));
+        assert!(!looks_like_card_number("4242"));
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use altis_core::repository::{ExperimentRepository, ProductRepository};
+use altis_offer::ai_ranker::OfferRanker;
+use altis_offer::experiments::Experiment;
+use altis_store::app_config::{Config, ConfigProblem};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Adjustment types the pricing engine knows how to apply
+const ADJUSTMENT_TYPES: &[&str] = &["MULTIPLIER", "FIXED", "FORMULA"];
+
+/// Runs the static config checks plus the ones that need the database:
+/// pricing rules pointing at products that are no longer sold or carrying
+/// adjustments the engine can't apply, and experiments naming a ranking
+/// strategy this instance doesn't have.
+pub async fn run(
+    config: &Config,
+    catalog_repo: &dyn ProductRepository,
+    experiment_repo: &dyn ExperimentRepository,
+    ranker: &OfferRanker,
+) -> Vec<ConfigProblem> {
+    let mut problems = config.validate();
+    problems.extend(check_pricing_rules(catalog_repo).await);
+    problems.extend(check_experiments(experiment_repo, ranker).await);
+    problems
+}
+
+/// Logs each problem; true when there were none
+pub fn report(problems: &[ConfigProblem]) -> bool {
+    for problem in problems {
+        tracing::error!("Pre-flight check failed: {}", problem);
+    }
+    if !problems.is_empty() {
+        tracing::error!("{} configuration problem(s) found; fix them or run with --check to re-check", problems.len());
+    }
+    problems.is_empty()
+}
+
+async fn check_pricing_rules(catalog_repo: &dyn ProductRepository) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+    let airlines = match catalog_repo.list_active_airlines().await {
+        Ok(airlines) => airlines,
+        Err(e) => return vec![unreadable("airlines", e)],
+    };
+
+    for airline in &airlines {
+        let code = airline["code"].as_str().unwrap_or_default();
+        let Some(airline_id) = airline["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+
+        let (products, rules) = match (
+            catalog_repo.list_products(airline_id, None).await,
+            catalog_repo.list_pricing_rules(airline_id).await,
+        ) {
+            (Ok(products), Ok(rules)) => (products, rules),
+            (Err(e), _) | (_, Err(e)) => {
+                problems.push(unreadable(&format!("catalog for airline {}", code), e));
+                continue;
+            }
+        };
+        let active: HashMap<&str, bool> = products.iter()
+            .filter_map(|p| Some((p["id"].as_str()?, p["is_active"].as_bool().unwrap_or(true))))
+            .collect();
+
+        for rule in &rules {
+            let setting = format!("pricing_rules[{}] '{}' ({})", code, rule["rule_name"].as_str().unwrap_or_default(), rule["id"].as_str().unwrap_or_default());
+            problems.extend(check_pricing_rule(&setting, rule, &active));
+        }
+    }
+
+    problems
+}
+
+fn check_pricing_rule(setting: &str, rule: &Value, active_products: &HashMap<&str, bool>) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |message: String| problems.push(ConfigProblem { setting: setting.to_string(), message });
+
+    if let Some(product_id) = rule["product_id"].as_str() {
+        match active_products.get(product_id) {
+            Some(true) => {}
+            Some(false) => problem(format!("references product {}, which is inactive; deactivate the rule or reactivate the product", product_id)),
+            None => problem(format!("references product {}, which belongs to another airline", product_id)),
+        }
+    }
+
+    problems.extend(check_rule_terms(setting, rule));
+    problems
+}
+
+/// Conditions and adjustments the pricing engine can evaluate; shared with
+/// catalog imports, where rules name their product by code instead of id.
+pub(crate) fn check_rule_terms(setting: &str, rule: &Value) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |message: String| problems.push(ConfigProblem { setting: setting.to_string(), message });
+
+    if !rule["conditions"].is_object() {
+        problem("conditions must be a JSON object".to_string());
+    }
+
+    let adjustments = &rule["adjustments"];
+    match adjustments["type"].as_str() {
+        Some("FORMULA") if !adjustments["formula"].is_string() => problem("FORMULA adjustment has no \"formula\"".to_string()),
+        Some("MULTIPLIER") if adjustments["value"].as_f64().is_none_or(|v| v <= 0.0) => {
+            problem("MULTIPLIER adjustment needs a positive numeric \"value\"".to_string())
+        }
+        Some("FIXED") if !adjustments["value"].is_i64() => problem("FIXED adjustment needs an integer NUC \"value\"".to_string()),
+        Some(kind) if !ADJUSTMENT_TYPES.contains(&kind) => {
+            problem(format!("adjustment type '{}' is unknown (expected one of {})", kind, ADJUSTMENT_TYPES.join(", ")))
+        }
+        Some(_) => {}
+        None => problem("adjustments has no \"type\"".to_string()),
+    }
+
+    problems
+}
+
+async fn check_experiments(experiment_repo: &dyn ExperimentRepository, ranker: &OfferRanker) -> Vec<ConfigProblem> {
+    let experiments = match experiment_repo.list_experiments().await {
+        Ok(experiments) => experiments,
+        Err(e) => return vec![unreadable("ranking experiments", e)],
+    };
+    let now = chrono::Utc::now();
+
+    experiments.into_iter()
+        .filter_map(|value| serde_json::from_value::<Experiment>(value).ok())
+        .filter(|e| e.ends_at.is_none_or(|end| end > now) && !ranker.has_strategy(&e.strategy))
+        .map(|e| ConfigProblem {
+            setting: format!("ranking_experiments '{}'", e.name),
+            message: format!("uses strategy '{}', which isn't registered; its treatment would silently get control rankings", e.strategy),
+        })
+        .collect()
+}
+
+fn unreadable(what: &str, e: Box<dyn std::error::Error + Send + Sync>) -> ConfigProblem {
+    ConfigProblem { setting: "database".to_string(), message: format!("could not read {}: {}", what, e) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_rule_problems() {
+        let active = HashMap::from([("p-live", true), ("p-retired", false)]);
+        let rule = |product: Option<&str>, adjustments: Value| serde_json::json!({
+            "product_id": product,
+            "conditions": {"product_type": "BAG"},
+            "adjustments": adjustments,
+        });
+
+        assert!(check_pricing_rule("r", &rule(Some("p-live"), serde_json::json!({"type": "MULTIPLIER", "value": 1.2})), &active).is_empty());
+        assert!(check_pricing_rule("r", &rule(None, serde_json::json!({"type": "FIXED", "value": 500})), &active).is_empty());
+
+        let problems = check_pricing_rule("r", &rule(Some("p-retired"), serde_json::json!({"type": "DISCOUNT", "value": 0.9})), &active);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].message.contains("inactive"));
+        assert!(problems[1].message.contains("'DISCOUNT' is unknown"));
+    }
+}
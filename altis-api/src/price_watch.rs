@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_store::app_config::PriceWatchConfig;
+
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePriceWatchRequest {
+    pub origin: String,
+    pub destination: String,
+    /// First and last departure dates of interest (inclusive)
+    pub depart_from: NaiveDate,
+    pub depart_to: NaiveDate,
+    pub passengers: Option<u32>,
+    /// Alert once the cheapest offer in the window costs this much or less
+    pub target_price_nuc: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceWatchResponse {
+    pub id: Uuid,
+    pub origin: String,
+    pub destination: String,
+    pub depart_from: NaiveDate,
+    pub depart_to: NaiveDate,
+    pub passengers: i32,
+    pub target_price_nuc: i32,
+    /// ACTIVE, TRIGGERED, EXPIRED or CANCELLED
+    pub status: String,
+    /// Cheapest offer found at the last check
+    pub lowest_price_nuc: Option<i32>,
+    pub lowest_price_date: Option<NaiveDate>,
+    pub last_checked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub triggered_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub(crate) fn is_airport_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Checks a new watch against `config`, relative to `today`
+fn validate_request(req: &CreatePriceWatchRequest, config: &PriceWatchConfig, today: NaiveDate) -> Result<(), &'static str> {
+    if !is_airport_code(&req.origin) || !is_airport_code(&req.destination) {
+        return Err("origin and destination must be IATA airport codes");
+    }
+    if req.origin.eq_ignore_ascii_case(&req.destination) {
+        return Err("origin and destination must differ");
+    }
+    if req.depart_to < req.depart_from {
+        return Err("depart_to is before depart_from");
+    }
+    if req.depart_to < today {
+        return Err("the departure window has already passed");
+    }
+    if (req.depart_to - req.depart_from).num_days() >= config.max_window_days {
+        return Err("the departure window is too wide");
+    }
+    if req.target_price_nuc <= 0 || req.passengers == Some(0) {
+        return Err("target_price_nuc and passengers must be positive");
+    }
+    Ok(())
+}
+
+fn to_response(watch: serde_json::Value) -> Result<PriceWatchResponse, StatusCode> {
+    serde_json::from_value(watch).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /v1/price-watches
+/// Watch a route over a departure window and get a fare alert at the target price
+pub async fn create_price_watch(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Json(req): Json<CreatePriceWatchRequest>,
+) -> Result<(StatusCode, Json<PriceWatchResponse>), StatusCode> {
+    if let Err(reason) = validate_request(&req, &state.price_watch, chrono::Utc::now().date_naive()) {
+        tracing::debug!("Rejected price watch: {}", reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let watch = serde_json::json!({
+        "customer_id": customer_id,
+        "customer_email": claims.email,
+        "origin": req.origin.to_uppercase(),
+        "destination": req.destination.to_uppercase(),
+        "depart_from": req.depart_from,
+        "depart_to": req.depart_to,
+        "passengers": req.passengers.unwrap_or(1),
+        "target_price_nuc": req.target_price_nuc,
+    });
+    let created = state.price_watch_repo.create_watch(&watch).await.map_err(|e| {
+        tracing::error!("Failed to create price watch: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok((StatusCode::CREATED, Json(to_response(created)?)))
+}
+
+/// GET /v1/price-watches
+/// The customer's price watches, newest first
+pub async fn list_price_watches(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+) -> Result<Json<Vec<PriceWatchResponse>>, StatusCode> {
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let watches = state.price_watch_repo.list_watches(&customer_id).await.map_err(|e| {
+        tracing::error!("Failed to list price watches: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(watches.into_iter().map(to_response).collect::<Result<_, _>>()?))
+}
+
+/// GET /v1/price-watches/{id}
+pub async fn get_price_watch(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PriceWatchResponse>, StatusCode> {
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let watch = state.price_watch_repo.get_watch(id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        // Someone else's watch is reported as missing, not forbidden
+        .filter(|w| w["customer_id"].as_str() == Some(customer_id.as_str()))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(to_response(watch)?))
+}
+
+/// DELETE /v1/price-watches/{id}
+/// Stop watching; only ACTIVE watches can be cancelled
+pub async fn cancel_price_watch(
+    State(state): State<AppState>,
+    axum::Extension(claims): axum::Extension<CustomerClaims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PriceWatchResponse>, StatusCode> {
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    if let Some(cancelled) = state.price_watch_repo.cancel_watch(id, &customer_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        return Ok(Json(to_response(cancelled)?));
+    }
+
+    // Not cancellable: tell a missing watch apart from one that already ended
+    match state.price_watch_repo.get_watch(id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        Some(w) if w["customer_id"].as_str() == Some(customer_id.as_str()) => Err(StatusCode::CONFLICT),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Background loop re-pricing due watches through the offer pipeline and
+/// sending a fare alert when one reaches its target.
+pub async fn run_price_watch_worker(state: AppState, config: PriceWatchConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let watches = match state.price_watch_repo.claim_due_watches(config.batch_size, config.recheck_seconds).await {
+            Ok(watches) => watches,
+            Err(e) => {
+                tracing::error!("Failed to claim price watches: {:?}", e);
+                continue;
+            }
+        };
+        for watch in watches {
+            match serde_json::from_value::<PriceWatchResponse>(watch.clone()) {
+                Ok(parsed) => check_watch(&state, &parsed, &watch).await,
+                Err(e) => tracing::error!("Unreadable price watch {}: {}", watch["id"], e),
+            }
+        }
+    }
+}
+
+/// Prices every remaining day of the window and alerts on the cheapest
+async fn check_watch(state: &AppState, watch: &PriceWatchResponse, raw: &serde_json::Value) {
+    let today = chrono::Utc::now().date_naive();
+    let mut lowest: Option<(i32, NaiveDate)> = None;
+
+    for date in watch.depart_from.max(today).iter_days().take_while(|d| *d <= watch.depart_to) {
+        let context = altis_offer::features::SearchContext {
+            origin: watch.origin.clone(),
+            destination: watch.destination.clone(),
+            departure_date: date.to_string(),
+            passengers: watch.passengers,
+            children: 0,
+            infants: 0,
+            cabin_class: None,
+            user_segment: None,
+            customer: None,
+        };
+        let offers = match crate::offers::generate_offers(state, &context, crate::offers::CATALOG_AIRLINE).await {
+            Ok(offers) => offers,
+            Err(status) => {
+                tracing::warn!("Could not price watch {} for {}: {}", watch.id, date, status);
+                continue;
+            }
+        };
+        if let Some(cheapest) = offers.iter().filter_map(|o| o.total.to_i32().ok()).min() {
+            if lowest.is_none_or(|(price, _)| cheapest < price) {
+                lowest = Some((cheapest, date));
+            }
+        }
+    }
+
+    let Some((price, date)) = lowest else {
+        return;
+    };
+    if let Err(e) = state.price_watch_repo.record_price(watch.id, price, date).await {
+        tracing::warn!("Failed to record price for watch {}: {:?}", watch.id, e);
+    }
+    if price > watch.target_price_nuc {
+        return;
+    }
+
+    // Triggered before notifying, so a cancelled watch never alerts and a
+    // watch alerts at most once
+    match state.price_watch_repo.mark_triggered(watch.id).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::error!("Failed to trigger price watch {}: {:?}", watch.id, e);
+            return;
+        }
+    }
+
+    let event = serde_json::json!({
+        "event_type": "PRICE_WATCH_TRIGGERED",
+        "watch_id": watch.id,
+        "customer_id": raw["customer_id"],
+        "customer_email": raw["customer_email"],
+        "origin": watch.origin,
+        "destination": watch.destination,
+        "departure_date": date,
+        "passengers": watch.passengers,
+        "price_nuc": price,
+        "target_price_nuc": watch.target_price_nuc,
+        "timestamp": chrono::Utc::now().timestamp(),
+    });
+    if let Err(e) = crate::notifier::notify(state, &watch.id.to_string(), &event).await {
+        tracing::warn!("Price watch {} triggered but the fare alert was not sent: {}", watch.id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_request() {
+        let config = PriceWatchConfig::default();
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let request = |from, to| CreatePriceWatchRequest {
+            origin: "SIN".to_string(),
+            destination: "bkk".to_string(),
+            depart_from: date(from),
+            depart_to: date(to),
+            passengers: None,
+            target_price_nuc: 15000,
+        };
+
+        assert!(validate_request(&request(10, 16), &config, today).is_ok());
+        assert!(validate_request(&request(10, 9), &config, today).is_err());
+        assert!(validate_request(&request(1, 20), &config, today).is_err(), "wider than max_window_days");
+        assert!(validate_request(&request(10, 16), &config, date(20)).is_err(), "window in the past");
+
+        let mut same_route = request(10, 16);
+        same_route.destination = "sin".to_string();
+        assert!(validate_request(&same_route, &config, today).is_err());
+    }
+}
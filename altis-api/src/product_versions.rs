@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use altis_core::tenant::TenantContext;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authz::authorize_product;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleVersionRequest {
+    /// When the version takes over; must be in the future
+    pub effective_from: DateTime<Utc>,
+    /// Fields left out keep the product's current values
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub base_price_nuc: Option<i32>,
+    pub metadata: Option<serde_json::Value>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductVersionResponse {
+    pub product_id: Uuid,
+    pub version: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub base_price_nuc: i32,
+    pub metadata: Option<serde_json::Value>,
+    pub is_active: Option<bool>,
+    pub effective_from: DateTime<Utc>,
+    /// When the next version takes over; None while this one is the latest
+    pub effective_to: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Checks a scheduled change relative to `now`
+fn validate_schedule(req: &ScheduleVersionRequest, now: DateTime<Utc>) -> Result<(), &'static str> {
+    if req.effective_from <= now {
+        return Err("effective_from must be in the future; use PUT /products/:id for immediate changes");
+    }
+    if req.base_price_nuc.is_some_and(|price| price < 0) {
+        return Err("base_price_nuc must not be negative");
+    }
+    if req.name.is_none() && req.description.is_none() && req.base_price_nuc.is_none() && req.metadata.is_none() && req.is_active.is_none() {
+        return Err("the version changes nothing");
+    }
+    Ok(())
+}
+
+fn to_response(version: serde_json::Value) -> Result<ProductVersionResponse, StatusCode> {
+    serde_json::from_value(version).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /v1/admin/products/:id/versions
+/// Price and terms history of a product, oldest first, including versions scheduled ahead
+pub async fn list_product_versions(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(product_id): Path<Uuid>,
+) -> Result<Json<Vec<ProductVersionResponse>>, StatusCode> {
+    authorize_product(&state, &tenant, product_id).await?;
+    let versions = state.catalog_repo.list_product_versions(product_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if versions.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(versions.into_iter().map(to_response).collect::<Result<_, _>>()?))
+}
+
+/// POST /v1/admin/products/:id/versions
+/// Schedule a future price or terms change
+pub async fn schedule_product_version(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(product_id): Path<Uuid>,
+    Json(req): Json<ScheduleVersionRequest>,
+) -> Result<(StatusCode, Json<ProductVersionResponse>), StatusCode> {
+    if let Err(reason) = validate_schedule(&req, Utc::now()) {
+        tracing::debug!("Rejected product version for {}: {}", product_id, reason);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    authorize_product(&state, &tenant, product_id).await?;
+
+    let version = serde_json::json!({
+        "name": req.name,
+        "description": req.description,
+        "base_price_nuc": req.base_price_nuc,
+        "metadata": req.metadata,
+        "is_active": req.is_active,
+    });
+    let scheduled = state.catalog_repo.schedule_product_version(product_id, &version, req.effective_from).await
+        .map_err(|e| {
+            tracing::error!("Failed to schedule a version of product {}: {:?}", product_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok((StatusCode::CREATED, Json(to_response(scheduled)?)))
+}
+
+/// DELETE /v1/admin/products/:id/versions/:version
+/// Withdraw a scheduled version; versions already in force stay in the history
+pub async fn cancel_product_version(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path((product_id, version)): Path<(Uuid, i32)>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_product(&state, &tenant, product_id).await?;
+    let cancelled = state.catalog_repo.cancel_product_version(product_id, version).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if cancelled { Ok(StatusCode::NO_CONTENT) } else { Err(StatusCode::NOT_FOUND) }
+}
+
+/// Background loop switching products over to scheduled versions as they
+/// come into force
+pub async fn run_product_version_scheduler(state: AppState) {
+    let mut interval = tokio::time::interval(Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+        match state.catalog_repo.apply_due_product_versions().await {
+            Ok(changed) if !changed.is_empty() => {
+                tracing::info!("{} product(s) moved to a scheduled version", changed.len());
+                state.catalog_changed("scheduled product versions applied").await;
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to apply scheduled product versions: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_schedule() {
+        let now = Utc::now();
+        let request = |effective_from, base_price_nuc| ScheduleVersionRequest {
+            effective_from,
+            name: None,
+            description: None,
+            base_price_nuc,
+            metadata: None,
+            is_active: None,
+        };
+
+        assert!(validate_schedule(&request(now + chrono::Duration::days(7), Some(12000)), now).is_ok());
+        assert!(validate_schedule(&request(now, Some(12000)), now).is_err(), "not in the future");
+        assert!(validate_schedule(&request(now + chrono::Duration::days(7), Some(-1)), now).is_err());
+        assert!(validate_schedule(&request(now + chrono::Duration::days(7), None), now).is_err(), "changes nothing");
+    }
+}
@@ -0,0 +1,398 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_core::iata::{ContactInfo, Traveler};
+use altis_core::messaging::AlertChannel;
+use altis_shared::pii::{self, Masked, PiiField, Visibility};
+
+use crate::authz::registered_customer;
+use crate::middleware::auth::CustomerClaims;
+use crate::offers::AcceptOfferRequest;
+use crate::state::AppState;
+
+// ============================================================================
+// Models
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct ProfileResponse {
+    pub customer_id: String,
+    /// Used for bookings that don't give their own
+    pub contact_info: Option<ContactInfo>,
+    pub marketing_opt_in: bool,
+    /// When the customer last agreed to marketing
+    pub marketing_opt_in_at: Option<DateTime<Utc>>,
+    /// Where trip alerts go; none means the airline's default channels
+    pub alert_channels: Option<Vec<AlertChannel>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProfileRequest {
+    pub contact_info: Option<ContactInfo>,
+    #[serde(default)]
+    pub marketing_opt_in: bool,
+    #[serde(default)]
+    pub alert_channels: Option<Vec<AlertChannel>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavedTravelerRequest {
+    pub ptc: Option<String>,
+    pub first_name: Masked<String>,
+    pub last_name: Masked<String>,
+    pub date_of_birth: Option<Masked<String>>,
+    pub gender: Option<String>,
+    /// PASSPORT or ID_CARD
+    pub document_type: Option<String>,
+    pub document_number: Option<Masked<String>>,
+    pub document_expiry: Option<NaiveDate>,
+    /// ISO 3166 country code
+    pub nationality: Option<String>,
+}
+
+/// A saved traveler as the customer sees it. The document number only ever
+/// comes back masked.
+#[derive(Debug, Serialize)]
+pub struct SavedTravelerResponse {
+    pub id: Uuid,
+    pub ptc: String,
+    #[serde(serialize_with = "pii::name")]
+    pub first_name: Masked<String>,
+    #[serde(serialize_with = "pii::name")]
+    pub last_name: Masked<String>,
+    #[serde(serialize_with = "pii::date_of_birth")]
+    pub date_of_birth: Option<Masked<String>>,
+    pub gender: Option<String>,
+    pub document_type: Option<String>,
+    pub document_number: Option<String>,
+    pub document_expiry: Option<NaiveDate>,
+    pub nationality: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A saved traveler as stored
+#[derive(Debug, Deserialize)]
+struct SavedTraveler {
+    id: Uuid,
+    ptc: String,
+    first_name: Masked<String>,
+    last_name: Masked<String>,
+    date_of_birth: Option<Masked<String>>,
+    gender: Option<String>,
+    document_type: Option<String>,
+    document_number: Option<Masked<String>>,
+    document_expiry: Option<NaiveDate>,
+    nationality: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<SavedTraveler> for SavedTravelerResponse {
+    fn from(t: SavedTraveler) -> Self {
+        Self {
+            id: t.id,
+            ptc: t.ptc,
+            first_name: t.first_name,
+            last_name: t.last_name,
+            date_of_birth: t.date_of_birth,
+            gender: t.gender,
+            document_type: t.document_type,
+            document_number: t.document_number
+                .and_then(|number| pii::mask(PiiField::DocumentNumber, &number.0, Visibility::Partial)),
+            document_expiry: t.document_expiry,
+            nationality: t.nationality,
+            created_at: t.created_at,
+        }
+    }
+}
+
+fn saved_traveler(row: serde_json::Value) -> Result<SavedTraveler, StatusCode> {
+    serde_json::from_value(row).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn to_profile_response(customer_id: &str, profile: Option<serde_json::Value>) -> ProfileResponse {
+    let profile = profile.unwrap_or_default();
+    let text = |field: &str| profile[field].as_str().map(|v| Masked(v.to_string()));
+    ProfileResponse {
+        customer_id: customer_id.to_string(),
+        contact_info: text("email").map(|email| ContactInfo {
+            email,
+            phone: text("phone"),
+            first_name: text("first_name"),
+            last_name: text("last_name"),
+        }),
+        marketing_opt_in: profile["marketing_opt_in"].as_bool().unwrap_or(false),
+        marketing_opt_in_at: profile["marketing_opt_in_at"].as_str()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Utc)),
+        alert_channels: serde_json::from_value(profile["alert_channels"].clone()).unwrap_or_default(),
+    }
+}
+
+/// Alert channels a customer may choose: at least one, each once, and text
+/// channels only with a phone number to text and a provider that sends them
+fn valid_alert_channels(state: &AppState, channels: &[AlertChannel], has_phone: bool) -> bool {
+    let distinct: std::collections::HashSet<_> = channels.iter().collect();
+    !channels.is_empty()
+        && distinct.len() == channels.len()
+        && channels.iter().all(|channel| (has_phone || !channel.is_text()) && state.alerts.supports(*channel))
+}
+
+/// Validates a saved traveler and normalizes it for storage, with the same
+/// rules as traveler imports plus the travel document
+fn traveler_record(req: &SavedTravelerRequest) -> Result<serde_json::Value, StatusCode> {
+    let record = altis_order::travelers::validate_traveler(
+        req.ptc.as_deref(),
+        Some(req.first_name.0.trim()),
+        Some(req.last_name.0.trim()),
+        req.date_of_birth.as_ref().map(|dob| dob.0.trim()),
+        req.gender.as_deref(),
+    ).map_err(|errors| {
+        tracing::info!("Saved traveler rejected: {}", errors.join("; "));
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    let document_type = req.document_type.as_deref().map(str::to_uppercase);
+    let document_number = req.document_number.as_ref().map(|n| n.0.trim()).filter(|n| !n.is_empty());
+    let document_ok = match (document_type.as_deref(), document_number) {
+        (None, None) => true,
+        (Some("PASSPORT" | "ID_CARD"), Some(number)) => number.len() <= 50 && number.chars().all(|c| c.is_ascii_alphanumeric()),
+        _ => false,
+    };
+    let nationality = req.nationality.as_deref().map(str::to_uppercase);
+    let nationality_ok = nationality.as_deref()
+        .is_none_or(|code| (2..=3).contains(&code.len()) && code.chars().all(|c| c.is_ascii_uppercase()));
+    if !document_ok || !nationality_ok {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    Ok(serde_json::json!({
+        "ptc": record.ptc,
+        "first_name": record.first_name,
+        "last_name": record.last_name,
+        "date_of_birth": record.date_of_birth,
+        "gender": record.gender,
+        "document_type": document_type,
+        "document_number": document_number.map(str::to_uppercase),
+        "document_expiry": req.document_expiry,
+        "nationality": nationality,
+    }))
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/customers/me
+/// The customer's profile: default contact details and marketing consent
+pub async fn get_profile(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+) -> Result<Json<ProfileResponse>, StatusCode> {
+    let owner = registered_customer(&claims)?;
+    let profile = state.profile_repo.get_profile(owner).await.map_err(|e| {
+        tracing::error!("Failed to load profile: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(to_profile_response(owner, profile)))
+}
+
+/// PUT /v1/customers/me
+/// Replace the customer's default contact details, marketing consent and alert channels
+pub async fn update_profile(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Json(mut req): Json<UpdateProfileRequest>,
+) -> Result<Json<ProfileResponse>, StatusCode> {
+    let owner = registered_customer(&claims)?;
+    if req.contact_info.as_mut().is_some_and(|contact| !contact.email.0.contains('@') || !contact.normalize_phone()) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let has_phone = req.contact_info.as_ref().is_some_and(|contact| contact.phone.is_some());
+    if req.alert_channels.as_ref().is_some_and(|channels| !valid_alert_channels(&state, channels, has_phone)) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let contact = req.contact_info.as_ref();
+    let text = |value: Option<&Masked<String>>| value.map(|v| v.0.trim().to_string()).filter(|v| !v.is_empty());
+    let saved = state.profile_repo.save_profile(owner, &serde_json::json!({
+        "email": contact.map(|c| c.email.0.trim()),
+        "phone": text(contact.and_then(|c| c.phone.as_ref())),
+        "first_name": text(contact.and_then(|c| c.first_name.as_ref())),
+        "last_name": text(contact.and_then(|c| c.last_name.as_ref())),
+        "marketing_opt_in": req.marketing_opt_in,
+        "alert_channels": req.alert_channels,
+    })).await.map_err(|e| {
+        tracing::error!("Failed to save profile: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(to_profile_response(owner, Some(saved))))
+}
+
+/// GET /v1/customers/me/travelers
+/// Travelers saved for later bookings, oldest first
+pub async fn list_saved_travelers(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+) -> Result<Json<Vec<SavedTravelerResponse>>, StatusCode> {
+    let owner = registered_customer(&claims)?;
+    let rows = state.profile_repo.list_saved_travelers(owner).await.map_err(|e| {
+        tracing::error!("Failed to list saved travelers: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let travelers = rows.into_iter().map(saved_traveler).collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(travelers.into_iter().map(Into::into).collect()))
+}
+
+/// POST /v1/customers/me/travelers
+/// Save a traveler for later bookings
+pub async fn create_saved_traveler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Json(req): Json<SavedTravelerRequest>,
+) -> Result<(StatusCode, Json<SavedTravelerResponse>), StatusCode> {
+    let owner = registered_customer(&claims)?;
+    let record = traveler_record(&req)?;
+    let saved = state.profile_repo.create_saved_traveler(owner, &record).await.map_err(|e| {
+        tracing::error!("Failed to save traveler: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok((StatusCode::CREATED, Json(saved_traveler(saved)?.into())))
+}
+
+/// PUT /v1/customers/me/travelers/:id
+/// Replace a saved traveler's details
+pub async fn update_saved_traveler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SavedTravelerRequest>,
+) -> Result<Json<SavedTravelerResponse>, StatusCode> {
+    let owner = registered_customer(&claims)?;
+    let record = traveler_record(&req)?;
+    let updated = state.profile_repo.update_saved_traveler(id, owner, &record).await
+        .map_err(|e| {
+            tracing::error!("Failed to update saved traveler {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(saved_traveler(updated)?.into()))
+}
+
+/// DELETE /v1/customers/me/travelers/:id
+/// Forget a saved traveler; orders already booked with them keep their copy
+pub async fn delete_saved_traveler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let owner = registered_customer(&claims)?;
+    let deleted = state.profile_repo.delete_saved_traveler(id, owner).await.map_err(|e| {
+        tracing::error!("Failed to delete saved traveler {}: {:?}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Booking With a Profile
+// ============================================================================
+
+/// Fills an offer acceptance from the caller's profile: saved traveler
+/// references become travelers, and the profile's contact details stand in
+/// when none are given. Travel documents stay on the profile; orders only
+/// note which saved traveler they came from.
+pub(crate) async fn apply_profile(state: &AppState, claims: &CustomerClaims, req: &mut AcceptOfferRequest) -> Result<(), StatusCode> {
+    if !req.saved_traveler_ids.is_empty() {
+        if req.travelers.is_some() {
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        let owner = registered_customer(claims)?;
+        let rows = state.profile_repo.get_saved_travelers(owner, &req.saved_traveler_ids).await.map_err(|e| {
+            tracing::error!("Failed to load saved travelers: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let saved = rows.into_iter().map(saved_traveler).collect::<Result<Vec<_>, _>>()?;
+        req.travelers = Some(order_travelers(&req.saved_traveler_ids, saved)?);
+    }
+
+    if req.contact_info.is_none() {
+        if let Ok(owner) = registered_customer(claims) {
+            let profile = state.profile_repo.get_profile(owner).await.map_err(|e| {
+                tracing::error!("Failed to load profile: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            req.contact_info = to_profile_response(owner, profile).contact_info;
+        }
+    }
+    Ok(())
+}
+
+/// Travelers in the order their ids were given. Ids that aren't among the
+/// customer's saved travelers, or repeat, are the request's fault.
+fn order_travelers(ids: &[Uuid], saved: Vec<SavedTraveler>) -> Result<Vec<Traveler>, StatusCode> {
+    let mut saved: std::collections::HashMap<Uuid, SavedTraveler> = saved.into_iter().map(|t| (t.id, t)).collect();
+    ids.iter().enumerate().map(|(index, id)| {
+        let traveler = saved.remove(id).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+        Ok(Traveler {
+            id: None,
+            traveler_index: index as i32,
+            ptc: traveler.ptc,
+            first_name: traveler.first_name,
+            last_name: traveler.last_name,
+            date_of_birth: traveler.date_of_birth,
+            gender: traveler.gender,
+            traveler_did: None,
+            metadata: Some(serde_json::json!({ "saved_traveler_id": traveler.id })),
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(first_name: &str) -> SavedTraveler {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "ptc": "ADT",
+            "first_name": first_name,
+            "last_name": "Tan",
+            "date_of_birth": "1990-12-10",
+            "document_type": "PASSPORT",
+            "document_number": "E1234567",
+            "created_at": "2026-10-16T08:00:00.123456+00:00",
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_saved_travelers_book_in_requested_order_without_documents() {
+        let (mei, wei) = (saved("Mei"), saved("Wei"));
+        let ids = [wei.id, mei.id];
+        let mei_id = mei.id;
+
+        let travelers = order_travelers(&ids, vec![mei, wei]).unwrap();
+        assert_eq!(travelers[0].first_name.0, "Wei");
+        assert_eq!(travelers[1].traveler_index, 1);
+        assert_eq!(travelers[1].metadata.as_ref().unwrap()["saved_traveler_id"], serde_json::json!(mei_id));
+        assert!(!serde_json::to_string(&travelers).unwrap().contains("E1234567"));
+
+        // Someone else's id, or the same traveler twice
+        let mei = saved("Mei");
+        assert_eq!(order_travelers(&[mei.id, Uuid::new_v4()], vec![mei]).unwrap_err(), StatusCode::UNPROCESSABLE_ENTITY);
+        let mei = saved("Mei");
+        assert_eq!(order_travelers(&[mei.id, mei.id], vec![mei]).unwrap_err(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let shown = serde_json::to_value(SavedTravelerResponse::from(saved("Lin"))).unwrap();
+        assert_eq!(shown["document_number"], "****4567");
+        assert_eq!(shown["first_name"], "Lin");
+    }
+}
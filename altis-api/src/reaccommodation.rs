@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use altis_catalog::product::FlightProduct;
+use altis_catalog::InventoryError;
+use altis_order::changes::{diff, DiffLine, OrderDiff};
+use altis_order::reaccommodation::ReaccommodationSearch;
+use altis_shared::money::Money;
+
+use crate::orders::OrderItemResponse;
+use crate::state::AppState;
+
+fn order_items(order: &serde_json::Value) -> Vec<OrderItemResponse> {
+    serde_json::from_value(order["items"].clone()).unwrap_or_default()
+}
+
+/// Seats a flight item holds: one per passenger, none for lap infants
+fn seats_of(quantity: Option<i32>, metadata: &serde_json::Value) -> i32 {
+    if crate::offers::is_lap_infant(metadata) { 0 } else { quantity.unwrap_or(1).max(1) }
+}
+
+fn time(value: &serde_json::Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    value.as_str()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+// ============================================================================
+// Proposals
+// ============================================================================
+
+/// Catalog flights that could take disrupted passengers, with live seat
+/// counts in place of the catalog's static ones
+pub(crate) async fn candidate_flights(state: &AppState, catalog_flights: &[serde_json::Value]) -> Vec<FlightProduct> {
+    let mut candidates: Vec<FlightProduct> = catalog_flights.iter()
+        .filter(|flight| flight["is_active"].as_bool().unwrap_or(true))
+        .filter_map(crate::admin::flight_product)
+        .collect();
+    for candidate in &mut candidates {
+        if let Ok(Some(inventory)) = state.inventory.get(candidate.product.id).await {
+            candidate.available_seats = inventory.available_quantity;
+        }
+    }
+    candidates
+}
+
+/// Proposes the best-ranked replacement flights for each of the order's
+/// bookings on the disrupted flight, holding seats on every proposal until
+/// the customer picks one or `reaccommodation.hold_minutes` runs out.
+/// Bookings that get a proposal are marked PROTECTED. Returns the number of
+/// proposals made.
+pub(crate) async fn propose_alternatives(
+    state: &AppState,
+    disruption_id: Uuid,
+    order: &serde_json::Value,
+    disrupted: &serde_json::Value,
+    candidates: &[FlightProduct],
+) -> usize {
+    let config = &state.reaccommodation;
+    let Some(order_id) = order["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { return 0 };
+    let Some(flight_id) = disrupted["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { return 0 };
+    let now = chrono::Utc::now();
+    let departure = time(&disrupted["metadata"]["departure_time"]).unwrap_or(now);
+    let hold_expires_at = now + chrono::Duration::minutes(config.hold_minutes);
+
+    let mut proposed = 0;
+    let booked = order_items(order).into_iter().filter(|item| {
+        item.product_type == "FLIGHT"
+            && item.status == "ACTIVE"
+            && item.metadata["flight_id"].as_str() == Some(flight_id.to_string().as_str())
+    });
+    for item in booked {
+        let seats = seats_of(item.quantity, &item.metadata);
+        let search = ReaccommodationSearch {
+            flight_id,
+            origin: disrupted["metadata"]["origin"].as_str().unwrap_or_default().to_string(),
+            destination: disrupted["metadata"]["destination"].as_str().unwrap_or_default().to_string(),
+            scheduled_arrival: time(&item.metadata["arrival_time"])
+                .or_else(|| time(&disrupted["metadata"]["arrival_time"]))
+                .unwrap_or(departure),
+            cabin_class: item.metadata["cabin_class"].as_str().map(str::to_string),
+            seats: seats.max(1),
+            not_before: now,
+            not_after: departure + chrono::Duration::hours(config.search_window_hours),
+        };
+
+        // Rank everything: a flight can fill up before its seats are held
+        let mut held = 0;
+        for alternative in search.rank(candidates, usize::MAX) {
+            if held == config.max_options {
+                break;
+            }
+            let product_id = alternative.flight.product.id;
+            let reserved = if seats > 0 { state.inventory.reserve(product_id, seats).await } else { Ok(()) };
+            match reserved {
+                Ok(()) | Err(InventoryError::NotFound(_)) => {}
+                Err(InventoryError::InsufficientInventory { .. }) => continue,
+                Err(e) => {
+                    tracing::warn!("Could not hold seats on flight {} for order {}: {}", product_id, order_id, e);
+                    continue;
+                }
+            }
+
+            let mut metadata = alternative.flight.product.metadata.clone();
+            metadata["flight_id"] = serde_json::json!(alternative.flight.flight_id.to_string());
+            metadata["disruption_id"] = serde_json::json!(disruption_id.to_string());
+            metadata["disrupted_flight_id"] = serde_json::json!(flight_id.to_string());
+            metadata["disrupted_item_id"] = serde_json::json!(item.id.to_string());
+            metadata["proposal_rank"] = serde_json::json!(held + 1);
+            metadata["arrival_delay_minutes"] = serde_json::json!(alternative.arrival_delay_minutes);
+            metadata["cabin_match"] = serde_json::json!(alternative.cabin_match);
+            metadata["hold_expires_at"] = serde_json::json!(hold_expires_at.to_rfc3339());
+            for fare_field in ["ptc", "lap_infant"] {
+                if !item.metadata[fare_field].is_null() {
+                    metadata[fare_field] = item.metadata[fare_field].clone();
+                }
+            }
+            let proposal = serde_json::json!({
+                "product_type": "FLIGHT",
+                "product_id": product_id,
+                "product_code": alternative.flight.product.product_code,
+                "name": alternative.flight.product.name,
+                "price_nuc": 0, // Involuntary re-accommodation is free
+                "quantity": item.quantity.unwrap_or(1).max(1),
+                "status": "REACCOMMODATED",
+                "metadata": metadata,
+            });
+            if let Err(e) = state.order_repo.add_order_item(order_id, &proposal).await {
+                tracing::error!("Failed to propose flight {} on order {}: {:?}", product_id, order_id, e);
+                release_hold(state, product_id, seats).await;
+                continue;
+            }
+            held += 1;
+        }
+
+        if held > 0 {
+            let _ = state.order_repo.transition_item_status(item.id, "ACTIVE", "PROTECTED").await;
+        } else {
+            tracing::warn!("No alternative with {} seat(s) for item {} of order {}", seats, item.id, order_id);
+        }
+        proposed += held;
+    }
+    proposed
+}
+
+async fn release_hold(state: &AppState, product_id: Uuid, seats: i32) {
+    if seats == 0 {
+        return;
+    }
+    match state.inventory.release(product_id, seats).await {
+        Ok(()) | Err(InventoryError::NotFound(_)) => {}
+        Err(e) => tracing::error!("Failed to release {} proposed seat(s) on flight {}: {}", seats, product_id, e),
+    }
+}
+
+// ============================================================================
+// Acceptance
+// ============================================================================
+
+/// Confirms the chosen proposals, one per disrupted booking: the original
+/// booking becomes MODIFIED, and the other proposals for it are declined and
+/// their seats released. The chosen seats stay held until payment, or are
+/// sold now if the order is paid.
+pub(crate) async fn accept_proposals(state: &AppState, order: &serde_json::Value, selected: &[Uuid]) -> Result<(), StatusCode> {
+    let items = order_items(order);
+    let paid = order["status"].as_str() == Some("PAID");
+    let chosen = chosen_proposals(&items, selected)?;
+
+    for item in chosen {
+        let accepted = state.order_repo.transition_item_status(item.id, "REACCOMMODATED", "ACTIVE").await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if !accepted {
+            // Expired (or accepted elsewhere) since the order was read
+            return Err(StatusCode::CONFLICT);
+        }
+        let _ = state.order_repo.merge_item_metadata(item.id, &serde_json::json!({"proposal_status": "ACCEPTED"})).await;
+        let seats = seats_of(item.quantity, &item.metadata);
+        if let Some(product_id) = item.product_id.filter(|_| paid && seats > 0) {
+            match state.inventory.commit(product_id, seats).await {
+                Ok(()) | Err(InventoryError::NotFound(_)) => {}
+                Err(e) => tracing::error!("Re-accommodation seat on flight {} held but not sold: {}", product_id, e),
+            }
+        }
+
+        let disrupted_id = disrupted_of(item);
+        if let Some(original) = disrupted_id {
+            let _ = state.order_repo.update_item_status(original, "MODIFIED").await;
+        }
+
+        let declined = items.iter().filter(|i| i.id != item.id && i.status == "REACCOMMODATED" && disrupted_of(i) == disrupted_id);
+        for other in declined {
+            if let Ok(true) = state.order_repo.transition_item_status(other.id, "REACCOMMODATED", "CANCELLED").await {
+                let _ = state.order_repo.merge_item_metadata(other.id, &serde_json::json!({"proposal_status": "DECLINED"})).await;
+                if let Some(product_id) = other.product_id {
+                    release_hold(state, product_id, seats_of(other.quantity, &other.metadata)).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn disrupted_of(item: &OrderItemResponse) -> Option<Uuid> {
+    item.metadata["disrupted_item_id"].as_str().and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// The selected proposals, if they can be accepted: open proposals (422
+/// otherwise), one per disrupted booking (422) and still held (410)
+fn chosen_proposals<'a>(items: &'a [OrderItemResponse], selected: &[Uuid]) -> Result<Vec<&'a OrderItemResponse>, StatusCode> {
+    let mut chosen = Vec::new();
+    let mut disrupted = HashSet::new();
+    for id in selected {
+        let Some(item) = items.iter().find(|item| item.id == *id && item.status == "REACCOMMODATED") else {
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        };
+        // One replacement per booking
+        if !disrupted_of(item).is_some_and(|d| disrupted.insert(d)) {
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        if time(&item.metadata["hold_expires_at"]).is_some_and(|expires| expires < chrono::Utc::now()) {
+            return Err(StatusCode::GONE);
+        }
+        chosen.push(item);
+    }
+    Ok(chosen)
+}
+
+/// How accepting the selected proposals would change the order. Each chosen
+/// flight takes over its disrupted booking's fare, so the balance only moves
+/// if a booking had nothing left to carry over.
+pub(crate) fn reaccommodation_changes(order: &serde_json::Value, selected: &[Uuid]) -> Result<OrderDiff, StatusCode> {
+    let items = order_items(order);
+    let chosen = chosen_proposals(&items, selected)?;
+
+    let before = crate::orders::held_lines(&items)?;
+    let replaced: Vec<Uuid> = chosen.iter().filter_map(|item| disrupted_of(item)).collect();
+    let mut after: Vec<DiffLine> = before.iter().filter(|line| !replaced.contains(&line.item_id)).cloned().collect();
+    for item in chosen {
+        let original = disrupted_of(item).and_then(|id| before.iter().find(|line| line.item_id == id));
+        after.push(DiffLine {
+            item_id: item.id,
+            product_id: item.product_id,
+            product_type: item.product_type.clone(),
+            name: item.name.clone(),
+            amount: original.map_or(Money::nuc(item.price_nuc as i64), |line| line.amount),
+            replaces: original.map(|line| line.item_id),
+        });
+    }
+    diff::compare(&before, &after).map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)
+}
+
+// ============================================================================
+// Expiry
+// ============================================================================
+
+/// Cancels proposals nobody chose in time and gives their seats back
+pub async fn run_proposal_expiry_worker(state: AppState) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        let expired = match state.order_repo.expire_reaccommodation_proposals(500).await {
+            Ok(expired) => expired,
+            Err(e) => {
+                tracing::error!("Failed to expire re-accommodation proposals: {:?}", e);
+                continue;
+            }
+        };
+        for proposal in &expired {
+            let Some(product_id) = proposal["product_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+            let quantity = proposal["quantity"].as_i64().map(|q| q as i32);
+            release_hold(&state, product_id, seats_of(quantity, &proposal["metadata"])).await;
+        }
+        if !expired.is_empty() {
+            tracing::info!("Expired {} re-accommodation proposal(s)", expired.len());
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Every offer or paid order, whatever it contained
+const ALL_PRODUCTS: &str = "ALL";
+
+#[derive(Debug, Deserialize)]
+pub struct RetailReportQuery {
+    /// First day (UTC) of the report; 30 days before `to` when left out
+    pub from: Option<NaiveDate>,
+    /// Last day (UTC) of the report, inclusive; today when left out
+    pub to: Option<NaiveDate>,
+    /// Only offers, or only attach rates, for this product type
+    pub product_type: Option<String>,
+}
+
+/// How far offers got, with the share that made it through each step
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunnelStats {
+    pub offers_generated: i64,
+    pub offers_accepted: i64,
+    pub orders_paid: i64,
+    pub revenue_nuc: i64,
+    /// Accepted out of generated
+    #[serde(default)]
+    pub acceptance_rate: Option<f64>,
+    /// Paid out of accepted
+    #[serde(default)]
+    pub payment_rate: Option<f64>,
+    /// Paid out of generated
+    #[serde(default)]
+    pub conversion_rate: Option<f64>,
+}
+
+impl FunnelStats {
+    fn with_rates(mut self) -> Self {
+        self.acceptance_rate = rate(self.offers_accepted, self.offers_generated);
+        self.payment_rate = rate(self.orders_paid, self.offers_accepted);
+        self.conversion_rate = rate(self.orders_paid, self.offers_generated);
+        self
+    }
+
+    fn add(mut self, other: &FunnelStats) -> Self {
+        self.offers_generated += other.offers_generated;
+        self.offers_accepted += other.offers_accepted;
+        self.orders_paid += other.orders_paid;
+        self.revenue_nuc += other.revenue_nuc;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FunnelDay {
+    pub day: NaiveDate,
+    #[serde(flatten)]
+    pub stats: FunnelStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunnelResponse {
+    pub airline_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub product_type: String,
+    pub totals: FunnelStats,
+    /// Days with offers, oldest first
+    pub days: Vec<FunnelDay>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachRate {
+    pub product_type: String,
+    /// Paid orders that included the product type
+    pub orders: i64,
+    pub items: i64,
+    pub revenue_nuc: i64,
+    /// Share of all paid orders that included it
+    #[serde(default)]
+    pub attach_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttachRateResponse {
+    pub airline_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub paid_orders: i64,
+    pub product_types: Vec<AttachRate>,
+}
+
+/// `part` out of `whole` to four places; None when there's nothing to divide by
+fn rate(part: i64, whole: i64) -> Option<f64> {
+    (whole > 0).then(|| (part as f64 / whole as f64 * 10_000.0).round() / 10_000.0)
+}
+
+/// The days a report covers: the last 30 up to today unless asked otherwise
+fn report_range(query: &RetailReportQuery, today: NaiveDate, max_range_days: i64) -> Result<(NaiveDate, NaiveDate), StatusCode> {
+    let to = query.to.unwrap_or(today);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(29));
+    if from > to || (to - from).num_days() >= max_range_days {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok((from, to))
+}
+
+/// Where a rollup starts: the trailing `restate_days` before the latest day
+/// already rolled up (or today, whichever is earlier), so a gap left while
+/// the job wasn't running is filled too; `backfill_days` back on a first run
+fn rollup_start(latest: Option<NaiveDate>, today: NaiveDate, restate_days: i64, backfill_days: i64) -> NaiveDate {
+    match latest {
+        Some(latest) => latest.min(today) - chrono::Duration::days(restate_days),
+        None => today - chrono::Duration::days(backfill_days),
+    }
+}
+
+/// GET /v1/admin/airlines/:airline_id/analytics/funnel
+/// Offers generated, accepted and paid per day, with conversion rates
+pub async fn get_funnel(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Query(query): Query<RetailReportQuery>,
+) -> Result<Json<FunnelResponse>, StatusCode> {
+    let (from, to) = report_range(&query, Utc::now().date_naive(), state.analytics.max_range_days)?;
+    let product_type = query.product_type.as_deref().map_or(ALL_PRODUCTS.to_string(), str::to_uppercase);
+
+    let days: Vec<FunnelDay> = state.analytics_repo.daily_funnel(airline_id, from, to, &product_type).await
+        .map_err(|e| {
+            tracing::error!("Failed to read the funnel of airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let totals = days.iter().fold(FunnelStats::default(), |totals, day| totals.add(&day.stats)).with_rates();
+    let days = days.into_iter().map(|day| FunnelDay { day: day.day, stats: day.stats.with_rates() }).collect();
+    Ok(Json(FunnelResponse { airline_id, from, to, product_type, totals, days }))
+}
+
+/// GET /v1/admin/airlines/:airline_id/analytics/attach-rate
+/// Share of paid orders that included each kind of ancillary
+pub async fn get_attach_rates(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+    Query(query): Query<RetailReportQuery>,
+) -> Result<Json<AttachRateResponse>, StatusCode> {
+    let (from, to) = report_range(&query, Utc::now().date_naive(), state.analytics.max_range_days)?;
+    let wanted = query.product_type.as_deref().map(str::to_uppercase);
+
+    let totals: Vec<AttachRate> = state.analytics_repo.attach_totals(airline_id, from, to).await
+        .map_err(|e| {
+            tracing::error!("Failed to read attach rates of airline {}: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let paid_orders = totals.iter().find(|t| t.product_type == ALL_PRODUCTS).map_or(0, |t| t.orders);
+    // Every order has a flight; only what was added to it is an attachment
+    let product_types = totals.into_iter()
+        .filter(|t| t.product_type != ALL_PRODUCTS && t.product_type != "FLIGHT")
+        .filter(|t| wanted.as_ref().is_none_or(|wanted| &t.product_type == wanted))
+        .map(|t| AttachRate { attach_rate: rate(t.orders, paid_orders), ..t })
+        .collect();
+    Ok(Json(AttachRateResponse { airline_id, from, to, paid_orders, product_types }))
+}
+
+/// Rolls offers and orders up into the daily stats the reports read. Recent
+/// days are recomputed each time, as their offers are still being paid for.
+pub async fn run_analytics_rollup(state: AppState) {
+    let config = state.analytics.clone();
+    let mut interval = tokio::time::interval(Duration::from_secs(config.rollup_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let latest = match state.analytics_repo.latest_rollup_day().await {
+            Ok(latest) => latest,
+            Err(e) => {
+                tracing::error!("Failed to read the last analytics rollup: {:?}", e);
+                continue;
+            }
+        };
+        let since = rollup_start(latest, Utc::now().date_naive(), config.restate_days, config.backfill_days);
+        match state.analytics_repo.roll_up_daily_stats(since).await {
+            Ok(()) => tracing::debug!("Rolled up retail analytics from {}", since),
+            Err(e) => tracing::error!("Failed to roll up retail analytics from {}: {:?}", since, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_funnel_rates_and_report_windows() {
+        let stats = FunnelStats { offers_generated: 300, offers_accepted: 45, orders_paid: 30, revenue_nuc: 810_000, ..Default::default() }.with_rates();
+        assert_eq!(stats.acceptance_rate, Some(0.15));
+        assert_eq!(stats.payment_rate, Some(0.6667));
+        assert_eq!(stats.conversion_rate, Some(0.1));
+        // Nothing generated is no rate at all, not a zero one
+        assert_eq!(FunnelStats::default().with_rates().conversion_rate, None);
+
+        let today = day("2026-03-31");
+        let query = |from: Option<&str>, to: Option<&str>| RetailReportQuery { from: from.map(day), to: to.map(day), product_type: None };
+        assert_eq!(report_range(&query(None, None), today, 366), Ok((day("2026-03-02"), today)));
+        assert_eq!(report_range(&query(Some("2026-03-10"), Some("2026-03-10")), today, 366), Ok((day("2026-03-10"), day("2026-03-10"))));
+        assert_eq!(report_range(&query(Some("2026-03-11"), Some("2026-03-10")), today, 366), Err(StatusCode::BAD_REQUEST));
+        assert_eq!(report_range(&query(Some("2025-01-01"), None), today, 366), Err(StatusCode::BAD_REQUEST));
+
+        // First run backfills; later runs restate the trailing days and any gap
+        assert_eq!(rollup_start(None, today, 3, 90), day("2025-12-31"));
+        assert_eq!(rollup_start(Some(today), today, 3, 90), day("2026-03-28"));
+        assert_eq!(rollup_start(Some(day("2026-03-20")), today, 3, 90), day("2026-03-17"));
+    }
+}
@@ -0,0 +1,143 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use serde_json::Value;
+use uuid::Uuid;
+
+use altis_store::app_config::SandboxConfig;
+
+use crate::state::AppState;
+
+/// A flight the sandbox airline operates every day
+struct Route {
+    origin: &'static str,
+    destination: &'static str,
+    number: u16,
+    /// Hour and minute, UTC
+    departs: (u32, u32),
+    block_minutes: i64,
+    fare_nuc: i32,
+}
+
+const ROUTES: &[Route] = &[
+    Route { origin: "SIN", destination: "BKK", number: 101, departs: (8, 30), block_minutes: 145, fare_nuc: 12000 },
+    Route { origin: "BKK", destination: "SIN", number: 102, departs: (13, 15), block_minutes: 140, fare_nuc: 12000 },
+    Route { origin: "SIN", destination: "KUL", number: 201, departs: (9, 0), block_minutes: 60, fare_nuc: 6500 },
+    Route { origin: "LHR", destination: "CDG", number: 301, departs: (7, 45), block_minutes: 75, fare_nuc: 9000 },
+];
+
+/// Airline whose catalog a search is answered from: the sandbox airline for
+/// test keys, the live one otherwise
+pub(crate) fn catalog_airline(state: &AppState, test: bool) -> &str {
+    if test { &state.sandbox.airline_code } else { crate::offers::CATALOG_AIRLINE }
+}
+
+/// Whether the offer was generated from the sandbox catalog. Test callers
+/// may only accept these, live callers never.
+pub(crate) fn is_sandbox_offer(state: &AppState, offer: &altis_offer::Offer) -> bool {
+    offer.metadata["owner"].as_str() == Some(state.sandbox.airline_code.as_str())
+}
+
+/// Catalog bundle of one flight per route and day for `days_ahead` days
+/// from `today`, in the shape `apply_catalog` takes
+fn synthetic_catalog(config: &SandboxConfig, today: NaiveDate) -> Value {
+    let mut products = Vec::new();
+    for day in 0..config.days_ahead {
+        let date = today + Duration::days(day);
+        for route in ROUTES {
+            let flight_number = format!("{}{}", config.airline_code, route.number);
+            let (hour, minute) = route.departs;
+            let departure = date.and_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or_default());
+            let arrival = departure + Duration::minutes(route.block_minutes);
+            products.push(serde_json::json!({
+                "product_type": "FLIGHT",
+                "product_code": format!("{}-{}", flight_number, date.format("%Y%m%d")),
+                "name": format!("{} {}-{}", flight_number, route.origin, route.destination),
+                "description": "Sandbox flight",
+                "base_price_nuc": route.fare_nuc,
+                "metadata": {
+                    "flight_number": flight_number,
+                    "origin": route.origin,
+                    "destination": route.destination,
+                    "departure_date": date.to_string(),
+                    "departure_time": departure.and_utc().to_rfc3339(),
+                    "arrival_time": arrival.and_utc().to_rfc3339(),
+                    "available_seats": config.seats_per_flight,
+                    "cabin_class": "ECONOMY",
+                },
+            }));
+        }
+    }
+    serde_json::json!({ "products": products })
+}
+
+/// POST /v1/admin/sandbox/reset
+/// Reseed the sandbox airline's flights from today and put every seat back on sale
+pub async fn reset_sandbox(
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    let config = &state.sandbox;
+    let airline_id = state.catalog_repo.upsert_airline(&config.airline_code, &config.airline_name).await
+        .map_err(|e| {
+            tracing::error!("Failed to create sandbox airline {}: {:?}", config.airline_code, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Flights from past days are missing from the bundle and retired with it
+    let bundle = synthetic_catalog(config, chrono::Utc::now().date_naive());
+    state.catalog_repo.apply_catalog(airline_id, &bundle).await.map_err(|e| {
+        tracing::error!("Failed to reseed sandbox catalog: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    state.catalog_changed("sandbox catalog reset").await;
+
+    let products = state.catalog_cache.products(airline_id).await.map_err(|e| {
+        tracing::error!("Failed to fetch sandbox products: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut flights = 0;
+    for product in products.iter() {
+        if product["product_type"].as_str() != Some("FLIGHT") || product["is_active"].as_bool() == Some(false) {
+            continue;
+        }
+        let Some(product_id) = product["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+        state.inventory.reset(product_id, config.seats_per_flight).await.map_err(|e| {
+            tracing::error!("Failed to reset sandbox inventory of {}: {:?}", product_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        flights += 1;
+    }
+    state.search_cache.invalidate().await;
+
+    tracing::info!("Reset sandbox airline {}: {} flights", config.airline_code, flights);
+    Ok(Json(serde_json::json!({
+        "airline_id": airline_id,
+        "airline_code": config.airline_code,
+        "flights": flights,
+        "seats_per_flight": config.seats_per_flight,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_catalog_has_a_flight_per_route_and_day() {
+        let config = SandboxConfig { days_ahead: 3, seats_per_flight: 50, ..SandboxConfig::default() };
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let bundle = synthetic_catalog(&config, today);
+
+        let products = bundle["products"].as_array().unwrap();
+        assert_eq!(products.len(), 3 * ROUTES.len());
+        let codes: std::collections::HashSet<_> = products.iter().map(|p| p["product_code"].as_str().unwrap()).collect();
+        assert_eq!(codes.len(), products.len());
+
+        let first = &products[0];
+        assert_eq!(first["product_code"], "ZZ101-20260301");
+        assert_eq!(first["metadata"]["departure_date"], "2026-03-01");
+        assert_eq!(first["metadata"]["departure_time"], "2026-03-01T08:30:00+00:00");
+        assert_eq!(first["metadata"]["arrival_time"], "2026-03-01T10:55:00+00:00");
+        assert_eq!(first["metadata"]["available_seats"], 50);
+        assert_eq!(products.last().unwrap()["metadata"]["departure_date"], "2026-03-03");
+    }
+}
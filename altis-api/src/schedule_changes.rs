@@ -0,0 +1,224 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+
+use altis_order::ledger::JournalTransaction;
+use altis_order::schedule_change::{ScheduleChange, ScheduleChangeSeverity};
+use altis_shared::money::Money;
+
+use crate::authz::authorize_order;
+use crate::middleware::auth::CustomerClaims;
+use crate::orders::{OrderItemResponse, OrderResponse};
+use crate::state::AppState;
+
+/// A major change the passenger hasn't answered yet
+const PENDING: &str = "PENDING";
+
+fn booked_on(order: &serde_json::Value, flight_id: &str) -> Vec<OrderItemResponse> {
+    let items: Vec<OrderItemResponse> = serde_json::from_value(order["items"].clone()).unwrap_or_default();
+    items.into_iter()
+        .filter(|item| item.product_type == "FLIGHT" && matches!(item.status.as_str(), "ACTIVE" | "PROTECTED"))
+        .filter(|item| item.metadata["flight_id"].as_str() == Some(flight_id))
+        .collect()
+}
+
+/// Carries an admin's edit of a flight's times over to the bookings on it:
+/// each booked item gets the new times, the order's change log records the
+/// move, and passengers are alerted once it passes
+/// `schedule_changes.notify_minutes`. Beyond `major_minutes` the booking
+/// waits for the passenger to accept or decline the new times.
+pub(crate) async fn propagate(state: &AppState, flight: &serde_json::Value, change: &ScheduleChange, actor: &str) {
+    let flight_id = flight["id"].as_str().unwrap_or_default();
+    let metadata = &flight["metadata"];
+    let config = &state.schedule_changes;
+    let severity = change.severity(config.notify_minutes, config.major_minutes);
+
+    let orders = match state.order_repo.find_orders_by_flight(flight_id).await {
+        Ok(orders) => orders,
+        Err(e) => {
+            tracing::error!("Failed to find bookings on retimed flight {}: {:?}", flight_id, e);
+            return;
+        }
+    };
+
+    let mut updated = 0;
+    for order in &orders {
+        let Some(order_id) = order["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+        let items = booked_on(order, flight_id);
+        if items.is_empty() {
+            continue;
+        }
+
+        for item in &items {
+            let mut patch = serde_json::json!({
+                "departure_time": metadata["departure_time"],
+                "arrival_time": metadata["arrival_time"],
+            });
+            if severity == ScheduleChangeSeverity::Major {
+                // The times the passenger booked, even across several moves
+                let booked = item.metadata["schedule_change"].as_object()
+                    .filter(|pending| pending.get("status").and_then(|s| s.as_str()) == Some(PENDING))
+                    .and_then(|pending| pending.get("previous_departure_time").cloned())
+                    .unwrap_or_else(|| item.metadata["departure_time"].clone());
+                patch["schedule_change"] = serde_json::json!({
+                    "status": PENDING,
+                    "previous_departure_time": booked,
+                    "shift_minutes": change.shift_minutes(),
+                    "changed_at": chrono::Utc::now(),
+                });
+            }
+            if let Err(e) = state.order_repo.merge_item_metadata(item.id, &patch).await {
+                tracing::error!("Failed to retime item {} of order {}: {:?}", item.id, order_id, e);
+            }
+        }
+
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            "SCHEDULE_CHANGED",
+            Some(serde_json::json!({"departure_time": change.previous_departure, "arrival_time": change.previous_arrival})),
+            Some(serde_json::json!({
+                "flight_id": flight_id,
+                "item_ids": items.iter().map(|item| item.id).collect::<Vec<_>>(),
+                "departure_time": metadata["departure_time"],
+                "arrival_time": metadata["arrival_time"],
+                "shift_minutes": change.shift_minutes(),
+                "severity": severity,
+            })),
+            actor,
+            Some("Flight retimed"),
+        ).await;
+
+        if severity > ScheduleChangeSeverity::Minor {
+            let alert = crate::travel_alerts::schedule_change_alert(flight, change, severity);
+            crate::travel_alerts::send_alert(state, order, &alert).await;
+        }
+        updated += 1;
+    }
+    tracing::info!("Flight {} moved by {} min; {} order(s) updated ({:?})", flight_id, change.shift_minutes(), updated, severity);
+}
+
+/// The booked flight with a major change waiting on the passenger (404 when
+/// the order has no such item, 409 when nothing is pending)
+fn pending_item(order: &serde_json::Value, item_id: Uuid) -> Result<OrderItemResponse, StatusCode> {
+    let items: Vec<OrderItemResponse> = serde_json::from_value(order["items"].clone())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let item = items.into_iter().find(|item| item.id == item_id).ok_or(StatusCode::NOT_FOUND)?;
+    if item.metadata["schedule_change"]["status"].as_str() != Some(PENDING) || item.status != "ACTIVE" {
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(item)
+}
+
+async fn answer(state: &AppState, order_id: Uuid, item: &OrderItemResponse, status: &str) -> Result<(), StatusCode> {
+    let mut schedule_change = item.metadata["schedule_change"].clone();
+    schedule_change["status"] = serde_json::json!(status);
+    schedule_change["answered_at"] = serde_json::json!(chrono::Utc::now());
+    state.order_repo.merge_item_metadata(item.id, &serde_json::json!({"schedule_change": schedule_change})).await
+        .map_err(|e| {
+            tracing::error!("Failed to record schedule change answer on order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+async fn order_response(state: &AppState, order_id: Uuid) -> Result<Json<OrderResponse>, StatusCode> {
+    let order = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    serde_json::from_value(order).map(Json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /v1/orders/:id/schedule-changes/:item_id/accept
+/// Keep the booking on the flight's new times
+pub async fn accept_schedule_change(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path((order_id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    let order = authorize_order(&state, &claims, order_id).await?;
+    let item = pending_item(&order, item_id)?;
+
+    answer(&state, order_id, &item, "ACCEPTED").await?;
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "SCHEDULE_CHANGE_ACCEPTED",
+        None,
+        Some(serde_json::json!({"item_id": item.id, "departure_time": item.metadata["departure_time"]})),
+        "CUSTOMER",
+        Some("Customer accepted the new flight times"),
+    ).await;
+
+    order_response(&state, order_id).await
+}
+
+/// POST /v1/orders/:id/schedule-changes/:item_id/decline
+/// Give up the retimed flight; a paid booking is refunded in full, with no fees
+pub async fn decline_schedule_change(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path((order_id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    let order = authorize_order(&state, &claims, order_id).await?;
+    let item = pending_item(&order, item_id)?;
+    let paid = matches!(order["status"].as_str(), Some("PAID" | "FULFILLED"));
+
+    let target = if paid { "REFUNDED" } else { "CANCELLED" };
+    let declined = state.order_repo.transition_item_status(item.id, "ACTIVE", target).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !declined {
+        // Answered (or changed) since the order was read
+        return Err(StatusCode::CONFLICT);
+    }
+    answer(&state, order_id, &item, "DECLINED").await?;
+    crate::orders::return_inventory(&state, std::slice::from_ref(&item), paid).await;
+
+    let refund_nuc = if paid { item.price_nuc + item.tax_nuc } else { 0 };
+    if refund_nuc > 0 {
+        let reason = "Refund after a declined schedule change";
+        let _ = state.order_repo.update_item_revenue_status(item.id, "REFUNDED").await;
+        let _ = state.order_repo.add_order_ledger_entry(order_id, item.id, "REFUND", -refund_nuc, Some(reason)).await;
+        crate::documents::issue_for_order(&state, order_id, "CREDIT_NOTE", refund_nuc as i64).await;
+        crate::finance::post_journal(
+            &state,
+            JournalTransaction::refund(order_id, Some(item.id), Money::nuc(refund_nuc as i64), Money::nuc(item.tax_nuc as i64), reason),
+        ).await;
+    }
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "SCHEDULE_CHANGE_DECLINED",
+        Some(serde_json::json!({"item_id": item.id, "status": item.status})),
+        Some(serde_json::json!({"item_id": item.id, "status": target, "refund_nuc": refund_nuc})),
+        "CUSTOMER",
+        Some("Customer declined the new flight times"),
+    ).await;
+
+    order_response(&state, order_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_item() {
+        let flight_id = Uuid::new_v4().to_string();
+        let item = |status: &str, schedule_change: serde_json::Value| serde_json::json!({
+            "id": Uuid::new_v4(), "product_id": null, "product_type": "FLIGHT", "name": "SIN-BKK", "price_nuc": 20000,
+            "status": status, "revenue_status": "UNEARNED", "operating_carrier_id": null, "net_rate_nuc": null,
+            "commission_nuc": null, "metadata": {"flight_id": flight_id, "schedule_change": schedule_change},
+        });
+        let pending = item("ACTIVE", serde_json::json!({"status": PENDING}));
+        let accepted = item("ACTIVE", serde_json::json!({"status": "ACCEPTED"}));
+        let cancelled = item("CANCELLED", serde_json::json!({"status": PENDING}));
+        let order = serde_json::json!({"items": [pending, accepted, cancelled]});
+        let id = |item: &serde_json::Value| Uuid::parse_str(item["id"].as_str().unwrap()).unwrap();
+
+        assert_eq!(booked_on(&order, &flight_id).len(), 2);
+        assert!(pending_item(&order, id(&order["items"][0])).is_ok());
+        assert_eq!(pending_item(&order, id(&order["items"][1])).unwrap_err(), StatusCode::CONFLICT);
+        assert_eq!(pending_item(&order, id(&order["items"][2])).unwrap_err(), StatusCode::CONFLICT);
+        assert_eq!(pending_item(&order, Uuid::new_v4()).unwrap_err(), StatusCode::NOT_FOUND);
+    }
+}
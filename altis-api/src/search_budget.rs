@@ -0,0 +1,140 @@
+use std::future::Future;
+use std::time::Duration;
+
+use altis_store::app_config::SearchConfig;
+use altis_store::deadline::{self, Deadline, DeadlineExceeded};
+
+/// The steps of an offer search that run against their own deadline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStage {
+    Catalog,
+    Generation,
+    Ranking,
+}
+
+impl SearchStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchStage::Catalog => "catalog",
+            SearchStage::Generation => "generation",
+            SearchStage::Ranking => "ranking",
+        }
+    }
+}
+
+/// Time a search may spend, overall and per stage. Each stage's deadline is
+/// its own limit cut to what is left of the search's budget and of the
+/// request's deadline, so a slow stage leaves the rest nothing to overrun.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchBudget {
+    deadline: Option<Deadline>,
+    catalog: Duration,
+    generation: Duration,
+    ranking: Duration,
+}
+
+impl SearchBudget {
+    pub fn start(config: &SearchConfig) -> Self {
+        let total = Deadline::after(Duration::from_millis(config.budget_ms));
+        let deadline = match deadline::current() {
+            Some(request) if request.at() < total.at() => request,
+            _ => total,
+        };
+        Self {
+            deadline: Some(deadline),
+            catalog: Duration::from_millis(config.catalog_budget_ms),
+            generation: Duration::from_millis(config.generation_budget_ms),
+            ranking: Duration::from_millis(config.ranking_budget_ms),
+        }
+    }
+
+    /// No limits; background jobs pricing offers aren't waiting on anyone
+    pub fn unbounded() -> Self {
+        Self { deadline: None, catalog: Duration::MAX, generation: Duration::MAX, ranking: Duration::MAX }
+    }
+
+    /// When `stage`, started now, must be done by
+    pub fn stage_deadline(&self, stage: SearchStage) -> Option<Deadline> {
+        let deadline = self.deadline?;
+        let limit = match stage {
+            SearchStage::Catalog => self.catalog,
+            SearchStage::Generation => self.generation,
+            SearchStage::Ranking => self.ranking,
+        };
+        Some(Deadline::after(limit.min(deadline.remaining())))
+    }
+
+    /// Runs `fut` as `stage`, abandoning it at the stage's deadline. Calls
+    /// it makes see that deadline and stop waiting there too.
+    pub async fn run<F: Future>(&self, stage: SearchStage, fut: F) -> Result<F::Output, DeadlineExceeded> {
+        match self.stage_deadline(stage) {
+            Some(deadline) => tokio::time::timeout_at(deadline.at(), deadline.scope(fut)).await.map_err(|_| DeadlineExceeded),
+            None => Ok(fut.await),
+        }
+    }
+
+    /// Runs `fut` as `stage` without abandoning it; for work that honours
+    /// the deadline itself and shouldn't be dropped halfway
+    pub async fn scope<F: Future>(&self, stage: SearchStage, fut: F) -> F::Output {
+        match self.stage_deadline(stage) {
+            Some(deadline) => deadline.scope(fut).await,
+            None => fut.await,
+        }
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.remaining())
+    }
+}
+
+/// The stages a search cut short, in the order it hit them
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Degraded(Vec<SearchStage>);
+
+impl Degraded {
+    pub fn mark(&mut self, stage: SearchStage) {
+        if !self.0.contains(&stage) {
+            self.0.push(stage);
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    /// Value of the `X-Partial-Result` header, e.g. "catalog,ranking"
+    pub fn header_value(&self) -> String {
+        self.0.iter().map(SearchStage::as_str).collect::<Vec<_>>().join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stages_are_cut_to_the_remaining_budget() {
+        let config = SearchConfig { budget_ms: 500, catalog_budget_ms: 200, generation_budget_ms: 200, ranking_budget_ms: 1000, ..Default::default() };
+
+        let budget = SearchBudget::start(&config);
+        let catalog = budget.stage_deadline(SearchStage::Catalog).unwrap().remaining();
+        assert!(catalog <= Duration::from_millis(200) && catalog > Duration::from_millis(100));
+        // A stage never outlives the search
+        assert!(budget.stage_deadline(SearchStage::Ranking).unwrap().remaining() <= Duration::from_millis(500));
+
+        // ...nor the request it serves
+        let request = Deadline::after(Duration::from_millis(50));
+        let budget = request.scope(async { SearchBudget::start(&config) }).await;
+        assert!(budget.remaining().unwrap() <= Duration::from_millis(50));
+
+        assert_eq!(budget.run(SearchStage::Generation, tokio::time::sleep(Duration::from_secs(1))).await, Err(DeadlineExceeded));
+        assert_eq!(SearchBudget::unbounded().run(SearchStage::Generation, async { 7 }).await, Ok(7));
+
+        let mut degraded = Degraded::default();
+        assert!(!degraded.is_degraded());
+        degraded.mark(SearchStage::Catalog);
+        degraded.mark(SearchStage::Ranking);
+        degraded.mark(SearchStage::Catalog);
+        assert_eq!(degraded.header_value(), "catalog,ranking");
+    }
+}
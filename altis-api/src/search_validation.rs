@@ -0,0 +1,187 @@
+//! Checks an offer search before anything is priced, so a mistyped airport
+//! or a date in the past gets told what's wrong instead of an empty result.
+
+use std::sync::Arc;
+
+use altis_catalog::RouteNetwork;
+use altis_store::app_config::SearchConfig;
+use chrono::NaiveDate;
+
+use crate::error::{ApiError, FieldError};
+use crate::offers::SearchOffersRequest;
+use crate::state::AppState;
+
+/// Cabins offers are priced in
+pub const CABIN_CLASSES: &[&str] = &["ECONOMY", "PREMIUM_ECONOMY", "BUSINESS", "FIRST"];
+
+/// Every problem with the search at once. `today` is a UTC date; a departure
+/// on the day before is still allowed, as it may be today where the
+/// customer is. Airports are only looked up when `network` has routes.
+pub fn check_search(req: &SearchOffersRequest, today: NaiveDate, network: &RouteNetwork, config: &SearchConfig) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    for (field, code) in [("origin", &req.origin), ("destination", &req.destination)] {
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            errors.push(FieldError::new(field, format!("'{}' is not a three-letter IATA code", code)));
+        } else if !network.is_empty() && !network.serves(code) {
+            errors.push(FieldError::new(field, format!("no route serves {}", code.to_uppercase())));
+        }
+    }
+    if req.origin.eq_ignore_ascii_case(&req.destination) {
+        errors.push(FieldError::new("destination", "must differ from origin"));
+    }
+
+    let departure = match NaiveDate::parse_from_str(&req.departure_date, "%Y-%m-%d") {
+        Ok(date) if date < today - chrono::Duration::days(1) => {
+            errors.push(FieldError::new("departure_date", format!("{} is in the past", date)));
+            None
+        }
+        Ok(date) if date > today + chrono::Duration::days(config.max_days_ahead as i64) => {
+            errors.push(FieldError::new(
+                "departure_date",
+                format!("{} is more than {} days ahead", date, config.max_days_ahead),
+            ));
+            None
+        }
+        Ok(date) => Some(date),
+        Err(_) => {
+            errors.push(FieldError::new("departure_date", format!("'{}' is not a YYYY-MM-DD date", req.departure_date)));
+            None
+        }
+    };
+    if let Some(return_date) = req.return_date.as_deref() {
+        match NaiveDate::parse_from_str(return_date, "%Y-%m-%d") {
+            Ok(date) if departure.is_some_and(|departure| date < departure) => {
+                errors.push(FieldError::new("return_date", "must not be before departure_date"));
+            }
+            Ok(date) if date > today + chrono::Duration::days(config.max_days_ahead as i64) => {
+                errors.push(FieldError::new(
+                    "return_date",
+                    format!("{} is more than {} days ahead", date, config.max_days_ahead),
+                ));
+            }
+            Ok(_) => {}
+            Err(_) => errors.push(FieldError::new("return_date", format!("'{}' is not a YYYY-MM-DD date", return_date))),
+        }
+    }
+
+    if req.passengers == 0 || req.passengers > config.max_passengers {
+        errors.push(FieldError::new("passengers", format!("must be between 1 and {}", config.max_passengers)));
+    } else if req.children.saturating_add(req.infants) >= req.passengers {
+        errors.push(FieldError::new("passengers", "must include at least one adult"));
+    } else if req.infants > req.passengers - req.children - req.infants {
+        errors.push(FieldError::new("infants", "each infant needs an adult's lap"));
+    }
+    if let Some(cabin) = req.cabin_class.as_deref() {
+        if !CABIN_CLASSES.iter().any(|c| c.eq_ignore_ascii_case(cabin)) {
+            errors.push(FieldError::new("cabin_class", format!("must be one of {}", CABIN_CLASSES.join(", "))));
+        }
+    }
+    errors
+}
+
+/// Refuses the search with 422 if `check_search` finds anything. When the
+/// airline's routes can't be read, airports are only checked for format.
+pub async fn validate_search(state: &AppState, req: &SearchOffersRequest, airline_code: &str) -> Result<(), ApiError> {
+    let network = routes_of(state, airline_code).await.unwrap_or_else(|e| {
+        tracing::warn!("Searched airports not checked against the routes of {}: {:?}", airline_code, e);
+        None
+    });
+    let unrouted = RouteNetwork::default();
+    let errors = check_search(req, chrono::Utc::now().date_naive(), network.as_deref().unwrap_or(&unrouted), &state.search);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        tracing::debug!("Rejected search {}-{}: {:?}", req.origin, req.destination, errors);
+        Err(ApiError::Invalid(errors))
+    }
+}
+
+async fn routes_of(state: &AppState, airline_code: &str) -> Result<Option<Arc<RouteNetwork>>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(airline) = state.catalog_cache.airline(airline_code).await? else {
+        return Ok(None);
+    };
+    let airline_id = uuid::Uuid::parse_str(airline["id"].as_str().unwrap_or_default())?;
+    Ok(Some(state.catalog_cache.routes(airline_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(origin: &str, destination: &str) -> altis_catalog::Route {
+        altis_catalog::Route {
+            id: uuid::Uuid::new_v4(),
+            airline_id: uuid::Uuid::nil(),
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            origin_city: None,
+            destination_city: None,
+            origin_country: "SG".to_string(),
+            destination_country: "TH".to_string(),
+            distance_km: 1400,
+            market: altis_catalog::MarketType::International,
+            sale_currency: "SGD".to_string(),
+            is_active: true,
+        }
+    }
+
+    fn search(origin: &str, destination: &str, departure_date: &str) -> SearchOffersRequest {
+        SearchOffersRequest {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            departure_date: departure_date.to_string(),
+            return_date: None,
+            passengers: 1,
+            children: 0,
+            infants: 0,
+            cabin_class: None,
+            user_segment: None,
+            campaign: Default::default(),
+        }
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_check_search() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let network = RouteNetwork::new(vec![route("SIN", "BKK")]);
+        let config = SearchConfig::default();
+
+        assert!(check_search(&search("SIN", "bkk", "2026-03-10"), today, &network, &config).is_empty());
+        // A customer west of UTC may still be on the day before
+        assert!(check_search(&search("SIN", "BKK", "2026-02-28"), today, &network, &config).is_empty());
+
+        let errors = check_search(&search("SINGAPORE", "LHR", "2026-02-01"), today, &network, &config);
+        assert_eq!(fields(&errors), vec!["origin", "destination", "departure_date"]);
+        assert_eq!(errors[1].message, "no route serves LHR");
+
+        let mut req = search("SIN", "SIN", "01/03/2026");
+        req.return_date = Some("2026-02-01".to_string());
+        req.passengers = 10;
+        req.cabin_class = Some("Luxury".to_string());
+        let errors = check_search(&req, today, &network, &config);
+        assert_eq!(fields(&errors), vec!["destination", "departure_date", "passengers", "cabin_class"]);
+
+        let mut req = search("SIN", "BKK", "2026-03-10");
+        (req.passengers, req.children, req.infants) = (4, 1, 2);
+        assert_eq!(fields(&check_search(&req, today, &network, &config)), vec!["infants"]);
+        (req.passengers, req.children, req.infants) = (2, 1, 1);
+        assert_eq!(fields(&check_search(&req, today, &network, &config)), vec!["passengers"]);
+        (req.passengers, req.children, req.infants) = (3, 1, 1);
+        assert!(check_search(&req, today, &network, &config).is_empty());
+
+        let mut req = search("SIN", "BKK", "2026-03-10");
+        req.return_date = Some("2026-03-05".to_string());
+        assert_eq!(fields(&check_search(&req, today, &network, &config)), vec!["return_date"]);
+        let errors = check_search(&search("SIN", "BKK", "2027-03-10"), today, &network, &config);
+        assert_eq!(errors[0].message, "2027-03-10 is more than 361 days ahead");
+
+        // Without routes to check against, only the format counts
+        let unrouted = RouteNetwork::default();
+        assert!(check_search(&search("LHR", "JFK", "2026-03-10"), today, &unrouted, &config).is_empty());
+    }
+}
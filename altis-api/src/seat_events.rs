@@ -0,0 +1,261 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use prometheus::{IntCounter, IntGauge};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use altis_shared::models::events::SeatHeldEvent;
+use altis_store::app_config::SseConfig;
+
+/// A seat hold numbered within its flight's channel
+#[derive(Debug)]
+pub struct Sequenced {
+    pub seq: u64,
+    pub event: SeatHeldEvent,
+}
+
+/// What a subscriber is handed next
+#[derive(Debug, Clone)]
+pub enum Delivery {
+    Event(Arc<Sequenced>),
+    /// Events were missed and can't be replayed; the client should reload
+    /// the seat map. Carries the sequence it is current up to.
+    Resync(u64),
+}
+
+struct FlightChannel {
+    /// Identifies this channel in resume tokens; a channel dropped and
+    /// recreated for the same flight gets a new one
+    generation: u64,
+    sender: broadcast::Sender<Arc<Sequenced>>,
+    backlog: VecDeque<Arc<Sequenced>>,
+    last_seq: u64,
+    last_active: Instant,
+}
+
+/// Seat-hold fan-out with a channel per flight, so a busy flight can't push
+/// another's events out of a shared buffer. Each channel keeps its recent
+/// events: clients resume from `Last-Event-ID`, and a subscriber that falls
+/// behind the channel is caught up from them. Only when they have moved on
+/// too is it told to resync, and the events it missed are counted.
+pub struct SeatEventHub {
+    config: SseConfig,
+    flights: Mutex<HashMap<Uuid, FlightChannel>>,
+    generations: AtomicU64,
+    channels: IntGauge,
+    replayed: IntCounter,
+    dropped: IntCounter,
+}
+
+impl SeatEventHub {
+    pub fn new(config: SseConfig) -> Self {
+        let channels = IntGauge::new("altis_sse_flight_channels", "Flights with a live seat-hold channel")
+            .expect("valid gauge definition");
+        let replayed = IntCounter::new("altis_sse_replayed_events_total", "Seat-hold events delivered from the replay buffer after a lag or resume")
+            .expect("valid counter definition");
+        let dropped = IntCounter::new("altis_sse_dropped_events_total", "Seat-hold events subscribers missed and were told to resync over")
+            .expect("valid counter definition");
+
+        // Seeded from the clock so tokens from a previous run don't match this one's channels
+        let start = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        Self { config, flights: Mutex::new(HashMap::new()), generations: AtomicU64::new(start), channels, replayed, dropped }
+    }
+
+    pub fn metrics(&self) -> (&IntGauge, &IntCounter, &IntCounter) {
+        (&self.channels, &self.replayed, &self.dropped)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, FlightChannel>> {
+        self.flights.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn channel<'a>(&self, flights: &'a mut HashMap<Uuid, FlightChannel>, flight_id: Uuid) -> &'a mut FlightChannel {
+        // Idle channels are swept whenever a new one is needed
+        if !flights.contains_key(&flight_id) {
+            let idle = Duration::from_secs(self.config.idle_seconds);
+            flights.retain(|_, channel| channel.sender.receiver_count() > 0 || channel.last_active.elapsed() < idle);
+        }
+        let channel = flights.entry(flight_id).or_insert_with(|| FlightChannel {
+            generation: self.generations.fetch_add(1, Ordering::Relaxed),
+            sender: broadcast::channel(self.config.channel_capacity).0,
+            backlog: VecDeque::new(),
+            last_seq: 0,
+            last_active: Instant::now(),
+        });
+        channel.last_active = Instant::now();
+        channel
+    }
+
+    pub fn publish(&self, event: SeatHeldEvent) {
+        let mut flights = self.lock();
+        let channel = self.channel(&mut flights, event.flight_id);
+        channel.last_seq += 1;
+        let item = Arc::new(Sequenced { seq: channel.last_seq, event });
+        channel.backlog.push_back(item.clone());
+        if channel.backlog.len() > self.config.replay_events {
+            channel.backlog.pop_front();
+        }
+        // No subscribers is fine; the backlog still serves resumes
+        let _ = channel.sender.send(item);
+        let count = flights.len() as i64;
+        self.channels.set(count);
+    }
+
+    /// Subscribes to a flight, resuming after `last_event_id` when it's a
+    /// token this channel issued; unknown tokens start with a resync
+    pub fn subscribe(self: &Arc<Self>, flight_id: Uuid, last_event_id: Option<&str>) -> Subscription {
+        let mut flights = self.lock();
+        let channel = self.channel(&mut flights, flight_id);
+        let receiver = channel.sender.subscribe();
+        let generation = channel.generation;
+
+        let (last_seq, pending) = match last_event_id {
+            None => (channel.last_seq, VecDeque::new()),
+            Some(token) => match parse_token(token) {
+                Some((token_generation, seq)) if token_generation == generation && seq <= channel.last_seq => {
+                    (seq, self.catch_up(channel, seq))
+                }
+                _ => (channel.last_seq, VecDeque::from([Delivery::Resync(channel.last_seq)])),
+            },
+        };
+        let count = flights.len() as i64;
+        self.channels.set(count);
+
+        Subscription { hub: self.clone(), flight_id, generation, receiver, pending, last_seq }
+    }
+
+    /// Events after `seq` from the backlog, or a resync if some are gone
+    fn catch_up(&self, channel: &FlightChannel, seq: u64) -> VecDeque<Delivery> {
+        let oldest = channel.backlog.front().map_or(channel.last_seq + 1, |item| item.seq);
+        if oldest > seq + 1 {
+            self.dropped.inc_by(channel.last_seq - seq);
+            return VecDeque::from([Delivery::Resync(channel.last_seq)]);
+        }
+        let missed: VecDeque<_> = channel.backlog.iter()
+            .filter(|item| item.seq > seq)
+            .map(|item| Delivery::Event(item.clone()))
+            .collect();
+        self.replayed.inc_by(missed.len() as u64);
+        missed
+    }
+
+    fn catch_up_flight(&self, flight_id: Uuid, generation: u64, seq: u64) -> VecDeque<Delivery> {
+        let flights = self.lock();
+        match flights.get(&flight_id) {
+            Some(channel) if channel.generation == generation => self.catch_up(channel, seq),
+            _ => VecDeque::new(),
+        }
+    }
+}
+
+/// `Last-Event-ID` value for an event of a channel
+pub fn event_token(generation: u64, seq: u64) -> String {
+    format!("{}-{}", generation, seq)
+}
+
+fn parse_token(token: &str) -> Option<(u64, u64)> {
+    let (generation, seq) = token.trim().split_once('-')?;
+    Some((generation.parse().ok()?, seq.parse().ok()?))
+}
+
+/// One client's view of a flight channel
+pub struct Subscription {
+    hub: Arc<SeatEventHub>,
+    flight_id: Uuid,
+    generation: u64,
+    receiver: broadcast::Receiver<Arc<Sequenced>>,
+    pending: VecDeque<Delivery>,
+    last_seq: u64,
+}
+
+impl Subscription {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The next delivery, in sequence order and without repeats; None once
+    /// the channel is gone
+    pub async fn next(&mut self) -> Option<Delivery> {
+        loop {
+            if let Some(delivery) = self.pending.pop_front() {
+                self.last_seq = match &delivery {
+                    Delivery::Event(item) => item.seq,
+                    Delivery::Resync(seq) => *seq,
+                };
+                return Some(delivery);
+            }
+            match self.receiver.recv().await {
+                // Already replayed from the backlog
+                Ok(item) if item.seq <= self.last_seq => continue,
+                Ok(item) => {
+                    self.last_seq = item.seq;
+                    return Some(Delivery::Event(item));
+                }
+                Err(RecvError::Lagged(_)) => {
+                    self.pending = self.hub.catch_up_flight(self.flight_id, self.generation, self.last_seq);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hold(flight_id: Uuid, seat: &str) -> SeatHeldEvent {
+        SeatHeldEvent { flight_id, seat_number: seat.to_string(), trip_id: Uuid::new_v4(), held_at: 0 }
+    }
+
+    fn seat(delivery: Option<Delivery>) -> String {
+        match delivery {
+            Some(Delivery::Event(item)) => item.event.seat_number.clone(),
+            other => format!("{:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flights_are_isolated_and_laggards_catch_up_or_resync() {
+        let hub = Arc::new(SeatEventHub::new(SseConfig { channel_capacity: 2, replay_events: 4, idle_seconds: 300 }));
+        let (busy, quiet) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut quiet_sub = hub.subscribe(quiet, None);
+        let mut busy_sub = hub.subscribe(busy, None);
+
+        hub.publish(hold(quiet, "1A"));
+        for n in 0..3 {
+            hub.publish(hold(busy, &format!("{}C", n)));
+        }
+        // The busy flight overflowed its own channel, not the quiet one's
+        assert_eq!(seat(quiet_sub.next().await), "1A");
+        // Lagged past the channel, but the backlog still has everything
+        assert_eq!(seat(busy_sub.next().await), "0C");
+        assert_eq!(seat(busy_sub.next().await), "1C");
+        assert_eq!(seat(busy_sub.next().await), "2C");
+        assert_eq!(hub.metrics().1.get(), 3);
+
+        // A reconnect resumes after the last event it saw
+        let token = event_token(busy_sub.generation(), 1);
+        let mut resumed = hub.subscribe(busy, Some(&token));
+        assert_eq!(seat(resumed.next().await), "1C");
+        assert_eq!(seat(resumed.next().await), "2C");
+
+        // Once the backlog has moved past it, the client is told to resync
+        for n in 3..10 {
+            hub.publish(hold(busy, &format!("{}C", n)));
+        }
+        let mut stale = hub.subscribe(busy, Some(&token));
+        assert!(matches!(stale.next().await, Some(Delivery::Resync(10))));
+        assert_eq!(hub.metrics().2.get(), 9);
+        hub.publish(hold(busy, "10C"));
+        assert_eq!(seat(stale.next().await), "10C");
+
+        // Tokens from another channel or run start with a resync too
+        let mut foreign = hub.subscribe(busy, Some("1-3"));
+        assert!(matches!(foreign.next().await, Some(Delivery::Resync(11))));
+        assert_eq!(hub.metrics().0.get(), 2);
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use altis_order::seating::{assign_seats, SeatCategory, SeatMap, SeatPassenger};
+use altis_store::app_config::SeatingConfig;
+
+use crate::state::AppState;
+
+/// Seat items written by the engine carry this in `metadata.assignment`
+const AUTO: &str = "AUTO";
+
+fn is_active(item: &serde_json::Value, product_type: &str) -> bool {
+    item["product_type"].as_str() == Some(product_type) && item["status"].as_str() == Some("ACTIVE")
+}
+
+/// The booking's passengers still without a seat on the flight item, with
+/// the chargeable seats their order already includes (bought, or bundled
+/// with the fare) handed out in traveler order. Infants sit on a lap. A
+/// flight item priced for one passenger type only seats travelers of it.
+fn seat_passengers(order: &serde_json::Value, flight_item: &serde_json::Value) -> Vec<SeatPassenger> {
+    if crate::offers::is_lap_infant(&flight_item["metadata"]) {
+        return Vec::new();
+    }
+    let items = order["items"].as_array().cloned().unwrap_or_default();
+    let flight_id = flight_item["metadata"]["flight_id"].as_str();
+    let for_flight = |item: &serde_json::Value| item["metadata"]["flight_id"].as_str().is_none_or(|id| Some(id) == flight_id);
+    let seats = items.iter().filter(|item| is_active(item, "SEAT") && for_flight(item));
+
+    let seated: HashSet<i64> = seats.clone()
+        .filter(|item| item["metadata"]["seat_number"].is_string())
+        .filter_map(|item| item["traveler_index"].as_i64())
+        .collect();
+    let mut entitlements = seats
+        .filter(|item| !item["metadata"]["seat_number"].is_string())
+        .flat_map(|item| {
+            let category = SeatCategory::of_item(item["product_code"].as_str(), &item["metadata"]);
+            std::iter::repeat_n(category, item["quantity"].as_u64().unwrap_or(1) as usize)
+        })
+        .filter(|category| *category != SeatCategory::Standard);
+
+    let travelers = order["travelers"].as_array().cloned().unwrap_or_default();
+    let booked: Vec<(i64, bool)> = if travelers.is_empty() {
+        (0..flight_item["quantity"].as_i64().unwrap_or(1).max(1)).map(|index| (index, false)).collect()
+    } else {
+        let ptc = flight_item["metadata"]["ptc"].as_str();
+        travelers.iter()
+            .filter(|traveler| traveler["ptc"].as_str() != Some("INF"))
+            .filter(|traveler| ptc.is_none_or(|ptc| traveler["ptc"].as_str().is_some_and(|t| t.eq_ignore_ascii_case(ptc))))
+            .map(|traveler| (traveler["traveler_index"].as_i64().unwrap_or(0), traveler["ptc"].as_str() == Some("CHD")))
+            .collect()
+    };
+    booked.into_iter()
+        .filter(|(index, _)| !seated.contains(index))
+        .map(|(index, child)| SeatPassenger { traveler_index: index as i32, child, entitlement: entitlements.next() })
+        .collect()
+}
+
+/// Seats the passengers on one booked flight who haven't chosen a seat,
+/// each as a SEAT item at no charge. The flight item is marked as done
+/// either way, so a flight with no seat map isn't tried again.
+async fn assign_flight(state: &AppState, order: &serde_json::Value, flight_item: &serde_json::Value) {
+    let order_id = order["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).unwrap_or_default();
+    let Some(item_id) = flight_item["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { return };
+    let flight_id = flight_item["metadata"]["flight_id"].as_str().unwrap_or_default();
+
+    let passengers = seat_passengers(order, flight_item);
+    let map = match Uuid::parse_str(flight_id).ok() {
+        Some(id) => state.catalog_repo.get_product(id).await.ok().flatten().and_then(|flight| SeatMap::from_flight(&flight["metadata"])),
+        None => None,
+    };
+    let mut seated = Vec::new();
+    match map {
+        Some(map) if !passengers.is_empty() => {
+            let taken: HashSet<String> = match state.order_repo.seats_taken(flight_id).await {
+                Ok(seats) => seats.into_iter().collect(),
+                Err(e) => {
+                    tracing::error!("Failed to load seats taken on flight {}: {:?}", flight_id, e);
+                    return;
+                }
+            };
+            for assignment in assign_seats(&map, &taken, &passengers) {
+                let seat = serde_json::json!({
+                    "product_type": "SEAT",
+                    "product_code": "SEAT_AUTO",
+                    "name": format!("Seat {}", assignment.seat_number),
+                    "price_nuc": 0,
+                    "quantity": 1,
+                    "traveler_index": assignment.traveler_index,
+                    "metadata": {
+                        "flight_id": flight_id,
+                        "flight_item_id": item_id,
+                        "traveler_index": assignment.traveler_index,
+                        "seat_number": assignment.seat_number,
+                        "seat_category": assignment.category,
+                        "assignment": AUTO,
+                    },
+                });
+                // Taken by someone else since the seat map was read
+                match state.order_repo.add_order_item(order_id, &seat).await {
+                    Ok(_) => seated.push(assignment),
+                    Err(e) => tracing::warn!("Seat {} on flight {} not assigned to order {}: {:?}", assignment.seat_number, flight_id, order_id, e),
+                }
+            }
+        }
+        Some(_) => {}
+        None => tracing::debug!("Flight {} has no seat map; order {} left for seating at the airport", flight_id, order_id),
+    }
+
+    if !seated.is_empty() {
+        let _ = state.order_repo.add_order_change(
+            order_id,
+            "SEATS_ASSIGNED",
+            None,
+            Some(serde_json::json!({
+                "flight_item_id": item_id,
+                "seats": seated,
+                "unseated": passengers.len() - seated.len(),
+            })),
+            "SYSTEM",
+            Some("Seats assigned to passengers who hadn't chosen one"),
+        ).await;
+    }
+    if let Err(e) = state.order_repo.merge_item_metadata(item_id, &serde_json::json!({"seats_assigned_at": chrono::Utc::now()})).await {
+        tracing::error!("Failed to mark seats assigned on item {} of order {}: {:?}", item_id, order_id, e);
+    }
+}
+
+/// Seats the order's passengers on each booked flight not seated yet; run
+/// at check-in, ahead of the worker
+pub(crate) async fn assign_order_seats(state: &AppState, order: &serde_json::Value) {
+    let items = order["items"].as_array().cloned().unwrap_or_default();
+    for item in items.iter().filter(|item| is_active(item, "FLIGHT") && item["metadata"]["seats_assigned_at"].is_null()) {
+        assign_flight(state, order, item).await;
+    }
+}
+
+/// Background loop seating passengers who didn't choose a seat, once their
+/// flight is within `seating.assign_hours_before` of departure
+pub async fn run_seat_assignment_worker(state: AppState, config: SeatingConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let until = chrono::Utc::now() + chrono::Duration::hours(config.assign_hours_before);
+        let due = match state.order_repo.due_seat_assignments(until, config.batch_size).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to find bookings due for seats: {:?}", e);
+                continue;
+            }
+        };
+        for booking in &due {
+            let Some(order_id) = booking["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+            let order = match state.order_repo.get_order(order_id).await {
+                Ok(Some(order)) => order,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::error!("Failed to load order {} for seat assignment: {:?}", order_id, e);
+                    continue;
+                }
+            };
+            let items = order["items"].as_array().cloned().unwrap_or_default();
+            if let Some(item) = items.iter().find(|item| item["id"] == booking["order_item_id"]) {
+                assign_flight(&state, &order, item).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seat_passengers() {
+        let flight_id = Uuid::new_v4().to_string();
+        let flight = serde_json::json!({"id": Uuid::new_v4(), "product_type": "FLIGHT", "status": "ACTIVE", "quantity": 3, "metadata": {"flight_id": flight_id}});
+        let seat = |code: &str, traveler_index: Option<i32>, metadata: serde_json::Value| serde_json::json!({
+            "product_type": "SEAT", "product_code": code, "status": "ACTIVE", "quantity": 1, "traveler_index": traveler_index, "metadata": metadata,
+        });
+        let traveler = |index: i32, ptc: &str| serde_json::json!({"traveler_index": index, "ptc": ptc});
+
+        let order = serde_json::json!({
+            "items": [
+                flight,
+                seat("SEAT_XL", None, serde_json::json!({})),
+                seat("SEAT_STD", Some(1), serde_json::json!({"flight_id": flight_id, "seat_number": "12C"})),
+                seat("SEAT_XL", None, serde_json::json!({"flight_id": Uuid::new_v4()})),
+            ],
+            "travelers": [traveler(0, "ADT"), traveler(1, "ADT"), traveler(2, "CHD"), traveler(3, "INF")],
+        });
+        let passengers = seat_passengers(&order, &order["items"][0]);
+        assert_eq!(passengers, vec![
+            SeatPassenger { traveler_index: 0, child: false, entitlement: Some(SeatCategory::ExtraLegroom) },
+            SeatPassenger { traveler_index: 2, child: true, entitlement: None },
+        ]);
+
+        // Without names yet, each booked seat is an adult
+        let unnamed = serde_json::json!({"items": [order["items"][0].clone()]});
+        assert_eq!(seat_passengers(&unnamed, &unnamed["items"][0]).len(), 3);
+    }
+}
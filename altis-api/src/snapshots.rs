@@ -0,0 +1,153 @@
+use std::collections::BTreeSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use altis_core::tenant::TenantContext;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::authz::authorize_tenant_order;
+use crate::state::AppState;
+
+/// Product versions and campaigns the offers' items were priced from
+fn priced_from(offers: &[Value]) -> (Vec<Value>, Vec<Value>) {
+    let items = offers.iter().flat_map(|offer| offer["items"].as_array().into_iter().flatten());
+    let mut versions = Vec::new();
+    let mut campaigns = Vec::new();
+    for item in items {
+        versions.push(serde_json::json!({
+            "item_id": item["id"],
+            "product_id": item["product_id"],
+            "product_code": item["product_code"],
+            "version": item["metadata"]["product_version"],
+        }));
+        let campaign = &item["metadata"]["campaign"];
+        if !campaign.is_null() && !campaigns.contains(campaign) {
+            campaigns.push(campaign.clone());
+        }
+    }
+    (versions, campaigns)
+}
+
+/// SHA-256 of the snapshot's content. `Value` keeps object keys sorted, so
+/// the digest comes out the same after a round trip through JSONB.
+fn digest(snapshot: &Value) -> String {
+    let content = serde_json::json!({
+        "offers": snapshot["offers"],
+        "pricing": snapshot["pricing"],
+        "business_rules": snapshot["business_rules"],
+    });
+    format!("{:x}", Sha256::digest(content.to_string().as_bytes()))
+}
+
+fn build_snapshot(offers: &[Value], pricing_rules: Value, business_rules: Value) -> Value {
+    let (product_versions, campaigns) = priced_from(offers);
+    let mut snapshot = serde_json::json!({
+        "offers": offers,
+        "pricing": {
+            "product_versions": product_versions,
+            "pricing_rules": pricing_rules,
+            "campaigns": campaigns,
+        },
+        "business_rules": business_rules,
+    });
+    snapshot["sha256"] = digest(&snapshot).into();
+    snapshot
+}
+
+/// Freezes what a just-created order was sold on: the accepted offers, the
+/// pricing rules and product versions behind them, and the business and
+/// inventory rules in force. A failure is logged and the order goes ahead
+/// without a snapshot.
+pub(crate) async fn record_order_snapshot(state: &AppState, order_id: Uuid, offers: &[Value]) {
+    let airlines: BTreeSet<Uuid> = offers.iter()
+        .filter_map(|offer| offer["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+        .collect();
+
+    let mut pricing_rules = serde_json::Map::new();
+    let mut inventory_rules = serde_json::Map::new();
+    let mut airline_config = serde_json::Map::new();
+    for airline_id in airlines {
+        let rules = tokio::try_join!(
+            state.catalog_cache.pricing_rules(airline_id),
+            state.catalog_cache.inventory_rules(airline_id),
+            state.catalog_cache.business_rules(airline_id, &state.business_rules),
+        );
+        match rules {
+            Ok((pricing, inventory, config)) => {
+                pricing_rules.insert(airline_id.to_string(), serde_json::json!(*pricing));
+                inventory_rules.insert(airline_id.to_string(), serde_json::json!(*inventory));
+                airline_config.insert(airline_id.to_string(), serde_json::json!(*config));
+            }
+            Err(e) => {
+                tracing::error!("Failed to load rules of airline {} to snapshot order {}: {:?}", airline_id, order_id, e);
+                return;
+            }
+        }
+    }
+
+    let business_rules = serde_json::json!({
+        "config": state.business_rules,
+        "airline_config": airline_config,
+        "inventory_rules": inventory_rules,
+    });
+    let snapshot = build_snapshot(offers, pricing_rules.into(), business_rules);
+    match state.order_repo.save_order_snapshot(order_id, &snapshot).await {
+        Ok(true) => {}
+        Ok(false) => tracing::warn!("Order {} already has a snapshot; kept the first", order_id),
+        Err(e) => tracing::error!("Failed to snapshot order {}: {:?}", order_id, e),
+    }
+}
+
+/// GET /v1/admin/orders/:id/snapshot
+/// What the order was sold on, frozen at acceptance; `intact` is false if the stored content no longer matches its digest
+pub async fn get_order_snapshot(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<Value>, StatusCode> {
+    authorize_tenant_order(&state, &tenant, order_id).await?;
+    let mut snapshot = state.order_repo.get_order_snapshot(order_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to read snapshot of order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    snapshot["intact"] = (snapshot["sha256"].as_str() == Some(digest(&snapshot).as_str())).into();
+    Ok(Json(snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_records_pricing_inputs_and_detects_tampering() {
+        let campaign = serde_json::json!({ "id": "c-1", "name": "Year-end sale", "discount_percentage": 15.0 });
+        let offer = serde_json::json!({
+            "id": "o-1",
+            "total_nuc": 27000,
+            "items": [
+                { "id": "i-1", "product_id": "p-1", "product_code": "SIN-BKK", "price_nuc": 25000, "metadata": { "product_version": 3 } },
+                { "id": "i-2", "product_id": "p-2", "product_code": "BAG_20", "price_nuc": 2000, "metadata": { "product_version": 1, "campaign": campaign } },
+            ],
+        });
+        let snapshot = build_snapshot(&[offer], serde_json::json!({}), serde_json::json!({ "config": { "tax_rate": 0.1 } }));
+
+        let versions = snapshot["pricing"]["product_versions"].as_array().unwrap();
+        assert_eq!(versions.iter().map(|v| v["version"].as_i64()).collect::<Vec<_>>(), vec![Some(3), Some(1)]);
+        assert_eq!(snapshot["pricing"]["campaigns"], serde_json::json!([campaign]));
+
+        // Reading it back from JSONB gives the same digest
+        let stored: Value = serde_json::from_str(&snapshot.to_string()).unwrap();
+        assert_eq!(stored["sha256"].as_str(), Some(digest(&stored).as_str()));
+
+        let mut tampered = stored;
+        tampered["offers"][0]["total_nuc"] = 1.into();
+        assert_ne!(tampered["sha256"].as_str(), Some(digest(&tampered).as_str()));
+    }
+}
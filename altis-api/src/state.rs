@@ -1,16 +1,19 @@
 use std::sync::Arc;
-use altis_store::{RedisClient, EventProducer};
+use altis_store::{DbClient, RedisClient, EventProducer, InventoryManager, SearchCache};
 use crate::middleware::resiliency::CircuitBreaker;
-use tokio::sync::{broadcast, Mutex};
-use altis_shared::models::events::SeatHeldEvent;
-use altis_core::repository::{OfferRepository, OrderRepository, ProductRepository};
+use crate::middleware::key_cache::AuthKeyCache;
+use altis_core::repository::{AnalyticsRepository, ArchiveRepository, AttributionRepository, BaggageRepository, BulkRefundRepository, CartRepository, CustomerFeatureRepository, DisputeRepository, DisruptionRepository, DocumentRepository, EmailRepository, ExperimentRepository, LedgerRepository, LowFareRepository, MessageRepository, NoteRepository, OfferRepository, OrderRepository, PaymentMethodRepository, PaymentScheduleRepository, PriceWatchRepository, ProductRepository, ProfileRepository, SettlementRepository, UpsellRepository, WaitlistRepository, WebhookDeliveryRepository};
 use altis_offer::ai_ranker::OfferRanker;
 use altis_offer::events::OfferTelemetry;
 
 #[derive(Clone)]
 pub struct AuthConfig {
-    pub secret: String,
+    pub keys: Arc<AuthKeyCache>,
     pub expiration: u64,
+    pub qr_grant_ttl: u64,
+    pub service_keys: Arc<crate::middleware::service_auth::ServiceKeys>,
+    /// Signs the passes scanners verify offline; None when not configured
+    pub scan_signer: Option<Arc<crate::scanning::ScanSigner>>,
 }
 
 pub struct ResiliencyState {
@@ -20,18 +23,104 @@ pub struct ResiliencyState {
 
 #[derive(Clone)]
 pub struct AppState {
+    pub db: DbClient,
     pub redis: Arc<RedisClient>,
     pub kafka: Arc<EventProducer>,
-    pub sse_tx: broadcast::Sender<SeatHeldEvent>,
+    /// Identifies this process among the API instances, e.g. in catalog change events
+    pub instance_id: uuid::Uuid,
+    pub seat_events: Arc<crate::seat_events::SeatEventHub>,
     pub auth: AuthConfig,
+    /// Global business rules; `business_rules_for` adds an airline's overrides
     pub business_rules: altis_store::app_config::BusinessRules,
+    pub refunds: altis_store::app_config::RefundsConfig,
+    pub analytics: altis_store::app_config::AnalyticsConfig,
+    pub attribution: altis_store::app_config::AttributionConfig,
+    pub price_watch: altis_store::app_config::PriceWatchConfig,
+    pub low_fares: altis_store::app_config::LowFaresConfig,
+    pub cart: altis_store::app_config::CartConfig,
+    pub reaccommodation: altis_store::app_config::ReaccommodationConfig,
+    pub compensation: altis_store::app_config::CompensationConfig,
+    pub deadlines: altis_store::app_config::DeadlinesConfig,
+    pub search: altis_store::app_config::SearchConfig,
+    pub sandbox: altis_store::app_config::SandboxConfig,
+    pub installments: altis_store::app_config::InstallmentsConfig,
+    pub upsell: altis_store::app_config::UpsellConfig,
+    pub markets: altis_store::app_config::MarketsConfig,
+    pub schedule_changes: altis_store::app_config::ScheduleChangeConfig,
+    pub waitlist: altis_store::app_config::WaitlistConfig,
+    pub payment: altis_store::app_config::PaymentConfig,
+    pub scanning: altis_store::app_config::ScanningConfig,
     pub offer_repo: Arc<dyn OfferRepository>,
     pub order_repo: Arc<dyn OrderRepository>,
     pub catalog_repo: Arc<dyn ProductRepository>,
+    pub settlement_repo: Arc<dyn SettlementRepository>,
+    pub document_repo: Arc<dyn DocumentRepository>,
+    pub ledger_repo: Arc<dyn LedgerRepository>,
+    pub bulk_refund_repo: Arc<dyn BulkRefundRepository>,
+    pub experiment_repo: Arc<dyn ExperimentRepository>,
+    pub customer_feature_repo: Arc<dyn CustomerFeatureRepository>,
+    pub attribution_repo: Arc<dyn AttributionRepository>,
+    pub price_watch_repo: Arc<dyn PriceWatchRepository>,
+    pub webhook_delivery_repo: Arc<dyn WebhookDeliveryRepository>,
+    pub cart_repo: Arc<dyn CartRepository>,
+    pub baggage_repo: Arc<dyn BaggageRepository>,
+    pub disruption_repo: Arc<dyn DisruptionRepository>,
+    pub low_fare_repo: Arc<dyn LowFareRepository>,
+    pub payment_schedule_repo: Arc<dyn PaymentScheduleRepository>,
+    pub payment_method_repo: Arc<dyn PaymentMethodRepository>,
+    pub profile_repo: Arc<dyn ProfileRepository>,
+    pub note_repo: Arc<dyn NoteRepository>,
+    pub dispute_repo: Arc<dyn DisputeRepository>,
+    pub analytics_repo: Arc<dyn AnalyticsRepository>,
+    pub email_repo: Arc<dyn EmailRepository>,
+    pub message_repo: Arc<dyn MessageRepository>,
+    pub upsell_repo: Arc<dyn UpsellRepository>,
+    pub waitlist_repo: Arc<dyn WaitlistRepository>,
+    pub archive_repo: Arc<dyn ArchiveRepository>,
+    pub blob_store: Arc<dyn altis_core::blob::BlobStore>,
+    pub email_sender: Arc<dyn altis_core::email::EmailSender>,
+    pub pii_policy: Arc<altis_shared::pii::MaskingPolicy>,
     pub telemetry: Arc<OfferTelemetry>,
-    pub ranker: Arc<Mutex<OfferRanker>>,
+    pub ranker: Arc<OfferRanker>,
+    pub search_cache: Arc<SearchCache>,
+    pub search_admission: Arc<crate::middleware::admission::SearchAdmission>,
+    pub inventory: Arc<InventoryManager>,
+    pub catalog_cache: Arc<crate::catalog_cache::CatalogCache>,
+    pub webhooks: Arc<crate::partner_webhooks::WebhookSender>,
+    pub alerts: Arc<crate::travel_alerts::TravelAlerts>,
+    pub edifact: Arc<crate::edifact::EdifactGateway>,
+    pub suppliers: Arc<crate::suppliers::SupplierRegistry>,
+    pub interline: Arc<crate::interline::InterlineGateway>,
+    pub warmup: Arc<crate::warmup::Warmup>,
     pub payment_orchestrator: Arc<altis_order::orchestrator::PaymentOrchestrator>,
+    /// Where test orders' payments and refunds go
+    pub sandbox_payments: Arc<altis_order::orchestrator::PaymentOrchestrator>,
     pub one_id_resolver: Arc<dyn altis_core::identity::OneIdResolver>,
     pub resiliency: Arc<ResiliencyState>,
+    pub chaos: Arc<altis_store::ChaosInjector>,
     pub api_base_url: String, // Dynamic base URL for QR codes, etc.
 }
+
+impl AppState {
+    /// The payment orchestrator for a live or a test order
+    pub fn payments(&self, test: bool) -> &altis_order::orchestrator::PaymentOrchestrator {
+        if test { &self.sandbox_payments } else { &self.payment_orchestrator }
+    }
+
+    /// The business rules an airline's bookings follow: `business_rules`
+    /// with the airline's overrides, or without them if they can't be read
+    pub async fn business_rules_for(&self, airline_id: uuid::Uuid) -> Arc<altis_store::app_config::BusinessRules> {
+        self.catalog_cache.business_rules(airline_id, &self.business_rules).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load business rules of airline {}, using the global ones: {:?}", airline_id, e);
+            Arc::new(self.business_rules.clone())
+        })
+    }
+
+    /// Drops cached searches and this instance's catalog after a catalog
+    /// write, and announces it so the other instances drop theirs
+    pub async fn catalog_changed(&self, reason: &str) {
+        self.search_cache.invalidate().await;
+        self.catalog_cache.invalidate();
+        crate::catalog_cache::announce_change(self, reason);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use altis_core::supplier::{
+    SupplierAdapter, SupplierAvailability, SupplierBooking, SupplierBookingRequest, SupplierError, SupplierProduct,
+};
+use altis_store::app_config::SupplierConfig;
+use altis_store::circuit_breaker::CircuitBreaker;
+
+use crate::orders::OrderItemResponse;
+use crate::state::AppState;
+
+// ============================================================================
+// HTTP Adapter
+// ============================================================================
+
+/// A supplier exposing the generic REST contract: `POST /availability`,
+/// `POST /bookings` (idempotent on our item id) and `DELETE /bookings/{reference}`.
+/// Insurance and lounge aggregators are integrated this way.
+pub struct HttpSupplierAdapter {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl HttpSupplierAdapter {
+    pub fn new(config: &SupplierConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .expect("supplier HTTP client builds with static settings");
+        Self { client, base_url: config.base_url.trim_end_matches('/').to_string(), api_key: config.api_key.clone() }
+    }
+
+    fn post(&self, path: &str, body: &serde_json::Value) -> reqwest::RequestBuilder {
+        self.client.post(format!("{}/{}", self.base_url, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+    }
+
+    /// Sends the request, sorting failed responses into outages and refusals
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, SupplierError> {
+        let response = request.bearer_auth(&self.api_key).send().await
+            .map_err(|e| SupplierError::Unavailable(e.to_string()))?;
+        let status = response.status();
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SupplierError::Unavailable(format!("HTTP {}", status)));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(SupplierError::Rejected(format!("HTTP {}: {}", status, body.chars().take(200).collect::<String>())));
+        }
+        Ok(response)
+    }
+
+    async fn call<T: serde::de::DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T, SupplierError> {
+        let body = self.send(request).await?
+            .bytes()
+            .await
+            .map_err(|e| SupplierError::Unavailable(e.to_string()))?;
+        serde_json::from_slice(&body).map_err(|e| SupplierError::Unavailable(format!("unreadable response: {}", e)))
+    }
+}
+
+#[async_trait]
+impl SupplierAdapter for HttpSupplierAdapter {
+    async fn check_availability(
+        &self,
+        supplier_product_id: &str,
+        quantity: i32,
+        context: &serde_json::Value,
+    ) -> Result<SupplierAvailability, SupplierError> {
+        let body = serde_json::json!({
+            "product_id": supplier_product_id,
+            "quantity": quantity,
+            "context": context,
+        });
+        self.call(self.post("availability", &body)).await
+    }
+
+    async fn book(&self, request: &SupplierBookingRequest) -> Result<SupplierBooking, SupplierError> {
+        let body = serde_json::to_value(request).map_err(|e| SupplierError::Rejected(e.to_string()))?;
+        self.call(self.post("bookings", &body).header("Idempotency-Key", request.item_id.to_string())).await
+    }
+
+    async fn cancel(&self, reference: &str) -> Result<(), SupplierError> {
+        self.send(self.client.delete(format!("{}/bookings/{}", self.base_url, reference))).await?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Configured suppliers, each behind its own circuit breaker so one slow
+/// provider can't hold up search or payment for the rest
+pub struct SupplierRegistry {
+    suppliers: HashMap<String, (Arc<dyn SupplierAdapter>, CircuitBreaker)>,
+}
+
+impl SupplierRegistry {
+    pub fn new(configs: &HashMap<String, SupplierConfig>) -> Self {
+        let suppliers = configs.iter().map(|(name, config)| {
+            let adapter: Arc<dyn SupplierAdapter> = Arc::new(HttpSupplierAdapter::new(config));
+            let breaker = CircuitBreaker::new(&format!("Supplier:{}", name), config.breaker_threshold, Duration::from_secs(config.breaker_reset_seconds));
+            (name.clone(), (adapter, breaker))
+        }).collect();
+        Self { suppliers }
+    }
+
+    /// Runs one call through the supplier's breaker. Refusals are answers,
+    /// so only outages count as failures.
+    async fn guarded<T, F>(&self, supplier: &str, call: impl FnOnce(Arc<dyn SupplierAdapter>) -> F) -> Result<T, SupplierError>
+    where
+        F: std::future::Future<Output = Result<T, SupplierError>>,
+    {
+        let (adapter, breaker) = self.suppliers.get(supplier)
+            .ok_or_else(|| SupplierError::Unavailable(format!("supplier '{}' is not configured", supplier)))?;
+        if !breaker.check().await {
+            return Err(SupplierError::Unavailable(format!("circuit for supplier '{}' is open", supplier)));
+        }
+        let result = call(adapter.clone()).await;
+        match &result {
+            Err(SupplierError::Unavailable(_)) => breaker.record_failure().await,
+            _ => breaker.record_success().await,
+        }
+        result
+    }
+
+    /// Whether the supplier will sell the product now; anything short of a
+    /// yes keeps it out of offers
+    pub async fn is_available(&self, product: &SupplierProduct, quantity: i32, context: &serde_json::Value) -> bool {
+        let result = self.guarded(&product.supplier, |adapter| async move {
+            adapter.check_availability(&product.supplier_product_id, quantity, context).await
+        }).await;
+        match result {
+            Ok(availability) => availability.available,
+            Err(e) => {
+                tracing::warn!("Skipping {} product {}: {}", product.supplier, product.supplier_product_id, e);
+                false
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Order Confirmation
+// ============================================================================
+
+/// Books supplier-sourced items with their suppliers once the order is paid,
+/// recording the supplier's reference on the item. Items already booked are
+/// skipped, so a repeated payment notification books nothing twice. Failures
+/// are marked on the item for follow-up rather than failing the payment.
+pub(crate) async fn confirm_supplier_items(state: &AppState, order_id: Uuid, items: &[OrderItemResponse]) {
+    for item in items {
+        let Some(product) = SupplierProduct::from_metadata(&item.metadata) else { continue };
+        if item.metadata["supplier_reference"].is_string() {
+            continue;
+        }
+
+        let request = SupplierBookingRequest {
+            item_id: item.id,
+            order_id,
+            supplier_product_id: product.supplier_product_id.clone(),
+            quantity: item.quantity.unwrap_or(1),
+            context: item.metadata.clone(),
+        };
+        let patch = match state.suppliers.guarded(&product.supplier, |adapter| async move { adapter.book(&request).await }).await {
+            Ok(booking) => {
+                tracing::info!("Booked item {} with {} as {}", item.id, product.supplier, booking.reference);
+                serde_json::json!({"supplier_reference": booking.reference, "supplier_status": booking.status})
+            }
+            Err(e) => {
+                tracing::error!("Failed to book item {} of order {} with {}: {}", item.id, order_id, product.supplier, e);
+                serde_json::json!({"supplier_status": "FAILED", "supplier_error": e.to_string()})
+            }
+        };
+        if let Err(e) = state.order_repo.merge_item_metadata(item.id, &patch).await {
+            tracing::error!("Failed to record supplier booking on item {}: {:?}", item.id, e);
+        }
+    }
+}
+
+/// Cancels a cancelled order's supplier bookings
+pub(crate) async fn cancel_supplier_items(state: &AppState, items: &[OrderItemResponse]) {
+    for item in items {
+        let Some(product) = SupplierProduct::from_metadata(&item.metadata) else { continue };
+        let Some(reference) = item.metadata["supplier_reference"].as_str() else { continue };
+
+        let status = match state.suppliers.guarded(&product.supplier, |adapter| async move { adapter.cancel(reference).await }).await {
+            Ok(()) => "CANCELLED".to_string(),
+            Err(e) => {
+                tracing::error!("Failed to cancel {} booking {} for item {}: {}", product.supplier, reference, item.id, e);
+                "CANCEL_FAILED".to_string()
+            }
+        };
+        let _ = state.order_repo.merge_item_metadata(item.id, &serde_json::json!({"supplier_status": status})).await;
+    }
+}
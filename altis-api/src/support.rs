@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+    Json,
+};
+use altis_core::tenant::TenantContext;
+use altis_shared::pii::{MaskingTier, PiiField};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authz::authorize_tenant_order;
+use crate::middleware::auth::{has_permission, AdminClaims};
+use crate::orders::OrderResponse;
+use crate::state::AppState;
+
+/// Permission needed to see fields the caller's tier masks.
+pub const UNMASK_PERMISSION: &str = "pii:unmask";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupportOrderResponse {
+    #[serde(flatten)]
+    pub order: OrderResponse,
+    pub payment_method: Option<String>,
+    #[serde(serialize_with = "altis_shared::pii::payment_reference")]
+    pub payment_reference: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnmaskRequest {
+    /// Why the agent needs the data; stored in the audit trail
+    pub reason: String,
+    /// Fields to reveal; all fields when omitted
+    pub fields: Option<Vec<PiiField>>,
+}
+
+async fn load_order(state: &AppState, tenant: &TenantContext, order_id: Uuid) -> Result<SupportOrderResponse, StatusCode> {
+    let order_json = authorize_tenant_order(state, tenant, order_id).await?;
+
+    serde_json::from_value(order_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn render(order: &SupportOrderResponse, tier: &MaskingTier) -> Result<Json<serde_json::Value>, StatusCode> {
+    altis_shared::pii::with_masking(tier, || serde_json::to_value(order))
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /v1/admin/orders/:id
+/// Order as seen by back-office staff, masked per the caller's role
+pub async fn get_order(
+    State(state): State<AppState>,
+    Extension(claims): Extension<AdminClaims>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let order = load_order(&state, &tenant, order_id).await?;
+    render(&order, &state.pii_policy.tier(&claims.role))
+}
+
+/// POST /v1/admin/orders/:id/unmask
+/// Reveal masked fields of one order; requires `pii:unmask` and is audited
+pub async fn unmask_order(
+    State(state): State<AppState>,
+    Extension(claims): Extension<AdminClaims>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+    Json(req): Json<UnmaskRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    if !has_permission(&claims, UNMASK_PERMISSION) {
+        tracing::warn!("{} ({}) denied unmask on order {}", claims.sub, claims.role, order_id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if req.reason.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let order = load_order(&state, &tenant, order_id).await?;
+    let fields = req.fields.unwrap_or_else(|| PiiField::ALL.to_vec());
+
+    // No audit record, no data
+    state.order_repo.add_order_change(
+        order_id,
+        "PII_UNMASKED",
+        None,
+        Some(serde_json::json!({ "fields": fields, "role": claims.role })),
+        &claims.sub,
+        Some(&req.reason),
+    ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    render(&order, &state.pii_policy.tier(&claims.role).reveal(&fields))
+}
@@ -0,0 +1,446 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_core::messaging::{normalize_phone, AlertChannel, MessageSender, MessagingError, TextMessage};
+use altis_core::tenant::TenantContext;
+use altis_order::schedule_change::{ScheduleChange, ScheduleChangeSeverity};
+use altis_store::app_config::MessagingConfig;
+
+use crate::admin::TriggerDisruptionRequest;
+use crate::authz::authorize_tenant_order;
+use crate::middleware::auth::AdminClaims;
+use crate::state::AppState;
+
+// ============================================================================
+// Providers
+// ============================================================================
+
+/// SMS and WhatsApp through Twilio's Messages API; WhatsApp numbers are the
+/// same E.164 numbers with a `whatsapp:` prefix
+pub struct TwilioSender {
+    client: reqwest::Client,
+    messages_url: String,
+    account_sid: String,
+    auth_token: String,
+    sms_from: String,
+    whatsapp_from: Option<String>,
+}
+
+impl TwilioSender {
+    pub fn new(config: &MessagingConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .expect("messaging HTTP client builds with static settings");
+        Self {
+            client,
+            messages_url: format!("{}/Accounts/{}/Messages.json", config.api_base_url.trim_end_matches('/'), config.account_sid),
+            account_sid: config.account_sid.clone(),
+            auth_token: config.auth_token.clone(),
+            sms_from: config.sms_from.clone(),
+            whatsapp_from: config.whatsapp_from.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl MessageSender for TwilioSender {
+    fn supports(&self, channel: AlertChannel) -> bool {
+        match channel {
+            AlertChannel::Sms => true,
+            AlertChannel::Whatsapp => self.whatsapp_from.is_some(),
+            AlertChannel::Email => false,
+        }
+    }
+
+    async fn send(&self, message: &TextMessage) -> Result<String, MessagingError> {
+        let (from, to) = match (message.channel, &self.whatsapp_from) {
+            (AlertChannel::Sms, _) => (self.sms_from.clone(), message.to.clone()),
+            (AlertChannel::Whatsapp, Some(from)) => (format!("whatsapp:{}", from), format!("whatsapp:{}", message.to)),
+            (channel, _) => return Err(MessagingError::Rejected(format!("{} is not configured", channel.as_str()))),
+        };
+        let response = self.client.post(&self.messages_url)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[("From", from.as_str()), ("To", to.as_str()), ("Body", message.body.as_str())])
+            .send()
+            .await
+            .map_err(|e| MessagingError::Unavailable(e.to_string()))?;
+
+        let status = response.status();
+        let body = response.bytes().await.map_err(|e| MessagingError::Unavailable(e.to_string()))?;
+        let reply: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(MessagingError::Unavailable(format!("HTTP {}", status)));
+        }
+        if !status.is_success() {
+            return Err(MessagingError::Rejected(format!("HTTP {}: {}", status, reply["message"].as_str().unwrap_or_default())));
+        }
+        reply["sid"].as_str().map(str::to_string)
+            .ok_or_else(|| MessagingError::Unavailable("response without a message sid".to_string()))
+    }
+}
+
+/// Writes messages to the log instead of sending them, for development
+pub struct LogMessageSender;
+
+#[async_trait]
+impl MessageSender for LogMessageSender {
+    fn supports(&self, channel: AlertChannel) -> bool {
+        channel.is_text()
+    }
+
+    async fn send(&self, message: &TextMessage) -> Result<String, MessagingError> {
+        tracing::info!("{} alert not sent (log provider): {}", message.channel.as_str(), message.body);
+        Ok(format!("log-{}", Uuid::new_v4()))
+    }
+}
+
+/// The configured provider, and the channels customers get by default
+pub struct TravelAlerts {
+    sender: Arc<dyn MessageSender>,
+    default_channels: Vec<AlertChannel>,
+}
+
+impl TravelAlerts {
+    pub fn new(config: &MessagingConfig) -> Self {
+        let sender: Arc<dyn MessageSender> = match config.provider.as_str() {
+            "twilio" => Arc::new(TwilioSender::new(config)),
+            _ => Arc::new(LogMessageSender),
+        };
+        Self { sender, default_channels: parse_channels(&config.default_channels) }
+    }
+
+    /// Whether alerts can go out on the channel; email always can
+    pub fn supports(&self, channel: AlertChannel) -> bool {
+        channel == AlertChannel::Email || self.sender.supports(channel)
+    }
+}
+
+/// Channel names as stored and configured; unknown ones are dropped
+fn parse_channels<S: AsRef<str>>(names: &[S]) -> Vec<AlertChannel> {
+    names.iter()
+        .filter_map(|name| serde_json::from_value(serde_json::json!(name.as_ref())).ok())
+        .collect()
+}
+
+// ============================================================================
+// Sending
+// ============================================================================
+
+/// One alert about an order, sent once per channel however often its
+/// trigger fires
+pub struct TripAlert {
+    /// Unique per order, e.g. `CHECK_IN:<item id>`
+    pub key: String,
+    /// `event_type` of the email handed to the notification service
+    pub event_type: &'static str,
+    /// What the text message says
+    pub text: String,
+    /// Extra fields for the email template
+    pub details: serde_json::Value,
+}
+
+/// Sends the alert on each of the customer's channels: text messages to the
+/// order's contact number (or the profile's), email through the
+/// notification service. Only paid trips are alerted, and never sandbox ones.
+pub(crate) async fn send_alert(state: &AppState, order: &serde_json::Value, alert: &TripAlert) {
+    let Some(order_id) = order["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { return };
+    let booked = matches!(order["status"].as_str(), Some("PAID" | "FULFILLED"));
+    if !booked || order["test"].as_bool().unwrap_or(false) {
+        return;
+    }
+
+    let (channels, profile_phone) = customer_channels(state, order).await;
+    let phone = order["contact_info"]["phone"].as_str().and_then(normalize_phone).or(profile_phone);
+
+    for channel in channels {
+        let recipient = if channel.is_text() { phone.as_deref() } else { None };
+        let claimed = state.message_repo.claim_message(order_id, &alert.key, channel.as_str(), recipient).await;
+        let message_id = match claimed {
+            Ok(Some(id)) => id,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::error!("Failed to claim {} alert {} on order {}: {:?}", channel.as_str(), alert.key, order_id, e);
+                continue;
+            }
+        };
+
+        let outcome = deliver(state, order, alert, channel, recipient).await;
+        let (status, provider_id, error) = match &outcome {
+            Delivery::Sent(provider_id) => ("SENT", Some(provider_id.as_str()), None),
+            Delivery::Skipped(reason) => ("SKIPPED", None, Some(reason.as_str())),
+            Delivery::Failed(reason) => {
+                tracing::warn!("{} alert {} on order {} failed: {}", channel.as_str(), alert.key, order_id, reason);
+                ("FAILED", None, Some(reason.as_str()))
+            }
+        };
+        if let Err(e) = state.message_repo.finish_message(message_id, status, provider_id, error).await {
+            tracing::error!("Failed to record {} alert {} on order {}: {:?}", channel.as_str(), alert.key, order_id, e);
+        }
+    }
+}
+
+enum Delivery {
+    Sent(String),
+    Skipped(String),
+    Failed(String),
+}
+
+async fn deliver(state: &AppState, order: &serde_json::Value, alert: &TripAlert, channel: AlertChannel, recipient: Option<&str>) -> Delivery {
+    if channel == AlertChannel::Email {
+        let mut event = serde_json::json!({
+            "event_type": alert.event_type,
+            "order_id": order["id"],
+            "customer_id": order["customer_id"],
+            "customer_email": order["customer_email"],
+            "message": alert.text,
+            "timestamp": chrono::Utc::now().timestamp(),
+        });
+        if let (Some(event), Some(details)) = (event.as_object_mut(), alert.details.as_object()) {
+            event.extend(details.clone());
+        }
+        let key = order["id"].as_str().unwrap_or_default();
+        return match crate::notifier::notify(state, key, &event).await {
+            Ok(()) => Delivery::Sent("notifications".to_string()),
+            Err(e) => Delivery::Failed(e.to_string()),
+        };
+    }
+
+    let Some(to) = recipient else {
+        return Delivery::Skipped("no phone number".to_string());
+    };
+    if !state.alerts.supports(channel) {
+        return Delivery::Skipped(format!("{} is not configured", channel.as_str()));
+    }
+    let message = TextMessage { channel, to: to.to_string(), body: alert.text.clone() };
+    match state.alerts.sender.send(&message).await {
+        Ok(provider_id) => Delivery::Sent(provider_id),
+        Err(e) => Delivery::Failed(e.to_string()),
+    }
+}
+
+/// The customer's chosen channels and phone from their profile, or the
+/// defaults for customers without one
+async fn customer_channels(state: &AppState, order: &serde_json::Value) -> (Vec<AlertChannel>, Option<String>) {
+    let owner = order["customer_did"].as_str().or(order["customer_id"].as_str()).unwrap_or_default();
+    let profile = match state.profile_repo.get_profile(owner).await {
+        Ok(profile) => profile.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("Failed to load alert preferences, using the defaults: {:?}", e);
+            serde_json::Value::Null
+        }
+    };
+    let channels = profile["alert_channels"].as_array()
+        .map(|names| parse_channels(&names.iter().filter_map(|n| n.as_str()).collect::<Vec<_>>()))
+        .unwrap_or_else(|| state.alerts.default_channels.clone());
+    (channels, profile["phone"].as_str().and_then(normalize_phone))
+}
+
+// ============================================================================
+// Alerts
+// ============================================================================
+
+/// `SQ318 SIN-LHR on 2026-11-02`, from a flight's metadata
+fn flight_label(metadata: &serde_json::Value) -> String {
+    let text = |field: &str| metadata[field].as_str().unwrap_or_default();
+    let date = text("departure_time").get(..10).unwrap_or_default();
+    format!("{} {}-{} on {}", text("flight_number"), text("origin"), text("destination"), date)
+}
+
+/// The alert for passengers of a disrupted flight
+pub fn disruption_alert(req: &TriggerDisruptionRequest, disruption_id: Uuid, flight: &serde_json::Value, proposed: usize) -> TripAlert {
+    let label = flight_label(&flight["metadata"]);
+    let mut text = match (req.new_status.as_str(), req.delay_minutes) {
+        ("CANCELLED", _) => format!("Your flight {} is cancelled.", label),
+        ("DELAYED", Some(minutes)) => format!("Your flight {} is delayed by {} min.", label, minutes),
+        (status, _) => format!("Your flight {} is now {}.", label, status.to_lowercase()),
+    };
+    if proposed > 0 {
+        text.push_str(&format!(" We're holding {} alternative flight(s) for you; open your booking to choose.", proposed));
+    }
+    TripAlert {
+        key: format!("DISRUPTION:{}", disruption_id),
+        event_type: "FLIGHT_DISRUPTED",
+        text,
+        details: serde_json::json!({
+            "flight_id": req.flight_id,
+            "new_status": req.new_status,
+            "delay_minutes": req.delay_minutes,
+            "alternatives_proposed": proposed,
+        }),
+    }
+}
+
+/// The alert for passengers of a retimed flight; a major change asks them
+/// to accept the new times or decline them
+pub fn schedule_change_alert(flight: &serde_json::Value, change: &ScheduleChange, severity: ScheduleChangeSeverity) -> TripAlert {
+    let metadata = &flight["metadata"];
+    let clock = |t: &serde_json::Value| t.as_str()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.format("%H:%M").to_string())
+        .unwrap_or_default();
+    let mut text = format!("Your flight {} now departs at {}.", flight_label(metadata), clock(&metadata["departure_time"]));
+    if severity == ScheduleChangeSeverity::Major {
+        text.push_str(" Open your booking to accept the new time or cancel the flight for a full refund.");
+    }
+    TripAlert {
+        key: format!("SCHEDULE_CHANGE:{}:{}", flight["id"].as_str().unwrap_or_default(), change.new_departure.timestamp()),
+        event_type: "SCHEDULE_CHANGED",
+        text,
+        details: serde_json::json!({
+            "flight_id": flight["id"],
+            "previous_departure_time": change.previous_departure,
+            "departure_time": metadata["departure_time"],
+            "arrival_time": metadata["arrival_time"],
+            "shift_minutes": change.shift_minutes(),
+            "severity": severity,
+        }),
+    }
+}
+
+/// The alert that check-in has opened for a flight on the order
+pub fn check_in_alert(order_id: Uuid, item: &serde_json::Value) -> TripAlert {
+    let metadata = &item["metadata"];
+    let departure = metadata["departure_time"].as_str()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.format("%H:%M").to_string())
+        .unwrap_or_default();
+    let text = format!(
+        "Check-in is open for {} departing {}. Booking {}.",
+        flight_label(metadata), departure, crate::itinerary_email::booking_reference(order_id),
+    );
+    TripAlert {
+        key: format!("CHECK_IN:{}", item["id"].as_str().unwrap_or_default()),
+        event_type: "CHECK_IN_OPEN",
+        text,
+        details: serde_json::json!({ "order_item_id": item["id"], "departure_time": metadata["departure_time"] }),
+    }
+}
+
+/// Background loop sending check-in alerts as flights come within
+/// `messaging.check_in_opens_hours` of departure
+pub async fn run_check_in_alert_worker(state: AppState, config: MessagingConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.check_in_poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        let until = chrono::Utc::now() + chrono::Duration::hours(config.check_in_opens_hours);
+        let due = match state.message_repo.due_check_in_alerts(until, config.check_in_batch_size).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to find due check-in alerts: {:?}", e);
+                continue;
+            }
+        };
+        for row in due {
+            let id = |field: &str| row[field].as_str().and_then(|id| Uuid::parse_str(id).ok());
+            let (Some(order_id), Some(item_id)) = (id("order_id"), id("order_item_id")) else { continue };
+            let order = match state.order_repo.get_order(order_id).await {
+                Ok(Some(order)) => order,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::error!("Failed to load order {} for its check-in alert: {:?}", order_id, e);
+                    continue;
+                }
+            };
+            let item_id = item_id.to_string();
+            let Some(item) = order["items"].as_array().into_iter().flatten().find(|item| item["id"].as_str() == Some(item_id.as_str())) else {
+                continue;
+            };
+            send_alert(&state, &order, &check_in_alert(order_id, item)).await;
+        }
+    }
+}
+
+// ============================================================================
+// Delivery Status
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderMessageResponse {
+    pub alert_key: String,
+    pub channel: AlertChannel,
+    #[serde(serialize_with = "altis_shared::pii::phone")]
+    pub recipient: Option<altis_shared::pii::Masked<String>>,
+    pub status: String,
+    pub provider_message_id: Option<String>,
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// GET /v1/admin/orders/:id/messages
+/// Trip alerts sent about an order on each channel, masked for the caller's role
+pub async fn list_order_messages(
+    State(state): State<AppState>,
+    Extension(claims): Extension<AdminClaims>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    authorize_tenant_order(&state, &tenant, order_id).await?;
+    let messages = state.message_repo.list_order_messages(order_id).await.map_err(|e| {
+        tracing::error!("Failed to list alerts of order {}: {:?}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let messages: Vec<OrderMessageResponse> = messages.into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    altis_shared::pii::with_masking(&state.pii_policy.tier(&claims.role), || serde_json::to_value(&messages))
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disruption_and_check_in_alert_text() {
+        let flight = serde_json::json!({
+            "metadata": {"flight_number": "SQ318", "origin": "SIN", "destination": "LHR", "departure_time": "2026-11-02T23:35:00+08:00"},
+        });
+        let disruption_id = Uuid::new_v4();
+        let mut req = TriggerDisruptionRequest {
+            flight_id: Uuid::new_v4(),
+            new_status: "DELAYED".to_string(),
+            delay_minutes: Some(95),
+            cause: None,
+        };
+        let alert = disruption_alert(&req, disruption_id, &flight, 0);
+        assert_eq!(alert.key, format!("DISRUPTION:{}", disruption_id));
+        assert_eq!(alert.text, "Your flight SQ318 SIN-LHR on 2026-11-02 is delayed by 95 min.");
+
+        req.new_status = "CANCELLED".to_string();
+        let alert = disruption_alert(&req, disruption_id, &flight, 2);
+        assert!(alert.text.starts_with("Your flight SQ318 SIN-LHR on 2026-11-02 is cancelled. We're holding 2 alternative"));
+        assert_eq!(alert.details["alternatives_proposed"], 2);
+
+        let order_id = Uuid::new_v4();
+        let item = serde_json::json!({"id": Uuid::new_v4(), "metadata": flight["metadata"]});
+        let alert = check_in_alert(order_id, &item);
+        assert_eq!(alert.key, format!("CHECK_IN:{}", item["id"].as_str().unwrap()));
+        assert!(alert.text.contains("SQ318 SIN-LHR on 2026-11-02 departing 23:35"));
+        assert!(alert.text.ends_with(&format!("Booking {}.", crate::itinerary_email::booking_reference(order_id))));
+
+        let mut retimed = flight.clone();
+        retimed["id"] = serde_json::json!(Uuid::new_v4());
+        retimed["metadata"]["departure_time"] = serde_json::json!("2026-11-03T04:05:00+08:00");
+        let change = ScheduleChange::between(&flight["metadata"], &retimed["metadata"]).unwrap();
+        let alert = schedule_change_alert(&retimed, &change, ScheduleChangeSeverity::Major);
+        assert!(alert.text.starts_with("Your flight SQ318 SIN-LHR on 2026-11-03 now departs at 04:05. Open your booking to accept"));
+        assert_eq!(alert.details["shift_minutes"], 270);
+
+        // Channel names from preferences and config; unknown ones are dropped
+        assert_eq!(parse_channels(&["SMS", "WHATSAPP", "PIGEON"]), vec![AlertChannel::Sms, AlertChannel::Whatsapp]);
+    }
+}
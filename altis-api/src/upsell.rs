@@ -0,0 +1,407 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_catalog::InventoryError;
+use altis_core::payment::PaymentStatus;
+use altis_offer::upsell::{rank_upsells, UpsellReason};
+use altis_offer::OfferItem;
+use altis_order::ledger::JournalTransaction;
+use altis_shared::money::Money;
+
+use crate::authz::{authorize_order, registered_customer};
+use crate::middleware::auth::CustomerClaims;
+use crate::orders::{OrderItemResponse, OrderResponse};
+use crate::state::AppState;
+
+// ============================================================================
+// Models
+// ============================================================================
+
+/// An ancillary offered on a paid order, at a price held until `expires_at`
+#[derive(Debug, Serialize)]
+pub struct UpsellOfferResponse {
+    /// What `POST .../upsell/:offer_item_id/accept` takes
+    pub offer_item_id: Uuid,
+    /// 1 is the best bet
+    pub rank: i32,
+    pub reason: UpsellReason,
+    pub product_id: Option<Uuid>,
+    pub product_type: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub price_nuc: i64,
+    pub tax_nuc: i64,
+    /// Charged on acceptance: price plus taxes
+    pub total_nuc: i64,
+    pub discount_percentage: f64,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An upsell offer as stored
+#[derive(Debug, Deserialize)]
+struct UpsellOffer {
+    id: Uuid,
+    rank: i32,
+    reason: UpsellReason,
+    item: OfferItem,
+    expires_at: DateTime<Utc>,
+}
+
+impl From<UpsellOffer> for UpsellOfferResponse {
+    fn from(offer: UpsellOffer) -> Self {
+        let item = offer.item;
+        Self {
+            offer_item_id: offer.id,
+            rank: offer.rank,
+            reason: offer.reason,
+            product_id: item.product_id,
+            product_type: item.product_type,
+            name: item.name,
+            description: item.description,
+            price_nuc: item.price.minor_units(),
+            tax_nuc: item.tax.minor_units(),
+            total_nuc: item.price.minor_units() + item.tax.minor_units(),
+            discount_percentage: item.metadata["upsell_discount"].as_f64().unwrap_or(0.0) * 100.0,
+            expires_at: offer.expires_at,
+        }
+    }
+}
+
+fn upsell_offer(row: serde_json::Value) -> Result<UpsellOffer, StatusCode> {
+    serde_json::from_value(row).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Orders extras can still be added to: paid, and with a flight yet to leave
+fn accepts_upsells(order: &OrderResponse, now: DateTime<Utc>) -> bool {
+    matches!(order.status.as_str(), "PAID" | "FULFILLED")
+        && order.items.iter()
+            .filter(|item| item.product_type.eq_ignore_ascii_case("FLIGHT") && item.status == "ACTIVE")
+            .filter_map(|item| item.metadata["departure_time"].as_str())
+            .filter_map(|t| DateTime::parse_from_rfc3339(t).ok())
+            .any(|departure| departure > now)
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/orders/:id/upsell
+/// Ancillaries worth adding to a paid order, best first, at prices held for a while
+pub async fn get_upsell(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<Vec<UpsellOfferResponse>>, StatusCode> {
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let order: OrderResponse = serde_json::from_value(order_json.clone())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !matches!(order.status.as_str(), "PAID" | "FULFILLED") {
+        return Err(StatusCode::CONFLICT);
+    }
+    if !accepts_upsells(&order, Utc::now()) {
+        return Ok(Json(Vec::new()));
+    }
+
+    // Prices already shown hold until they expire; asking again doesn't reprice
+    let open = state.upsell_repo.open_upsell_offers(order_id).await.map_err(|e| {
+        tracing::error!("Failed to load upsell offers of order {}: {:?}", order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let offers = if open.is_empty() {
+        let priced = price_upsells(&state, &order, &order_json).await?;
+        state.upsell_repo.save_upsell_offers(order_id, &priced).await.map_err(|e| {
+            tracing::error!("Failed to save upsell offers of order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+    } else {
+        open
+    };
+
+    let offers = offers.into_iter().map(upsell_offer).collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(offers.into_iter().map(Into::into).collect()))
+}
+
+/// Ranks the airline's sellable ancillaries for the order and prices them
+/// with the rule discount and the trip's taxes
+async fn price_upsells(state: &AppState, order: &OrderResponse, order_json: &serde_json::Value) -> Result<Vec<serde_json::Value>, StatusCode> {
+    let airline_id = order_json["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or(StatusCode::CONFLICT)?;
+    let (products, tax_engine) = tokio::try_join!(state.catalog_cache.products(airline_id), state.catalog_cache.tax_engine())
+        .map_err(|e| {
+            tracing::error!("Failed to load the catalog of airline {} for upsell: {:?}", airline_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // The trip as the rules see it: the shopped segment and route, when the offer is still around
+    let flight = order.items.iter().find(|item| item.product_type.eq_ignore_ascii_case("FLIGHT"));
+    let route = flight.map(|f| f.metadata.clone()).unwrap_or_default();
+    let search = match order.offer_id {
+        Some(offer_id) => state.offer_repo.get_offer(offer_id).await.ok().flatten()
+            .map(|offer| offer["search_context"].clone())
+            .unwrap_or_default(),
+        None => serde_json::Value::Null,
+    };
+    let passengers = order.travelers.as_ref().map(|t| t.len() as i64).filter(|n| *n > 0)
+        .or(search["passengers"].as_i64())
+        .unwrap_or(1);
+    let context = serde_json::json!({
+        "user_segment": search["user_segment"],
+        "search": {"origin": route["origin"], "destination": route["destination"], "passengers": passengers},
+    });
+
+    let ancillaries: Vec<altis_catalog::Product> = products.iter()
+        .filter(|p| p["is_active"].as_bool().unwrap_or(true))
+        .map(crate::offers::catalog_product)
+        .filter(|p| p.product_type != altis_catalog::ProductType::Flight)
+        .collect();
+    let ancillaries = crate::offers::sellable_ancillaries(state, airline_id, ancillaries, &route).await;
+    let customer = crate::customer_features::for_customer(state, &order.customer_id).await.unwrap_or_default();
+    let owned: HashSet<Uuid> = order.items.iter()
+        .filter(|item| item.status == "ACTIVE")
+        .filter_map(|item| item.product_id)
+        .collect();
+
+    let rules = altis_offer::rules::RuleEngine::new(altis_offer::rules::get_default_rules());
+    let ranked = rank_upsells(&rules, &context, &customer, &ancillaries, &owned, state.upsell.max_offers)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let expires_at = Utc::now() + chrono::Duration::minutes(state.upsell.price_valid_minutes);
+    let mut priced = Vec::with_capacity(ranked.len());
+    for (index, candidate) in ranked.into_iter().enumerate() {
+        let product = candidate.product;
+        let mut metadata = product.metadata.clone();
+        if let Some(fields) = metadata.as_object_mut() {
+            fields.insert("upsell_discount".to_string(), serde_json::json!(candidate.discount));
+        }
+        let mut item = OfferItem::new(
+            format!("{:?}", product.product_type),
+            Some(product.id),
+            Some(product.product_code.clone()),
+            product.name.clone(),
+            product.description.clone(),
+            candidate.price,
+            1,
+            metadata,
+        );
+        let tax_type = serde_json::to_value(&product.product_type).unwrap_or_default();
+        let price_nuc = item.price.to_i32().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        item.apply_taxes(tax_engine.calculate(tax_type.as_str().unwrap_or_default(), &route, price_nuc, 1))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        priced.push(serde_json::json!({
+            "id": item.id,
+            "rank": index + 1,
+            "reason": candidate.reason,
+            "item": item,
+            "expires_at": expires_at,
+        }));
+    }
+    Ok(priced)
+}
+
+/// POST /v1/orders/:id/upsell/:offer_item_id/accept
+/// Buy an upsell offer with one click, charged to the customer's default saved card
+pub async fn accept_upsell(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path((order_id, offer_item_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    let owner = registered_customer(&claims)?.to_string();
+    let order_json = authorize_order(&state, &claims, order_id).await?;
+    let order: OrderResponse = serde_json::from_value(order_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !accepts_upsells(&order, Utc::now()) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // One click: the default card, which lists first
+    let payments = state.payments(order.test);
+    let methods = state.payment_method_repo.list_payment_methods(&owner, payments.provider(), order.test).await
+        .map_err(|e| {
+            tracing::error!("Failed to list payment methods for upsell on order {}: {:?}", order_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let token = methods.first()
+        .and_then(|method| method["provider_token"].as_str())
+        .map(str::to_string)
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    // Only the first click gets past here; an expired price is gone
+    let claimed = state.upsell_repo.claim_upsell_offer(offer_item_id, order_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to claim upsell offer {}: {:?}", offer_item_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::GONE)?;
+    let offer = upsell_offer(claimed)?;
+
+    buy_upsell(&state, &order, &offer, &token).await?;
+
+    let updated = state.order_repo.get_order(order_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let response: OrderResponse = serde_json::from_value(updated)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(response))
+}
+
+/// Holds the unit, charges the card and adds the item to the order. Until
+/// the charge goes through the offer is released again on failure; after,
+/// it stays claimed so it can't be charged a second time.
+async fn buy_upsell(state: &AppState, order: &OrderResponse, offer: &UpsellOffer, token: &str) -> Result<(), StatusCode> {
+    let order_id = order.id;
+    let item = &offer.item;
+    let amount = item.price.checked_add(item.tax).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 1. A unit of the product, if its stock is counted
+    let held = match item.product_id {
+        Some(product_id) => match state.inventory.reserve(product_id, 1).await {
+            Ok(()) => Some(product_id),
+            Err(InventoryError::NotFound(_)) => None,
+            Err(e) => {
+                let _ = state.upsell_repo.release_upsell_offer(offer.id).await;
+                if let InventoryError::InsufficientInventory { .. } = e {
+                    return Err(StatusCode::CONFLICT);
+                }
+                tracing::error!("Failed to hold upsell product {} for order {}: {}", product_id, order_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        },
+        None => None,
+    };
+    let give_back = || async {
+        if let Some(product_id) = held {
+            if let Err(e) = state.inventory.release(product_id, 1).await {
+                tracing::error!("Failed to release upsell product {} held for order {}: {}", product_id, order_id, e);
+            }
+        }
+        let _ = state.upsell_repo.release_upsell_offer(offer.id).await;
+    };
+
+    // 2. The card on file
+    let charged = state.payments(order.test).charge_upsell(order_id, offer.id, Money::new(amount.minor_units(), altis_shared::money::Currency::NUC), token).await;
+    match charged {
+        Ok(PaymentStatus::Succeeded) => {}
+        Ok(status) => {
+            give_back().await;
+            let _ = state.order_repo.add_order_change(
+                order_id,
+                "PAYMENT_FAILED",
+                None,
+                Some(serde_json::json!({"status": status, "upsell_offer_id": offer.id})),
+                "SYSTEM",
+                Some("Upsell charge declined"),
+            ).await;
+            return Err(StatusCode::PAYMENT_REQUIRED);
+        }
+        Err(e) => {
+            // The intent id is stable, so accepting again can't charge twice
+            give_back().await;
+            tracing::error!("Upsell charge on order {} failed: {:?}", order_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    if let Some(product_id) = held {
+        if let Err(e) = state.inventory.commit(product_id, 1).await {
+            tracing::warn!("Upsell unit of product {} held but not sold: {}", product_id, e);
+        }
+    }
+
+    // 3. The item joins the order, and the books
+    let mut item_json = serde_json::to_value(item).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    item_json["status"] = serde_json::json!("ACTIVE");
+    item_json["metadata"]["upsell_offer_id"] = serde_json::json!(offer.id);
+    let total_nuc = amount.to_i32().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let item_id = state.order_repo.add_order_item(order_id, &item_json).await.map_err(|e| {
+        tracing::error!("Charged upsell {} on order {} but failed to add the item: {:?}", offer.id, order_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if let Err(e) = state.order_repo.adjust_order_total(order_id, total_nuc).await {
+        tracing::error!("Failed to add upsell {} to the total of order {}: {:?}", offer.id, order_id, e);
+    }
+    if let Err(e) = state.upsell_repo.complete_upsell_offer(offer.id, item_id).await {
+        tracing::error!("Failed to mark upsell offer {} accepted: {:?}", offer.id, e);
+    }
+    let _ = state.order_repo.add_order_change(
+        order_id,
+        "UPSELL_ACCEPTED",
+        Some(serde_json::json!({"total_nuc": order.total_nuc})),
+        Some(serde_json::json!({"total_nuc": order.total_nuc + total_nuc, "order_item_id": item_id, "upsell_offer_id": offer.id, "rank": offer.rank})),
+        "CUSTOMER",
+        Some(&format!("{} added after payment", item.name)),
+    ).await;
+    crate::finance::post_journal(state, JournalTransaction::sale(order_id, amount, item.tax)).await;
+    crate::finance::post_journal(state, JournalTransaction::payment(order_id, amount, Some(token))).await;
+
+    // 4. Supplier booking and barcode, as for items paid at checkout
+    let updated = state.order_repo.get_order(order_id).await.ok().flatten()
+        .and_then(|order| serde_json::from_value::<OrderResponse>(order).ok());
+    if let Some(updated) = updated {
+        let added: Vec<&OrderItemResponse> = updated.items.iter().filter(|i| i.id == item_id).collect();
+        if let Some(added) = added.first() {
+            crate::suppliers::confirm_supplier_items(state, order_id, std::slice::from_ref(*added)).await;
+        }
+        crate::orders::fulfill_added_items(state, &updated, &[item_id]).await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsells_need_a_paid_trip_still_ahead() {
+        let now = Utc::now();
+        let order = |status: &str, departure: DateTime<Utc>| -> OrderResponse {
+            serde_json::from_value(serde_json::json!({
+                "id": Uuid::new_v4(),
+                "offer_id": null,
+                "customer_id": "cust-1",
+                "customer_email": null,
+                "customer_did": null,
+                "status": status,
+                "items": [{
+                    "id": Uuid::new_v4(), "product_id": null, "product_type": "Flight", "name": "SQ318",
+                    "price_nuc": 50000, "status": "ACTIVE", "revenue_status": "UNEARNED",
+                    "operating_carrier_id": null, "net_rate_nuc": null, "commission_nuc": null,
+                    "metadata": {"departure_time": departure.to_rfc3339()},
+                }],
+                "travelers": null,
+                "contact_info": null,
+                "total_nuc": 50000,
+                "currency": "NUC",
+                "expires_at": null,
+                "group_size": null,
+                "names_due_at": null,
+                "created_at": now,
+            })).unwrap()
+        };
+
+        let tomorrow = now + chrono::Duration::days(1);
+        assert!(accepts_upsells(&order("PAID", tomorrow), now));
+        assert!(accepts_upsells(&order("FULFILLED", tomorrow), now));
+        assert!(!accepts_upsells(&order("PROPOSED", tomorrow), now));
+        assert!(!accepts_upsells(&order("PAID", now - chrono::Duration::hours(2)), now));
+
+        let shown = UpsellOfferResponse::from(upsell_offer(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "rank": 1,
+            "reason": "RULE",
+            "item": {
+                "id": Uuid::new_v4(), "product_id": null, "product_type": "Bag", "product_code": "BAG23",
+                "name": "23kg bag", "description": null, "price_nuc": 3750, "quantity": 1,
+                "metadata": {"upsell_discount": 0.25}, "tax_nuc": 300, "taxes": [],
+            },
+            "expires_at": tomorrow,
+        })).unwrap());
+        assert_eq!(shown.total_nuc, 4050);
+        assert_eq!(shown.discount_percentage, 25.0);
+    }
+}
@@ -15,8 +15,11 @@ impl From<AirShoppingRequest> for SearchOffersRequest {
             departure_date: req.shopping_criteria.travel_date,
             return_date: None,
             passengers: 1,
+            children: 0,
+            infants: 0,
             cabin_class: None,
             user_segment: None,
+            campaign: Default::default(),
         }
     }
 }
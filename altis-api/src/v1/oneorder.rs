@@ -3,8 +3,10 @@ use axum::{
     http::StatusCode,
     Json,
 };
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use crate::state::AppState;
+use altis_core::dcs::{DeliveryPassenger, DeliverySegment, ServiceDeliveredEvent, ServiceDeliveryRecord, ServiceEntitlement};
 use altis_core::iata::{OneOrderResponse, OneOrder, OneOrderItem, NdcPrice};
 use crate::orders::OrderResponse;
 
@@ -44,3 +46,196 @@ pub async fn order_retrieve(
 
     Ok(Json(OneOrderResponse { order: one_order }))
 }
+
+// ============================================================================
+// Service Delivery (DCS)
+// ============================================================================
+
+/// Why an order couldn't be mapped to service delivery records, or a
+/// delivery confirmation back onto the order
+#[derive(Debug, thiserror::Error)]
+pub enum DeliveryMappingError {
+    /// The order or flight couldn't be read just now; try again
+    #[error("retryable: {0}")]
+    Retryable(String),
+    /// Will map the same way every time
+    #[error("rejected: {0}")]
+    Rejected(String),
+}
+
+fn retryable(e: Box<dyn std::error::Error + Send + Sync>) -> DeliveryMappingError {
+    DeliveryMappingError::Retryable(e.to_string())
+}
+
+/// One record per passenger checked in on the flight item: the flight
+/// itself, their seat, and the order's other active items on that flight,
+/// each with its fulfillment barcode. Ancillaries not tied to a passenger
+/// go with every passenger's record, for the DCS to hand out.
+pub(crate) fn service_delivery_records(
+    order: &serde_json::Value,
+    flight_item: &serde_json::Value,
+    flight: &serde_json::Value,
+    checked_in_at: DateTime<Utc>,
+) -> Result<Vec<ServiceDeliveryRecord>, DeliveryMappingError> {
+    let id_of = |value: &serde_json::Value| value.as_str().and_then(|id| Uuid::parse_str(id).ok());
+    let order_id = id_of(&order["id"]).ok_or_else(|| DeliveryMappingError::Rejected("order has no id".to_string()))?;
+    let flight_id = id_of(&flight["id"]).ok_or_else(|| DeliveryMappingError::Rejected(format!("flight of order {} has no id", order_id)))?;
+    let metadata = &flight["metadata"];
+    let text = |field: &str| metadata[field].as_str().unwrap_or_default().to_string();
+
+    // The passenger's own barcode for the item, else the one covering the booking
+    let barcode = |item_id: Uuid, index: i64| {
+        let records: Vec<&serde_json::Value> = order["fulfillment"].as_array().into_iter().flatten()
+            .filter(|f| id_of(&f["order_item_id"]) == Some(item_id) && f["barcode"].is_string())
+            .collect();
+        records.iter().find(|f| f["traveler_index"].as_i64() == Some(index))
+            .or_else(|| records.iter().find(|f| f["traveler_index"].is_null()))
+            .and_then(|f| f["barcode"].as_str().map(str::to_string))
+    };
+    let entitlement = |item: &serde_json::Value, index: i64| Some(ServiceEntitlement {
+        order_item_id: id_of(&item["id"])?,
+        service_type: item["product_type"].as_str().unwrap_or_default().to_string(),
+        service_code: item["product_code"].as_str().map(str::to_string),
+        quantity: item["quantity"].as_i64().unwrap_or(1) as i32,
+        barcode: id_of(&item["id"]).and_then(|item_id| barcode(item_id, index)),
+    });
+
+    let items = order["items"].as_array().cloned().unwrap_or_default();
+    let on_flight: Vec<&serde_json::Value> = items.iter()
+        .filter(|item| item["status"].as_str() == Some("ACTIVE") && item["product_type"].as_str() != Some("FLIGHT"))
+        .filter(|item| item["metadata"]["flight_id"].as_str().is_none_or(|id| Uuid::parse_str(id).ok() == Some(flight_id)))
+        .collect();
+
+    let travelers = order["travelers"].as_array().cloned().unwrap_or_default();
+    let passengers: Vec<DeliveryPassenger> = if travelers.is_empty() {
+        (0..flight_item["quantity"].as_i64().unwrap_or(1).max(1) as i32)
+            .map(|traveler_index| DeliveryPassenger { traveler_index, ptc: "ADT".to_string(), given_name: None, surname: None })
+            .collect()
+    } else {
+        travelers.iter().map(|traveler| DeliveryPassenger {
+            traveler_index: traveler["traveler_index"].as_i64().unwrap_or(0) as i32,
+            ptc: traveler["ptc"].as_str().unwrap_or("ADT").to_string(),
+            given_name: traveler["first_name"].as_str().map(str::to_string),
+            surname: traveler["last_name"].as_str().map(str::to_string),
+        }).collect()
+    };
+
+    let booking_reference = crate::itinerary_email::booking_reference(order_id);
+    Ok(passengers.into_iter().map(|passenger| {
+        let index = passenger.traveler_index as i64;
+        let theirs: Vec<&&serde_json::Value> = on_flight.iter()
+            .filter(|item| item["traveler_index"].as_i64().is_none_or(|i| i == index))
+            .collect();
+        let seat_number = theirs.iter()
+            .find(|item| item["product_type"].as_str() == Some("SEAT") && item["traveler_index"].as_i64() == Some(index))
+            .and_then(|item| item["metadata"]["seat_number"].as_str().map(str::to_string));
+        let entitlements = std::iter::once(flight_item).chain(theirs.into_iter().copied()).filter_map(|item| entitlement(item, index)).collect();
+        ServiceDeliveryRecord {
+            order_id,
+            booking_reference: booking_reference.clone(),
+            passenger,
+            segment: DeliverySegment {
+                flight_id,
+                flight_number: text("flight_number"),
+                origin: text("origin"),
+                destination: text("destination"),
+                departure_time: text("departure_time"),
+                seat_number,
+            },
+            entitlements,
+            checked_in_at,
+        }
+    }).collect())
+}
+
+/// The records to send the DCS for a checked-in flight item
+pub(crate) async fn checked_in_records(
+    state: &AppState,
+    order_id: Uuid,
+    item_id: Uuid,
+) -> Result<Vec<ServiceDeliveryRecord>, DeliveryMappingError> {
+    let order = state.order_repo.get_order(order_id).await.map_err(retryable)?
+        .ok_or_else(|| DeliveryMappingError::Rejected(format!("order {} not found", order_id)))?;
+    let item = order["items"].as_array().into_iter().flatten()
+        .find(|item| item["id"].as_str() == Some(item_id.to_string().as_str()))
+        .cloned()
+        .ok_or_else(|| DeliveryMappingError::Rejected(format!("item {} not on order {}", item_id, order_id)))?;
+    let checked_in_at = item["metadata"]["checked_in_at"].as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| DeliveryMappingError::Rejected(format!("item {} is not checked in", item_id)))?;
+    let flight_id = item["metadata"]["flight_id"].as_str().and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| DeliveryMappingError::Rejected(format!("item {} has no flight", item_id)))?;
+    let flight = state.catalog_repo.get_product(flight_id).await.map_err(retryable)?
+        .ok_or_else(|| DeliveryMappingError::Rejected(format!("flight {} not found", flight_id)))?;
+
+    service_delivery_records(&order, &item, &flight, checked_in_at)
+}
+
+/// Marks the item's fulfillment delivered as the DCS reports it
+pub(crate) async fn record_delivery(state: &AppState, event: &ServiceDeliveredEvent) -> Result<(), DeliveryMappingError> {
+    let delivered = state.order_repo
+        .confirm_service_delivery(event.order_id, event.order_item_id, event.barcode.as_deref(), event.delivered_at)
+        .await
+        .map_err(retryable)?;
+    if delivered == 0 {
+        return Err(DeliveryMappingError::Rejected(format!(
+            "no fulfillment for item {} of order {}", event.order_item_id, event.order_id,
+        )));
+    }
+    let _ = state.order_repo.add_order_change(
+        event.order_id,
+        "SERVICE_DELIVERED",
+        None,
+        Some(serde_json::json!({"item_id": event.order_item_id, "delivered_at": event.delivered_at, "station": event.station})),
+        "DCS",
+        Some("Delivery confirmed by departure control"),
+    ).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_delivery_records() {
+        let order_id = Uuid::new_v4();
+        let flight_id = Uuid::new_v4();
+        let (flight_item, bag, seat, meal) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let item = |id: Uuid, product_type: &str, traveler_index: Option<i32>, metadata: serde_json::Value| serde_json::json!({
+            "id": id, "product_type": product_type, "product_code": null, "status": "ACTIVE", "quantity": 1,
+            "traveler_index": traveler_index, "metadata": metadata,
+        });
+        let order = serde_json::json!({
+            "id": order_id,
+            "items": [
+                item(flight_item, "FLIGHT", None, serde_json::json!({"flight_id": flight_id})),
+                item(bag, "BAG", None, serde_json::json!({})),
+                item(seat, "SEAT", Some(1), serde_json::json!({"flight_id": flight_id, "seat_number": "14C"})),
+                item(meal, "MEAL", None, serde_json::json!({"flight_id": Uuid::new_v4()})),
+            ],
+            "travelers": [
+                {"traveler_index": 0, "ptc": "ADT", "first_name": "Ana", "last_name": "Lim"},
+                {"traveler_index": 1, "ptc": "CHD", "first_name": "Ben", "last_name": "Lim"},
+            ],
+            "fulfillment": [
+                {"order_item_id": flight_item, "barcode": "M1LIM/ANA", "traveler_index": 0},
+                {"order_item_id": flight_item, "barcode": "M1LIM/BEN", "traveler_index": 1},
+            ],
+        });
+        let flight = serde_json::json!({"id": flight_id, "metadata": {"flight_number": "AL101", "origin": "SIN", "destination": "BKK"}});
+
+        let records = service_delivery_records(&order, &order["items"][0], &flight, Utc::now()).unwrap();
+        assert_eq!(records.len(), 2);
+        let services = |record: &ServiceDeliveryRecord| record.entitlements.iter().map(|e| e.order_item_id).collect::<Vec<_>>();
+        // The bag isn't tied to a passenger; the meal is on another flight
+        assert_eq!(services(&records[0]), vec![flight_item, bag]);
+        assert_eq!(services(&records[1]), vec![flight_item, bag, seat]);
+        assert_eq!(records[0].entitlements[0].barcode.as_deref(), Some("M1LIM/ANA"));
+        assert_eq!(records[1].entitlements[0].barcode.as_deref(), Some("M1LIM/BEN"));
+        assert_eq!(records[1].segment.seat_number.as_deref(), Some("14C"));
+        assert_eq!(records[1].passenger.surname.as_deref(), Some("Lim"));
+        assert_eq!(records[0].booking_reference, crate::itinerary_email::booking_reference(order_id));
+    }
+}
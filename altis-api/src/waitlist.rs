@@ -0,0 +1,412 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use altis_catalog::InventoryError;
+use altis_core::tenant::TenantContext;
+use altis_store::app_config::WaitlistConfig;
+
+use crate::authz::authorize_product;
+use crate::middleware::auth::CustomerClaims;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct JoinWaitlistRequest {
+    /// Seats wanted together; 1 when left out
+    pub seats: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WaitlistEntryResponse {
+    pub id: Uuid,
+    pub flight_id: Uuid,
+    pub seats: i32,
+    /// WAITING, OFFERED, BOOKED, EXPIRED or CANCELLED
+    pub status: String,
+    /// Place in line while WAITING, 1 being next
+    pub position: Option<i64>,
+    /// The offer to accept while OFFERED, good until `offer_expires_at`
+    pub offer_id: Option<Uuid>,
+    pub offer_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn to_response(entry: serde_json::Value) -> Result<WaitlistEntryResponse, StatusCode> {
+    serde_json::from_value(entry).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn departure(flight: &serde_json::Value) -> Option<DateTime<Utc>> {
+    flight["metadata"]["departure_time"].as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Whether the flight can still be sold, waitlist or not
+fn on_sale(flight: &serde_json::Value, now: DateTime<Utc>) -> bool {
+    flight["product_type"].as_str() == Some("FLIGHT")
+        && flight["is_active"].as_bool().unwrap_or(true)
+        && flight["deleted_at"].is_null()
+        && departure(flight).is_some_and(|departs| departs > now)
+}
+
+/// A customer may only queue for a flight on sale that can't seat them now
+/// (409 when it can; untracked flights always can)
+fn check_join(flight: &serde_json::Value, seats: i32, max_seats: i32, available: Option<i32>, now: DateTime<Utc>) -> Result<(), StatusCode> {
+    if !(1..=max_seats).contains(&seats) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    if !on_sale(flight, now) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if available.is_none_or(|available| available >= seats) {
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(())
+}
+
+async fn seats_left(state: &AppState, flight_id: Uuid) -> Result<Option<i32>, StatusCode> {
+    match state.inventory.get(flight_id).await {
+        Ok(item) => Ok(item.map(|item| item.available_quantity)),
+        Err(e) => {
+            tracing::error!("Failed to read seats left on flight {}: {}", flight_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// ============================================================================
+// Customers
+// ============================================================================
+
+/// POST /v1/flights/:id/waitlist
+/// Queue for seats on a sold-out flight; the customer is offered them first when they come back
+pub async fn join_waitlist(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(flight_id): Path<Uuid>,
+    Json(req): Json<JoinWaitlistRequest>,
+) -> Result<(StatusCode, Json<WaitlistEntryResponse>), StatusCode> {
+    let flight = state.catalog_repo.get_product(flight_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let seats = req.seats.unwrap_or(1);
+    let available = seats_left(&state, flight_id).await?;
+    if let Err(status) = check_join(&flight, seats, state.waitlist.max_seats, available, Utc::now()) {
+        tracing::debug!("Refused waitlist for flight {} ({} seat(s), {:?} left): {}", flight_id, seats, available, status);
+        return Err(status);
+    }
+
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let entry = serde_json::json!({
+        "flight_id": flight_id,
+        "airline_id": flight["airline_id"],
+        "customer_id": customer_id,
+        "customer_email": claims.email,
+        "seats": seats,
+    });
+    let joined = state.waitlist_repo.join_waitlist(&entry).await
+        .map_err(|e| {
+            tracing::error!("Failed to add a customer to the waitlist of flight {}: {:?}", flight_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        // Already in line
+        .ok_or(StatusCode::CONFLICT)?;
+
+    Ok((StatusCode::CREATED, Json(to_response(joined)?)))
+}
+
+/// GET /v1/flights/:id/waitlist
+/// The customer's place in line, or the offer waiting for them
+pub async fn get_waitlist_entry(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(flight_id): Path<Uuid>,
+) -> Result<Json<WaitlistEntryResponse>, StatusCode> {
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let entry = state.waitlist_repo.find_waitlist_entry(flight_id, &customer_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(to_response(entry)?))
+}
+
+/// DELETE /v1/flights/:id/waitlist
+/// Leave the line; seats held on an offer go back on sale
+pub async fn leave_waitlist(
+    State(state): State<AppState>,
+    Extension(claims): Extension<CustomerClaims>,
+    Path(flight_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let (customer_id, _) = crate::authz::customer_id_for(&claims);
+    let left = state.waitlist_repo.leave_waitlist(flight_id, &customer_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if left["status"].as_str() == Some("OFFERED") {
+        release_seats(&state, &left).await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Takes over the seats held for a waitlist offer being accepted, so the
+/// order can reserve them. Only the customer it was made for can accept it,
+/// and only within its priority window (410 after).
+pub(crate) async fn redeem_offer(state: &AppState, claims: &CustomerClaims, offer: &altis_offer::Offer) -> Result<(), StatusCode> {
+    let Some(entry_id) = offer.search_context["waitlist_entry_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else {
+        return Ok(());
+    };
+    let (customer_id, _) = crate::authz::customer_id_for(claims);
+    if offer.customer_id.as_deref() != Some(customer_id.as_str()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let booked = state.waitlist_repo.book_waitlist_entry(entry_id, &customer_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !booked {
+        return Err(StatusCode::GONE);
+    }
+    if let Ok(Some(entry)) = state.waitlist_repo.get_waitlist_entry(entry_id).await {
+        release_seats(state, &entry).await;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Offering Returned Seats
+// ============================================================================
+
+async fn release_seats(state: &AppState, entry: &serde_json::Value) {
+    let Some(flight_id) = entry["flight_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { return };
+    let seats = entry["seats"].as_i64().unwrap_or(1) as i32;
+    match state.inventory.release(flight_id, seats).await {
+        Ok(()) | Err(InventoryError::NotFound(_)) => {}
+        Err(e) => tracing::error!("Failed to release {} waitlist seat(s) on flight {}: {}", seats, flight_id, e),
+    }
+}
+
+/// Background loop giving lapsed offers' seats back and offering seats that
+/// came back (a cancellation, a bigger aircraft) to the front of each line
+pub async fn run_waitlist_worker(state: AppState, config: WaitlistConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_seconds.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        match state.waitlist_repo.expire_waitlist_offers(config.batch_size).await {
+            Ok(expired) => {
+                for entry in &expired {
+                    release_seats(&state, entry).await;
+                }
+                if !expired.is_empty() {
+                    tracing::info!("{} waitlist offer(s) lapsed", expired.len());
+                }
+            }
+            Err(e) => tracing::error!("Failed to expire waitlist offers: {:?}", e),
+        }
+
+        let flights = match state.waitlist_repo.waitlisted_flights(config.batch_size).await {
+            Ok(flights) => flights,
+            Err(e) => {
+                tracing::error!("Failed to find waitlisted flights: {:?}", e);
+                continue;
+            }
+        };
+        for flight_id in flights {
+            offer_returned_seats(&state, &config, flight_id).await;
+        }
+    }
+}
+
+/// Offers the flight's free seats down its line, first come first served.
+/// Someone wanting more seats than are free holds up those behind them.
+async fn offer_returned_seats(state: &AppState, config: &WaitlistConfig, flight_id: Uuid) {
+    let flight = match state.catalog_repo.get_product(flight_id).await {
+        Ok(Some(flight)) => flight,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Failed to load waitlisted flight {}: {:?}", flight_id, e);
+            return;
+        }
+    };
+    if !on_sale(&flight, Utc::now()) {
+        match state.waitlist_repo.close_waitlist(flight_id).await {
+            Ok(closed) => tracing::info!("Closed the waitlist of flight {}: {} customer(s) still waiting", flight_id, closed),
+            Err(e) => tracing::error!("Failed to close the waitlist of flight {}: {:?}", flight_id, e),
+        }
+        return;
+    }
+
+    loop {
+        let entry = match state.waitlist_repo.next_waiting(flight_id).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!("Failed to read the waitlist of flight {}: {:?}", flight_id, e);
+                return;
+            }
+        };
+        let seats = entry["seats"].as_i64().unwrap_or(1) as i32;
+        // Held for the customer now, so nobody else books them meanwhile
+        match state.inventory.reserve(flight_id, seats).await {
+            Ok(()) => {}
+            Err(InventoryError::InsufficientInventory { .. }) => return,
+            Err(e) => {
+                tracing::warn!("Could not hold waitlist seats on flight {}: {}", flight_id, e);
+                return;
+            }
+        }
+        if !offer_seats(state, config, &flight, &entry).await {
+            release_seats(state, &entry).await;
+            return;
+        }
+    }
+}
+
+/// Prices the held seats into an offer reserved for the customer and tells
+/// them. False when no offer could be made.
+async fn offer_seats(state: &AppState, config: &WaitlistConfig, flight: &serde_json::Value, entry: &serde_json::Value) -> bool {
+    let flight_id = flight["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).unwrap_or_default();
+    let Some(entry_id) = entry["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { return false };
+    let metadata = &flight["metadata"];
+    let context = altis_offer::features::SearchContext {
+        origin: metadata["origin"].as_str().unwrap_or_default().to_string(),
+        destination: metadata["destination"].as_str().unwrap_or_default().to_string(),
+        departure_date: departure(flight).map(|t| t.date_naive().to_string()).unwrap_or_default(),
+        passengers: entry["seats"].as_i64().unwrap_or(1) as i32,
+        children: 0,
+        infants: 0,
+        cabin_class: None,
+        user_segment: None,
+        customer: None,
+    };
+    let airline = catalog_airline_of(state, flight).await;
+    let offers = match crate::offers::generate_offers(state, &context, &airline).await {
+        Ok(offers) => offers,
+        Err(status) => {
+            tracing::warn!("Could not price waitlist offer {} on flight {}: {}", entry_id, flight_id, status);
+            return false;
+        }
+    };
+    // The cheapest way onto the flight
+    let Some(mut offer) = offers.into_iter()
+        .filter(|offer| offer.items.iter().any(|item| item.product_id == Some(flight_id)))
+        .min_by_key(|offer| offer.total.minor_units())
+    else {
+        tracing::warn!("No offer on flight {} for waitlist entry {}", flight_id, entry_id);
+        return false;
+    };
+
+    let expires_at = Utc::now() + chrono::Duration::minutes(config.priority_minutes);
+    offer.customer_id = entry["customer_id"].as_str().map(str::to_string);
+    offer.expires_at = expires_at;
+    offer.search_context["waitlist_entry_id"] = serde_json::json!(entry_id);
+    let saved = match serde_json::to_value(&offer) {
+        Ok(value) => state.offer_repo.save_offer(&value).await,
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = saved {
+        tracing::error!("Failed to save waitlist offer for entry {}: {:?}", entry_id, e);
+        return false;
+    }
+    match state.waitlist_repo.mark_offered(entry_id, offer.id, expires_at).await {
+        Ok(true) => {}
+        // Left the line meanwhile
+        Ok(false) => return false,
+        Err(e) => {
+            tracing::error!("Failed to record waitlist offer for entry {}: {:?}", entry_id, e);
+            return false;
+        }
+    }
+
+    let event = serde_json::json!({
+        "event_type": "WAITLIST_SEATS_OFFERED",
+        "waitlist_entry_id": entry_id,
+        "customer_id": entry["customer_id"],
+        "customer_email": entry["customer_email"],
+        "flight_id": flight_id,
+        "flight_number": metadata["flight_number"],
+        "departure_time": metadata["departure_time"],
+        "seats": entry["seats"],
+        "offer_id": offer.id,
+        "total_nuc": offer.total.minor_units(),
+        "offer_expires_at": expires_at,
+        "timestamp": Utc::now().timestamp(),
+    });
+    if let Err(e) = crate::notifier::notify(state, &entry_id.to_string(), &event).await {
+        tracing::warn!("Waitlist entry {} offered seats but the customer was not told: {}", entry_id, e);
+    }
+    true
+}
+
+/// The catalog a flight is priced from: the sandbox's for sandbox flights
+async fn catalog_airline_of(state: &AppState, flight: &serde_json::Value) -> String {
+    let sandbox = match state.catalog_cache.airline(&state.sandbox.airline_code).await {
+        Ok(Some(airline)) => airline["id"].as_str().map(str::to_string),
+        _ => None,
+    };
+    let is_sandbox = sandbox.is_some() && flight["airline_id"].as_str() == sandbox.as_deref();
+    crate::sandbox::catalog_airline(state, is_sandbox).to_string()
+}
+
+// ============================================================================
+// Back Office
+// ============================================================================
+
+/// GET /v1/admin/airlines/:airline_id/waitlists
+/// Waitlist depth on each of the airline's flights, longest line first
+pub async fn list_waitlists(
+    State(state): State<AppState>,
+    Path(airline_id): Path<Uuid>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let depth = state.waitlist_repo.waitlist_depth(airline_id).await.map_err(|e| {
+        tracing::error!("Failed to read waitlist depth of airline {}: {:?}", airline_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(depth))
+}
+
+/// GET /v1/admin/flights/:id/waitlist
+/// The flight's line in order, customers with seats on offer first
+pub async fn get_flight_waitlist(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantContext>,
+    Path(flight_id): Path<Uuid>,
+) -> Result<Json<Vec<WaitlistEntryResponse>>, StatusCode> {
+    authorize_product(&state, &tenant, flight_id).await?;
+    let entries = state.waitlist_repo.list_waitlist(flight_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(entries.into_iter().map(to_response).collect::<Result<_, _>>()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_join() {
+        let now = Utc::now();
+        let flight = serde_json::json!({
+            "product_type": "FLIGHT",
+            "is_active": true,
+            "deleted_at": null,
+            "metadata": {"departure_time": (now + chrono::Duration::days(3)).to_rfc3339()},
+        });
+
+        assert_eq!(check_join(&flight, 2, 9, Some(1), now), Ok(()));
+        assert_eq!(check_join(&flight, 1, 9, Some(1), now), Err(StatusCode::CONFLICT), "a seat is free to book");
+        assert_eq!(check_join(&flight, 1, 9, None, now), Err(StatusCode::CONFLICT), "untracked flights never sell out");
+        assert_eq!(check_join(&flight, 10, 9, Some(0), now), Err(StatusCode::UNPROCESSABLE_ENTITY));
+        assert_eq!(check_join(&flight, 0, 9, Some(0), now), Err(StatusCode::UNPROCESSABLE_ENTITY));
+
+        let departed = now + chrono::Duration::days(4);
+        assert_eq!(check_join(&flight, 1, 9, Some(0), departed), Err(StatusCode::NOT_FOUND));
+        let mut bag = flight.clone();
+        bag["product_type"] = serde_json::json!("BAG");
+        assert_eq!(check_join(&bag, 1, 9, Some(0), now), Err(StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use altis_store::app_config::WarmupConfig;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Outcome of one warmup step, reported on /health/ready
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupStep {
+    pub name: &'static str,
+    /// Entries loaded (keys, products, rules, inventory counters)
+    pub loaded: usize,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Tracks startup cache warming. The instance reports not-ready until the
+/// warmup has finished or timed out, so it only joins the load balancer once
+/// the first wave of traffic can be served from cache.
+#[derive(Debug, Default)]
+pub struct Warmup {
+    complete: AtomicBool,
+    steps: RwLock<Vec<WarmupStep>>,
+}
+
+impl Warmup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::Acquire)
+    }
+
+    pub fn steps(&self) -> Vec<WarmupStep> {
+        self.steps.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn record(&self, step: WarmupStep) {
+        self.steps.write().unwrap_or_else(|e| e.into_inner()).push(step);
+    }
+
+    fn finish(&self) {
+        self.complete.store(true, Ordering::Release);
+    }
+}
+
+/// Loads JWT keys, ranking experiments, catalogs, pricing rules,
+/// upcoming-flight seat counters and ancillary stock, then marks the instance ready. Failed steps
+/// are logged and left to load lazily; they never keep the instance out of
+/// rotation past the timeout.
+pub async fn run_warmup(state: AppState, config: WarmupConfig) {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(config.timeout_seconds);
+
+    if tokio::time::timeout(timeout, warm_all(&state, &config)).await.is_err() {
+        tracing::warn!("Cache warmup timed out after {:?}; reporting ready with a partially warm cache", timeout);
+    } else {
+        tracing::info!("Cache warmup finished in {:?}", started.elapsed());
+    }
+    state.warmup.finish();
+}
+
+async fn warm_all(state: &AppState, config: &WarmupConfig) {
+    step(state, "auth_keys", state.auth.keys.warm()).await;
+    step(state, "experiments", crate::experiments::load_experiments(state)).await;
+
+    let airline_ids = step(state, "catalog", warm_catalog(state)).await.unwrap_or_default();
+    step(state, "pricing_rules", warm_pricing_rules(state, &airline_ids)).await;
+    step(state, "flight_availability", seed_flight_availability(state, &airline_ids, config.flight_lookahead_hours)).await;
+    step(state, "ancillary_inventory", seed_ancillary_inventory(state, &airline_ids)).await;
+}
+
+/// Runs one step and records it. The loaded count is the step's `usize`, or
+/// the length of the ids it returns for later steps.
+async fn step<T, F>(state: &AppState, name: &'static str, work: F) -> Option<T>
+where
+    T: Loaded,
+    F: std::future::Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let started = Instant::now();
+    let result = work.await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    let (loaded, error) = match &result {
+        Ok(value) => {
+            tracing::info!("Warmup {}: loaded {} in {}ms", name, value.loaded(), elapsed_ms);
+            (value.loaded(), None)
+        }
+        Err(e) => {
+            tracing::warn!("Warmup {} failed after {}ms: {}", name, elapsed_ms, e);
+            (0, Some(e.to_string()))
+        }
+    };
+    state.warmup.record(WarmupStep { name, loaded, elapsed_ms, error });
+    result.ok()
+}
+
+trait Loaded {
+    fn loaded(&self) -> usize;
+}
+
+impl Loaded for usize {
+    fn loaded(&self) -> usize {
+        *self
+    }
+}
+
+impl Loaded for Vec<Uuid> {
+    fn loaded(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Caches every active airline with its products, plus the tax engine.
+/// Returns the airline ids for the later steps.
+async fn warm_catalog(state: &AppState) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
+    let airlines = state.catalog_repo.list_active_airlines().await?;
+    let mut airline_ids = Vec::with_capacity(airlines.len());
+
+    for airline in &airlines {
+        let Some(code) = airline["code"].as_str() else { continue };
+        let Some(id) = airline["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+        state.catalog_cache.airline(code).await?;
+        state.catalog_cache.products(id).await?;
+        airline_ids.push(id);
+    }
+    state.catalog_cache.tax_engine().await?;
+
+    Ok(airline_ids)
+}
+
+async fn warm_pricing_rules(state: &AppState, airline_ids: &[Uuid]) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let mut loaded = 0;
+    for airline_id in airline_ids {
+        loaded += state.catalog_cache.pricing_rules(*airline_id).await?.len();
+    }
+    Ok(loaded)
+}
+
+/// Starts inventory tracking for active flights departing within the
+/// lookahead, from the catalog's `available_seats`. Flights already tracked
+/// are live and left alone; without a record, holds on the flight aren't
+/// tracked at all.
+async fn seed_flight_availability(
+    state: &AppState,
+    airline_ids: &[Uuid],
+    lookahead_hours: i64,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let now = chrono::Utc::now();
+    let horizon = now + chrono::Duration::hours(lookahead_hours);
+    let mut seeded = 0;
+
+    for airline_id in airline_ids {
+        let products = state.catalog_cache.products(*airline_id).await?;
+        for product in products.iter() {
+            if product["product_type"].as_str() != Some("FLIGHT") || product["is_active"].as_bool() == Some(false) {
+                continue;
+            }
+            let metadata = &product["metadata"];
+            let departs = metadata["departure_time"].as_str()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&chrono::Utc));
+            let (Some(departs), Some(seats), Some(product_id)) = (departs, metadata["available_seats"].as_i64(), product["id"].as_str().and_then(|id| Uuid::parse_str(id).ok())) else {
+                continue;
+            };
+            if departs < now || departs > horizon {
+                continue;
+            }
+
+            if state.inventory.initialize(product_id, seats as i32).await? {
+                seeded += 1;
+            }
+        }
+    }
+
+    Ok(seeded)
+}
+
+/// Starts inventory tracking for capacity-limited ancillaries: a product's
+/// own `metadata.capacity`, else its inventory rule's `capacity`, plus the
+/// rule's overbooking allowance. Ancillaries with neither stay unlimited.
+/// Like flights, products already tracked keep their live counts, so a
+/// changed capacity applies once the old record is gone.
+pub(crate) async fn seed_ancillary_inventory(
+    state: &AppState,
+    airline_ids: &[Uuid],
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let mut seeded = 0;
+
+    for airline_id in airline_ids {
+        let rules = state.catalog_cache.inventory_rules(*airline_id).await?;
+        let products = state.catalog_cache.products(*airline_id).await?;
+        for product in products.iter() {
+            let Some(product_type) = product["product_type"].as_str() else { continue };
+            if product_type == "FLIGHT" || product["is_active"].as_bool() == Some(false) {
+                continue;
+            }
+            let Some(product_id) = product["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) else { continue };
+            let rule = rules.iter().find(|rule| rule["resource_type"].as_str() == Some(product_type));
+            let capacity = product["metadata"]["capacity"].as_i64()
+                .or_else(|| rule.and_then(|rule| rule["capacity"].as_i64()));
+            let Some(capacity) = capacity else { continue };
+            let overbooking = rule.and_then(|rule| rule["overbooking_percentage"].as_f64()).unwrap_or(0.0);
+
+            let units = altis_catalog::inventory::sellable_capacity(capacity.clamp(0, i32::MAX as i64) as i32, overbooking);
+            if state.inventory.initialize(product_id, units).await? {
+                seeded += 1;
+            }
+        }
+    }
+
+    Ok(seeded)
+}
@@ -17,24 +17,55 @@ pub struct StripeWebhook {
 
 #[derive(Debug, Deserialize)]
 pub struct WebhookData {
-    pub object: PaymentIntentObject,
+    pub object: StripeObject,
 }
 
+/// The event's subject: a payment intent, or a dispute for `charge.dispute.*`
+/// events, which also carry the fields below `metadata`
 #[derive(Debug, Deserialize)]
-pub struct PaymentIntentObject {
+pub struct StripeObject {
     pub id: String,
     pub status: String,
     pub metadata: Option<serde_json::Value>,
+    pub payment_intent: Option<String>,
+    pub amount: Option<i64>,
+    pub currency: Option<String>,
+    pub reason: Option<String>,
+    pub evidence_details: Option<EvidenceDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvidenceDetails {
+    /// Unix seconds
+    pub due_by: Option<i64>,
 }
 
 /// POST /v1/webhooks/payments/stripe
-/// Receive payment status updates from Stripe
+/// Receive payment status updates and disputes from Stripe
 pub async fn handle_stripe_webhook(
     State(state): State<AppState>,
     Json(payload): Json<StripeWebhook>,
 ) -> Result<StatusCode, StatusCode> {
     tracing::info!("Received webhook: {} for intent {}", payload.type_, payload.data.object.id);
 
+    if payload.type_.starts_with("charge.dispute.") {
+        let dispute = payload.data.object;
+        let (Some(payment_intent_id), Some(amount_nuc)) = (dispute.payment_intent, dispute.amount) else {
+            tracing::warn!("Dispute {} names no payment intent or amount", dispute.id);
+            return Ok(StatusCode::OK);
+        };
+        crate::disputes::record_reported_dispute(&state, crate::disputes::ReportedDispute {
+            status: altis_order::disputes::DisputeStatus::from_stripe(&dispute.status),
+            provider_dispute_id: dispute.id,
+            payment_intent_id,
+            amount_nuc,
+            currency: dispute.currency.map_or("NUC".to_string(), |currency| currency.to_uppercase()),
+            reason: dispute.reason,
+            evidence_due_by: dispute.evidence_details.and_then(|details| details.due_by).and_then(|due_by| chrono::DateTime::from_timestamp(due_by, 0)),
+        }).await?;
+        return Ok(StatusCode::OK);
+    }
+
     if payload.type_ == "payment_intent.succeeded" || payload.type_ == "payment_intent.payment_failed" || payload.type_ == "payment_intent.canceled" {
         let intent_id = &payload.data.object.id;
         
@@ -42,35 +73,39 @@ pub async fn handle_stripe_webhook(
         let intent = state.payment_orchestrator.process_status_update(intent_id).await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+        let order = state.order_repo.get_order(intent.order_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .and_then(|order| serde_json::from_value::<crate::orders::OrderResponse>(order).ok());
+        let Some(order) = order else {
+            tracing::warn!("Webhook for intent {} names unknown order {}", intent.id, intent.order_id);
+            return Ok(StatusCode::OK);
+        };
+
         if intent.status == PaymentStatus::Succeeded {
-            // 2. Mark order as PAID
-            state.order_repo.update_order_status(intent.order_id, "PAID").await
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-            
-            tracing::info!("Order {} marked as PAID via webhook", intent.order_id);
+            // 2. Mark order as PAID: paid straight from the intent, or after a 3-D Secure
+            // challenge. A redelivered webhook finds the order already PAID.
+            if crate::orders::complete_payment(&state, &order, &["PROPOSED", "PAYMENT_PENDING"], intent.reference.as_deref(), "Order paid via webhook").await? {
+                tracing::info!("Order {} marked as PAID via webhook", intent.order_id);
+            } else if matches!(order.status.as_str(), "EXPIRED" | "CANCELLED") {
+                // Authenticated after the order was given up; the money goes back
+                let refunded = match intent.money() {
+                    Ok(amount) => state.payment_orchestrator.refund_payment(intent.order_id, amount, &format!("late_{}", intent.id)).await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                if let Err(e) = refunded {
+                    tracing::error!("Order {} is {} but payment {} succeeded and was not refunded: {}", intent.order_id, order.status, intent.id, e);
+                }
+            }
         } else if intent.status == PaymentStatus::Failed || intent.status == PaymentStatus::Canceled {
             // 2. Mark order as CANCELLED and release inventory
-            state.order_repo.update_order_status(intent.order_id, "CANCELLED").await
+            let cancelled = state.order_repo.transition_order_status(intent.order_id, &["PROPOSED", "PAYMENT_PENDING"], "CANCELLED").await
                 .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-            // 3. Release inventory (Reuse cancellation logic)
-            if let Ok(Some(order_json)) = state.order_repo.get_order(intent.order_id).await {
-                if let Ok(order) = serde_json::from_value::<crate::orders::OrderResponse>(order_json) {
-                    for item in &order.items {
-                        if item.product_type == "Flight" {
-                            if let Some(product_id) = item.product_id {
-                                let pid_str = product_id.to_string();
-                                let current = state.redis.get_flight_availability(&pid_str).await
-                                    .unwrap_or(Some(0))
-                                    .unwrap_or(0);
-                                let _ = state.redis.set_flight_availability(&pid_str, current + 1).await;
-                            }
-                        }
-                    }
-                }
+            // 3. Release inventory (Reuse cancellation logic); payment never went through
+            if cancelled {
+                crate::orders::return_inventory(&state, &order.items, false).await;
+                tracing::info!("Order {} marked as CANCELLED and inventory released via webhook due to payment {:?}", intent.order_id, intent.status);
             }
-            
-            tracing::info!("Order {} marked as CANCELLED and inventory released via webhook due to payment {:?}", intent.order_id, intent.status);
         }
     }
 
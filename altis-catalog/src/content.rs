@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An image or video shown with a product
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Media {
+    pub url: String,
+    /// IMAGE or VIDEO
+    #[serde(default = "default_media_kind")]
+    pub kind: String,
+    pub alt: Option<String>,
+}
+
+fn default_media_kind() -> String {
+    "IMAGE".to_string()
+}
+
+/// An airline's wording and media for one product in one language. Unset
+/// fields fall back to the catalog product's own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalizedContent {
+    pub product_id: Uuid,
+    /// BCP 47 tag, e.g. "fr" or "pt-BR"
+    pub locale: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub media: Vec<Media>,
+}
+
+/// Canonical form of a language tag: lowercase language, uppercase region
+/// ("pt-br" -> "pt-BR"). None for anything that isn't a plain language or
+/// language-region tag.
+pub fn normalize_locale(tag: &str) -> Option<String> {
+    let mut parts = tag.trim().split(['-', '_']);
+    let language = parts.next().filter(|l| (2..=3).contains(&l.len()) && l.chars().all(|c| c.is_ascii_alphabetic()))?;
+    let region = parts.next();
+    if parts.next().is_some() {
+        return None;
+    }
+    match region {
+        None => Some(language.to_ascii_lowercase()),
+        Some(region) if region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Some(format!("{}-{}", language.to_ascii_lowercase(), region.to_ascii_uppercase()))
+        }
+        Some(_) => None,
+    }
+}
+
+/// The languages an `Accept-Language` header asks for, most wanted first.
+/// Wildcards, refused (`q=0`) and malformed entries are dropped.
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut weighted: Vec<(String, f32)> = header.split(',').filter_map(|entry| {
+        let mut parts = entry.split(';');
+        let locale = normalize_locale(parts.next()?)?;
+        let quality = parts
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+        (quality > 0.0).then_some((locale, quality))
+    }).collect();
+    // Stable, so equally weighted languages keep the order they were sent in
+    weighted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut languages: Vec<String> = Vec::new();
+    for (locale, _) in weighted {
+        if !languages.contains(&locale) {
+            languages.push(locale);
+        }
+    }
+    languages
+}
+
+/// One airline's localized content for all its products
+#[derive(Debug, Clone, Default)]
+pub struct ProductContent {
+    by_product: HashMap<Uuid, Vec<LocalizedContent>>,
+}
+
+impl ProductContent {
+    pub fn new(entries: Vec<LocalizedContent>) -> Self {
+        let mut by_product: HashMap<Uuid, Vec<LocalizedContent>> = HashMap::new();
+        for entry in entries {
+            by_product.entry(entry.product_id).or_default().push(entry);
+        }
+        Self { by_product }
+    }
+
+    /// The best content for `languages`, in order of preference. Each
+    /// language matches its exact locale first and then its bare language,
+    /// so "fr-CA" is served "fr" content before a less wanted language is tried.
+    pub fn resolve(&self, product_id: Uuid, languages: &[String]) -> Option<&LocalizedContent> {
+        let entries = self.by_product.get(&product_id)?;
+        languages.iter().find_map(|wanted| {
+            let language = wanted.split('-').next().unwrap_or(wanted);
+            entries.iter().find(|entry| entry.locale.eq_ignore_ascii_case(wanted))
+                .or_else(|| entries.iter().find(|entry| entry.locale.eq_ignore_ascii_case(language)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(product_id: Uuid, locale: &str, name: &str) -> LocalizedContent {
+        LocalizedContent { product_id, locale: locale.to_string(), name: Some(name.to_string()), description: None, media: vec![] }
+    }
+
+    #[test]
+    fn test_accept_language_resolves_to_the_closest_translation() {
+        assert_eq!(parse_accept_language("fr-ca, en;q=0.8, de;q=0.9, *;q=0.1, es;q=0"), vec!["fr-CA", "de", "en"]);
+        assert_eq!(parse_accept_language("pt_br;q=0.5, bogus-tag-here, en"), vec!["en", "pt-BR"]);
+        assert!(parse_accept_language("").is_empty());
+        assert_eq!(normalize_locale("ZH-tw"), Some("zh-TW".to_string()));
+        assert_eq!(normalize_locale("english"), None);
+
+        let product = Uuid::new_v4();
+        let catalog = ProductContent::new(vec![
+            content(product, "fr", "Siège avant"),
+            content(product, "pt-BR", "Assento dianteiro"),
+            content(product, "de", "Vordersitz"),
+        ]);
+        let name = |languages: &[&str]| {
+            let languages: Vec<String> = languages.iter().map(|l| l.to_string()).collect();
+            catalog.resolve(product, &languages).and_then(|c| c.name.clone())
+        };
+        assert_eq!(name(&["fr-CA", "de"]).as_deref(), Some("Siège avant"));
+        assert_eq!(name(&["pt-BR"]).as_deref(), Some("Assento dianteiro"));
+        // Only an exact or bare-language match counts; pt-PT doesn't get pt-BR wording
+        assert_eq!(name(&["pt-PT", "de"]).as_deref(), Some("Vordersitz"));
+        assert_eq!(name(&["ja"]), None);
+        assert_eq!(catalog.resolve(Uuid::new_v4(), &["fr".to_string()]), None);
+    }
+}
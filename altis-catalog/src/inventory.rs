@@ -1,9 +1,10 @@
+use chrono::NaiveDate;
 use uuid::Uuid;
-use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
-/// Inventory tracking for products
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Inventory tracking for products. Every unit is either available, reserved
+/// by an unpaid order, or sold (the rest of `total_capacity`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InventoryItem {
     pub product_id: Uuid,
     pub available_quantity: i32,
@@ -11,94 +12,66 @@ pub struct InventoryItem {
     pub reserved_quantity: i32,
 }
 
-/// In-memory inventory manager (will integrate with Redis later)
-pub struct InventoryManager {
-    inventory: HashMap<Uuid, InventoryItem>,
-}
-
-impl InventoryManager {
-    pub fn new() -> Self {
-        Self {
-            inventory: HashMap::new(),
-        }
-    }
-    
-    /// Initialize inventory for a product
-    pub fn initialize(&mut self, product_id: Uuid, total_capacity: i32) {
-        self.inventory.insert(product_id, InventoryItem {
-            product_id,
-            available_quantity: total_capacity,
-            total_capacity,
-            reserved_quantity: 0,
-        });
-    }
-    
-    /// Get current inventory
-    pub fn get(&self, product_id: &Uuid) -> Option<&InventoryItem> {
-        self.inventory.get(product_id)
-    }
-    
-    /// Reserve inventory (for offer creation)
-    pub fn reserve(&mut self, product_id: &Uuid, quantity: i32) -> Result<(), InventoryError> {
-        let item = self.inventory.get_mut(product_id)
-            .ok_or_else(|| InventoryError::NotFound(product_id.to_string()))?;
-        
-        if item.available_quantity < quantity {
-            return Err(InventoryError::InsufficientInventory {
-                requested: quantity,
-                available: item.available_quantity,
-            });
+impl InventoryItem {
+    /// Share of capacity no longer on sale, reserved or sold
+    pub fn utilization(&self) -> f64 {
+        if self.total_capacity == 0 {
+            0.0
+        } else {
+            1.0 - (self.available_quantity as f64 / self.total_capacity as f64)
         }
-        
-        item.available_quantity -= quantity;
-        item.reserved_quantity += quantity;
-        
-        Ok(())
     }
-    
-    /// Release reserved inventory (offer expired)
-    pub fn release(&mut self, product_id: &Uuid, quantity: i32) -> Result<(), InventoryError> {
-        let item = self.inventory.get_mut(product_id)
-            .ok_or_else(|| InventoryError::NotFound(product_id.to_string()))?;
-        
-        item.available_quantity += quantity;
-        item.reserved_quantity = item.reserved_quantity.saturating_sub(quantity);
-        
-        Ok(())
-    }
-    
-    /// Commit reserved inventory (offer accepted)
-    pub fn commit(&mut self, product_id: &Uuid, quantity: i32) -> Result<(), InventoryError> {
-        let item = self.inventory.get_mut(product_id)
-            .ok_or_else(|| InventoryError::NotFound(product_id.to_string()))?;
-        
-        if item.reserved_quantity < quantity {
-            return Err(InventoryError::InsufficientReserved {
-                requested: quantity,
-                reserved: item.reserved_quantity,
-            });
-        }
-        
-        item.reserved_quantity -= quantity;
-        
-        Ok(())
-    }
-    
-    /// Get utilization percentage
-    pub fn get_utilization(&self, product_id: &Uuid) -> Option<f64> {
-        self.inventory.get(product_id).map(|item| {
-            if item.total_capacity == 0 {
-                0.0
-            } else {
-                1.0 - (item.available_quantity as f64 / item.total_capacity as f64)
-            }
-        })
+
+    pub fn sold_quantity(&self) -> i32 {
+        (self.total_capacity - self.available_quantity - self.reserved_quantity).max(0)
     }
 }
 
-impl Default for InventoryManager {
-    fn default() -> Self {
-        Self::new()
+/// Units put on sale for a physical capacity, including the share an
+/// inventory rule allows to be oversold. Rounds down.
+pub fn sellable_capacity(capacity: i32, overbooking_percentage: f64) -> i32 {
+    let overbooked = (capacity.max(0) as f64 * (1.0 + overbooking_percentage.max(0.0) / 100.0)).floor();
+    overbooked.min(i32::MAX as f64) as i32
+}
+
+/// An airline's rule for holding one resource type (FLIGHT, SEAT, BAG, ...)
+/// on unpaid orders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryRule {
+    pub resource_type: String,
+    pub hold_duration_seconds: u64,
+    /// Unpaid orders one customer may hold with the resource at once
+    #[serde(default)]
+    pub max_holds_per_customer: Option<i64>,
+    /// Departure dates the resource can't be held for
+    #[serde(default)]
+    pub blackout_dates: Vec<NaiveDate>,
+}
+
+/// Why an inventory rule refuses a hold
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HoldRefusal {
+    #[error("{resource_type} can't be held for departures on {date}")]
+    Blackout { resource_type: String, date: NaiveDate },
+
+    #[error("customer already holds {limit} unpaid order(s) with {resource_type}")]
+    TooManyHolds { resource_type: String, limit: i64 },
+}
+
+impl InventoryRule {
+    /// Whether one more hold is allowed for a departure on `departure`, from
+    /// a customer who already has `customer_holds` unpaid orders with the
+    /// resource. Items without a known departure date skip the blackout check.
+    pub fn check_hold(&self, departure: Option<NaiveDate>, customer_holds: i64) -> Result<(), HoldRefusal> {
+        if let Some(date) = departure.filter(|date| self.blackout_dates.contains(date)) {
+            return Err(HoldRefusal::Blackout { resource_type: self.resource_type.clone(), date });
+        }
+        match self.max_holds_per_customer {
+            Some(limit) if customer_holds >= limit => {
+                Err(HoldRefusal::TooManyHolds { resource_type: self.resource_type.clone(), limit })
+            }
+            _ => Ok(()),
+        }
     }
 }
 
@@ -118,6 +91,9 @@ pub enum InventoryError {
         requested: i32,
         reserved: i32,
     },
+
+    #[error("Inventory store unavailable: {0}")]
+    Unavailable(String),
 }
 
 #[cfg(test)]
@@ -125,25 +101,45 @@ mod tests {
     use super::*;
     
     #[test]
-    fn test_inventory_lifecycle() {
-        let mut manager = InventoryManager::new();
-        let product_id = Uuid::new_v4();
-        
-        // Initialize
-        manager.initialize(product_id, 100);
-        assert_eq!(manager.get(&product_id).unwrap().available_quantity, 100);
-        
-        // Reserve
-        manager.reserve(&product_id, 10).unwrap();
-        assert_eq!(manager.get(&product_id).unwrap().available_quantity, 90);
-        assert_eq!(manager.get(&product_id).unwrap().reserved_quantity, 10);
-        
-        // Commit
-        manager.commit(&product_id, 10).unwrap();
-        assert_eq!(manager.get(&product_id).unwrap().reserved_quantity, 0);
-        
-        // Check utilization
-        let utilization = manager.get_utilization(&product_id).unwrap();
-        assert!((utilization - 0.1).abs() < 0.01); // 10% utilized
+    fn test_utilization() {
+        let item = InventoryItem {
+            product_id: Uuid::new_v4(),
+            available_quantity: 90,
+            total_capacity: 100,
+            reserved_quantity: 4,
+        };
+        assert!((item.utilization() - 0.1).abs() < 0.01); // 10% utilized
+        assert_eq!(item.sold_quantity(), 6);
+
+        let empty = InventoryItem { total_capacity: 0, available_quantity: 0, ..item };
+        assert_eq!(empty.utilization(), 0.0);
+    }
+
+    #[test]
+    fn test_sellable_capacity() {
+        assert_eq!(sellable_capacity(40, 0.0), 40);
+        assert_eq!(sellable_capacity(40, 10.0), 44);
+        assert_eq!(sellable_capacity(25, 10.0), 27); // 27.5 rounds down
+        assert_eq!(sellable_capacity(-5, 10.0), 0);
+    }
+
+    #[test]
+    fn test_inventory_rule_holds() {
+        let christmas = NaiveDate::from_ymd_opt(2026, 12, 25).unwrap();
+        let rule = InventoryRule {
+            resource_type: "SEAT".to_string(),
+            hold_duration_seconds: 600,
+            max_holds_per_customer: Some(2),
+            blackout_dates: vec![christmas],
+        };
+
+        assert!(rule.check_hold(christmas.succ_opt(), 1).is_ok());
+        assert!(rule.check_hold(None, 0).is_ok());
+        assert!(matches!(rule.check_hold(Some(christmas), 0), Err(HoldRefusal::Blackout { .. })));
+        assert_eq!(
+            rule.check_hold(None, 2),
+            Err(HoldRefusal::TooManyHolds { resource_type: "SEAT".to_string(), limit: 2 })
+        );
+        assert!(InventoryRule { max_holds_per_customer: None, ..rule }.check_hold(None, 50).is_ok());
     }
 }
@@ -1,7 +1,13 @@
 pub mod product;
 pub mod pricing;
 pub mod inventory;
+pub mod tax;
+pub mod content;
+pub mod market;
 
-pub use product::{Product, ProductType, ProductTrait};
-pub use pricing::{PricingContext, PricingEngine};
-pub use inventory::InventoryManager;
+pub use product::{DeliveryPolicy, Product, ProductType, ProductTrait, RecognitionPolicy};
+pub use pricing::{Campaign, PassengerType, PricingContext, PricingEngine};
+pub use inventory::{HoldRefusal, InventoryError, InventoryItem, InventoryRule};
+pub use tax::{TaxCode, TaxEngine, TaxLine};
+pub use content::{LocalizedContent, Media, ProductContent};
+pub use market::{MarketType, Route, RouteNetwork, RouteScopeError};
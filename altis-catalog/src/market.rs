@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Whether a route stays within one country
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MarketType {
+    Domestic,
+    International,
+}
+
+impl MarketType {
+    pub fn between(origin_country: &str, destination_country: &str) -> Self {
+        if origin_country.eq_ignore_ascii_case(destination_country) {
+            MarketType::Domestic
+        } else {
+            MarketType::International
+        }
+    }
+}
+
+/// An origin and destination the airline sells, with what pricing and
+/// taxes need to know about the market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    /// IATA airport codes
+    pub origin: String,
+    pub destination: String,
+    /// IATA city codes (LON for LHR), where the airport has one
+    pub origin_city: Option<String>,
+    pub destination_city: Option<String>,
+    /// ISO 3166 countries of the two airports
+    pub origin_country: String,
+    pub destination_country: String,
+    /// Great-circle distance
+    pub distance_km: i32,
+    pub market: MarketType,
+    /// ISO 4217 currency fares on the route are sold in
+    pub sale_currency: String,
+    pub is_active: bool,
+}
+
+/// Why an origin or destination can't be used to scope a rule
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RouteScopeError {
+    #[error("airport {0} is not on any of the airline's routes")]
+    UnknownAirport(String),
+    #[error("the airline doesn't fly {0}-{1}")]
+    UnknownRoute(String, String),
+}
+
+/// The airline's active routes, by airport pair
+#[derive(Debug, Clone, Default)]
+pub struct RouteNetwork {
+    routes: HashMap<(String, String), Route>,
+}
+
+impl RouteNetwork {
+    pub fn new(routes: Vec<Route>) -> Self {
+        let routes = routes.into_iter()
+            .filter(|route| route.is_active)
+            .map(|route| ((route.origin.to_uppercase(), route.destination.to_uppercase()), route))
+            .collect();
+        Self { routes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    pub fn get(&self, origin: &str, destination: &str) -> Option<&Route> {
+        self.routes.get(&(origin.to_uppercase(), destination.to_uppercase()))
+    }
+
+    /// Whether any route starts or ends at the airport or city
+    pub fn serves(&self, code: &str) -> bool {
+        let is = |airport: &str, city: &Option<String>| {
+            airport.eq_ignore_ascii_case(code) || city.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(code))
+        };
+        self.routes.values().any(|route| is(&route.origin, &route.origin_city) || is(&route.destination, &route.destination_city))
+    }
+
+    /// Checks the origin and destination a rule or campaign is limited to:
+    /// each must be served, and together they must be a route
+    pub fn check_scope(&self, origin: Option<&str>, destination: Option<&str>) -> Result<(), RouteScopeError> {
+        for code in [origin, destination].into_iter().flatten() {
+            if !self.serves(code) {
+                return Err(RouteScopeError::UnknownAirport(code.to_uppercase()));
+            }
+        }
+        if let (Some(origin), Some(destination)) = (origin, destination) {
+            let flown = |from: &str, code: &str, city: &Option<String>| {
+                from.eq_ignore_ascii_case(code) || city.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(code))
+            };
+            let connected = self.routes.values().any(|route| {
+                flown(&route.origin, origin, &route.origin_city) && flown(&route.destination, destination, &route.destination_city)
+            });
+            if !connected {
+                return Err(RouteScopeError::UnknownRoute(origin.to_uppercase(), destination.to_uppercase()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(origin: &str, origin_city: &str, destination: &str, origin_country: &str, destination_country: &str) -> Route {
+        Route {
+            id: Uuid::new_v4(),
+            airline_id: Uuid::nil(),
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            origin_city: Some(origin_city.to_string()),
+            destination_city: None,
+            origin_country: origin_country.to_string(),
+            destination_country: destination_country.to_string(),
+            distance_km: 1000,
+            market: MarketType::between(origin_country, destination_country),
+            sale_currency: "SGD".to_string(),
+            is_active: true,
+        }
+    }
+
+    #[test]
+    fn test_scope_must_be_flown() {
+        let network = RouteNetwork::new(vec![route("LHR", "LON", "CDG", "GB", "FR"), route("JFK", "NYC", "LAX", "US", "US")]);
+        assert_eq!(network.get("jfk", "lax").map(|r| r.market), Some(MarketType::Domestic));
+        assert_eq!(network.get("LHR", "CDG").map(|r| r.market), Some(MarketType::International));
+
+        assert!(network.check_scope(Some("LHR"), None).is_ok());
+        // A city stands for its airports
+        assert!(network.check_scope(Some("LON"), Some("CDG")).is_ok());
+        assert_eq!(network.check_scope(Some("SIN"), None), Err(RouteScopeError::UnknownAirport("SIN".to_string())));
+        // Both airports are served, but not by one route
+        assert_eq!(network.check_scope(Some("LHR"), Some("LAX")), Err(RouteScopeError::UnknownRoute("LHR".to_string(), "LAX".to_string())));
+    }
+}
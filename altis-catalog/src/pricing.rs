@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use altis_shared::money::{Money, MoneyError, Rounding};
+use uuid::Uuid;
+
+use crate::market::MarketType;
+use crate::product::ProductType;
 
 /// Context for pricing calculations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +24,10 @@ pub struct PricingContext {
     /// Demand-based multiplier
     pub demand_multiplier: Option<f64>,
     
+    /// Domestic or international, when the route is known
+    #[serde(default)]
+    pub market: Option<MarketType>,
+    
     /// Additional context metadata
     pub metadata: serde_json::Value,
 }
@@ -31,15 +40,81 @@ impl Default for PricingContext {
             user_segment: None,
             time_multiplier: Some(1.0),
             demand_multiplier: Some(1.0),
+            market: None,
             metadata: serde_json::json!({}),
         }
     }
 }
 
+/// Passenger type code (PTC) a fare is priced for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PassengerType {
+    #[serde(rename = "ADT")]
+    Adult,
+    /// 2 to 11 on the day of travel
+    #[serde(rename = "CHD")]
+    Child,
+    /// Under 2, on an adult's lap: no seat of their own
+    #[serde(rename = "INF")]
+    Infant,
+}
+
+impl PassengerType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PassengerType::Adult => "ADT",
+            PassengerType::Child => "CHD",
+            PassengerType::Infant => "INF",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_uppercase().as_str() {
+            "ADT" => Some(PassengerType::Adult),
+            "CHD" => Some(PassengerType::Child),
+            "INF" => Some(PassengerType::Infant),
+            _ => None,
+        }
+    }
+}
+
+/// A time-boxed sale: a percentage off the products it covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    pub id: Uuid,
+    pub name: String,
+    pub discount_percentage: f64,
+    /// Empty covers every product type
+    #[serde(default)]
+    pub product_types: Vec<ProductType>,
+    /// None matches any airport
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Promotional copy and artwork for the storefront, passed through as is
+    #[serde(default)]
+    pub banner: serde_json::Value,
+}
+
+impl Campaign {
+    pub fn covers(&self, product_type: &ProductType, origin: Option<&str>, destination: Option<&str>, at: DateTime<Utc>) -> bool {
+        let airport = |scope: &Option<String>, airport: Option<&str>| {
+            scope.as_deref().is_none_or(|scope| airport.is_some_and(|a| a.eq_ignore_ascii_case(scope)))
+        };
+        (self.starts_at..self.ends_at).contains(&at)
+            && (self.product_types.is_empty() || self.product_types.contains(product_type))
+            && airport(&self.origin, origin)
+            && airport(&self.destination, destination)
+    }
+}
+
 /// Continuous pricing engine
 pub struct PricingEngine {
     /// Base configuration
     config: PricingConfig,
+    /// Sales running for the airline being priced
+    campaigns: Vec<Campaign>,
 }
 
 use std::collections::HashMap;
@@ -60,6 +135,30 @@ pub struct PricingConfig {
     
     /// Multipliers for different user segments (e.g., "premium" => 1.2)
     pub segment_multipliers: HashMap<String, f64>,
+    
+    /// Multipliers for routes within one country and across borders
+    #[serde(default = "unit_multiplier")]
+    pub domestic_multiplier: f64,
+    #[serde(default = "unit_multiplier")]
+    pub international_multiplier: f64,
+
+    /// Child and infant fares, as a percentage of the adult fare
+    #[serde(default = "default_child_fare_percentage")]
+    pub child_fare_percentage: f64,
+    #[serde(default = "default_infant_fare_percentage")]
+    pub infant_fare_percentage: f64,
+}
+
+fn unit_multiplier() -> f64 {
+    1.0
+}
+
+fn default_child_fare_percentage() -> f64 {
+    75.0
+}
+
+fn default_infant_fare_percentage() -> f64 {
+    10.0
 }
 
 impl Default for PricingConfig {
@@ -77,15 +176,47 @@ impl Default for PricingConfig {
                 m.insert("leisure".to_string(), 0.95);
                 m
             },
+            domestic_multiplier: 1.0,
+            international_multiplier: 1.0,
+            child_fare_percentage: default_child_fare_percentage(),
+            infant_fare_percentage: default_infant_fare_percentage(),
         }
     }
 }
 
 impl PricingEngine {
     pub fn new(config: PricingConfig) -> Self {
-        Self { config }
+        Self { config, campaigns: Vec::new() }
+    }
+
+    pub fn with_campaigns(mut self, campaigns: Vec<Campaign>) -> Self {
+        self.campaigns = campaigns;
+        self
+    }
+
+    /// The deepest campaign discount covering a product on the route at `at`;
+    /// campaigns don't stack
+    pub fn best_campaign(&self, product_type: &ProductType, origin: Option<&str>, destination: Option<&str>, at: DateTime<Utc>) -> Option<&Campaign> {
+        self.campaigns.iter()
+            .filter(|c| c.covers(product_type, origin, destination, at))
+            .max_by(|a, b| a.discount_percentage.total_cmp(&b.discount_percentage))
+    }
+
+    /// Takes a campaign's discount off a price, rounding in the customer's favour
+    pub fn apply_campaign(&self, price: Money, campaign: &Campaign) -> Result<Money, MoneyError> {
+        price.scale(1.0 - campaign.discount_percentage / 100.0, Rounding::Down)
     }
     
+    /// What a passenger of `ptc` pays where an adult pays `adult_fare`,
+    /// rounding in the customer's favour
+    pub fn passenger_fare(&self, adult_fare: Money, ptc: PassengerType) -> Result<Money, MoneyError> {
+        match ptc {
+            PassengerType::Adult => Ok(adult_fare),
+            PassengerType::Child => adult_fare.scale(self.config.child_fare_percentage / 100.0, Rounding::Down),
+            PassengerType::Infant => adult_fare.scale(self.config.infant_fare_percentage / 100.0, Rounding::Down),
+        }
+    }
+
     /// Calculate continuous price adjustment based on demand
     pub fn calculate_demand_multiplier(&self, available_inventory: i32, total_capacity: i32) -> f64 {
         if total_capacity == 0 {
@@ -118,10 +249,10 @@ impl PricingEngine {
         }
     }
     
-    /// Apply continuous pricing adjustment (by cents)
-    pub fn apply_continuous_adjustment(&self, base_price: i32, context: &PricingContext) -> i32 {
+    /// Apply continuous pricing adjustment, rounded to `min_adjustment_cents`
+    pub fn apply_continuous_adjustment(&self, base_price: Money, context: &PricingContext) -> Result<Money, MoneyError> {
         if !self.config.enable_continuous {
-            return base_price;
+            return Ok(base_price);
         }
         
         // Start with demand multiplier if present in context, or 1.0
@@ -139,14 +270,22 @@ impl PricingEngine {
             }
         }
         
-        let adjusted = (base_price as f64 * multiplier) as i32;
+        // Factor in the market
+        multiplier *= match context.market {
+            Some(MarketType::Domestic) => self.config.domestic_multiplier,
+            Some(MarketType::International) => self.config.international_multiplier,
+            None => 1.0,
+        };
+        
+        let adjusted = base_price.scale(multiplier, Rounding::HalfUp)?;
         
-        // Round to nearest cent
-        let remainder = adjusted % self.config.min_adjustment_cents;
-        if remainder >= self.config.min_adjustment_cents / 2 {
-            adjusted + (self.config.min_adjustment_cents - remainder)
+        // Round to the nearest adjustment step
+        let step = Money::new(self.config.min_adjustment_cents.max(1) as i64, adjusted.currency());
+        let remainder = Money::new(adjusted.minor_units().rem_euclid(step.minor_units()), adjusted.currency());
+        if !remainder.is_zero() && remainder.minor_units() * 2 >= step.minor_units() {
+            adjusted.checked_add(step)?.checked_sub(remainder)
         } else {
-            adjusted - remainder
+            adjusted.checked_sub(remainder)
         }
     }
 }
@@ -172,10 +311,61 @@ mod tests {
     fn test_continuous_adjustment() {
         let engine = PricingEngine::new(PricingConfig::default());
         
-        let base_price = 10000; // $100.00
-        let adjusted = engine.apply_continuous_adjustment(base_price, 1.234);
+        let base_price = Money::nuc(10000); // $100.00
+        let context = PricingContext { demand_multiplier: Some(1.234), ..Default::default() };
+        let adjusted = engine.apply_continuous_adjustment(base_price, &context).unwrap();
         
         // Should be rounded to nearest cent
-        assert_eq!(adjusted, 12340);
+        assert_eq!(adjusted, Money::nuc(12340));
+    }
+
+    #[test]
+    fn test_market_multiplier() {
+        let engine = PricingEngine::new(PricingConfig { international_multiplier: 1.1, ..Default::default() });
+        let priced = |market| engine.apply_continuous_adjustment(Money::nuc(10000), &PricingContext { market, ..Default::default() }).unwrap();
+
+        assert_eq!(priced(Some(MarketType::International)), Money::nuc(11000));
+        assert_eq!(priced(Some(MarketType::Domestic)), Money::nuc(10000));
+        assert_eq!(priced(None), Money::nuc(10000));
+    }
+
+    #[test]
+    fn test_passenger_fare() {
+        let engine = PricingEngine::new(PricingConfig::default());
+        let adult = Money::nuc(19999);
+
+        assert_eq!(engine.passenger_fare(adult, PassengerType::Adult).unwrap(), adult);
+        assert_eq!(engine.passenger_fare(adult, PassengerType::Child).unwrap(), Money::nuc(14999));
+        assert_eq!(engine.passenger_fare(adult, PassengerType::Infant).unwrap(), Money::nuc(1999));
+        assert_eq!(PassengerType::from_code("chd"), Some(PassengerType::Child));
+        assert_eq!(serde_json::json!(PassengerType::Infant), "INF");
+    }
+
+    #[test]
+    fn test_best_campaign() {
+        let now = Utc::now();
+        let campaign = |discount_percentage, product_types, origin: Option<&str>| Campaign {
+            id: Uuid::new_v4(),
+            name: format!("{}% off", discount_percentage),
+            discount_percentage,
+            product_types,
+            origin: origin.map(str::to_string),
+            destination: None,
+            starts_at: now - chrono::Duration::days(1),
+            ends_at: now + chrono::Duration::days(1),
+            banner: serde_json::json!({}),
+        };
+        let engine = PricingEngine::new(PricingConfig::default()).with_campaigns(vec![
+            campaign(10.0, vec![], None),
+            campaign(25.0, vec![ProductType::Bag], Some("SIN")),
+        ]);
+
+        let best = engine.best_campaign(&ProductType::Bag, Some("sin"), Some("BKK"), now).unwrap();
+        assert_eq!(best.discount_percentage, 25.0);
+        assert_eq!(engine.apply_campaign(Money::nuc(3999), best).unwrap(), Money::nuc(2999));
+
+        assert_eq!(engine.best_campaign(&ProductType::Bag, Some("KUL"), Some("BKK"), now).unwrap().discount_percentage, 10.0);
+        assert_eq!(engine.best_campaign(&ProductType::Flight, Some("SIN"), None, now).unwrap().discount_percentage, 10.0);
+        assert!(engine.best_campaign(&ProductType::Flight, Some("SIN"), None, now + chrono::Duration::days(2)).is_none(), "over");
     }
 }
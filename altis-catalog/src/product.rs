@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use async_trait::async_trait;
 use crate::pricing::PricingContext;
+use altis_shared::money::{Money, MoneyError, Rounding};
 
 /// Product types in the catalog
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,11 +41,88 @@ pub struct Product {
     pub metadata: serde_json::Value,
 }
 
+/// When a product's fulfillment artifact (barcode, voucher code) is delivered.
+/// Configured per product under `metadata.delivery_policy`; products without one
+/// are delivered on payment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(tag = "trigger", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryPolicy {
+    #[default]
+    OnPayment,
+    /// e.g. wifi codes 24h before departure
+    BeforeDeparture { hours: i64 },
+}
+
+impl DeliveryPolicy {
+    pub fn from_metadata(metadata: &serde_json::Value) -> Self {
+        serde_json::from_value(metadata["delivery_policy"].clone()).unwrap_or_default()
+    }
+
+    /// Scheduled delivery time, or `None` to deliver right away. Deadlines that
+    /// have already passed (late bookings) are delivered right away too.
+    pub fn deliver_at(
+        &self,
+        departure: Option<chrono::DateTime<chrono::Utc>>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            DeliveryPolicy::OnPayment => None,
+            DeliveryPolicy::BeforeDeparture { hours } => departure
+                .map(|d| d - chrono::Duration::hours(*hours))
+                .filter(|at| *at > now),
+        }
+    }
+}
+
+/// When a product's revenue is earned. Configured per product under
+/// `metadata.recognition_policy`; otherwise flights earn as their barcodes
+/// are scanned, insurance when its cover starts, and other ancillaries
+/// when their flight departs, used or not.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "trigger", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RecognitionPolicy {
+    /// Only as each unit is consumed
+    OnConsumption,
+    /// At `metadata.coverage_start`, or departure when it has none
+    CoverageStart,
+    /// `hours` after departure (0: as the flight leaves)
+    AtDeparture { hours: i64 },
+}
+
+impl RecognitionPolicy {
+    pub fn for_item(product_type: &str, metadata: &serde_json::Value) -> Self {
+        serde_json::from_value(metadata["recognition_policy"].clone()).unwrap_or_else(|_| {
+            match product_type.to_ascii_uppercase().as_str() {
+                "FLIGHT" => RecognitionPolicy::OnConsumption,
+                "INSURANCE" => RecognitionPolicy::CoverageStart,
+                _ => RecognitionPolicy::AtDeparture { hours: 0 },
+            }
+        })
+    }
+
+    /// When whatever is left unearned is recognized without a scan; `None`
+    /// when only consumption earns it, or the trigger time isn't known
+    pub fn recognize_at(
+        &self,
+        metadata: &serde_json::Value,
+        departure: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            RecognitionPolicy::OnConsumption => None,
+            RecognitionPolicy::CoverageStart => metadata["coverage_start"].as_str()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&chrono::Utc))
+                .or(departure),
+            RecognitionPolicy::AtDeparture { hours } => departure.map(|d| d + chrono::Duration::hours(*hours)),
+        }
+    }
+}
+
 /// Product trait for dynamic pricing
 #[async_trait]
 pub trait ProductTrait: Send + Sync {
     /// Calculate the current price based on context
-    async fn calculate_price(&self, context: &PricingContext) -> Result<i32, ProductError>;
+    async fn calculate_price(&self, context: &PricingContext) -> Result<Money, ProductError>;
     
     /// Check if product is available
     async fn is_available(&self, context: &PricingContext) -> Result<bool, ProductError>;
@@ -67,6 +145,9 @@ pub enum ProductError {
     
     #[error("Pricing calculation failed: {0}")]
     PricingFailed(String),
+
+    #[error("Price out of range: {0}")]
+    Money(#[from] MoneyError),
 }
 
 /// Flight-specific product
@@ -84,9 +165,9 @@ pub struct FlightProduct {
 
 #[async_trait]
 impl ProductTrait for FlightProduct {
-    async fn calculate_price(&self, context: &PricingContext) -> Result<i32, ProductError> {
+    async fn calculate_price(&self, context: &PricingContext) -> Result<Money, ProductError> {
         // Base price
-        let mut price = self.product.base_price_nuc;
+        let mut price = Money::nuc(self.product.base_price_nuc as i64);
         
         // Apply demand multiplier based on available seats
         let demand_multiplier = if self.available_seats < 10 {
@@ -97,11 +178,11 @@ impl ProductTrait for FlightProduct {
             1.0
         };
         
-        price = (price as f64 * demand_multiplier) as i32;
+        price = price.scale(demand_multiplier, Rounding::Down)?;
         
         // Apply time-based pricing
         if let Some(time_multiplier) = context.time_multiplier {
-            price = (price as f64 * time_multiplier) as i32;
+            price = price.scale(time_multiplier, Rounding::Down)?;
         }
         
         Ok(price)
@@ -126,12 +207,12 @@ pub struct AncillaryProduct {
 
 #[async_trait]
 impl ProductTrait for AncillaryProduct {
-    async fn calculate_price(&self, context: &PricingContext) -> Result<i32, ProductError> {
-        let mut price = self.product.base_price_nuc;
+    async fn calculate_price(&self, context: &PricingContext) -> Result<Money, ProductError> {
+        let mut price = Money::nuc(self.product.base_price_nuc as i64);
         
         // Apply bundle discount if part of an offer
         if context.is_bundled {
-            price = (price as f64 * 0.9) as i32; // 10% bundle discount
+            price = price.scale(0.9, Rounding::Down)?; // 10% bundle discount
         }
         
         Ok(price)
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use altis_shared::money::{Money, Rounding};
+use serde::{Deserialize, Serialize};
+
+use crate::market::MarketType;
+
+/// A tax levied by a jurisdiction on matching products and routes, e.g. UK
+/// Air Passenger Duty on flights departing GB. Unset filters match anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxCode {
+    /// Short code shown on tickets and receipts (e.g. "GB-APD", "US-SEG")
+    pub code: String,
+    pub name: String,
+    /// ISO 3166 country collecting the tax
+    pub jurisdiction: String,
+    /// Catalog product type (FLIGHT, BAG, ...); None taxes every product
+    pub product_type: Option<String>,
+    pub origin_country: Option<String>,
+    pub destination_country: Option<String>,
+    /// Domestic or international routes only; None taxes both
+    #[serde(default)]
+    pub market: Option<MarketType>,
+    /// Percentage of the price, as a fraction (0.07 = 7%)
+    pub rate: Option<f64>,
+    /// Fixed amount per unit
+    pub amount_nuc: Option<i32>,
+}
+
+/// One tax applied to one item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxLine {
+    pub code: String,
+    pub name: String,
+    pub jurisdiction: String,
+    pub amount_nuc: i32,
+}
+
+/// Works out the taxes due on an item from its product type and the
+/// countries its route starts and ends in.
+#[derive(Debug, Clone, Default)]
+pub struct TaxEngine {
+    codes: Vec<TaxCode>,
+    /// Airport (IATA) to country (ISO 3166) lookup
+    airports: HashMap<String, String>,
+}
+
+impl TaxEngine {
+    pub fn new(codes: Vec<TaxCode>, airports: HashMap<String, String>) -> Self {
+        let airports = airports.into_iter().map(|(iata, country)| (iata.to_uppercase(), country.to_uppercase())).collect();
+        Self { codes, airports }
+    }
+
+    /// Explicit `origin_country`/`destination_country` metadata wins over the
+    /// airport lookup.
+    fn route_countries(&self, metadata: &serde_json::Value) -> (Option<String>, Option<String>) {
+        let country = |country_key: &str, airport_key: &str| {
+            metadata[country_key].as_str().map(str::to_uppercase).or_else(|| {
+                metadata[airport_key].as_str().and_then(|iata| self.airports.get(&iata.to_uppercase()).cloned())
+            })
+        };
+        (country("origin_country", "origin"), country("destination_country", "destination"))
+    }
+
+    /// The route's market as stamped from the airline's routes, else from
+    /// its two countries when both are known
+    fn market(metadata: &serde_json::Value, origin: &Option<String>, destination: &Option<String>) -> Option<MarketType> {
+        serde_json::from_value(metadata["market"].clone()).ok().or_else(|| match (origin, destination) {
+            (Some(origin), Some(destination)) => Some(MarketType::between(origin, destination)),
+            _ => None,
+        })
+    }
+
+    pub fn calculate(&self, product_type: &str, metadata: &serde_json::Value, price_nuc: i32, quantity: i32) -> Vec<TaxLine> {
+        let (origin, destination) = self.route_countries(metadata);
+        let market = Self::market(metadata, &origin, &destination);
+        let matches = |filter: &Option<String>, value: &Option<String>| match filter {
+            None => true,
+            Some(filter) => value.as_deref().is_some_and(|v| v.eq_ignore_ascii_case(filter)),
+        };
+
+        self.codes.iter()
+            .filter(|code| matches(&code.product_type, &Some(product_type.to_string())))
+            .filter(|code| matches(&code.origin_country, &origin) && matches(&code.destination_country, &destination))
+            .filter(|code| code.market.is_none_or(|m| market == Some(m)))
+            .filter_map(|code| {
+                let price = Money::nuc(price_nuc as i64);
+                let percentage = code.rate.map_or(Ok(Money::nuc(0)), |rate| price.scale(rate, Rounding::HalfUp));
+                let fixed = Money::nuc(code.amount_nuc.unwrap_or(0) as i64).checked_mul(quantity.max(1) as i64);
+                // An amount that can't be represented is a misconfigured code, not a tax to charge
+                let amount_nuc = percentage.and_then(|p| p.checked_add(fixed?)).and_then(|a| a.to_i32()).ok()?;
+                (amount_nuc > 0).then(|| TaxLine {
+                    code: code.code.clone(),
+                    name: code.name.clone(),
+                    jurisdiction: code.jurisdiction.clone(),
+                    amount_nuc,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> TaxEngine {
+        let code = |code: &str, product_type: Option<&str>, origin: Option<&str>, destination: Option<&str>, rate: Option<f64>, amount_nuc: Option<i32>| TaxCode {
+            code: code.to_string(),
+            name: code.to_string(),
+            jurisdiction: code[..2].to_string(),
+            product_type: product_type.map(str::to_string),
+            origin_country: origin.map(str::to_string),
+            destination_country: destination.map(str::to_string),
+            market: None,
+            rate,
+            amount_nuc,
+        };
+        let airports = [("LHR", "GB"), ("JFK", "US"), ("LAX", "US"), ("SIN", "SG")]
+            .into_iter()
+            .map(|(a, c)| (a.to_string(), c.to_string()))
+            .collect();
+
+        TaxEngine::new(vec![
+            code("GB-APD", Some("FLIGHT"), Some("GB"), None, None, Some(1300)),
+            code("US-SEG", Some("FLIGHT"), Some("US"), Some("US"), None, Some(520)),
+            code("SG-GST", None, Some("SG"), None, Some(0.09), None),
+        ], airports)
+    }
+
+    #[test]
+    fn test_route_and_product_matching() {
+        let engine = engine();
+
+        let lhr_jfk = engine.calculate("FLIGHT", &serde_json::json!({ "origin": "LHR", "destination": "JFK" }), 50000, 1);
+        assert_eq!(lhr_jfk.iter().map(|t| t.code.as_str()).collect::<Vec<_>>(), vec!["GB-APD"]);
+
+        // Segment tax only on domestic US flights
+        let jfk_lax = engine.calculate("FLIGHT", &serde_json::json!({ "origin": "JFK", "destination": "LAX" }), 30000, 2);
+        assert_eq!(jfk_lax, vec![TaxLine { code: "US-SEG".into(), name: "US-SEG".into(), jurisdiction: "US".into(), amount_nuc: 1040 }]);
+
+        // Product-agnostic percentage tax applies to ancillaries too
+        let bag = engine.calculate("BAG", &serde_json::json!({ "origin_country": "sg" }), 3500, 1);
+        assert_eq!(bag[0].amount_nuc, 315);
+
+        // Unknown route: nothing due
+        assert!(engine.calculate("FLIGHT", &serde_json::json!({}), 50000, 1).is_empty());
+    }
+
+    #[test]
+    fn test_market_scoped_codes() {
+        let international = TaxCode {
+            code: "SG-INT".to_string(),
+            name: "International departure levy".to_string(),
+            jurisdiction: "SG".to_string(),
+            product_type: Some("FLIGHT".to_string()),
+            origin_country: Some("SG".to_string()),
+            destination_country: None,
+            market: Some(MarketType::International),
+            rate: None,
+            amount_nuc: Some(1000),
+        };
+        let engine = TaxEngine::new(vec![international], [("SIN".to_string(), "SG".to_string())].into());
+
+        // Known from the route's market, or from its two countries
+        assert_eq!(engine.calculate("FLIGHT", &serde_json::json!({ "origin": "SIN", "market": "INTERNATIONAL" }), 20000, 1).len(), 1);
+        assert_eq!(engine.calculate("FLIGHT", &serde_json::json!({ "origin": "SIN", "destination_country": "MY" }), 20000, 1).len(), 1);
+        assert!(engine.calculate("FLIGHT", &serde_json::json!({ "origin": "SIN", "destination_country": "SG" }), 20000, 1).is_empty());
+        // Unknown market: a scoped code doesn't apply
+        assert!(engine.calculate("FLIGHT", &serde_json::json!({ "origin": "SIN" }), 20000, 1).is_empty());
+    }
+}
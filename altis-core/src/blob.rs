@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+/// Storage for opaque artifacts (evidence bundles, exports) addressed by key.
+/// Keys are `/`-separated paths, e.g. `evidence/<order_id>/<timestamp>.zip`.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Stores the bytes under `key`, replacing any existing blob.
+    /// Returns a URI identifying the stored object.
+    async fn put(
+        &self,
+        key: &str,
+        bytes: Vec<u8>,
+        content_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get(
+        &self,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>>;
+}
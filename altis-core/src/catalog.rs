@@ -0,0 +1,18 @@
+//! Catalog changes announced to every API instance, each of which keeps
+//! its own in-memory copy of the catalog that searches price from.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kafka topic carrying [`CatalogChangedEvent`]s
+pub const CATALOG_CHANGED_TOPIC: &str = "catalog.changed";
+
+/// Products, pricing rules, campaigns or tax codes were written; cached
+/// copies of the catalog are out of date
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CatalogChangedEvent {
+    /// The instance that made the change, which has dropped its copy already
+    pub instance_id: Uuid,
+    /// What changed, for the logs
+    pub reason: String,
+}
@@ -0,0 +1,70 @@
+//! Service delivery exchanged with airport departure control (DCS), in the
+//! shape of ONE Order ServiceDeliveryRQ: what each checked-in passenger is
+//! entitled to on a segment going out, and confirmations of what was
+//! actually delivered coming back.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kafka topic carrying [`ServiceDeliveryRecord`]s to the DCS
+pub const SERVICE_DELIVERY_TOPIC: &str = "dcs.service.delivery";
+
+/// Kafka topic the DCS publishes [`ServiceDeliveredEvent`]s on
+pub const SERVICE_DELIVERED_TOPIC: &str = "dcs.service.delivered";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeliveryPassenger {
+    pub traveler_index: i32,
+    /// ADT, CHD or INF
+    pub ptc: String,
+    pub given_name: Option<String>,
+    pub surname: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeliverySegment {
+    /// Our catalog product
+    pub flight_id: Uuid,
+    pub flight_number: String,
+    pub origin: String,
+    pub destination: String,
+    pub departure_time: String,
+    pub seat_number: Option<String>,
+}
+
+/// One order item the passenger may use on the segment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceEntitlement {
+    pub order_item_id: Uuid,
+    /// FLIGHT, SEAT, BAG, MEAL, ...
+    pub service_type: String,
+    pub service_code: Option<String>,
+    pub quantity: i32,
+    /// The fulfillment barcode the service is redeemed with, once issued
+    pub barcode: Option<String>,
+}
+
+/// A checked-in passenger on one segment, with everything they're entitled to on it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceDeliveryRecord {
+    pub order_id: Uuid,
+    pub booking_reference: String,
+    pub passenger: DeliveryPassenger,
+    pub segment: DeliverySegment,
+    pub entitlements: Vec<ServiceEntitlement>,
+    pub checked_in_at: DateTime<Utc>,
+}
+
+/// The DCS delivered a service: a boarded flight, a loaded bag, a served meal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceDeliveredEvent {
+    pub order_id: Uuid,
+    pub order_item_id: Uuid,
+    /// Narrows the confirmation to one fulfillment record of the item
+    #[serde(default)]
+    pub barcode: Option<String>,
+    pub delivered_at: DateTime<Utc>,
+    #[serde(default)]
+    pub station: Option<String>,
+}
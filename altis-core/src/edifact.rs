@@ -0,0 +1,375 @@
+//! UN/EDIFACT for distribution channels that predate NDC: PADIS-style
+//! availability requests (PAOREQ) and replies (PAORES). Only the segments
+//! Altis reads or writes are modelled, with the default UNA separators.
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+/// Service string advice: component `:`, element `+`, decimal `.`, release `?`, terminator `'`
+pub const UNA: &str = "UNA:+.? '";
+
+const COMPONENT: char = ':';
+const ELEMENT: char = '+';
+const RELEASE: char = '?';
+const TERMINATOR: char = '\'';
+
+/// GDS availability displays count seats up to 9; "9" means nine or more
+pub const MAX_DISPLAYED_SEATS: i32 = 9;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum EdifactError {
+    #[error("Malformed interchange: {0}")]
+    Malformed(String),
+
+    #[error("Missing {0} segment")]
+    MissingSegment(&'static str),
+
+    #[error("Expected a {expected} message, got {found}")]
+    UnexpectedMessage {
+        expected: &'static str,
+        found: String,
+    },
+
+    #[error("Invalid {field}: '{value}'")]
+    InvalidValue {
+        field: &'static str,
+        value: String,
+    },
+}
+
+/// One segment: a tag followed by elements, each a list of components
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub tag: String,
+    pub elements: Vec<Vec<String>>,
+}
+
+impl Segment {
+    pub fn new(tag: &str, elements: &[&[&str]]) -> Self {
+        Self {
+            tag: tag.to_string(),
+            elements: elements.iter().map(|e| e.iter().map(|c| c.to_string()).collect()).collect(),
+        }
+    }
+
+    /// Component `component` of element `element`, both from 0 after the tag; empty when absent
+    pub fn component(&self, element: usize, component: usize) -> &str {
+        self.elements.get(element).and_then(|e| e.get(component)).map_or("", |c| c.as_str())
+    }
+
+    fn encode(&self) -> String {
+        let mut out = self.tag.clone();
+        for element in &self.elements {
+            out.push(ELEMENT);
+            out.push_str(&element.iter().map(|c| escape(c)).collect::<Vec<_>>().join(&COMPONENT.to_string()));
+        }
+        // Trailing empty elements are dropped, as EDIFACT expects
+        while out.ends_with(ELEMENT) || out.ends_with(COMPONENT) {
+            out.pop();
+        }
+        out.push(TERMINATOR);
+        out
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, COMPONENT | ELEMENT | RELEASE | TERMINATOR) {
+            out.push(RELEASE);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Splits an interchange into segments, honouring the release character.
+/// A leading UNA is skipped; other separator sets aren't supported.
+pub fn parse(interchange: &str) -> Result<Vec<Segment>, EdifactError> {
+    let body = interchange.trim_start();
+    let body = match body.strip_prefix("UNA") {
+        Some(rest) if rest.starts_with(&UNA[3..]) => &rest[UNA.len() - 3..],
+        Some(_) => return Err(EdifactError::Malformed("only the default UNA separators are supported".to_string())),
+        None => body,
+    };
+
+    let mut segments = Vec::new();
+    let mut elements: Vec<Vec<String>> = vec![vec![String::new()]];
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            RELEASE => {
+                let escaped = chars.next().ok_or_else(|| EdifactError::Malformed("dangling release character".to_string()))?;
+                elements.last_mut().and_then(|e| e.last_mut()).expect("never empty").push(escaped);
+            }
+            ELEMENT => elements.push(vec![String::new()]),
+            COMPONENT => elements.last_mut().expect("never empty").push(String::new()),
+            TERMINATOR => {
+                let mut finished = std::mem::replace(&mut elements, vec![vec![String::new()]]);
+                let tag = finished.remove(0).concat().trim().to_string();
+                if tag.is_empty() {
+                    return Err(EdifactError::Malformed("segment without a tag".to_string()));
+                }
+                segments.push(Segment { tag, elements: finished });
+            }
+            // Line breaks between segments are common and carry nothing
+            '\r' | '\n' if elements.len() == 1 && elements[0].len() == 1 && elements[0][0].is_empty() => {}
+            c => elements.last_mut().and_then(|e| e.last_mut()).expect("never empty").push(c),
+        }
+    }
+    if elements.len() > 1 || !elements[0][0].trim().is_empty() {
+        return Err(EdifactError::Malformed("last segment is not terminated".to_string()));
+    }
+    Ok(segments)
+}
+
+/// Who an interchange is between, and the references a reply echoes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub sender: String,
+    pub recipient: String,
+    /// UNB interchange control reference
+    pub control_ref: String,
+    /// UNH message reference
+    pub message_ref: String,
+}
+
+impl Envelope {
+    /// Reads UNB and UNH, checking the message is an `expected` one
+    pub fn read(segments: &[Segment], expected: &'static str) -> Result<Self, EdifactError> {
+        let unb = find(segments, "UNB")?;
+        let unh = find(segments, "UNH")?;
+        if unh.component(1, 0) != expected {
+            return Err(EdifactError::UnexpectedMessage { expected, found: unh.component(1, 0).to_string() });
+        }
+        Ok(Self {
+            sender: unb.component(1, 0).to_string(),
+            recipient: unb.component(2, 0).to_string(),
+            control_ref: unb.component(4, 0).to_string(),
+            message_ref: unh.component(0, 0).to_string(),
+        })
+    }
+
+    /// Wraps one message in UNA/UNB/UNH ... UNT/UNZ
+    pub fn wrap(&self, message_type: &str, body: &[Segment], sent_at: DateTime<Utc>) -> String {
+        let date = sent_at.format("%y%m%d").to_string();
+        let time = sent_at.format("%H%M").to_string();
+        let mut segments = vec![
+            Segment::new("UNB", &[&["IATA", "1"], &[&self.sender], &[&self.recipient], &[&date, &time], &[&self.control_ref]]),
+            Segment::new("UNH", &[&[&self.message_ref], &[message_type, "96", "2", "IA"]]),
+        ];
+        segments.extend_from_slice(body);
+        // UNT counts the message's segments, UNH and UNT included
+        let count = (body.len() + 2).to_string();
+        segments.push(Segment::new("UNT", &[&[&count], &[&self.message_ref]]));
+        segments.push(Segment::new("UNZ", &[&["1"], &[&self.control_ref]]));
+
+        let mut out = UNA.to_string();
+        for segment in &segments {
+            out.push_str(&segment.encode());
+        }
+        out
+    }
+
+    /// The envelope for a reply: parties swapped, references kept for correlation
+    pub fn reply(&self, sender: &str) -> Self {
+        Self {
+            sender: sender.to_string(),
+            recipient: self.sender.clone(),
+            control_ref: self.control_ref.clone(),
+            message_ref: self.message_ref.clone(),
+        }
+    }
+}
+
+fn find<'a>(segments: &'a [Segment], tag: &'static str) -> Result<&'a Segment, EdifactError> {
+    segments.iter().find(|s| s.tag == tag).ok_or(EdifactError::MissingSegment(tag))
+}
+
+// ============================================================================
+// PAOREQ / PAORES
+// ============================================================================
+
+/// A PAOREQ: flights on one city pair and date, for one carrier
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvailabilityRequest {
+    pub envelope: Envelope,
+    pub origin: String,
+    pub destination: String,
+    pub date: NaiveDate,
+    pub carrier: String,
+}
+
+impl AvailabilityRequest {
+    /// Reads `ODI+<origin>+<destination>'` and `TVL+<ddmmyy>+<origin>+<destination>+<carrier>'`
+    pub fn parse(interchange: &str) -> Result<Self, EdifactError> {
+        Self::from_segments(&parse(interchange)?)
+    }
+
+    pub fn from_segments(segments: &[Segment]) -> Result<Self, EdifactError> {
+        let envelope = Envelope::read(segments, "PAOREQ")?;
+        let odi = find(segments, "ODI")?;
+        let tvl = find(segments, "TVL")?;
+
+        let code = |field: &'static str, value: &str| {
+            let value = value.trim().to_uppercase();
+            if value.len() == 3 && value.chars().all(|c| c.is_ascii_alphabetic()) {
+                Ok(value)
+            } else {
+                Err(EdifactError::InvalidValue { field, value })
+            }
+        };
+        let raw_date = tvl.component(0, 0);
+        let date = NaiveDate::parse_from_str(raw_date, "%d%m%y")
+            .map_err(|_| EdifactError::InvalidValue { field: "date", value: raw_date.to_string() })?;
+        let carrier = tvl.component(3, 0).trim().to_uppercase();
+        if carrier.len() != 2 {
+            return Err(EdifactError::InvalidValue { field: "carrier", value: carrier });
+        }
+
+        Ok(Self {
+            envelope,
+            origin: code("origin", odi.component(0, 0))?,
+            destination: code("destination", odi.component(1, 0))?,
+            date,
+            carrier,
+        })
+    }
+}
+
+/// One flight line of a PAORES
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvailableFlight {
+    pub carrier: String,
+    pub flight_number: String,
+    pub origin: String,
+    pub destination: String,
+    pub departure: NaiveDateTime,
+    pub arrival: NaiveDateTime,
+    pub booking_class: String,
+    pub seats: i32,
+    /// Lowest fare in the booking class: amount in the currency's minor units, and currency
+    pub fare: Option<(i64, String)>,
+}
+
+/// Application errors a PAORES carries in its ERC segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvailabilityError {
+    /// The request couldn't be read
+    InvalidRequest,
+    /// The carrier isn't distributed over EDIFACT
+    CarrierNotOffered,
+    /// Nothing operates on the city pair and date
+    NoFlights,
+}
+
+impl AvailabilityError {
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::InvalidRequest => "1",
+            Self::CarrierNotOffered => "2",
+            Self::NoFlights => "3",
+        }
+    }
+}
+
+/// A PAORES: either flights with seats and fares, or an error
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvailabilityResponse {
+    Flights(Vec<AvailableFlight>),
+    Error(AvailabilityError),
+}
+
+impl AvailabilityResponse {
+    /// Per flight: `TVL+<dep ddmmyy>:<hhmm>:<arr ddmmyy>:<hhmm>+<origin>+<destination>+<carrier>+<number>'`,
+    /// `PDI++<class>:<seats>'` and, when priced, `MON+B:<amount>:<currency>'`.
+    pub fn encode(&self, envelope: &Envelope, sent_at: DateTime<Utc>) -> String {
+        let mut body = Vec::new();
+        match self {
+            Self::Error(error) => body.push(Segment::new("ERC", &[&[error.code()]])),
+            Self::Flights(flights) if flights.is_empty() => body.push(Segment::new("ERC", &[&[AvailabilityError::NoFlights.code()]])),
+            Self::Flights(flights) => {
+                for flight in flights {
+                    let times = [
+                        flight.departure.format("%d%m%y").to_string(),
+                        flight.departure.format("%H%M").to_string(),
+                        flight.arrival.format("%d%m%y").to_string(),
+                        flight.arrival.format("%H%M").to_string(),
+                    ];
+                    body.push(Segment::new("TVL", &[
+                        &[&times[0], &times[1], &times[2], &times[3]],
+                        &[&flight.origin],
+                        &[&flight.destination],
+                        &[&flight.carrier],
+                        &[&flight.flight_number],
+                    ]));
+                    let seats = flight.seats.clamp(0, MAX_DISPLAYED_SEATS).to_string();
+                    body.push(Segment::new("PDI", &[&[], &[&flight.booking_class, &seats]]));
+                    if let Some((amount, currency)) = &flight.fare {
+                        body.push(Segment::new("MON", &[&["B", &amount.to_string(), currency]]));
+                    }
+                }
+            }
+        }
+        envelope.wrap("PAORES", &body, sent_at)
+    }
+}
+
+/// Carries replies to a channel that doesn't take them on the request's
+/// own connection
+#[async_trait]
+pub trait EdifactTransport: Send + Sync {
+    /// Delivers one interchange to `channel`, the partner that asked for it
+    async fn send(
+        &self,
+        channel: &str,
+        interchange: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_availability_round_trip() {
+        let request = AvailabilityRequest::parse(
+            "UNA:+.? 'UNB+IATA:1+1AGDS+ALTIS+260301:0900+REF42'\nUNH+1+PAOREQ:96:2:IA'ODI+sin+KUL'TVL+150326+SIN+KUL+AL'UNT+4+1'UNZ+1+REF42'",
+        ).unwrap();
+        assert_eq!(request.origin, "SIN");
+        assert_eq!(request.date, NaiveDate::from_ymd_opt(2026, 3, 15).unwrap());
+        assert_eq!(request.carrier, "AL");
+        assert_eq!(request.envelope.control_ref, "REF42");
+
+        let departure = request.date.and_hms_opt(8, 0, 0).unwrap();
+        let response = AvailabilityResponse::Flights(vec![AvailableFlight {
+            carrier: "AL".to_string(),
+            flight_number: "123".to_string(),
+            origin: "SIN".to_string(),
+            destination: "KUL".to_string(),
+            departure,
+            arrival: departure + chrono::Duration::minutes(65),
+            booking_class: "Y".to_string(),
+            seats: 42,
+            fare: Some((12050, "SGD".to_string())),
+        }]);
+        let sent_at = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 5).unwrap();
+        let reply = response.encode(&request.envelope.reply("ALTIS"), sent_at);
+        assert_eq!(
+            reply,
+            "UNA:+.? 'UNB+IATA:1+ALTIS+1AGDS+260301:0900+REF42'UNH+1+PAORES:96:2:IA'\
+             TVL+150326:0800:150326:0905+SIN+KUL+AL+123'PDI++Y:9'MON+B:12050:SGD'UNT+5+1'UNZ+1+REF42'"
+        );
+
+        // Separators inside values survive a round trip
+        let segments = parse(&Segment::new("FTX", &[&["10:30 + ?"]]).encode()).unwrap();
+        assert_eq!(segments[0].component(0, 0), "10:30 + ?");
+
+        assert_eq!(
+            AvailabilityRequest::parse("UNB+IATA:1+X+Y+260301:0900+R'UNH+1+PAOREQ:96:2:IA'ODI+SIN+KUL'UNT+3+1'"),
+            Err(EdifactError::MissingSegment("TVL"))
+        );
+        assert!(matches!(parse("UNB+IATA:1+X"), Err(EdifactError::Malformed(_))));
+    }
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+
+/// A message ready to hand to a mail provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmailError {
+    /// The provider refused the message or address; sending again won't help
+    #[error("Email rejected: {0}")]
+    Rejected(String),
+    /// The provider couldn't be reached or deferred the message
+    #[error("Email provider unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Outbound email, behind whichever provider is configured (SMTP, SES)
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    /// Sends the message; returns the provider's id or response for it
+    async fn send(&self, message: &EmailMessage) -> Result<String, EmailError>;
+}
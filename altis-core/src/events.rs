@@ -1 +1,387 @@
+//! Versioned envelopes for the events we publish. Every payload is wrapped
+//! with its type and schema version, checked against the registered schema
+//! on the way out and on the way in, and upcast from older versions so a
+//! consumer only ever sees the current shape.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
 pub use altis_shared::events::SeatHeldEvent;
+use altis_shared::events::{ExperimentExposureEvent, OfferAcceptedEvent, OfferGeneratedEvent, OrderPaidEvent, SettlementEvent};
+
+use crate::catalog::CatalogChangedEvent;
+use crate::dcs::{ServiceDeliveredEvent, ServiceDeliveryRecord};
+use crate::flight_status::FlightStatusEvent;
+
+/// What goes on the wire
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventEnvelope {
+    pub event_type: String,
+    pub version: u32,
+    pub occurred_at: DateTime<Utc>,
+    pub payload: Value,
+}
+
+/// An event with a registered schema
+pub trait VersionedEvent: Serialize + DeserializeOwned {
+    const EVENT_TYPE: &'static str;
+    /// The version this struct serializes as; older ones are upcast to it
+    const VERSION: u32;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EventError {
+    #[error("no schema registered for {0}")]
+    UnknownType(String),
+    #[error("expected a {expected} event, got {actual}")]
+    WrongType { expected: String, actual: String },
+    #[error("{event_type} v{version} is not supported (current is v{current})")]
+    UnsupportedVersion { event_type: String, version: u32, current: u32 },
+    #[error("{event_type} v{version} does not match its schema: {reason}")]
+    Invalid { event_type: String, version: u32, reason: String },
+    #[error("malformed event: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Object,
+    Array,
+    /// Any JSON value
+    Any,
+}
+
+impl FieldType {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::Number => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Object => value.is_object(),
+            FieldType::Array => value.is_array(),
+            FieldType::Any => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: FieldType,
+    pub required: bool,
+}
+
+/// Top-level fields of one version of an event. Fields it doesn't list are
+/// let through, so adding an optional field doesn't need a new version.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventSchema {
+    pub event_type: &'static str,
+    pub version: u32,
+    pub fields: Vec<FieldSchema>,
+}
+
+impl EventSchema {
+    pub fn new(event_type: &'static str, version: u32) -> Self {
+        Self { event_type, version, fields: Vec::new() }
+    }
+
+    pub fn required(mut self, name: &'static str, kind: FieldType) -> Self {
+        self.fields.push(FieldSchema { name, kind, required: true });
+        self
+    }
+
+    pub fn optional(mut self, name: &'static str, kind: FieldType) -> Self {
+        self.fields.push(FieldSchema { name, kind, required: false });
+        self
+    }
+
+    pub fn validate(&self, payload: &Value) -> Result<(), EventError> {
+        let invalid = |reason: String| EventError::Invalid { event_type: self.event_type.to_string(), version: self.version, reason };
+        let Some(object) = payload.as_object() else {
+            return Err(invalid("payload is not an object".to_string()));
+        };
+        for field in &self.fields {
+            match object.get(field.name).filter(|v| !v.is_null()) {
+                None if field.required => return Err(invalid(format!("{} is missing", field.name))),
+                Some(value) if !field.kind.matches(value) => {
+                    return Err(invalid(format!("{} is not of type {:?}", field.name, field.kind)));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites a payload of one version into the next
+pub type Upcaster = fn(Value) -> Value;
+
+/// Schemas of every event version we have published, and how to bring each
+/// old version up to date
+#[derive(Default)]
+pub struct EventRegistry {
+    schemas: HashMap<(String, u32), EventSchema>,
+    upcasters: HashMap<(String, u32), Upcaster>,
+    current: HashMap<String, u32>,
+}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The newest version registered for a type is its current one
+    pub fn register(&mut self, schema: EventSchema) {
+        let current = self.current.entry(schema.event_type.to_string()).or_insert(schema.version);
+        *current = (*current).max(schema.version);
+        self.schemas.insert((schema.event_type.to_string(), schema.version), schema);
+    }
+
+    /// Registers how to turn a `from_version` payload into `from_version + 1`
+    pub fn register_upcaster(&mut self, event_type: &'static str, from_version: u32, upcaster: Upcaster) {
+        self.upcasters.insert((event_type.to_string(), from_version), upcaster);
+    }
+
+    pub fn current_version(&self, event_type: &str) -> Option<u32> {
+        self.current.get(event_type).copied()
+    }
+
+    /// Every registered schema, by type then version
+    pub fn schemas(&self) -> Vec<&EventSchema> {
+        let mut schemas: Vec<_> = self.schemas.values().collect();
+        schemas.sort_by_key(|s| (s.event_type, s.version));
+        schemas
+    }
+
+    pub fn validate(&self, event_type: &str, version: u32, payload: &Value) -> Result<(), EventError> {
+        let current = self.current_version(event_type).ok_or_else(|| EventError::UnknownType(event_type.to_string()))?;
+        let schema = self.schemas.get(&(event_type.to_string(), version))
+            .ok_or_else(|| EventError::UnsupportedVersion { event_type: event_type.to_string(), version, current })?;
+        schema.validate(payload)
+    }
+
+    /// Wraps an event for publishing, refusing one its own schema rejects
+    pub fn seal<E: VersionedEvent>(&self, event: &E) -> Result<EventEnvelope, EventError> {
+        let payload = serde_json::to_value(event)?;
+        self.validate(E::EVENT_TYPE, E::VERSION, &payload)?;
+        Ok(EventEnvelope {
+            event_type: E::EVENT_TYPE.to_string(),
+            version: E::VERSION,
+            occurred_at: Utc::now(),
+            payload,
+        })
+    }
+
+    /// Reads an event off the wire, upcasting it to `E`'s version. A bare
+    /// payload without an envelope, as published before envelopes, is taken
+    /// as version 1.
+    pub fn open<E: VersionedEvent>(&self, bytes: &[u8]) -> Result<E, EventError> {
+        let value: Value = serde_json::from_slice(bytes)?;
+        let (version, payload) = match serde_json::from_value::<EventEnvelope>(value.clone()) {
+            Ok(envelope) if envelope.event_type == E::EVENT_TYPE => (envelope.version, envelope.payload),
+            Ok(envelope) => return Err(EventError::WrongType { expected: E::EVENT_TYPE.to_string(), actual: envelope.event_type }),
+            Err(_) => (1, value),
+        };
+        let payload = self.upcast(E::EVENT_TYPE, version, E::VERSION, payload)?;
+        Ok(serde_json::from_value(payload)?)
+    }
+
+    /// Validates the payload as `version`, then steps it up one version at a
+    /// time to `target`, validating each step
+    pub fn upcast(&self, event_type: &str, version: u32, target: u32, mut payload: Value) -> Result<Value, EventError> {
+        if version > target {
+            return Err(EventError::UnsupportedVersion { event_type: event_type.to_string(), version, current: target });
+        }
+        self.validate(event_type, version, &payload)?;
+        for from in version..target {
+            let upcaster = self.upcasters.get(&(event_type.to_string(), from))
+                .ok_or_else(|| EventError::UnsupportedVersion { event_type: event_type.to_string(), version, current: target })?;
+            payload = upcaster(payload);
+            self.validate(event_type, from + 1, &payload)?;
+        }
+        Ok(payload)
+    }
+}
+
+/// The registry of every event this system publishes or consumes
+pub fn registry() -> &'static EventRegistry {
+    static REGISTRY: LazyLock<EventRegistry> = LazyLock::new(standard_registry);
+    &REGISTRY
+}
+
+fn standard_registry() -> EventRegistry {
+    use FieldType::*;
+
+    let mut registry = EventRegistry::new();
+    registry.register(EventSchema::new(SeatHeldEvent::EVENT_TYPE, 1)
+        .required("flight_id", String)
+        .required("seat_number", String)
+        .required("trip_id", String)
+        .required("held_at", Integer));
+    registry.register(EventSchema::new(OfferGeneratedEvent::EVENT_TYPE, 1)
+        .required("offer_id", String)
+        .optional("customer_id", String)
+        .required("timestamp", Integer)
+        .required("search_context", Any)
+        .required("features", Any));
+    registry.register(EventSchema::new(OfferAcceptedEvent::EVENT_TYPE, 1)
+        .required("offer_id", String)
+        .optional("customer_id", String)
+        .required("timestamp", Integer));
+    registry.register(EventSchema::new(ExperimentExposureEvent::EVENT_TYPE, 1)
+        .optional("experiment", String)
+        .required("variant", String)
+        .required("bucket", Integer)
+        .required("strategy", String)
+        .required("subject", String)
+        .required("offer_ids", Array)
+        .required("cached", Boolean)
+        .required("timestamp", Integer));
+
+    // v2 names the currency of total_nuc; everything paid before it was NUC
+    let order_paid_v1 = EventSchema::new(OrderPaidEvent::EVENT_TYPE, 1)
+        .required("order_id", String)
+        .optional("offer_id", String)
+        .required("customer_id", String)
+        .required("total_nuc", Integer)
+        .required("timestamp", Integer);
+    let mut order_paid_v2 = order_paid_v1.clone().required("currency", String);
+    order_paid_v2.version = 2;
+    registry.register(order_paid_v1);
+    registry.register(order_paid_v2);
+    registry.register_upcaster(OrderPaidEvent::EVENT_TYPE, 1, |mut payload| {
+        payload["currency"] = "NUC".into();
+        payload
+    });
+
+    registry.register(EventSchema::new(SettlementEvent::EVENT_TYPE, 1)
+        .required("order_id", String)
+        .required("amount_nuc", Integer)
+        .required("currency", String)
+        .required("event_type", String)
+        .required("timestamp", Integer));
+    registry.register(EventSchema::new(FlightStatusEvent::EVENT_TYPE, 1)
+        .optional("flight_id", String)
+        .required("carrier", String)
+        .required("flight_number", String)
+        .required("departure_date", String)
+        .required("status", String)
+        .optional("delay_minutes", Integer)
+        .optional("cause", String));
+    registry.register(EventSchema::new(CatalogChangedEvent::EVENT_TYPE, 1)
+        .required("instance_id", String)
+        .required("reason", String));
+    registry.register(EventSchema::new(ServiceDeliveryRecord::EVENT_TYPE, 1)
+        .required("order_id", String)
+        .required("booking_reference", String)
+        .required("passenger", Object)
+        .required("segment", Object)
+        .required("entitlements", Array)
+        .required("checked_in_at", String));
+    registry.register(EventSchema::new(ServiceDeliveredEvent::EVENT_TYPE, 1)
+        .required("order_id", String)
+        .required("order_item_id", String)
+        .optional("barcode", String)
+        .required("delivered_at", String)
+        .optional("station", String));
+    registry
+}
+
+impl VersionedEvent for SeatHeldEvent {
+    const EVENT_TYPE: &'static str = "seat_held";
+    const VERSION: u32 = 1;
+}
+
+impl VersionedEvent for OfferGeneratedEvent {
+    const EVENT_TYPE: &'static str = "offer_generated";
+    const VERSION: u32 = 1;
+}
+
+impl VersionedEvent for OfferAcceptedEvent {
+    const EVENT_TYPE: &'static str = "offer_accepted";
+    const VERSION: u32 = 1;
+}
+
+impl VersionedEvent for ExperimentExposureEvent {
+    const EVENT_TYPE: &'static str = "experiment_exposure";
+    const VERSION: u32 = 1;
+}
+
+/// Kafka topic carrying [`OrderPaidEvent`]s alone, for consumers acting on a
+/// payment; the telemetry topic mixes it with every other offer event
+pub const ORDER_PAID_TOPIC: &str = "order.paid";
+
+impl VersionedEvent for OrderPaidEvent {
+    const EVENT_TYPE: &'static str = "order_paid";
+    const VERSION: u32 = 2;
+}
+
+impl VersionedEvent for SettlementEvent {
+    const EVENT_TYPE: &'static str = "settlement";
+    const VERSION: u32 = 1;
+}
+
+impl VersionedEvent for FlightStatusEvent {
+    const EVENT_TYPE: &'static str = "flight_status_changed";
+    const VERSION: u32 = 1;
+}
+
+impl VersionedEvent for CatalogChangedEvent {
+    const EVENT_TYPE: &'static str = "catalog_changed";
+    const VERSION: u32 = 1;
+}
+
+impl VersionedEvent for ServiceDeliveryRecord {
+    const EVENT_TYPE: &'static str = "service_delivery";
+    const VERSION: u32 = 1;
+}
+
+impl VersionedEvent for ServiceDeliveredEvent {
+    const EVENT_TYPE: &'static str = "service_delivered";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_old_order_paid_events_are_upcast_and_bad_ones_rejected() {
+        let registry = registry();
+        let order_id = Uuid::new_v4();
+
+        // Published before envelopes existed
+        let bare = serde_json::json!({ "order_id": order_id, "offer_id": null, "customer_id": "c-1", "total_nuc": 27000, "timestamp": 1 });
+        let event: OrderPaidEvent = registry.open(bare.to_string().as_bytes()).unwrap();
+        assert_eq!((event.order_id, event.currency.as_str()), (order_id, "NUC"));
+
+        let sealed = registry.seal(&event).unwrap();
+        assert_eq!((sealed.event_type.as_str(), sealed.version), ("order_paid", 2));
+        let reopened: OrderPaidEvent = registry.open(serde_json::to_string(&sealed).unwrap().as_bytes()).unwrap();
+        assert_eq!(reopened.total_nuc, 27000);
+
+        let mut newer = sealed.clone();
+        newer.version = 3;
+        assert!(matches!(registry.open::<OrderPaidEvent>(serde_json::to_string(&newer).unwrap().as_bytes()), Err(EventError::UnsupportedVersion { .. })));
+
+        let mut broken = sealed.clone();
+        broken.payload["total_nuc"] = "lots".into();
+        assert!(matches!(registry.open::<OrderPaidEvent>(serde_json::to_string(&broken).unwrap().as_bytes()), Err(EventError::Invalid { .. })));
+
+        let mut other = sealed;
+        other.event_type = "settlement".to_string();
+        assert!(matches!(registry.open::<OrderPaidEvent>(serde_json::to_string(&other).unwrap().as_bytes()), Err(EventError::WrongType { .. })));
+    }
+}
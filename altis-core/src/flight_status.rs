@@ -0,0 +1,53 @@
+//! Flight status changes reported by operations, which drive disruption
+//! handling without anyone going through the admin API.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kafka topic carrying [`FlightStatusEvent`]s, from operations systems and
+/// from the external feed adapter alike
+pub const FLIGHT_STATUS_TOPIC: &str = "flight.status.changed";
+
+/// One flight's new status
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FlightStatusEvent {
+    /// Our catalog product, when the publisher knows it; otherwise the flight
+    /// is found by carrier, number and date
+    #[serde(default)]
+    pub flight_id: Option<Uuid>,
+    pub carrier: String,
+    pub flight_number: String,
+    /// Scheduled departure date, local to the origin
+    pub departure_date: chrono::NaiveDate,
+    /// SCHEDULED, DELAYED, CANCELLED or DIVERTED
+    pub status: String,
+    #[serde(default)]
+    pub delay_minutes: Option<i64>,
+    /// Why, when operations say: WEATHER, ATC, TECHNICAL, CREW, ... Causes
+    /// outside the carrier's control don't earn EU261 compensation
+    #[serde(default)]
+    pub cause: Option<String>,
+}
+
+impl FlightStatusEvent {
+    /// Identifies the change rather than the message, so a change published
+    /// twice (or by two feed pollers) is handled once
+    pub fn dedup_key(&self) -> String {
+        format!(
+            "{}{}:{}:{}:{}",
+            self.carrier.to_uppercase(),
+            self.flight_number,
+            self.departure_date,
+            self.status,
+            self.delay_minutes.unwrap_or(0),
+        )
+    }
+}
+
+/// An external source of flight status, polled for changes
+#[async_trait]
+pub trait FlightStatusFeed: Send + Sync {
+    /// Changes reported since the previous poll
+    async fn poll(&self) -> Result<Vec<FlightStatusEvent>, Box<dyn std::error::Error + Send + Sync>>;
+}
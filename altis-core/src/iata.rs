@@ -64,6 +64,17 @@ pub struct NdcOfferItem {
 // ONE Order Models
 // ============================================================================
 
+/// Books a shopped offer with the carrier that owns it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrderCreateRequest {
+    pub party: Party,
+    pub offer_id: String,
+    pub owner: String,
+    pub offer_item_ids: Vec<String>,
+    pub travelers: Option<Vec<Traveler>>,
+    pub contact_info: Option<ContactInfo>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OrderRetrieveRequest {
     pub order_id: String,
@@ -98,8 +109,11 @@ pub struct Traveler {
     pub id: Option<Uuid>,
     pub traveler_index: i32,
     pub ptc: String, // ADT, CHD, etc.
+    #[serde(serialize_with = "altis_shared::pii::name")]
     pub first_name: altis_shared::pii::Masked<String>,
+    #[serde(serialize_with = "altis_shared::pii::name")]
     pub last_name: altis_shared::pii::Masked<String>,
+    #[serde(serialize_with = "altis_shared::pii::date_of_birth")]
     pub date_of_birth: Option<altis_shared::pii::Masked<String>>, // ISO date
     pub gender: Option<String>,
     pub traveler_did: Option<String>,
@@ -108,10 +122,33 @@ pub struct Traveler {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContactInfo {
+    #[serde(serialize_with = "altis_shared::pii::email")]
     pub email: altis_shared::pii::Masked<String>,
+    #[serde(serialize_with = "altis_shared::pii::phone")]
     pub phone: Option<altis_shared::pii::Masked<String>>,
+    #[serde(serialize_with = "altis_shared::pii::name")]
     pub first_name: Option<altis_shared::pii::Masked<String>>,
+    #[serde(serialize_with = "altis_shared::pii::name")]
     pub last_name: Option<altis_shared::pii::Masked<String>>,
 }
 
+impl ContactInfo {
+    /// Puts the phone number, if any, in E.164 form so alerts can be texted
+    /// to it; false when it isn't a number with a country code
+    pub fn normalize_phone(&mut self) -> bool {
+        let Some(phone) = &mut self.phone else { return true };
+        if phone.0.trim().is_empty() {
+            self.phone = None;
+            return true;
+        }
+        match crate::messaging::normalize_phone(&phone.0) {
+            Some(normalized) => {
+                phone.0 = normalized;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 use uuid::Uuid;
@@ -5,6 +5,14 @@ pub mod identity;
 pub mod payment;
 pub mod iata;
 pub mod supplier;
+pub mod blob;
+pub mod email;
+pub mod messaging;
+pub mod edifact;
+pub mod flight_status;
+pub mod dcs;
+pub mod catalog;
+pub mod tenant;
 
 #[derive(Debug, thiserror::Error)]
 pub enum CoreError {
@@ -0,0 +1,95 @@
+//! Alerts sent to the traveler's phone, for news that can't wait for email:
+//! disruptions and check-in opening on the day of travel.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Where a customer wants trip alerts sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AlertChannel {
+    /// Through the notification service, like every other customer email
+    Email,
+    Sms,
+    Whatsapp,
+}
+
+impl AlertChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertChannel::Email => "EMAIL",
+            AlertChannel::Sms => "SMS",
+            AlertChannel::Whatsapp => "WHATSAPP",
+        }
+    }
+
+    /// Sent as a text message to a phone number
+    pub fn is_text(&self) -> bool {
+        matches!(self, AlertChannel::Sms | AlertChannel::Whatsapp)
+    }
+}
+
+/// A text message ready for the messaging provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMessage {
+    pub channel: AlertChannel,
+    /// E.164, as returned by [`normalize_phone`]
+    pub to: String,
+    pub body: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MessagingError {
+    /// The provider refused the number or the message; sending again won't help
+    #[error("Message rejected: {0}")]
+    Rejected(String),
+    #[error("Messaging provider unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// SMS and WhatsApp delivery, behind whichever provider is configured
+#[async_trait]
+pub trait MessageSender: Send + Sync {
+    /// Whether messages can go out on the channel, e.g. WhatsApp needs its own sender
+    fn supports(&self, channel: AlertChannel) -> bool;
+
+    /// Sends the message; returns the provider's id for it
+    async fn send(&self, message: &TextMessage) -> Result<String, MessagingError>;
+}
+
+/// The number in E.164 form (`+` and 8 to 15 digits), with the spaces,
+/// dashes, dots and brackets people type removed. Numbers without a country
+/// code can't be told apart, so they are not accepted.
+pub fn normalize_phone(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let rest = raw.strip_prefix('+').or_else(|| raw.strip_prefix("00"))?;
+    let mut digits = String::with_capacity(rest.len());
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            _ => return None,
+        }
+    }
+    let valid = (8..=15).contains(&digits.len()) && !digits.starts_with('0');
+    valid.then(|| format!("+{}", digits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phone_numbers_need_a_country_code() {
+        assert_eq!(normalize_phone("+65 9123 4567").as_deref(), Some("+6591234567"));
+        assert_eq!(normalize_phone(" +1 (415) 555-0142 ").as_deref(), Some("+14155550142"));
+        assert_eq!(normalize_phone("0044 20.7946.0958").as_deref(), Some("+442079460958"));
+
+        // Local numbers, letters, and lengths no country uses
+        assert_eq!(normalize_phone("9123 4567"), None);
+        assert_eq!(normalize_phone("+65 9123 CALL"), None);
+        assert_eq!(normalize_phone("+0 123 456 789"), None);
+        assert_eq!(normalize_phone("+6512"), None);
+        assert_eq!(normalize_phone("+1234567890123456"), None);
+    }
+}
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use altis_shared::money::{Currency, Money, MoneyError};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -23,20 +24,69 @@ pub struct PaymentIntent {
     pub status: PaymentStatus,
     pub reference: Option<String>,
     pub client_secret: Option<String>,
+    /// Where the provider sends the customer back after a challenge
+    #[serde(default)]
+    pub return_url: Option<String>,
+    /// Set while the intent is `RequiresAction`
+    #[serde(default)]
+    pub next_action: Option<NextAction>,
     pub created_at: DateTime<Utc>,
 }
 
+impl PaymentIntent {
+    /// The intent's amount, checked against its currency code
+    pub fn money(&self) -> Result<Money, MoneyError> {
+        Ok(Money::new(self.amount as i64, Currency::new(&self.currency)?))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NextActionKind {
+    /// Send the customer's browser to `redirect_url`
+    RedirectToUrl,
+    /// Hand `client_secret` to the provider's client SDK, which runs the challenge in place
+    UseSdk,
+}
+
+/// What the customer must do before the provider will take the payment,
+/// typically a 3-D Secure challenge from the card issuer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NextAction {
+    pub kind: NextActionKind,
+    pub redirect_url: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// A card or wallet kept with the provider for later charges. The engine
+/// holds the provider's token and these display details, never the card
+/// number itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultedPaymentMethod {
+    /// Charged by passing it as a payment's `reference`
+    pub provider_token: String,
+    pub brand: Option<String>,
+    pub last4: Option<String>,
+    pub exp_month: Option<i16>,
+    pub exp_year: Option<i16>,
+    /// Same for every token of the same card, so it is only stored once
+    pub fingerprint: Option<String>,
+}
+
 /// Standardized adapter for external payment providers (e.g., Stripe, IATA Pay).
 /// This trait allows the Altis Engine to remain provider-agnostic.
 #[async_trait]
 pub trait PaymentAdapter: Send + Sync {
+    /// Provider name stored with vaulted payment methods, whose tokens only
+    /// this provider can charge
+    fn provider(&self) -> &'static str;
+
     /// Create a payment intent with the provider. 
     /// Returns a [PaymentIntent] containing the provider's transaction ID and client secrets.
     async fn create_intent(
         &self,
         order_id: Uuid,
-        amount: i32,
-        currency: &str,
+        amount: Money,
     ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>>;
 
     /// Retrieve the current status of an existing payment intent.
@@ -45,6 +95,13 @@ pub trait PaymentAdapter: Send + Sync {
         intent_id: &str,
     ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>>;
 
+    /// Completes an intent once the customer is back from its `next_action`
+    /// and returns where it ended up.
+    async fn confirm_payment(
+        &self,
+        intent_id: &str,
+    ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>>;
+
     /// Capture a previously authorized payment (Auth-Capture flow).
     async fn capture_payment(
         &self,
@@ -57,4 +114,27 @@ pub trait PaymentAdapter: Send + Sync {
         &self,
         payment: &PaymentIntent,
     ) -> Result<PaymentStatus, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Refund `amount` of an order's captured payment back to the customer.
+    /// Providers dedupe on `idempotency_key`, so a retried refund is not paid twice.
+    async fn refund_payment(
+        &self,
+        order_id: Uuid,
+        amount: Money,
+        idempotency_key: &str,
+    ) -> Result<PaymentStatus, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Exchanges a single-use token from the provider's client SDK for a
+    /// reusable one attached to `customer_ref`.
+    async fn vault_payment_method(
+        &self,
+        customer_ref: &str,
+        single_use_token: &str,
+    ) -> Result<VaultedPaymentMethod, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Detaches a vaulted method so it can no longer be charged.
+    async fn remove_payment_method(
+        &self,
+        provider_token: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
@@ -3,6 +3,7 @@ use uuid::Uuid;
 
 
 use crate::search::FlightSearchResult;
+use crate::tenant::TenantContext;
 
 // Re-export types from other crates to avoid circular dependencies
 // These are defined here as traits/interfaces that implementations will use
@@ -27,11 +28,30 @@ pub trait OfferRepository: Send + Sync {
         offer: &serde_json::Value,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
     
+    /// Persists a whole search result in one round-trip per store.
+    /// Defaults to saving offers one by one.
+    async fn save_offers_batch(
+        &self,
+        offers: &[serde_json::Value],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for offer in offers {
+            self.save_offer(offer).await?;
+        }
+        Ok(())
+    }
+    
     async fn get_offer(
         &self,
         id: Uuid,
     ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
     
+    /// Like `get_offer`, but another airline's offer reads as missing
+    async fn get_tenant_offer(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+    
     async fn list_active_offers(
         &self,
         customer_id: &str,
@@ -41,6 +61,19 @@ pub trait OfferRepository: Send + Sync {
         &self,
         id: Uuid,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Marks an active, unexpired offer accepted. False when someone else
+    /// got there first or it has expired.
+    async fn claim_offer(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Puts a claimed offer back on sale when buying it fell through
+    async fn release_offer(
+        &self,
+        id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
 
 /// Generic repository trait for order data access
@@ -56,12 +89,21 @@ pub trait OrderRepository: Send + Sync {
         id: Uuid,
     ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
     
+    /// Like `get_order`, but another airline's order reads as missing
+    async fn get_tenant_order(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+    
     async fn update_order_status(
         &self,
         id: Uuid,
         status: &str,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
     
+    /// `traveler_index`, or else `metadata.traveler_index`, says which of the
+    /// order's travelers the item is for
     async fn add_order_item(
         &self,
         order_id: Uuid,
@@ -73,19 +115,89 @@ pub trait OrderRepository: Send + Sync {
         customer_id: &str,
     ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
 
+    /// Orders by id without their items, travelers or fulfillment, for
+    /// callers that load those separately. Unknown ids are left out.
+    async fn get_order_headers(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Items of several orders in one query, each carrying its `order_id`
+    async fn list_order_items(
+        &self,
+        order_ids: &[Uuid],
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Travelers of several orders in one query, each carrying its `order_id`
+    async fn list_order_travelers(
+        &self,
+        order_ids: &[Uuid],
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// `holder` says who the record is for: `traveler_id` and
+    /// `traveler_index`, and for a flight the `segment`; all null for the
+    /// whole booking
     async fn create_fulfillment(
         &self,
         order_id: Uuid,
         order_item_id: Uuid,
+        holder: &serde_json::Value,
         fulfillment_type: &str,
         barcode: &str,
     ) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>>;
 
+    /// Creates a fulfillment record without an artifact; the delivery worker
+    /// issues the barcode at `deliver_at`.
+    async fn schedule_fulfillment(
+        &self,
+        order_id: Uuid,
+        order_item_id: Uuid,
+        holder: &serde_json::Value,
+        fulfillment_type: &str,
+        deliver_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Claims up to `limit` due deliveries with fewer than `max_attempts` tries.
+    /// Claimed rows are pushed back by `lease_seconds` so a crashed worker's
+    /// claims are retried, not lost.
+    async fn claim_due_deliveries(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+        max_attempts: i32,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn complete_delivery(
+        &self,
+        fulfillment_id: Uuid,
+        barcode: &str,
+        delivery_method: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn fail_delivery(
+        &self,
+        fulfillment_id: Uuid,
+        error: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The fulfillment record behind `barcode` (with who consumed it), its
+    /// order's `airline_id` and the `airport` it is used at (the segment's origin,
+    /// or the origin of the flight an ancillary is on); None if unknown
+    async fn find_fulfillment(
+        &self,
+        barcode: &str,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Marks the barcode consumed at `consumed_at` (when an offline scanner
+    /// read it); None when it is void or was consumed already, so a
+    /// replayed scan never counts twice
     async fn consume_fulfillment(
         &self,
         barcode: &str,
         location: &str,
-    ) -> Result<(Uuid, Uuid), Box<dyn std::error::Error + Send + Sync>>;
+        consumed_by: &str,
+        consumed_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<(Uuid, Uuid)>, Box<dyn std::error::Error + Send + Sync>>;
 
     async fn add_order_change(
         &self,
@@ -102,6 +214,41 @@ pub trait OrderRepository: Send + Sync {
         flight_id: &str,
     ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
 
+    /// Orders under a booking reference (the first six characters of the
+    /// id, any case) whose contact or one of whose travelers is `last_name`
+    async fn find_orders_by_reference(
+        &self,
+        reference: &str,
+        last_name: &str,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Inserts or replaces travelers by (order_id, traveler_index), and ties
+    /// the order's items bought for each index to its traveler
+    async fn save_travelers(
+        &self,
+        order_id: Uuid,
+        travelers: &[serde_json::Value],
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// True when the customer (by `customer_id` or full DID) holds a live order on the flight
+    async fn customer_has_flight_order(
+        &self,
+        owner: &str,
+        flight_id: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Moves an unused ancillary to another order in one transaction: voids its
+    /// barcode, issues `new_barcode` under the target order and writes memo entries
+    /// on both ledgers. Returns false when the item is not transferable.
+    async fn transfer_order_item(
+        &self,
+        item_id: Uuid,
+        from_order_id: Uuid,
+        to_order_id: Uuid,
+        traveler_index: Option<i32>,
+        new_barcode: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
     async fn add_order_ledger_entry(
         &self,
         order_id: Uuid,
@@ -111,16 +258,201 @@ pub trait OrderRepository: Send + Sync {
         description: Option<&str>,
     ) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>>;
 
+    /// Writes several ledger entries (as serialized `LedgerEntry`s) atomically
+    async fn add_ledger_entries(
+        &self,
+        entries: &[serde_json::Value],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
     async fn update_item_revenue_status(
         &self,
         item_id: Uuid,
         status: &str,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
+    async fn update_item_status(
+        &self,
+        item_id: Uuid,
+        status: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// When the item's revenue is recognized if no scan earns it first
+    async fn schedule_revenue_recognition(
+        &self,
+        item_id: Uuid,
+        recognize_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Claims UNEARNED items of paid orders whose recognition time has
+    /// passed, returning each one's `id` and `order_id`. The time is pushed
+    /// out by `lease_seconds` while they are handled.
+    async fn claim_due_recognitions(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Marks a scheduled item EARNED and takes it off the schedule; false
+    /// when it was no longer UNEARNED
+    async fn complete_scheduled_recognition(
+        &self,
+        item_id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Stops the item's live, unused barcodes from scanning; how many there were
+    async fn expire_unconsumed_fulfillment(
+        &self,
+        item_id: Uuid,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Moves the item from status `from` to `to`; false when it wasn't in
+    /// `from` (someone else got there first)
+    async fn transition_item_status(
+        &self,
+        item_id: Uuid,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Refunds `units` of an ACTIVE item in one transaction: adds them to its
+    /// `refunded_quantity` (the item is REFUNDED once none are left) and voids
+    /// the unconsumed fulfillment records of the highest units. False when
+    /// fewer than `units` are held and unused.
+    async fn refund_item_units(
+        &self,
+        item_id: Uuid,
+        units: i32,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Cancels re-accommodation proposals whose seat hold has run out, returning
+    /// each one's `id`, `order_id`, `product_id` and `quantity`
+    async fn expire_reaccommodation_proposals(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Records the intent a PAYMENT_PENDING order is waiting on the customer
+    /// to authenticate, and when to stop waiting
+    async fn await_payment_action(
+        &self,
+        order_id: Uuid,
+        intent_id: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Moves the order to `to` if its status is one of `from`, ending any
+    /// payment action it was waiting on; false when someone else moved it first
+    async fn transition_order_status(
+        &self,
+        order_id: Uuid,
+        from: &[&str],
+        to: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Claims PAYMENT_PENDING orders whose payment action is past its
+    /// deadline, returning each one's `id`, `payment_intent_id` and `test`.
+    /// The deadline is pushed out by `lease_seconds` while it is handled.
+    async fn claim_expired_payment_actions(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Sets the given keys in the item's metadata, keeping the rest
+    async fn merge_item_metadata(
+        &self,
+        item_id: Uuid,
+        patch: &serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Adds `delta_nuc` (may be negative) to the order total
+    async fn adjust_order_total(
+        &self,
+        order_id: Uuid,
+        delta_nuc: i32,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
     async fn get_order_ledger(
         &self,
         order_id: Uuid,
     ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Full audit trail of the order, oldest first
+    async fn get_order_changes(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Every fulfillment record of the order, including voided and consumed ones
+    async fn get_fulfillment_history(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Records a compensation payout; None when the item was already
+    /// compensated under the scheme
+    async fn create_compensation_payout(
+        &self,
+        payout: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_compensation_payouts(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The customer's unpaid, unexpired orders with the airline that hold an
+    /// active item of `product_type`, given either as `CARBON_OFFSET` or `CarbonOffset`
+    async fn count_customer_holds(
+        &self,
+        customer_id: &str,
+        airline_id: Uuid,
+        product_type: &str,
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Stores the order's snapshot (`offers`, `pricing`, `business_rules`,
+    /// `sha256`); false if it already has one, which is left as it is
+    async fn save_order_snapshot(
+        &self,
+        order_id: Uuid,
+        snapshot: &serde_json::Value,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_order_snapshot(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Seat numbers held by active SEAT items on the flight, across all orders
+    async fn seats_taken(
+        &self,
+        flight_id: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Paid bookings (`order_id`, `order_item_id`) on flights departing by
+    /// `until` whose passengers haven't been through seat assignment yet
+    async fn due_seat_assignments(
+        &self,
+        until: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Checked-in flight items (`order_id`, `order_item_id`) not yet sent to
+    /// departure control
+    async fn due_dcs_exports(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Sets `delivered_at` on the item's fulfillment records (just the one
+    /// with `barcode`, when given); the number of records updated
+    async fn confirm_service_delivery(
+        &self,
+        order_id: Uuid,
+        order_item_id: Uuid,
+        barcode: Option<&str>,
+        delivered_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
 }
 
 /// Generic repository trait for product catalog access
@@ -136,6 +468,13 @@ pub trait ProductRepository: Send + Sync {
         id: Uuid,
     ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
     
+    /// Like `get_product`, but another airline's product reads as missing
+    async fn get_tenant_product(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+    
     async fn list_products(
         &self,
         airline_id: Uuid,
@@ -148,10 +487,149 @@ pub trait ProductRepository: Send + Sync {
         product: &serde_json::Value,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
     
+    /// Soft delete: the product is deactivated and stamped `deleted_at`, but
+    /// stays for the orders and offers that reference it. False if there's no
+    /// such product or it's already deleted.
     async fn delete_product(
         &self,
         id: Uuid,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Reactivates a soft-deleted product; false if it isn't deleted
+    async fn restore_product(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Order and offer items pointing at the product
+    async fn count_product_references(
+        &self,
+        id: Uuid,
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Removes the row for good, unless anything still references it; false
+    /// when nothing was removed
+    async fn purge_product(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Localized content of every product the airline sells, in all locales
+    async fn list_airline_product_content(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// One product's translations, by locale
+    async fn list_product_content(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Creates or replaces the product's content for `locale`
+    async fn upsert_product_content(
+        &self,
+        product_id: Uuid,
+        locale: &str,
+        content: &serde_json::Value,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// False when the product had no content for `locale`
+    async fn delete_product_content(
+        &self,
+        product_id: Uuid,
+        locale: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The product's versions, oldest first, each with the window it's in force
+    async fn list_product_versions(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Adds a version taking effect at `effective_from`. Fields the version
+    /// doesn't give are carried over from the product as it is now. None if
+    /// there's no such product, or it's deleted.
+    async fn schedule_product_version(
+        &self,
+        product_id: Uuid,
+        version: &serde_json::Value,
+        effective_from: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Withdraws a version that hasn't taken effect yet; false if there's none
+    async fn cancel_product_version(
+        &self,
+        product_id: Uuid,
+        version: i32,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Moves every product onto the version in force now; the ids of those that changed
+    async fn apply_due_product_versions(
+        &self,
+    ) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Reprices many products in one transaction, each `(product_id,
+    /// old_price_nuc, new_price_nuc)`, as new product versions. The returned
+    /// id is the undo token. None, and nothing changed, when any product is no
+    /// longer at its old price or has been deleted since the preview.
+    async fn apply_bulk_price_update(
+        &self,
+        airline_id: Uuid,
+        request: &serde_json::Value,
+        changes: &[(Uuid, i32, i32)],
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Puts back the prices a bulk update replaced. Products repriced since
+    /// are left alone and reported as skipped. None if there's no such
+    /// update or it was already rolled back.
+    async fn rollback_bulk_price_update(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Every campaign of the airline, newest first
+    async fn list_campaigns(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Campaigns currently ACTIVE, the ones offer pricing applies
+    async fn list_active_campaigns(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_campaign(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Starts out ACTIVE if its window has already begun, SCHEDULED otherwise
+    async fn create_campaign(
+        &self,
+        airline_id: Uuid,
+        campaign: &serde_json::Value,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// None unless the campaign is SCHEDULED or ACTIVE
+    async fn update_campaign(
+        &self,
+        id: Uuid,
+        campaign: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// False unless the campaign was SCHEDULED or ACTIVE
+    async fn cancel_campaign(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Activates campaigns whose window has begun and ends those past it;
+    /// the number that changed status
+    async fn sync_campaign_statuses(
+        &self,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
 
     async fn get_airline_by_code(
         &self,
@@ -163,4 +641,771 @@ pub trait ProductRepository: Send + Sync {
         airline_id: Uuid,
         resource_type: &str,
     ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// `rule_config` of the airline's highest-priority active business rule
+    /// of `rule_type` in force now
+    async fn get_business_rule(
+        &self,
+        airline_id: Uuid,
+        rule_type: &str,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Sets the airline's overrides of the global business rules (its CONFIG
+    /// rule), replacing any it had
+    async fn put_airline_business_rules(
+        &self,
+        airline_id: Uuid,
+        overrides: &serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Retires the airline's overrides, leaving it on the global business
+    /// rules; false if it had none
+    async fn clear_airline_business_rules(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Active inventory rules, one per resource type, with their `capacity`,
+    /// hold limits and blackout dates
+    async fn list_inventory_rules(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_inventory_rule_by_id(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// None when the airline already has an active rule for the resource type
+    async fn create_inventory_rule(
+        &self,
+        airline_id: Uuid,
+        rule: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Replaces the rule's settings; its resource type stays. None if there's no such active rule.
+    async fn update_inventory_rule(
+        &self,
+        id: Uuid,
+        rule: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Retires the rule, leaving its resource type unrestricted; false if it wasn't active
+    async fn deactivate_inventory_rule(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Active tax codes, shaped like `altis_catalog::TaxCode`
+    async fn list_tax_codes(
+        &self,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn create_tax_code(
+        &self,
+        tax_code: &serde_json::Value,
+    ) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// IATA airport code to ISO 3166 country code
+    async fn list_airport_countries(
+        &self,
+    ) -> Result<std::collections::HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Country, city and coordinates of an airport
+    async fn get_airport(
+        &self,
+        iata_code: &str,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_airports(
+        &self,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The airline's routes, shaped like `altis_catalog::Route`, with their
+    /// airports' cities and countries
+    async fn list_routes(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_route(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// None when the airline already has an active route between the two airports
+    async fn create_route(
+        &self,
+        airline_id: Uuid,
+        route: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Changes an active route's distance and currency of sale
+    async fn update_route(
+        &self,
+        id: Uuid,
+        route: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn deactivate_route(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_active_airlines(
+        &self,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Creates the airline, or renames and reactivates it; its id either way
+    async fn upsert_airline(
+        &self,
+        code: &str,
+        name: &str,
+    ) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Active pricing rules, highest priority first
+    async fn list_pricing_rules(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The airline's active catalog as a portable bundle: products, pricing
+    /// rules, bundle templates and inventory rules, keyed by natural keys
+    /// (product code, rule name, bundle name, resource type) instead of ids.
+    async fn export_catalog(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Makes the airline's active catalog match `bundle` in one transaction.
+    /// Entries missing from the bundle are deactivated, not deleted, since
+    /// orders may still reference them.
+    async fn apply_catalog(
+        &self,
+        airline_id: Uuid,
+        bundle: &serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Repository trait for settlement batches built from the order ledger
+#[async_trait]
+pub trait SettlementRepository: Send + Sync {
+    /// Sweeps ledger entries created before `period_end` that are not yet in a
+    /// batch into one new DRAFT batch per airline. Returns the created batches.
+    /// Test orders' entries are never swept.
+    async fn create_batches(
+        &self,
+        period_end: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_batches(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_batch(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Ledger entries of a batch, ordered by order then time
+    async fn get_batch_entries(
+        &self,
+        id: Uuid,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Moves a batch from `from` to `to`; false if it was not in `from`
+    async fn update_batch_status(
+        &self,
+        id: Uuid,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Interline payables and commissions posted over `[from, to)`, one row per
+    /// selling airline and operating carrier, optionally for one carrier only.
+    /// Test orders are left out.
+    async fn interline_report(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        carrier_id: Option<Uuid>,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Settlement metrics for an airline over `[from, to)`, one row per group.
+    /// `group_by` is `"product_type"`, `"day"` or `"none"` (a single total row).
+    /// Test orders are left out.
+    async fn settlement_report(
+        &self,
+        airline_id: Uuid,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        group_by: &str,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Repository trait for gaplessly numbered accounting documents
+#[async_trait]
+pub trait DocumentRepository: Send + Sync {
+    /// Issues the next `document_type` number (INVOICE, CREDIT_NOTE) for the
+    /// order's airline. None when the order is not tied to an airline.
+    async fn issue_order_document(
+        &self,
+        order_id: Uuid,
+        document_type: &str,
+        amount_nuc: i64,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_documents(
+        &self,
+        airline_id: Uuid,
+        document_type: Option<&str>,
+        fiscal_year: Option<i32>,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Allocated sequence numbers with no document behind them
+    async fn find_sequence_gaps(
+        &self,
+        airline_id: Uuid,
+        document_type: &str,
+        fiscal_year: i32,
+    ) -> Result<Vec<i64>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The order's latest invoice with the seller's name and, once rendered,
+    /// its stored file (`file`: blob key, sha256, breakdown).
+    async fn get_order_invoice(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Records the rendered file of a document. A document is rendered once;
+    /// later calls keep the first file.
+    async fn save_document_file(
+        &self,
+        document_id: Uuid,
+        blob_key: &str,
+        content_type: &str,
+        sha256: &str,
+        breakdown: &serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait LedgerRepository: Send + Sync {
+    /// Writes a journal transaction and its postings atomically. The database
+    /// rejects it at commit unless debits equal credits.
+    async fn post_transaction(&self, transaction: &serde_json::Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Journal transactions of an order with their postings, oldest first
+    async fn get_order_journal(&self, order_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Debit and credit totals per account for transactions up to `as_of`,
+    /// test orders left out
+    async fn trial_balance(
+        &self,
+        as_of: chrono::DateTime<chrono::Utc>,
+        airline_id: Option<Uuid>,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait BulkRefundRepository: Send + Sync {
+    /// Creates a RUNNING job and snapshots the live orders holding `flight_id`
+    /// departing within the range. Returns the job with its order count.
+    async fn create_job(
+        &self,
+        flight_id: &str,
+        departure_from: chrono::DateTime<chrono::Utc>,
+        departure_to: chrono::DateTime<chrono::Utc>,
+        reason: &str,
+        created_by: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Job with per-status order counts and the amount refunded so far
+    async fn get_job(&self, job_id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_running_jobs(&self) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Marks up to `limit` pending orders of the job PROCESSING and returns them.
+    /// Orders left PROCESSING longer than `lease_seconds` are claimed again.
+    async fn claim_items(
+        &self,
+        job_id: Uuid,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn complete_item(
+        &self,
+        job_id: Uuid,
+        order_id: Uuid,
+        status: &str,
+        refunded_nuc: i64,
+        error: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Closes the job once no order is pending; returns the final status, or
+    /// None while work remains.
+    async fn finish_job(&self, job_id: Uuid) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_failures(&self, job_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Puts FAILED orders back in the queue and reopens the job; returns how many
+    async fn retry_failures(&self, job_id: Uuid) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait ExperimentRepository: Send + Sync {
+    /// Every experiment, newest first
+    async fn list_experiments(&self) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn create_experiment(&self, experiment: &serde_json::Value, created_by: &str) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Stops a running or scheduled experiment now; None if it doesn't exist or already ended
+    async fn end_experiment(&self, id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait CustomerFeatureRepository: Send + Sync {
+    /// Purchase history features, shaped like `altis_offer::features::CustomerFeatures`;
+    /// None for customers without purchases in the lookback window
+    async fn get_customer_features(&self, customer_id: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Recomputes features for customers whose orders changed after `since`
+    /// (everyone when None) from the last `lookback_days` of purchases, and
+    /// drops customers with none left. Returns how many rows were written.
+    async fn refresh_customer_features(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        lookback_days: i32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Campaign attribution keyed by hashed identifiers only; callers hash
+/// customer and order ids before they reach the repository.
+#[async_trait]
+pub trait AttributionRepository: Send + Sync {
+    /// `campaign` carries the utm_* fields; missing ones are stored empty
+    async fn record_touch(&self, subject_hash: &str, campaign: &serde_json::Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Idempotent per order
+    async fn record_conversion(&self, order_hash: &str, subject_hash: &str, value_nuc: i32) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Per campaign over [from, to): distinct subjects reached, plus the
+    /// conversions whose latest touch within `lookback_days` was that campaign
+    async fn campaign_report(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        lookback_days: i32,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait PriceWatchRepository: Send + Sync {
+    async fn create_watch(&self, watch: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The customer's watches, newest first
+    async fn list_watches(&self, customer_id: &str) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_watch(&self, id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Cancels an ACTIVE watch owned by the customer; None if there is none
+    async fn cancel_watch(&self, id: Uuid, customer_id: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Expires watches whose window has passed, then claims up to `limit`
+    /// ACTIVE watches not checked in the last `recheck_seconds`, stamping
+    /// them as checked so other instances skip them.
+    async fn claim_due_watches(&self, limit: i64, recheck_seconds: i64) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn record_price(
+        &self,
+        id: Uuid,
+        lowest_price_nuc: i32,
+        lowest_price_date: chrono::NaiveDate,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// ACTIVE -> TRIGGERED; false if the watch was cancelled meanwhile
+    async fn mark_triggered(&self, id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait LowFareRepository: Send + Sync {
+    /// Cached prices for the (origin, destination, month) tuples, registering
+    /// any not seen before and stamping all of them as requested so the
+    /// aggregation job keeps them fresh. `month` is the first of the month.
+    async fn request_low_fares(
+        &self,
+        routes: &[(String, String, chrono::NaiveDate)],
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Drops past months, then claims up to `limit` route-months requested in
+    /// the last `retain_days` and not checked in the last `refresh_seconds`,
+    /// stamping them as checked so other instances skip them.
+    async fn claim_stale_low_fares(
+        &self,
+        limit: i64,
+        refresh_seconds: i64,
+        retain_days: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Stores the month's cheapest price and its day; None when nothing was sellable
+    async fn record_low_fare(
+        &self,
+        origin: &str,
+        destination: &str,
+        month: chrono::NaiveDate,
+        lowest: Option<(i32, chrono::NaiveDate)>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait WebhookDeliveryRepository: Send + Sync {
+    async fn record_delivery(&self, delivery: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The partner's most recent attempts, newest first
+    async fn list_deliveries(&self, partner: &str, limit: i64) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_delivery(&self, id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait CartRepository: Send + Sync {
+    async fn create_cart(&self, customer_id: &str) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The cart with its `items` (offer_id, offer_item_id) in the order added
+    async fn get_cart(&self, id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Adds items of one offer to an OPEN cart; false if the cart is not open
+    async fn add_cart_items(&self, cart_id: Uuid, offer_id: Uuid, item_ids: &[Uuid]) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Removes every item of the offer from an OPEN cart; false if the cart is not open
+    async fn remove_cart_offer(&self, cart_id: Uuid, offer_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// OPEN -> CHECKED_OUT, recording the order about to be created. False if
+    /// the cart was not open, e.g. a concurrent checkout won.
+    async fn checkout_cart(&self, cart_id: Uuid, order_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Undoes `checkout_cart` when the order could not be created
+    async fn reopen_cart(&self, cart_id: Uuid) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait BaggageRepository: Send + Sync {
+    /// Takes the next `count` serials of the airline's bag tag sequence
+    async fn next_bag_tag_serials(&self, airline_id: Uuid, count: i64) -> Result<Vec<i64>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Creates a tag (`tag_number`, `order_id`, `order_item_id`, `airline_id`)
+    /// together with its CHECKED_IN event at `station`
+    async fn create_bag_tag(&self, tag: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The most recent bag with this tag number, with its `events` oldest first
+    async fn get_bag_tag(&self, tag_number: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The order's bags in the order they were tagged, each with its `events`
+    async fn list_order_bag_tags(&self, order_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn record_bag_event(&self, bag_tag_id: Uuid, event: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait DisruptionRepository: Send + Sync {
+    async fn create_disruption(&self, disruption: &serde_json::Value) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Records what the engine did once every affected order was handled
+    async fn complete_disruption(&self, id: Uuid, affected_orders: i32, proposals_made: i32) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Newest first
+    async fn list_disruptions(
+        &self,
+        airline_id: Option<Uuid>,
+        flight_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_disruption(&self, id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// One row per booking offered alternatives by these disruptions:
+    /// `disruption_id`, `order_id`, `item_id`, `order_status`, `proposals`,
+    /// `open_proposals`, `accepted` and the earliest open `hold_expires_at`
+    async fn list_disrupted_bookings(&self, disruption_ids: &[Uuid]) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait PaymentScheduleRepository: Send + Sync {
+    /// Creates an ACTIVE plan for the order from `installments` (`sequence`,
+    /// `due_at`, `amount_nuc`), returned with its `installments`
+    async fn create_schedule(
+        &self,
+        order_id: Uuid,
+        payment_reference: Option<&str>,
+        installments: &[serde_json::Value],
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The order's plan, leaving out ones cancelled before they started
+    async fn get_schedule(&self, order_id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Claims up to `limit` PENDING installments of ACTIVE plans that are due,
+    /// pushing their next attempt `lease_seconds` out so other instances skip
+    /// them. Each carries its plan's `order_id` and `payment_reference`.
+    async fn claim_due_installments(&self, limit: i64, lease_seconds: i64) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// PENDING -> PAID, completing the plan with its last installment.
+    /// Returns how many are left to pay, or None if it was no longer pending.
+    async fn mark_installment_paid(&self, installment_id: Uuid) -> Result<Option<i64>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Counts a failed attempt. The installment is tried again at `retry_at`,
+    /// or marked FAILED when there is none.
+    async fn mark_installment_failed(
+        &self,
+        installment_id: Uuid,
+        error: &str,
+        retry_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Ends an ACTIVE plan as DEFAULTED or CANCELLED, cancelling what is left
+    /// to pay; false if it had already ended
+    async fn close_schedule(&self, schedule_id: Uuid, status: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait PaymentMethodRepository: Send + Sync {
+    /// Saves a vaulted method (`customer_id`, `provider`, `provider_token`,
+    /// display details, `test`). The customer's first method becomes their
+    /// default. A card already saved under the same fingerprint is returned
+    /// as it is.
+    async fn create_payment_method(&self, method: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The customer's ACTIVE methods with this provider, default first
+    async fn list_payment_methods(
+        &self,
+        customer_id: &str,
+        provider: &str,
+        test: bool,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// An ACTIVE method, if it is the customer's
+    async fn get_payment_method(&self, id: Uuid, customer_id: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// ACTIVE -> REMOVED. The newest remaining method takes over as default.
+    async fn remove_payment_method(&self, id: Uuid, customer_id: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Agent notes on orders and the waivers recorded with them
+#[async_trait]
+pub trait NoteRepository: Send + Sync {
+    /// Stores a note (`order_id`, `author`, `author_role`, `visibility`,
+    /// `body`). A `waiver` on it is stored with it, and its `ledger_entry` (a
+    /// serialized `LedgerEntry`) is posted in the same transaction. Returns
+    /// the note with its `waiver`.
+    async fn add_note(&self, note: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// An order's notes, oldest first, each with its `waiver` or null. Only
+    /// CUSTOMER notes when `customer_only`.
+    async fn list_notes(&self, order_id: Uuid, customer_only: bool) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Customer profiles and the travelers saved on them, keyed by token
+/// subject (the full DID for One ID holders)
+#[async_trait]
+pub trait ProfileRepository: Send + Sync {
+    async fn get_profile(&self, customer_id: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Creates the profile or replaces its contact details (`email`, `phone`,
+    /// `first_name`, `last_name`), `alert_channels` and `marketing_opt_in`.
+    /// Opting in stamps `marketing_opt_in_at`; opting out clears it.
+    async fn save_profile(&self, customer_id: &str, profile: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Oldest first
+    async fn list_saved_travelers(&self, customer_id: &str) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Those of `ids` saved by the customer; anyone else's are left out
+    async fn get_saved_travelers(&self, customer_id: &str, ids: &[Uuid]) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn create_saved_traveler(&self, customer_id: &str, traveler: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Replaces a saved traveler's details, if it is the customer's
+    async fn update_saved_traveler(&self, id: Uuid, customer_id: &str, traveler: &serde_json::Value) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// False if the customer has no such saved traveler
+    async fn delete_saved_traveler(&self, id: Uuid, customer_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+pub trait DisputeRepository: Send + Sync {
+    /// Stores a dispute reported by the provider, or returns the one already
+    /// stored under the same provider id. A repeat report can move the
+    /// evidence deadline; the status only changes through `update_dispute_status`.
+    /// `inserted` on the result is true for a dispute not seen before.
+    async fn record_dispute(&self, dispute: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_dispute(&self, id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Disputes with the given status, or all of them; earliest evidence deadline first
+    async fn list_disputes(&self, status: Option<&str>, limit: i64) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Moves a dispute from `from` to `to`; false if it was not in `from`
+    async fn update_dispute_status(&self, id: Uuid, from: &str, to: &str, note: Option<&str>) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Daily retail stats per airline, rolled up from offers and orders
+#[async_trait]
+pub trait AnalyticsRepository: Send + Sync {
+    /// Latest day rolled up so far; None before the first run
+    async fn latest_rollup_day(&self) -> Result<Option<chrono::NaiveDate>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Recomputes every day from `since` (UTC) to today, replacing what was
+    /// rolled up for those days before. Safe to run from several instances.
+    async fn roll_up_daily_stats(&self, since: chrono::NaiveDate) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Funnel rows (`day`, `offers_generated`, `offers_accepted`,
+    /// `orders_paid`, `revenue_nuc`) for days in [from, to], oldest first
+    async fn daily_funnel(
+        &self,
+        airline_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+        product_type: &str,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Per product type over [from, to]: paid `orders` carrying it, `items`
+    /// and `revenue_nuc`; product type ALL counts every paid order
+    async fn attach_totals(
+        &self,
+        airline_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Emails sent to customers about their orders, one record per order and kind
+#[async_trait]
+pub trait EmailRepository: Send + Sync {
+    async fn get_order_email(&self, order_id: Uuid, kind: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Records the outcome of a send (`order_id`, `kind`, `recipient`,
+    /// `status`, `provider`, `provider_message_id`, `error`). A later attempt
+    /// replaces the earlier outcome and counts towards `attempts`.
+    async fn record_order_email(&self, email: &serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Every email recorded for the order, oldest first
+    async fn list_order_emails(&self, order_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Trip alerts texted or emailed about orders, one record per alert and channel
+#[async_trait]
+pub trait MessageRepository: Send + Sync {
+    /// Claims sending `alert_key` on the channel for the order; None when it
+    /// was claimed before, so each alert goes out once per channel
+    async fn claim_message(&self, order_id: Uuid, alert_key: &str, channel: &str, recipient: Option<&str>) -> Result<Option<Uuid>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Records how the claimed send went (SENT, FAILED or SKIPPED)
+    async fn finish_message(&self, id: Uuid, status: &str, provider_message_id: Option<&str>, error: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Every alert recorded for the order, oldest first
+    async fn list_order_messages(&self, order_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Active flight items (`order_id`, `order_item_id`) of paid live orders
+    /// departing before `until` whose check-in alert hasn't been sent
+    async fn due_check_in_alerts(&self, until: chrono::DateTime<chrono::Utc>, limit: i64) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Ancillaries offered on paid orders at prices held for a while
+#[async_trait]
+pub trait UpsellRepository: Send + Sync {
+    /// The order's offers still OPEN and in time, best first
+    async fn open_upsell_offers(&self, order_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Stores a fresh set of offers (`id`, `rank`, `reason`, `item`,
+    /// `expires_at`) and returns them as stored
+    async fn save_upsell_offers(&self, order_id: Uuid, offers: &[serde_json::Value]) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// OPEN -> ACCEPTING, if the offer is the order's and its price still
+    /// holds. None for everyone but the first caller.
+    async fn claim_upsell_offer(&self, id: Uuid, order_id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// ACCEPTING -> OPEN, after a declined charge
+    async fn release_upsell_offer(&self, id: Uuid) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// ACCEPTING -> ACCEPTED, with the order item it became
+    async fn complete_upsell_offer(&self, id: Uuid, order_item_id: Uuid) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Customers queued for sold-out flights
+#[async_trait]
+pub trait WaitlistRepository: Send + Sync {
+    /// Puts the customer at the back of the flight's line, returning the
+    /// entry with its `position`. None if they're already in line.
+    async fn join_waitlist(&self, entry: &serde_json::Value) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The customer's WAITING or OFFERED entry for the flight, with its `position` while waiting
+    async fn find_waitlist_entry(&self, flight_id: Uuid, customer_id: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_waitlist_entry(&self, id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// WAITING or OFFERED -> CANCELLED; returns the entry as it was
+    async fn leave_waitlist(&self, flight_id: Uuid, customer_id: &str) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Up to `limit` flights with customers waiting
+    async fn waitlisted_flights(&self, limit: i64) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The entry at the front of the flight's line
+    async fn next_waiting(&self, flight_id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// WAITING -> OFFERED, with the offer held for the customer until
+    /// `expires_at`; false if the entry left the line meanwhile
+    async fn mark_offered(
+        &self,
+        id: Uuid,
+        offer_id: Uuid,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// OFFERED -> BOOKED, if the entry is the customer's and its offer is still held
+    async fn book_waitlist_entry(&self, id: Uuid, customer_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Expires up to `limit` offers past their window, returning the
+    /// entries so their seats can be given back
+    async fn expire_waitlist_offers(&self, limit: i64) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Expires everyone still waiting for a flight that can no longer be sold
+    async fn close_waitlist(&self, flight_id: Uuid) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Per flight of the airline with an open line: `waiting`,
+    /// `waiting_seats`, `offered` and `oldest_waiting_since`
+    async fn waitlist_depth(&self, airline_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The flight's open entries in line order, offered ones first
+    async fn list_waitlist(&self, flight_id: Uuid) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Closed orders moved out of the live tables into cold storage, with a
+/// thin index kept for finding them by booking reference
+#[async_trait]
+pub trait ArchiveRepository: Send + Sync {
+    /// Up to `limit` FULFILLED or CANCELLED orders untouched since `before`, oldest first
+    async fn archivable_orders(&self, before: chrono::DateTime<chrono::Utc>, limit: i64) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The order and every row that moves with it, as `{order_id, exported_at, tables}`
+    async fn export_order(&self, order_id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Indexes the exported order under `blob_key` and deletes its rows;
+    /// false, leaving it live, if it changed since `document` was exported
+    async fn archive_order(&self, document: &serde_json::Value, blob_key: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Archived orders under a booking reference (any case), narrowed to a
+    /// contact or traveler surname when given
+    async fn find_archived_orders(
+        &self,
+        tenant: &TenantContext,
+        reference: &str,
+        last_name: Option<&str>,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn get_archived_order(&self, tenant: &TenantContext, order_id: Uuid) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Puts an exported order's rows back and drops it from the index;
+    /// false if it isn't archived
+    async fn restore_order(&self, document: &serde_json::Value) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
 }
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use serde_json::Value;
+
+pub mod ndc;
+
+#[async_trait]
+pub trait SupplierClient: Send + Sync {
+    /// Sync the status of an order item with the external supplier
+    async fn sync_item_status(
+        &self,
+        order_id: Uuid,
+        item_id: Uuid,
+        external_reference: &str,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Notify supplier of a consumption event (e.g., flight taken)
+    async fn notify_consumption(
+        &self,
+        item_id: Uuid,
+        barcode: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+// ============================================================================
+// Ancillary Supplier Adapters
+// ============================================================================
+
+/// Where a supplier-sourced product comes from, read from catalog metadata:
+/// `supplier` names the adapter and `supplier_product_id` is the supplier's own code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupplierProduct {
+    pub supplier: String,
+    pub supplier_product_id: String,
+}
+
+impl SupplierProduct {
+    /// None for products Altis fulfils itself
+    pub fn from_metadata(metadata: &Value) -> Option<Self> {
+        Some(Self {
+            supplier: metadata["supplier"].as_str().filter(|s| !s.is_empty())?.to_string(),
+            supplier_product_id: metadata["supplier_product_id"].as_str().filter(|s| !s.is_empty())?.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplierAvailability {
+    pub available: bool,
+    /// The supplier's current price, when it quotes one
+    pub price_nuc: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplierBookingRequest {
+    /// Our order item; suppliers use it to recognise a retried booking
+    pub item_id: Uuid,
+    pub order_id: Uuid,
+    pub supplier_product_id: String,
+    pub quantity: i32,
+    /// What the item was sold with: its catalog metadata
+    pub context: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplierBooking {
+    /// The supplier's booking reference, needed to cancel
+    pub reference: String,
+    pub status: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SupplierError {
+    /// The supplier couldn't be reached or failed; counts against its circuit
+    #[error("Supplier unavailable: {0}")]
+    Unavailable(String),
+
+    /// The supplier answered and refused
+    #[error("Supplier rejected the request: {0}")]
+    Rejected(String),
+}
+
+/// An external provider of ancillaries (insurance, lounge access, ...).
+/// Consulted during offer generation and confirmed once the order is paid.
+#[async_trait]
+pub trait SupplierAdapter: Send + Sync {
+    /// Whether `quantity` units can currently be sold
+    async fn check_availability(
+        &self,
+        supplier_product_id: &str,
+        quantity: i32,
+        context: &Value,
+    ) -> Result<SupplierAvailability, SupplierError>;
+
+    /// Confirms the product with the supplier for a paid order item
+    async fn book(&self, request: &SupplierBookingRequest) -> Result<SupplierBooking, SupplierError>;
+
+    async fn cancel(&self, reference: &str) -> Result<(), SupplierError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supplier_product_from_metadata() {
+        let metadata = serde_json::json!({"supplier": "covermore", "supplier_product_id": "TRV-BASIC"});
+        assert_eq!(
+            SupplierProduct::from_metadata(&metadata),
+            Some(SupplierProduct { supplier: "covermore".to_string(), supplier_product_id: "TRV-BASIC".to_string() })
+        );
+        assert_eq!(SupplierProduct::from_metadata(&serde_json::json!({"supplier": "covermore"})), None);
+        assert_eq!(SupplierProduct::from_metadata(&Value::Null), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Interline resale: shopping partner carriers' flights through their NDC
+//! APIs and booking the offers our customers accept.
+
+use async_trait::async_trait;
+
+use crate::iata::{AirShoppingRequest, AirShoppingResponse, NdcOffer, OneOrderResponse, OrderCreateRequest};
+use super::SupplierError;
+
+/// A partner carrier's NDC endpoint
+#[async_trait]
+pub trait NdcPartner: Send + Sync {
+    /// Configured name, as in `interline.partners`
+    fn name(&self) -> &str;
+
+    /// IATA code of the carrier whose offers this partner sells
+    fn carrier(&self) -> &str;
+
+    async fn air_shopping(&self, request: &AirShoppingRequest) -> Result<AirShoppingResponse, SupplierError>;
+
+    async fn order_create(&self, request: &OrderCreateRequest) -> Result<OneOrderResponse, SupplierError>;
+}
+
+/// The partner's offers that it owns itself. Offers it resells from a third
+/// carrier are dropped: we only book with the carrier we have an agreement with.
+pub fn owned_offers(carrier: &str, response: AirShoppingResponse) -> Vec<NdcOffer> {
+    response.offers
+        .into_iter()
+        .filter(|offer| offer.owner.eq_ignore_ascii_case(carrier) && !offer.items.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iata::{NdcOfferItem, NdcPrice};
+
+    fn offer(owner: &str, items: usize) -> NdcOffer {
+        NdcOffer {
+            offer_id: format!("{}-offer", owner),
+            owner: owner.to_string(),
+            total_price: NdcPrice { amount: 180, currency: "NUC".to_string() },
+            items: (0..items).map(|i| NdcOfferItem {
+                item_id: format!("item-{}", i),
+                service_name: "Flight SIN-BKK".to_string(),
+                price: NdcPrice { amount: 180, currency: "NUC".to_string() },
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_owned_offers() {
+        let response = AirShoppingResponse {
+            response_id: "r1".to_string(),
+            offers: vec![offer("xy", 1), offer("ZZ", 1), offer("XY", 0)],
+        };
+        let owned = owned_offers("XY", response);
+        assert_eq!(owned.len(), 1);
+        assert_eq!(owned[0].offer_id, "xy-offer");
+    }
+}
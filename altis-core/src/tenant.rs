@@ -0,0 +1,54 @@
+use uuid::Uuid;
+
+/// The airline a request is confined to. Back-office users tied to an
+/// airline only reach that airline's products, offers and orders; platform
+/// staff and the system itself reach every airline's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantContext {
+    airline_id: Option<Uuid>,
+}
+
+impl TenantContext {
+    /// Access to every airline
+    pub fn platform() -> Self {
+        Self { airline_id: None }
+    }
+
+    /// Access to one airline's data only
+    pub fn airline(airline_id: Uuid) -> Self {
+        Self { airline_id: Some(airline_id) }
+    }
+
+    /// The airline queries are filtered on; None when unrestricted
+    pub fn airline_id(&self) -> Option<Uuid> {
+        self.airline_id
+    }
+
+    /// True when a record owned by `owner` is visible. Records that belong
+    /// to no airline are the platform's alone.
+    pub fn permits(&self, owner: Option<Uuid>) -> bool {
+        self.airline_id.is_none_or(|airline_id| owner == Some(airline_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_airline_tenants_only_see_their_own_records() {
+        let ours = Uuid::new_v4();
+        let theirs = Uuid::new_v4();
+
+        let tenant = TenantContext::airline(ours);
+        assert!(tenant.permits(Some(ours)));
+        assert!(!tenant.permits(Some(theirs)));
+        assert!(!tenant.permits(None));
+
+        let platform = TenantContext::platform();
+        assert!(platform.permits(Some(ours)));
+        assert!(platform.permits(Some(theirs)));
+        assert!(platform.permits(None));
+        assert_eq!(platform.airline_id(), None);
+    }
+}
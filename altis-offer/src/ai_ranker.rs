@@ -1,185 +1,188 @@
 use crate::models::Offer;
 use crate::features::{SearchContext, OfferFeatures};
 use crate::events::OfferTelemetry;
-use altis_shared::models::events::OfferGeneratedEvent;
-use std::sync::Arc;
-use tonic::transport::Channel;
+use crate::experiments::{self, Assignment, Experiment};
+use crate::strategy::{RankingStrategy, RuleStrategy};
+use crate::training::TrainingRecord;
+use altis_shared::models::events::{ExperimentExposureEvent, OfferGeneratedEvent};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
 
 pub mod ranking {
     tonic::include_proto!("ranking");
 }
 
-use ranking::ranking_service_client::RankingServiceClient;
-use ranking::{PredictConversionRequest, UserContext, SearchContext as ProtoSearchContext, OfferFeatures as ProtoOfferFeatures};
+/// Name of the experiment built from `ml_experiment_percentage`, used while
+/// no persisted experiment is running
+pub const DEFAULT_EXPERIMENT: &str = "ML_RANKER_V1";
 
-/// AI-driven offer ranking (initial rule-based implementation)
+/// AI-driven offer ranking. Each search is ranked by the strategy its
+/// subject's experiment assignment picks.
 pub struct OfferRanker {
     config: altis_store::app_config::RankingConfig,
     telemetry: Option<Arc<OfferTelemetry>>,
-    ml_client: Option<RankingServiceClient<Channel>>,
+    rules: Arc<RuleStrategy>,
+    strategies: HashMap<String, Arc<dyn RankingStrategy>>,
+    experiments: RwLock<Vec<Experiment>>,
 }
 
 // Redundant local config removed, using altis_store::app_config::RankingConfig
 
 impl OfferRanker {
-    pub fn new(config: altis_store::app_config::RankingConfig, telemetry: Option<Arc<OfferTelemetry>>, ml_client: Option<RankingServiceClient<Channel>>) -> Self {
-        Self { config, telemetry, ml_client }
+    pub fn new(config: altis_store::app_config::RankingConfig, telemetry: Option<Arc<OfferTelemetry>>) -> Self {
+        let rules = Arc::new(RuleStrategy::new(&config));
+        let mut strategies: HashMap<String, Arc<dyn RankingStrategy>> = HashMap::new();
+        strategies.insert(rules.name().to_string(), rules.clone());
+
+        Self { config, telemetry, rules, strategies, experiments: RwLock::new(Vec::new()) }
     }
-    
+
+    /// Registers a strategy experiments can select by name
+    pub fn with_strategy(mut self, strategy: Arc<dyn RankingStrategy>) -> Self {
+        self.strategies.insert(strategy.name().to_string(), strategy);
+        self
+    }
+
+    pub fn has_strategy(&self, name: &str) -> bool {
+        self.strategies.contains_key(name)
+    }
+
+    /// Replaces the experiment definitions (reloaded from the database)
+    pub fn set_experiments(&self, experiments: Vec<Experiment>) {
+        *self.experiments.write().unwrap_or_else(|e| e.into_inner()) = experiments;
+    }
+
+    /// Deterministic for a subject (customer or session id) while the
+    /// experiment set is unchanged.
+    pub fn assign(&self, subject: &str) -> Assignment {
+        let now = chrono::Utc::now();
+        let experiments = self.experiments.read().unwrap_or_else(|e| e.into_inner());
+
+        let assignment = if experiments.iter().any(|e| e.is_running(now)) || self.config.ml_experiment_percentage <= 0.0 {
+            experiments::assign(&experiments, subject, now)
+        } else {
+            let default = Experiment {
+                id: Uuid::nil(),
+                name: DEFAULT_EXPERIMENT.to_string(),
+                strategy: "ml".to_string(),
+                traffic_percentage: self.config.ml_experiment_percentage,
+                starts_at: chrono::DateTime::<chrono::Utc>::MIN_UTC,
+                ends_at: None,
+            };
+            experiments::assign(&[default], subject, now)
+        };
+
+        // An experiment naming a strategy this build doesn't have is served as control
+        if self.has_strategy(&assignment.strategy) {
+            assignment
+        } else {
+            Assignment { variant: experiments::Variant::Control, strategy: experiments::CONTROL_STRATEGY.to_string(), ..assignment }
+        }
+    }
+
     /// Rank offers for a specific request
-    pub async fn rank_offers_with_context(&mut self, search_context: &SearchContext, offers: &mut Vec<Offer>) {
-        // 1. Assign experiment
-        let use_ml = self.should_use_ml();
-        let experiment_id = if use_ml { "ML_RANKER_V1" } else { "CONTROL" };
+    pub async fn rank_offers_with_context(&self, search_context: &SearchContext, assignment: &Assignment, offers: &mut Vec<Offer>) {
+        let search_id = Uuid::new_v4();
+        let experiment_id = assignment.experiment.clone().unwrap_or_else(|| "CONTROL".to_string());
+
+        // 1. Extract features
+        let features: HashMap<Uuid, OfferFeatures> = offers.iter()
+            .map(|offer| (offer.id, OfferFeatures::extract(search_context, offer)))
+            .collect();
+
+        // 2. Score with the assigned strategy; offers it didn't score (or
+        //    every offer, if it fails) get rule scores
+        let strategy = self.strategies.get(&assignment.strategy).cloned().unwrap_or_else(|| self.rules.clone());
+        let scores = match strategy.score(search_context, offers, &features).await {
+            Ok(scores) => Some(scores),
+            Err(e) => {
+                tracing::warn!("Ranking strategy {} failed, using rule scores: {}", strategy.name(), e);
+                None
+            }
+        };
 
         for offer in offers.iter_mut() {
-            // 2. Extract features
-            let features = OfferFeatures::extract(search_context, offer);
-            
-            // 3. Calculate score (Rules or ML)
-            let score = if use_ml {
-                self.get_ml_score(search_context, offer, &features).await.unwrap_or_else(|_| self.calculate_rule_score(offer))
-            } else {
-                self.calculate_rule_score(offer)
+            let (score, score_source) = match scores.as_ref().and_then(|s| s.by_offer.get(&offer.id)) {
+                Some(score) => (*score, strategy.name().to_string()),
+                None => (self.rules.score_offer(offer, search_context.customer.as_ref()), "rules_fallback".to_string()),
             };
-            
-            // 4. Update metadata for tracking
+
+            // 3. Update metadata for tracking
             offer.metadata["experiment_id"] = serde_json::json!(experiment_id);
+            offer.metadata["variant"] = serde_json::json!(assignment.variant);
+            offer.metadata["bucket"] = serde_json::json!(assignment.bucket);
             offer.metadata["score"] = serde_json::json!(score);
-
-            // 5. Log telemetry
-            if let Some(ref tel) = self.telemetry {
-                let event = OfferGeneratedEvent {
-                    offer_id: offer.id,
-                    customer_id: None, // TODO: Pull from context
-                    timestamp: chrono::Utc::now().timestamp(),
-                    search_context: serde_json::to_value(search_context).unwrap_or_default(),
-                    features: serde_json::json!({
-                        "days_until_departure": features.days_until_departure,
-                        "is_weekend": features.is_weekend,
-                        "hour_of_day": features.hour_of_day,
-                        "is_domestic": features.is_domestic,
-                        "passenger_count": features.passenger_count,
-                        "price_per_passenger": features.price_per_passenger,
-                        "item_count": features.item_count,
-                    }),
-                };
-                let _ = tel.log_offer_generated(event).await;
+            offer.metadata["score_source"] = serde_json::json!(score_source);
+            offer.metadata["search_id"] = serde_json::json!(search_id);
+            if let Some(model_version) = scores.as_ref().and_then(|s| s.model_version.as_ref()) {
+                offer.metadata["model_version"] = serde_json::json!(model_version);
             }
         }
 
-        // 6. Sort
+        // 4. Sort
         offers.sort_by(|a, b| {
             let score_a = a.metadata["score"].as_f64().unwrap_or(0.0);
             let score_b = b.metadata["score"].as_f64().unwrap_or(0.0);
             score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
         });
-    }
 
-    fn should_use_ml(&self) -> bool {
-        if self.config.ml_experiment_percentage <= 0.0 { return false; }
-        if self.config.ml_experiment_percentage >= 1.0 { return true; }
-        
-        // Simple random assignment for illustration
-        use rand::Rng;
-        rand::thread_rng().gen_bool(self.config.ml_experiment_percentage)
+        // 5. Log telemetry and training rows off the request path
+        if let Some(tel) = self.telemetry.clone() {
+            let events: Vec<(OfferGeneratedEvent, TrainingRecord)> = offers.iter().enumerate().map(|(position, offer)| {
+                let features = features.get(&offer.id)
+                    .and_then(|f| serde_json::to_value(f).ok())
+                    .unwrap_or_default();
+                let generated = OfferGeneratedEvent {
+                    offer_id: offer.id,
+                    customer_id: None, // TODO: Pull from context
+                    timestamp: chrono::Utc::now().timestamp(),
+                    search_context: serde_json::to_value(search_context).unwrap_or_default(),
+                    features: features.clone(),
+                };
+                (generated, TrainingRecord::shown(offer, position, features))
+            }).collect();
+
+            tokio::spawn(async move {
+                for (generated, shown) in events {
+                    let _ = tel.log_offer_generated(generated).await;
+                    if let Err(e) = tel.log_training(&shown).await {
+                        tracing::warn!("Failed to log training row for offer {}: {}", shown.offer_id, e);
+                    }
+                }
+            });
+        }
     }
 
-    async fn get_ml_score(&mut self, context: &SearchContext, offer: &Offer, _features: &OfferFeatures) -> Result<f64, String> {
-        let client = self.ml_client.as_mut().ok_or("ML client not configured")?;
-        
-        let request = tonic::Request::new(PredictConversionRequest {
-            user_context: Some(UserContext {
-                user_id: "".to_string(), // TODO
-                is_guest: true,
-                session_id: "".to_string(),
-            }),
-            search_context: Some(ProtoSearchContext {
-                origin: context.origin.clone(),
-                destination: context.destination.clone(),
-                departure_date: context.departure_date.clone(),
-                passengers: context.passengers,
-                cabin_class: context.cabin_class.clone().unwrap_or_default(),
-                user_segment: context.user_segment.clone().unwrap_or_default(),
-            }),
-            offer_features: Some(ProtoOfferFeatures {
-                offer_id: offer.id.to_string(),
-                total_price_nuc: offer.total_nuc,
-                product_codes: offer.items.iter().filter_map(|i| i.product_code.clone()).collect(),
-                discount_percentage: 0.0, // TODO
-            }),
+    /// Records that the subject saw results ranked under `assignment`,
+    /// whether freshly ranked or served from the search cache
+    pub fn log_exposure(&self, assignment: &Assignment, subject: &str, offer_ids: Vec<Uuid>, cached: bool) {
+        let Some(tel) = self.telemetry.clone() else { return };
+        let event = ExperimentExposureEvent {
+            experiment: assignment.experiment.clone(),
+            variant: assignment.variant.as_str().to_string(),
+            bucket: assignment.bucket,
+            strategy: assignment.strategy.clone(),
+            subject: subject.to_string(),
+            offer_ids,
+            cached,
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        tokio::spawn(async move {
+            if let Err(e) = tel.log_exposure(event).await {
+                tracing::warn!("Failed to log experiment exposure: {}", e);
+            }
         });
-
-        let response = client.predict_conversion(request).await
-            .map_err(|e| e.to_string())?;
-            
-        Ok(response.into_inner().probability)
     }
-    
+
     /// Rank offers using rule-based scoring (deprecated but used as fallback/control)
     pub fn rank_offers(&self, offers: &mut Vec<Offer>) {
         offers.sort_by(|a, b| {
-            let score_a = self.calculate_rule_score(a);
-            let score_b = self.calculate_rule_score(b);
+            let score_a = self.rules.score_offer(a, None);
+            let score_b = self.rules.score_offer(b, None);
             score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
         });
     }
-    
-    /// Calculate ranking score for an offer
-    fn calculate_rule_score(&self, offer: &Offer) -> f64 {
-        let conversion_score = self.estimate_conversion_probability(offer);
-        let margin_score = self.calculate_margin_score(offer);
-        
-        (conversion_score * self.config.conversion_weight) +
-        (margin_score * self.config.margin_weight)
-    }
-    
-    /// Estimate conversion probability (rule-based for now)
-    fn estimate_conversion_probability(&self, offer: &Offer) -> f64 {
-        // Simple heuristic: fewer items = higher conversion
-        // (customers prefer simplicity)
-        let item_count = offer.items.len() as f64;
-        
-        if item_count == 1.0 {
-            0.8 // Flight-only: high conversion
-        } else if item_count <= 3.0 {
-            0.6 // Small bundle: medium conversion
-        } else {
-            0.4 // Large bundle: lower conversion
-        }
-    }
-    
-    /// Calculate profit margin score
-    fn calculate_margin_score(&self, offer: &Offer) -> f64 {
-        // Higher score for offers with higher margin percentage
-        // Average margin percentage across all items
-        let mut total_margin = 0.0;
-        let item_count = offer.items.len();
-        
-        if item_count == 0 { return 0.0; }
-
-        for item in &offer.items {
-            // Try to get margin_percentage from metadata (product repository should have populated this)
-            let margin = item.metadata["margin_percentage"].as_f64().unwrap_or(0.15); // Default 15%
-            total_margin += margin;
-        }
-
-        let avg_margin = total_margin / item_count as f64;
-        
-        // Combine average margin % with total price to prioritize high-value/high-margin bundles
-        let normalized_price = (offer.total_nuc as f64 / 100000.0).min(1.0);
-        
-        (avg_margin * 0.7) + (normalized_price * 0.3)
-    }
-    
-    /// Future: ML model integration stub
-    #[allow(dead_code)]
-    async fn predict_conversion_ml(&self, _offer: &Offer) -> Result<f64, String> {
-        // TODO: Integrate with ML model service
-        // - Send offer features to gRPC/REST endpoint
-        // - Receive conversion probability prediction
-        Err("ML model not yet implemented".to_string())
-    }
 }
 
 #[cfg(test)]
@@ -187,6 +190,7 @@ mod tests {
     use super::*;
     use crate::models::OfferItem;
     use uuid::Uuid;
+    use altis_shared::money::Money;
     
     #[tokio::test]
     async fn test_offer_ranking() {
@@ -195,8 +199,12 @@ mod tests {
             margin_weight: 0.4,
             ml_experiment_percentage: 0.0,
             ml_service_url: None,
+            ml_timeout_ms: 150,
+            ml_breaker_threshold: 5,
+            ml_breaker_reset_seconds: 30,
+            training_topic: "ranking-training".to_string(),
         };
-        let mut ranker = OfferRanker::new(config, None, None);
+        let ranker = OfferRanker::new(config, None);
         
         let mut offers = vec![
             create_test_offer(1, 10000), // Flight-only, low price
@@ -209,20 +217,24 @@ mod tests {
             destination: "LHR".to_string(),
             departure_date: "2024-12-01".to_string(),
             passengers: 1,
+            children: 0,
+            infants: 0,
             cabin_class: None,
             user_segment: None,
+            customer: None,
         };
 
-        ranker.rank_offers_with_context(&context, &mut offers).await;
+        let assignment = ranker.assign("cust-1");
+        ranker.rank_offers_with_context(&context, &assignment, &mut offers).await;
         
         // Flight-only with high price should rank highest (due to rule-based fallback)
         assert_eq!(offers[0].items.len(), 1);
-        assert_eq!(offers[0].total_nuc, 30000);
+        assert_eq!(offers[0].total, Money::nuc(30000));
     }
     
     fn create_test_offer(item_count: usize, total_price: i32) -> Offer {
         let mut offer = Offer::new(None, serde_json::json!({}));
-        offer.total_nuc = total_price;
+        offer.total = Money::from_nuc_i32(total_price);
         
         for _ in 0..item_count {
             offer.items.push(OfferItem::new(
@@ -1,12 +1,16 @@
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use rdkafka::config::ClientConfig;
 use std::time::Duration;
-use altis_shared::models::events::{OfferGeneratedEvent, OfferAcceptedEvent};
+use altis_shared::models::events::{ExperimentExposureEvent, OfferGeneratedEvent, OfferAcceptedEvent};
+use altis_core::events::{registry, VersionedEvent};
 use std::sync::Arc;
 
+use crate::training::TrainingRecord;
+
 pub struct OfferTelemetry {
     producer: Arc<FutureProducer>,
     topic: String,
+    training_topic: String,
 }
 
 impl OfferTelemetry {
@@ -20,31 +24,52 @@ impl OfferTelemetry {
         Self {
             producer: Arc::new(producer),
             topic: topic.to_string(),
+            training_topic: "ranking-training".to_string(),
         }
     }
 
+    pub fn with_training_topic(mut self, topic: &str) -> Self {
+        self.training_topic = topic.to_string();
+        self
+    }
+
     pub async fn log_offer_generated(&self, event: OfferGeneratedEvent) -> Result<(), String> {
-        self.publish("offer_generated", &event).await
+        self.publish(&event).await
     }
 
     pub async fn log_offer_accepted(&self, event: OfferAcceptedEvent) -> Result<(), String> {
-        self.publish("offer_accepted", &event).await
+        self.publish(&event).await
+    }
+
+    pub async fn log_exposure(&self, event: ExperimentExposureEvent) -> Result<(), String> {
+        self.publish(&event).await
     }
 
     pub async fn log_order_paid(&self, event: altis_shared::models::events::OrderPaidEvent) -> Result<(), String> {
-        self.publish("order_paid", &event).await
+        self.publish(&event).await
     }
 
     pub async fn log_settlement(&self, event: altis_shared::models::events::SettlementEvent) -> Result<(), String> {
-        self.publish("settlement", &event).await
+        self.publish(&event).await
+    }
+
+    /// Keyed by offer so an offer's shown and label rows stay on one partition
+    pub async fn log_training(&self, record: &TrainingRecord) -> Result<(), String> {
+        self.send(&self.training_topic, &record.offer_id.to_string(), record).await
+    }
+
+    /// Sent in a versioned envelope, keyed by event type
+    async fn publish<E: VersionedEvent>(&self, event: &E) -> Result<(), String> {
+        let envelope = registry().seal(event).map_err(|e| e.to_string())?;
+        self.send(&self.topic, E::EVENT_TYPE, &envelope).await
     }
 
-    async fn publish<T: serde::Serialize>(&self, event_type: &str, payload: &T) -> Result<(), String> {
+    async fn send<T: serde::Serialize>(&self, topic: &str, key: &str, payload: &T) -> Result<(), String> {
         let json = serde_json::to_string(payload).map_err(|e| e.to_string())?;
         
-        let record = FutureRecord::to(&self.topic)
+        let record = FutureRecord::to(topic)
             .payload(&json)
-            .key(event_type);
+            .key(key);
             
         self.producer
             .send(record, Duration::from_secs(0))
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Subjects are hashed into this many buckets per experiment
+pub const BUCKETS: u32 = 10_000;
+
+/// Strategy served to everyone outside an experiment's treatment
+pub const CONTROL_STRATEGY: &str = "rules";
+
+/// A ranking experiment: `traffic_percentage` of subjects get `strategy`
+/// while it runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Experiment {
+    pub id: Uuid,
+    pub name: String,
+    pub strategy: String,
+    /// Share of subjects (0..=1) in the treatment
+    pub traffic_percentage: f64,
+    pub starts_at: DateTime<Utc>,
+    /// Open-ended when None
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl Experiment {
+    pub fn is_running(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && self.ends_at.is_none_or(|end| now < end)
+    }
+
+    fn treatment_buckets(&self) -> u32 {
+        (self.traffic_percentage.clamp(0.0, 1.0) * BUCKETS as f64).round() as u32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    Treatment,
+    Control,
+}
+
+impl Variant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Variant::Treatment => "treatment",
+            Variant::Control => "control",
+        }
+    }
+}
+
+/// Which strategy ranks a subject's searches, and why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    /// None when no experiment is running
+    pub experiment: Option<String>,
+    pub variant: Variant,
+    pub bucket: u32,
+    pub strategy: String,
+}
+
+impl Assignment {
+    /// Distinguishes cached results ranked under different assignments
+    pub fn cache_label(&self) -> String {
+        match &self.experiment {
+            Some(experiment) => format!("{}:{}", experiment, self.strategy),
+            None => self.strategy.clone(),
+        }
+    }
+}
+
+/// Stable across processes and releases (unlike `DefaultHasher`), so a
+/// subject keeps its bucket for the life of the experiment.
+pub fn bucket(experiment: &str, subject: &str) -> u32 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in experiment.bytes().chain([b':']).chain(subject.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash % BUCKETS as u64) as u32
+}
+
+/// Experiments running at `now` are checked in start order, each with its own
+/// hash, and the first whose treatment the subject falls in wins. Everyone
+/// else is control for the earliest running experiment. Ending an experiment
+/// only releases its treatment subjects; nobody else changes variant.
+pub fn assign(experiments: &[Experiment], subject: &str, now: DateTime<Utc>) -> Assignment {
+    let mut running: Vec<&Experiment> = experiments.iter().filter(|e| e.is_running(now)).collect();
+    running.sort_by(|a, b| a.starts_at.cmp(&b.starts_at).then_with(|| a.name.cmp(&b.name)));
+
+    for experiment in &running {
+        let bucket = bucket(&experiment.name, subject);
+        if bucket < experiment.treatment_buckets() {
+            return Assignment {
+                experiment: Some(experiment.name.clone()),
+                variant: Variant::Treatment,
+                bucket,
+                strategy: experiment.strategy.clone(),
+            };
+        }
+    }
+
+    match running.first() {
+        Some(experiment) => Assignment {
+            experiment: Some(experiment.name.clone()),
+            variant: Variant::Control,
+            bucket: bucket(&experiment.name, subject),
+            strategy: CONTROL_STRATEGY.to_string(),
+        },
+        None => Assignment {
+            experiment: None,
+            variant: Variant::Control,
+            bucket: bucket("", subject),
+            strategy: CONTROL_STRATEGY.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(name: &str, traffic: f64, starts_days_ago: i64) -> Experiment {
+        Experiment {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            strategy: "ml".to_string(),
+            traffic_percentage: traffic,
+            starts_at: Utc::now() - chrono::Duration::days(starts_days_ago),
+            ends_at: None,
+        }
+    }
+
+    #[test]
+    fn test_assignment_is_deterministic_and_respects_traffic() {
+        let experiments = vec![experiment("ML_V2", 0.2, 1)];
+        let now = Utc::now();
+
+        let first = assign(&experiments, "cust-42", now);
+        assert_eq!(assign(&experiments, "cust-42", now), first);
+
+        let treated = (0..10_000)
+            .filter(|i| assign(&experiments, &format!("cust-{}", i), now).variant == Variant::Treatment)
+            .count();
+        assert!((1_700..2_300).contains(&treated), "treated {}", treated);
+    }
+
+    #[test]
+    fn test_ending_an_experiment_keeps_other_assignments() {
+        let mut experiments = vec![experiment("A", 0.3, 2), experiment("B", 0.3, 1)];
+        let now = Utc::now();
+        let before: Vec<Assignment> = (0..1_000).map(|i| assign(&experiments, &format!("s{}", i), now)).collect();
+
+        experiments[0].ends_at = Some(now - chrono::Duration::seconds(1));
+        for (i, old) in before.iter().enumerate() {
+            if old.experiment.as_deref() == Some("B") && old.variant == Variant::Treatment {
+                assert_eq!(&assign(&experiments, &format!("s{}", i), now), old);
+            }
+        }
+
+        // Nothing running: everyone gets control
+        experiments[1].starts_at = now + chrono::Duration::days(1);
+        let idle = assign(&experiments, "s1", now);
+        assert_eq!((idle.experiment, idle.strategy.as_str()), (None, CONTROL_STRATEGY));
+    }
+}
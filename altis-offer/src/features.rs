@@ -8,10 +8,56 @@ pub struct SearchContext {
     pub destination: String,
     pub departure_date: String,
     pub passengers: i32,
+    /// How many of `passengers` are children (CHD) and lap infants (INF)
+    #[serde(default)]
+    pub children: i32,
+    #[serde(default)]
+    pub infants: i32,
     pub cabin_class: Option<String>,
     pub user_segment: Option<String>,
+    /// Purchase history of the searching customer, when there is any
+    #[serde(skip)]
+    pub customer: Option<CustomerFeatures>,
 }
 
+/// Aggregated from a customer's paid orders by the feature refresh worker
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomerFeatures {
+    pub order_count: i32,
+    /// Most frequent cabin on past flights, upper-case
+    pub preferred_cabin: Option<String>,
+    /// Share of orders with at least one ancillary
+    pub ancillary_attach_rate: f64,
+    /// Share of past flights bought at or below the median fare (0.5 = typical)
+    pub price_sensitivity: f64,
+    pub avg_order_value_nuc: i32,
+}
+
+impl CustomerFeatures {
+    /// Coarse profile for keying cached searches: customers in the same
+    /// profile see the same ranking for identical searches.
+    pub fn cache_label(&self) -> String {
+        format!(
+            "{}:{}:{:.1}:{:.1}",
+            self.order_count.min(5),
+            self.preferred_cabin.as_deref().unwrap_or("-"),
+            self.ancillary_attach_rate,
+            self.price_sensitivity,
+        )
+    }
+
+    /// Whether any flight in the offer is in the customer's preferred cabin
+    pub fn prefers_cabin_of(&self, offer: &Offer) -> bool {
+        let Some(preferred) = self.preferred_cabin.as_deref() else { return false };
+        offer.items.iter()
+            .filter(|item| item.product_type.eq_ignore_ascii_case("flight"))
+            .any(|item| item.metadata["cabin_class"].as_str().is_some_and(|cabin| cabin.eq_ignore_ascii_case(preferred)))
+    }
+}
+
+/// Model inputs for one offer. Served to the ranking service and logged
+/// for training under the same field names.
+#[derive(Debug, Clone, Serialize)]
 pub struct OfferFeatures {
     // Temporal features
     pub days_until_departure: i32,
@@ -25,6 +71,12 @@ pub struct OfferFeatures {
     // Price features
     pub price_per_passenger: f64,
     pub item_count: i32,
+
+    // Customer features (zero for customers without purchases)
+    pub customer_order_count: i32,
+    pub customer_ancillary_attach_rate: f64,
+    pub customer_price_sensitivity: f64,
+    pub matches_preferred_cabin: bool,
 }
 
 impl OfferFeatures {
@@ -47,11 +99,14 @@ impl OfferFeatures {
         // 3. Price
         let item_count = offer.items.len() as i32;
         let price_per_passenger = if passenger_count > 0 {
-            offer.total_nuc as f64 / passenger_count as f64
+            offer.total.minor_units() as f64 / passenger_count as f64
         } else {
-            offer.total_nuc as f64
+            offer.total.minor_units() as f64
         };
 
+        // 4. Customer
+        let customer = context.customer.clone().unwrap_or_default();
+
         Self {
             days_until_departure,
             is_weekend,
@@ -60,6 +115,10 @@ impl OfferFeatures {
             passenger_count,
             price_per_passenger,
             item_count,
+            customer_order_count: customer.order_count,
+            customer_ancillary_attach_rate: customer.ancillary_attach_rate,
+            customer_price_sensitivity: customer.price_sensitivity,
+            matches_preferred_cabin: customer.prefers_cabin_of(offer),
         }
     }
 }
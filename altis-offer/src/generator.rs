@@ -1,6 +1,13 @@
 use crate::models::{Offer, OfferItem};
 use crate::rules::{RuleEngine, get_default_rules};
-use altis_catalog::{Product, ProductType, PricingEngine, PricingContext};
+use altis_catalog::{MarketType, PassengerType, Product, ProductType, PricingEngine, PricingContext, RouteNetwork, TaxEngine};
+use altis_shared::money::{Money, MoneyError, Rounding};
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use uuid::Uuid;
+
+/// Offer variants built at once for a search
+const VARIANT_CONCURRENCY: usize = 4;
 
 /// Offer generation strategies
 /// Offer generation strategies (Dynamic variants)
@@ -17,6 +24,8 @@ pub enum OfferStrategy {
 pub struct OfferGenerator {
     pricing_engine: PricingEngine,
     rule_engine: RuleEngine,
+    tax_engine: TaxEngine,
+    routes: RouteNetwork,
 }
 
 impl OfferGenerator {
@@ -24,7 +33,64 @@ impl OfferGenerator {
         Self { 
             pricing_engine,
             rule_engine: RuleEngine::new(get_default_rules()),
+            tax_engine: TaxEngine::default(),
+            routes: RouteNetwork::default(),
+        }
+    }
+
+    /// Taxes are itemized on every offer item; without an engine none are charged
+    pub fn with_tax_engine(mut self, tax_engine: TaxEngine) -> Self {
+        self.tax_engine = tax_engine;
+        self
+    }
+
+    /// The airline's routes: their market sets the fare multiplier and which
+    /// taxes apply. Routes it doesn't know are priced without one.
+    pub fn with_routes(mut self, routes: RouteNetwork) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Stamps the market data of the route in `route` (its `origin` and
+    /// `destination`) onto it, for the tax engine and the storefront, and
+    /// returns the market. Values already there are kept.
+    fn stamp_market(&self, route: &mut serde_json::Value) -> Option<MarketType> {
+        let known = self.routes.get(route["origin"].as_str()?, route["destination"].as_str()?)?;
+        let fields = route.as_object_mut()?;
+        for (key, value) in [
+            ("market", serde_json::json!(known.market)),
+            ("origin_country", serde_json::json!(known.origin_country)),
+            ("destination_country", serde_json::json!(known.destination_country)),
+            ("distance_km", serde_json::json!(known.distance_km)),
+            ("sale_currency", serde_json::json!(known.sale_currency)),
+        ] {
+            fields.entry(key).or_insert(value);
         }
+        Some(known.market)
+    }
+
+    /// Takes the best campaign covering the item off its price, and names the
+    /// campaign in the item's metadata for the storefront
+    fn apply_campaign(&self, item: &mut OfferItem, product_type: &ProductType, route: &serde_json::Value, at: DateTime<Utc>) -> Result<(), OfferError> {
+        let Some(campaign) = self.pricing_engine.best_campaign(product_type, route["origin"].as_str(), route["destination"].as_str(), at) else {
+            return Ok(());
+        };
+        item.price = self.pricing_engine.apply_campaign(item.price, campaign)?;
+        if let Some(metadata) = item.metadata.as_object_mut() {
+            metadata.insert("campaign".to_string(), serde_json::json!({
+                "id": campaign.id,
+                "name": campaign.name,
+                "discount_percentage": campaign.discount_percentage,
+                "banner": campaign.banner,
+            }));
+        }
+        Ok(())
+    }
+
+    fn apply_taxes(&self, item: &mut OfferItem, product_type: &ProductType, route: &serde_json::Value) -> Result<(), MoneyError> {
+        let product_type = serde_json::to_value(product_type).ok();
+        let product_type = product_type.as_ref().and_then(|v| v.as_str()).unwrap_or_default();
+        item.apply_taxes(self.tax_engine.calculate(product_type, route, item.line_price()?.to_i32()?, item.quantity))
     }
     
     /// Generate multiple offer variants for a search
@@ -36,62 +102,90 @@ impl OfferGenerator {
         flight_products: Vec<Product>,
         ancillary_products: Vec<Product>,
     ) -> Result<Vec<Offer>, OfferError> {
-        let mut offers = Vec::new();
-        
         let mut context = search_context.clone();
         context["user_segment"] = serde_json::json!(user_segment);
-        
-        // Strategy 1: Baseline
-        if let Some(offer) = self.create_offer(
-            customer_id.clone(),
-            user_segment.clone(),
-            context.clone(),
-            &flight_products,
-            &ancillary_products,
-            OfferStrategy::Baseline,
-        ).await? {
-            offers.push(offer);
-        }
-        
-        // Strategy 2: Dynamic (Rule-based)
-        if let Some(offer) = self.create_offer(
-            customer_id.clone(),
-            user_segment.clone(),
-            context.clone(),
-            &flight_products,
-            &ancillary_products,
-            OfferStrategy::Dynamic,
-        ).await? {
-            offers.push(offer);
-        }
-        
-        Ok(offers)
-    }
-    
-    /// Create a single offer based on strategy
-    async fn create_offer(
-        &self,
-        customer_id: Option<String>,
-        user_segment: Option<String>,
-        context: serde_json::Value,
-        flight_products: &[Product],
-        ancillary_products: &[Product],
-        strategy: OfferStrategy,
-    ) -> Result<Option<Offer>, OfferError> {
-        let mut offer = Offer::new(customer_id, None, context.clone());
-        
+        // Ancillaries are taxed on the searched route's market
+        let market = self.stamp_market(&mut context);
+
         let pricing_context = PricingContext {
             user_segment,
+            market,
             ..Default::default()
         };
+        // Every variant carries the same flights at the same fares, so
+        // they're priced once and shared
+        let flight_items = self.price_flights(&context, &pricing_context, &flight_products)?;
 
-        // Add flight products
-        for flight in flight_products {
-            let price = self.pricing_engine.apply_continuous_adjustment(
-                flight.base_price_nuc,
+        // Strategy 1: Baseline; Strategy 2: Dynamic (Rule-based)
+        let strategies = [OfferStrategy::Baseline, OfferStrategy::Dynamic];
+        let variants: Vec<_> = stream::iter(strategies)
+            .map(|strategy| self.create_offer(
+                customer_id.clone(),
+                &context,
                 &pricing_context,
+                &flight_items,
+                &ancillary_products,
+                strategy,
+            ))
+            .buffered(VARIANT_CONCURRENCY)
+            .collect()
+            .await;
+
+        variants.into_iter().filter_map(Result::transpose).collect()
+    }
+
+    /// Ancillary-only offers for a booking that already exists, one per
+    /// product. There is no flight to price: `search_context` is the booked
+    /// trip (route, departure date and passengers), and each offer carries
+    /// `order_id` in it so accepting adds the item to that order instead of
+    /// creating a new one. Rules, campaigns and taxes apply as in a search.
+    pub fn generate_ancillary_offers(
+        &self,
+        order_id: Uuid,
+        airline_id: Option<Uuid>,
+        user_segment: Option<String>,
+        search_context: serde_json::Value,
+        ancillary_products: &[Product],
+    ) -> Result<Vec<Offer>, OfferError> {
+        let mut context = search_context;
+        context["user_segment"] = serde_json::json!(user_segment);
+        context["order_id"] = serde_json::json!(order_id);
+        self.stamp_market(&mut context);
+        let at = Utc::now();
+
+        let mut offers = Vec::new();
+        for product in ancillary_products.iter().filter(|p| p.is_active && p.product_type != ProductType::Flight) {
+            let discount = self.rule_engine.evaluate_discount(&product.product_type, &context);
+            let mut item = OfferItem::new(
+                format!("{:?}", product.product_type),
+                Some(product.id),
+                Some(product.product_code.clone()),
+                product.name.clone(),
+                product.description.clone(),
+                discounted(product.base_price_nuc, discount)?,
+                1,
+                product.metadata.clone(),
             );
-            
+            self.apply_campaign(&mut item, &product.product_type, &context, at)?;
+            self.apply_taxes(&mut item, &product.product_type, &context)?;
+
+            let mut offer = Offer::new(None, airline_id, context.clone());
+            offer.add_item(item)?;
+            offers.push(offer);
+        }
+        Ok(offers)
+    }
+
+    /// Flight items at their adjusted, campaign-discounted and taxed fares,
+    /// one per passenger type in the party, for as many passengers as it has
+    fn price_flights(
+        &self,
+        context: &serde_json::Value,
+        pricing_context: &PricingContext,
+        flight_products: &[Product],
+    ) -> Result<Vec<OfferItem>, OfferError> {
+        let mut items = Vec::with_capacity(flight_products.len());
+        for flight in flight_products {
             // Enrich metadata with flight details if missing
             let mut metadata = if flight.metadata.is_null() {
                 serde_json::json!({})
@@ -119,18 +213,58 @@ impl OfferGenerator {
                 }
             }
 
-            let item = OfferItem::new(
-                format!("{:?}", flight.product_type),
-                Some(flight.id),
-                None,
-                flight.name.clone(),
-                flight.description.clone(),
-                price,
-                1,
-                metadata,
-            );
-            
-            offer.add_item(item);
+            // Each flight is priced for its own route's market
+            let pricing_context = PricingContext {
+                market: self.stamp_market(&mut metadata).or(pricing_context.market),
+                ..pricing_context.clone()
+            };
+            let adult_fare = self.pricing_engine.apply_continuous_adjustment(
+                Money::from_nuc_i32(flight.base_price_nuc),
+                &pricing_context,
+            )?;
+
+            for (ptc, count) in party(context) {
+                let mut metadata = metadata.clone();
+                if let Some(obj) = metadata.as_object_mut() {
+                    obj.insert("ptc".to_string(), serde_json::json!(ptc));
+                    if ptc == PassengerType::Infant {
+                        obj.insert("lap_infant".to_string(), serde_json::json!(true));
+                    }
+                }
+                let mut item = OfferItem::new(
+                    format!("{:?}", flight.product_type),
+                    Some(flight.id),
+                    None,
+                    flight.name.clone(),
+                    flight.description.clone(),
+                    self.pricing_engine.passenger_fare(adult_fare, ptc)?,
+                    count,
+                    metadata,
+                );
+                let route = item.metadata.clone();
+                self.apply_campaign(&mut item, &flight.product_type, &route, pricing_context.timestamp)?;
+                self.apply_taxes(&mut item, &flight.product_type, &route)?;
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+    
+    /// Create a single offer based on strategy
+    async fn create_offer(
+        &self,
+        customer_id: Option<String>,
+        context: &serde_json::Value,
+        pricing_context: &PricingContext,
+        flight_items: &[OfferItem],
+        ancillary_products: &[Product],
+        strategy: OfferStrategy,
+    ) -> Result<Option<Offer>, OfferError> {
+        let mut offer = Offer::new(customer_id, None, context.clone());
+
+        // Add flight products, each a separate item of this offer
+        for item in flight_items {
+            offer.add_item(OfferItem { id: Uuid::new_v4(), ..item.clone() })?;
         }
         
         // Add ancillaries based on strategy
@@ -140,13 +274,13 @@ impl OfferGenerator {
             },
             OfferStrategy::Dynamic => {
                 // Evaluate rules for bundling
-                let bundled_types = self.rule_engine.evaluate_bundling(&context);
+                let bundled_types = self.rule_engine.evaluate_bundling(context);
                 for pt in bundled_types {
                     if let Some(product) = ancillary_products.iter().find(|p| p.product_type == pt) {
-                        let discount = self.rule_engine.evaluate_discount(&pt, &context);
-                        let final_price = (product.base_price_nuc as f64 * (1.0 - discount)) as i32;
+                        let discount = self.rule_engine.evaluate_discount(&pt, context);
+                        let final_price = discounted(product.base_price_nuc, discount)?;
                         
-                        let item = OfferItem::new(
+                        let mut item = OfferItem::new(
                             format!("{:?}", pt),
                             Some(product.id),
                             None,
@@ -156,7 +290,10 @@ impl OfferGenerator {
                             1,
                             product.metadata.clone(),
                         );
-                        offer.add_item(item);
+                        // Ancillaries are discounted and taxed on the route they're sold with
+                        self.apply_campaign(&mut item, &pt, context, pricing_context.timestamp)?;
+                        self.apply_taxes(&mut item, &pt, context)?;
+                        offer.add_item(item)?;
                     }
                 }
             },
@@ -180,9 +317,11 @@ impl OfferGenerator {
                 .find(|p| &p.product_type == product_type && p.is_active)
             {
                 // Apply 10% bundle discount
-                let price = (product.base_price_nuc as f64 * 0.9) as i32;
+                let Ok(price) = discounted(product.base_price_nuc, 0.1) else {
+                    continue;
+                };
                 
-                let item = OfferItem::new(
+                let mut item = OfferItem::new(
                     format!("{:?}", product.product_type),
                     Some(product.id),
                     None, // product_code
@@ -192,13 +331,34 @@ impl OfferGenerator {
                     1, // quantity
                     product.metadata.clone(),
                 );
+                let search_context = offer.search_context.clone();
+                if self.apply_taxes(&mut item, &product.product_type, &search_context).is_err() {
+                    continue;
+                }
                 
-                offer.add_item(item);
+                let _ = offer.add_item(item);
             }
         }
     }
 }
 
+/// Passengers of each type the search is for, leaving out types nobody
+/// is; adults are whoever isn't a child or infant, and at least one
+fn party(context: &serde_json::Value) -> Vec<(PassengerType, i32)> {
+    let count = |field: &str| context[field].as_i64().unwrap_or(0).max(0) as i32;
+    let (children, infants) = (count("children"), count("infants"));
+    let adults = (count("passengers") - children - infants).max(1);
+    [(PassengerType::Adult, adults), (PassengerType::Child, children), (PassengerType::Infant, infants)]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+/// A base price less a fractional discount, rounded down
+pub(crate) fn discounted(base_price_nuc: i32, discount: f64) -> Result<Money, MoneyError> {
+    Money::from_nuc_i32(base_price_nuc).scale(1.0 - discount, Rounding::Down)
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum OfferError {
     #[error("No products available for offer generation")]
@@ -210,3 +370,101 @@ pub enum OfferError {
     #[error("Invalid search context: {0}")]
     InvalidContext(String),
 }
+
+impl From<MoneyError> for OfferError {
+    fn from(e: MoneyError) -> Self {
+        OfferError::PricingFailed(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altis_catalog::pricing::PricingConfig;
+
+    fn product(product_type: ProductType, base_price_nuc: i32) -> Product {
+        Product {
+            id: Uuid::new_v4(),
+            product_type,
+            product_code: "TEST".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            base_price_nuc,
+            margin_percentage: 0.15,
+            is_active: true,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_variants_share_flight_fares_but_not_items() {
+        let generator = OfferGenerator::new(PricingEngine::new(PricingConfig::default()));
+        let context = serde_json::json!({"origin": "SIN", "destination": "KUL", "departure_date": "2026-06-01"});
+        let flights = vec![product(ProductType::Flight, 20_000), product(ProductType::Flight, 35_000)];
+
+        let offers = generator.generate_offers(None, None, context, flights, Vec::new()).await.unwrap();
+        assert_eq!(offers.len(), 2);
+        let fares = |offer: &Offer| offer.items.iter().map(|i| i.price).collect::<Vec<_>>();
+        assert_eq!(fares(&offers[0]), fares(&offers[1]));
+        // Priced once, but each offer still has items of its own
+        assert!(offers[0].items.iter().all(|a| offers[1].items.iter().all(|b| a.id != b.id)));
+    }
+
+    #[tokio::test]
+    async fn test_flights_are_priced_for_their_market() {
+        let route = altis_catalog::Route {
+            id: Uuid::new_v4(),
+            airline_id: Uuid::nil(),
+            origin: "SIN".to_string(),
+            destination: "KUL".to_string(),
+            origin_city: Some("SIN".to_string()),
+            destination_city: Some("KUL".to_string()),
+            origin_country: "SG".to_string(),
+            destination_country: "MY".to_string(),
+            distance_km: 300,
+            market: MarketType::International,
+            sale_currency: "SGD".to_string(),
+            is_active: true,
+        };
+        let pricing = PricingConfig { international_multiplier: 1.1, ..Default::default() };
+        let generator = OfferGenerator::new(PricingEngine::new(pricing)).with_routes(RouteNetwork::new(vec![route]));
+        let context = serde_json::json!({"origin": "SIN", "destination": "KUL", "departure_date": "2026-06-01"});
+
+        let offers = generator.generate_offers(None, None, context, vec![product(ProductType::Flight, 20_000)], Vec::new()).await.unwrap();
+        let flight = &offers[0].items[0];
+        assert_eq!(flight.price.minor_units(), 22_000);
+        assert_eq!(flight.metadata["market"], "INTERNATIONAL");
+        assert_eq!(flight.metadata["sale_currency"], "SGD");
+    }
+
+    #[tokio::test]
+    async fn test_flights_are_priced_per_passenger_type() {
+        let generator = OfferGenerator::new(PricingEngine::new(PricingConfig::default()));
+        let context = serde_json::json!({"origin": "SIN", "destination": "KUL", "departure_date": "2026-06-01",
+                                         "passengers": 4, "children": 1, "infants": 1});
+
+        let offers = generator.generate_offers(None, None, context, vec![product(ProductType::Flight, 20_000)], Vec::new()).await.unwrap();
+        let fares: Vec<_> = offers[0].items.iter()
+            .map(|item| (item.metadata["ptc"].as_str().unwrap(), item.quantity, item.price.minor_units()))
+            .collect();
+        assert_eq!(fares, vec![("ADT", 2, 20_000), ("CHD", 1, 15_000), ("INF", 1, 2_000)]);
+        assert_eq!(offers[0].items[2].metadata["lap_infant"], true);
+        assert!(offers[0].items[..2].iter().all(|item| item.metadata["lap_infant"].is_null()));
+        assert_eq!(offers[0].total.minor_units(), 57_000);
+    }
+
+    #[test]
+    fn test_ancillary_offers_attach_to_the_booking_without_a_flight() {
+        let generator = OfferGenerator::new(PricingEngine::new(PricingConfig::default()));
+        let order_id = Uuid::new_v4();
+        let context = serde_json::json!({"origin": "SIN", "destination": "KUL", "departure_date": "2026-06-01", "passengers": 2});
+        let products = vec![product(ProductType::Flight, 20_000), product(ProductType::Bag, 3_000), product(ProductType::Seat, 1_500)];
+
+        let offers = generator.generate_ancillary_offers(order_id, None, Some("corporate".to_string()), context, &products).unwrap();
+        assert_eq!(offers.len(), 2);
+        assert!(offers.iter().all(|offer| offer.items.len() == 1 && offer.items[0].product_type != "Flight"));
+        assert!(offers.iter().all(|offer| offer.search_context["order_id"] == serde_json::json!(order_id)));
+        // Corporate travelers get half-price seats by rule, as in a search
+        assert_eq!(offers[1].items[0].price.minor_units(), 750);
+    }
+}
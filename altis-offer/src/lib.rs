@@ -5,6 +5,10 @@ pub mod expiry;
 pub mod features;
 pub mod events;
 pub mod rules;
+pub mod training;
+pub mod strategy;
+pub mod experiments;
+pub mod upsell;
 
 pub use models::{Offer, OfferItem, OfferStatus};
 pub use generator::OfferGenerator;
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use altis_catalog::TaxLine;
+use altis_shared::money::{self, Currency, Money, MoneyError};
 
 /// Offer status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -20,7 +22,9 @@ pub struct Offer {
     pub airline_id: Option<Uuid>,
     pub search_context: serde_json::Value,
     pub items: Vec<OfferItem>,
-    pub total_nuc: i32,
+    /// Items' prices and taxes
+    #[serde(rename = "total_nuc", with = "money::nuc")]
+    pub total: Money,
     pub currency: String,
     pub status: OfferStatus,
     pub expires_at: DateTime<Utc>,
@@ -38,7 +42,7 @@ impl Offer {
             airline_id,
             search_context,
             items: Vec::new(),
-            total_nuc: 0,
+            total: Money::zero(Currency::NUC),
             currency: "NUC".to_string(),
             status: OfferStatus::Active,
             expires_at: now + chrono::Duration::minutes(15),
@@ -47,10 +51,11 @@ impl Offer {
         }
     }
     
-    /// Add an item to the offer
-    pub fn add_item(&mut self, item: OfferItem) {
-        self.total_nuc += item.price_nuc;
+    /// Add an item to the offer; the total includes its taxes
+    pub fn add_item(&mut self, item: OfferItem) -> Result<(), MoneyError> {
+        self.total = self.total.checked_add(item.line_price()?)?.checked_add(item.tax)?;
         self.items.push(item);
+        Ok(())
     }
     
     /// Check if offer is expired
@@ -73,9 +78,20 @@ pub struct OfferItem {
     pub product_code: Option<String>,
     pub name: String,
     pub description: Option<String>,
-    pub price_nuc: i32,
+    /// Price of one unit
+    #[serde(rename = "price_nuc", with = "money::nuc")]
+    pub price: Money,
     pub quantity: i32,
     pub metadata: serde_json::Value,
+    /// Sum of `taxes` for all units, charged on top of the line price
+    #[serde(rename = "tax_nuc", with = "money::nuc", default = "money::nuc::zero")]
+    pub tax: Money,
+    #[serde(default)]
+    pub taxes: Vec<TaxLine>,
+    /// The traveler an ancillary is for, by their index on the order it
+    /// becomes; none when it serves the whole booking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traveler_index: Option<i32>,
 }
 
 impl OfferItem {
@@ -85,7 +101,7 @@ impl OfferItem {
         product_code: Option<String>,
         name: String,
         description: Option<String>,
-        price_nuc: i32,
+        price: Money,
         quantity: i32,
         metadata: serde_json::Value,
     ) -> Self {
@@ -96,9 +112,23 @@ impl OfferItem {
             product_code,
             name,
             description,
-            price_nuc,
+            price,
             quantity,
             metadata,
+            tax: money::nuc::zero(),
+            taxes: Vec::new(),
+            traveler_index: None,
         }
     }
+
+    /// `price` for every unit on the line
+    pub fn line_price(&self) -> Result<Money, MoneyError> {
+        self.price.checked_mul(self.quantity.max(1) as i64)
+    }
+
+    pub fn apply_taxes(&mut self, taxes: Vec<TaxLine>) -> Result<(), MoneyError> {
+        self.tax = Money::sum(Currency::NUC, taxes.iter().map(|t| Money::from_nuc_i32(t.amount_nuc)))?;
+        self.taxes = taxes;
+        Ok(())
+    }
 }
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use altis_store::chaos::{ChaosInjector, ChaosTarget};
+use altis_store::deadline;
+use altis_store::CircuitBreaker;
+use tonic::transport::Channel;
+use uuid::Uuid;
+
+use crate::ai_ranker::ranking::ranking_service_client::RankingServiceClient;
+use crate::ai_ranker::ranking::{PredictConversionBatchRequest, UserContext, SearchContext as ProtoSearchContext, OfferFeatures as ProtoOfferFeatures};
+use crate::features::{CustomerFeatures, OfferFeatures, SearchContext};
+use crate::models::Offer;
+
+/// Scores produced by a strategy for one search
+#[derive(Debug, Default)]
+pub struct Scores {
+    pub by_offer: HashMap<Uuid, f64>,
+    pub model_version: Option<String>,
+}
+
+/// A way of scoring offers; higher ranks first. Experiments pick strategies
+/// by `name`. Offers a strategy leaves unscored, or all of them when it
+/// errors, fall back to the rule score.
+#[async_trait]
+pub trait RankingStrategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn score(
+        &self,
+        context: &SearchContext,
+        offers: &[Offer],
+        features: &HashMap<Uuid, OfferFeatures>,
+    ) -> Result<Scores, String>;
+}
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+/// Weighted mix of a heuristic conversion estimate and margin, with the
+/// estimate nudged by the customer's purchase history. Serves the control
+/// group and every fallback.
+pub struct RuleStrategy {
+    conversion_weight: f64,
+    margin_weight: f64,
+}
+
+impl RuleStrategy {
+    pub fn new(config: &altis_store::app_config::RankingConfig) -> Self {
+        Self { conversion_weight: config.conversion_weight, margin_weight: config.margin_weight }
+    }
+
+    /// Calculate ranking score for an offer
+    pub fn score_offer(&self, offer: &Offer, customer: Option<&CustomerFeatures>) -> f64 {
+        let mut conversion_score = self.estimate_conversion_probability(offer);
+        if let Some(customer) = customer {
+            conversion_score = personalize_conversion(conversion_score, offer, customer);
+        }
+        let margin_score = self.calculate_margin_score(offer);
+
+        (conversion_score * self.conversion_weight) +
+        (margin_score * self.margin_weight)
+    }
+
+    /// Estimate conversion probability (rule-based for now)
+    fn estimate_conversion_probability(&self, offer: &Offer) -> f64 {
+        // Simple heuristic: fewer items = higher conversion
+        // (customers prefer simplicity)
+        let item_count = offer.items.len() as f64;
+
+        if item_count == 1.0 {
+            0.8 // Flight-only: high conversion
+        } else if item_count <= 3.0 {
+            0.6 // Small bundle: medium conversion
+        } else {
+            0.4 // Large bundle: lower conversion
+        }
+    }
+
+    /// Calculate profit margin score
+    fn calculate_margin_score(&self, offer: &Offer) -> f64 {
+        // Higher score for offers with higher margin percentage
+        // Average margin percentage across all items
+        let mut total_margin = 0.0;
+        let item_count = offer.items.len();
+
+        if item_count == 0 { return 0.0; }
+
+        for item in &offer.items {
+            // Try to get margin_percentage from metadata (product repository should have populated this)
+            let margin = item.metadata["margin_percentage"].as_f64().unwrap_or(0.15); // Default 15%
+            total_margin += margin;
+        }
+
+        let avg_margin = total_margin / item_count as f64;
+
+        // Combine average margin % with total price to prioritize high-value/high-margin bundles
+        let normalized_price = (offer.total.minor_units() as f64 / 100000.0).min(1.0);
+
+        (avg_margin * 0.7) + (normalized_price * 0.3)
+    }
+}
+
+/// Blends the generic estimate with what the customer tends to buy. History
+/// gains weight with each order, up to half the estimate at five orders.
+fn personalize_conversion(base: f64, offer: &Offer, customer: &CustomerFeatures) -> f64 {
+    let confidence = (customer.order_count as f64 / 5.0).clamp(0.0, 1.0) * 0.5;
+    if confidence == 0.0 {
+        return base;
+    }
+
+    // Bundles suit customers who usually add extras; flight-only suits the rest
+    let has_ancillaries = offer.items.len() > 1;
+    let mut personal = if has_ancillaries {
+        customer.ancillary_attach_rate
+    } else {
+        1.0 - customer.ancillary_attach_rate
+    };
+
+    // Price-sensitive customers shy away from expensive offers
+    let normalized_price = (offer.total.minor_units() as f64 / 100000.0).min(1.0);
+    personal *= 1.0 - (customer.price_sensitivity - 0.5).max(0.0) * normalized_price;
+
+    if customer.prefers_cabin_of(offer) {
+        personal = (personal + 0.2).min(1.0);
+    }
+
+    base * (1.0 - confidence) + personal * confidence
+}
+
+#[async_trait]
+impl RankingStrategy for RuleStrategy {
+    fn name(&self) -> &str {
+        "rules"
+    }
+
+    async fn score(
+        &self,
+        context: &SearchContext,
+        offers: &[Offer],
+        _features: &HashMap<Uuid, OfferFeatures>,
+    ) -> Result<Scores, String> {
+        Ok(Scores {
+            by_offer: offers.iter().map(|offer| (offer.id, self.score_offer(offer, context.customer.as_ref()))).collect(),
+            model_version: None,
+        })
+    }
+}
+
+// ============================================================================
+// ML
+// ============================================================================
+
+/// Conversion probability from the gRPC ranking service: one deadline-bound
+/// batch call per search, skipped while the circuit is open.
+pub struct MlStrategy {
+    client: Option<RankingServiceClient<Channel>>,
+    timeout: Duration,
+    breaker: CircuitBreaker,
+    chaos: Arc<ChaosInjector>,
+}
+
+impl MlStrategy {
+    pub fn new(config: &altis_store::app_config::RankingConfig, client: Option<RankingServiceClient<Channel>>) -> Self {
+        Self {
+            client,
+            timeout: Duration::from_millis(config.ml_timeout_ms),
+            breaker: CircuitBreaker::new("MLRanking", config.ml_breaker_threshold, Duration::from_secs(config.ml_breaker_reset_seconds)),
+            chaos: Arc::new(ChaosInjector::disabled()),
+        }
+    }
+
+    pub fn with_chaos(mut self, chaos: Arc<ChaosInjector>) -> Self {
+        self.chaos = chaos;
+        self
+    }
+}
+
+#[async_trait]
+impl RankingStrategy for MlStrategy {
+    fn name(&self) -> &str {
+        "ml"
+    }
+
+    async fn score(
+        &self,
+        context: &SearchContext,
+        offers: &[Offer],
+        features: &HashMap<Uuid, OfferFeatures>,
+    ) -> Result<Scores, String> {
+        let mut client = self.client.clone().ok_or("ML client not configured")?;
+        // Never wait past the search request's own deadline
+        let timeout = deadline::bounded(self.timeout);
+        if timeout.is_zero() {
+            return Err("request deadline exceeded before ranking".to_string());
+        }
+        if !self.breaker.check().await {
+            return Err("ML ranking circuit is open".to_string());
+        }
+
+        let customer = context.customer.clone().unwrap_or_default();
+        let mut request = tonic::Request::new(PredictConversionBatchRequest {
+            user_context: Some(UserContext {
+                user_id: "".to_string(), // TODO
+                is_guest: true,
+                session_id: "".to_string(),
+                order_count: customer.order_count,
+                preferred_cabin: customer.preferred_cabin.clone().unwrap_or_default(),
+                ancillary_attach_rate: customer.ancillary_attach_rate,
+                price_sensitivity: customer.price_sensitivity,
+                avg_order_value_nuc: customer.avg_order_value_nuc,
+            }),
+            search_context: Some(ProtoSearchContext {
+                origin: context.origin.clone(),
+                destination: context.destination.clone(),
+                departure_date: context.departure_date.clone(),
+                passengers: context.passengers,
+                cabin_class: context.cabin_class.clone().unwrap_or_default(),
+                user_segment: context.user_segment.clone().unwrap_or_default(),
+            }),
+            offers: offers.iter().filter_map(|offer| {
+                let f = features.get(&offer.id)?;
+                Some(ProtoOfferFeatures {
+                    offer_id: offer.id.to_string(),
+                    total_price_nuc: offer.total.to_i32().ok()?,
+                    product_codes: offer.items.iter().filter_map(|i| i.product_code.clone()).collect(),
+                    discount_percentage: 0.0, // TODO
+                    days_until_departure: f.days_until_departure,
+                    is_weekend: f.is_weekend,
+                    hour_of_day: f.hour_of_day,
+                    is_domestic: f.is_domestic,
+                    passenger_count: f.passenger_count,
+                    price_per_passenger: f.price_per_passenger,
+                    item_count: f.item_count,
+                    matches_preferred_cabin: f.matches_preferred_cabin,
+                })
+            }).collect(),
+        });
+        // Lets the service drop work we've stopped waiting for
+        request.set_timeout(timeout);
+
+        let call = async {
+            self.chaos.inject(ChaosTarget::Ml).await.map_err(|e| e.to_string())?;
+            client.predict_conversion_batch(request).await.map_err(|e| e.to_string())
+        };
+        let result = match tokio::time::timeout(timeout, call).await {
+            Ok(result) => result,
+            // Cut short by the request rather than slow on its own: not the service's failure
+            Err(_) if timeout < self.timeout => return Err(format!("request deadline exceeded after {:?}", timeout)),
+            Err(_) => Err(format!("timed out after {:?}", timeout)),
+        };
+
+        match result {
+            Ok(response) => {
+                self.breaker.record_success().await;
+                let response = response.into_inner();
+                Ok(Scores {
+                    by_offer: response.scores.into_iter()
+                        .filter_map(|s| Some((Uuid::parse_str(&s.offer_id).ok()?, s.probability)))
+                        .collect(),
+                    model_version: Some(response.model_version),
+                })
+            }
+            Err(e) => {
+                self.breaker.record_failure().await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OfferItem;
+    use altis_shared::money::Money;
+
+    fn offer(item_types: &[&str], cabin: &str) -> Offer {
+        let mut offer = Offer::new(None, None, serde_json::json!({}));
+        for product_type in item_types {
+            offer.add_item(OfferItem::new(
+                product_type.to_string(), None, None, product_type.to_string(), None,
+                Money::nuc(10000), 1, serde_json::json!({"cabin_class": cabin}),
+            )).unwrap();
+        }
+        offer
+    }
+
+    #[test]
+    fn test_history_shifts_bundle_preference() {
+        let flight_only = offer(&["FLIGHT"], "ECONOMY");
+        let bundle = offer(&["FLIGHT", "BAG", "SEAT"], "ECONOMY");
+        let extras_buyer = CustomerFeatures {
+            order_count: 8,
+            ancillary_attach_rate: 0.9,
+            price_sensitivity: 0.5,
+            ..Default::default()
+        };
+
+        // Without history the flight-only offer converts better
+        assert!(personalize_conversion(0.8, &flight_only, &CustomerFeatures::default()) > personalize_conversion(0.6, &bundle, &CustomerFeatures::default()));
+        assert!(personalize_conversion(0.6, &bundle, &extras_buyer) > personalize_conversion(0.8, &flight_only, &extras_buyer));
+
+        let business_flyer = CustomerFeatures { preferred_cabin: Some("BUSINESS".to_string()), ..extras_buyer };
+        assert!(personalize_conversion(0.8, &offer(&["FLIGHT"], "business"), &business_flyer) > personalize_conversion(0.8, &flight_only, &business_flyer));
+    }
+}
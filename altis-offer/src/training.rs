@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::Offer;
+
+/// Bumped whenever the feature set or record layout changes, so the training
+/// job never mixes incompatible rows.
+pub const TRAINING_SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TrainingLabel {
+    /// The offer was returned by a search
+    Shown,
+    Accepted,
+    Paid,
+}
+
+/// One row of the ranking training log. SHOWN rows carry the features and
+/// score the ranker saw; ACCEPTED and PAID rows are the labels, joined back
+/// on `offer_id`. Rows from one search share a `search_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingRecord {
+    pub schema_version: u32,
+    pub label: TrainingLabel,
+    pub offer_id: Uuid,
+    pub search_id: Option<Uuid>,
+    pub experiment_id: Option<String>,
+    /// "ml", "rules" or "rules_fallback"
+    pub score_source: Option<String>,
+    pub model_version: Option<String>,
+    /// Zero-based rank in the returned list
+    pub position: Option<usize>,
+    pub score: Option<f64>,
+    pub features: Option<serde_json::Value>,
+    pub order_id: Option<Uuid>,
+    pub value_nuc: Option<i32>,
+    pub timestamp: i64,
+}
+
+impl TrainingRecord {
+    pub fn shown(offer: &Offer, position: usize, features: serde_json::Value) -> Self {
+        let metadata = &offer.metadata;
+        Self {
+            position: Some(position),
+            score: metadata["score"].as_f64(),
+            features: Some(features),
+            value_nuc: offer.total.to_i32().ok(),
+            ..Self::label(TrainingLabel::Shown, offer)
+        }
+    }
+
+    /// Label row for an offer, copying the ranking metadata stamped on it at search time
+    pub fn label(label: TrainingLabel, offer: &Offer) -> Self {
+        let metadata = &offer.metadata;
+        let text = |key: &str| metadata[key].as_str().map(str::to_string);
+        Self {
+            schema_version: TRAINING_SCHEMA_VERSION,
+            label,
+            offer_id: offer.id,
+            search_id: metadata["search_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()),
+            experiment_id: text("experiment_id"),
+            score_source: text("score_source"),
+            model_version: text("model_version"),
+            position: None,
+            score: None,
+            features: None,
+            order_id: None,
+            value_nuc: None,
+            timestamp: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// PAID label; payment only knows the offer id, the rest comes from the join
+    pub fn paid(offer_id: Uuid, order_id: Uuid, value_nuc: i32) -> Self {
+        Self {
+            schema_version: TRAINING_SCHEMA_VERSION,
+            label: TrainingLabel::Paid,
+            offer_id,
+            search_id: None,
+            experiment_id: None,
+            score_source: None,
+            model_version: None,
+            position: None,
+            score: None,
+            features: None,
+            order_id: Some(order_id),
+            value_nuc: Some(value_nuc),
+            timestamp: chrono::Utc::now().timestamp(),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Ancillaries offered on a booking once it is paid, best bet first.
+
+use std::collections::HashSet;
+
+use altis_catalog::{Product, ProductType};
+use altis_shared::money::{Money, MoneyError};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::features::CustomerFeatures;
+use crate::generator::discounted;
+use crate::rules::RuleEngine;
+
+/// Cabins whose travelers are shown lounge and fast track first
+const PREMIUM_CABINS: &[&str] = &["PREMIUM_ECONOMY", "BUSINESS", "FIRST"];
+
+/// Why an ancillary made the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UpsellReason {
+    /// A rule bundles it for this customer or trip
+    Rule,
+    /// The customer's past bookings point to it
+    History,
+    /// Still on sale; nothing in particular points to it
+    Available,
+}
+
+/// An ancillary worth offering, priced for this order
+#[derive(Debug, Clone)]
+pub struct UpsellCandidate {
+    pub product: Product,
+    /// Base price less the rule discount, before taxes
+    pub price: Money,
+    pub discount: f64,
+    pub score: f64,
+    pub reason: UpsellReason,
+}
+
+/// Ranks the airline's ancillaries for a paid order, one per product type
+/// and at most `limit`. `context` is what the rules match on (`user_segment`
+/// and `search`); products already on the order aren't offered again.
+///
+/// A rule bundling the product outranks everything else. Among the rest,
+/// premium cabin travelers see lounge and fast track first, and the more
+/// price-sensitive the customer, the more the cheaper extras move up.
+pub fn rank_upsells(
+    rules: &RuleEngine,
+    context: &serde_json::Value,
+    customer: &CustomerFeatures,
+    products: &[Product],
+    owned: &HashSet<Uuid>,
+    limit: usize,
+) -> Result<Vec<UpsellCandidate>, MoneyError> {
+    let bundled = rules.evaluate_bundling(context);
+    let premium = customer.preferred_cabin.as_deref().is_some_and(|cabin| PREMIUM_CABINS.contains(&cabin));
+    let most_expensive = products.iter().map(|p| p.base_price_nuc).max().unwrap_or(0).max(1) as f64;
+
+    let mut candidates = Vec::new();
+    for product in products {
+        if !product.is_active || product.product_type == ProductType::Flight || owned.contains(&product.id) {
+            continue;
+        }
+        let discount = rules.evaluate_discount(&product.product_type, context);
+        let price = discounted(product.base_price_nuc, discount)?;
+
+        let (mut score, reason) = if bundled.contains(&product.product_type) {
+            (2.0, UpsellReason::Rule)
+        } else if premium && matches!(product.product_type, ProductType::Lounge | ProductType::FastTrack) {
+            (1.0, UpsellReason::History)
+        } else {
+            (0.0, UpsellReason::Available)
+        };
+        score += discount - customer.price_sensitivity * price.minor_units() as f64 / most_expensive;
+        candidates.push(UpsellCandidate { product: product.clone(), price, discount, score, reason });
+    }
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.price.minor_units().cmp(&b.price.minor_units())));
+    let mut offered: Vec<ProductType> = Vec::new();
+    candidates.retain(|candidate| {
+        let first_of_type = !offered.contains(&candidate.product.product_type);
+        offered.push(candidate.product.product_type.clone());
+        first_of_type
+    });
+    candidates.truncate(limit);
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::get_default_rules;
+
+    fn product(product_type: ProductType, base_price_nuc: i32) -> Product {
+        Product {
+            id: Uuid::new_v4(),
+            product_type,
+            product_code: "TEST".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            base_price_nuc,
+            margin_percentage: 0.15,
+            is_active: true,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_rules_then_history_then_price_decide_the_order() {
+        let rules = RuleEngine::new(get_default_rules());
+        let bag = product(ProductType::Bag, 5000);
+        let seat = product(ProductType::Seat, 2000);
+        let meal = product(ProductType::Meal, 1500);
+        let lounge = product(ProductType::Lounge, 8000);
+        let cheaper_seat = product(ProductType::Seat, 1000);
+        let products = vec![bag.clone(), seat.clone(), meal.clone(), lounge.clone(), cheaper_seat.clone()];
+
+        // Corporate travelers get bags and half-price seats by rule
+        let corporate = serde_json::json!({"user_segment": "corporate", "search": {"passengers": 1}});
+        let ranked = rank_upsells(&rules, &corporate, &CustomerFeatures::default(), &products, &HashSet::new(), 3).unwrap();
+        assert_eq!(ranked[0].product.id, cheaper_seat.id);
+        assert_eq!(ranked[0].price.minor_units(), 500);
+        assert_eq!(ranked[0].reason, UpsellReason::Rule);
+        assert_eq!(ranked[1].product.id, bag.id);
+        assert_eq!(ranked.iter().filter(|c| c.product.product_type == ProductType::Seat).count(), 1);
+
+        // A business traveler who watches prices: lounge first, then the cheap extras
+        let customer = CustomerFeatures { preferred_cabin: Some("BUSINESS".to_string()), price_sensitivity: 0.9, ..Default::default() };
+        let owned = HashSet::from([cheaper_seat.id]);
+        let ranked = rank_upsells(&rules, &serde_json::json!({}), &customer, &products, &owned, 10).unwrap();
+        let order: Vec<Uuid> = ranked.iter().map(|c| c.product.id).collect();
+        assert_eq!(order, vec![lounge.id, meal.id, seat.id, bag.id]);
+        assert_eq!(ranked[0].reason, UpsellReason::History);
+    }
+}
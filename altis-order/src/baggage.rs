@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// Custody scans of a checked bag
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BagEvent {
+    /// Accepted at the counter or bag drop; recorded when the tag is issued
+    CheckedIn,
+    /// Loaded into the aircraft hold
+    Loaded,
+    /// Unloaded at the destination, or at a transfer point
+    Arrived,
+}
+
+impl BagEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BagEvent::CheckedIn => "CHECKED_IN",
+            BagEvent::Loaded => "LOADED",
+            BagEvent::Arrived => "ARRIVED",
+        }
+    }
+
+    /// Whether a bag whose last scan was `last` can be scanned as `self`.
+    /// Repeated scans are accepted, and a bag that arrived at a transfer
+    /// point can be loaded onto its next flight.
+    pub fn can_follow(&self, last: BagEvent) -> bool {
+        *self == last
+            || matches!(
+                (last, self),
+                (BagEvent::CheckedIn, BagEvent::Loaded)
+                    | (BagEvent::Loaded, BagEvent::Arrived)
+                    | (BagEvent::Arrived, BagEvent::Loaded)
+            )
+    }
+}
+
+/// IATA Resolution 740 license plate: a leading 0 for the carrier's own tag,
+/// its three-digit accounting code and a six-digit serial, which wraps.
+/// None when the accounting code isn't three digits.
+pub fn bag_tag_number(accounting_code: &str, serial: i64) -> Option<String> {
+    if accounting_code.len() != 3 || !accounting_code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("0{}{:06}", accounting_code, serial.rem_euclid(1_000_000)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_and_custody_order() {
+        assert_eq!(bag_tag_number("618", 42).as_deref(), Some("0618000042"));
+        assert_eq!(bag_tag_number("618", 1_000_001).as_deref(), Some("0618000001"));
+        assert_eq!(bag_tag_number("SQ", 1), None);
+
+        assert!(BagEvent::Loaded.can_follow(BagEvent::CheckedIn));
+        assert!(BagEvent::Loaded.can_follow(BagEvent::Arrived));
+        assert!(BagEvent::Arrived.can_follow(BagEvent::Arrived));
+        assert!(!BagEvent::Arrived.can_follow(BagEvent::CheckedIn));
+        assert!(!BagEvent::CheckedIn.can_follow(BagEvent::Loaded));
+    }
+}
@@ -0,0 +1,141 @@
+use altis_shared::money::{self, Currency, Money, MoneyError, Rounding};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Discounts for buying several offers in one order, as fractions of the
+/// pre-tax price. They don't stack: each item gets the larger one that applies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleDiscounts {
+    /// Off every item once the cart holds two or more offers
+    pub multi_offer: f64,
+    /// Off ancillaries in a cart that also holds a flight
+    pub ancillary_with_flight: f64,
+}
+
+impl Default for BundleDiscounts {
+    fn default() -> Self {
+        Self { multi_offer: 0.05, ancillary_with_flight: 0.10 }
+    }
+}
+
+/// An offer item placed in a cart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartLine {
+    pub offer_id: Uuid,
+    pub item_id: Uuid,
+    pub product_type: String,
+    /// The item's price for all its units
+    #[serde(rename = "price_nuc", with = "money::nuc")]
+    pub price: Money,
+    #[serde(rename = "tax_nuc", with = "money::nuc")]
+    pub tax: Money,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PricedLine {
+    pub offer_id: Uuid,
+    pub item_id: Uuid,
+    /// Offered price before the discount
+    #[serde(rename = "price_nuc", with = "money::nuc")]
+    pub price: Money,
+    #[serde(rename = "discount_nuc", with = "money::nuc")]
+    pub discount: Money,
+    /// Taxes stay as quoted on the offer
+    #[serde(rename = "tax_nuc", with = "money::nuc")]
+    pub tax: Money,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CartTotals {
+    pub lines: Vec<PricedLine>,
+    #[serde(rename = "subtotal_nuc", with = "money::nuc")]
+    pub subtotal: Money,
+    #[serde(rename = "discount_nuc", with = "money::nuc")]
+    pub discount: Money,
+    #[serde(rename = "tax_nuc", with = "money::nuc")]
+    pub tax: Money,
+    /// What the customer pays: subtotal less discount, plus tax
+    #[serde(rename = "total_nuc", with = "money::nuc")]
+    pub total: Money,
+}
+
+fn is_flight(product_type: &str) -> bool {
+    product_type.eq_ignore_ascii_case("FLIGHT")
+}
+
+/// Prices a cart's lines together, applying bundle discounts. Fails when
+/// the lines aren't all in NUC.
+pub fn price_cart(lines: &[CartLine], discounts: &BundleDiscounts) -> Result<CartTotals, MoneyError> {
+    let mut offers: Vec<Uuid> = lines.iter().map(|l| l.offer_id).collect();
+    offers.sort();
+    offers.dedup();
+    let multi_offer = if offers.len() >= 2 { discounts.multi_offer } else { 0.0 };
+    let has_flight = lines.iter().any(|l| is_flight(&l.product_type));
+
+    let priced: Vec<PricedLine> = lines.iter().map(|line| {
+        let ancillary = if has_flight && !is_flight(&line.product_type) { discounts.ancillary_with_flight } else { 0.0 };
+        let rate = multi_offer.max(ancillary).clamp(0.0, 1.0);
+        let discount = if line.price.is_positive() {
+            line.price.scale(rate, Rounding::Down)?
+        } else {
+            Money::zero(line.price.currency())
+        };
+        Ok(PricedLine {
+            offer_id: line.offer_id,
+            item_id: line.item_id,
+            price: line.price,
+            discount,
+            tax: line.tax,
+        })
+    }).collect::<Result<_, MoneyError>>()?;
+
+    let subtotal = Money::sum(Currency::NUC, priced.iter().map(|l| l.price))?;
+    let discount = Money::sum(Currency::NUC, priced.iter().map(|l| l.discount))?;
+    let tax = Money::sum(Currency::NUC, priced.iter().map(|l| l.tax))?;
+    Ok(CartTotals {
+        lines: priced,
+        subtotal,
+        discount,
+        tax,
+        total: subtotal.checked_sub(discount)?.checked_add(tax)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(offer_id: Uuid, product_type: &str, price_nuc: i64) -> CartLine {
+        CartLine { offer_id, item_id: Uuid::new_v4(), product_type: product_type.to_string(), price: Money::nuc(price_nuc), tax: Money::nuc(100) }
+    }
+
+    #[test]
+    fn test_single_offer_only_discounts_ancillaries() {
+        let offer = Uuid::new_v4();
+        let totals = price_cart(
+            &[line(offer, "Flight", 20000), line(offer, "Bag", 3000)],
+            &BundleDiscounts::default(),
+        ).unwrap();
+
+        assert_eq!(totals.lines[0].discount, Money::nuc(0));
+        assert_eq!(totals.lines[1].discount, Money::nuc(300));
+        assert_eq!(totals.subtotal, Money::nuc(23000));
+        assert_eq!(totals.total, Money::nuc(23000 - 300 + 200));
+    }
+
+    #[test]
+    fn test_discounts_do_not_stack() {
+        let (outbound, inbound) = (Uuid::new_v4(), Uuid::new_v4());
+        let totals = price_cart(
+            &[line(outbound, "FLIGHT", 20000), line(inbound, "FLIGHT", 18000), line(inbound, "Meal", 1000)],
+            &BundleDiscounts::default(),
+        ).unwrap();
+
+        assert_eq!(totals.lines.iter().map(|l| l.discount.minor_units()).collect::<Vec<_>>(), vec![1000, 900, 100]);
+        assert_eq!(totals.discount, Money::nuc(2000));
+
+        // Ancillaries without a flight get nothing
+        let totals = price_cart(&[line(inbound, "Meal", 1000)], &BundleDiscounts::default()).unwrap();
+        assert_eq!(totals.discount, Money::nuc(0));
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+use altis_shared::money::{self, Currency, Money, MoneyError};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An order line as the diff compares it: what the customer holds before
+/// the change, or would hold after it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub item_id: Uuid,
+    pub product_id: Option<Uuid>,
+    pub product_type: String,
+    pub name: String,
+    /// Line amount, taxes included
+    #[serde(rename = "amount_nuc", with = "money::nuc")]
+    pub amount: Money,
+    /// The line this one takes the place of, e.g. a re-accommodated flight
+    /// replacing the disrupted one
+    #[serde(default)]
+    pub replaces: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// Same item, or the same product swapped in, at a different amount
+    Repriced,
+    /// A different product standing in for a line that goes
+    Replaced,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemChange {
+    pub kind: ChangeKind,
+    /// The line after the change; the removed line for REMOVED
+    pub item_id: Uuid,
+    /// The line it takes over from, when that is a different item
+    pub previous_item_id: Option<Uuid>,
+    pub product_id: Option<Uuid>,
+    pub product_type: String,
+    pub name: String,
+    #[serde(with = "money::nuc::option")]
+    pub before_nuc: Option<Money>,
+    #[serde(with = "money::nuc::option")]
+    pub after_nuc: Option<Money>,
+    #[serde(with = "money::nuc")]
+    pub delta_nuc: Money,
+}
+
+/// What a change would do to an order. At most one of `collect_nuc` and
+/// `refund_nuc` is non-zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderDiff {
+    pub changes: Vec<ItemChange>,
+    #[serde(with = "money::nuc")]
+    pub before_total_nuc: Money,
+    #[serde(with = "money::nuc")]
+    pub after_total_nuc: Money,
+    #[serde(with = "money::nuc")]
+    pub total_delta_nuc: Money,
+    /// Owed by the customer to confirm
+    #[serde(with = "money::nuc")]
+    pub collect_nuc: Money,
+    /// Owed back to the customer
+    #[serde(with = "money::nuc")]
+    pub refund_nuc: Money,
+}
+
+impl OrderDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+fn change(kind: ChangeKind, line: &DiffLine, previous: Option<&DiffLine>, before: Option<Money>, after: Option<Money>) -> Result<ItemChange, MoneyError> {
+    let zero = Money::zero(Currency::NUC);
+    Ok(ItemChange {
+        kind,
+        item_id: line.item_id,
+        previous_item_id: previous.map(|p| p.item_id).filter(|id| *id != line.item_id),
+        product_id: line.product_id,
+        product_type: line.product_type.clone(),
+        name: line.name.clone(),
+        before_nuc: before,
+        after_nuc: after,
+        delta_nuc: after.unwrap_or(zero).checked_sub(before.unwrap_or(zero))?,
+    })
+}
+
+/// Compares the lines held before a change with those held after it. Lines
+/// are paired by item id, then by `replaces`, then by product, so a flight
+/// exchanged for another of the same product reads as a reprice rather than
+/// a removal and an addition. Unchanged lines are left out.
+pub fn compare(before: &[DiffLine], after: &[DiffLine]) -> Result<OrderDiff, MoneyError> {
+    let mut changes = Vec::new();
+    let mut matched_before: HashSet<Uuid> = HashSet::new();
+    let mut pairs: Vec<(Option<&DiffLine>, &DiffLine)> = Vec::new();
+
+    let remaining = |matched: &HashSet<Uuid>, id: Uuid| !matched.contains(&id) && !after.iter().any(|a| a.item_id == id);
+    for line in after {
+        let previous = before.iter().find(|b| b.item_id == line.item_id)
+            .or_else(|| line.replaces.and_then(|id| before.iter().find(|b| b.item_id == id && remaining(&matched_before, id))))
+            .or_else(|| line.product_id.and_then(|product| {
+                before.iter().find(|b| b.product_id == Some(product) && remaining(&matched_before, b.item_id))
+            }));
+        if let Some(previous) = previous {
+            matched_before.insert(previous.item_id);
+        }
+        pairs.push((previous, line));
+    }
+
+    for (previous, line) in pairs {
+        match previous {
+            None => changes.push(change(ChangeKind::Added, line, None, None, Some(line.amount))?),
+            Some(previous) if previous.product_id != line.product_id && previous.item_id != line.item_id => {
+                changes.push(change(ChangeKind::Replaced, line, Some(previous), Some(previous.amount), Some(line.amount))?);
+            }
+            Some(previous) if previous.amount != line.amount => {
+                changes.push(change(ChangeKind::Repriced, line, Some(previous), Some(previous.amount), Some(line.amount))?);
+            }
+            Some(_) => {}
+        }
+    }
+    for line in before.iter().filter(|b| !matched_before.contains(&b.item_id)) {
+        changes.push(change(ChangeKind::Removed, line, None, Some(line.amount), None)?);
+    }
+
+    let before_total = Money::sum(Currency::NUC, before.iter().map(|line| line.amount))?;
+    let after_total = Money::sum(Currency::NUC, after.iter().map(|line| line.amount))?;
+    let delta = after_total.checked_sub(before_total)?;
+    let zero = Money::zero(Currency::NUC);
+    Ok(OrderDiff {
+        changes,
+        before_total_nuc: before_total,
+        after_total_nuc: after_total,
+        total_delta_nuc: delta,
+        collect_nuc: if delta.is_positive() { delta } else { zero },
+        refund_nuc: if delta.is_positive() { zero } else { delta.checked_neg()? },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(name: &str, product: Option<Uuid>, amount: i64) -> DiffLine {
+        DiffLine {
+            item_id: Uuid::new_v4(),
+            product_id: product,
+            product_type: "FLIGHT".to_string(),
+            name: name.to_string(),
+            amount: Money::nuc(amount),
+            replaces: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_each_kind_and_the_balance() {
+        let (fare, bag_product) = (Uuid::new_v4(), Uuid::new_v4());
+        let flight = line("SIN-BKK", Some(fare), 25000);
+        let bag = line("Extra bag", Some(bag_product), 3000);
+        let meal = line("Meal", Some(Uuid::new_v4()), 1500);
+        let before = vec![flight.clone(), bag.clone(), meal.clone()];
+
+        // Same fare reissued dearer, the bag kept, the meal dropped, a seat added
+        let reissued = line("SIN-BKK", Some(fare), 27000);
+        let seat = line("Seat 12A", Some(Uuid::new_v4()), 2000);
+        let after = vec![reissued.clone(), bag.clone(), seat.clone()];
+        let result = compare(&before, &after).unwrap();
+
+        let kinds: Vec<_> = result.changes.iter().map(|c| (c.kind, c.name.as_str(), c.delta_nuc.minor_units())).collect();
+        assert_eq!(kinds, vec![
+            (ChangeKind::Repriced, "SIN-BKK", 2000),
+            (ChangeKind::Added, "Seat 12A", 2000),
+            (ChangeKind::Removed, "Meal", -1500),
+        ]);
+        assert_eq!(result.changes[0].previous_item_id, Some(flight.item_id));
+        assert_eq!(result.total_delta_nuc, Money::nuc(2500));
+        assert_eq!((result.collect_nuc, result.refund_nuc), (Money::nuc(2500), Money::nuc(0)));
+
+        // A re-accommodated flight carries the fare over: nothing to pay or refund
+        let alternative = DiffLine { replaces: Some(flight.item_id), ..line("SIN-BKK (next day)", Some(Uuid::new_v4()), 25000) };
+        let result = compare(&before, &[alternative.clone(), bag, meal]).unwrap();
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].kind, ChangeKind::Replaced);
+        assert_eq!(result.changes[0].previous_item_id, Some(flight.item_id));
+        assert!(result.total_delta_nuc.is_zero() && result.refund_nuc.is_zero());
+
+        let dropped = compare(&before, &[flight]).unwrap();
+        assert_eq!(dropped.refund_nuc, Money::nuc(4500));
+        assert!(compare(&before, &before).unwrap().is_empty());
+    }
+}
@@ -1,6 +1,11 @@
 use crate::models::{Order, OrderItem, OrderItemStatus};
+use altis_shared::money::{Money, MoneyError};
 use uuid::Uuid;
 
+pub mod diff;
+
+pub use diff::{DiffLine, OrderDiff};
+
 /// Handles order modifications and changes
 pub struct ChangeHandler;
 
@@ -12,7 +17,7 @@ impl ChangeHandler {
             return Err(ChangeError::OrderNotModifiable(order.id.to_string()));
         }
         
-        order.add_item(new_item);
+        order.add_item(new_item)?;
         Ok(())
     }
     
@@ -29,12 +34,35 @@ impl ChangeHandler {
         item.refund();
         
         // Recalculate order total
-        order.total_nuc = order.calculate_active_total();
+        order.total = order.calculate_active_total()?;
         order.updated_at = chrono::Utc::now();
         
         Ok(())
     }
     
+    /// Refund some units of an item; the rest stay held. Returns the price
+    /// and tax to give back.
+    pub fn refund_units(order: &mut Order, item_id: &Uuid, units: i32) -> Result<(Money, Money), ChangeError> {
+        let item = order.items.iter_mut()
+            .find(|i| i.id == *item_id)
+            .ok_or_else(|| ChangeError::ItemNotFound(item_id.to_string()))?;
+
+        if item.status != OrderItemStatus::Active {
+            return Err(ChangeError::ItemNotActive(item_id.to_string()));
+        }
+        if units < 1 || units > item.active_units() {
+            return Err(ChangeError::ValidationFailed(format!(
+                "{} unit(s) requested, {} held", units, item.active_units()
+            )));
+        }
+
+        let refunded = item.refund_units(units)?;
+        order.total = order.calculate_active_total()?;
+        order.updated_at = chrono::Utc::now();
+
+        Ok(refunded)
+    }
+
     /// Change flight (add new flight item, refund old one)
     pub fn change_flight(
         order: &mut Order,
@@ -70,6 +98,9 @@ pub enum ChangeError {
     
     #[error("Change validation failed: {0}")]
     ValidationFailed(String),
+
+    #[error("Amount error: {0}")]
+    Amount(#[from] MoneyError),
 }
 
 #[cfg(test)]
@@ -90,11 +121,11 @@ mod tests {
             serde_json::json!({}),
         );
         
-        let initial_total = order.total_nuc;
+        let initial_total = order.total;
         ChangeHandler::add_item(&mut order, new_item).unwrap();
         
         assert_eq!(order.items.len(), 1);
-        assert_eq!(order.total_nuc, initial_total + 1500);
+        assert_eq!(order.total, initial_total + 1500);
     }
     
     #[test]
@@ -111,13 +142,13 @@ mod tests {
         );
         let item_id = item.id;
         
-        order.add_item(item);
-        let initial_total = order.total_nuc;
+        order.add_item(item).unwrap();
+        let initial_total = order.total;
         
         ChangeHandler::refund_item(&mut order, &item_id).unwrap();
         
         assert_eq!(order.items[0].status, OrderItemStatus::Refunded);
-        assert_eq!(order.total_nuc, 0); // Refunded items don't count
+        assert_eq!(order.total, 0); // Refunded items don't count
     }
     
     #[test]
@@ -133,7 +164,7 @@ mod tests {
             serde_json::json!({}),
         );
         let old_flight_id = old_flight.id;
-        order.add_item(old_flight);
+        order.add_item(old_flight).unwrap();
         
         let new_flight = OrderItem::new(
             order.id,
@@ -149,6 +180,6 @@ mod tests {
         assert_eq!(order.items.len(), 2);
         assert_eq!(order.items[0].status, OrderItemStatus::Refunded);
         assert_eq!(order.items[1].status, OrderItemStatus::Active);
-        assert_eq!(order.total_nuc, 25000);
+        assert_eq!(order.total, 25000);
     }
 }
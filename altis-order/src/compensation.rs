@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+/// Countries whose departures (and whose carriers' arrivals) fall under
+/// EU261: the EU, the EEA and Switzerland, plus the UK, which kept the
+/// regulation as UK261 with the same amounts.
+pub const EU261_COUNTRIES: &[&str] = &[
+    "AT", "BE", "BG", "HR", "CY", "CZ", "DK", "EE", "FI", "FR", "DE", "GR", "HU", "IE", "IT", "LV", "LT", "LU",
+    "MT", "NL", "PL", "PT", "RO", "SK", "SI", "ES", "SE", "IS", "LI", "NO", "CH", "GB",
+];
+
+/// Causes outside the carrier's control, which don't give a right to
+/// compensation under EU261
+pub const EXTRAORDINARY_CAUSES: &[&str] = &["WEATHER", "ATC", "SECURITY", "POLITICAL", "EXTERNAL_STRIKE", "BIRD_STRIKE", "MEDICAL"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Scheme {
+    /// Fixed amounts by distance for long delays and cancellations
+    Eu261,
+    /// US DOT: the fare back in cash for cancellations and significant delays
+    /// the passenger didn't accept a replacement for
+    UsDot,
+}
+
+/// What happened to one booked flight
+#[derive(Debug, Clone)]
+pub struct DisruptionFacts {
+    pub origin_country: String,
+    pub destination_country: String,
+    /// Country of the operating carrier
+    pub carrier_country: Option<String>,
+    pub distance_km: f64,
+    pub cancelled: bool,
+    /// How late the passenger reached the destination, on the original
+    /// flight or the replacement they took
+    pub arrival_delay_minutes: i64,
+    /// Whether the passenger took a replacement flight
+    pub reaccommodated: bool,
+    pub cause: Option<String>,
+    /// Passengers on the booking; EU261 amounts are per passenger
+    pub passengers: i32,
+    /// Price paid for the booking
+    pub fare_nuc: i32,
+}
+
+/// Amounts EU261 sets in euros, converted to NUC for the ledger
+#[derive(Debug, Clone)]
+pub struct CompensationTerms {
+    /// NUC per euro
+    pub eur_to_nuc: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompensationAssessment {
+    pub scheme: Option<Scheme>,
+    pub eligible: bool,
+    pub amount_nuc: i32,
+    pub reason: String,
+}
+
+fn covered(country: &str) -> bool {
+    EU261_COUNTRIES.contains(&country)
+}
+
+/// The scheme that protects the flight, EU261 first: it covers departures from
+/// its countries and, on a carrier from there, arrivals into them
+pub fn scheme_for(facts: &DisruptionFacts) -> Option<Scheme> {
+    let eu_carrier = facts.carrier_country.as_deref().is_some_and(covered);
+    if covered(&facts.origin_country) || (eu_carrier && covered(&facts.destination_country)) {
+        Some(Scheme::Eu261)
+    } else if facts.origin_country == "US" || facts.destination_country == "US" {
+        Some(Scheme::UsDot)
+    } else {
+        None
+    }
+}
+
+/// Great-circle distance between two airports, the way EU261 measures a route
+pub fn distance_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+pub fn assess(facts: &DisruptionFacts, terms: &CompensationTerms) -> CompensationAssessment {
+    let not_eligible = |scheme, reason: &str| CompensationAssessment { scheme, eligible: false, amount_nuc: 0, reason: reason.to_string() };
+
+    match scheme_for(facts) {
+        None => not_eligible(None, "No compensation scheme covers this route"),
+        Some(Scheme::Eu261) => {
+            if facts.cause.as_deref().is_some_and(|cause| EXTRAORDINARY_CAUSES.contains(&cause)) {
+                return not_eligible(Some(Scheme::Eu261), "Extraordinary circumstances");
+            }
+            if !facts.cancelled && facts.arrival_delay_minutes < 180 {
+                return not_eligible(Some(Scheme::Eu261), "Arrived less than 3 hours late");
+            }
+            // Art. 7(1) bands; intra-EU flights never reach the top one
+            let intra_eu = covered(&facts.origin_country) && covered(&facts.destination_country);
+            let (euros, reduced_within_minutes) = match facts.distance_km {
+                d if d <= 1500.0 => (250.0, 120),
+                d if d <= 3500.0 || intra_eu => (400.0, 180),
+                _ => (600.0, 240),
+            };
+            // Art. 7(2): halved when the passenger still arrived within the band's limit
+            let halved = facts.arrival_delay_minutes <= reduced_within_minutes
+                && (facts.reaccommodated || !facts.cancelled);
+            let euros = if halved { euros / 2.0 } else { euros };
+            CompensationAssessment {
+                scheme: Some(Scheme::Eu261),
+                eligible: true,
+                amount_nuc: (euros * terms.eur_to_nuc * 100.0).round() as i32 * facts.passengers.max(1),
+                reason: format!("EU261: {:.0} EUR for a {:.0} km flight{}", euros, facts.distance_km, if halved { ", halved under Art. 7(2)" } else { "" }),
+            }
+        }
+        Some(Scheme::UsDot) => {
+            let domestic = facts.origin_country == facts.destination_country;
+            let significant_delay = facts.arrival_delay_minutes >= if domestic { 180 } else { 360 };
+            if !facts.cancelled && !significant_delay {
+                return not_eligible(Some(Scheme::UsDot), "Not a cancellation or significant delay");
+            }
+            if facts.reaccommodated {
+                return not_eligible(Some(Scheme::UsDot), "Replacement flight accepted");
+            }
+            CompensationAssessment {
+                scheme: Some(Scheme::UsDot),
+                eligible: true,
+                amount_nuc: facts.fare_nuc,
+                reason: "US DOT: fare refunded in cash".to_string(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts(origin: &str, destination: &str, distance_km: f64, delay: i64) -> DisruptionFacts {
+        DisruptionFacts {
+            origin_country: origin.to_string(),
+            destination_country: destination.to_string(),
+            carrier_country: Some("SG".to_string()),
+            distance_km,
+            cancelled: false,
+            arrival_delay_minutes: delay,
+            reaccommodated: false,
+            cause: None,
+            passengers: 1,
+            fare_nuc: 45000,
+        }
+    }
+
+    #[test]
+    fn test_assess() {
+        let terms = CompensationTerms { eur_to_nuc: 1.0 };
+
+        // London to Singapore, 10,900 km
+        let london = facts("GB", "SG", 10_900.0, 300);
+        assert_eq!(assess(&london, &terms).amount_nuc, 60000);
+        assert_eq!(assess(&DisruptionFacts { passengers: 2, ..london.clone() }, &terms).amount_nuc, 120000);
+        // 3-4 hours late on a long-haul flight is halved
+        assert_eq!(assess(&facts("GB", "SG", 10_900.0, 200), &terms).amount_nuc, 30000);
+        assert!(!assess(&facts("GB", "SG", 10_900.0, 170), &terms).eligible);
+        assert!(!assess(&DisruptionFacts { cause: Some("WEATHER".to_string()), ..london.clone() }, &terms).eligible);
+        // Inbound to the EU is only covered on an EU carrier
+        assert_eq!(scheme_for(&facts("SG", "GB", 10_900.0, 300)), None);
+
+        let domestic = facts("US", "US", 3_980.0, 200);
+        assert_eq!(assess(&domestic, &terms).amount_nuc, 45000);
+        assert!(!assess(&DisruptionFacts { reaccommodated: true, ..domestic }, &terms).eligible);
+
+        let london_jfk = distance_km((51.47, -0.4543), (40.6413, -73.7781));
+        assert!((london_jfk - 5540.0).abs() < 20.0);
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// Where a chargeback stands with the card network. WON and LOST are final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DisputeStatus {
+    Open,
+    EvidenceSubmitted,
+    Won,
+    Lost,
+}
+
+impl DisputeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisputeStatus::Open => "OPEN",
+            DisputeStatus::EvidenceSubmitted => "EVIDENCE_SUBMITTED",
+            DisputeStatus::Won => "WON",
+            DisputeStatus::Lost => "LOST",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "OPEN" => Some(DisputeStatus::Open),
+            "EVIDENCE_SUBMITTED" => Some(DisputeStatus::EvidenceSubmitted),
+            "WON" => Some(DisputeStatus::Won),
+            "LOST" => Some(DisputeStatus::Lost),
+            _ => None,
+        }
+    }
+
+    /// Our status for a Stripe dispute status. Anything still waiting on a
+    /// response from us is open.
+    pub fn from_stripe(status: &str) -> Self {
+        match status {
+            "under_review" | "warning_under_review" => DisputeStatus::EvidenceSubmitted,
+            "won" | "warning_closed" => DisputeStatus::Won,
+            "lost" => DisputeStatus::Lost,
+            _ => DisputeStatus::Open,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self, DisputeStatus::Won | DisputeStatus::Lost)
+    }
+
+    /// Evidence may be skipped, since the network can rule without it, but a
+    /// ruling is never reopened.
+    pub fn can_transition_to(&self, next: DisputeStatus) -> bool {
+        matches!(
+            (self, next),
+            (DisputeStatus::Open, DisputeStatus::EvidenceSubmitted)
+                | (DisputeStatus::Open | DisputeStatus::EvidenceSubmitted, DisputeStatus::Won | DisputeStatus::Lost)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disputes_move_forward_and_rulings_are_final() {
+        use DisputeStatus::*;
+        assert!(Open.can_transition_to(EvidenceSubmitted));
+        assert!(Open.can_transition_to(Lost));
+        assert!(EvidenceSubmitted.can_transition_to(Won));
+        assert!(!EvidenceSubmitted.can_transition_to(Open));
+        assert!(!Won.can_transition_to(Lost));
+        assert!(!Lost.can_transition_to(Won));
+        assert!(!Open.can_transition_to(Open));
+
+        assert_eq!(DisputeStatus::from_stripe("needs_response"), Open);
+        assert_eq!(DisputeStatus::from_stripe("under_review"), EvidenceSubmitted);
+        assert_eq!(DisputeStatus::from_stripe("lost"), Lost);
+        assert_eq!(DisputeStatus::parse(Lost.as_str()), Some(Lost));
+        assert!(Won.is_closed() && !EvidenceSubmitted.is_closed());
+    }
+}
@@ -1,5 +1,8 @@
 use crate::models::{Order, OrderItem, OrderItemStatus};
+use crate::protection::{self, ProtectionOutcome, PROTECTION_PRODUCT_TYPE};
 use altis_catalog::product::{FlightProduct, FlightStatus};
+use altis_shared::money::{Currency, Money};
+use serde::Serialize;
 use uuid::Uuid;
 
 /// Result of a re-accommodation attempt
@@ -19,6 +22,17 @@ impl DisruptionManager {
         Self {}
     }
 
+    /// Whether a status change moves passengers to another flight: always for
+    /// a cancellation, and for a delay only beyond the airline's threshold.
+    /// Shorter delays are left for passengers to sit out.
+    pub fn requires_reaccommodation(&self, status: &FlightStatus, delay_minutes: i64, threshold_minutes: i64) -> bool {
+        match status {
+            FlightStatus::Cancelled => true,
+            FlightStatus::Delayed => delay_minutes > threshold_minutes,
+            _ => false,
+        }
+    }
+
     /// Process a flight status change and identify affected orders
     /// (In this mock implementation, we take the orders as input)
     pub fn process_disruption(
@@ -61,7 +75,7 @@ impl DisruptionManager {
                             Some(alt_flight.product.product_code.clone()),
                             alt_flight.product.name.clone(),
                             alt_flight.product.description.clone(),
-                            0, // Involuntary re-accommodation is usually zero-cost to customer
+                            Money::zero(Currency::NUC), // Involuntary re-accommodation is usually zero-cost to customer
                             1,
                             metadata,
                         );
@@ -85,4 +99,120 @@ impl DisruptionManager {
 
         results
     }
+
+    /// Applies missed connection protection for an order whose inbound flight was
+    /// disrupted: each protected downstream ticket is rebooked or refunded.
+    pub fn resolve_protected_connections(
+        &self,
+        flight_id: &str,
+        new_status: FlightStatus,
+        delay_minutes: i64,
+        order: &Order,
+        alternatives: &[FlightProduct],
+    ) -> Vec<ProtectionOutcome> {
+        let cancelled = new_status == FlightStatus::Cancelled;
+        if !cancelled && new_status != FlightStatus::Delayed {
+            return Vec::new();
+        }
+
+        order.items.iter()
+            .filter(|item| item.product_type == PROTECTION_PRODUCT_TYPE && item.status == OrderItemStatus::Active)
+            .filter(|item| item.metadata["inbound_flight_id"].as_str() == Some(flight_id))
+            .filter_map(|item| {
+                let outbound_id = item.metadata["outbound_item_id"].as_str()?;
+                let outbound = order.items.iter().find(|i| i.id.to_string() == outbound_id)?;
+                protection::resolve_protection(order.id, item, outbound, cancelled, delay_minutes, alternatives)
+            })
+            .collect()
+    }
+}
+
+/// Where a disrupted booking stands with the replacement flights proposed for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BookingOutcome {
+    Accepted,
+    /// Proposals still held, waiting for the customer
+    Pending,
+    /// The customer cancelled or took a refund instead
+    Declined,
+    /// Every hold ran out unanswered
+    Expired,
+}
+
+impl BookingOutcome {
+    pub fn classify(accepted: bool, open_proposals: i64, order_cancelled: bool) -> Self {
+        if accepted {
+            BookingOutcome::Accepted
+        } else if order_cancelled {
+            BookingOutcome::Declined
+        } else if open_proposals > 0 {
+            BookingOutcome::Pending
+        } else {
+            BookingOutcome::Expired
+        }
+    }
+}
+
+/// How passengers of one disruption responded, with rates over every booking
+/// that was offered alternatives
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReaccommodationStats {
+    pub bookings: usize,
+    pub accepted: usize,
+    pub declined: usize,
+    pub expired: usize,
+    pub pending: usize,
+    pub acceptance_rate: f64,
+    pub decline_rate: f64,
+}
+
+impl ReaccommodationStats {
+    pub fn from_outcomes(outcomes: impl IntoIterator<Item = BookingOutcome>) -> Self {
+        let mut stats = Self::default();
+        for outcome in outcomes {
+            stats.bookings += 1;
+            match outcome {
+                BookingOutcome::Accepted => stats.accepted += 1,
+                BookingOutcome::Declined => stats.declined += 1,
+                BookingOutcome::Expired => stats.expired += 1,
+                BookingOutcome::Pending => stats.pending += 1,
+            }
+        }
+        if stats.bookings > 0 {
+            stats.acceptance_rate = stats.accepted as f64 / stats.bookings as f64;
+            stats.decline_rate = stats.declined as f64 / stats.bookings as f64;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_reaccommodation() {
+        let manager = DisruptionManager::new();
+        assert!(manager.requires_reaccommodation(&FlightStatus::Cancelled, 0, 180));
+        assert!(!manager.requires_reaccommodation(&FlightStatus::Delayed, 180, 180));
+        assert!(manager.requires_reaccommodation(&FlightStatus::Delayed, 181, 180));
+        assert!(!manager.requires_reaccommodation(&FlightStatus::Scheduled, 600, 180));
+    }
+
+    #[test]
+    fn test_reaccommodation_stats() {
+        let outcomes = [
+            BookingOutcome::classify(true, 0, true), // accepted, then cancelled later
+            BookingOutcome::classify(false, 2, true),
+            BookingOutcome::classify(false, 2, false),
+            BookingOutcome::classify(false, 0, false),
+        ];
+        assert_eq!(outcomes, [BookingOutcome::Accepted, BookingOutcome::Declined, BookingOutcome::Pending, BookingOutcome::Expired]);
+
+        let stats = ReaccommodationStats::from_outcomes(outcomes);
+        assert_eq!((stats.bookings, stats.pending), (4, 1));
+        assert_eq!(stats.acceptance_rate, 0.25);
+        assert_eq!(ReaccommodationStats::from_outcomes([]).acceptance_rate, 0.0);
+    }
 }
@@ -1,6 +1,92 @@
-use crate::models::{Order, RevenueStatus, LedgerEntry};
+use crate::models::{Order, OrderItem, RevenueStatus, LedgerEntry};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::Utc;
+use altis_shared::money::{self, Money};
+
+/// One flown leg of a fare, read from a flight item's `metadata.segments`.
+/// Single-leg items without that array are treated as one segment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FareSegment {
+    pub segment_id: Option<String>,
+    pub origin: String,
+    pub destination: String,
+    /// Ticketed point mileage used as the proration weight
+    #[serde(default)]
+    pub mileage: u32,
+    pub operating_carrier_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProratedSegment {
+    pub segment: FareSegment,
+    #[serde(rename = "amount_nuc", serialize_with = "money::nuc::serialize")]
+    pub amount: Money,
+}
+
+/// Share of a prorated fare owed to the carrier operating a segment.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct InterlineSplit {
+    pub segment: FareSegment,
+    pub operating_carrier_id: Uuid,
+    #[serde(rename = "gross_nuc", serialize_with = "money::nuc::serialize")]
+    pub gross: Money,
+    #[serde(rename = "commission_nuc", serialize_with = "money::nuc::serialize")]
+    pub commission: Money,
+    #[serde(rename = "payable_nuc", serialize_with = "money::nuc::serialize")]
+    pub payable: Money,
+}
+
+/// Mileage-based NUC proration.
+pub struct ProrationEngine;
+
+impl ProrationEngine {
+    /// Splits `amount` across segments in proportion to their mileage.
+    /// Uses [`Money::allocate`], so the shares always add up to the fare;
+    /// falls back to an even split when no segment carries mileage.
+    pub fn prorate(amount: Money, segments: &[FareSegment]) -> Vec<ProratedSegment> {
+        if segments.is_empty() {
+            return Vec::new();
+        }
+
+        let weights: Vec<u64> = segments.iter().map(|s| s.mileage as u64).collect();
+        let shares = amount.allocate(&weights)
+            .expect("segments is non-empty");
+
+        segments.iter().zip(shares).map(|(segment, share)| ProratedSegment {
+            segment: segment.clone(),
+            amount: share,
+        }).collect()
+    }
+
+    /// Reads the segments a flight item covers.
+    pub fn segments_for(item: &OrderItem) -> Vec<FareSegment> {
+        if let Some(segments) = item.metadata["segments"].as_array() {
+            let parsed: Vec<FareSegment> = segments.iter()
+                .filter_map(|s| serde_json::from_value(s.clone()).ok())
+                .collect();
+            if !parsed.is_empty() {
+                return parsed;
+            }
+        }
+
+        vec![FareSegment {
+            segment_id: item.metadata["flight_id"].as_str().map(|s| s.to_string()),
+            origin: item.metadata["origin"].as_str().unwrap_or_default().to_string(),
+            destination: item.metadata["destination"].as_str().unwrap_or_default().to_string(),
+            mileage: item.metadata["mileage"].as_u64().unwrap_or(0) as u32,
+            operating_carrier_id: item.operating_carrier_id,
+        }]
+    }
+}
+
+/// Order the revenue of an item is booked against. Transferred (gifted) items
+/// earn revenue for the order that paid for them.
+pub(crate) fn revenue_order_id(order: &Order, item: &OrderItem) -> Uuid {
+    item.metadata["original_order_id"].as_str()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .unwrap_or(order.id)
+}
 
 /// Handles financial operations for orders
 pub struct FinancialManager {
@@ -12,33 +98,116 @@ impl FinancialManager {
         Self {}
     }
 
-    /// Recognize revenue for a specific item
+    /// Recognize revenue for one unit of an item, `unit` counting from 0
+    /// in the order units are consumed. Each unit earns its price and its
+    /// share of the tax; units past those still held earn nothing.
     /// Returns a LedgerEntry if successful
     pub fn recognize_revenue(
         &self,
         order: &Order,
         item_id: Uuid,
+        unit: i32,
     ) -> Option<LedgerEntry> {
         // Find the item in the order
         let item = order.items.iter().find(|i| i.id == item_id)?;
 
         // Only recognize if unearned
-        if item.revenue_status != RevenueStatus::Unearned {
+        if item.revenue_status != RevenueStatus::Unearned || unit < 0 || unit >= item.active_units() {
             return None;
         }
 
         Some(LedgerEntry {
             id: Uuid::new_v4(),
-            order_id: order.id,
+            order_id: revenue_order_id(order, item),
             order_item_id: item.id,
             transaction_type: "REVENUE_RECOGNITION".to_string(),
-            amount_nuc: item.price_nuc,
+            amount: item.price,
             currency: order.currency.clone(),
-            description: Some(format!("Revenue recognized for {} ({})", item.name, item.product_type)),
+            description: Some(format!("Revenue recognized for {} ({}), unit {} of {}", item.name, item.product_type, unit + 1, item.units())),
             created_at: Utc::now(),
+            counterparty_id: None,
+            tax: item.unit_tax(unit).ok()?,
         })
     }
 
+    /// Breakage for one unit still unused when its service was over: the
+    /// same amounts `recognize_revenue` would earn, booked as `BREAKAGE`
+    pub fn recognize_breakage(
+        &self,
+        order: &Order,
+        item_id: Uuid,
+        unit: i32,
+    ) -> Option<LedgerEntry> {
+        let mut entry = self.recognize_revenue(order, item_id, unit)?;
+        let item = order.items.iter().find(|i| i.id == item_id)?;
+        entry.transaction_type = "BREAKAGE".to_string();
+        entry.description = Some(format!("Breakage recognized for {} ({}), unused unit {} of {}", item.name, item.product_type, unit + 1, item.units()));
+        Some(entry)
+    }
+
+    /// Builds the segment-level revenue schedule for an item: one unearned
+    /// entry per flown segment, prorated by mileage.
+    pub fn segment_revenue_schedule(
+        &self,
+        order: &Order,
+        item_id: Uuid,
+    ) -> Vec<LedgerEntry> {
+        let Some(item) = order.items.iter().find(|i| i.id == item_id) else {
+            return Vec::new();
+        };
+
+        let segments = ProrationEngine::segments_for(item);
+        let total_miles: u32 = segments.iter().map(|s| s.mileage).sum();
+
+        ProrationEngine::prorate(item.price, &segments).into_iter().map(|prorated| LedgerEntry {
+            id: Uuid::new_v4(),
+            order_id: order.id,
+            order_item_id: item.id,
+            transaction_type: "REVENUE_SCHEDULE".to_string(),
+            amount: prorated.amount,
+            currency: order.currency.clone(),
+            description: Some(format!(
+                "Segment {}-{} prorated {}/{} miles",
+                prorated.segment.origin, prorated.segment.destination, prorated.segment.mileage, total_miles
+            )),
+            created_at: Utc::now(),
+            counterparty_id: None,
+            tax: Money::zero(item.price.currency()),
+        }).collect()
+    }
+
+    /// Splits an item's fare between the carriers operating its segments.
+    /// Segments flown by the marketing airline itself produce no split; the
+    /// retailer commission is prorated with the same weights as the fare.
+    pub fn interline_splits(
+        &self,
+        order: &Order,
+        item_id: Uuid,
+    ) -> Vec<InterlineSplit> {
+        let Some(item) = order.items.iter().find(|i| i.id == item_id) else {
+            return Vec::new();
+        };
+
+        let segments = ProrationEngine::segments_for(item);
+        let fares = ProrationEngine::prorate(item.price, &segments);
+        let commission = item.commission.unwrap_or(Money::zero(item.price.currency()));
+        let commissions = ProrationEngine::prorate(commission, &segments);
+
+        fares.into_iter().zip(commissions).filter_map(|(fare, commission)| {
+            let carrier = fare.segment.operating_carrier_id?;
+            if Some(carrier) == order.airline_id {
+                return None;
+            }
+            Some(InterlineSplit {
+                operating_carrier_id: carrier,
+                gross: fare.amount,
+                commission: commission.amount,
+                payable: fare.amount.checked_sub(commission.amount).ok()?,
+                segment: fare.segment,
+            })
+        }).collect()
+    }
+
     /// Calculate settlement report for an airline
     /// (Mock logic: in reality, this would query aggregated data)
     pub fn generate_settlement_report(
@@ -46,8 +215,8 @@ impl FinancialManager {
         airline_id: Uuid,
         orders: &[Order],
     ) -> serde_json::Value {
-        let mut total_earned = 0;
-        let mut total_unearned = 0;
+        let mut total_earned: i64 = 0;
+        let mut total_unearned: i64 = 0;
         let mut item_count = 0;
 
         for order in orders {
@@ -55,8 +224,8 @@ impl FinancialManager {
                 for item in &order.items {
                     item_count += 1;
                     match item.revenue_status {
-                        RevenueStatus::Earned => total_earned += item.price_nuc,
-                        RevenueStatus::Unearned => total_unearned += item.price_nuc,
+                        RevenueStatus::Earned => total_earned += item.line_price().map_or(0, |p| p.minor_units()),
+                        RevenueStatus::Unearned => total_unearned += item.line_price().map_or(0, |p| p.minor_units()),
                         RevenueStatus::Refunded => {}
                     }
                 }
@@ -74,3 +243,138 @@ impl FinancialManager {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(origin: &str, destination: &str, mileage: u32) -> FareSegment {
+        FareSegment {
+            segment_id: None,
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            mileage,
+            operating_carrier_id: None,
+        }
+    }
+
+    #[test]
+    fn test_prorate_by_mileage_sums_to_fare() {
+        let segments = vec![segment("SIN", "BKK", 890), segment("BKK", "LHR", 5930)];
+        let shares = ProrationEngine::prorate(Money::nuc(10001), &segments);
+
+        let amounts: Vec<i64> = shares.iter().map(|s| s.amount.minor_units()).collect();
+        assert_eq!(amounts, vec![1305, 8696]);
+        assert_eq!(amounts.iter().sum::<i64>(), 10001);
+    }
+
+    #[test]
+    fn test_prorate_without_mileage_splits_evenly() {
+        let segments = vec![segment("SIN", "KUL", 0), segment("KUL", "SIN", 0), segment("SIN", "HKG", 0)];
+        let amounts: Vec<i64> = ProrationEngine::prorate(Money::nuc(100), &segments).iter().map(|s| s.amount.minor_units()).collect();
+
+        assert_eq!(amounts.iter().sum::<i64>(), 100);
+        assert!(amounts.iter().all(|a| *a == 33 || *a == 34));
+    }
+
+    #[test]
+    fn test_prorate_negative_amount() {
+        let segments = vec![segment("SIN", "BKK", 1), segment("BKK", "SIN", 1)];
+        let amounts: Vec<i64> = ProrationEngine::prorate(Money::nuc(-101), &segments).iter().map(|s| s.amount.minor_units()).collect();
+        assert_eq!(amounts.iter().sum::<i64>(), -101);
+    }
+
+    #[test]
+    fn test_interline_splits_skip_own_segments() {
+        let own = Uuid::new_v4();
+        let partner = Uuid::new_v4();
+
+        let mut order = Order::new("cust".to_string());
+        order.airline_id = Some(own);
+        let mut item = OrderItem::new(
+            "Flight".to_string(),
+            None,
+            None,
+            "SIN-BKK-LHR".to_string(),
+            None,
+            Money::nuc(1000),
+            1,
+            serde_json::json!({
+                "segments": [
+                    { "origin": "SIN", "destination": "BKK", "mileage": 500, "operating_carrier_id": own },
+                    { "origin": "BKK", "destination": "LHR", "mileage": 1500, "operating_carrier_id": partner }
+                ]
+            }),
+        );
+        item.commission = Some(Money::nuc(100));
+        let item_id = item.id;
+        order.items.push(item);
+
+        let splits = FinancialManager::new().interline_splits(&order, item_id);
+        assert_eq!(splits.len(), 1);
+        assert_eq!(splits[0].operating_carrier_id, partner);
+        assert_eq!(splits[0].gross, Money::nuc(750));
+        assert_eq!(splits[0].commission, Money::nuc(75));
+        assert_eq!(splits[0].payable, Money::nuc(675));
+
+        let schedule = FinancialManager::new().segment_revenue_schedule(&order, item_id);
+        assert_eq!(schedule.iter().map(|e| e.amount.minor_units()).collect::<Vec<_>>(), vec![250, 750]);
+    }
+
+    #[test]
+    fn test_transferred_item_revenue_stays_with_original_order() {
+        let original = Uuid::new_v4();
+        let mut order = Order::new("recipient".to_string());
+        let item = OrderItem::new(
+            "LOUNGE".to_string(),
+            None,
+            None,
+            "Lounge Pass".to_string(),
+            None,
+            Money::nuc(45),
+            1,
+            serde_json::json!({ "original_order_id": original.to_string() }),
+        );
+        let item_id = item.id;
+        order.items.push(item);
+
+        let entry = FinancialManager::new().recognize_revenue(&order, item_id, 0).unwrap();
+        assert_eq!(entry.order_id, original);
+        assert_eq!(entry.amount, Money::nuc(45));
+    }
+
+    #[test]
+    fn test_revenue_recognized_per_unit() {
+        let mut order = Order::new("customer".to_string());
+        let mut item = OrderItem::new(
+            "LOUNGE".to_string(),
+            None,
+            None,
+            "Lounge Pass".to_string(),
+            None,
+            Money::nuc(45),
+            3,
+            serde_json::json!({}),
+        );
+        item.tax = Money::nuc(10);
+        let item_id = item.id;
+        order.add_item(item).unwrap();
+        assert_eq!(order.total, Money::nuc(145));
+
+        let manager = FinancialManager::new();
+        let first = manager.recognize_revenue(&order, item_id, 0).unwrap();
+        assert_eq!((first.amount, first.tax), (Money::nuc(45), Money::nuc(4)));
+        assert_eq!(manager.recognize_revenue(&order, item_id, 2).unwrap().tax, Money::nuc(3));
+
+        // One pass refunded: its price and the last tax share go back, and only two units can earn
+        let refunded = order.items[0].refund_units(1).unwrap();
+        assert_eq!(refunded, (Money::nuc(45), Money::nuc(3)));
+        assert_eq!(order.calculate_active_total().unwrap(), Money::nuc(97));
+        assert!(manager.recognize_revenue(&order, item_id, 2).is_none());
+
+        // The second pass went unused: the same amounts, kept as breakage
+        let breakage = manager.recognize_breakage(&order, item_id, 1).unwrap();
+        assert_eq!(breakage.transaction_type, "BREAKAGE");
+        assert_eq!((breakage.amount, breakage.tax), (Money::nuc(45), Money::nuc(3)));
+    }
+}
@@ -0,0 +1,100 @@
+use altis_shared::money::{self, Money, MoneyError};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum InstallmentError {
+    #[error("A plan has between 2 and {max} installments, not {count}")]
+    InstallmentCount { count: u32, max: u32 },
+    #[error("Installments must be at least a day apart")]
+    Interval,
+    #[error("The last installment would fall due on {last_due}, after the {deadline} deadline")]
+    PastDeadline { last_due: DateTime<Utc>, deadline: DateTime<Utc> },
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+}
+
+/// One payment of a plan. The first falls due when the plan is taken out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedPayment {
+    pub sequence: u32,
+    pub due_at: DateTime<Utc>,
+    #[serde(rename = "amount_nuc", with = "money::nuc")]
+    pub amount: Money,
+}
+
+/// Splits `total` into `count` payments `interval_days` apart from `start`.
+/// Amounts are as even as the minor unit allows, odd units going to the
+/// earliest payments. The last one must fall due by `deadline`, typically
+/// some days before departure.
+pub fn schedule(
+    total: Money,
+    count: u32,
+    max_installments: u32,
+    interval_days: i64,
+    start: DateTime<Utc>,
+    deadline: Option<DateTime<Utc>>,
+) -> Result<Vec<PlannedPayment>, InstallmentError> {
+    if count < 2 || count > max_installments {
+        return Err(InstallmentError::InstallmentCount { count, max: max_installments });
+    }
+    if interval_days < 1 {
+        return Err(InstallmentError::Interval);
+    }
+    let last_due = start + Duration::days(interval_days * (count as i64 - 1));
+    if let Some(deadline) = deadline.filter(|deadline| last_due > *deadline) {
+        return Err(InstallmentError::PastDeadline { last_due, deadline });
+    }
+
+    Ok(total.split(count as usize)?.into_iter().enumerate().map(|(i, amount)| PlannedPayment {
+        sequence: i as u32 + 1,
+        due_at: start + Duration::days(interval_days * i as i64),
+        amount,
+    }).collect())
+}
+
+/// How the payments taken so far are split when a plan defaults
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DefaultSettlement {
+    /// Paid back to the customer
+    pub refund: Money,
+    /// Kept as the cancellation fee
+    pub retained: Money,
+}
+
+/// The cancellation fee is charged on what was paid rather than on the whole
+/// fare, so a customer who defaults early loses proportionally less.
+pub fn settle_default(paid: Money, fee_percent: u8) -> Result<DefaultSettlement, MoneyError> {
+    let fee_percent = fee_percent.min(100) as u64;
+    let shares = paid.allocate(&[100 - fee_percent, fee_percent])?;
+    Ok(DefaultSettlement { refund: shares[0], retained: shares[1] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_splits_evenly_and_respects_the_deadline() {
+        let start = DateTime::parse_from_rfc3339("2026-03-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        let plan = schedule(Money::nuc(100_001), 3, 6, 30, start, None).unwrap();
+
+        let amounts: Vec<_> = plan.iter().map(|p| p.amount.minor_units()).collect();
+        assert_eq!(amounts, vec![33_334, 33_334, 33_333]);
+        assert_eq!(plan[0].due_at, start);
+        assert_eq!(plan[2].due_at, start + Duration::days(60));
+        assert_eq!(plan[2].sequence, 3);
+
+        let deadline = start + Duration::days(45);
+        assert!(matches!(schedule(Money::nuc(100_000), 3, 6, 30, start, Some(deadline)), Err(InstallmentError::PastDeadline { .. })));
+        assert!(schedule(Money::nuc(100_000), 2, 6, 30, start, Some(deadline)).is_ok());
+        assert_eq!(schedule(Money::nuc(100_000), 1, 6, 30, start, None), Err(InstallmentError::InstallmentCount { count: 1, max: 6 }));
+        assert_eq!(schedule(Money::nuc(100_000), 7, 6, 30, start, None), Err(InstallmentError::InstallmentCount { count: 7, max: 6 }));
+
+        // Two of five payments in: the fee only comes out of those
+        let settlement = settle_default(Money::nuc(40_001), 10).unwrap();
+        assert_eq!(settlement.refund, Money::nuc(36_001));
+        assert_eq!(settlement.retained, Money::nuc(4_000));
+        assert_eq!(settle_default(Money::nuc(500), 0).unwrap().retained, Money::nuc(0));
+    }
+}
@@ -0,0 +1,104 @@
+use altis_shared::money::Money;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::finance::{revenue_order_id, FinancialManager, InterlineSplit};
+use crate::models::{LedgerEntry, Order};
+
+/// Amount owed to the operating carrier for its part of an item.
+pub const INTERLINE_PAYABLE: &str = "INTERLINE_PAYABLE";
+/// Commission the retailer keeps on a partner-operated part of an item.
+pub const INTERLINE_COMMISSION: &str = "INTERLINE_COMMISSION";
+
+/// Ledger postings due when an item operated (wholly or partly) by another
+/// carrier earns revenue: a payable and a commission entry per partner
+/// segment, both carrying the partner as counterparty. Items the selling
+/// airline operates itself produce nothing.
+pub fn recognition_postings(order: &Order, item_id: Uuid) -> Vec<LedgerEntry> {
+    let Some(item) = order.items.iter().find(|i| i.id == item_id) else {
+        return Vec::new();
+    };
+
+    let mut splits = FinancialManager::new().interline_splits(order, item_id);
+
+    // An agreed net rate on a single-carrier item overrides the prorated split
+    if let ([split], Some(net_rate)) = (splits.as_mut_slice(), item.net_rate) {
+        if split.gross == item.price {
+            if let Some(commission) = item.commission.or_else(|| item.price.checked_sub(net_rate).ok()) {
+                split.payable = net_rate;
+                split.commission = commission;
+            }
+        }
+    }
+
+    let order_id = revenue_order_id(order, item);
+    let posting = |split: &InterlineSplit, transaction_type: &str, amount: Money, what: &str| LedgerEntry {
+        id: Uuid::new_v4(),
+        order_id,
+        order_item_id: item.id,
+        transaction_type: transaction_type.to_string(),
+        amount,
+        currency: order.currency.clone(),
+        description: Some(format!(
+            "{} {}-{} ({})",
+            what, split.segment.origin, split.segment.destination, item.name
+        )),
+        created_at: Utc::now(),
+        counterparty_id: Some(split.operating_carrier_id),
+        tax: Money::zero(amount.currency()),
+    };
+
+    splits.iter().flat_map(|split| {
+        let payable = (!split.payable.is_zero())
+            .then(|| posting(split, INTERLINE_PAYABLE, split.payable, "Payable to operating carrier"));
+        let commission = (!split.commission.is_zero())
+            .then(|| posting(split, INTERLINE_COMMISSION, split.commission, "Retailer commission"));
+        payable.into_iter().chain(commission)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderItem;
+
+    fn order_with(item: OrderItem, airline_id: Uuid) -> (Order, Uuid) {
+        let mut order = Order::new("cust".to_string());
+        order.airline_id = Some(airline_id);
+        let item_id = item.id;
+        order.items.push(item);
+        (order, item_id)
+    }
+
+    #[test]
+    fn test_codeshare_item_uses_net_rate() {
+        let partner = Uuid::new_v4();
+        let mut item = OrderItem::new(
+            "Flight".to_string(), None, None, "SIN-BKK".to_string(), None, Money::nuc(1000), 1,
+            serde_json::json!({ "origin": "SIN", "destination": "BKK" }),
+        );
+        item.operating_carrier_id = Some(partner);
+        item.net_rate = Some(Money::nuc(880));
+        let (order, item_id) = order_with(item, Uuid::new_v4());
+
+        let postings = recognition_postings(&order, item_id);
+        assert_eq!(postings.len(), 2);
+        assert_eq!(postings[0].transaction_type, INTERLINE_PAYABLE);
+        assert_eq!(postings[0].amount, Money::nuc(880));
+        assert_eq!(postings[1].transaction_type, INTERLINE_COMMISSION);
+        assert_eq!(postings[1].amount, Money::nuc(120));
+        assert!(postings.iter().all(|p| p.counterparty_id == Some(partner)));
+    }
+
+    #[test]
+    fn test_own_metal_has_no_postings() {
+        let airline = Uuid::new_v4();
+        let mut item = OrderItem::new(
+            "Flight".to_string(), None, None, "SIN-BKK".to_string(), None, Money::nuc(1000), 1, serde_json::json!({}),
+        );
+        item.operating_carrier_id = Some(airline);
+        let (order, item_id) = order_with(item, airline);
+
+        assert!(recognition_postings(&order, item_id).is_empty());
+    }
+}
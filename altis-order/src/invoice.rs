@@ -0,0 +1,275 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use altis_catalog::TaxLine;
+use altis_shared::money::{self, Currency, Money, MoneyError};
+
+use crate::models::{Order, OrderItemStatus};
+
+/// One billed order item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvoiceLine {
+    pub item_id: Uuid,
+    pub description: String,
+    /// Units billed: those bought less any refunded
+    pub quantity: i32,
+    /// Price of one unit
+    #[serde(rename = "price_nuc", with = "money::nuc")]
+    pub price: Money,
+    #[serde(rename = "tax_nuc", with = "money::nuc")]
+    pub tax: Money,
+    pub taxes: Vec<TaxLine>,
+    #[serde(rename = "total_nuc", with = "money::nuc")]
+    pub total: Money,
+}
+
+/// Customer invoice for a paid order. The number comes from the airline's
+/// gapless INVOICE sequence; everything else is a snapshot of the order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invoice {
+    pub document_number: String,
+    pub order_id: Uuid,
+    /// Airline name printed as the seller
+    pub seller: String,
+    pub customer_email: Option<String>,
+    pub currency: String,
+    pub issued_at: DateTime<Utc>,
+    pub lines: Vec<InvoiceLine>,
+    #[serde(rename = "subtotal_nuc", with = "money::nuc")]
+    pub subtotal: Money,
+    #[serde(rename = "tax_nuc", with = "money::nuc")]
+    pub tax: Money,
+    #[serde(rename = "total_nuc", with = "money::nuc")]
+    pub total: Money,
+}
+
+impl Invoice {
+    /// Bills the order's active items; fails if an amount isn't in NUC or
+    /// a total overflows
+    pub fn from_order(order: &Order, document_number: &str, seller: &str, issued_at: DateTime<Utc>) -> Result<Self, MoneyError> {
+        let lines: Vec<InvoiceLine> = order.items.iter()
+            .filter(|item| item.status == OrderItemStatus::Active)
+            .map(|item| Ok(InvoiceLine {
+                item_id: item.id,
+                description: item.name.clone(),
+                quantity: item.active_units(),
+                price: item.price,
+                tax: item.line_tax()?,
+                taxes: item.taxes.clone(),
+                total: item.line_price()?.checked_add(item.line_tax()?)?,
+            }))
+            .collect::<Result<_, MoneyError>>()?;
+
+        let subtotal = lines.iter().try_fold(Money::zero(Currency::NUC), |subtotal, l| subtotal.checked_add(l.price.checked_mul(l.quantity as i64)?))?;
+        let tax = Money::sum(Currency::NUC, lines.iter().map(|l| l.tax))?;
+
+        Ok(Self {
+            document_number: document_number.to_string(),
+            order_id: order.id,
+            seller: seller.to_string(),
+            customer_email: order.customer_email.clone(),
+            currency: order.currency.clone(),
+            issued_at,
+            lines,
+            subtotal,
+            tax,
+            total: subtotal.checked_add(tax)?,
+        })
+    }
+
+    /// Renders a plain A4 PDF, continuing the line table onto further pages
+    /// when it doesn't fit.
+    pub fn render_pdf(&self) -> Vec<u8> {
+        let mut pages = vec![PageBuilder::default()];
+        let mut y = TOP;
+
+        let page = pages.last_mut().unwrap();
+        page.text(LEFT, y, 18.0, &self.seller);
+        page.text(400.0, y, 18.0, "INVOICE");
+        y -= 28.0;
+        for (label, value) in [
+            ("Invoice no.", self.document_number.clone()),
+            ("Date", self.issued_at.format("%Y-%m-%d").to_string()),
+            ("Order", self.order_id.to_string()),
+            ("Billed to", self.customer_email.clone().unwrap_or_default()),
+        ] {
+            page.text(LEFT, y, 10.0, label);
+            page.text(140.0, y, 10.0, &value);
+            y -= 14.0;
+        }
+        y -= 14.0;
+
+        let header = |page: &mut PageBuilder, y: f32| {
+            page.text(LEFT, y, 10.0, "Description");
+            page.text(330.0, y, 10.0, "Qty");
+            page.text(370.0, y, 10.0, "Price");
+            page.text(440.0, y, 10.0, "Tax");
+            page.text(500.0, y, 10.0, "Total");
+        };
+        header(page, y);
+        y -= 18.0;
+
+        for line in &self.lines {
+            let rows = 1 + line.taxes.len();
+            if y - rows as f32 * ROW < BOTTOM {
+                pages.push(PageBuilder::default());
+                y = TOP;
+                header(pages.last_mut().unwrap(), y);
+                y -= 18.0;
+            }
+            let page = pages.last_mut().unwrap();
+            page.text(LEFT, y, 10.0, &truncate(&line.description, 50));
+            page.text(330.0, y, 10.0, &line.quantity.to_string());
+            page.text(370.0, y, 10.0, &format_amount(line.price));
+            page.text(440.0, y, 10.0, &format_amount(line.tax));
+            page.text(500.0, y, 10.0, &format_amount(line.total));
+            y -= ROW;
+            for tax in &line.taxes {
+                page.text(LEFT + 12.0, y, 8.0, &format!("{} {}: {}", tax.code, tax.name, format_amount(Money::from_nuc_i32(tax.amount_nuc))));
+                y -= ROW;
+            }
+        }
+
+        if y - 4.0 * ROW < BOTTOM {
+            pages.push(PageBuilder::default());
+            y = TOP;
+        }
+        let page = pages.last_mut().unwrap();
+        y -= ROW;
+        for (label, amount) in [("Subtotal", self.subtotal), ("Tax", self.tax), ("Total", self.total)] {
+            page.text(370.0, y, 10.0, label);
+            page.text(500.0, y, 10.0, &format!("{} {}", format_amount(amount), self.currency));
+            y -= ROW;
+        }
+
+        write_pdf(&pages)
+    }
+}
+
+const LEFT: f32 = 50.0;
+const TOP: f32 = 790.0;
+const BOTTOM: f32 = 60.0;
+const ROW: f32 = 14.0;
+
+/// Amounts are stored in minor units
+pub fn format_amount(amount: Money) -> String {
+    let sign = if amount.minor_units() < 0 { "-" } else { "" };
+    let abs = amount.minor_units().unsigned_abs();
+    format!("{}{}.{:02}", sign, abs / 100, abs % 100)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 3).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+/// Content stream of one page
+#[derive(Default)]
+struct PageBuilder {
+    content: String,
+}
+
+impl PageBuilder {
+    fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        self.content.push_str(&format!("BT /F1 {} Tf {} {} Td ({}) Tj ET\n", size, x, y, escape(text)));
+    }
+}
+
+/// Only printable ASCII is emitted; anything else prints as '?'
+fn escape(text: &str) -> String {
+    text.chars().fold(String::with_capacity(text.len()), |mut out, c| {
+        match c {
+            '\\' | '(' | ')' => { out.push('\\'); out.push(c); }
+            ' '..='~' => out.push(c),
+            _ => out.push('?'),
+        }
+        out
+    })
+}
+
+/// Lays out catalog, page tree, font, then a page and content stream object
+/// per page, followed by the cross-reference table.
+fn write_pdf(pages: &[PageBuilder]) -> Vec<u8> {
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + i * 2).collect();
+    let kids = page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" ");
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", page.content.len(), page.content));
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_offset).as_bytes());
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderItem;
+
+    fn paid_order(items: usize) -> Order {
+        let mut order = Order::new("cust-1".to_string());
+        order.customer_email = Some("jane@example.com".to_string());
+        for i in 0..items {
+            let mut item = OrderItem::new("Flight".to_string(), None, None, format!("SIN-LHR (leg {})", i), None, Money::nuc(50000), 1, serde_json::json!({}));
+            item.tax = Money::nuc(6570);
+            item.taxes = vec![TaxLine { code: "SG-PSC".into(), name: "Passenger Service Charge".into(), jurisdiction: "SG".into(), amount_nuc: 6570 }];
+            order.add_item(item).unwrap();
+        }
+        order
+    }
+
+    #[test]
+    fn test_invoice_totals_include_taxes() {
+        let mut order = paid_order(2);
+        order.items[1].status = OrderItemStatus::Refunded;
+
+        let invoice = Invoice::from_order(&order, "AL-INV-2026-000001", "AirAltis", Utc::now()).unwrap();
+        assert_eq!(invoice.lines.len(), 1);
+        assert_eq!((invoice.subtotal, invoice.tax, invoice.total), (Money::nuc(50000), Money::nuc(6570), Money::nuc(56570)));
+        assert_eq!(format_amount(invoice.total), "565.70");
+        assert_eq!(format_amount(Money::nuc(-5)), "-0.05");
+    }
+
+    #[test]
+    fn test_pdf_structure_and_pagination() {
+        let pdf = Invoice::from_order(&paid_order(1), "AL-INV-2026-000001", "Air (Altis)", Utc::now()).unwrap().render_pdf();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("(AL-INV-2026-000001) Tj"));
+        assert!(text.contains("(Air \\(Altis\\)) Tj"));
+
+        // startxref must point at the xref table
+        let startxref: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(text[startxref..].starts_with("xref\n"));
+
+        let long = Invoice::from_order(&paid_order(40), "AL-INV-2026-000002", "AirAltis", Utc::now()).unwrap().render_pdf();
+        let long = String::from_utf8_lossy(&long);
+        assert!(long.contains("/Count 2"));
+    }
+}
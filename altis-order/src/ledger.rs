@@ -0,0 +1,304 @@
+use altis_shared::money::{Currency, Money, MoneyError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Chart of accounts for order money flows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Account {
+    /// Owed by the customer for a sale not yet paid
+    CustomerReceivable,
+    /// Paid for but not yet flown/consumed
+    UnearnedRevenue,
+    EarnedRevenue,
+    /// Funds held by the payment provider awaiting payout
+    PspClearing,
+    /// Owed to operating carriers for interline segments
+    CarrierPayable,
+    /// Taxes collected on behalf of jurisdictions
+    TaxPayable,
+    /// Regulatory compensation paid to disrupted passengers
+    CompensationExpense,
+    /// Vouchers issued and not yet redeemed
+    VoucherLiability,
+    /// Payments the cardholder's bank took back after a dispute we lost
+    ChargebackLoss,
+}
+
+impl Account {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Account::CustomerReceivable => "CUSTOMER_RECEIVABLE",
+            Account::UnearnedRevenue => "UNEARNED_REVENUE",
+            Account::EarnedRevenue => "EARNED_REVENUE",
+            Account::PspClearing => "PSP_CLEARING",
+            Account::CarrierPayable => "CARRIER_PAYABLE",
+            Account::TaxPayable => "TAX_PAYABLE",
+            Account::CompensationExpense => "COMPENSATION_EXPENSE",
+            Account::VoucherLiability => "VOUCHER_LIABILITY",
+            Account::ChargebackLoss => "CHARGEBACK_LOSS",
+        }
+    }
+}
+
+/// One side of a journal transaction. Exactly one of debit/credit is non-zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Posting {
+    pub account: Account,
+    pub debit_nuc: i64,
+    pub credit_nuc: i64,
+    #[serde(default)]
+    pub counterparty_id: Option<Uuid>,
+}
+
+impl Posting {
+    pub fn debit(account: Account, amount_nuc: i64) -> Self {
+        Self { account, debit_nuc: amount_nuc, credit_nuc: 0, counterparty_id: None }
+    }
+
+    pub fn credit(account: Account, amount_nuc: i64) -> Self {
+        Self { account, debit_nuc: 0, credit_nuc: amount_nuc, counterparty_id: None }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum LedgerError {
+    #[error("Transaction has fewer than two postings")]
+    TooFewPostings,
+    #[error("Posting to {0:?} must have exactly one positive side")]
+    InvalidPosting(Account),
+    #[error("Transaction is unbalanced: debits {debits} != credits {credits}")]
+    Unbalanced { debits: i64, credits: i64 },
+    #[error("The journal is kept in NUC, not {0}")]
+    UnsupportedCurrency(Currency),
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+}
+
+/// Minor units of a NUC amount, the only currency the journal holds
+fn nuc(amount: Money) -> Result<i64, LedgerError> {
+    if amount.currency() != Currency::NUC {
+        return Err(LedgerError::UnsupportedCurrency(amount.currency()));
+    }
+    Ok(amount.minor_units())
+}
+
+/// A balanced set of postings recorded together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalTransaction {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub order_item_id: Option<Uuid>,
+    /// SALE, PAYMENT, REVENUE_RECOGNITION, BREAKAGE, REFUND, INSTALLMENT_DEFAULT, CARRIER_PAYABLE, COMPENSATION, CHARGEBACK
+    pub kind: String,
+    pub description: Option<String>,
+    pub postings: Vec<Posting>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl JournalTransaction {
+    fn new(order_id: Uuid, order_item_id: Option<Uuid>, kind: &str, description: String, postings: Vec<Posting>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            order_id,
+            order_item_id,
+            kind: kind.to_string(),
+            description: Some(description),
+            postings,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Order confirmed: the customer owes the fare, which is not yet earned,
+    /// plus any taxes included in `amount`, which are owed onward.
+    pub fn sale(order_id: Uuid, amount: Money, tax: Money) -> Result<Self, LedgerError> {
+        let fare = nuc(amount.checked_sub(tax)?)?;
+        let (amount_nuc, tax_nuc) = (nuc(amount)?, nuc(tax)?);
+        let mut postings = vec![
+            Posting::debit(Account::CustomerReceivable, amount_nuc),
+            Posting::credit(Account::UnearnedRevenue, fare),
+        ];
+        if tax_nuc > 0 {
+            postings.push(Posting::credit(Account::TaxPayable, tax_nuc));
+        }
+        Ok(Self::new(order_id, None, "SALE", "Order sold".to_string(), postings))
+    }
+
+    /// Payment captured by the PSP settles the receivable.
+    pub fn payment(order_id: Uuid, amount: Money, reference: Option<&str>) -> Result<Self, LedgerError> {
+        let amount_nuc = nuc(amount)?;
+        Ok(Self::new(order_id, None, "PAYMENT", format!("Payment captured ({})", reference.unwrap_or("no reference")), vec![
+            Posting::debit(Account::PspClearing, amount_nuc),
+            Posting::credit(Account::CustomerReceivable, amount_nuc),
+        ]))
+    }
+
+    pub fn revenue_recognition(order_id: Uuid, item_id: Uuid, amount: Money) -> Result<Self, LedgerError> {
+        let amount_nuc = nuc(amount)?;
+        Ok(Self::new(order_id, Some(item_id), "REVENUE_RECOGNITION", "Service delivered".to_string(), vec![
+            Posting::debit(Account::UnearnedRevenue, amount_nuc),
+            Posting::credit(Account::EarnedRevenue, amount_nuc),
+        ]))
+    }
+
+    /// Units never used by the time their service was over: kept as
+    /// revenue rather than owed back.
+    pub fn breakage(order_id: Uuid, item_id: Uuid, amount: Money) -> Result<Self, LedgerError> {
+        let amount_nuc = nuc(amount)?;
+        Ok(Self::new(order_id, Some(item_id), "BREAKAGE", "Unused after departure".to_string(), vec![
+            Posting::debit(Account::UnearnedRevenue, amount_nuc),
+            Posting::credit(Account::EarnedRevenue, amount_nuc),
+        ]))
+    }
+
+    /// Refund of unflown value back through the PSP; the taxes included in
+    /// `amount` are no longer owed.
+    pub fn refund(order_id: Uuid, item_id: Option<Uuid>, amount: Money, tax: Money, reason: &str) -> Result<Self, LedgerError> {
+        let fare = nuc(amount.checked_sub(tax)?)?;
+        let (amount_nuc, tax_nuc) = (nuc(amount)?, nuc(tax)?);
+        let mut postings = vec![Posting::debit(Account::UnearnedRevenue, fare)];
+        if tax_nuc > 0 {
+            postings.push(Posting::debit(Account::TaxPayable, tax_nuc));
+        }
+        postings.push(Posting::credit(Account::PspClearing, amount_nuc));
+        Ok(Self::new(order_id, item_id, "REFUND", reason.to_string(), postings))
+    }
+
+    /// An installment plan defaulted: the unpaid balance is no longer owed,
+    /// and the part of the payments kept as a cancellation fee is earned. Its
+    /// tax stays owed; the rest of the payments go back through `refund`.
+    pub fn installment_default(order_id: Uuid, unpaid: Money, unpaid_tax: Money, retained: Money, retained_tax: Money) -> Result<Self, LedgerError> {
+        let unpaid_fare = nuc(unpaid.checked_sub(unpaid_tax)?)?;
+        let retained_fare = nuc(retained.checked_sub(retained_tax)?)?;
+        let (unpaid_nuc, unpaid_tax_nuc) = (nuc(unpaid)?, nuc(unpaid_tax)?);
+        let mut postings = Vec::new();
+        if unpaid_fare + retained_fare > 0 {
+            postings.push(Posting::debit(Account::UnearnedRevenue, unpaid_fare + retained_fare));
+        }
+        if unpaid_tax_nuc > 0 {
+            postings.push(Posting::debit(Account::TaxPayable, unpaid_tax_nuc));
+        }
+        if unpaid_nuc > 0 {
+            postings.push(Posting::credit(Account::CustomerReceivable, unpaid_nuc));
+        }
+        if retained_fare > 0 {
+            postings.push(Posting::credit(Account::EarnedRevenue, retained_fare));
+        }
+        Ok(Self::new(order_id, None, "INSTALLMENT_DEFAULT", "Installment plan defaulted".to_string(), postings))
+    }
+
+    /// The operating carrier's share moves out of earned revenue.
+    pub fn carrier_payable(order_id: Uuid, item_id: Uuid, carrier_id: Uuid, amount: Money) -> Result<Self, LedgerError> {
+        let amount_nuc = nuc(amount)?;
+        let mut payable = Posting::credit(Account::CarrierPayable, amount_nuc);
+        payable.counterparty_id = Some(carrier_id);
+        Ok(Self::new(order_id, Some(item_id), "CARRIER_PAYABLE", "Interline payable to operating carrier".to_string(), vec![
+            Posting::debit(Account::EarnedRevenue, amount_nuc),
+            payable,
+        ]))
+    }
+
+    /// Compensation owed under a passenger rights scheme, paid out through the
+    /// PSP or owed as a voucher until redeemed.
+    pub fn compensation(order_id: Uuid, item_id: Uuid, amount: Money, voucher: bool, scheme: &str) -> Result<Self, LedgerError> {
+        let amount_nuc = nuc(amount)?;
+        let paid_from = if voucher { Account::VoucherLiability } else { Account::PspClearing };
+        Ok(Self::new(order_id, Some(item_id), "COMPENSATION", format!("{} compensation", scheme), vec![
+            Posting::debit(Account::CompensationExpense, amount_nuc),
+            Posting::credit(paid_from, amount_nuc),
+        ]))
+    }
+
+    /// A lost dispute: the provider takes the disputed amount back out of
+    /// clearing. The sale itself stands, so the loss is booked against the
+    /// order rather than unwinding its revenue.
+    pub fn chargeback(order_id: Uuid, amount: Money, dispute_ref: &str) -> Result<Self, LedgerError> {
+        let amount_nuc = nuc(amount)?;
+        Ok(Self::new(order_id, None, "CHARGEBACK", format!("Dispute {} lost", dispute_ref), vec![
+            Posting::debit(Account::ChargebackLoss, amount_nuc),
+            Posting::credit(Account::PspClearing, amount_nuc),
+        ]))
+    }
+
+    /// Write-time invariants: at least two one-sided postings whose debits
+    /// equal their credits.
+    pub fn validate(&self) -> Result<(), LedgerError> {
+        if self.postings.len() < 2 {
+            return Err(LedgerError::TooFewPostings);
+        }
+
+        for posting in &self.postings {
+            let one_sided = (posting.debit_nuc > 0) != (posting.credit_nuc > 0);
+            if !one_sided || posting.debit_nuc < 0 || posting.credit_nuc < 0 {
+                return Err(LedgerError::InvalidPosting(posting.account));
+            }
+        }
+
+        let debits = Money::sum(Currency::NUC, self.postings.iter().map(|p| Money::nuc(p.debit_nuc)))?.minor_units();
+        let credits = Money::sum(Currency::NUC, self.postings.iter().map(|p| Money::nuc(p.credit_nuc)))?.minor_units();
+        if debits != credits {
+            return Err(LedgerError::Unbalanced { debits, credits });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builders_are_balanced() {
+        let order_id = Uuid::new_v4();
+        let item_id = Uuid::new_v4();
+
+        for tx in [
+            JournalTransaction::sale(order_id, Money::nuc(1000), Money::nuc(0)),
+            JournalTransaction::sale(order_id, Money::nuc(1150), Money::nuc(150)),
+            JournalTransaction::payment(order_id, Money::nuc(1000), Some("pi_1")),
+            JournalTransaction::revenue_recognition(order_id, item_id, Money::nuc(600)),
+            JournalTransaction::breakage(order_id, item_id, Money::nuc(45)),
+            JournalTransaction::refund(order_id, Some(item_id), Money::nuc(400), Money::nuc(0), "Flight removed"),
+            JournalTransaction::refund(order_id, None, Money::nuc(1150), Money::nuc(150), "Flight removed"),
+            JournalTransaction::installment_default(order_id, Money::nuc(690), Money::nuc(90), Money::nuc(46), Money::nuc(6)),
+            JournalTransaction::installment_default(order_id, Money::nuc(600), Money::nuc(0), Money::nuc(0), Money::nuc(0)),
+            JournalTransaction::carrier_payable(order_id, item_id, Uuid::new_v4(), Money::nuc(300)),
+            JournalTransaction::compensation(order_id, item_id, Money::nuc(64800), true, "EU261"),
+            JournalTransaction::chargeback(order_id, Money::nuc(1150), "dp_1"),
+        ] {
+            let tx = tx.unwrap();
+            assert_eq!(tx.validate(), Ok(()), "{}", tx.kind);
+        }
+    }
+
+    #[test]
+    fn test_invariants_reject_bad_transactions() {
+        let mut tx = JournalTransaction::sale(Uuid::new_v4(), Money::nuc(1000), Money::nuc(0)).unwrap();
+        tx.postings[1].credit_nuc = 900;
+        assert_eq!(tx.validate(), Err(LedgerError::Unbalanced { debits: 1000, credits: 900 }));
+
+        tx.postings[1] = Posting { account: Account::UnearnedRevenue, debit_nuc: 10, credit_nuc: 990, counterparty_id: None };
+        assert_eq!(tx.validate(), Err(LedgerError::InvalidPosting(Account::UnearnedRevenue)));
+
+        tx.postings.truncate(1);
+        assert_eq!(tx.validate(), Err(LedgerError::TooFewPostings));
+
+        // Zero-amount transactions have nothing to record
+        assert!(JournalTransaction::sale(Uuid::new_v4(), Money::nuc(0), Money::nuc(0)).unwrap().validate().is_err());
+    }
+
+    #[test]
+    fn test_journal_only_takes_nuc() {
+        let usd = Money::new(1000, Currency::new("USD").unwrap());
+        assert_eq!(
+            JournalTransaction::payment(Uuid::new_v4(), usd, None).unwrap_err(),
+            LedgerError::UnsupportedCurrency(usd.currency())
+        );
+        assert!(matches!(
+            JournalTransaction::sale(Uuid::new_v4(), Money::nuc(1000), usd),
+            Err(LedgerError::Money(MoneyError::CurrencyMismatch { .. }))
+        ));
+    }
+}
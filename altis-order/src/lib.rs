@@ -6,9 +6,23 @@ pub mod finance;
 pub mod changes;
 pub mod settlement;
 pub mod orchestrator;
+pub mod travelers;
+pub mod protection;
+pub mod interline;
+pub mod ledger;
+pub mod invoice;
+pub mod cart;
+pub mod reaccommodation;
 
 pub use models::{Order, OrderItem, OrderStatus, Fulfillment};
 pub use manager::OrderManager;
 pub use fulfillment::FulfillmentService;
 pub use changes::ChangeHandler;
 pub use orchestrator::PaymentOrchestrator;
+pub mod compensation;
+pub mod baggage;
+pub mod installments;
+pub mod disputes;
+pub mod waivers;
+pub mod schedule_change;
+pub mod seating;
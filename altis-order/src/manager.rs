@@ -19,7 +19,7 @@ impl OrderManager {
         let mut order = Order::new(customer_id);
         
         for item in items {
-            order.add_item(item);
+            order.add_item(item).map_err(|e| OrderError::ModificationFailed(e.to_string()))?;
         }
         
         self.orders.insert(order.id, order.clone());
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use altis_shared::money::{self, Currency, Money, MoneyError};
 
 /// Order status in the lifecycle
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -8,6 +9,8 @@ use chrono::{DateTime, Utc};
 pub enum OrderStatus {
     Proposed,
     Locked,
+    /// On an installment plan with payments still to come
+    HoldPartiallyPaid,
     Paid,
     Fulfilled,
     Archived,
@@ -46,7 +49,8 @@ pub struct Order {
     pub offer_id: Option<Uuid>,
     pub airline_id: Option<Uuid>,
     pub items: Vec<OrderItem>,
-    pub total_nuc: i32,
+    #[serde(rename = "total_nuc", with = "money::nuc")]
+    pub total: Money,
     pub currency: String,
     pub status: OrderStatus,
     pub payment_method: Option<String>,
@@ -66,7 +70,7 @@ impl Order {
             offer_id: None,
             airline_id: None,
             items: Vec::new(),
-            total_nuc: 0,
+            total: Money::zero(Currency::NUC),
             currency: "NUC".to_string(),
             status: OrderStatus::Proposed,
             payment_method: None,
@@ -77,10 +81,11 @@ impl Order {
     }
     
     /// Add an item to the order
-    pub fn add_item(&mut self, item: OrderItem) {
-        self.total_nuc += item.price_nuc;
+    pub fn add_item(&mut self, item: OrderItem) -> Result<(), MoneyError> {
+        self.total = self.total.checked_add(item.line_price()?)?.checked_add(item.line_tax()?)?;
         self.items.push(item);
         self.updated_at = Utc::now();
+        Ok(())
     }
     
     /// Update order status
@@ -89,12 +94,11 @@ impl Order {
         self.updated_at = Utc::now();
     }
     
-    /// Calculate active items total
-    pub fn calculate_active_total(&self) -> i32 {
+    /// Calculate active items total, taxes included
+    pub fn calculate_active_total(&self) -> Result<Money, MoneyError> {
         self.items.iter()
             .filter(|item| item.status == OrderItemStatus::Active)
-            .map(|item| item.price_nuc)
-            .sum()
+            .try_fold(Money::zero(self.total.currency()), |total, item| total.checked_add(item.line_price()?)?.checked_add(item.line_tax()?))
     }
 }
 
@@ -107,14 +111,32 @@ pub struct OrderItem {
     pub product_code: Option<String>,
     pub name: String,
     pub description: Option<String>,
-    pub price_nuc: i32,
+    /// Price of one unit
+    #[serde(rename = "price_nuc", with = "money::nuc")]
+    pub price: Money,
     pub quantity: i32,
+    /// Units of `quantity` refunded since purchase
+    #[serde(default)]
+    pub refunded_quantity: i32,
     pub status: OrderItemStatus,
     pub revenue_status: RevenueStatus,
     pub operating_carrier_id: Option<Uuid>,
-    pub net_rate_nuc: Option<i32>,
-    pub commission_nuc: Option<i32>,
+    #[serde(rename = "net_rate_nuc", with = "money::nuc::option")]
+    pub net_rate: Option<Money>,
+    #[serde(rename = "commission_nuc", with = "money::nuc::option")]
+    pub commission: Option<Money>,
     pub metadata: serde_json::Value,
+    /// Taxes on all `quantity` units, charged on top of the price; not revenue
+    #[serde(rename = "tax_nuc", with = "money::nuc", default = "money::nuc::zero")]
+    pub tax: Money,
+    #[serde(default)]
+    pub taxes: Vec<altis_catalog::TaxLine>,
+    /// The traveler the item is for, by their index on the order and, once
+    /// they're named, their id; neither for what covers the whole booking
+    #[serde(default)]
+    pub traveler_index: Option<i32>,
+    #[serde(default)]
+    pub traveler_id: Option<Uuid>,
 }
 
 impl OrderItem {
@@ -124,7 +146,7 @@ impl OrderItem {
         product_code: Option<String>,
         name: String,
         description: Option<String>,
-        price_nuc: i32,
+        price: Money,
         quantity: i32,
         metadata: serde_json::Value,
     ) -> Self {
@@ -135,14 +157,19 @@ impl OrderItem {
             product_code,
             name,
             description,
-            price_nuc,
+            price,
             quantity,
+            refunded_quantity: 0,
             status: OrderItemStatus::Active,
             revenue_status: RevenueStatus::Unearned,
             operating_carrier_id: None,
-            net_rate_nuc: None,
-            commission_nuc: None,
+            net_rate: None,
+            commission: None,
             metadata,
+            tax: money::nuc::zero(),
+            taxes: Vec::new(),
+            traveler_index: None,
+            traveler_id: None,
         }
     }
     
@@ -150,6 +177,46 @@ impl OrderItem {
     pub fn refund(&mut self) {
         self.status = OrderItemStatus::Refunded;
     }
+
+    /// Units bought, counting a line without a quantity as one
+    pub fn units(&self) -> i32 {
+        self.quantity.max(1)
+    }
+
+    /// Units still held: bought and not refunded
+    pub fn active_units(&self) -> i32 {
+        (self.units() - self.refunded_quantity).max(0)
+    }
+
+    /// What the units still held cost, before tax
+    pub fn line_price(&self) -> Result<Money, MoneyError> {
+        self.price.checked_mul(self.active_units() as i64)
+    }
+
+    /// The share of `tax` on unit `unit` (from 0): split evenly, with any
+    /// odd minor units on the first
+    pub fn unit_tax(&self, unit: i32) -> Result<Money, MoneyError> {
+        let shares = self.tax.split(self.units() as usize)?;
+        Ok(usize::try_from(unit).ok().and_then(|unit| shares.get(unit).copied()).unwrap_or(Money::zero(self.tax.currency())))
+    }
+
+    /// Tax on the units still held. Refunds give back the last units first.
+    pub fn line_tax(&self) -> Result<Money, MoneyError> {
+        Money::sum(self.tax.currency(), self.tax.split(self.units() as usize)?.into_iter().take(self.active_units() as usize))
+    }
+
+    /// Refund the last `units` still held, returning their price and tax.
+    /// The item is REFUNDED once none are left.
+    pub fn refund_units(&mut self, units: i32) -> Result<(Money, Money), MoneyError> {
+        let units = units.clamp(0, self.active_units());
+        let tax_before = self.line_tax()?;
+        self.refunded_quantity += units;
+        let tax = tax_before.checked_sub(self.line_tax()?)?;
+        if self.active_units() == 0 {
+            self.refund();
+        }
+        Ok((self.price.checked_mul(units as i64)?, tax))
+    }
 }
 
 /// Fulfillment record for delivering order items
@@ -188,11 +255,18 @@ pub struct LedgerEntry {
     pub id: Uuid,
     pub order_id: Uuid,
     pub order_item_id: Uuid,
-    pub transaction_type: String, // REVENUE_RECOGNITION, REFUND, ADJUSTMENT
-    pub amount_nuc: i32,
+    pub transaction_type: String, // REVENUE_RECOGNITION, BREAKAGE, REFUND, ADJUSTMENT
+    #[serde(rename = "amount_nuc", with = "money::nuc")]
+    pub amount: Money,
     pub currency: String,
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Partner carrier an interline posting is settled with
+    #[serde(default)]
+    pub counterparty_id: Option<Uuid>,
+    /// Tax collected alongside `amount`, kept out of revenue figures
+    #[serde(rename = "tax_nuc", with = "money::nuc", default = "money::nuc::zero")]
+    pub tax: Money,
 }
 
 /// A record for IATA settlement reporting
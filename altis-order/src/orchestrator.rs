@@ -1,25 +1,38 @@
-use altis_core::payment::{PaymentAdapter, PaymentIntent, PaymentStatus};
+use altis_core::payment::{NextAction, NextActionKind, PaymentAdapter, PaymentIntent, PaymentStatus, VaultedPaymentMethod};
+use altis_shared::money::Money;
+use altis_store::chaos::{ChaosInjector, ChaosTarget};
 use uuid::Uuid;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 pub struct PaymentOrchestrator {
     adapter: Arc<dyn PaymentAdapter>,
+    chaos: Arc<ChaosInjector>,
 }
 
 impl PaymentOrchestrator {
     pub fn new(adapter: Arc<dyn PaymentAdapter>) -> Self {
-        Self { adapter }
+        Self { adapter, chaos: Arc::new(ChaosInjector::disabled()) }
+    }
+
+    pub fn with_chaos(mut self, chaos: Arc<ChaosInjector>) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    pub fn provider(&self) -> &'static str {
+        self.adapter.provider()
     }
 
     /// Initialize a payment intent for an order
     pub async fn initialize_payment(
         &self,
         order_id: Uuid,
-        amount: i32,
-        currency: &str,
+        amount: Money,
     ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>> {
         // Here we could add logic to select different adapters based on currency/country
-        self.adapter.create_intent(order_id, amount, currency).await
+        self.chaos.inject(ChaosTarget::Payment).await?;
+        self.adapter.create_intent(order_id, amount).await
     }
 
     /// Process a status update (e.g., from a webhook)
@@ -27,6 +40,7 @@ impl PaymentOrchestrator {
         &self,
         intent_id: &str,
     ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>> {
+        self.chaos.inject(ChaosTarget::Payment).await?;
         let intent = self.adapter.get_intent(intent_id).await?;
         
         if intent.status == PaymentStatus::Succeeded {
@@ -40,29 +54,132 @@ impl PaymentOrchestrator {
         &self,
         payment: &altis_core::payment::PaymentIntent,
     ) -> Result<altis_core::payment::PaymentStatus, Box<dyn std::error::Error + Send + Sync>> {
+        self.chaos.inject(ChaosTarget::Payment).await?;
         self.adapter.process_payment(payment).await
     }
+
+    /// Finishes a payment the customer has authenticated, e.g. after a
+    /// 3-D Secure challenge
+    pub async fn confirm_payment(
+        &self,
+        intent_id: &str,
+    ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>> {
+        self.chaos.inject(ChaosTarget::Payment).await?;
+        self.adapter.confirm_payment(intent_id).await
+    }
+
+    /// Charges one installment of a plan against the payment method kept on
+    /// file. The intent id is the same on every attempt at an installment,
+    /// so a retry after a timeout can't take the money twice.
+    pub async fn charge_installment(
+        &self,
+        order_id: Uuid,
+        sequence: u32,
+        amount: Money,
+        reference: Option<&str>,
+    ) -> Result<PaymentStatus, Box<dyn std::error::Error + Send + Sync>> {
+        let intent = PaymentIntent {
+            id: format!("pi_{}_{}", order_id.simple(), sequence),
+            order_id,
+            amount: amount.to_i32()?,
+            currency: amount.currency().to_string(),
+            status: PaymentStatus::RequiresPaymentMethod,
+            reference: reference.map(str::to_string),
+            client_secret: None,
+            return_url: None,
+            next_action: None,
+            created_at: chrono::Utc::now(),
+        };
+        self.process_payment(&intent).await
+    }
+
+    /// Charges an extra bought on a paid order to the payment method on file.
+    /// The intent id follows the upsell or ancillary offer, so accepting it
+    /// again after a timeout can't take the money twice.
+    pub async fn charge_upsell(
+        &self,
+        order_id: Uuid,
+        upsell_id: Uuid,
+        amount: Money,
+        reference: &str,
+    ) -> Result<PaymentStatus, Box<dyn std::error::Error + Send + Sync>> {
+        let intent = PaymentIntent {
+            id: format!("pi_{}_{}", order_id.simple(), upsell_id.simple()),
+            order_id,
+            amount: amount.to_i32()?,
+            currency: amount.currency().to_string(),
+            status: PaymentStatus::RequiresPaymentMethod,
+            reference: Some(reference.to_string()),
+            client_secret: None,
+            return_url: None,
+            next_action: None,
+            created_at: chrono::Utc::now(),
+        };
+        self.process_payment(&intent).await
+    }
+
+    pub async fn refund_payment(
+        &self,
+        order_id: Uuid,
+        amount: Money,
+        idempotency_key: &str,
+    ) -> Result<PaymentStatus, Box<dyn std::error::Error + Send + Sync>> {
+        self.chaos.inject(ChaosTarget::Payment).await?;
+        self.adapter.refund_payment(order_id, amount, idempotency_key).await
+    }
+    pub async fn vault_payment_method(
+        &self,
+        customer_ref: &str,
+        single_use_token: &str,
+    ) -> Result<VaultedPaymentMethod, Box<dyn std::error::Error + Send + Sync>> {
+        self.chaos.inject(ChaosTarget::Payment).await?;
+        self.adapter.vault_payment_method(customer_ref, single_use_token).await
+    }
+
+    pub async fn remove_payment_method(&self, provider_token: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.chaos.inject(ChaosTarget::Payment).await?;
+        self.adapter.remove_payment_method(provider_token).await
+    }
+}
+
+/// Stands in for a provider in development and the sandbox. Payment
+/// references steer the outcome: `card-declined` fails, `fail-circuit`
+/// errors, `requires-sca` asks for a challenge that confirms and
+/// `sca-fails` for one that doesn't.
+#[derive(Default)]
+pub struct MockPaymentAdapter {
+    /// Intents processed so far, for lookups and confirmation
+    intents: Mutex<HashMap<String, PaymentIntent>>,
 }
 
-pub struct MockPaymentAdapter;
+impl MockPaymentAdapter {
+    fn remember(&self, intent: PaymentIntent) {
+        self.intents.lock().unwrap().insert(intent.id.clone(), intent);
+    }
+}
 
 #[async_trait::async_trait]
 impl PaymentAdapter for MockPaymentAdapter {
+    fn provider(&self) -> &'static str {
+        "mock"
+    }
+
     async fn create_intent(
         &self,
         order_id: Uuid,
-        amount: i32,
-        currency: &str,
+        amount: Money,
     ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>> {
         Ok(PaymentIntent {
             // Encode order_id in intent_id for the mock to "remember" it
             id: format!("mock_pi_{}", order_id.simple()),
             order_id,
-            amount,
-            currency: currency.to_string(),
+            amount: amount.to_i32()?,
+            currency: amount.currency().to_string(),
             status: PaymentStatus::RequiresPaymentMethod,
             reference: None,
             client_secret: Some("mock_secret_123".to_string()),
+            return_url: None,
+            next_action: None,
             created_at: chrono::Utc::now(),
         })
     }
@@ -71,6 +188,10 @@ impl PaymentAdapter for MockPaymentAdapter {
         &self,
         intent_id: &str,
     ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(intent) = self.intents.lock().unwrap().get(intent_id) {
+            return Ok(intent.clone());
+        }
+
         // Decode order_id from mock intent_id
         let order_id_str = intent_id.strip_prefix("mock_pi_").unwrap_or_default();
         let order_id = Uuid::parse_str(order_id_str).unwrap_or_else(|_| Uuid::new_v4());
@@ -84,10 +205,25 @@ impl PaymentAdapter for MockPaymentAdapter {
             status: PaymentStatus::Succeeded,
             reference: None,
             client_secret: None,
+            return_url: None,
+            next_action: None,
             created_at: chrono::Utc::now(),
         })
     }
 
+    async fn confirm_payment(
+        &self,
+        intent_id: &str,
+    ) -> Result<PaymentIntent, Box<dyn std::error::Error + Send + Sync>> {
+        let mut intents = self.intents.lock().unwrap();
+        let intent = intents.get_mut(intent_id).ok_or_else(|| format!("No such payment intent: {}", intent_id))?;
+        if intent.status == PaymentStatus::RequiresAction {
+            intent.status = if intent.reference.as_deref() == Some("sca-fails") { PaymentStatus::Failed } else { PaymentStatus::Succeeded };
+            intent.next_action = None;
+        }
+        Ok(intent.clone())
+    }
+
     async fn capture_payment(
         &self,
         intent_id: &str,
@@ -100,6 +236,93 @@ impl PaymentAdapter for MockPaymentAdapter {
         if payment.reference.as_deref() == Some("fail-circuit") {
             return Err("Simulated Payment Gateway Failure".into());
         }
+        let status = match payment.reference.as_deref() {
+            Some("card-declined") => PaymentStatus::Failed,
+            Some("requires-sca" | "sca-fails") => PaymentStatus::RequiresAction,
+            _ => PaymentStatus::Succeeded,
+        };
+        let next_action = (status == PaymentStatus::RequiresAction).then(|| NextAction {
+            kind: NextActionKind::RedirectToUrl,
+            redirect_url: Some(format!("https://mock-psp.test/3ds/{}", payment.id)),
+            client_secret: Some(format!("{}_secret", payment.id)),
+        });
+        self.remember(PaymentIntent { status: status.clone(), next_action, ..payment.clone() });
+        Ok(status)
+    }
+
+    async fn refund_payment(
+        &self,
+        _order_id: Uuid,
+        _amount: Money,
+        _idempotency_key: &str,
+    ) -> Result<PaymentStatus, Box<dyn std::error::Error + Send + Sync>> {
         Ok(PaymentStatus::Succeeded)
     }
+
+    async fn vault_payment_method(
+        &self,
+        _customer_ref: &str,
+        single_use_token: &str,
+    ) -> Result<VaultedPaymentMethod, Box<dyn std::error::Error + Send + Sync>> {
+        // Tokens ending in four digits show those as the card's last four
+        let last4 = single_use_token.len().checked_sub(4)
+            .and_then(|start| single_use_token.get(start..))
+            .filter(|tail| tail.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or("4242");
+        Ok(VaultedPaymentMethod {
+            provider_token: format!("pm_mock_{}", Uuid::new_v4().simple()),
+            brand: Some("VISA".to_string()),
+            last4: Some(last4.to_string()),
+            exp_month: Some(12),
+            exp_year: Some(2030),
+            fingerprint: Some(format!("fp_mock_{}", single_use_token)),
+        })
+    }
+
+    async fn remove_payment_method(&self, _provider_token: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intent(reference: &str) -> PaymentIntent {
+        let order_id = Uuid::new_v4();
+        PaymentIntent {
+            id: format!("pi_{}", order_id.simple()),
+            order_id,
+            amount: 12_000,
+            currency: "NUC".to_string(),
+            status: PaymentStatus::RequiresPaymentMethod,
+            reference: Some(reference.to_string()),
+            client_secret: None,
+            return_url: Some("https://shop.test/return".to_string()),
+            next_action: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_challenge_is_confirmed_or_fails_by_reference() {
+        let payments = PaymentOrchestrator::new(Arc::new(MockPaymentAdapter::default()));
+
+        let challenged = intent("requires-sca");
+        assert_eq!(payments.process_payment(&challenged).await.unwrap(), PaymentStatus::RequiresAction);
+        let pending = payments.process_status_update(&challenged.id).await.unwrap();
+        let action = pending.next_action.expect("a challenge to complete");
+        assert_eq!(action.kind, NextActionKind::RedirectToUrl);
+        assert!(action.redirect_url.unwrap().ends_with(&challenged.id));
+
+        let confirmed = payments.confirm_payment(&challenged.id).await.unwrap();
+        assert_eq!(confirmed.status, PaymentStatus::Succeeded);
+        assert!(confirmed.next_action.is_none());
+
+        let failing = intent("sca-fails");
+        assert_eq!(payments.process_payment(&failing).await.unwrap(), PaymentStatus::RequiresAction);
+        assert_eq!(payments.confirm_payment(&failing.id).await.unwrap().status, PaymentStatus::Failed);
+
+        assert!(payments.confirm_payment("pi_unknown").await.is_err());
+    }
 }
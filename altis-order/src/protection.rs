@@ -0,0 +1,359 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{OrderItem, OrderItemStatus};
+use altis_catalog::product::{FlightProduct, FlightStatus};
+use altis_shared::money::{self, Money, MoneyError, Rounding};
+
+/// Order item type for missed connection protection.
+pub const PROTECTION_PRODUCT_TYPE: &str = "CONNECTION_PROTECTION";
+
+/// Commercial terms of missed connection protection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionTerms {
+    /// Minimum connection time the passenger is guaranteed at the connecting airport
+    pub min_connection_minutes: i64,
+    /// Longer layovers are stopovers, not connections, and are not protected
+    pub max_connection_minutes: i64,
+    pub base_price_nuc: i32,
+    /// Share of the downstream fare added to the price (that fare is what we insure)
+    pub fare_percentage: f64,
+    /// Layovers shorter than this carry the tight-connection loading
+    pub tight_connection_minutes: i64,
+    pub tight_connection_loading: f64,
+}
+
+impl Default for ProtectionTerms {
+    fn default() -> Self {
+        Self {
+            min_connection_minutes: 90,
+            max_connection_minutes: 24 * 60,
+            base_price_nuc: 15,
+            fare_percentage: 0.05,
+            tight_connection_minutes: 180,
+            tight_connection_loading: 1.5,
+        }
+    }
+}
+
+/// Two separately ticketed flights where the first arrives where the second departs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SelfConnection {
+    pub inbound_item_id: Uuid,
+    pub outbound_item_id: Uuid,
+    pub inbound_flight_id: Option<String>,
+    pub connection_airport: String,
+    pub inbound_arrival: DateTime<Utc>,
+    pub outbound_departure: DateTime<Utc>,
+    pub layover_minutes: i64,
+    #[serde(rename = "outbound_price_nuc", with = "money::nuc")]
+    pub outbound_price: Money,
+}
+
+/// What the protection does for a disrupted connection.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "remedy", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProtectionOutcome {
+    /// Downstream flight replaced by a later one the passenger can still make
+    Rebook {
+        order_id: Uuid,
+        protection_item_id: Uuid,
+        downstream_item_id: Uuid,
+        replacement: OrderItem,
+    },
+    /// No workable alternative: the downstream ticket is refunded in full
+    Refund {
+        order_id: Uuid,
+        protection_item_id: Uuid,
+        downstream_item_id: Uuid,
+        #[serde(rename = "amount_nuc", serialize_with = "money::nuc::serialize")]
+        amount: Money,
+    },
+}
+
+struct FlightLeg<'a> {
+    item: &'a OrderItem,
+    origin: &'a str,
+    destination: &'a str,
+    departure: DateTime<Utc>,
+    arrival: DateTime<Utc>,
+}
+
+fn flight_leg(item: &OrderItem) -> Option<FlightLeg<'_>> {
+    if item.product_type != "FLIGHT" || item.status != OrderItemStatus::Active {
+        return None;
+    }
+
+    let time = |field: &str| {
+        item.metadata[field].as_str()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc))
+    };
+
+    Some(FlightLeg {
+        item,
+        origin: item.metadata["origin"].as_str()?,
+        destination: item.metadata["destination"].as_str()?,
+        departure: time("departure_time")?,
+        arrival: time("arrival_time")?,
+    })
+}
+
+/// Finds self-connections in a basket. Flights sold together on a through fare
+/// share `metadata.itinerary_id` and are protected by the carrier, so they are skipped.
+pub fn detect_self_connections(items: &[OrderItem], terms: &ProtectionTerms) -> Vec<SelfConnection> {
+    let legs: Vec<FlightLeg> = items.iter().filter_map(flight_leg).collect();
+    let mut connections = Vec::new();
+
+    for inbound in &legs {
+        for outbound in &legs {
+            if inbound.item.id == outbound.item.id || inbound.destination != outbound.origin {
+                continue;
+            }
+
+            let through_fare = inbound.item.metadata["itinerary_id"].as_str()
+                .is_some_and(|id| outbound.item.metadata["itinerary_id"].as_str() == Some(id));
+            if through_fare {
+                continue;
+            }
+
+            let layover = (outbound.departure - inbound.arrival).num_minutes();
+            if layover <= 0 || layover > terms.max_connection_minutes {
+                continue;
+            }
+
+            connections.push(SelfConnection {
+                inbound_item_id: inbound.item.id,
+                outbound_item_id: outbound.item.id,
+                inbound_flight_id: inbound.item.metadata["flight_id"].as_str().map(|s| s.to_string()),
+                connection_airport: inbound.destination.to_string(),
+                inbound_arrival: inbound.arrival,
+                outbound_departure: outbound.departure,
+                layover_minutes: layover,
+                outbound_price: outbound.item.price,
+            });
+        }
+    }
+
+    connections
+}
+
+/// Prices protection for one connection. Tighter layovers are likelier to be missed.
+pub fn price_protection(connection: &SelfConnection, terms: &ProtectionTerms) -> Result<Money, MoneyError> {
+    let fare_share = connection.outbound_price.scale(terms.fare_percentage, Rounding::HalfUp)?;
+    let mut price = Money::nuc(terms.base_price_nuc as i64).checked_add(fare_share)?;
+    if connection.layover_minutes < terms.tight_connection_minutes {
+        price = price.scale(terms.tight_connection_loading, Rounding::HalfUp)?;
+    }
+    Ok(price)
+}
+
+/// Builds the order item that records the protection and its terms.
+pub fn protection_item(connection: &SelfConnection, terms: &ProtectionTerms) -> Result<OrderItem, MoneyError> {
+    Ok(OrderItem::new(
+        PROTECTION_PRODUCT_TYPE.to_string(),
+        None,
+        Some("MCP".to_string()),
+        format!("Missed connection protection ({})", connection.connection_airport),
+        Some("Rebooking or refund of the onward flight if the inbound flight is disrupted".to_string()),
+        price_protection(connection, terms)?,
+        1,
+        serde_json::json!({
+            "inbound_item_id": connection.inbound_item_id,
+            "outbound_item_id": connection.outbound_item_id,
+            "inbound_flight_id": connection.inbound_flight_id,
+            "connection_airport": connection.connection_airport,
+            "layover_minutes": connection.layover_minutes,
+            "min_connection_minutes": terms.min_connection_minutes,
+        }),
+    ))
+}
+
+/// Decides the remedy for a protected connection whose inbound flight was disrupted.
+///
+/// A delay is covered once it eats into the guaranteed minimum connection time;
+/// a cancellation always is. The passenger is rebooked on the earliest
+/// alternative on the downstream route that departs after the (delayed) arrival
+/// plus the minimum connection time, otherwise the downstream fare is refunded.
+/// Cancelled inbounds have no known arrival, so they go straight to refund.
+pub fn resolve_protection(
+    order_id: Uuid,
+    protection: &OrderItem,
+    outbound: &OrderItem,
+    cancelled: bool,
+    delay_minutes: i64,
+    alternatives: &[FlightProduct],
+) -> Option<ProtectionOutcome> {
+    let min_connection = protection.metadata["min_connection_minutes"].as_i64()
+        .unwrap_or(ProtectionTerms::default().min_connection_minutes);
+    let layover = protection.metadata["layover_minutes"].as_i64()?;
+
+    if !cancelled && layover - delay_minutes >= min_connection {
+        return None;
+    }
+
+    let refund = ProtectionOutcome::Refund {
+        order_id,
+        protection_item_id: protection.id,
+        downstream_item_id: outbound.id,
+        amount: outbound.price,
+    };
+    if cancelled {
+        return Some(refund);
+    }
+
+    let leg = flight_leg(outbound)?;
+    let earliest_departure = leg.departure - Duration::minutes(layover) + Duration::minutes(delay_minutes + min_connection);
+
+    let replacement = alternatives.iter()
+        .filter(|alt| alt.status == FlightStatus::Scheduled && alt.available_seats > 0)
+        .filter(|alt| alt.origin == leg.origin && alt.destination == leg.destination)
+        .filter(|alt| alt.departure_time >= earliest_departure)
+        .min_by_key(|alt| alt.departure_time);
+
+    Some(match replacement {
+        Some(alt) => {
+            let mut metadata = alt.product.metadata.clone();
+            metadata["flight_id"] = serde_json::json!(alt.flight_id.to_string());
+            metadata["origin"] = serde_json::json!(alt.origin);
+            metadata["destination"] = serde_json::json!(alt.destination);
+            metadata["departure_time"] = serde_json::json!(alt.departure_time.to_rfc3339());
+            metadata["arrival_time"] = serde_json::json!(alt.arrival_time.to_rfc3339());
+            metadata["protected_item_id"] = serde_json::json!(outbound.id.to_string());
+
+            // Rebooked outright: unlike carrier re-accommodation there is nothing to accept
+            let replacement = OrderItem::new(
+                "FLIGHT".to_string(),
+                Some(alt.product.id),
+                Some(alt.product.product_code.clone()),
+                alt.product.name.clone(),
+                alt.product.description.clone(),
+                Money::zero(outbound.price.currency()), // Covered by the protection
+                1,
+                metadata,
+            );
+
+            ProtectionOutcome::Rebook {
+                order_id,
+                protection_item_id: protection.id,
+                downstream_item_id: outbound.id,
+                replacement,
+            }
+        }
+        None => refund,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altis_catalog::product::{Product, ProductType};
+
+    fn flight(origin: &str, destination: &str, departs: &str, arrives: &str, price: i64) -> OrderItem {
+        OrderItem::new(
+            "FLIGHT".to_string(),
+            None,
+            None,
+            format!("{}-{}", origin, destination),
+            None,
+            Money::nuc(price),
+            1,
+            serde_json::json!({
+                "flight_id": Uuid::new_v4().to_string(),
+                "origin": origin,
+                "destination": destination,
+                "departure_time": departs,
+                "arrival_time": arrives,
+            }),
+        )
+    }
+
+    fn alternative(departs: &str) -> FlightProduct {
+        let departure = DateTime::parse_from_rfc3339(departs).unwrap().with_timezone(&Utc);
+        FlightProduct {
+            product: Product {
+                id: Uuid::new_v4(),
+                product_type: ProductType::Flight,
+                product_code: "AL202".to_string(),
+                name: "SIN-NRT".to_string(),
+                description: None,
+                base_price_nuc: 300,
+                margin_percentage: 0.15,
+                is_active: true,
+                metadata: serde_json::json!({}),
+            },
+            flight_id: Uuid::new_v4(),
+            origin: "SIN".to_string(),
+            destination: "NRT".to_string(),
+            departure_time: departure,
+            arrival_time: departure + Duration::hours(7),
+            available_seats: 10,
+            status: FlightStatus::Scheduled,
+        }
+    }
+
+    fn basket() -> Vec<OrderItem> {
+        vec![
+            flight("BKK", "SIN", "2026-03-01T08:00:00Z", "2026-03-01T10:30:00Z", 120),
+            flight("SIN", "NRT", "2026-03-01T12:30:00Z", "2026-03-01T19:30:00Z", 400),
+        ]
+    }
+
+    #[test]
+    fn test_detects_and_prices_self_connection() {
+        let terms = ProtectionTerms::default();
+        let items = basket();
+        let connections = detect_self_connections(&items, &terms);
+
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].connection_airport, "SIN");
+        assert_eq!(connections[0].layover_minutes, 120);
+        // (15 + 5% of 400) with the tight-connection loading
+        assert_eq!(price_protection(&connections[0], &terms), Ok(Money::nuc(53)));
+    }
+
+    #[test]
+    fn test_through_fare_is_not_a_self_connection() {
+        let mut items = basket();
+        for item in &mut items {
+            item.metadata["itinerary_id"] = serde_json::json!("IT-1");
+        }
+        assert!(detect_self_connections(&items, &ProtectionTerms::default()).is_empty());
+    }
+
+    #[test]
+    fn test_delay_rebooks_onto_reachable_alternative() {
+        let terms = ProtectionTerms::default();
+        let items = basket();
+        let connection = &detect_self_connections(&items, &terms)[0];
+        let protection = protection_item(connection, &terms).unwrap();
+        let order_id = Uuid::new_v4();
+
+        // 20 minutes late still leaves the 90 minute minimum
+        assert!(resolve_protection(order_id, &protection, &items[1], false, 20, &[]).is_none());
+
+        // 60 minutes late: arrives 11:30, needs a departure from 13:00
+        let alternatives = [alternative("2026-03-01T12:45:00Z"), alternative("2026-03-01T15:00:00Z"), alternative("2026-03-01T13:30:00Z")];
+        match resolve_protection(order_id, &protection, &items[1], false, 60, &alternatives) {
+            Some(ProtectionOutcome::Rebook { replacement, downstream_item_id, .. }) => {
+                assert_eq!(downstream_item_id, items[1].id);
+                assert_eq!(replacement.metadata["departure_time"], "2026-03-01T13:30:00+00:00");
+                assert!(replacement.price.is_zero());
+            }
+            other => panic!("expected rebook, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cancellation_refunds_downstream() {
+        let terms = ProtectionTerms::default();
+        let items = basket();
+        let protection = protection_item(&detect_self_connections(&items, &terms)[0], &terms).unwrap();
+
+        match resolve_protection(Uuid::new_v4(), &protection, &items[1], true, 0, &[alternative("2026-03-01T15:00:00Z")]) {
+            Some(ProtectionOutcome::Refund { amount, .. }) => assert_eq!(amount, Money::nuc(400)),
+            other => panic!("expected refund, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use altis_catalog::product::FlightProduct;
+
+/// A different cabin counts as much against an alternative as arriving this
+/// much later in the booked one
+pub const CABIN_MISMATCH_PENALTY_MINUTES: i64 = 240;
+
+/// What a disrupted passenger booked, and which replacement flights are
+/// acceptable
+#[derive(Debug, Clone)]
+pub struct ReaccommodationSearch {
+    /// The disrupted flight, never proposed again
+    pub flight_id: Uuid,
+    pub origin: String,
+    pub destination: String,
+    /// When the passenger was due to arrive
+    pub scheduled_arrival: DateTime<Utc>,
+    pub cabin_class: Option<String>,
+    /// Seats needed on the replacement
+    pub seats: i32,
+    /// Departures outside this window aren't considered
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+/// A replacement flight and how it compares with the original booking
+#[derive(Debug, Clone)]
+pub struct RankedAlternative {
+    pub flight: FlightProduct,
+    /// Minutes between the original and the new arrival, either way
+    pub arrival_delay_minutes: i64,
+    pub cabin_match: bool,
+}
+
+impl RankedAlternative {
+    fn cost(&self) -> i64 {
+        self.arrival_delay_minutes + if self.cabin_match { 0 } else { CABIN_MISMATCH_PENALTY_MINUTES }
+    }
+}
+
+impl ReaccommodationSearch {
+    /// Future departures on the same route with enough seats, closest
+    /// arrival to the original first, up to `max_options`
+    pub fn rank(&self, candidates: &[FlightProduct], max_options: usize) -> Vec<RankedAlternative> {
+        let mut ranked: Vec<RankedAlternative> = candidates.iter()
+            .filter(|f| f.product.id != self.flight_id && f.flight_id != self.flight_id && f.product.is_active)
+            .filter(|f| f.origin == self.origin && f.destination == self.destination)
+            .filter(|f| f.departure_time >= self.not_before && f.departure_time <= self.not_after)
+            .filter(|f| f.available_seats >= self.seats)
+            .map(|f| RankedAlternative {
+                flight: f.clone(),
+                arrival_delay_minutes: (f.arrival_time - self.scheduled_arrival).num_minutes().abs(),
+                cabin_match: self.cabin_class.as_deref().is_none_or(|booked| {
+                    f.product.metadata["cabin_class"].as_str().is_some_and(|cabin| cabin.eq_ignore_ascii_case(booked))
+                }),
+            })
+            .collect();
+        ranked.sort_by_key(|alt| (alt.cost(), alt.flight.departure_time));
+        ranked.truncate(max_options);
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altis_catalog::product::{FlightStatus, Product, ProductType};
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 15).unwrap().and_hms_opt(hour, 0, 0).unwrap().and_utc()
+    }
+
+    fn flight(departs: u32, cabin: &str, seats: i32) -> FlightProduct {
+        let id = Uuid::new_v4();
+        FlightProduct {
+            product: Product {
+                id,
+                product_type: ProductType::Flight,
+                product_code: format!("AL{}", departs),
+                name: "SIN-BKK".to_string(),
+                description: None,
+                base_price_nuc: 20000,
+                margin_percentage: 0.15,
+                is_active: true,
+                metadata: serde_json::json!({"cabin_class": cabin}),
+            },
+            flight_id: id,
+            origin: "SIN".to_string(),
+            destination: "BKK".to_string(),
+            departure_time: at(departs),
+            arrival_time: at(departs + 2),
+            available_seats: seats,
+            status: FlightStatus::Scheduled,
+        }
+    }
+
+    #[test]
+    fn test_rank_alternatives() {
+        let search = ReaccommodationSearch {
+            flight_id: Uuid::new_v4(),
+            origin: "SIN".to_string(),
+            destination: "BKK".to_string(),
+            scheduled_arrival: at(10),
+            cabin_class: Some("ECONOMY".to_string()),
+            seats: 2,
+            not_before: at(9),
+            not_after: at(20),
+        };
+        let candidates = vec![
+            flight(7, "ECONOMY", 50),  // departed already
+            flight(12, "ECONOMY", 1),  // not enough seats
+            flight(11, "BUSINESS", 9), // 3h later, other cabin
+            flight(14, "ECONOMY", 9),  // 6h later
+            flight(10, "ECONOMY", 9),  // 2h later
+            flight(21, "ECONOMY", 9),  // outside the window
+        ];
+
+        let ranked = search.rank(&candidates, 2);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].flight.departure_time, at(10));
+        assert_eq!(ranked[0].arrival_delay_minutes, 120);
+        // Three hours late plus the cabin penalty loses to six hours in cabin
+        assert_eq!(ranked[1].flight.departure_time, at(14));
+    }
+}
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How much a retimed flight matters to the passengers booked on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ScheduleChangeSeverity {
+    /// Bookings are updated quietly
+    Minor,
+    /// Passengers are told about the new times
+    Notify,
+    /// Passengers are told, and accept the new times or decline them for a refund
+    Major,
+}
+
+/// A flight's times before and after an edit to its schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleChange {
+    pub previous_departure: DateTime<Utc>,
+    pub new_departure: DateTime<Utc>,
+    pub previous_arrival: Option<DateTime<Utc>>,
+    pub new_arrival: Option<DateTime<Utc>>,
+}
+
+fn time(metadata: &serde_json::Value, field: &str) -> Option<DateTime<Utc>> {
+    metadata[field].as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+impl ScheduleChange {
+    /// The change between two versions of a flight's metadata, if either
+    /// of its times moved
+    pub fn between(before: &serde_json::Value, after: &serde_json::Value) -> Option<Self> {
+        let change = Self {
+            previous_departure: time(before, "departure_time")?,
+            new_departure: time(after, "departure_time")?,
+            previous_arrival: time(before, "arrival_time"),
+            new_arrival: time(after, "arrival_time"),
+        };
+        let arrival_moved = change.previous_arrival.is_some() && change.new_arrival.is_some() && change.previous_arrival != change.new_arrival;
+        (change.previous_departure != change.new_departure || arrival_moved).then_some(change)
+    }
+
+    /// The larger move of the departure and the arrival, earlier or later
+    pub fn shift_minutes(&self) -> i64 {
+        let departure = (self.new_departure - self.previous_departure).num_minutes().abs();
+        let arrival = match (self.previous_arrival, self.new_arrival) {
+            (Some(before), Some(after)) => (after - before).num_minutes().abs(),
+            _ => 0,
+        };
+        departure.max(arrival)
+    }
+
+    /// Passengers are notified beyond `notify_minutes`, and asked to accept
+    /// beyond `major_minutes`
+    pub fn severity(&self, notify_minutes: i64, major_minutes: i64) -> ScheduleChangeSeverity {
+        let shift = self.shift_minutes();
+        if shift > major_minutes {
+            ScheduleChangeSeverity::Major
+        } else if shift > notify_minutes {
+            ScheduleChangeSeverity::Notify
+        } else {
+            ScheduleChangeSeverity::Minor
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_change_severity() {
+        let flight = |departure: &str, arrival: &str| serde_json::json!({"departure_time": departure, "arrival_time": arrival});
+        let before = flight("2026-11-02T10:00:00Z", "2026-11-02T12:00:00Z");
+
+        assert_eq!(ScheduleChange::between(&before, &before.clone()), None);
+        // Offsets are compared as instants
+        assert_eq!(ScheduleChange::between(&before, &flight("2026-11-02T18:00:00+08:00", "2026-11-02T12:00:00Z")), None);
+
+        let retimed = ScheduleChange::between(&before, &flight("2026-11-02T10:20:00Z", "2026-11-02T12:20:00Z")).unwrap();
+        assert_eq!(retimed.shift_minutes(), 20);
+        assert_eq!(retimed.severity(30, 180), ScheduleChangeSeverity::Minor);
+
+        // A longer flight moves the arrival more than the departure
+        let retimed = ScheduleChange::between(&before, &flight("2026-11-02T09:30:00Z", "2026-11-02T13:00:00Z")).unwrap();
+        assert_eq!(retimed.shift_minutes(), 60);
+        assert_eq!(retimed.severity(30, 180), ScheduleChangeSeverity::Notify);
+
+        let retimed = ScheduleChange::between(&before, &flight("2026-11-02T06:00:00Z", "2026-11-02T08:00:00Z")).unwrap();
+        assert_eq!(retimed.severity(30, 180), ScheduleChangeSeverity::Major);
+    }
+}
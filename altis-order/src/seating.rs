@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// What a seat is sold as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SeatCategory {
+    Standard,
+    /// Chargeable; only assigned to passengers who paid for one or whose fare includes one
+    ExtraLegroom,
+}
+
+impl SeatCategory {
+    /// The category of a SEAT item bought or bundled on an order
+    pub fn of_item(product_code: Option<&str>, metadata: &serde_json::Value) -> Self {
+        let named = metadata["category"].as_str().or(metadata["seat_category"].as_str());
+        match named {
+            Some("EXTRA_LEGROOM") => SeatCategory::ExtraLegroom,
+            Some(_) => SeatCategory::Standard,
+            None if product_code == Some("SEAT_XL") => SeatCategory::ExtraLegroom,
+            None => SeatCategory::Standard,
+        }
+    }
+}
+
+fn first_row() -> u32 {
+    1
+}
+
+/// A flight's cabin, as published in its `seat_map` metadata
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeatMap {
+    #[serde(default = "first_row")]
+    pub first_row: u32,
+    pub last_row: u32,
+    /// Seat letters across a row with `-` for each aisle, e.g. `ABC-DEF`
+    pub layout: String,
+    #[serde(default)]
+    pub extra_legroom_rows: Vec<u32>,
+    /// Rows children may not sit in
+    #[serde(default)]
+    pub exit_rows: Vec<u32>,
+    /// Seats never assigned, e.g. crew rest or out of service
+    #[serde(default)]
+    pub blocked: Vec<String>,
+}
+
+impl SeatMap {
+    /// The map in a flight's metadata, if it has a usable one
+    pub fn from_flight(metadata: &serde_json::Value) -> Option<Self> {
+        let map: SeatMap = serde_json::from_value(metadata["seat_map"].clone()).ok()?;
+        let letters = map.layout.chars().any(|c| c.is_ascii_alphabetic());
+        (map.first_row <= map.last_row && letters).then_some(map)
+    }
+
+    /// Groups of seats between aisles, window to aisle
+    fn blocks(&self) -> Vec<Vec<char>> {
+        self.layout.split('-')
+            .map(|block| block.chars().filter(|c| c.is_ascii_alphabetic()).map(|c| c.to_ascii_uppercase()).collect::<Vec<_>>())
+            .filter(|block| !block.is_empty())
+            .collect()
+    }
+
+    fn category(&self, row: u32) -> SeatCategory {
+        if self.extra_legroom_rows.contains(&row) { SeatCategory::ExtraLegroom } else { SeatCategory::Standard }
+    }
+}
+
+/// A passenger waiting for a seat on one flight
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeatPassenger {
+    pub traveler_index: i32,
+    /// Children sit next to an adult from their booking, away from exit rows
+    pub child: bool,
+    /// A chargeable seat the passenger already has a right to
+    pub entitlement: Option<SeatCategory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SeatAssignment {
+    pub traveler_index: i32,
+    pub seat_number: String,
+    pub category: SeatCategory,
+}
+
+struct Cabin<'a> {
+    map: &'a SeatMap,
+    blocks: Vec<Vec<char>>,
+    unavailable: HashSet<String>,
+}
+
+impl Cabin<'_> {
+    /// The first `count` free seats side by side in a row, with no aisle
+    /// between them, in one of `categories` (tried in order)
+    fn take_run(&mut self, count: usize, categories: &[SeatCategory], children: bool) -> Option<Vec<(String, SeatCategory)>> {
+        for category in categories {
+            for row in self.map.first_row..=self.map.last_row {
+                if self.map.category(row) != *category || (children && self.map.exit_rows.contains(&row)) {
+                    continue;
+                }
+                for block in &self.blocks {
+                    for run in block.windows(count) {
+                        let seats: Vec<String> = run.iter().map(|letter| format!("{}{}", row, letter)).collect();
+                        if seats.iter().all(|seat| !self.unavailable.contains(seat)) {
+                            self.unavailable.extend(seats.iter().cloned());
+                            return Some(seats.into_iter().map(|seat| (seat, *category)).collect());
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn seat(&mut self, party: &[&SeatPassenger]) -> Option<Vec<SeatAssignment>> {
+        // A chargeable seat only if everyone sitting together has a right to one
+        let categories: &[SeatCategory] = if party.iter().all(|p| p.entitlement == Some(SeatCategory::ExtraLegroom)) {
+            &[SeatCategory::ExtraLegroom, SeatCategory::Standard]
+        } else {
+            &[SeatCategory::Standard]
+        };
+        let children = party.iter().any(|p| p.child);
+        let seats = self.take_run(party.len(), categories, children)?;
+        Some(party.iter().zip(seats).map(|(passenger, (seat_number, category))| SeatAssignment {
+            traveler_index: passenger.traveler_index,
+            seat_number,
+            category,
+        }).collect())
+    }
+}
+
+/// Seats one booking's passengers on a flight, around the seats already
+/// `taken` and those the map blocks.
+///
+/// The booking sits together in one row where it fits. Otherwise each child
+/// sits beside an adult from the booking, as many children per adult as the
+/// widest block between aisles allows; children who can't be seated next to
+/// an adult are left unassigned, with that adult, for the airport to seat.
+/// Extra-legroom seats go only to passengers entitled to them.
+pub fn assign_seats(map: &SeatMap, taken: &HashSet<String>, passengers: &[SeatPassenger]) -> Vec<SeatAssignment> {
+    let mut unavailable: HashSet<String> = taken.iter().map(|seat| seat.to_uppercase()).collect();
+    unavailable.extend(map.blocked.iter().map(|seat| seat.to_uppercase()));
+    let mut cabin = Cabin { map, blocks: map.blocks(), unavailable };
+    let width = cabin.blocks.iter().map(Vec::len).max().unwrap_or(0);
+    if width == 0 || passengers.is_empty() {
+        return Vec::new();
+    }
+
+    let everyone: Vec<&SeatPassenger> = passengers.iter().collect();
+    if let Some(together) = cabin.seat(&everyone) {
+        return together;
+    }
+
+    let (children, mut adults): (Vec<&SeatPassenger>, Vec<&SeatPassenger>) = everyone.into_iter().partition(|p| p.child);
+    let mut assigned = Vec::new();
+    // One adult per group, with as many children as fit beside them
+    for group in children.chunks(width.saturating_sub(1).max(1)) {
+        if adults.is_empty() {
+            break;
+        }
+        let mut family = vec![adults.remove(0)];
+        family.extend_from_slice(group);
+        if family.len() <= width {
+            assigned.extend(cabin.seat(&family).unwrap_or_default());
+        }
+    }
+    for adult in adults {
+        assigned.extend(cabin.seat(&[adult]).unwrap_or_default());
+    }
+    assigned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passenger(traveler_index: i32, child: bool, entitlement: Option<SeatCategory>) -> SeatPassenger {
+        SeatPassenger { traveler_index, child, entitlement }
+    }
+
+    #[test]
+    fn test_assign_seats() {
+        let map = SeatMap::from_flight(&serde_json::json!({"seat_map": {
+            "last_row": 4, "layout": "AB-CD", "extra_legroom_rows": [1], "exit_rows": [2], "blocked": ["3a"],
+        }})).unwrap();
+        let seat = |assigned: &[SeatAssignment], index: i32| assigned.iter().find(|a| a.traveler_index == index).map(|a| a.seat_number.clone());
+
+        // Nobody is given a chargeable seat they haven't paid for
+        let solo = assign_seats(&map, &HashSet::new(), &[passenger(0, false, None)]);
+        assert_eq!(seat(&solo, 0).as_deref(), Some("2A"));
+        let entitled = assign_seats(&map, &HashSet::new(), &[passenger(0, false, Some(SeatCategory::ExtraLegroom))]);
+        assert_eq!(entitled[0].category, SeatCategory::ExtraLegroom);
+        assert_eq!(entitled[0].seat_number, "1A");
+
+        // A parent and child skip the exit row, the blocked seat and taken ones
+        let taken: HashSet<String> = ["3C".to_string()].into();
+        let family = assign_seats(&map, &taken, &[passenger(0, false, None), passenger(1, true, None)]);
+        assert_eq!(seat(&family, 0).as_deref(), Some("4A"));
+        assert_eq!(seat(&family, 1).as_deref(), Some("4B"));
+
+        // Too many to sit together: each child still sits beside an adult
+        let full: HashSet<String> = ["3B".to_string(), "4A".to_string()].into();
+        let split = assign_seats(&map, &full, &[passenger(0, false, None), passenger(1, false, None), passenger(2, true, None), passenger(3, true, None)]);
+        assert_eq!(split.len(), 4);
+        assert_eq!(seat(&split, 0).as_deref(), Some("3C"));
+        assert_eq!(seat(&split, 2).as_deref(), Some("3D"));
+
+        // A child with no adult free to sit beside is left for the airport
+        let crowded: HashSet<String> = ["3B", "3C", "4A", "4C"].map(str::to_string).into();
+        let left = assign_seats(&map, &crowded, &[passenger(0, false, None), passenger(1, true, None)]);
+        assert!(left.is_empty());
+    }
+}
@@ -1,7 +1,8 @@
 use crate::models::{Order, LedgerEntry};
 use async_trait::async_trait;
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 /// Standardized interface for converting internal financial records into industry-recognized formats.
@@ -30,7 +31,7 @@ impl SettlementAdaptor for IataSwoAdaptor {
                 },
                 "financialDetails": {
                     "totalAmount": {
-                        "value": entry.amount_nuc,
+                        "value": entry.amount.minor_units(),
                         "currency": entry.currency,
                     },
                     "transactionType": entry.transaction_type,
@@ -61,13 +62,9 @@ impl SettlementAdaptor for LegacyHotAdaptor {
 
         for entry in ledger {
             items.push(json!({
-                "trans_type": match entry.transaction_type.as_str() {
-                    "REVENUE_RECOGNITION" => "TKTT", // Mocking legacy RET codes
-                    "REFUND" => "RFND",
-                    _ => "MISC",
-                },
-                "doc_number": format!("999-{}", Uuid::new_v4().to_string().split('-').next().unwrap_or("000")),
-                "amount": entry.amount_nuc,
+                "trans_type": legacy_trans_code(&entry.transaction_type),
+                "doc_number": document_number(&entry),
+                "amount": entry.amount.minor_units(),
                 "currency": entry.currency,
                 "order_id": order.id,
                 "timestamp": entry.created_at.to_rfc3339(),
@@ -77,3 +74,144 @@ impl SettlementAdaptor for LegacyHotAdaptor {
         Ok(json!(items))
     }
 }
+
+/// Legacy RET/HOT transaction code for a ledger transaction type.
+fn legacy_trans_code(transaction_type: &str) -> &'static str {
+    match transaction_type {
+        "REVENUE_RECOGNITION" => "TKTT",
+        "REFUND" => "RFND",
+        _ => "MISC",
+    }
+}
+
+/// Stable document number derived from the ledger entry, so re-exporting a
+/// batch yields the same file.
+fn document_number(entry: &LedgerEntry) -> String {
+    format!("999-{}", &entry.id.simple().to_string()[..10])
+}
+
+// ============================================================================
+// Settlement Batches
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BatchStatus {
+    Draft,
+    Submitted,
+    Confirmed,
+}
+
+impl BatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchStatus::Draft => "DRAFT",
+            BatchStatus::Submitted => "SUBMITTED",
+            BatchStatus::Confirmed => "CONFIRMED",
+        }
+    }
+
+    /// Batches only move forward, one step at a time.
+    pub fn can_transition_to(&self, next: BatchStatus) -> bool {
+        matches!(
+            (self, next),
+            (BatchStatus::Draft, BatchStatus::Submitted) | (BatchStatus::Submitted, BatchStatus::Confirmed)
+        )
+    }
+}
+
+/// Ledger entries of one airline swept together for settlement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementBatch {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    pub period_start: Option<DateTime<Utc>>,
+    pub period_end: DateTime<Utc>,
+    pub status: BatchStatus,
+    pub entry_count: i32,
+    pub total_nuc: i64,
+    pub currency: String,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Sequential settlement statement number issued with the batch
+    #[serde(default)]
+    pub document_number: Option<String>,
+}
+
+/// Fixed-width HOT file rendering: a BFH01 header, one BKT06 record per ledger
+/// entry and a BFT99 trailer carrying the record count and net amount.
+pub struct HotFile;
+
+impl HotFile {
+    pub fn header(batch: &SettlementBatch) -> String {
+        format!(
+            "BFH01{}{}{}{:<3}{:<20}\n",
+            batch.id.simple(),
+            batch.airline_id.simple(),
+            batch.period_end.format("%Y%m%d"),
+            batch.currency,
+            batch.document_number.as_deref().unwrap_or_default(),
+        )
+    }
+
+    pub fn record(sequence: usize, order_id: Uuid, entry: &LedgerEntry) -> String {
+        format!(
+            "BKT06{:08}{:<4}{:<14}{}{}{:015}{:<3}{}\n",
+            sequence,
+            legacy_trans_code(&entry.transaction_type),
+            document_number(entry),
+            order_id.simple(),
+            if entry.amount.minor_units() < 0 { '-' } else { '+' },
+            entry.amount.minor_units().unsigned_abs(),
+            entry.currency,
+            entry.created_at.format("%Y%m%d"),
+        )
+    }
+
+    pub fn trailer(record_count: usize, total_nuc: i64) -> String {
+        format!(
+            "BFT99{:08}{}{:015}\n",
+            record_count,
+            if total_nuc < 0 { '-' } else { '+' },
+            total_nuc.unsigned_abs(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altis_shared::money::{Currency, Money};
+
+    #[test]
+    fn test_batch_status_moves_forward_only() {
+        assert!(BatchStatus::Draft.can_transition_to(BatchStatus::Submitted));
+        assert!(BatchStatus::Submitted.can_transition_to(BatchStatus::Confirmed));
+        assert!(!BatchStatus::Draft.can_transition_to(BatchStatus::Confirmed));
+        assert!(!BatchStatus::Confirmed.can_transition_to(BatchStatus::Draft));
+    }
+
+    #[test]
+    fn test_hot_records_are_fixed_width() {
+        let entry = |transaction_type: &str, amount_nuc: i64| LedgerEntry {
+            id: Uuid::new_v4(),
+            order_id: Uuid::new_v4(),
+            order_item_id: Uuid::new_v4(),
+            transaction_type: transaction_type.to_string(),
+            amount: Money::nuc(amount_nuc),
+            currency: "NUC".to_string(),
+            description: None,
+            created_at: Utc::now(),
+            counterparty_id: None,
+            tax: Money::zero(Currency::NUC),
+        };
+
+        let sale = HotFile::record(1, Uuid::new_v4(), &entry("REVENUE_RECOGNITION", 5000));
+        let refund = HotFile::record(2, Uuid::new_v4(), &entry("REFUND", -1200));
+
+        assert_eq!(sale.len(), refund.len());
+        assert!(sale.starts_with("BKT0600000001TKTT"));
+        assert!(refund.contains("RFND"));
+        assert!(refund.contains("-000000000001200NUC"));
+        assert_eq!(HotFile::trailer(2, 3800), "BFT9900000002+000000000003800\n");
+    }
+}
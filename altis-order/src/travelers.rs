@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::Cursor;
+
+use calamine::{open_workbook_from_rs, Data, DataType, Reader, Xlsx};
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+
+/// Orders with at least this many passengers are handled as group bookings
+/// (deferred names, name deadline).
+pub const GROUP_BOOKING_MIN_PAX: i32 = 10;
+
+/// A validated traveler row ready to be stored.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TravelerRecord {
+    pub traveler_index: Option<i32>,
+    pub ptc: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub date_of_birth: Option<String>,
+    pub gender: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RowError {
+    /// 1-based data row number (the header row is not counted)
+    pub row: usize,
+    pub errors: Vec<String>,
+}
+
+/// Outcome of parsing an upload: valid rows are accepted even if others fail.
+#[derive(Debug, Default, Serialize)]
+pub struct TravelerImport {
+    pub accepted: Vec<TravelerRecord>,
+    pub rejected: Vec<RowError>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Unreadable file: {0}")]
+    Unreadable(String),
+    #[error("Missing required column: {0}")]
+    MissingColumn(&'static str),
+}
+
+/// Parses a CSV upload with a header row.
+pub fn parse_csv(bytes: &[u8]) -> Result<TravelerImport, ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(bytes);
+
+    let header: Vec<String> = reader.headers()
+        .map_err(|e| ImportError::Unreadable(e.to_string()))?
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| ImportError::Unreadable(e.to_string()))?;
+        rows.push(record.iter().map(|c| c.to_string()).collect());
+    }
+
+    validate_rows(&header, rows)
+}
+
+/// Parses the first worksheet of an XLSX upload with a header row.
+pub fn parse_xlsx(bytes: &[u8]) -> Result<TravelerImport, ImportError> {
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes))
+        .map_err(|e: calamine::XlsxError| ImportError::Unreadable(e.to_string()))?;
+
+    let range = workbook.worksheet_range_at(0)
+        .ok_or(ImportError::Unreadable("Workbook has no sheets".to_string()))?
+        .map_err(|e| ImportError::Unreadable(e.to_string()))?;
+
+    let mut rows = range.rows().map(|row| row.iter().map(cell_to_string).collect::<Vec<_>>());
+    let header = rows.next().unwrap_or_default();
+
+    validate_rows(&header, rows.collect())
+}
+
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        // Excel stores dates as serial numbers
+        Data::DateTime(_) => cell.as_date().map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default(),
+        Data::Empty => String::new(),
+        other => other.to_string().trim().to_string(),
+    }
+}
+
+fn validate_rows(header: &[String], rows: Vec<Vec<String>>) -> Result<TravelerImport, ImportError> {
+    let column = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+    let first_name_col = column("first_name").ok_or(ImportError::MissingColumn("first_name"))?;
+    let last_name_col = column("last_name").ok_or(ImportError::MissingColumn("last_name"))?;
+    let index_col = column("traveler_index");
+    let ptc_col = column("ptc");
+    let dob_col = column("date_of_birth");
+    let gender_col = column("gender");
+
+    let mut import = TravelerImport::default();
+    let mut seen_indices = HashSet::new();
+
+    for (i, row) in rows.into_iter().enumerate() {
+        let cell = |col: Option<usize>| {
+            col.and_then(|c| row.get(c)).map(|v| v.trim()).filter(|v| !v.is_empty())
+        };
+
+        // Skip fully blank lines (common at the end of spreadsheets)
+        if row.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+
+        let (record, mut errors) = match validate_traveler(
+            cell(ptc_col),
+            cell(Some(first_name_col)),
+            cell(Some(last_name_col)),
+            cell(dob_col),
+            cell(gender_col),
+        ) {
+            Ok(record) => (Some(record), Vec::new()),
+            Err(errors) => (None, errors),
+        };
+
+        let traveler_index = match cell(index_col) {
+            Some(raw) => match raw.parse::<f64>() {
+                // XLSX hands numbers back as floats
+                Ok(n) if n >= 0.0 && n.fract() == 0.0 => {
+                    let n = n as i32;
+                    if !seen_indices.insert(n) {
+                        errors.push(format!("duplicate traveler_index {}", n));
+                    }
+                    Some(n)
+                }
+                _ => {
+                    errors.push(format!("traveler_index must be a non-negative integer (got {})", raw));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        match record {
+            Some(record) if errors.is_empty() => import.accepted.push(TravelerRecord { traveler_index, ..record }),
+            _ => import.rejected.push(RowError { row: i + 1, errors }),
+        }
+    }
+
+    Ok(import)
+}
+
+/// Checks one traveler's details: names fit for ticketing, a known PTC, a
+/// date of birth matching it, and a known gender. The record comes back
+/// normalized and without a traveler index.
+pub fn validate_traveler(
+    ptc: Option<&str>,
+    first_name: Option<&str>,
+    last_name: Option<&str>,
+    date_of_birth: Option<&str>,
+    gender: Option<&str>,
+) -> Result<TravelerRecord, Vec<String>> {
+    let mut errors = Vec::new();
+
+    let first_name = validate_name(first_name, "first_name", &mut errors);
+    let last_name = validate_name(last_name, "last_name", &mut errors);
+
+    let ptc = ptc.unwrap_or("ADT").to_uppercase();
+    if !matches!(ptc.as_str(), "ADT" | "CHD" | "INF") {
+        errors.push(format!("ptc must be ADT, CHD or INF (got {})", ptc));
+    }
+
+    let date_of_birth = match date_of_birth {
+        Some(raw) => match NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+            Ok(dob) => {
+                validate_age(&ptc, dob, &mut errors);
+                Some(dob.format("%Y-%m-%d").to_string())
+            }
+            Err(_) => {
+                errors.push(format!("date_of_birth must be YYYY-MM-DD (got {})", raw));
+                None
+            }
+        },
+        // Age-restricted fares need a DOB
+        None if ptc != "ADT" => {
+            errors.push(format!("date_of_birth is required for {}", ptc));
+            None
+        }
+        None => None,
+    };
+
+    let gender = gender.map(|g| g.to_uppercase());
+    if let Some(g) = &gender {
+        if !matches!(g.as_str(), "M" | "F" | "X") {
+            errors.push(format!("gender must be M, F or X (got {})", g));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(TravelerRecord {
+        traveler_index: None,
+        ptc,
+        first_name: first_name.unwrap_or_default(),
+        last_name: last_name.unwrap_or_default(),
+        date_of_birth,
+        gender,
+    })
+}
+
+fn validate_name(value: Option<&str>, field: &str, errors: &mut Vec<String>) -> Option<String> {
+    match value {
+        None => {
+            errors.push(format!("{} is required", field));
+            None
+        }
+        Some(name) if name.chars().count() > 64 => {
+            errors.push(format!("{} exceeds 64 characters", field));
+            None
+        }
+        // Names must survive into ticketing systems: letters, spaces, hyphens, apostrophes
+        Some(name) if !name.chars().all(|c| c.is_alphabetic() || matches!(c, ' ' | '-' | '\'')) => {
+            errors.push(format!("{} contains invalid characters", field));
+            None
+        }
+        Some(name) => Some(name.to_string()),
+    }
+}
+
+fn validate_age(ptc: &str, dob: NaiveDate, errors: &mut Vec<String>) {
+    let today = Utc::now().date_naive();
+    if dob > today {
+        errors.push("date_of_birth is in the future".to_string());
+        return;
+    }
+
+    let age = today.years_since(dob).unwrap_or(0);
+    match ptc {
+        "INF" if age >= 2 => errors.push(format!("INF must be under 2 (age {})", age)),
+        "CHD" if !(2..12).contains(&age) => errors.push(format!("CHD must be 2-11 (age {})", age)),
+        "ADT" if age < 12 => errors.push(format!("ADT must be 12 or older (age {})", age)),
+        _ => {}
+    }
+}
+
+/// Checks the travelers named on a booking against the passengers its fares
+/// were priced for (`fares`, a count per PTC): the same number of each
+/// type, and no more infants than adults to hold them on their laps.
+pub fn check_party(travelers: &[TravelerRecord], fares: &BTreeMap<String, i32>) -> Vec<String> {
+    let mut named: BTreeMap<String, i32> = BTreeMap::new();
+    for traveler in travelers {
+        *named.entry(traveler.ptc.clone()).or_default() += 1;
+    }
+
+    let mut errors = Vec::new();
+    if !fares.is_empty() {
+        for ptc in ["ADT", "CHD", "INF"] {
+            let (priced, travelling) = (fares.get(ptc).copied().unwrap_or(0), named.get(ptc).copied().unwrap_or(0));
+            if priced != travelling {
+                errors.push(format!("fares are for {} {} but {} traveler(s) are {}", priced, ptc, travelling, ptc));
+            }
+        }
+    }
+    let count = |ptc: &str| named.get(ptc).copied().unwrap_or(0);
+    if count("INF") > count("ADT") {
+        errors.push(format!("{} INF but only {} ADT to hold them", count("INF"), count("ADT")));
+    }
+    errors
+}
+
+/// Assigns free traveler indices to rows that didn't specify one and drops rows
+/// beyond the group size. Returns the rows to store and the rejections.
+pub fn assign_indices(
+    records: Vec<TravelerRecord>,
+    existing_indices: &[i32],
+    group_size: Option<i32>,
+) -> (Vec<TravelerRecord>, Vec<String>) {
+    let mut taken: HashSet<i32> = existing_indices.iter().copied().collect();
+    let mut next = 0;
+    let mut stored = Vec::new();
+    let mut overflow = Vec::new();
+
+    for mut record in records {
+        // Rows re-using an existing index replace that traveler rather than adding one
+        let is_new = record.traveler_index.is_none_or(|i| !taken.contains(&i));
+
+        if is_new {
+            if let Some(size) = group_size {
+                if taken.len() as i32 >= size {
+                    overflow.push(format!("{} {}: group of {} is already complete", record.first_name, record.last_name, size));
+                    continue;
+                }
+            }
+        }
+
+        let index = match record.traveler_index {
+            Some(i) => i,
+            None => {
+                while taken.contains(&next) {
+                    next += 1;
+                }
+                next
+            }
+        };
+        taken.insert(index);
+        record.traveler_index = Some(index);
+        stored.push(record);
+    }
+
+    (stored, overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_partial_acceptance() {
+        let csv = "first_name,last_name,ptc,date_of_birth,gender\n\
+                   Ada,Lovelace,ADT,1990-12-10,F\n\
+                   ,Turing,ADT,,\n\
+                   Tim,O'Neil,CHD,2019-05-01,M\n\
+                   Baby,Doe,INF,2000-01-01,\n";
+
+        let import = parse_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(import.accepted.len(), 2);
+        assert_eq!(import.rejected.len(), 2);
+        assert_eq!(import.rejected[0].row, 2);
+        assert_eq!(import.rejected[1].row, 4);
+        assert!(import.rejected[1].errors[0].contains("INF"));
+    }
+
+    #[test]
+    fn test_csv_missing_column() {
+        let result = parse_csv(b"first_name,ptc\nAda,ADT\n");
+        assert!(matches!(result, Err(ImportError::MissingColumn("last_name"))));
+    }
+
+    #[test]
+    fn test_check_party() {
+        let traveler = |ptc: &str| TravelerRecord {
+            traveler_index: None,
+            ptc: ptc.to_string(),
+            first_name: "Ada".to_string(),
+            last_name: "Tan".to_string(),
+            date_of_birth: None,
+            gender: None,
+        };
+        let fares = BTreeMap::from([("ADT".to_string(), 1), ("INF".to_string(), 1)]);
+
+        assert!(check_party(&[traveler("INF"), traveler("ADT")], &fares).is_empty());
+        let errors = check_party(&[traveler("ADT"), traveler("CHD")], &fares);
+        assert_eq!(errors, vec![
+            "fares are for 0 CHD but 1 traveler(s) are CHD".to_string(),
+            "fares are for 1 INF but 0 traveler(s) are INF".to_string(),
+        ]);
+        // Fares from before passenger types were priced aren't counted, laps still are
+        assert_eq!(check_party(&[traveler("ADT"), traveler("INF"), traveler("INF")], &BTreeMap::new()).len(), 1);
+    }
+
+    #[test]
+    fn test_assign_indices_respects_group_size() {
+        let record = |first: &str, index: Option<i32>| TravelerRecord {
+            traveler_index: index,
+            ptc: "ADT".to_string(),
+            first_name: first.to_string(),
+            last_name: "Smith".to_string(),
+            date_of_birth: None,
+            gender: None,
+        };
+
+        let (stored, overflow) = assign_indices(
+            vec![record("A", None), record("B", Some(0)), record("C", None), record("D", None)],
+            &[0],
+            Some(3),
+        );
+
+        let indices: Vec<_> = stored.iter().map(|r| r.traveler_index.unwrap()).collect();
+        assert_eq!(indices, vec![1, 0, 2]);
+        assert_eq!(overflow.len(), 1);
+    }
+}
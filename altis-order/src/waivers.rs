@@ -0,0 +1,111 @@
+use altis_shared::money::{self, Money};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::LedgerEntry;
+
+/// What an agent waived
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WaiverKind {
+    ChangeFee,
+    CancellationFee,
+    NameChangeFee,
+    BaggageFee,
+    FareDifference,
+    Other,
+}
+
+impl WaiverKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WaiverKind::ChangeFee => "CHANGE_FEE",
+            WaiverKind::CancellationFee => "CANCELLATION_FEE",
+            WaiverKind::NameChangeFee => "NAME_CHANGE_FEE",
+            WaiverKind::BaggageFee => "BAGGAGE_FEE",
+            WaiverKind::FareDifference => "FARE_DIFFERENCE",
+            WaiverKind::Other => "OTHER",
+        }
+    }
+}
+
+/// A charge an agent waived for the customer on one order item. A waived
+/// amount is credited back to the item as a ledger ADJUSTMENT; a waiver of
+/// a rule alone (e.g. a missed name deadline) moves no money.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Waiver {
+    pub kind: WaiverKind,
+    pub order_item_id: Uuid,
+    #[serde(rename = "amount_nuc", with = "money::nuc", default = "money::nuc::zero")]
+    pub amount: Money,
+    pub reason: String,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum WaiverError {
+    #[error("A waiver needs a reason")]
+    MissingReason,
+    #[error("A waived amount can't be negative")]
+    NegativeAmount,
+}
+
+impl Waiver {
+    pub fn validate(&self) -> Result<(), WaiverError> {
+        if self.reason.trim().is_empty() {
+            return Err(WaiverError::MissingReason);
+        }
+        if self.amount.minor_units() < 0 {
+            return Err(WaiverError::NegativeAmount);
+        }
+        Ok(())
+    }
+
+    /// The adjustment crediting the waived amount to the item, if any
+    pub fn ledger_entry(&self, order_id: Uuid) -> Option<LedgerEntry> {
+        if self.amount.is_zero() {
+            return None;
+        }
+        Some(LedgerEntry {
+            id: Uuid::new_v4(),
+            order_id,
+            order_item_id: self.order_item_id,
+            transaction_type: "ADJUSTMENT".to_string(),
+            amount: Money::nuc(-self.amount.minor_units()),
+            currency: "NUC".to_string(),
+            description: Some(format!("{} waived: {}", self.kind.as_str(), self.reason.trim())),
+            created_at: Utc::now(),
+            counterparty_id: None,
+            tax: Money::nuc(0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waived_amount_is_credited_to_the_item() {
+        let item_id = Uuid::new_v4();
+        let waiver: Waiver = serde_json::from_value(serde_json::json!({
+            "kind": "CHANGE_FEE",
+            "order_item_id": item_id,
+            "amount_nuc": 5000,
+            "reason": "Schedule change by the airline",
+        })).unwrap();
+        assert_eq!(waiver.validate(), Ok(()));
+
+        let entry = waiver.ledger_entry(Uuid::new_v4()).unwrap();
+        assert_eq!(entry.transaction_type, "ADJUSTMENT");
+        assert_eq!(entry.order_item_id, item_id);
+        assert_eq!(entry.amount, Money::nuc(-5000));
+
+        // Waiving a rule rather than a charge posts nothing
+        let rule_only = Waiver { amount: Money::nuc(0), ..waiver.clone() };
+        assert!(rule_only.ledger_entry(Uuid::new_v4()).is_none());
+
+        assert_eq!(Waiver { reason: " ".to_string(), ..waiver.clone() }.validate(), Err(WaiverError::MissingReason));
+        assert_eq!(Waiver { amount: Money::nuc(-1), ..waiver }.validate(), Err(WaiverError::NegativeAmount));
+    }
+}
@@ -1,4 +1,6 @@
 pub mod models;
+pub mod money;
 pub mod pii;
 
 pub use models::events;
+pub use money::{Currency, Money, MoneyError, Rounding};
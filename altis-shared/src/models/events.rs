@@ -24,12 +24,27 @@ pub struct OfferAcceptedEvent {
     pub timestamp: i64,
 }
 
+/// A subject was shown offers ranked under an experiment assignment
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ExperimentExposureEvent {
+    pub experiment: Option<String>,
+    pub variant: String, // treatment, control
+    pub bucket: u32,
+    pub strategy: String,
+    pub subject: String,
+    pub offer_ids: Vec<Uuid>,
+    pub cached: bool,
+    pub timestamp: i64,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct OrderPaidEvent {
     pub order_id: Uuid,
     pub offer_id: Option<Uuid>,
     pub customer_id: String,
     pub total_nuc: i32,
+    /// Currency of `total_nuc`
+    pub currency: String,
     pub timestamp: i64,
 }
 
@@ -0,0 +1,341 @@
+//! Amounts of money in integer minor units (NUC cents, for the engine's own
+//! prices). Arithmetic is checked, combining two currencies is an error, and
+//! the only way a rate gets applied is through [`Money::scale`] with an
+//! explicit rounding mode.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum MoneyError {
+    #[error("Cannot combine {left} with {right}")]
+    CurrencyMismatch { left: Currency, right: Currency },
+    #[error("Amount out of range")]
+    Overflow,
+    #[error("'{0}' is not a three-letter currency code")]
+    InvalidCurrency(String),
+    #[error("{0} is not a usable factor")]
+    InvalidFactor(f64),
+    #[error("Nothing to allocate to")]
+    NoAllocationTargets,
+}
+
+/// ISO 4217-style three-letter code, upper-case
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    /// Neutral Unit of Construction, the currency of catalog prices
+    pub const NUC: Currency = Currency(*b"NUC");
+
+    pub fn new(code: &str) -> Result<Self, MoneyError> {
+        let bytes = code.as_bytes();
+        if bytes.len() != 3 || !bytes.iter().all(u8::is_ascii_alphabetic) {
+            return Err(MoneyError::InvalidCurrency(code.to_string()));
+        }
+        Ok(Currency([bytes[0].to_ascii_uppercase(), bytes[1].to_ascii_uppercase(), bytes[2].to_ascii_uppercase()]))
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("currency codes are ASCII letters")
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Currency::new(code)
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = MoneyError;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        Currency::new(&code)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.as_str().to_string()
+    }
+}
+
+/// How [`Money::scale`] settles a fractional minor unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Toward zero: never charges or refunds more than the exact amount
+    Down,
+    /// To the nearest unit, halves away from zero
+    HalfUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Money {
+    minor_units: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn new(minor_units: i64, currency: Currency) -> Self {
+        Self { minor_units, currency }
+    }
+
+    pub fn nuc(minor_units: i64) -> Self {
+        Self::new(minor_units, Currency::NUC)
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(0, currency)
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor_units == 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.minor_units > 0
+    }
+
+    /// A NUC amount read from an `i32` `*_nuc` column or field
+    pub fn from_nuc_i32(minor_units: i32) -> Self {
+        Self::nuc(minor_units as i64)
+    }
+
+    /// The amount for an `i32` `*_nuc` column or field
+    pub fn to_i32(&self) -> Result<i32, MoneyError> {
+        i32::try_from(self.minor_units).map_err(|_| MoneyError::Overflow)
+    }
+
+    /// The amount, provided it's in `currency`
+    pub fn require(self, currency: Currency) -> Result<Money, MoneyError> {
+        self.same_currency(&Money::zero(currency))?;
+        Ok(self)
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch { left: self.currency, right: other.currency });
+        }
+        Ok(())
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let sum = self.minor_units.checked_add(other.minor_units).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(sum, self.currency))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let difference = self.minor_units.checked_sub(other.minor_units).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(difference, self.currency))
+    }
+
+    /// The amount times a whole quantity
+    pub fn checked_mul(self, quantity: i64) -> Result<Money, MoneyError> {
+        let product = self.minor_units.checked_mul(quantity).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(product, self.currency))
+    }
+
+    pub fn checked_neg(self) -> Result<Money, MoneyError> {
+        Ok(Money::new(self.minor_units.checked_neg().ok_or(MoneyError::Overflow)?, self.currency))
+    }
+
+    /// Applies a multiplier or rate (1.2 for +20%, 0.1 for a 10% tax),
+    /// rounding the result to a whole minor unit.
+    pub fn scale(self, factor: f64, rounding: Rounding) -> Result<Money, MoneyError> {
+        if !factor.is_finite() {
+            return Err(MoneyError::InvalidFactor(factor));
+        }
+        let exact = self.minor_units as f64 * factor;
+        let rounded = match rounding {
+            Rounding::Down => exact.trunc(),
+            Rounding::HalfUp => exact.round(),
+        };
+        // i64::MAX as f64 rounds up to 2^63, so the bound is exclusive
+        if rounded >= i64::MAX as f64 || rounded < i64::MIN as f64 {
+            return Err(MoneyError::Overflow);
+        }
+        Ok(Money::new(rounded as i64, self.currency))
+    }
+
+    /// Splits the amount in proportion to `weights`, largest remainder
+    /// first, so the parts always add back up to the whole; earlier parts
+    /// win ties. All-zero weights split evenly.
+    pub fn allocate(self, weights: &[u64]) -> Result<Vec<Money>, MoneyError> {
+        if weights.is_empty() {
+            return Err(MoneyError::NoAllocationTargets);
+        }
+        let weights: Vec<u128> = if weights.iter().all(|w| *w == 0) {
+            vec![1; weights.len()]
+        } else {
+            weights.iter().map(|w| *w as u128).collect()
+        };
+        let total_weight: u128 = weights.iter().sum();
+
+        // Allocate the magnitude, then restore the sign (refunds are negative)
+        let magnitude = self.minor_units.unsigned_abs() as u128;
+        let mut shares: Vec<u128> = weights.iter().map(|w| magnitude * w / total_weight).collect();
+
+        let mut remainder = magnitude - shares.iter().sum::<u128>();
+        let mut by_fraction: Vec<usize> = (0..weights.len()).collect();
+        by_fraction.sort_by_key(|&i| std::cmp::Reverse(magnitude * weights[i] % total_weight));
+        for &i in by_fraction.iter().cycle() {
+            if remainder == 0 {
+                break;
+            }
+            shares[i] += 1;
+            remainder -= 1;
+        }
+
+        let negative = self.minor_units < 0;
+        shares.into_iter().map(|share| {
+            let share = i64::try_from(share).map_err(|_| MoneyError::Overflow)?;
+            Ok(Money::new(if negative { -share } else { share }, self.currency))
+        }).collect()
+    }
+
+    /// Splits the amount into `parts` as-equal-as-possible pieces
+    pub fn split(self, parts: usize) -> Result<Vec<Money>, MoneyError> {
+        self.allocate(&vec![1; parts])
+    }
+
+    /// Adds up amounts in `currency`; an empty list sums to zero
+    pub fn sum<I: IntoIterator<Item = Money>>(currency: Currency, amounts: I) -> Result<Money, MoneyError> {
+        amounts.into_iter().try_fold(Money::zero(currency), Money::checked_add)
+    }
+}
+
+/// Serde for a `Money` field kept in the JSON shape of the `*_nuc` fields
+/// it replaced: a bare integer of NUC minor units. Amounts in any other
+/// currency refuse to serialize rather than pass as NUC.
+///
+/// ```ignore
+/// #[serde(rename = "price_nuc", with = "altis_shared::money::nuc")]
+/// pub price: Money,
+/// ```
+pub mod nuc {
+    use super::{Currency, Money};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        let amount = amount.require(Currency::NUC).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_i64(amount.minor_units())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        i64::deserialize(deserializer).map(Money::nuc)
+    }
+
+    /// For `#[serde(default = "money::nuc::zero")]` on fields older payloads lack
+    pub fn zero() -> Money {
+        Money::zero(Currency::NUC)
+    }
+
+    /// The same for `Option<Money>`, with None as null
+    pub mod option {
+        use super::Money;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(amount: &Option<Money>, serializer: S) -> Result<S::Ok, S::Error> {
+            match amount {
+                Some(amount) => super::serialize(amount, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Money>, D::Error> {
+            Ok(Option::<i64>::deserialize(deserializer)?.map(Money::nuc))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic_is_checked_and_single_currency() {
+        let usd = Currency::new("usd").unwrap();
+        assert_eq!(usd.as_str(), "USD");
+        assert!(Currency::new("US").is_err());
+
+        assert_eq!(Money::nuc(1000).checked_add(Money::nuc(250)), Ok(Money::nuc(1250)));
+        assert_eq!(Money::nuc(1000).checked_sub(Money::nuc(1250)), Ok(Money::nuc(-250)));
+        assert!(matches!(
+            Money::nuc(1000).checked_add(Money::new(1, usd)),
+            Err(MoneyError::CurrencyMismatch { .. })
+        ));
+        assert_eq!(Money::nuc(i64::MAX).checked_add(Money::nuc(1)), Err(MoneyError::Overflow));
+        assert_eq!(Money::nuc(i64::from(i32::MAX) + 1).to_i32(), Err(MoneyError::Overflow));
+        assert_eq!(Money::sum(Currency::NUC, [Money::nuc(1), Money::nuc(2)]), Ok(Money::nuc(3)));
+    }
+
+    #[test]
+    fn test_scale_rounds_explicitly() {
+        assert_eq!(Money::nuc(999).scale(0.9, Rounding::Down), Ok(Money::nuc(899)));
+        assert_eq!(Money::nuc(999).scale(0.9, Rounding::HalfUp), Ok(Money::nuc(899)));
+        assert_eq!(Money::nuc(1005).scale(0.1, Rounding::HalfUp), Ok(Money::nuc(101)));
+        assert_eq!(Money::nuc(-1005).scale(0.1, Rounding::Down), Ok(Money::nuc(-100)));
+        assert!(matches!(Money::nuc(100).scale(f64::NAN, Rounding::Down), Err(MoneyError::InvalidFactor(_))));
+        assert_eq!(Money::nuc(i64::MAX).scale(2.0, Rounding::Down), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn test_nuc_fields_keep_their_json_shape() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Item {
+            #[serde(rename = "price_nuc", with = "nuc")]
+            price: Money,
+            #[serde(rename = "commission_nuc", with = "nuc::option", default)]
+            commission: Option<Money>,
+        }
+
+        let item: Item = serde_json::from_value(serde_json::json!({"price_nuc": 12900})).unwrap();
+        assert_eq!(item, Item { price: Money::nuc(12900), commission: None });
+        assert_eq!(serde_json::to_value(&item).unwrap(), serde_json::json!({"price_nuc": 12900, "commission_nuc": null}));
+
+        let usd = Item { price: Money::new(100, Currency::new("USD").unwrap()), commission: None };
+        assert!(serde_json::to_value(&usd).is_err(), "USD must not pass as NUC");
+    }
+
+    #[test]
+    fn test_allocate_adds_up() {
+        let parts = Money::nuc(100).split(3).unwrap();
+        assert_eq!(parts, vec![Money::nuc(34), Money::nuc(33), Money::nuc(33)]);
+
+        let parts = Money::nuc(-101).allocate(&[1, 1, 1]).unwrap();
+        assert_eq!(parts.iter().map(Money::minor_units).sum::<i64>(), -101);
+
+        let parts = Money::nuc(10001).allocate(&[890, 5930]).unwrap();
+        assert_eq!(parts, vec![Money::nuc(1305), Money::nuc(8696)]);
+
+        assert_eq!(Money::nuc(10).allocate(&[0, 0]).unwrap(), vec![Money::nuc(5), Money::nuc(5)]);
+        assert_eq!(Money::nuc(10).allocate(&[]), Err(MoneyError::NoAllocationTargets));
+    }
+}
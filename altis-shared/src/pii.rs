@@ -1,4 +1,6 @@
 use serde::{Serialize, Deserialize, Serializer};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 
 /// A wrapper for sensitive data that masks its value in Debug output and can be customized for Serialization.
@@ -33,3 +35,254 @@ impl<T> Masked<T> {
         self.0
     }
 }
+
+// ============================================================================
+// Role-based masking
+// ============================================================================
+
+/// Kinds of personal data that masking policies can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiField {
+    Email,
+    Phone,
+    Name,
+    DateOfBirth,
+    PaymentReference,
+    /// Passport or ID card number
+    DocumentNumber,
+}
+
+impl PiiField {
+    pub const ALL: [PiiField; 6] = [
+        PiiField::Email,
+        PiiField::Phone,
+        PiiField::Name,
+        PiiField::DateOfBirth,
+        PiiField::PaymentReference,
+        PiiField::DocumentNumber,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    Full,
+    Partial,
+    Hidden,
+}
+
+/// Field visibilities for one role. Fields not listed are hidden.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaskingTier(pub HashMap<PiiField, Visibility>);
+
+impl MaskingTier {
+    pub fn full() -> Self {
+        Self(PiiField::ALL.iter().map(|f| (*f, Visibility::Full)).collect())
+    }
+
+    pub fn visibility(&self, field: PiiField) -> Visibility {
+        self.0.get(&field).copied().unwrap_or(Visibility::Hidden)
+    }
+
+    /// Same tier with the given fields fully revealed.
+    pub fn reveal(&self, fields: &[PiiField]) -> Self {
+        let mut tier = self.clone();
+        for field in fields {
+            tier.0.insert(*field, Visibility::Full);
+        }
+        tier
+    }
+}
+
+/// Masking tiers per role (role names are matched case-insensitively).
+/// Roles without a tier see nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaskingPolicy {
+    roles: HashMap<String, MaskingTier>,
+}
+
+impl Default for MaskingPolicy {
+    fn default() -> Self {
+        use PiiField::*;
+        use Visibility::*;
+
+        let tier = |fields: &[(PiiField, Visibility)]| MaskingTier(fields.iter().copied().collect());
+        let mut roles = HashMap::new();
+        roles.insert("super_admin".to_string(), MaskingTier::full());
+        roles.insert("admin".to_string(), tier(&[
+            (Email, Full), (Phone, Full), (Name, Full), (DateOfBirth, Partial), (PaymentReference, Partial),
+            (DocumentNumber, Partial),
+        ]));
+        roles.insert("support".to_string(), tier(&[
+            (Email, Partial), (Phone, Partial), (Name, Full), (DateOfBirth, Hidden), (PaymentReference, Partial),
+        ]));
+
+        Self { roles }
+    }
+}
+
+impl MaskingPolicy {
+    /// Applies configured tiers on top of the defaults, field by field.
+    pub fn with_overrides(mut self, overrides: HashMap<String, HashMap<PiiField, Visibility>>) -> Self {
+        for (role, fields) in overrides {
+            self.roles.entry(role.to_lowercase()).or_default().0.extend(fields);
+        }
+        self
+    }
+
+    pub fn tier(&self, role: &str) -> MaskingTier {
+        self.roles.get(&role.to_lowercase()).cloned().unwrap_or_default()
+    }
+}
+
+/// Renders a value at the given visibility; `None` means the field is withheld.
+pub fn mask(field: PiiField, value: &str, visibility: Visibility) -> Option<String> {
+    match visibility {
+        Visibility::Full => Some(value.to_string()),
+        Visibility::Hidden => None,
+        Visibility::Partial => Some(match field {
+            // j***@example.com
+            PiiField::Email => match value.split_once('@') {
+                Some((local, domain)) => format!("{}***@{}", local.chars().next().unwrap_or('*'), domain),
+                None => "***".to_string(),
+            },
+            // Ada -> A.
+            PiiField::Name => value.chars().next().map(|c| format!("{}.", c)).unwrap_or_default(),
+            // Year only
+            PiiField::DateOfBirth => format!("{}-**-**", value.chars().take(4).collect::<String>()),
+            // ****1234
+            PiiField::Phone | PiiField::PaymentReference | PiiField::DocumentNumber => {
+                let chars: Vec<char> = value.chars().collect();
+                let visible = if chars.len() > 4 { 4 } else { 0 };
+                let tail: String = chars[chars.len() - visible..].iter().collect();
+                format!("{}{}", "*".repeat(chars.len() - visible), tail)
+            }
+        }),
+    }
+}
+
+thread_local! {
+    static ACTIVE_TIER: RefCell<Option<MaskingTier>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` (typically `serde_json::to_value`) with `tier` applied to every
+/// field tagged with one of the serializers below. Outside such a scope the
+/// tagged fields serialize in full, so customer-facing responses are unaffected.
+pub fn with_masking<R>(tier: &MaskingTier, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<MaskingTier>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ACTIVE_TIER.with(|active| *active.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(ACTIVE_TIER.with(|active| active.replace(Some(tier.clone()))));
+    f()
+}
+
+/// Text values that masking serializers can read.
+pub trait PiiText {
+    fn pii_str(&self) -> Option<&str>;
+}
+
+impl PiiText for String {
+    fn pii_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T: PiiText> PiiText for Masked<T> {
+    fn pii_str(&self) -> Option<&str> {
+        self.0.pii_str()
+    }
+}
+
+impl<T: PiiText> PiiText for Option<T> {
+    fn pii_str(&self) -> Option<&str> {
+        self.as_ref().and_then(|v| v.pii_str())
+    }
+}
+
+fn serialize_field<T, S>(field: PiiField, value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: PiiText + Serialize,
+    S: Serializer,
+{
+    let visibility = ACTIVE_TIER.with(|active| active.borrow().as_ref().map(|tier| tier.visibility(field)));
+    match visibility {
+        None | Some(Visibility::Full) => value.serialize(serializer),
+        Some(visibility) => value.pii_str()
+            .and_then(|v| mask(field, v, visibility))
+            .serialize(serializer),
+    }
+}
+
+macro_rules! pii_serializer {
+    ($name:ident, $field:expr) => {
+        /// `serialize_with` hook applying the active masking tier.
+        pub fn $name<T: PiiText + Serialize, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_field($field, value, serializer)
+        }
+    };
+}
+
+pii_serializer!(email, PiiField::Email);
+pii_serializer!(phone, PiiField::Phone);
+pii_serializer!(name, PiiField::Name);
+pii_serializer!(date_of_birth, PiiField::DateOfBirth);
+pii_serializer!(payment_reference, PiiField::PaymentReference);
+pii_serializer!(document_number, PiiField::DocumentNumber);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Contact {
+        #[serde(serialize_with = "email")]
+        email: Masked<String>,
+        #[serde(serialize_with = "date_of_birth")]
+        dob: Option<String>,
+        #[serde(serialize_with = "payment_reference")]
+        card: Option<String>,
+    }
+
+    fn contact() -> Contact {
+        Contact {
+            email: Masked("ada@example.com".to_string()),
+            dob: Some("1990-12-10".to_string()),
+            card: Some("4242424242424242".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_support_tier_masks_fields() {
+        let tier = MaskingPolicy::default().tier("SUPPORT");
+        let json = with_masking(&tier, || serde_json::to_value(contact())).unwrap();
+
+        assert_eq!(json["email"], "a***@example.com");
+        assert!(json["dob"].is_null());
+        assert_eq!(json["card"], "************4242");
+    }
+
+    #[test]
+    fn test_unscoped_and_unknown_roles() {
+        // No scope: full values (customer responses)
+        let json = serde_json::to_value(contact()).unwrap();
+        assert_eq!(json["dob"], "1990-12-10");
+
+        // Unknown role: everything withheld
+        let tier = MaskingPolicy::default().tier("intern");
+        let json = with_masking(&tier, || serde_json::to_value(contact())).unwrap();
+        assert!(json["email"].is_null());
+
+        // Overrides merge over the defaults; reveal lifts a field to full
+        let mut overrides = HashMap::new();
+        overrides.insert("Support".to_string(), HashMap::from([(PiiField::DateOfBirth, Visibility::Partial)]));
+        let tier = MaskingPolicy::default().with_overrides(overrides).tier("support");
+        assert_eq!(tier.visibility(PiiField::DateOfBirth), Visibility::Partial);
+        assert_eq!(tier.visibility(PiiField::Email), Visibility::Partial);
+        assert_eq!(tier.reveal(&[PiiField::Email]).visibility(PiiField::Email), Visibility::Full);
+    }
+}
@@ -0,0 +1,3 @@
+-- Group bookings: names can be submitted after booking, up to a deadline
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS group_size INTEGER;
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS names_due_at TIMESTAMPTZ;
@@ -0,0 +1,2 @@
+-- Ancillary transfers: barcodes of transferred items are voided, not deleted
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS voided_at TIMESTAMPTZ;
@@ -0,0 +1,7 @@
+-- Scheduled fulfillment delivery: artifacts for some products are issued
+-- closer to departure by the delivery worker instead of at payment
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS deliver_at TIMESTAMPTZ;
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS delivery_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS last_delivery_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_fulfillment_pending_delivery ON fulfillment(deliver_at) WHERE delivered_at IS NULL;
@@ -0,0 +1,21 @@
+-- Settlement batches: ledger entries are swept into one batch per airline and
+-- period; an entry without a batch is still pending settlement
+CREATE TABLE IF NOT EXISTS settlement_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    airline_id UUID NOT NULL REFERENCES airlines(id),
+    period_start TIMESTAMPTZ,
+    period_end TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'DRAFT' CHECK (status IN ('DRAFT', 'SUBMITTED', 'CONFIRMED')),
+    entry_count INTEGER NOT NULL DEFAULT 0,
+    total_nuc BIGINT NOT NULL DEFAULT 0,
+    currency VARCHAR(10) NOT NULL DEFAULT 'NUC',
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    submitted_at TIMESTAMPTZ,
+    confirmed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_settlement_batches_airline ON settlement_batches(airline_id, period_end DESC);
+
+ALTER TABLE order_ledger ADD COLUMN IF NOT EXISTS settlement_batch_id UUID REFERENCES settlement_batches(id);
+CREATE INDEX IF NOT EXISTS idx_ledger_unbatched ON order_ledger(created_at) WHERE settlement_batch_id IS NULL;
+CREATE INDEX IF NOT EXISTS idx_ledger_batch ON order_ledger(settlement_batch_id);
@@ -0,0 +1,5 @@
+-- Interline settlement: payable and commission postings name the partner
+-- carrier they are settled with
+ALTER TABLE order_ledger ADD COLUMN IF NOT EXISTS counterparty_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_ledger_counterparty ON order_ledger(counterparty_id, created_at) WHERE counterparty_id IS NOT NULL;
@@ -0,0 +1,30 @@
+-- Gapless per-airline document numbering (invoices, credit notes, settlement
+-- statements). A sequence row is locked by the allocating transaction until it
+-- commits, so a rolled-back document never burns a number.
+CREATE TABLE IF NOT EXISTS document_sequences (
+    airline_id UUID NOT NULL REFERENCES airlines(id),
+    document_type VARCHAR(20) NOT NULL,
+    fiscal_year INTEGER NOT NULL,
+    last_value BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (airline_id, document_type, fiscal_year)
+);
+
+CREATE TABLE IF NOT EXISTS accounting_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    airline_id UUID NOT NULL REFERENCES airlines(id),
+    document_type VARCHAR(20) NOT NULL CHECK (document_type IN ('INVOICE', 'CREDIT_NOTE', 'SETTLEMENT')),
+    fiscal_year INTEGER NOT NULL,
+    sequence_no BIGINT NOT NULL,
+    document_number VARCHAR(50) NOT NULL UNIQUE,
+    order_id UUID REFERENCES orders(id),
+    reference_id UUID,
+    amount_nuc BIGINT NOT NULL DEFAULT 0,
+    currency VARCHAR(10) NOT NULL DEFAULT 'NUC',
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (airline_id, document_type, fiscal_year, sequence_no)
+);
+
+CREATE INDEX IF NOT EXISTS idx_accounting_documents_order ON accounting_documents(order_id);
+
+ALTER TABLE settlement_batches ADD COLUMN IF NOT EXISTS document_number VARCHAR(50);
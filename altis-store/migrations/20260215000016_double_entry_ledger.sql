@@ -0,0 +1,41 @@
+-- Double-entry journal. Every transaction posts balanced debits and credits;
+-- order_ledger remains the per-item detail feed for settlement exports.
+CREATE TABLE IF NOT EXISTS journal_transactions (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    order_item_id UUID,
+    kind VARCHAR(30) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS journal_postings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES journal_transactions(id),
+    account VARCHAR(30) NOT NULL CHECK (account IN ('CUSTOMER_RECEIVABLE', 'UNEARNED_REVENUE', 'EARNED_REVENUE', 'PSP_CLEARING', 'CARRIER_PAYABLE')),
+    debit_nuc BIGINT NOT NULL DEFAULT 0 CHECK (debit_nuc >= 0),
+    credit_nuc BIGINT NOT NULL DEFAULT 0 CHECK (credit_nuc >= 0),
+    counterparty_id UUID,
+    CHECK ((debit_nuc > 0) <> (credit_nuc > 0))
+);
+
+CREATE INDEX IF NOT EXISTS idx_journal_transactions_order ON journal_transactions(order_id);
+CREATE INDEX IF NOT EXISTS idx_journal_transactions_created ON journal_transactions(created_at);
+CREATE INDEX IF NOT EXISTS idx_journal_postings_transaction ON journal_postings(transaction_id);
+
+-- Checked at commit, once all postings of the transaction are written
+CREATE OR REPLACE FUNCTION check_journal_balanced() RETURNS TRIGGER AS $$
+BEGIN
+    IF (SELECT COALESCE(SUM(debit_nuc), 0) <> COALESCE(SUM(credit_nuc), 0)
+        FROM journal_postings WHERE transaction_id = COALESCE(NEW.transaction_id, OLD.transaction_id)) THEN
+        RAISE EXCEPTION 'journal transaction % is unbalanced', COALESCE(NEW.transaction_id, OLD.transaction_id);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS journal_postings_balanced ON journal_postings;
+CREATE CONSTRAINT TRIGGER journal_postings_balanced
+    AFTER INSERT OR UPDATE OR DELETE ON journal_postings
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION check_journal_balanced();
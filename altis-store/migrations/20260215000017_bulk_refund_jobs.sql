@@ -0,0 +1,27 @@
+-- Bulk cancel-and-refund jobs (e.g. a discontinued route). Each job snapshots
+-- the affected orders up front so progress and failures can be tracked per order.
+CREATE TABLE IF NOT EXISTS bulk_refund_jobs (
+    id UUID PRIMARY KEY,
+    flight_id VARCHAR(100) NOT NULL,
+    departure_from TIMESTAMPTZ NOT NULL,
+    departure_to TIMESTAMPTZ NOT NULL,
+    reason TEXT NOT NULL,
+    status VARCHAR(30) NOT NULL DEFAULT 'RUNNING',  -- RUNNING, COMPLETED, COMPLETED_WITH_FAILURES
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS bulk_refund_items (
+    job_id UUID NOT NULL REFERENCES bulk_refund_jobs(id),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING',  -- PENDING, PROCESSING, REFUNDED, CANCELLED (unpaid), SKIPPED, FAILED
+    attempts INT NOT NULL DEFAULT 0,
+    refunded_nuc BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (job_id, order_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bulk_refund_jobs_status ON bulk_refund_jobs(status);
+CREATE INDEX IF NOT EXISTS idx_bulk_refund_items_pending ON bulk_refund_items(job_id) WHERE status = 'PENDING';
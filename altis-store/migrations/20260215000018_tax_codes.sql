@@ -0,0 +1,53 @@
+-- Jurisdiction-based taxes. Codes match on product type and the route's
+-- origin/destination country; airports map IATA codes to countries.
+CREATE TABLE IF NOT EXISTS airports (
+    iata_code VARCHAR(3) PRIMARY KEY,
+    name VARCHAR(255),
+    country VARCHAR(2) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tax_codes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code VARCHAR(20) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    jurisdiction VARCHAR(2) NOT NULL,
+    product_type VARCHAR(50),          -- NULL applies to every product type
+    origin_country VARCHAR(2),         -- NULL matches any origin
+    destination_country VARCHAR(2),    -- NULL matches any destination
+    rate NUMERIC(6, 4),                -- fraction of the price (0.0700 = 7%)
+    amount_nuc INTEGER,                -- fixed amount per unit
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (rate IS NOT NULL OR amount_nuc IS NOT NULL)
+);
+
+-- Tax charged on top of the item price, with the itemized breakdown
+ALTER TABLE offer_items ADD COLUMN IF NOT EXISTS tax_nuc INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE offer_items ADD COLUMN IF NOT EXISTS taxes JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS tax_nuc INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS taxes JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE order_ledger ADD COLUMN IF NOT EXISTS tax_nuc INTEGER NOT NULL DEFAULT 0;
+
+-- Collected taxes are owed to the jurisdiction, not earned
+ALTER TABLE journal_postings DROP CONSTRAINT IF EXISTS journal_postings_account_check;
+ALTER TABLE journal_postings ADD CONSTRAINT journal_postings_account_check
+    CHECK (account IN ('CUSTOMER_RECEIVABLE', 'UNEARNED_REVENUE', 'EARNED_REVENUE', 'PSP_CLEARING', 'CARRIER_PAYABLE', 'TAX_PAYABLE'));
+
+INSERT INTO airports (iata_code, name, country) VALUES
+('SIN', 'Singapore Changi', 'SG'),
+('BKK', 'Bangkok Suvarnabhumi', 'TH'),
+('KUL', 'Kuala Lumpur International', 'MY'),
+('CGK', 'Jakarta Soekarno-Hatta', 'ID'),
+('MNL', 'Manila Ninoy Aquino', 'PH'),
+('SGN', 'Ho Chi Minh City Tan Son Nhat', 'VN'),
+('LHR', 'London Heathrow', 'GB'),
+('JFK', 'New York John F. Kennedy', 'US'),
+('LAX', 'Los Angeles International', 'US')
+ON CONFLICT (iata_code) DO NOTHING;
+
+INSERT INTO tax_codes (code, name, jurisdiction, product_type, origin_country, destination_country, rate, amount_nuc) VALUES
+('SG-PSC', 'Singapore Passenger Service and Security Charge', 'SG', 'FLIGHT', 'SG', NULL, NULL, 6570),
+('GB-APD', 'UK Air Passenger Duty', 'GB', 'FLIGHT', 'GB', NULL, NULL, 1300),
+('US-SEG', 'US Flight Segment Tax', 'US', 'FLIGHT', 'US', 'US', NULL, 520),
+('US-TRANS', 'US Transportation Tax', 'US', 'FLIGHT', 'US', 'US', 0.0750, NULL)
+ON CONFLICT (code) DO NOTHING;
@@ -0,0 +1,10 @@
+-- Rendered customer documents. The PDF lives in the blob store; the invoice
+-- breakdown is kept here so the JSON view always matches what was sent.
+CREATE TABLE IF NOT EXISTS document_files (
+    document_id UUID PRIMARY KEY REFERENCES accounting_documents(id),
+    blob_key VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL DEFAULT 'application/pdf',
+    sha256 VARCHAR(64) NOT NULL,
+    breakdown JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,13 @@
+-- Offer ranking experiments. While running, traffic_percentage of subjects
+-- (hashed by customer or session id) are ranked by the named strategy.
+CREATE TABLE IF NOT EXISTS ranking_experiments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL UNIQUE,
+    strategy VARCHAR(50) NOT NULL,
+    traffic_percentage NUMERIC(5, 4) NOT NULL CHECK (traffic_percentage >= 0 AND traffic_percentage <= 1),
+    starts_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ends_at TIMESTAMPTZ,               -- NULL runs until ended
+    created_by VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at IS NULL OR ends_at >= starts_at)
+);
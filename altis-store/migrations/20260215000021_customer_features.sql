@@ -0,0 +1,16 @@
+-- Per-customer purchase history features used to personalize offer ranking.
+-- Recomputed from paid orders by the API's refresh worker; a missing row
+-- means the customer has no purchases in the lookback window.
+CREATE TABLE IF NOT EXISTS customer_features (
+    customer_id VARCHAR(255) PRIMARY KEY,
+    order_count INTEGER NOT NULL,
+    preferred_cabin VARCHAR(50),                   -- most frequent cabin_class on flight items
+    ancillary_attach_rate NUMERIC(5, 4) NOT NULL,  -- share of orders with at least one non-flight item
+    price_sensitivity NUMERIC(5, 4) NOT NULL,      -- share of flights bought at or below the median fare
+    avg_order_value_nuc INTEGER NOT NULL,
+    last_order_at TIMESTAMPTZ NOT NULL,
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The refresh worker only revisits customers whose orders changed since its last run
+CREATE INDEX IF NOT EXISTS idx_orders_updated ON orders(updated_at);
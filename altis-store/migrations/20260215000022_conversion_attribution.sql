@@ -0,0 +1,28 @@
+-- Campaign attribution without raw identifiers. Customers and orders are
+-- stored only as keyed hashes (HMAC-SHA256 with a server-side salt), which
+-- join touches to conversions but can't be reversed from the tables alone.
+
+-- One row per search arriving with campaign (utm_*) parameters
+CREATE TABLE IF NOT EXISTS attribution_touches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    subject_hash CHAR(64) NOT NULL,
+    utm_source VARCHAR(255) NOT NULL DEFAULT '',
+    utm_medium VARCHAR(255) NOT NULL DEFAULT '',
+    utm_campaign VARCHAR(255) NOT NULL DEFAULT '',
+    utm_term VARCHAR(255) NOT NULL DEFAULT '',
+    utm_content VARCHAR(255) NOT NULL DEFAULT '',
+    touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_attribution_touches_subject ON attribution_touches(subject_hash, touched_at);
+CREATE INDEX IF NOT EXISTS idx_attribution_touches_time ON attribution_touches(touched_at);
+
+-- One row per paid order
+CREATE TABLE IF NOT EXISTS attribution_conversions (
+    order_hash CHAR(64) PRIMARY KEY,
+    subject_hash CHAR(64) NOT NULL,
+    value_nuc INTEGER NOT NULL,
+    converted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_attribution_conversions_time ON attribution_conversions(converted_at);
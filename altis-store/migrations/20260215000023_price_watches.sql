@@ -0,0 +1,23 @@
+-- Fare alerts: customers watch a route over a departure window and are
+-- notified once the cheapest offer drops to their target price.
+CREATE TABLE IF NOT EXISTS price_watches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    customer_id VARCHAR(255) NOT NULL,
+    customer_email VARCHAR(255),
+    origin VARCHAR(3) NOT NULL,
+    destination VARCHAR(3) NOT NULL,
+    depart_from DATE NOT NULL,
+    depart_to DATE NOT NULL,
+    passengers INTEGER NOT NULL DEFAULT 1 CHECK (passengers > 0),
+    target_price_nuc INTEGER NOT NULL CHECK (target_price_nuc > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE',  -- ACTIVE, TRIGGERED, EXPIRED, CANCELLED
+    lowest_price_nuc INTEGER,                      -- cheapest offer at the last check
+    lowest_price_date DATE,
+    last_checked_at TIMESTAMPTZ,
+    triggered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (depart_to >= depart_from)
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_watches_customer ON price_watches(customer_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_price_watches_due ON price_watches(last_checked_at NULLS FIRST) WHERE status = 'ACTIVE';
@@ -0,0 +1,21 @@
+-- Outbound webhook delivery attempts, kept so partners can inspect what we
+-- sent and what their endpoint answered. Each redelivery is its own row.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    partner VARCHAR(255) NOT NULL,              -- auth.api_keys name
+    event_id UUID NOT NULL,                     -- stable across redeliveries
+    event_type VARCHAR(100) NOT NULL,
+    is_test BOOLEAN NOT NULL DEFAULT FALSE,
+    redelivery_of UUID REFERENCES webhook_deliveries(id),
+    url TEXT NOT NULL,
+    request_headers JSONB NOT NULL,
+    request_body TEXT NOT NULL,
+    response_status INTEGER,                    -- NULL when no response arrived
+    response_headers JSONB,
+    response_body TEXT,                         -- truncated to webhooks.max_captured_body_bytes
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_partner ON webhook_deliveries(partner, created_at DESC);
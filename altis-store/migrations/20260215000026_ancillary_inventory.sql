@@ -0,0 +1,5 @@
+-- Capacity for ancillaries (lounge passes, meals, extra-legroom seats). Each
+-- active product of the rule's resource type is tracked with this many units
+-- unless its metadata carries its own "capacity"; NULL leaves the type
+-- unlimited. Flights keep taking their seat count from the product.
+ALTER TABLE inventory_rules ADD COLUMN IF NOT EXISTS capacity INTEGER CHECK (capacity >= 0);
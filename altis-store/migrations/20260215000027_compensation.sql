@@ -0,0 +1,41 @@
+-- Passenger rights compensation (EU261, US DOT). Schemes measure routes by
+-- great-circle distance, so airports get coordinates.
+ALTER TABLE airports ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION;
+ALTER TABLE airports ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION;
+
+INSERT INTO airports (iata_code, name, country, latitude, longitude) VALUES
+('SIN', 'Singapore Changi', 'SG', 1.3644, 103.9915),
+('BKK', 'Bangkok Suvarnabhumi', 'TH', 13.6900, 100.7501),
+('KUL', 'Kuala Lumpur International', 'MY', 2.7456, 101.7099),
+('CGK', 'Jakarta Soekarno-Hatta', 'ID', -6.1256, 106.6559),
+('MNL', 'Manila Ninoy Aquino', 'PH', 14.5086, 121.0194),
+('SGN', 'Ho Chi Minh City Tan Son Nhat', 'VN', 10.8188, 106.6520),
+('LHR', 'London Heathrow', 'GB', 51.4700, -0.4543),
+('JFK', 'New York John F. Kennedy', 'US', 40.6413, -73.7781),
+('LAX', 'Los Angeles International', 'US', 33.9416, -118.4085),
+('CDG', 'Paris Charles de Gaulle', 'FR', 49.0097, 2.5479),
+('FRA', 'Frankfurt', 'DE', 50.0379, 8.5622),
+('AMS', 'Amsterdam Schiphol', 'NL', 52.3105, 4.7683)
+ON CONFLICT (iata_code) DO UPDATE SET latitude = EXCLUDED.latitude, longitude = EXCLUDED.longitude;
+
+-- Compensation is an expense; vouchers stay a liability until redeemed
+ALTER TABLE journal_postings DROP CONSTRAINT IF EXISTS journal_postings_account_check;
+ALTER TABLE journal_postings ADD CONSTRAINT journal_postings_account_check
+    CHECK (account IN ('CUSTOMER_RECEIVABLE', 'UNEARNED_REVENUE', 'EARNED_REVENUE', 'PSP_CLEARING', 'CARRIER_PAYABLE', 'TAX_PAYABLE',
+                       'COMPENSATION_EXPENSE', 'VOUCHER_LIABILITY'));
+
+-- One payout per booking and scheme, however often it's requested
+CREATE TABLE IF NOT EXISTS compensation_payouts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    order_item_id UUID NOT NULL REFERENCES order_items(id),
+    scheme VARCHAR(10) NOT NULL CHECK (scheme IN ('EU261', 'US_DOT')),
+    amount_nuc INTEGER NOT NULL CHECK (amount_nuc > 0),
+    payout_method VARCHAR(10) NOT NULL CHECK (payout_method IN ('CASH', 'VOUCHER')),
+    voucher_code VARCHAR(20) UNIQUE,
+    status VARCHAR(20) NOT NULL DEFAULT 'ISSUED',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (order_item_id, scheme)
+);
+
+CREATE INDEX IF NOT EXISTS idx_compensation_payouts_order ON compensation_payouts(order_id);
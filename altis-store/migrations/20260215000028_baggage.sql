@@ -0,0 +1,38 @@
+-- Checked bag tracking. Tags are IATA license plates built from the airline's
+-- three-digit accounting code and a per-airline serial.
+ALTER TABLE airlines ADD COLUMN IF NOT EXISTS accounting_code VARCHAR(3);
+UPDATE airlines SET accounting_code = '999' WHERE code = 'AL' AND accounting_code IS NULL;
+
+CREATE TABLE IF NOT EXISTS bag_tag_sequences (
+    airline_id UUID PRIMARY KEY REFERENCES airlines(id),
+    last_value BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One row per physical bag; a BAG item of quantity 2 gets two tags. Serials
+-- wrap, so a tag number is only unique among recent bags.
+CREATE TABLE IF NOT EXISTS bag_tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tag_number VARCHAR(10) NOT NULL,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    order_item_id UUID NOT NULL REFERENCES order_items(id),
+    airline_id UUID NOT NULL REFERENCES airlines(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_bag_tags_number ON bag_tags(tag_number, created_at);
+CREATE INDEX IF NOT EXISTS idx_bag_tags_order ON bag_tags(order_id);
+CREATE INDEX IF NOT EXISTS idx_bag_tags_item ON bag_tags(order_item_id);
+
+-- Custody trail, oldest first
+CREATE TABLE IF NOT EXISTS bag_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    bag_tag_id UUID NOT NULL REFERENCES bag_tags(id),
+    event_type VARCHAR(20) NOT NULL CHECK (event_type IN ('CHECKED_IN', 'LOADED', 'ARRIVED')),
+    station VARCHAR(3),
+    flight_id UUID,
+    scanned_by VARCHAR(255),
+    scanned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_bag_events_tag ON bag_events(bag_tag_id, scanned_at);
@@ -0,0 +1,22 @@
+-- One row per flight status change applied by the disruption engine, for the
+-- ops dashboard. Orders link back through their FLIGHT_DISRUPTION change and
+-- proposals through metadata->>'disruption_id'.
+CREATE TABLE IF NOT EXISTS disruptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    flight_id UUID NOT NULL,
+    airline_id UUID REFERENCES airlines(id),
+    new_status VARCHAR(20) NOT NULL,
+    delay_minutes INTEGER,
+    cause VARCHAR(50),
+    reaccommodate BOOLEAN NOT NULL,
+    source VARCHAR(50) NOT NULL,       -- ADMIN, FLIGHT_STATUS
+    reason TEXT,
+    affected_orders INTEGER NOT NULL DEFAULT 0,
+    proposals_made INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_disruptions_created ON disruptions(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_disruptions_flight ON disruptions(flight_id);
+CREATE INDEX IF NOT EXISTS idx_order_items_disruption ON order_items((metadata->>'disruption_id'));
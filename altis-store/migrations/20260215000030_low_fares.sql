@@ -0,0 +1,17 @@
+-- "From" prices for the marketing site: the cheapest offer per route and
+-- departure month, recomputed in the background for route-months the site
+-- has asked for recently.
+CREATE TABLE IF NOT EXISTS low_fares (
+    origin VARCHAR(3) NOT NULL,
+    destination VARCHAR(3) NOT NULL,
+    month DATE NOT NULL,                 -- first day of the departure month
+    lowest_price_nuc INTEGER,            -- NULL until priced, or when nothing in the month is sellable
+    lowest_price_date DATE,
+    priced_at TIMESTAMPTZ,
+    checked_at TIMESTAMPTZ,              -- claimed by the aggregation job
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (origin, destination, month),
+    CHECK (EXTRACT(DAY FROM month) = 1)
+);
+
+CREATE INDEX IF NOT EXISTS idx_low_fares_due ON low_fares(checked_at NULLS FIRST);
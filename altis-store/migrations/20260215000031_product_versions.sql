@@ -0,0 +1,28 @@
+-- Product version history. Every change to a product's sellable terms is a
+-- new version with the window it's in force; `products` holds the version in
+-- force now. Versions can be scheduled ahead, and offers and orders record
+-- the version they were priced from (item metadata.product_version).
+ALTER TABLE products ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE IF NOT EXISTS product_versions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    base_price_nuc INTEGER NOT NULL,
+    metadata JSONB,
+    is_active BOOLEAN,
+    effective_from TIMESTAMPTZ NOT NULL,
+    effective_to TIMESTAMPTZ,             -- the next version's effective_from; NULL while open-ended
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(product_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_versions_effective ON product_versions(product_id, effective_from);
+
+-- Existing products start at version 1, in force since they were created
+INSERT INTO product_versions (product_id, version, name, description, base_price_nuc, metadata, is_active, effective_from)
+SELECT id, version, name, description, base_price_nuc, metadata, is_active, COALESCE(created_at, NOW())
+FROM products
+ON CONFLICT (product_id, version) DO NOTHING;
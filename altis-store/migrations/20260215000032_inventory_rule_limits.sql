@@ -0,0 +1,4 @@
+-- Inventory rules beyond hold duration: how many unpaid orders a customer may
+-- hold at once with the resource, and departure dates it can't be held for.
+ALTER TABLE inventory_rules ADD COLUMN IF NOT EXISTS max_holds_per_customer INTEGER CHECK (max_holds_per_customer > 0);  -- NULL: no limit
+ALTER TABLE inventory_rules ADD COLUMN IF NOT EXISTS blackout_dates DATE[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,3 @@
+-- Deleting a product retires it instead: orders and offers keep pointing at
+-- it for reporting and reshop. Rows are only removed when nothing references them.
+ALTER TABLE products ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
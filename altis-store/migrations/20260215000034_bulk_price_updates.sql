@@ -0,0 +1,12 @@
+-- Applied bulk price updates. Each keeps every product's old and new price,
+-- so the update can be rolled back with its id as the undo token.
+CREATE TABLE IF NOT EXISTS bulk_price_updates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    airline_id UUID NOT NULL REFERENCES airlines(id),
+    request JSONB NOT NULL,           -- filters and adjustment as submitted
+    changes JSONB NOT NULL,           -- [{product_id, old_price_nuc, new_price_nuc}]
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rolled_back_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_bulk_price_updates_airline ON bulk_price_updates(airline_id, applied_at DESC);
@@ -0,0 +1,22 @@
+-- Scheduled sales, replacing the global business_rules.sale_start/sale_end
+-- settings. A campaign takes a percentage off the products it covers while
+-- ACTIVE; the scheduler moves it SCHEDULED -> ACTIVE -> ENDED on its dates.
+CREATE TABLE IF NOT EXISTS campaigns (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    airline_id UUID NOT NULL REFERENCES airlines(id),
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    discount_percentage NUMERIC(5,2) NOT NULL CHECK (discount_percentage > 0 AND discount_percentage <= 100),
+    product_types TEXT[] NOT NULL DEFAULT '{}',   -- empty: every product type
+    origin VARCHAR(3),                            -- NULL: any airport
+    destination VARCHAR(3),
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    banner JSONB NOT NULL DEFAULT '{}',           -- storefront promo copy and artwork
+    status VARCHAR(20) NOT NULL DEFAULT 'SCHEDULED', -- SCHEDULED, ACTIVE, ENDED, CANCELLED
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_campaigns_airline_status ON campaigns(airline_id, status);
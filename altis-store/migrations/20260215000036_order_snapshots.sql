@@ -0,0 +1,24 @@
+-- Frozen copy of what an order was sold on: the accepted offer(s), the
+-- pricing inputs (product versions, pricing rules, campaigns) and the
+-- business rules in force at acceptance. Offers expire from the cache and
+-- catalog rows change, so disputes are settled from this instead.
+CREATE TABLE IF NOT EXISTS order_snapshots (
+    order_id UUID PRIMARY KEY REFERENCES orders(id),
+    offers JSONB NOT NULL,
+    pricing JSONB NOT NULL,
+    business_rules JSONB NOT NULL,
+    sha256 VARCHAR(64) NOT NULL,          -- over offers, pricing and business_rules as first written
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Snapshots are written once and never touched again
+CREATE OR REPLACE FUNCTION reject_order_snapshot_change() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'order snapshot % is immutable', OLD.order_id;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS order_snapshots_immutable ON order_snapshots;
+CREATE TRIGGER order_snapshots_immutable
+    BEFORE UPDATE OR DELETE ON order_snapshots
+    FOR EACH ROW EXECUTE FUNCTION reject_order_snapshot_change();
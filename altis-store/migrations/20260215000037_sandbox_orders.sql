@@ -0,0 +1,5 @@
+-- Orders placed with sandbox API keys. They pay through the mock adapter
+-- and are left out of settlement, ledger and interline reports.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS test BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_orders_test ON orders(test) WHERE test;
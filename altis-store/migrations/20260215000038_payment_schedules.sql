@@ -0,0 +1,31 @@
+-- Installment plans: an order's total paid in several charges against a
+-- payment method kept on file. The order is HOLD_PARTIALLY_PAID until the
+-- last one succeeds; an installment that keeps failing defaults the plan and
+-- cancels the order.
+CREATE TABLE IF NOT EXISTS payment_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    payment_reference VARCHAR(255),               -- token charged for each installment
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE', -- ACTIVE, COMPLETED, DEFAULTED, CANCELLED
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- A plan whose deposit was declined is CANCELLED and may be taken out again
+CREATE UNIQUE INDEX IF NOT EXISTS idx_payment_schedules_order ON payment_schedules(order_id) WHERE status <> 'CANCELLED';
+
+CREATE TABLE IF NOT EXISTS payment_installments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    schedule_id UUID NOT NULL REFERENCES payment_schedules(id) ON DELETE CASCADE,
+    sequence INTEGER NOT NULL CHECK (sequence > 0),
+    due_at TIMESTAMPTZ NOT NULL,
+    amount_nuc INTEGER NOT NULL CHECK (amount_nuc > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING', -- PENDING, PAID, FAILED, CANCELLED
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL,          -- due_at, then pushed back by each retry
+    last_error TEXT,
+    paid_at TIMESTAMPTZ,
+    UNIQUE (schedule_id, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_installments_due ON payment_installments(next_attempt_at) WHERE status = 'PENDING';
@@ -0,0 +1,22 @@
+-- Payment method vault: cards customers saved for later checkouts. Only the
+-- provider's token and display details are kept, never a card number.
+CREATE TABLE IF NOT EXISTS payment_methods (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    customer_id VARCHAR(255) NOT NULL,              -- token subject; the full DID for One ID holders
+    provider VARCHAR(50) NOT NULL,                  -- the PSP whose token this is
+    provider_token VARCHAR(255) NOT NULL,
+    brand VARCHAR(50),
+    last4 VARCHAR(4),
+    exp_month SMALLINT,
+    exp_year SMALLINT,
+    fingerprint VARCHAR(255),                       -- same card, same fingerprint
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    test BOOLEAN NOT NULL DEFAULT FALSE,            -- saved with a sandbox key
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE',   -- ACTIVE, REMOVED
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    removed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_methods_customer ON payment_methods(customer_id, created_at) WHERE status = 'ACTIVE';
+CREATE UNIQUE INDEX IF NOT EXISTS idx_payment_methods_fingerprint
+    ON payment_methods(customer_id, provider, test, fingerprint) WHERE status = 'ACTIVE';
@@ -0,0 +1,8 @@
+-- Payments waiting on the customer, e.g. a 3-D Secure challenge. The order
+-- sits in PAYMENT_PENDING until the intent is confirmed or the deadline
+-- passes and the timeout worker releases it.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS payment_intent_id VARCHAR(255);
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS payment_action_expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_orders_payment_action_expiry ON orders(payment_action_expires_at)
+    WHERE payment_action_expires_at IS NOT NULL;
@@ -0,0 +1,29 @@
+-- Chargebacks raised by cardholders, as reported by the payment provider.
+-- A lost dispute books the amount taken back as a chargeback loss.
+ALTER TABLE journal_postings DROP CONSTRAINT IF EXISTS journal_postings_account_check;
+ALTER TABLE journal_postings ADD CONSTRAINT journal_postings_account_check
+    CHECK (account IN ('CUSTOMER_RECEIVABLE', 'UNEARNED_REVENUE', 'EARNED_REVENUE', 'PSP_CLEARING', 'CARRIER_PAYABLE', 'TAX_PAYABLE',
+                       'COMPENSATION_EXPENSE', 'VOUCHER_LIABILITY', 'CHARGEBACK_LOSS'));
+
+CREATE TABLE IF NOT EXISTS payment_disputes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    provider VARCHAR(50) NOT NULL,
+    provider_dispute_id VARCHAR(255) NOT NULL,
+    payment_intent_id VARCHAR(255) NOT NULL,       -- the disputed payment
+    amount_nuc INTEGER NOT NULL CHECK (amount_nuc > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'NUC',
+    reason VARCHAR(100),                           -- network reason, e.g. fraudulent, product_not_received
+    status VARCHAR(20) NOT NULL DEFAULT 'OPEN'
+        CHECK (status IN ('OPEN', 'EVIDENCE_SUBMITTED', 'WON', 'LOST')),
+    evidence_due_by TIMESTAMPTZ,
+    evidence_submitted_at TIMESTAMPTZ,
+    closed_at TIMESTAMPTZ,
+    note TEXT,                                     -- last status change note from finance
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (provider, provider_dispute_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_disputes_status ON payment_disputes(status, evidence_due_by);
+CREATE INDEX IF NOT EXISTS idx_payment_disputes_order ON payment_disputes(order_id);
@@ -0,0 +1,11 @@
+-- Localized names, descriptions and media for catalog products, so
+-- white-label partners can show offers in the customer's language
+CREATE TABLE IF NOT EXISTS product_content (
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    locale VARCHAR(10) NOT NULL,                   -- BCP 47, e.g. fr, pt-BR
+    name VARCHAR(255),                             -- NULL keeps the product's own
+    description TEXT,
+    media JSONB NOT NULL DEFAULT '[]',             -- [{url, kind, alt}]
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (product_id, locale)
+);
@@ -0,0 +1,29 @@
+-- Daily retail stats per airline, rolled up from offers and orders by a
+-- background job. Product type ALL is every offer or order; the other
+-- rows only count those that included that product type.
+
+-- Offers generated each day and how far they got: an order was created
+-- from them (accepted), and that order was paid
+CREATE TABLE IF NOT EXISTS analytics_daily_funnel (
+    airline_id UUID NOT NULL REFERENCES airlines(id) ON DELETE CASCADE,
+    day DATE NOT NULL,                             -- UTC day the offer was generated
+    product_type VARCHAR(50) NOT NULL,
+    offers_generated INTEGER NOT NULL,
+    offers_accepted INTEGER NOT NULL,
+    orders_paid INTEGER NOT NULL,
+    revenue_nuc BIGINT NOT NULL,                   -- totals of the paid orders
+    rolled_up_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (airline_id, day, product_type)
+);
+
+-- Paid orders each day, and those carrying each product type
+CREATE TABLE IF NOT EXISTS analytics_daily_attach (
+    airline_id UUID NOT NULL REFERENCES airlines(id) ON DELETE CASCADE,
+    day DATE NOT NULL,                             -- UTC day the order was created
+    product_type VARCHAR(50) NOT NULL,
+    orders INTEGER NOT NULL,
+    items INTEGER NOT NULL,
+    revenue_nuc BIGINT NOT NULL,                   -- what those items sold for
+    rolled_up_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (airline_id, day, product_type)
+);
@@ -0,0 +1,31 @@
+-- Customer profiles: default contact details and marketing consent, plus
+-- travelers saved for later bookings so their details aren't retyped.
+CREATE TABLE IF NOT EXISTS customer_profiles (
+    customer_id VARCHAR(255) PRIMARY KEY,           -- token subject; the full DID for One ID holders
+    email VARCHAR(255),
+    phone VARCHAR(50),
+    first_name VARCHAR(255),
+    last_name VARCHAR(255),
+    marketing_opt_in BOOLEAN NOT NULL DEFAULT FALSE,
+    marketing_opt_in_at TIMESTAMPTZ,                -- when consent was last given; kept as the consent record
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS saved_travelers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    customer_id VARCHAR(255) NOT NULL,
+    ptc VARCHAR(10) NOT NULL DEFAULT 'ADT',
+    first_name VARCHAR(255) NOT NULL,
+    last_name VARCHAR(255) NOT NULL,
+    date_of_birth DATE,
+    gender VARCHAR(20),
+    document_type VARCHAR(20),                      -- PASSPORT, ID_CARD
+    document_number VARCHAR(50),
+    document_expiry DATE,
+    nationality VARCHAR(3),                         -- ISO 3166 country code
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_travelers_customer ON saved_travelers(customer_id, created_at);
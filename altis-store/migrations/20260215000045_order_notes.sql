@@ -0,0 +1,30 @@
+-- Order notes: what contact-center agents record on an order, either for
+-- colleagues (INTERNAL) or to be shown to the customer (CUSTOMER).
+CREATE TABLE IF NOT EXISTS order_notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    author VARCHAR(255) NOT NULL,                   -- back-office token subject
+    author_role VARCHAR(50) NOT NULL,
+    visibility VARCHAR(20) NOT NULL DEFAULT 'INTERNAL', -- INTERNAL, CUSTOMER
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_notes_order ON order_notes(order_id, created_at);
+
+-- Charges waived for the customer, each recorded with the note explaining it
+-- and the ledger adjustment that credited the amount (none for a rule waived
+-- without money involved).
+CREATE TABLE IF NOT EXISTS order_waivers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    note_id UUID NOT NULL UNIQUE REFERENCES order_notes(id),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    order_item_id UUID NOT NULL REFERENCES order_items(id),
+    kind VARCHAR(30) NOT NULL,                      -- CHANGE_FEE, CANCELLATION_FEE, NAME_CHANGE_FEE, BAGGAGE_FEE, FARE_DIFFERENCE, OTHER
+    amount_nuc INTEGER NOT NULL DEFAULT 0,
+    reason TEXT NOT NULL,
+    ledger_entry_id UUID REFERENCES order_ledger(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_waivers_order ON order_waivers(order_id);
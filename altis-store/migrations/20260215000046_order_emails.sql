@@ -0,0 +1,18 @@
+-- Emails sent to the customer about an order, one row per kind of email.
+-- Redelivered events find the row and don't send twice; failed sends keep
+-- the provider's error for support to follow up.
+CREATE TABLE IF NOT EXISTS order_emails (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    kind VARCHAR(30) NOT NULL,                      -- ITINERARY
+    recipient VARCHAR(255),                         -- none when the order has no email address
+    status VARCHAR(20) NOT NULL,                    -- SENT, FAILED, SKIPPED
+    provider VARCHAR(20) NOT NULL,                  -- log, smtp, ses
+    provider_message_id TEXT,
+    error TEXT,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (order_id, kind)
+);
@@ -0,0 +1,22 @@
+-- Where each customer wants trip alerts (EMAIL, SMS, WHATSAPP); NULL keeps
+-- the configured defaults.
+ALTER TABLE customer_profiles ADD COLUMN IF NOT EXISTS alert_channels TEXT[];
+
+-- Trip alerts sent about an order, one row per alert and channel, so an
+-- alert is sent once even when its trigger repeats. `alert_key` names the
+-- alert, e.g. CHECK_IN:<order item> or DISRUPTION:<disruption>.
+CREATE TABLE IF NOT EXISTS order_messages (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id),
+    alert_key VARCHAR(100) NOT NULL,
+    channel VARCHAR(20) NOT NULL,                   -- EMAIL, SMS, WHATSAPP
+    recipient VARCHAR(255),                         -- E.164 number; none for email and skipped alerts
+    status VARCHAR(20) NOT NULL DEFAULT 'SENDING',  -- SENDING, SENT, FAILED, SKIPPED
+    provider_message_id TEXT,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (order_id, alert_key, channel)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_messages_key ON order_messages(alert_key);
@@ -0,0 +1,17 @@
+-- Ancillaries offered on paid orders, each at a price held until expires_at.
+-- Accepting claims the row first (OPEN -> ACCEPTING), so a double click
+-- can't charge the customer twice.
+CREATE TABLE IF NOT EXISTS upsell_offers (
+    id UUID PRIMARY KEY,                            -- offer item id the customer accepts
+    order_id UUID NOT NULL REFERENCES orders(id),
+    rank INTEGER NOT NULL,                          -- 1 is the best bet
+    reason VARCHAR(20) NOT NULL,                    -- RULE, HISTORY, AVAILABLE
+    item JSONB NOT NULL,                            -- the priced offer item, taxes included
+    status VARCHAR(20) NOT NULL DEFAULT 'OPEN',     -- OPEN, ACCEPTING, ACCEPTED
+    order_item_id UUID,                             -- set once accepted
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_upsell_offers_order ON upsell_offers(order_id, expires_at);
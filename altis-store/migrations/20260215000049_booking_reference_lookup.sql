@@ -0,0 +1,3 @@
+-- Existing bookings are found by booking reference, the first six characters
+-- of the order id, to sell ancillaries on their own.
+CREATE INDEX IF NOT EXISTS idx_orders_booking_reference ON orders (upper(left(id::text, 6)));
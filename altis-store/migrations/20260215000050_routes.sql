@@ -0,0 +1,29 @@
+-- Routes (origin and destination pairs) each airline sells, with the market
+-- data pricing and taxes read. Whether a route is domestic follows from its
+-- airports' countries, so it isn't stored.
+ALTER TABLE airports ADD COLUMN IF NOT EXISTS city_code VARCHAR(3);   -- IATA city, e.g. LON for LHR
+
+UPDATE airports SET city_code = v.city_code
+FROM (VALUES ('SIN', 'SIN'), ('BKK', 'BKK'), ('KUL', 'KUL'), ('CGK', 'JKT'), ('MNL', 'MNL'), ('SGN', 'SGN'),
+             ('LHR', 'LON'), ('JFK', 'NYC'), ('LAX', 'LAX'), ('CDG', 'PAR'), ('FRA', 'FRA'), ('AMS', 'AMS'))
+     AS v(iata_code, city_code)
+WHERE airports.iata_code = v.iata_code AND airports.city_code IS NULL;
+
+CREATE TABLE IF NOT EXISTS routes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    airline_id UUID NOT NULL REFERENCES airlines(id),
+    origin VARCHAR(3) NOT NULL REFERENCES airports(iata_code),
+    destination VARCHAR(3) NOT NULL REFERENCES airports(iata_code),
+    distance_km INTEGER NOT NULL CHECK (distance_km > 0),
+    sale_currency VARCHAR(3) NOT NULL,   -- ISO 4217 currency fares are sold in
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (origin <> destination)
+);
+
+-- One active route per airport pair; deactivated ones are kept for history
+CREATE UNIQUE INDEX IF NOT EXISTS idx_routes_active_pair ON routes(airline_id, origin, destination) WHERE is_active;
+
+-- Taxes levied only on domestic or only on international routes
+ALTER TABLE tax_codes ADD COLUMN IF NOT EXISTS market VARCHAR(13) CHECK (market IN ('DOMESTIC', 'INTERNATIONAL'));
@@ -0,0 +1,24 @@
+-- Customers queued for a sold-out flight, first come first served. When
+-- seats come back the entry at the front is OFFERED: its seats are held and
+-- an offer is priced for it, both until offer_expires_at. An offer that
+-- isn't accepted by then EXPIRES and the next customer gets their turn.
+CREATE TABLE IF NOT EXISTS waitlist_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    flight_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    airline_id UUID REFERENCES airlines(id),
+    customer_id VARCHAR(255) NOT NULL,
+    customer_email VARCHAR(255),
+    seats INTEGER NOT NULL DEFAULT 1 CHECK (seats > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'WAITING',  -- WAITING, OFFERED, BOOKED, EXPIRED, CANCELLED
+    offer_id UUID,                                  -- set once OFFERED
+    offered_at TIMESTAMPTZ,
+    offer_expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One place in line per customer and flight
+CREATE UNIQUE INDEX IF NOT EXISTS idx_waitlist_open_entry ON waitlist_entries(flight_id, customer_id) WHERE status IN ('WAITING', 'OFFERED');
+CREATE INDEX IF NOT EXISTS idx_waitlist_queue ON waitlist_entries(flight_id, created_at) WHERE status = 'WAITING';
+CREATE INDEX IF NOT EXISTS idx_waitlist_offers_due ON waitlist_entries(offer_expires_at) WHERE status = 'OFFERED';
+CREATE INDEX IF NOT EXISTS idx_waitlist_airline ON waitlist_entries(airline_id, status);
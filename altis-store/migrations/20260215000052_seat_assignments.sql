@@ -0,0 +1,5 @@
+-- Seat numbers on SEAT items, whether chosen or assigned automatically.
+-- One active item per seat, so two assignments can't take the same seat.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_order_items_seat
+    ON order_items ((metadata->>'flight_id'), (metadata->>'seat_number'))
+    WHERE product_type = 'SEAT' AND status = 'ACTIVE' AND metadata ? 'seat_number';
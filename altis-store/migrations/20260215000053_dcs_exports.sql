@@ -0,0 +1,5 @@
+-- Checked-in flight items waiting to be sent to departure control
+CREATE INDEX IF NOT EXISTS idx_order_items_dcs_due
+    ON order_items ((metadata->>'checked_in_at'))
+    WHERE product_type = 'FLIGHT' AND status = 'ACTIVE'
+      AND metadata ? 'checked_in_at' AND NOT metadata ? 'dcs_exported_at';
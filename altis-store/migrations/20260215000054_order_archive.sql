@@ -0,0 +1,36 @@
+-- Closed orders past retention leave the live tables for cold storage (a
+-- JSON document in the blob store). What stays behind is enough to find
+-- them again by booking reference and passenger name, and to restore them.
+CREATE TABLE IF NOT EXISTS archived_orders (
+    order_id UUID PRIMARY KEY,
+    airline_id UUID,
+    customer_id VARCHAR(255),
+    status VARCHAR(50) NOT NULL,
+    booking_reference VARCHAR(6) NOT NULL,
+    contact_last_name VARCHAR(255),
+    traveler_last_names TEXT[] NOT NULL DEFAULT '{}',
+    total_nuc INTEGER,
+    currency VARCHAR(3),
+    ordered_at TIMESTAMPTZ,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    blob_key TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_archived_orders_reference ON archived_orders(booking_reference);
+CREATE INDEX IF NOT EXISTS idx_archived_orders_airline ON archived_orders(airline_id, archived_at);
+
+-- Finance records outlive the order rows: ledger entries, journals,
+-- accounting documents, payouts, refunds and chargebacks keep their
+-- order_id, no longer as a foreign key, so closed orders can be archived
+-- without taking the books with them.
+ALTER TABLE order_ledger DROP CONSTRAINT IF EXISTS order_ledger_order_id_fkey;
+ALTER TABLE order_ledger DROP CONSTRAINT IF EXISTS order_ledger_order_item_id_fkey;
+ALTER TABLE journal_transactions DROP CONSTRAINT IF EXISTS journal_transactions_order_id_fkey;
+ALTER TABLE accounting_documents DROP CONSTRAINT IF EXISTS accounting_documents_order_id_fkey;
+ALTER TABLE compensation_payouts DROP CONSTRAINT IF EXISTS compensation_payouts_order_id_fkey;
+ALTER TABLE compensation_payouts DROP CONSTRAINT IF EXISTS compensation_payouts_order_item_id_fkey;
+ALTER TABLE bulk_refund_items DROP CONSTRAINT IF EXISTS bulk_refund_items_order_id_fkey;
+ALTER TABLE payment_disputes DROP CONSTRAINT IF EXISTS payment_disputes_order_id_fkey;
+
+-- Archiving candidates: closed orders by age
+CREATE INDEX IF NOT EXISTS idx_orders_closed ON orders(updated_at) WHERE status IN ('FULFILLED', 'CANCELLED');
@@ -0,0 +1,72 @@
+-- Fulfillment per traveler: each passenger gets their own barcode for each
+-- flight segment, and ancillaries bought for one traveler are theirs.
+-- Records with no traveler cover the whole booking.
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS traveler_id UUID REFERENCES travelers(id) ON DELETE SET NULL;
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS traveler_index INTEGER;
+-- Flight records: the segment the barcode boards
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS segment JSONB;
+
+UPDATE fulfillment f
+SET segment = jsonb_strip_nulls(jsonb_build_object(
+        'flight_id', i.metadata->'flight_id',
+        'flight_number', i.metadata->'flight_number',
+        'origin', i.metadata->'origin',
+        'destination', i.metadata->'destination',
+        'departure_time', i.metadata->'departure_time'))
+FROM order_items i
+WHERE i.id = f.order_item_id AND i.product_type = 'FLIGHT' AND f.segment IS NULL;
+
+-- Ancillaries already tied to a traveler
+UPDATE fulfillment f
+SET traveler_index = (i.metadata->>'traveler_index')::int,
+    traveler_id = (SELECT t.id FROM travelers t WHERE t.order_id = f.order_id AND t.traveler_index = (i.metadata->>'traveler_index')::int)
+FROM order_items i
+WHERE i.id = f.order_item_id AND i.product_type <> 'FLIGHT'
+  AND jsonb_typeof(i.metadata->'traveler_index') = 'number' AND f.traveler_index IS NULL;
+
+-- Flights issued one barcode for the whole booking: every traveler after
+-- the first gets a copy of the record under their own barcode (the old
+-- one suffixed with their index), or one per seat booked before names
+INSERT INTO fulfillment (
+    order_id, order_item_id, fulfillment_type, barcode, delivery_method, delivered_at, created_at,
+    consumed_at, consumption_location, deliver_at, delivery_attempts, last_delivery_error,
+    traveler_id, traveler_index, segment
+)
+SELECT f.order_id, f.order_item_id, f.fulfillment_type, f.barcode || '-T' || t.traveler_index, f.delivery_method, f.delivered_at, f.created_at,
+       f.consumed_at, f.consumption_location, f.deliver_at, f.delivery_attempts, f.last_delivery_error,
+       t.id, t.traveler_index, f.segment
+FROM fulfillment f
+JOIN order_items i ON i.id = f.order_item_id AND i.product_type = 'FLIGHT'
+JOIN travelers t ON t.order_id = f.order_id
+WHERE f.voided_at IS NULL AND f.traveler_index IS NULL
+  AND t.traveler_index > (SELECT min(traveler_index) FROM travelers WHERE order_id = f.order_id);
+
+INSERT INTO fulfillment (
+    order_id, order_item_id, fulfillment_type, barcode, delivery_method, delivered_at, created_at,
+    consumed_at, consumption_location, deliver_at, delivery_attempts, last_delivery_error,
+    traveler_index, segment
+)
+SELECT f.order_id, f.order_item_id, f.fulfillment_type, f.barcode || '-T' || seat, f.delivery_method, f.delivered_at, f.created_at,
+       f.consumed_at, f.consumption_location, f.deliver_at, f.delivery_attempts, f.last_delivery_error,
+       seat, f.segment
+FROM fulfillment f
+JOIN order_items i ON i.id = f.order_item_id AND i.product_type = 'FLIGHT'
+CROSS JOIN generate_series(1, GREATEST(COALESCE(i.quantity, 1), 1) - 1) seat
+WHERE f.voided_at IS NULL AND f.traveler_index IS NULL
+  AND NOT EXISTS (SELECT 1 FROM travelers t WHERE t.order_id = f.order_id);
+
+-- The original record, and its barcode, stay with the first traveler
+UPDATE fulfillment f
+SET traveler_index = COALESCE(t.traveler_index, 0), traveler_id = t.id
+FROM order_items i
+LEFT JOIN LATERAL (
+    SELECT id, traveler_index FROM travelers WHERE order_id = i.order_id ORDER BY traveler_index LIMIT 1
+) t ON true
+WHERE i.id = f.order_item_id AND i.product_type = 'FLIGHT'
+  AND f.voided_at IS NULL AND f.traveler_index IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_fulfillment_traveler ON fulfillment(traveler_id);
+-- One live record per traveler on an item
+CREATE UNIQUE INDEX IF NOT EXISTS idx_fulfillment_item_traveler
+    ON fulfillment(order_item_id, traveler_index)
+    WHERE voided_at IS NULL AND traveler_index IS NOT NULL;
@@ -0,0 +1,3 @@
+-- The scanner credential that consumed a barcode (the token subject of the
+-- gate or lounge device, or the staff member)
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS consumed_by TEXT;
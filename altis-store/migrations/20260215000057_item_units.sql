@@ -0,0 +1,36 @@
+-- Quantity is first-class: price_nuc is per unit, units can be refunded
+-- on their own, and every unit gets its own fulfillment record
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS refunded_quantity INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS unit_index INTEGER NOT NULL DEFAULT 0;
+
+-- A flight's units are its travelers' seats, in traveler order
+UPDATE fulfillment f
+SET unit_index = n.unit_index
+FROM (
+    SELECT id, (row_number() OVER (PARTITION BY order_item_id ORDER BY traveler_index, created_at) - 1)::int AS unit_index
+    FROM fulfillment
+    WHERE voided_at IS NULL
+) n
+JOIN order_items i ON i.product_type = 'FLIGHT'
+WHERE n.id = f.id AND i.id = f.order_item_id;
+
+-- Ancillaries bought several at a time had one record for all of them:
+-- it stays unit 0, and each further unit gets its own under the old
+-- barcode suffixed with the unit
+INSERT INTO fulfillment (
+    order_id, order_item_id, fulfillment_type, barcode, delivery_method, delivered_at, created_at,
+    deliver_at, delivery_attempts, last_delivery_error, traveler_id, traveler_index, segment, unit_index
+)
+SELECT f.order_id, f.order_item_id, f.fulfillment_type, f.barcode || '-U' || unit, f.delivery_method, f.delivered_at, f.created_at,
+       f.deliver_at, f.delivery_attempts, f.last_delivery_error, f.traveler_id, f.traveler_index, f.segment, unit
+FROM fulfillment f
+JOIN order_items i ON i.id = f.order_item_id AND i.product_type <> 'FLIGHT' AND UPPER(i.status) = 'ACTIVE'
+CROSS JOIN generate_series(1, GREATEST(COALESCE(i.quantity, 1), 1) - 1) unit
+WHERE f.voided_at IS NULL
+  AND NOT EXISTS (SELECT 1 FROM fulfillment o WHERE o.order_item_id = f.order_item_id AND o.unit_index > 0);
+
+-- Several units may now belong to one traveler; units are what's unique
+DROP INDEX IF EXISTS idx_fulfillment_item_traveler;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_fulfillment_item_unit
+    ON fulfillment(order_item_id, unit_index)
+    WHERE voided_at IS NULL;
@@ -0,0 +1,17 @@
+-- When an item's revenue is earned without a scan (insurance at cover
+-- start, ancillaries at departure); NULL when only consumption earns it
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS recognize_at TIMESTAMPTZ;
+
+-- Paid ancillaries still unearned, on the defaults of their product type
+UPDATE order_items i
+SET recognize_at = COALESCE(
+        CASE WHEN UPPER(i.product_type) = 'INSURANCE' THEN (i.metadata->>'coverage_start')::timestamptz END,
+        (i.metadata->>'departure_time')::timestamptz,
+        (SELECT min((f.metadata->>'departure_time')::timestamptz) FROM order_items f
+         WHERE f.order_id = i.order_id AND f.product_type = 'FLIGHT'))
+FROM orders o
+WHERE o.id = i.order_id AND o.status IN ('PAID', 'FULFILLED')
+  AND i.product_type <> 'FLIGHT' AND UPPER(i.status) = 'ACTIVE' AND UPPER(i.revenue_status) = 'UNEARNED'
+  AND i.metadata->'recognition_policy' IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_order_items_recognize_at ON order_items(recognize_at) WHERE recognize_at IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Units still unused when their service is over: no longer scannable, and
+-- their revenue kept as breakage or refunded as the airline chooses
+ALTER TABLE fulfillment ADD COLUMN IF NOT EXISTS expired_at TIMESTAMPTZ;
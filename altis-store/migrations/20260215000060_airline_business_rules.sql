@@ -0,0 +1,5 @@
+-- Airline-scoped values of the [business_rules] settings: one active CONFIG
+-- rule per airline, whose rule_config holds only the settings it overrides
+CREATE UNIQUE INDEX IF NOT EXISTS idx_business_rules_airline_config
+    ON business_rules(airline_id)
+    WHERE rule_type = 'CONFIG' AND is_active;
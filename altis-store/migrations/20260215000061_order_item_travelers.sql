@@ -0,0 +1,13 @@
+-- The traveler an item is for (a bag, a seat, a lounge pass); both NULL
+-- when it covers the whole booking. The index is set from the start, the
+-- id once a traveler with that index is on the order.
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS traveler_index INTEGER;
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS traveler_id UUID REFERENCES travelers(id) ON DELETE SET NULL;
+
+-- Items tied to a traveler so far only said so in their metadata
+UPDATE order_items i
+SET traveler_index = (i.metadata->>'traveler_index')::int,
+    traveler_id = (SELECT t.id FROM travelers t WHERE t.order_id = i.order_id AND t.traveler_index = (i.metadata->>'traveler_index')::int)
+WHERE jsonb_typeof(i.metadata->'traveler_index') = 'number' AND i.traveler_index IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_order_items_traveler ON order_items(traveler_id) WHERE traveler_id IS NOT NULL;
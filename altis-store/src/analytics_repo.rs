@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::AnalyticsRepository;
+
+use crate::DbClient;
+
+/// Serializes rollups, so two instances never replace the same days at once
+const ROLLUP_LOCK: i64 = 0xa7a1_7c00;
+
+pub struct StoreAnalyticsRepository {
+    db: DbClient,
+}
+
+impl StoreAnalyticsRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AnalyticsRepository for StoreAnalyticsRepository {
+    async fn latest_rollup_day(&self) -> Result<Option<NaiveDate>, Box<dyn std::error::Error + Send + Sync>> {
+        let day = sqlx::query_scalar::<_, Option<NaiveDate>>("SELECT MAX(day) FROM analytics_daily_funnel")
+            .fetch_one(self.db.reader())
+            .await?;
+        Ok(day)
+    }
+
+    async fn roll_up_daily_stats(&self, since: NaiveDate) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(ROLLUP_LOCK)
+            .execute(&mut *tx)
+            .await?;
+
+        for table in ["analytics_daily_funnel", "analytics_daily_attach"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE day >= $1", table))
+                .bind(since)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // An offer counts as accepted once any order was made from it and as
+        // paid once one of those orders took a payment; sandbox orders don't count
+        sqlx::query(
+            r#"
+            WITH cohort AS (
+                SELECT f.id, f.airline_id, (f.created_at AT TIME ZONE 'UTC')::date AS day,
+                       EXISTS (SELECT 1 FROM orders o WHERE o.offer_id = f.id AND NOT o.test) AS accepted,
+                       (SELECT MAX(o.total_nuc) FROM orders o
+                        WHERE o.offer_id = f.id AND NOT o.test
+                          AND EXISTS (SELECT 1 FROM order_changes c WHERE c.order_id = o.id AND c.change_type = 'PAYMENT_RECEIVED')) AS paid_nuc
+                FROM offers f
+                WHERE f.airline_id IS NOT NULL AND f.created_at >= ($1::date)::timestamp AT TIME ZONE 'UTC'
+            ),
+            typed AS (
+                SELECT c.*, 'ALL' AS product_type FROM cohort c
+                UNION ALL
+                SELECT c.*, t.product_type FROM cohort c
+                JOIN LATERAL (SELECT DISTINCT UPPER(i.product_type) AS product_type FROM offer_items i WHERE i.offer_id = c.id) t ON TRUE
+            )
+            INSERT INTO analytics_daily_funnel (airline_id, day, product_type, offers_generated, offers_accepted, orders_paid, revenue_nuc)
+            SELECT airline_id, day, product_type,
+                   COUNT(*), COUNT(*) FILTER (WHERE accepted), COUNT(paid_nuc), COALESCE(SUM(paid_nuc), 0)
+            FROM typed
+            GROUP BY airline_id, day, product_type
+            "#,
+        )
+        .bind(since)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            WITH paid AS (
+                SELECT o.id, o.airline_id, o.total_nuc, (o.created_at AT TIME ZONE 'UTC')::date AS day
+                FROM orders o
+                WHERE o.airline_id IS NOT NULL AND NOT o.test
+                  AND o.created_at >= ($1::date)::timestamp AT TIME ZONE 'UTC'
+                  AND EXISTS (SELECT 1 FROM order_changes c WHERE c.order_id = o.id AND c.change_type = 'PAYMENT_RECEIVED')
+            )
+            INSERT INTO analytics_daily_attach (airline_id, day, product_type, orders, items, revenue_nuc)
+            SELECT p.airline_id, p.day, 'ALL', COUNT(*),
+                   COALESCE(SUM((SELECT COUNT(*) FROM order_items i WHERE i.order_id = p.id)), 0), COALESCE(SUM(p.total_nuc), 0)
+            FROM paid p
+            GROUP BY p.airline_id, p.day
+            UNION ALL
+            SELECT p.airline_id, p.day, UPPER(i.product_type), COUNT(DISTINCT p.id), SUM(COALESCE(i.quantity, 1)), SUM(i.price_nuc * COALESCE(i.quantity, 1))
+            FROM paid p JOIN order_items i ON i.order_id = p.id
+            GROUP BY p.airline_id, p.day, UPPER(i.product_type)
+            "#,
+        )
+        .bind(since)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn daily_funnel(
+        &self,
+        airline_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+        product_type: &str,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT jsonb_build_object(
+                'day', day, 'offers_generated', offers_generated, 'offers_accepted', offers_accepted,
+                'orders_paid', orders_paid, 'revenue_nuc', revenue_nuc)
+            FROM analytics_daily_funnel
+            WHERE airline_id = $1 AND day BETWEEN $2 AND $3 AND product_type = $4
+            ORDER BY day
+            "#,
+        )
+        .bind(airline_id)
+        .bind(from)
+        .bind(to)
+        .bind(product_type)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(rows)
+    }
+
+    async fn attach_totals(
+        &self,
+        airline_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT jsonb_build_object(
+                'product_type', product_type, 'orders', SUM(orders),
+                'items', SUM(items), 'revenue_nuc', SUM(revenue_nuc))
+            FROM analytics_daily_attach
+            WHERE airline_id = $1 AND day BETWEEN $2 AND $3
+            GROUP BY product_type
+            ORDER BY product_type
+            "#,
+        )
+        .bind(airline_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(rows)
+    }
+}
@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -10,6 +11,961 @@ pub struct Config {
     pub auth: AuthConfig,
     pub business_rules: BusinessRules,
     pub ranking: RankingConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub fulfillment: FulfillmentConfig,
+    #[serde(default)]
+    pub settlement: SettlementConfig,
+    #[serde(default)]
+    pub blob: BlobConfig,
+    #[serde(default)]
+    pub pii: PiiConfig,
+    #[serde(default)]
+    pub documents: DocumentsConfig,
+    #[serde(default)]
+    pub refunds: RefundsConfig,
+    #[serde(default)]
+    pub chaos: ChaosConfig,
+    #[serde(default)]
+    pub warmup: WarmupConfig,
+    #[serde(default)]
+    pub payment: PaymentConfig,
+    #[serde(default)]
+    pub personalization: PersonalizationConfig,
+    #[serde(default)]
+    pub attribution: AttributionConfig,
+    #[serde(default)]
+    pub price_watch: PriceWatchConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub cart: CartConfig,
+    #[serde(default)]
+    pub deadlines: DeadlinesConfig,
+    #[serde(default)]
+    pub edifact: EdifactConfig,
+    /// External ancillary suppliers, keyed by the name products give in `metadata.supplier`
+    #[serde(default)]
+    pub suppliers: HashMap<String, SupplierConfig>,
+    #[serde(default)]
+    pub interline: InterlineConfig,
+    #[serde(default)]
+    pub flight_status: FlightStatusConfig,
+    #[serde(default)]
+    pub catalog_events: CatalogEventsConfig,
+    #[serde(default)]
+    pub reaccommodation: ReaccommodationConfig,
+    #[serde(default)]
+    pub compensation: CompensationConfig,
+    #[serde(default)]
+    pub search_admission: SearchAdmissionConfig,
+    #[serde(default)]
+    pub low_fares: LowFaresConfig,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub sse: SseConfig,
+    #[serde(default)]
+    pub installments: InstallmentsConfig,
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+    #[serde(default)]
+    pub email: EmailConfig,
+    #[serde(default)]
+    pub messaging: MessagingConfig,
+    #[serde(default)]
+    pub upsell: UpsellConfig,
+    #[serde(default)]
+    pub markets: MarketsConfig,
+    #[serde(default)]
+    pub schedule_changes: ScheduleChangeConfig,
+    #[serde(default)]
+    pub waitlist: WaitlistConfig,
+    #[serde(default)]
+    pub seating: SeatingConfig,
+    #[serde(default)]
+    pub dcs: DcsConfig,
+    #[serde(default)]
+    pub archival: ArchivalConfig,
+    #[serde(default)]
+    pub scanning: ScanningConfig,
+    #[serde(default)]
+    pub revenue_recognition: RevenueRecognitionConfig,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// How long identical searches are served from cache (0 disables caching)
+    pub cache_ttl_seconds: u64,
+    /// How long products, tax codes and pricing rules are kept in memory
+    pub catalog_ttl_seconds: u64,
+    /// Time a search may spend before it settles for baseline offers
+    pub budget_ms: u64,
+    /// Loading the airline, its products and tax codes; past this the last
+    /// catalog held in memory is used, however old
+    pub catalog_budget_ms: u64,
+    /// Campaigns, ancillary availability and pricing; past this only the
+    /// flights are offered, at full fare
+    pub generation_budget_ms: u64,
+    /// Scoring by the assigned ranking strategy; past this rule scores are used
+    pub ranking_budget_ms: u64,
+    /// Most passengers one search may price for; larger parties book as a group
+    pub max_passengers: u32,
+    /// How far ahead a departure may be searched, as far as schedules are loaded
+    pub max_days_ahead: u32,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_seconds: 60,
+            catalog_ttl_seconds: 300,
+            budget_ms: 2000,
+            catalog_budget_ms: 800,
+            generation_budget_ms: 800,
+            ranking_budget_ms: 300,
+            max_passengers: 9,
+            max_days_ahead: 361,
+        }
+    }
+}
+
+/// gRPC API for internal clients, served next to the REST one
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct GrpcConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self { enabled: false, port: 50052 }
+    }
+}
+
+/// Customer emails, such as the itinerary sent once an order is paid
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct EmailConfig {
+    /// Consume `order.paid` and send itinerary confirmations
+    pub consume: bool,
+    pub consumer_group: String,
+    /// One of `EMAIL_PROVIDERS`
+    pub provider: String,
+    /// Sender address, optionally with a display name
+    pub from: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    /// SMTP credentials; for SES, the SMTP credentials made for the IAM user
+    pub username: String,
+    pub password: String,
+    /// Region of the SES SMTP endpoint
+    pub ses_region: String,
+}
+
+/// `log` only writes messages to the log, for development
+pub const EMAIL_PROVIDERS: &[&str] = &["log", "smtp", "ses"];
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            consume: true,
+            consumer_group: "altis-emails".to_string(),
+            provider: "log".to_string(),
+            from: "Altis <no-reply@altis.example>".to_string(),
+            smtp_host: "localhost".to_string(),
+            smtp_port: 587,
+            username: String::new(),
+            password: String::new(),
+            ses_region: "us-east-1".to_string(),
+        }
+    }
+}
+
+/// Trip alerts by SMS and WhatsApp, for disruptions and check-in opening
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct MessagingConfig {
+    /// One of `MESSAGING_PROVIDERS`
+    pub provider: String,
+    pub api_base_url: String,
+    pub account_sid: String,
+    pub auth_token: String,
+    /// E.164 number or alphanumeric sender id that SMS come from
+    pub sms_from: String,
+    /// WhatsApp business number; none turns the channel off
+    pub whatsapp_from: Option<String>,
+    pub timeout_ms: u64,
+    /// Channels for customers without a preference of their own, guests included
+    pub default_channels: Vec<String>,
+    /// How long before departure the check-in alert goes out
+    pub check_in_opens_hours: i64,
+    pub check_in_poll_seconds: u64,
+    pub check_in_batch_size: i64,
+}
+
+/// `log` only writes messages to the log, for development
+pub const MESSAGING_PROVIDERS: &[&str] = &["log", "twilio"];
+
+/// Values `default_channels` and customers' preferences take
+pub const ALERT_CHANNELS: &[&str] = &["EMAIL", "SMS", "WHATSAPP"];
+
+impl Default for MessagingConfig {
+    fn default() -> Self {
+        Self {
+            provider: "log".to_string(),
+            api_base_url: "https://api.twilio.com/2010-04-01".to_string(),
+            account_sid: String::new(),
+            auth_token: String::new(),
+            sms_from: "ALTIS".to_string(),
+            whatsapp_from: None,
+            timeout_ms: 5000,
+            default_channels: vec!["EMAIL".to_string(), "SMS".to_string()],
+            check_in_opens_hours: 24,
+            check_in_poll_seconds: 300,
+            check_in_batch_size: 100,
+        }
+    }
+}
+
+/// Ancillaries offered on paid orders (`GET /v1/orders/:id/upsell`)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct UpsellConfig {
+    /// How long an upsell price holds once shown
+    pub price_valid_minutes: i64,
+    /// Offers shown per order, one per product type
+    pub max_offers: usize,
+}
+
+impl Default for UpsellConfig {
+    fn default() -> Self {
+        Self { price_valid_minutes: 30, max_offers: 3 }
+    }
+}
+
+/// Fare multipliers by the market of the airline's route (see `/v1/admin/airlines/:id/routes`)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct MarketsConfig {
+    pub domestic_multiplier: f64,
+    pub international_multiplier: f64,
+}
+
+impl Default for MarketsConfig {
+    fn default() -> Self {
+        Self { domestic_multiplier: 1.0, international_multiplier: 1.0 }
+    }
+}
+
+/// What happens to bookings when an admin retimes a flight
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScheduleChangeConfig {
+    /// Passengers are told about moves of more than this
+    pub notify_minutes: i64,
+    /// Moves of more than this need the passenger to accept them
+    pub major_minutes: i64,
+}
+
+impl Default for ScheduleChangeConfig {
+    fn default() -> Self {
+        Self { notify_minutes: 30, major_minutes: 180 }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct FulfillmentConfig {
+    /// How often the delivery worker looks for due scheduled deliveries
+    pub delivery_poll_seconds: u64,
+    pub delivery_batch_size: i64,
+    /// Deliveries still failing after this many tries are left for manual follow-up
+    pub delivery_max_attempts: i32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SettlementConfig {
+    /// Hour (UTC) of the nightly run that sweeps the previous days' ledger into batches
+    pub batch_hour_utc: u32,
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self { batch_hour_utc: 2 }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DocumentsConfig {
+    /// Month (1-12) the fiscal year starts; document sequences restart then
+    pub fiscal_year_start_month: u32,
+    /// Document number prefix per airline code; defaults to the airline code
+    #[serde(default)]
+    pub prefixes: HashMap<String, String>,
+}
+
+impl Default for DocumentsConfig {
+    fn default() -> Self {
+        Self { fiscal_year_start_month: 1, prefixes: HashMap::new() }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RefundsConfig {
+    /// Orders refunded per batch by bulk cancel-and-refund jobs
+    pub bulk_batch_size: i64,
+    /// Pause between batches, keeping bulk jobs within the PSP's rate limits
+    pub bulk_batch_interval_ms: u64,
+    /// Orders still failing after this many tries go on the job's failure list
+    pub bulk_max_attempts: i32,
+}
+
+impl Default for RefundsConfig {
+    fn default() -> Self {
+        Self { bulk_batch_size: 50, bulk_batch_interval_ms: 1000, bulk_max_attempts: 3 }
+    }
+}
+
+/// Fault injection for resiliency testing. Enable it per environment (e.g. in
+/// `config/staging.toml`); never in production.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ChaosConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Cache warming run at startup before the instance reports ready
+#[derive(Debug, Deserialize, Clone)]
+pub struct WarmupConfig {
+    /// Give up waiting and report ready after this long; whatever wasn't
+    /// warmed is loaded on first use as before
+    pub timeout_seconds: u64,
+    /// Flights departing within this window get their seat counters seeded
+    pub flight_lookahead_hours: i64,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self { timeout_seconds: 60, flight_lookahead_hours: 72 }
+    }
+}
+
+/// Customer purchase-history features used by the ranker
+#[derive(Debug, Deserialize, Clone)]
+pub struct PersonalizationConfig {
+    /// How often features are recomputed for customers with changed orders
+    pub refresh_interval_seconds: u64,
+    /// Only purchases this recent count
+    pub lookback_days: i32,
+}
+
+impl Default for PersonalizationConfig {
+    fn default() -> Self {
+        Self { refresh_interval_seconds: 900, lookback_days: 365 }
+    }
+}
+
+/// Campaign attribution of paid orders
+#[derive(Debug, Deserialize, Clone)]
+pub struct AttributionConfig {
+    /// Key for hashing customer and order ids. Changing it breaks the link
+    /// between touches and conversions recorded before and after.
+    pub salt: String,
+    /// A conversion is credited to the latest campaign touch this recent
+    pub lookback_days: i32,
+}
+
+impl Default for AttributionConfig {
+    fn default() -> Self {
+        Self { salt: String::new(), lookback_days: 30 }
+    }
+}
+
+/// Fare alerts on customer price watches
+#[derive(Debug, Deserialize, Clone)]
+pub struct PriceWatchConfig {
+    /// How often the worker looks for watches due a re-price
+    pub poll_seconds: u64,
+    /// A watch is re-priced at most this often
+    pub recheck_seconds: i64,
+    pub batch_size: i64,
+    /// Widest departure window a watch may cover; each day is priced separately
+    pub max_window_days: i64,
+}
+
+impl Default for PriceWatchConfig {
+    fn default() -> Self {
+        Self { poll_seconds: 300, recheck_seconds: 3600, batch_size: 20, max_window_days: 14 }
+    }
+}
+
+/// Waitlists on sold-out flights
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct WaitlistConfig {
+    /// How often the worker looks for returned seats and lapsed offers
+    pub poll_seconds: u64,
+    /// How long the customer at the front of the line has to take the seats offered
+    pub priority_minutes: i64,
+    /// Flights checked per pass
+    pub batch_size: i64,
+    /// Most seats one customer can wait for
+    pub max_seats: i32,
+}
+
+impl Default for WaitlistConfig {
+    fn default() -> Self {
+        Self { poll_seconds: 30, priority_minutes: 30, batch_size: 100, max_seats: 9 }
+    }
+}
+
+/// Seats given to passengers who didn't choose one
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SeatingConfig {
+    /// Seats are assigned this long before departure, if not already at check-in
+    pub assign_hours_before: i64,
+    pub poll_seconds: u64,
+    /// Bookings seated per pass
+    pub batch_size: i64,
+}
+
+impl Default for SeatingConfig {
+    fn default() -> Self {
+        Self { assign_hours_before: 24, poll_seconds: 300, batch_size: 100 }
+    }
+}
+
+/// Service delivery exchanged with airport departure control
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct DcsConfig {
+    /// Send checked-in passengers to `dcs.service.delivery`
+    pub export: bool,
+    pub export_poll_seconds: u64,
+    pub export_batch_size: i64,
+    /// Take delivery confirmations from `dcs.service.delivered`
+    pub consume: bool,
+    pub consumer_group: String,
+}
+
+impl Default for DcsConfig {
+    fn default() -> Self {
+        Self {
+            export: true,
+            export_poll_seconds: 15,
+            export_batch_size: 100,
+            consume: true,
+            consumer_group: "altis-dcs".to_string(),
+        }
+    }
+}
+
+/// Closed orders moved to cold storage once past retention
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ArchivalConfig {
+    pub enabled: bool,
+    /// FULFILLED and CANCELLED orders untouched this long are archived
+    pub retention_days: i64,
+    pub poll_seconds: u64,
+    /// Orders archived per pass
+    pub batch_size: i64,
+}
+
+impl Default for ArchivalConfig {
+    fn default() -> Self {
+        Self { enabled: true, retention_days: 365, poll_seconds: 3600, batch_size: 100 }
+    }
+}
+
+/// Barcode scans at gates and lounges
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScanningConfig {
+    /// Scans one device (scanner token subject) may make per minute
+    pub scans_per_device_per_minute: i64,
+    /// Ed25519 key pair (PKCS#8 and SPKI PEM) signing the passes scanners
+    /// verify offline; without it no passes are issued
+    pub signing_key_pem: Option<String>,
+    pub verifying_key_pem: Option<String>,
+    /// `kid` of the passes, for scanners holding more than one key
+    pub key_id: String,
+    /// A flight's pass is valid from this long before departure...
+    pub pass_opens_hours: i64,
+    /// ...to this long after it
+    pub pass_closes_hours: i64,
+    /// Validity of passes for codes with no flight, from when they're fetched
+    pub undated_pass_hours: i64,
+    /// Offline scans reconciled in one request
+    pub max_batch_scans: usize,
+}
+
+impl Default for ScanningConfig {
+    fn default() -> Self {
+        Self {
+            scans_per_device_per_minute: 60,
+            signing_key_pem: None,
+            verifying_key_pem: None,
+            key_id: "scan-1".to_string(),
+            pass_opens_hours: 48,
+            pass_closes_hours: 6,
+            undated_pass_hours: 72,
+            max_batch_scans: 500,
+        }
+    }
+}
+
+/// Revenue earned on time rather than a scan: insurance at cover start,
+/// ancillaries at departure
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RevenueRecognitionConfig {
+    pub enabled: bool,
+    pub poll_seconds: u64,
+    /// Items recognized per pass
+    pub batch_size: i64,
+    /// What becomes of ancillary units unused at departure, for airlines
+    /// without a BREAKAGE business rule: one of `BREAKAGE_TREATMENTS`
+    pub breakage: String,
+}
+
+/// `RECOGNIZE` keeps unused units as breakage revenue; `REFUND` pays them back
+pub const BREAKAGE_TREATMENTS: &[&str] = &["RECOGNIZE", "REFUND"];
+
+impl Default for RevenueRecognitionConfig {
+    fn default() -> Self {
+        Self { enabled: true, poll_seconds: 300, batch_size: 100, breakage: "RECOGNIZE".to_string() }
+    }
+}
+
+/// Outbound webhooks to partners
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    pub timeout_ms: u64,
+    /// Partner response bodies are cut to this size in the delivery log
+    pub max_captured_body_bytes: usize,
+    /// Keyed by partner name, as in `auth.api_keys`
+    pub endpoints: HashMap<String, WebhookEndpoint>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// Signs each payload; shared with the partner out of band
+    pub secret: String,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self { timeout_ms: 5000, max_captured_body_bytes: 16 * 1024, endpoints: HashMap::new() }
+    }
+}
+
+/// Trip builder carts
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CartConfig {
+    /// Most offers one cart may draw items from
+    pub max_offers: usize,
+    /// Fraction off every item once a cart holds two or more offers
+    pub multi_offer_discount: f64,
+    /// Fraction off ancillaries in a cart that also holds a flight
+    pub ancillary_bundle_discount: f64,
+}
+
+impl Default for CartConfig {
+    fn default() -> Self {
+        Self { max_offers: 6, multi_offer_discount: 0.05, ancillary_bundle_discount: 0.10 }
+    }
+}
+
+/// EDIFACT availability (PAOREQ/PAORES) for GDS channels not yet on NDC
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct EdifactConfig {
+    /// Airline codes answered over EDIFACT; empty turns the interface off
+    pub airlines: Vec<String>,
+    /// Our identification in the UNB of replies
+    pub sender_id: String,
+    /// One of `EDIFACT_TRANSPORTS`
+    pub transport: String,
+    /// Where `http` transport posts each channel's replies, keyed by partner
+    /// name as in `auth.api_keys`
+    pub reply_urls: HashMap<String, String>,
+    pub timeout_ms: u64,
+}
+
+/// `inline` answers in the HTTP response; `http` acknowledges the request and
+/// posts the reply to the channel's `reply_urls` entry
+pub const EDIFACT_TRANSPORTS: &[&str] = &["inline", "http"];
+
+impl Default for EdifactConfig {
+    fn default() -> Self {
+        Self {
+            airlines: Vec::new(),
+            sender_id: "ALTIS".to_string(),
+            transport: "inline".to_string(),
+            reply_urls: HashMap::new(),
+            timeout_ms: 5000,
+        }
+    }
+}
+
+/// An external ancillary supplier reached over its HTTP API
+#[derive(Debug, Deserialize, Clone)]
+pub struct SupplierConfig {
+    pub base_url: String,
+    /// Sent as a bearer token
+    pub api_key: String,
+    #[serde(default = "default_supplier_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Consecutive failures that stop calls to the supplier for `breaker_reset_seconds`
+    #[serde(default = "default_supplier_breaker_threshold")]
+    pub breaker_threshold: usize,
+    #[serde(default = "default_supplier_breaker_reset_seconds")]
+    pub breaker_reset_seconds: u64,
+}
+
+fn default_supplier_timeout_ms() -> u64 { 2000 }
+fn default_supplier_breaker_threshold() -> usize { 5 }
+fn default_supplier_breaker_reset_seconds() -> u64 { 30 }
+
+/// Partner carriers whose flights we resell, shopped over their NDC APIs
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct InterlineConfig {
+    /// How long search waits for partners; late answers are left out
+    pub timeout_ms: u64,
+    pub partners: HashMap<String, InterlinePartnerConfig>,
+}
+
+impl Default for InterlineConfig {
+    fn default() -> Self {
+        Self { timeout_ms: 3000, partners: HashMap::new() }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct InterlinePartnerConfig {
+    /// IATA code of the carrier; only offers it owns are resold
+    pub carrier: String,
+    /// Base of the partner's NDC API (`/airshopping`, `/ordercreate`)
+    pub url: String,
+    /// Sent as a bearer token
+    pub api_key: String,
+}
+
+/// Disruption handling driven by flight status changes
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct FlightStatusConfig {
+    /// Consume `flight.status.changed`; off leaves disruptions to the admin API
+    pub consume: bool,
+    pub consumer_group: String,
+    /// Delays up to this long don't move passengers, unless the airline's
+    /// DISRUPTION business rule sets its own `reaccommodate_after_delay_minutes`
+    pub reaccommodate_after_delay_minutes: i64,
+    /// External status feed, polled and republished onto the topic
+    pub feed_url: Option<String>,
+    /// Sent as a bearer token
+    pub feed_api_key: String,
+    pub feed_poll_seconds: u64,
+}
+
+impl Default for FlightStatusConfig {
+    fn default() -> Self {
+        Self {
+            consume: true,
+            consumer_group: "altis-disruptions".to_string(),
+            reaccommodate_after_delay_minutes: 180,
+            feed_url: None,
+            feed_api_key: String::new(),
+            feed_poll_seconds: 60,
+        }
+    }
+}
+
+/// `catalog.changed`, which tells every instance to drop its in-memory catalog
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CatalogEventsConfig {
+    /// Listen for changes made through other instances; off leaves their
+    /// edits to show up here once `search.catalog_ttl_seconds` runs out
+    pub consume: bool,
+    /// Prefix of each instance's own consumer group, as every instance
+    /// needs every change
+    pub consumer_group: String,
+}
+
+impl Default for CatalogEventsConfig {
+    fn default() -> Self {
+        Self { consume: true, consumer_group: "altis-catalog-cache".to_string() }
+    }
+}
+
+/// Replacement flights proposed to passengers of a disrupted flight
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ReaccommodationConfig {
+    /// Alternatives proposed per disrupted booking
+    pub max_options: usize,
+    /// How long proposed seats stay held for the customer to choose
+    pub hold_minutes: i64,
+    /// Latest departure considered, counted from the disrupted departure
+    pub search_window_hours: i64,
+}
+
+impl Default for ReaccommodationConfig {
+    fn default() -> Self {
+        Self { max_options: 3, hold_minutes: 120, search_window_hours: 72 }
+    }
+}
+
+/// Passenger rights compensation for delayed and cancelled flights
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CompensationConfig {
+    /// NUC per euro, for schemes with amounts fixed in euros (EU261)
+    pub eur_to_nuc: f64,
+    /// Multiplier on the amount when the passenger takes a voucher instead of cash
+    pub voucher_uplift: f64,
+}
+
+impl Default for CompensationConfig {
+    fn default() -> Self {
+        Self { eur_to_nuc: 1.08, voucher_uplift: 1.2 }
+    }
+}
+
+/// Admission control for offer search bursts: each tenant (a partner, or all
+/// direct customers together) gets its own concurrency, and requests beyond
+/// it wait in a bounded queue rather than being turned away at once
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SearchAdmissionConfig {
+    pub enabled: bool,
+    /// Searches running at once, across tenants
+    pub max_concurrent: usize,
+    /// Searches one tenant may run at once
+    pub tenant_concurrency: usize,
+    /// Per-partner overrides of `tenant_concurrency`, keyed by partner name as
+    /// in `auth.api_keys`
+    pub partner_concurrency: HashMap<String, usize>,
+    /// Searches waiting for a slot, across tenants; beyond this they're shed
+    pub max_queued: usize,
+    /// Longest a search waits for a slot before it's shed
+    pub max_wait_ms: u64,
+    /// Sent as Retry-After with shed searches
+    pub retry_after_seconds: u64,
+}
+
+impl Default for SearchAdmissionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_concurrent: 64,
+            tenant_concurrency: 8,
+            partner_concurrency: HashMap::new(),
+            max_queued: 256,
+            max_wait_ms: 2000,
+            retry_after_seconds: 2,
+        }
+    }
+}
+
+/// "From" prices per route and month, aggregated in the background for the
+/// marketing site
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LowFaresConfig {
+    /// How often the aggregation job looks for stale route-months
+    pub poll_seconds: u64,
+    /// A cached price is recomputed once it's this old
+    pub refresh_seconds: i64,
+    /// Route-months priced per run; each remaining day of the month is priced separately
+    pub batch_size: i64,
+    /// Route-months nobody has asked for in this long stop being refreshed
+    pub retain_days: i64,
+    /// Most tuples a single batch request may ask for
+    pub max_batch: usize,
+    /// Furthest month ahead that can be asked for
+    pub max_months_ahead: u32,
+}
+
+impl Default for LowFaresConfig {
+    fn default() -> Self {
+        Self { poll_seconds: 60, refresh_seconds: 3600, batch_size: 10, retain_days: 7, max_batch: 100, max_months_ahead: 12 }
+    }
+}
+
+/// Per-request time budgets, propagated into Postgres, Redis and gRPC calls
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct DeadlinesConfig {
+    /// Budget for requests that don't send `X-Request-Timeout`
+    pub default_ms: u64,
+    /// Longest budget a client may ask for
+    pub max_ms: u64,
+}
+
+impl Default for DeadlinesConfig {
+    fn default() -> Self {
+        Self { default_ms: 10_000, max_ms: 30_000 }
+    }
+}
+
+/// The synthetic airline test API keys book against
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub airline_code: String,
+    pub airline_name: String,
+    /// Synthetic flights are laid out for this many days from the reset
+    pub days_ahead: i64,
+    pub seats_per_flight: i32,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            airline_code: "ZZ".to_string(),
+            airline_name: "Altis Sandbox".to_string(),
+            days_ahead: 14,
+            seats_per_flight: 180,
+        }
+    }
+}
+
+/// Live seat-hold streams (`GET /v1/flights/:id/stream`)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SseConfig {
+    /// Events a flight's channel buffers for its slowest subscriber
+    pub channel_capacity: usize,
+    /// Recent events kept per flight for clients resuming with `Last-Event-ID`
+    pub replay_events: usize,
+    /// A flight's channel is dropped once it has had no subscribers or events for this long
+    pub idle_seconds: u64,
+}
+
+impl Default for SseConfig {
+    fn default() -> Self {
+        Self { channel_capacity: 64, replay_events: 256, idle_seconds: 300 }
+    }
+}
+
+/// Installment plans (`POST /v1/orders/:id/installments`)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct InstallmentsConfig {
+    /// How often the worker looks for installments due a charge
+    pub poll_seconds: u64,
+    pub batch_size: i64,
+    pub max_installments: u32,
+    /// Default spacing between installments when the request gives none
+    pub interval_days: i64,
+    /// The last installment must fall due at least this long before the first departure
+    pub final_due_days: i64,
+    /// Charge attempts per installment before the plan defaults
+    pub max_attempts: i32,
+    pub retry_hours: i64,
+    /// Kept out of what a defaulting customer has paid
+    pub cancellation_fee_percent: u8,
+}
+
+impl Default for InstallmentsConfig {
+    fn default() -> Self {
+        Self {
+            poll_seconds: 300,
+            batch_size: 20,
+            max_installments: 6,
+            interval_days: 30,
+            final_due_days: 14,
+            max_attempts: 3,
+            retry_hours: 24,
+            cancellation_fee_percent: 10,
+        }
+    }
+}
+
+/// Daily funnel and attach-rate rollups behind the admin analytics endpoints
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AnalyticsConfig {
+    /// How often the rollup job runs
+    pub rollup_seconds: u64,
+    /// Trailing days recomputed on every run, since offers keep converting
+    /// for a while after the day they were generated
+    pub restate_days: i64,
+    /// How far back the first run rolls up
+    pub backfill_days: i64,
+    /// Widest date range a report may ask for
+    pub max_range_days: i64,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self { rollup_seconds: 900, restate_days: 3, backfill_days: 90, max_range_days: 366 }
+    }
+}
+
+/// Payment service provider integration
+#[derive(Debug, Deserialize, Clone)]
+pub struct PaymentConfig {
+    /// One of `PAYMENT_ADAPTERS`
+    pub adapter: String,
+    /// How long a customer has to finish a 3-D Secure challenge before the
+    /// order is released from PAYMENT_PENDING
+    #[serde(default = "default_payment_action_timeout_seconds")]
+    pub action_timeout_seconds: u64,
+}
+
+fn default_payment_action_timeout_seconds() -> u64 { 900 }
+
+/// Payment adapters this build can run
+pub const PAYMENT_ADAPTERS: &[&str] = &["mock"];
+
+impl Default for PaymentConfig {
+    fn default() -> Self {
+        Self { adapter: "mock".to_string(), action_timeout_seconds: default_payment_action_timeout_seconds() }
+    }
+}
+
+/// Per-role overrides of the built-in masking tiers, e.g.
+/// `[pii.roles.support]` `date_of_birth = "partial"`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PiiConfig {
+    #[serde(default)]
+    pub roles: HashMap<String, HashMap<altis_shared::pii::PiiField, altis_shared::pii::Visibility>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BlobConfig {
+    /// Directory backing the filesystem blob store (evidence bundles, exports)
+    pub root_dir: String,
+}
+
+impl Default for BlobConfig {
+    fn default() -> Self {
+        Self { root_dir: "./data/blobs".to_string() }
+    }
+}
+
+impl Default for FulfillmentConfig {
+    fn default() -> Self {
+        Self {
+            delivery_poll_seconds: 30,
+            delivery_batch_size: 50,
+            delivery_max_attempts: 5,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -18,9 +974,26 @@ pub struct RankingConfig {
     pub margin_weight: f64,
     pub ml_experiment_percentage: f64,
     pub ml_service_url: Option<String>,
+    /// Deadline for the batch scoring call; slower searches fall back to rules
+    #[serde(default = "default_ml_timeout_ms")]
+    pub ml_timeout_ms: u64,
+    /// Consecutive failed calls before the ML channel is skipped
+    #[serde(default = "default_ml_breaker_threshold")]
+    pub ml_breaker_threshold: usize,
+    /// How long the channel stays skipped before a trial call
+    #[serde(default = "default_ml_breaker_reset_seconds")]
+    pub ml_breaker_reset_seconds: u64,
+    /// Kafka topic receiving shown/accepted/paid rows for model training
+    #[serde(default = "default_training_topic")]
+    pub training_topic: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_ml_timeout_ms() -> u64 { 150 }
+fn default_ml_breaker_threshold() -> usize { 5 }
+fn default_ml_breaker_reset_seconds() -> u64 { 30 }
+fn default_training_topic() -> String { "ranking-training".to_string() }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BusinessRules {
     pub trip_hold_seconds: u64,
     pub seat_hold_seconds: u64,
@@ -30,16 +1003,82 @@ pub struct BusinessRules {
     pub pricing_multiplier: f64, 
     #[serde(default)]
     pub pricing_adjustment: f64,
-    pub sale_start: Option<String>, // ISO 8601
-    pub sale_end: Option<String>,   // ISO 8601
+    /// How long group bookings have to submit traveler names
+    #[serde(default = "default_group_name_deadline")]
+    pub group_name_deadline_seconds: u64,
+    /// Child (CHD) and lap infant (INF) fares, as a percentage of the adult fare
+    #[serde(default = "default_child_fare_percentage")]
+    pub child_fare_percentage: f64,
+    #[serde(default = "default_infant_fare_percentage")]
+    pub infant_fare_percentage: f64,
+}
+
+impl BusinessRules {
+    /// These settings with an airline's overrides laid over them. `overrides`
+    /// names only the settings the airline changes; a name that isn't a
+    /// setting, or a value of the wrong type, is refused.
+    pub fn with_overrides(&self, overrides: &serde_json::Value) -> Result<BusinessRules, String> {
+        let fields = overrides.as_object().ok_or("overrides must be an object")?;
+        let mut merged = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        for (name, value) in fields {
+            if merged.get(name).is_none() {
+                return Err(format!("'{}' is not a business rule", name));
+            }
+            merged[name] = value.clone();
+        }
+        serde_json::from_value(merged).map_err(|e| e.to_string())
+    }
+
+    /// Values that would misprice or mis-hold bookings, named as under `[business_rules]`
+    pub fn problems(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, setting: &str, message: String| {
+            if !ok {
+                problems.push(ConfigProblem { setting: format!("business_rules.{}", setting), message });
+            }
+        };
+        check((0.0..1.0).contains(&self.tax_rate), "tax_rate", format!("{} is not a fraction between 0 and 1", self.tax_rate));
+        check(self.booking_fee >= 0.0, "booking_fee", "must not be negative".to_string());
+        check(self.pricing_multiplier > 0.0, "pricing_multiplier", "must be positive".to_string());
+        check(self.trip_hold_seconds > 0, "trip_hold_seconds", "must be positive".to_string());
+        for (setting, percentage) in [("child_fare_percentage", self.child_fare_percentage), ("infant_fare_percentage", self.infant_fare_percentage)] {
+            check((0.0..=100.0).contains(&percentage), setting, format!("{} is not a percentage between 0 and 100", percentage));
+        }
+        problems
+    }
 }
 
 fn default_multiplier() -> f64 { 1.0 }
 
+fn default_group_name_deadline() -> u64 { 7 * 24 * 3600 }
+
+fn default_child_fare_percentage() -> f64 { 75.0 }
+
+fn default_infant_fare_percentage() -> f64 { 10.0 }
+
+fn default_qr_grant_ttl() -> u64 { 900 }
+
+fn default_service_signature_tolerance() -> i64 { 300 }
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct AuthConfig {
     pub jwt_secret: String,
     pub jwt_expiration_seconds: u64,
+    #[serde(default = "default_qr_grant_ttl")]
+    pub qr_grant_ttl_seconds: u64,
+    /// Partner API keys, keyed by partner name
+    #[serde(default)]
+    pub api_keys: HashMap<String, String>,
+    /// Sandbox keys, keyed by partner name: requests made with them book the
+    /// sandbox airline and pay through the mock adapter
+    #[serde(default)]
+    pub test_api_keys: HashMap<String, String>,
+    /// HMAC keys internal services sign their requests with, keyed by service name
+    #[serde(default)]
+    pub service_keys: HashMap<String, String>,
+    /// How far a signed request's timestamp may be from the server clock
+    #[serde(default = "default_service_signature_tolerance")]
+    pub service_signature_tolerance_seconds: i64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -51,18 +1090,66 @@ pub struct ServerConfig {
 #[derive(Debug, Deserialize, Clone)]
 pub struct DatabaseConfig {
     pub url: String,
+    /// Optional read replica; read-only repository queries go here when set
+    pub replica_url: Option<String>,
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    #[serde(default)]
+    pub min_connections: u32,
+    #[serde(default = "default_acquire_timeout")]
+    pub acquire_timeout_seconds: u64,
+    #[serde(default = "default_idle_timeout")]
+    pub idle_timeout_seconds: u64,
+    /// Set each checked-out connection's statement_timeout to the time left
+    /// on the request's deadline
+    #[serde(default = "default_deadline_statement_timeout")]
+    pub deadline_statement_timeout: bool,
+    /// One of `migrations::MIGRATION_MODES`
+    #[serde(default = "default_migrations")]
+    pub migrations: String,
 }
 
+fn default_max_connections() -> u32 { 10 }
+
+fn default_acquire_timeout() -> u64 { 5 }
+
+fn default_idle_timeout() -> u64 { 600 }
+
+fn default_deadline_statement_timeout() -> bool { true }
+fn default_migrations() -> String { "apply".to_string() }
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct RedisConfig {
     pub url: String,
+    /// Turn on expired-key events at startup so seat locks are released when
+    /// their trip expires. Disable where CONFIG is not allowed (managed Redis)
+    /// and set notify-keyspace-events to include "Ex" there instead.
+    #[serde(default = "default_expiry_notifications")]
+    pub expiry_notifications: bool,
 }
 
+fn default_expiry_notifications() -> bool { true }
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct KafkaConfig {
     pub brokers: String,
+    /// Times a consumer tries an event, the first included, before sending
+    /// it to the topic's dead-letter topic
+    #[serde(default = "default_retry_attempts")]
+    pub retry_attempts: u32,
+    /// Wait after the first failure, doubled after each one after that
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    #[serde(default = "default_retry_max_backoff_ms")]
+    pub retry_max_backoff_ms: u64,
 }
 
+fn default_retry_attempts() -> u32 { 5 }
+
+fn default_retry_backoff_ms() -> u64 { 500 }
+
+fn default_retry_max_backoff_ms() -> u64 { 30_000 }
+
 impl Config {
     pub fn load() -> Result<Self, config::ConfigError> {
         let run_mode = env::var("RUN_MODE").unwrap_or_else(|_| "development".into());
@@ -84,4 +1171,397 @@ impl Config {
 
         s.try_deserialize()
     }
+
+    /// Checks values that deserialize fine but would only fail, or quietly
+    /// misbehave, once requests arrive. Returns every problem found rather
+    /// than stopping at the first.
+    pub fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, setting: &str, message: String| {
+            if !ok {
+                problems.push(ConfigProblem { setting: setting.to_string(), message });
+            }
+        };
+        let production = env::var("RUN_MODE").is_ok_and(|mode| mode == "production");
+
+        check(
+            self.server.base_url.starts_with("http://") || self.server.base_url.starts_with("https://"),
+            "server.base_url",
+            format!("'{}' must be an absolute http(s) URL; it is used in links sent to customers", self.server.base_url),
+        );
+        check(
+            self.database.max_connections > 0 && self.database.min_connections <= self.database.max_connections,
+            "database.min_connections",
+            format!("must be at most max_connections ({})", self.database.max_connections),
+        );
+        check(
+            crate::migrations::MIGRATION_MODES.contains(&self.database.migrations.as_str()),
+            "database.migrations",
+            format!("'{}' is not a mode (supported: {})", self.database.migrations, crate::migrations::MIGRATION_MODES.join(", ")),
+        );
+
+        check(!self.auth.jwt_secret.is_empty(), "auth.jwt_secret", "must not be empty".to_string());
+        check(
+            !production || self.auth.jwt_secret != "super-secret-key-change-me",
+            "auth.jwt_secret",
+            "still the placeholder from config/default.toml; set ALTIS__AUTH__JWT_SECRET".to_string(),
+        );
+        for (partner, key) in &self.auth.api_keys {
+            check(!key.is_empty(), &format!("auth.api_keys.{}", partner), "must not be empty".to_string());
+        }
+        for (partner, key) in &self.auth.test_api_keys {
+            check(!key.is_empty(), &format!("auth.test_api_keys.{}", partner), "must not be empty".to_string());
+            check(
+                !self.auth.api_keys.values().any(|live| live == key),
+                &format!("auth.test_api_keys.{}", partner),
+                "is also a live key in auth.api_keys".to_string(),
+            );
+        }
+        for (service, key) in &self.auth.service_keys {
+            check(key.len() >= 32, &format!("auth.service_keys.{}", service), "must be at least 32 characters".to_string());
+            check(
+                !self.auth.api_keys.contains_key(service),
+                &format!("auth.service_keys.{}", service),
+                "is also a partner name in auth.api_keys".to_string(),
+            );
+        }
+        check(
+            self.auth.service_signature_tolerance_seconds > 0,
+            "auth.service_signature_tolerance_seconds",
+            "must be positive".to_string(),
+        );
+
+        for problem in self.business_rules.problems() {
+            check(false, &problem.setting, problem.message);
+        }
+
+        let ranking = &self.ranking;
+        check(
+            ranking.conversion_weight >= 0.0 && ranking.margin_weight >= 0.0 && ranking.conversion_weight + ranking.margin_weight > 0.0,
+            "ranking.conversion_weight",
+            "conversion_weight and margin_weight must be non-negative and not both zero".to_string(),
+        );
+        check(
+            (0.0..=1.0).contains(&ranking.ml_experiment_percentage),
+            "ranking.ml_experiment_percentage",
+            format!("{} is not a share between 0 and 1", ranking.ml_experiment_percentage),
+        );
+        if let Some(url) = &ranking.ml_service_url {
+            check(
+                url.starts_with("http://") || url.starts_with("https://"),
+                "ranking.ml_service_url",
+                format!("'{}' must be an http(s) URL; remove it to rank with rules only", url),
+            );
+        }
+        check(ranking.ml_timeout_ms > 0, "ranking.ml_timeout_ms", "must be positive".to_string());
+        check(ranking.ml_breaker_threshold > 0, "ranking.ml_breaker_threshold", "must be positive".to_string());
+
+        check(self.fulfillment.delivery_batch_size > 0, "fulfillment.delivery_batch_size", "must be positive".to_string());
+        check(self.fulfillment.delivery_max_attempts > 0, "fulfillment.delivery_max_attempts", "must be positive".to_string());
+        check(self.settlement.batch_hour_utc < 24, "settlement.batch_hour_utc", format!("{} is not an hour of the day (0-23)", self.settlement.batch_hour_utc));
+        check(
+            (1..=12).contains(&self.documents.fiscal_year_start_month),
+            "documents.fiscal_year_start_month",
+            format!("{} is not a month (1-12)", self.documents.fiscal_year_start_month),
+        );
+        for (airline, prefix) in &self.documents.prefixes {
+            check(!prefix.is_empty(), &format!("documents.prefixes.{}", airline), "must not be empty".to_string());
+        }
+        check(
+            self.sandbox.airline_code.len() == 2 && self.sandbox.airline_code != "AL",
+            "sandbox.airline_code",
+            format!("'{}' must be a two-letter code of its own", self.sandbox.airline_code),
+        );
+        check((1..=366).contains(&self.sandbox.days_ahead), "sandbox.days_ahead", "must be between 1 and 366".to_string());
+        check(self.sandbox.seats_per_flight > 0, "sandbox.seats_per_flight", "must be positive".to_string());
+        check(self.refunds.bulk_batch_size > 0, "refunds.bulk_batch_size", "must be positive".to_string());
+        check(self.refunds.bulk_max_attempts > 0, "refunds.bulk_max_attempts", "must be positive".to_string());
+
+        check(
+            PAYMENT_ADAPTERS.contains(&self.payment.adapter.as_str()),
+            "payment.adapter",
+            format!("'{}' is not available in this build (supported: {})", self.payment.adapter, PAYMENT_ADAPTERS.join(", ")),
+        );
+        check(self.payment.action_timeout_seconds >= 60, "payment.action_timeout_seconds", "must be at least 60".to_string());
+        check(!self.attribution.salt.is_empty(), "attribution.salt", "must be set; identifiers are hashed with it".to_string());
+        check(
+            !production || self.attribution.salt != "change-me-attribution-salt",
+            "attribution.salt",
+            "still the placeholder from config/default.toml; set ALTIS__ATTRIBUTION__SALT".to_string(),
+        );
+        check(self.attribution.lookback_days > 0, "attribution.lookback_days", "must be positive".to_string());
+        check(self.personalization.lookback_days > 0, "personalization.lookback_days", "must be positive".to_string());
+        check(self.price_watch.batch_size > 0, "price_watch.batch_size", "must be positive".to_string());
+        check(self.price_watch.max_window_days > 0, "price_watch.max_window_days", "must be positive".to_string());
+        check(self.waitlist.priority_minutes > 0, "waitlist.priority_minutes", "must be positive".to_string());
+        check(self.waitlist.batch_size > 0, "waitlist.batch_size", "must be positive".to_string());
+        check(self.waitlist.max_seats > 0, "waitlist.max_seats", "must be positive".to_string());
+        check(self.seating.assign_hours_before > 0, "seating.assign_hours_before", "must be positive".to_string());
+        check(self.seating.batch_size > 0, "seating.batch_size", "must be positive".to_string());
+        check(self.dcs.export_batch_size > 0, "dcs.export_batch_size", "must be positive".to_string());
+        check(self.archival.retention_days > 0, "archival.retention_days", "must be positive".to_string());
+        check(self.archival.batch_size > 0, "archival.batch_size", "must be positive".to_string());
+        check(self.scanning.scans_per_device_per_minute > 0, "scanning.scans_per_device_per_minute", "must be positive".to_string());
+        check(
+            self.scanning.signing_key_pem.is_some() == self.scanning.verifying_key_pem.is_some(),
+            "scanning.verifying_key_pem",
+            "signing_key_pem and verifying_key_pem are set together".to_string(),
+        );
+        check(self.scanning.undated_pass_hours > 0, "scanning.undated_pass_hours", "must be positive".to_string());
+        check(self.scanning.max_batch_scans > 0, "scanning.max_batch_scans", "must be positive".to_string());
+        check(self.revenue_recognition.batch_size > 0, "revenue_recognition.batch_size", "must be positive".to_string());
+        check(
+            BREAKAGE_TREATMENTS.contains(&self.revenue_recognition.breakage.as_str()),
+            "revenue_recognition.breakage",
+            format!("'{}' is not a treatment (supported: {})", self.revenue_recognition.breakage, BREAKAGE_TREATMENTS.join(", ")),
+        );
+        check(self.installments.batch_size > 0, "installments.batch_size", "must be positive".to_string());
+        check(self.installments.max_installments >= 2, "installments.max_installments", "must be at least 2".to_string());
+        check(self.installments.interval_days > 0, "installments.interval_days", "must be positive".to_string());
+        check(self.installments.final_due_days >= 0, "installments.final_due_days", "must not be negative".to_string());
+        check(self.installments.max_attempts > 0, "installments.max_attempts", "must be positive".to_string());
+        check(self.installments.retry_hours > 0, "installments.retry_hours", "must be positive".to_string());
+        check(
+            self.installments.cancellation_fee_percent <= 100,
+            "installments.cancellation_fee_percent",
+            format!("{} is not a percentage", self.installments.cancellation_fee_percent),
+        );
+        check(self.analytics.rollup_seconds > 0, "analytics.rollup_seconds", "must be positive".to_string());
+        check(self.analytics.restate_days >= 0, "analytics.restate_days", "must not be negative".to_string());
+        check(self.analytics.backfill_days >= 0, "analytics.backfill_days", "must not be negative".to_string());
+        check(self.analytics.max_range_days > 0, "analytics.max_range_days", "must be positive".to_string());
+        check(self.cart.max_offers > 0, "cart.max_offers", "must be positive".to_string());
+        for (setting, discount) in [
+            ("cart.multi_offer_discount", self.cart.multi_offer_discount),
+            ("cart.ancillary_bundle_discount", self.cart.ancillary_bundle_discount),
+        ] {
+            check((0.0..1.0).contains(&discount), setting, format!("{} is not a fraction between 0 and 1", discount));
+        }
+        check(self.webhooks.timeout_ms > 0, "webhooks.timeout_ms", "must be positive".to_string());
+        for (partner, endpoint) in &self.webhooks.endpoints {
+            let setting = format!("webhooks.endpoints.{}", partner);
+            check(self.auth.api_keys.contains_key(partner), &setting, "has no API key in auth.api_keys".to_string());
+            check(
+                endpoint.url.starts_with("https://") || (!production && endpoint.url.starts_with("http://")),
+                &format!("{}.url", setting),
+                format!("'{}' must be an https URL", endpoint.url),
+            );
+            check(!endpoint.secret.is_empty(), &format!("{}.secret", setting), "must not be empty".to_string());
+        }
+        check(self.deadlines.default_ms > 0, "deadlines.default_ms", "must be positive".to_string());
+        check(
+            self.deadlines.max_ms >= self.deadlines.default_ms,
+            "deadlines.max_ms",
+            format!("must be at least deadlines.default_ms ({})", self.deadlines.default_ms),
+        );
+        check(
+            !self.grpc.enabled || self.grpc.port != self.server.port,
+            "grpc.port",
+            format!("must differ from server.port ({})", self.server.port),
+        );
+        check(self.search.budget_ms > 0, "search.budget_ms", "must be positive".to_string());
+        for (setting, ms) in [
+            ("search.catalog_budget_ms", self.search.catalog_budget_ms),
+            ("search.generation_budget_ms", self.search.generation_budget_ms),
+            ("search.ranking_budget_ms", self.search.ranking_budget_ms),
+        ] {
+            check(
+                ms > 0 && ms <= self.search.budget_ms,
+                setting,
+                format!("must be positive and at most search.budget_ms ({})", self.search.budget_ms),
+            );
+        }
+        check(self.search.max_passengers > 0, "search.max_passengers", "must be positive".to_string());
+        check(self.search.max_days_ahead > 0, "search.max_days_ahead", "must be positive".to_string());
+        let edifact = &self.edifact;
+        check(
+            !edifact.sender_id.is_empty() && edifact.sender_id.len() <= 35,
+            "edifact.sender_id",
+            "must be 1-35 characters, the UNB identification limit".to_string(),
+        );
+        check(
+            EDIFACT_TRANSPORTS.contains(&edifact.transport.as_str()),
+            "edifact.transport",
+            format!("'{}' is not a transport (supported: {})", edifact.transport, EDIFACT_TRANSPORTS.join(", ")),
+        );
+        check(edifact.timeout_ms > 0, "edifact.timeout_ms", "must be positive".to_string());
+        for (partner, url) in &edifact.reply_urls {
+            let setting = format!("edifact.reply_urls.{}", partner);
+            check(self.auth.api_keys.contains_key(partner), &setting, "has no API key in auth.api_keys".to_string());
+            check(
+                url.starts_with("https://") || (!production && url.starts_with("http://")),
+                &setting,
+                format!("'{}' must be an https URL", url),
+            );
+        }
+        for (name, supplier) in &self.suppliers {
+            let setting = format!("suppliers.{}", name);
+            check(
+                supplier.base_url.starts_with("https://") || (!production && supplier.base_url.starts_with("http://")),
+                &format!("{}.base_url", setting),
+                format!("'{}' must be an https URL", supplier.base_url),
+            );
+            check(!supplier.api_key.is_empty(), &format!("{}.api_key", setting), "must not be empty".to_string());
+            check(supplier.timeout_ms > 0, &format!("{}.timeout_ms", setting), "must be positive".to_string());
+            check(supplier.breaker_threshold > 0, &format!("{}.breaker_threshold", setting), "must be positive".to_string());
+        }
+        check(self.interline.timeout_ms > 0, "interline.timeout_ms", "must be positive".to_string());
+        for (name, partner) in &self.interline.partners {
+            let setting = format!("interline.partners.{}", name);
+            check(
+                partner.carrier.len() == 2 && partner.carrier.chars().all(|c| c.is_ascii_alphanumeric()),
+                &format!("{}.carrier", setting),
+                format!("'{}' is not a two-character airline code", partner.carrier),
+            );
+            check(
+                partner.url.starts_with("https://") || (!production && partner.url.starts_with("http://")),
+                &format!("{}.url", setting),
+                format!("'{}' must be an https URL", partner.url),
+            );
+            check(!partner.api_key.is_empty(), &format!("{}.api_key", setting), "must not be empty".to_string());
+        }
+        check(!self.catalog_events.consumer_group.is_empty(), "catalog_events.consumer_group", "must not be empty".to_string());
+        let flight_status = &self.flight_status;
+        check(!flight_status.consumer_group.is_empty(), "flight_status.consumer_group", "must not be empty".to_string());
+        check(
+            flight_status.reaccommodate_after_delay_minutes >= 0,
+            "flight_status.reaccommodate_after_delay_minutes",
+            "must not be negative".to_string(),
+        );
+        if let Some(url) = &flight_status.feed_url {
+            check(
+                url.starts_with("https://") || (!production && url.starts_with("http://")),
+                "flight_status.feed_url",
+                format!("'{}' must be an https URL", url),
+            );
+            check(flight_status.feed_poll_seconds > 0, "flight_status.feed_poll_seconds", "must be positive".to_string());
+        }
+        let reaccommodation = &self.reaccommodation;
+        check(reaccommodation.max_options > 0, "reaccommodation.max_options", "must be positive".to_string());
+        check(reaccommodation.hold_minutes > 0, "reaccommodation.hold_minutes", "must be positive".to_string());
+        check(reaccommodation.search_window_hours > 0, "reaccommodation.search_window_hours", "must be positive".to_string());
+        check(self.compensation.eur_to_nuc > 0.0, "compensation.eur_to_nuc", "must be positive".to_string());
+        check(self.compensation.voucher_uplift >= 1.0, "compensation.voucher_uplift", "must be at least 1 so a voucher is never worth less than cash".to_string());
+        let admission = &self.search_admission;
+        check(admission.max_concurrent > 0, "search_admission.max_concurrent", "must be positive".to_string());
+        check(admission.tenant_concurrency > 0, "search_admission.tenant_concurrency", "must be positive".to_string());
+        for (partner, concurrency) in &admission.partner_concurrency {
+            let setting = format!("search_admission.partner_concurrency.{}", partner);
+            check(self.auth.api_keys.contains_key(partner), &setting, "has no API key in auth.api_keys".to_string());
+            check(*concurrency > 0, &setting, "must be positive".to_string());
+        }
+        let low_fares = &self.low_fares;
+        check(low_fares.batch_size > 0, "low_fares.batch_size", "must be positive".to_string());
+        check(low_fares.retain_days > 0, "low_fares.retain_days", "must be positive".to_string());
+        check(low_fares.max_batch > 0, "low_fares.max_batch", "must be positive".to_string());
+        check(low_fares.max_months_ahead > 0, "low_fares.max_months_ahead", "must be positive".to_string());
+        check(self.sse.channel_capacity > 0, "sse.channel_capacity", "must be positive".to_string());
+        check(self.sse.replay_events > 0, "sse.replay_events", "must be positive".to_string());
+        let email = &self.email;
+        check(!email.consumer_group.is_empty(), "email.consumer_group", "must not be empty".to_string());
+        check(
+            EMAIL_PROVIDERS.contains(&email.provider.as_str()),
+            "email.provider",
+            format!("'{}' is not a provider (supported: {})", email.provider, EMAIL_PROVIDERS.join(", ")),
+        );
+        check(email.from.contains('@'), "email.from", format!("'{}' is not an email address", email.from));
+        if email.provider != "log" {
+            check(!email.username.is_empty(), "email.username", "must not be empty".to_string());
+            check(!email.password.is_empty(), "email.password", "must not be empty".to_string());
+        }
+        let messaging = &self.messaging;
+        check(
+            MESSAGING_PROVIDERS.contains(&messaging.provider.as_str()),
+            "messaging.provider",
+            format!("'{}' is not a provider (supported: {})", messaging.provider, MESSAGING_PROVIDERS.join(", ")),
+        );
+        if messaging.provider != "log" {
+            check(messaging.api_base_url.starts_with("https://"), "messaging.api_base_url", format!("'{}' must be an https URL", messaging.api_base_url));
+            check(!messaging.account_sid.is_empty(), "messaging.account_sid", "must not be empty".to_string());
+            check(!messaging.auth_token.is_empty(), "messaging.auth_token", "must not be empty".to_string());
+        }
+        check(!messaging.sms_from.is_empty(), "messaging.sms_from", "must not be empty".to_string());
+        check(messaging.timeout_ms > 0, "messaging.timeout_ms", "must be positive".to_string());
+        check(!messaging.default_channels.is_empty(), "messaging.default_channels", "must name at least one channel".to_string());
+        for channel in &messaging.default_channels {
+            check(
+                ALERT_CHANNELS.contains(&channel.as_str()),
+                "messaging.default_channels",
+                format!("'{}' is not a channel (supported: {})", channel, ALERT_CHANNELS.join(", ")),
+            );
+        }
+        check(messaging.check_in_opens_hours > 0, "messaging.check_in_opens_hours", "must be positive".to_string());
+        check(messaging.check_in_batch_size > 0, "messaging.check_in_batch_size", "must be positive".to_string());
+        check(self.upsell.price_valid_minutes > 0, "upsell.price_valid_minutes", "must be positive".to_string());
+        check(self.upsell.max_offers > 0, "upsell.max_offers", "must be positive".to_string());
+        check(self.markets.domestic_multiplier > 0.0, "markets.domestic_multiplier", "must be positive".to_string());
+        check(self.markets.international_multiplier > 0.0, "markets.international_multiplier", "must be positive".to_string());
+        check(self.schedule_changes.notify_minutes >= 0, "schedule_changes.notify_minutes", "must not be negative".to_string());
+        check(
+            self.schedule_changes.major_minutes >= self.schedule_changes.notify_minutes,
+            "schedule_changes.major_minutes",
+            format!("must be at least schedule_changes.notify_minutes ({})", self.schedule_changes.notify_minutes),
+        );
+        check(!(production && self.chaos.enabled), "chaos.enabled", "fault injection must not be enabled in production".to_string());
+
+        problems
+    }
+}
+
+/// A setting that would break requests at runtime
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigProblem {
+    /// Dotted path, as in the TOML files (`ALTIS__SECTION__KEY` in the environment)
+    pub setting: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.setting, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_config() -> Config {
+        config::Config::builder()
+            .add_source(config::File::from_str(include_str!("../../config/default.toml"), config::FileFormat::Toml))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert_eq!(default_config().validate(), vec![]);
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let mut config = default_config();
+        config.business_rules.tax_rate = 1.5;
+        config.ranking.ml_experiment_percentage = 10.0;
+        config.payment.adapter = "stripe".to_string();
+
+        let settings: Vec<String> = config.validate().into_iter().map(|p| p.setting).collect();
+        assert_eq!(settings, ["business_rules.tax_rate", "ranking.ml_experiment_percentage", "payment.adapter"]);
+    }
+
+    #[test]
+    fn test_airline_business_rules() {
+        let rules = default_config().business_rules;
+        let airline = rules.with_overrides(&serde_json::json!({"trip_hold_seconds": 900, "pricing_multiplier": 1.05})).unwrap();
+        assert_eq!((airline.trip_hold_seconds, airline.pricing_multiplier), (900, 1.05));
+        // Whatever the airline doesn't override stays global
+        assert_eq!(airline.seat_hold_seconds, rules.seat_hold_seconds);
+
+        assert!(rules.with_overrides(&serde_json::json!({"hold_seconds": 900})).is_err());
+        assert!(rules.with_overrides(&serde_json::json!({"trip_hold_seconds": "15m"})).is_err());
+        let bad = rules.with_overrides(&serde_json::json!({"tax_rate": 2.0})).unwrap();
+        let settings: Vec<String> = bad.problems().into_iter().map(|p| p.setting).collect();
+        assert_eq!(settings, ["business_rules.tax_rate"]);
+    }
 }
@@ -0,0 +1,200 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::ArchiveRepository;
+use altis_core::tenant::TenantContext;
+
+use crate::DbClient;
+
+pub struct StoreArchiveRepository {
+    db: DbClient,
+}
+
+impl StoreArchiveRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+/// Every table an order's rows live in, with the rows that belong to order
+/// `$1`; parents before the rows that reference them. Finance tables
+/// (ledger, journals, accounting documents, payouts, refunds, disputes)
+/// stay behind and keep the order's id.
+const ORDER_TABLES: &[(&str, &str)] = &[
+    ("orders", "id = $1"),
+    ("order_items", "order_id = $1"),
+    ("travelers", "order_id = $1"),
+    ("fulfillment", "order_id = $1"),
+    ("seat_assignments", "order_id = $1"),
+    ("order_changes", "order_id = $1"),
+    ("order_notes", "order_id = $1"),
+    ("order_waivers", "order_id = $1"),
+    ("order_emails", "order_id = $1"),
+    ("order_messages", "order_id = $1"),
+    ("upsell_offers", "order_id = $1"),
+    ("order_snapshots", "order_id = $1"),
+    ("bag_tags", "order_id = $1"),
+    ("bag_events", "bag_tag_id IN (SELECT id FROM bag_tags WHERE order_id = $1)"),
+    ("payment_schedules", "order_id = $1"),
+    ("payment_installments", "schedule_id IN (SELECT id FROM payment_schedules WHERE order_id = $1)"),
+];
+
+fn document_order_id(document: &Value) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(document["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or("archive document without order_id")?)
+}
+
+#[async_trait]
+impl ArchiveRepository for StoreArchiveRepository {
+    async fn archivable_orders(&self, before: DateTime<Utc>, limit: i64) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM orders
+            WHERE status IN ('FULFILLED', 'CANCELLED') AND updated_at < $1
+            ORDER BY updated_at
+            LIMIT $2
+            "#,
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(ids)
+    }
+
+    async fn export_order(&self, order_id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        // One snapshot of every table, so the export is consistent
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ").execute(&mut *tx).await?;
+
+        let mut tables = serde_json::Map::new();
+        for (table, rows) in ORDER_TABLES {
+            let exported: Value = sqlx::query_scalar(&format!(
+                "SELECT coalesce(jsonb_agg(to_jsonb(t)), '[]'::jsonb) FROM {} t WHERE {}",
+                table, rows
+            ))
+            .bind(order_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            tables.insert(table.to_string(), exported);
+        }
+        tx.commit().await?;
+
+        if tables["orders"].as_array().is_none_or(|orders| orders.is_empty()) {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::json!({
+            "order_id": order_id,
+            "exported_at": Utc::now(),
+            "tables": tables,
+        })))
+    }
+
+    async fn archive_order(&self, document: &Value, blob_key: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let order_id = document_order_id(document)?;
+        let exported = &document["tables"]["orders"][0];
+        let mut tx = self.db.writer().begin().await?;
+
+        // Untouched since the export, so the document holds everything deleted here
+        let current: Option<Value> = sqlx::query_scalar("SELECT to_jsonb(o) FROM orders o WHERE id = $1 FOR UPDATE")
+            .bind(order_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let unchanged = current.is_some_and(|order| order["updated_at"] == exported["updated_at"] && order["status"] == exported["status"]);
+        if !unchanged {
+            return Ok(false);
+        }
+
+        let traveler_last_names: Vec<String> = document["tables"]["travelers"].as_array()
+            .map(|travelers| travelers.iter().filter_map(|t| t["last_name"].as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        sqlx::query(
+            r#"
+            INSERT INTO archived_orders (
+                order_id, airline_id, customer_id, status, booking_reference, contact_last_name,
+                traveler_last_names, total_nuc, currency, ordered_at, blob_key
+            )
+            SELECT o.id, o.airline_id, o.customer_id, o.status, upper(left(o.id::text, 6)), o.contact_last_name,
+                   $2, o.total_nuc, o.currency, o.created_at, $3
+            FROM jsonb_populate_record(NULL::orders, $1) o
+            ON CONFLICT (order_id) DO UPDATE SET archived_at = NOW(), blob_key = EXCLUDED.blob_key
+            "#,
+        )
+        .bind(exported)
+        .bind(&traveler_last_names)
+        .bind(blob_key)
+        .execute(&mut *tx)
+        .await?;
+
+        for (table, rows) in ORDER_TABLES.iter().rev() {
+            sqlx::query(&format!("DELETE FROM {} WHERE {}", table, rows))
+                .bind(order_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn find_archived_orders(
+        &self,
+        tenant: &TenantContext,
+        reference: &str,
+        last_name: Option<&str>,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let found: Vec<Value> = sqlx::query_scalar(
+            r#"
+            SELECT to_jsonb(a) FROM archived_orders a
+            WHERE a.booking_reference = upper($1)
+              AND ($2::uuid IS NULL OR a.airline_id = $2)
+              AND ($3::text IS NULL
+                   OR lower(a.contact_last_name) = lower($3)
+                   OR lower($3) = ANY (SELECT lower(n) FROM unnest(a.traveler_last_names) n))
+            ORDER BY a.ordered_at DESC
+            "#,
+        )
+        .bind(reference)
+        .bind(tenant.airline_id())
+        .bind(last_name)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(found)
+    }
+
+    async fn get_archived_order(&self, tenant: &TenantContext, order_id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let archived: Option<Value> = sqlx::query_scalar(
+            "SELECT to_jsonb(a) FROM archived_orders a WHERE a.order_id = $1 AND ($2::uuid IS NULL OR a.airline_id = $2)",
+        )
+        .bind(order_id)
+        .bind(tenant.airline_id())
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(archived)
+    }
+
+    async fn restore_order(&self, document: &Value) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let order_id = document_order_id(document)?;
+        let mut tx = self.db.writer().begin().await?;
+
+        let archived = sqlx::query("DELETE FROM archived_orders WHERE order_id = $1")
+            .bind(order_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if archived == 0 {
+            return Ok(false);
+        }
+        for (table, _) in ORDER_TABLES {
+            let rows = &document["tables"][*table];
+            if rows.as_array().is_none_or(|rows| rows.is_empty()) {
+                continue;
+            }
+            sqlx::query(&format!("INSERT INTO {0} SELECT * FROM jsonb_populate_recordset(NULL::{0}, $1)", table))
+                .bind(rows)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+}
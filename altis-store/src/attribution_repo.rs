@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use altis_core::repository::AttributionRepository;
+
+use crate::DbClient;
+
+pub struct StoreAttributionRepository {
+    db: DbClient,
+}
+
+impl StoreAttributionRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CampaignRow {
+    utm_source: String,
+    utm_medium: String,
+    utm_campaign: String,
+    reached: i64,
+    conversions: i64,
+    revenue_nuc: i64,
+}
+
+#[async_trait]
+impl AttributionRepository for StoreAttributionRepository {
+    async fn record_touch(&self, subject_hash: &str, campaign: &Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let field = |key: &str| campaign[key].as_str().unwrap_or_default().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO attribution_touches (subject_hash, utm_source, utm_medium, utm_campaign, utm_term, utm_content)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(subject_hash)
+        .bind(field("utm_source"))
+        .bind(field("utm_medium"))
+        .bind(field("utm_campaign"))
+        .bind(field("utm_term"))
+        .bind(field("utm_content"))
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn record_conversion(&self, order_hash: &str, subject_hash: &str, value_nuc: i32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            r#"
+            INSERT INTO attribution_conversions (order_hash, subject_hash, value_nuc)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (order_hash) DO NOTHING
+            "#,
+        )
+        .bind(order_hash)
+        .bind(subject_hash)
+        .bind(value_nuc)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn campaign_report(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        lookback_days: i32,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // Last touch wins; conversions with no touch in the lookback are organic
+        let rows: Vec<CampaignRow> = sqlx::query_as(
+            r#"
+            WITH reached AS (
+                SELECT utm_source, utm_medium, utm_campaign, COUNT(DISTINCT subject_hash) AS reached
+                FROM attribution_touches
+                WHERE touched_at >= $1 AND touched_at < $2
+                GROUP BY utm_source, utm_medium, utm_campaign
+            ),
+            converted AS (
+                SELECT t.utm_source, t.utm_medium, t.utm_campaign,
+                       COUNT(*) AS conversions, SUM(c.value_nuc) AS revenue_nuc
+                FROM attribution_conversions c
+                CROSS JOIN LATERAL (
+                    SELECT utm_source, utm_medium, utm_campaign
+                    FROM attribution_touches t
+                    WHERE t.subject_hash = c.subject_hash
+                      AND t.touched_at <= c.converted_at
+                      AND t.touched_at > c.converted_at - make_interval(days => $3)
+                    ORDER BY t.touched_at DESC
+                    LIMIT 1
+                ) t
+                WHERE c.converted_at >= $1 AND c.converted_at < $2
+                GROUP BY t.utm_source, t.utm_medium, t.utm_campaign
+            )
+            SELECT COALESCE(r.utm_source, v.utm_source) AS utm_source,
+                   COALESCE(r.utm_medium, v.utm_medium) AS utm_medium,
+                   COALESCE(r.utm_campaign, v.utm_campaign) AS utm_campaign,
+                   COALESCE(r.reached, 0) AS reached,
+                   COALESCE(v.conversions, 0) AS conversions,
+                   COALESCE(v.revenue_nuc, 0)::BIGINT AS revenue_nuc
+            FROM reached r
+            FULL OUTER JOIN converted v
+              ON v.utm_source = r.utm_source AND v.utm_medium = r.utm_medium AND v.utm_campaign = r.utm_campaign
+            ORDER BY revenue_nuc DESC, conversions DESC, utm_campaign
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(lookback_days)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| serde_json::json!({
+            "utm_source": row.utm_source,
+            "utm_medium": row.utm_medium,
+            "utm_campaign": row.utm_campaign,
+            "reached": row.reached,
+            "conversions": row.conversions,
+            "revenue_nuc": row.revenue_nuc,
+        })).collect())
+    }
+}
@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::BaggageRepository;
+
+use crate::DbClient;
+
+pub struct StoreBaggageRepository {
+    db: DbClient,
+}
+
+impl StoreBaggageRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+/// A bag tag row with its events folded in, oldest first
+const TAG_WITH_EVENTS: &str = r#"
+    SELECT to_jsonb(t) || jsonb_build_object('events', COALESCE(
+        (SELECT jsonb_agg(to_jsonb(e) - 'bag_tag_id' ORDER BY e.scanned_at, e.id) FROM bag_events e WHERE e.bag_tag_id = t.id),
+        '[]'::jsonb))
+    FROM bag_tags t
+"#;
+
+fn uuid_of(value: &Value) -> Option<Uuid> {
+    value.as_str().and_then(|id| Uuid::parse_str(id).ok())
+}
+
+#[async_trait]
+impl BaggageRepository for StoreBaggageRepository {
+    async fn next_bag_tag_serials(&self, airline_id: Uuid, count: i64) -> Result<Vec<i64>, Box<dyn std::error::Error + Send + Sync>> {
+        let last: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO bag_tag_sequences (airline_id, last_value) VALUES ($1, $2)
+            ON CONFLICT (airline_id)
+            DO UPDATE SET last_value = bag_tag_sequences.last_value + $2, updated_at = NOW()
+            RETURNING last_value
+            "#,
+        )
+        .bind(airline_id)
+        .bind(count)
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok((last - count + 1..=last).collect())
+    }
+
+    async fn create_bag_tag(&self, tag: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let id: Uuid = sqlx::query_scalar(
+            "INSERT INTO bag_tags (tag_number, order_id, order_item_id, airline_id) VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(tag["tag_number"].as_str())
+        .bind(uuid_of(&tag["order_id"]))
+        .bind(uuid_of(&tag["order_item_id"]))
+        .bind(uuid_of(&tag["airline_id"]))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO bag_events (bag_tag_id, event_type, station, scanned_by) VALUES ($1, 'CHECKED_IN', $2, $3)")
+            .bind(id)
+            .bind(tag["station"].as_str())
+            .bind(tag["scanned_by"].as_str())
+            .execute(&mut *tx)
+            .await?;
+
+        let created: Value = sqlx::query_scalar(&format!("{} WHERE t.id = $1", TAG_WITH_EVENTS))
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    async fn get_bag_tag(&self, tag_number: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // Scans arrive in quick succession; read each one's predecessor from the primary
+        let tag = sqlx::query_scalar::<_, Value>(&format!("{} WHERE t.tag_number = $1 ORDER BY t.created_at DESC LIMIT 1", TAG_WITH_EVENTS))
+            .bind(tag_number)
+            .fetch_optional(self.db.writer())
+            .await?;
+        Ok(tag)
+    }
+
+    async fn list_order_bag_tags(&self, order_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let tags = sqlx::query_scalar::<_, Value>(&format!("{} WHERE t.order_id = $1 ORDER BY t.created_at, t.tag_number", TAG_WITH_EVENTS))
+            .bind(order_id)
+            .fetch_all(self.db.reader())
+            .await?;
+        Ok(tags)
+    }
+
+    async fn record_bag_event(&self, bag_tag_id: Uuid, event: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let recorded: Value = sqlx::query_scalar(
+            r#"
+            INSERT INTO bag_events (bag_tag_id, event_type, station, flight_id, scanned_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING to_jsonb(bag_events) - 'bag_tag_id'
+            "#,
+        )
+        .bind(bag_tag_id)
+        .bind(event["event_type"].as_str())
+        .bind(event["station"].as_str())
+        .bind(uuid_of(&event["flight_id"]))
+        .bind(event["scanned_by"].as_str())
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(recorded)
+    }
+}
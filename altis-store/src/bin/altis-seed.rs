@@ -0,0 +1,89 @@
+//! Seeds a migrated database with synthetic airlines, flights and order
+//! history for load tests:
+//!
+//! ```text
+//! cargo run -p altis-store --bin altis-seed -- --seed 7 --airlines 5 --orders 100000 --start-date 2026-06-01
+//! ```
+//!
+//! The same options always produce the same rows. Pin `--start-date` when
+//! comparing runs; it defaults to today.
+
+use std::sync::Arc;
+
+use altis_store::seed::{self, SeedConfig};
+
+const USAGE: &str = "usage: altis-seed [--seed N] [--airlines N] [--routes N] [--days N] [--orders N] [--customers N] [--start-date YYYY-MM-DD] [--dry-run]";
+
+fn parse_args() -> Result<(SeedConfig, bool), String> {
+    let mut config = SeedConfig {
+        seed: 1,
+        airlines: 3,
+        routes_per_airline: 20,
+        days: 30,
+        orders: 10_000,
+        customers: 2_000,
+        start_date: chrono::Utc::now().date_naive(),
+    };
+    let mut dry_run = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--dry-run" {
+            dry_run = true;
+            continue;
+        }
+        let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        let invalid = |_| format!("invalid value for {}: {}", arg, value);
+        match arg.as_str() {
+            "--seed" => config.seed = value.parse().map_err(invalid)?,
+            "--airlines" => config.airlines = value.parse().map_err(invalid)?,
+            "--routes" => config.routes_per_airline = value.parse().map_err(invalid)?,
+            "--days" => config.days = value.parse().map_err(invalid)?,
+            "--orders" => config.orders = value.parse().map_err(invalid)?,
+            "--customers" => config.customers = value.parse().map_err(invalid)?,
+            "--start-date" => config.start_date = value.parse().map_err(|_| format!("invalid value for {}: {}", arg, value))?,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    config.validate()?;
+    Ok((config, dry_run))
+}
+
+#[tokio::main]
+async fn main() {
+    let (config, dry_run) = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+
+    let dataset = seed::generate(&config);
+    println!(
+        "Seed {} from {}: {} airlines, {} flights, {} products, {} orders",
+        config.seed,
+        config.start_date,
+        dataset.airlines.len(),
+        dataset.flights(),
+        dataset.products.len(),
+        dataset.orders.len(),
+    );
+    if dry_run {
+        return;
+    }
+
+    let app_config = altis_store::app_config::Config::load().expect("Failed to load config");
+    let chaos = Arc::new(altis_store::ChaosInjector::new(&app_config.chaos));
+    let db = altis_store::DbClient::new(&app_config.database, chaos)
+        .await
+        .expect("Failed to connect to Postgres");
+
+    match seed::write(db.writer(), &dataset).await {
+        Ok(counts) => println!(
+            "Inserted {} airlines, {} products, {} orders, {} order items (existing rows skipped)",
+            counts.airlines, counts.products, counts.orders, counts.order_items,
+        ),
+        Err(e) => {
+            eprintln!("Seeding failed, nothing was written: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
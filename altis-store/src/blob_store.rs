@@ -0,0 +1,78 @@
+use std::path::{Component, Path, PathBuf};
+
+use async_trait::async_trait;
+use altis_core::blob::BlobStore;
+
+/// Filesystem-backed blob store. Suitable for single-node deployments or a
+/// mounted volume; keys map to paths under `root`.
+pub struct FsBlobStore {
+    root: PathBuf,
+}
+
+impl FsBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Rejects keys that would escape the root (`..`, absolute paths).
+    fn path_for(&self, key: &str) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        let relative = Path::new(key);
+        if key.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("Invalid blob key: {}", key).into());
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl BlobStore for FsBlobStore {
+    async fn put(
+        &self,
+        key: &str,
+        bytes: Vec<u8>,
+        _content_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write then rename so readers never see a partial blob
+        let tmp = path.with_extension("partial");
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+
+        Ok(format!("file://{}", path.display()))
+    }
+
+    async fn get(
+        &self,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        match tokio::fs::read(self.path_for(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_roundtrip_and_key_validation() {
+        let root = std::env::temp_dir().join(format!("altis-blob-{}", uuid::Uuid::new_v4()));
+        let store = FsBlobStore::new(&root);
+
+        store.put("evidence/order-1/bundle.zip", b"zip".to_vec(), "application/zip").await.unwrap();
+        assert_eq!(store.get("evidence/order-1/bundle.zip").await.unwrap(), Some(b"zip".to_vec()));
+        assert_eq!(store.get("evidence/missing.zip").await.unwrap(), None);
+
+        assert!(store.put("../escape", vec![], "text/plain").await.is_err());
+        assert!(store.put("/etc/passwd", vec![], "text/plain").await.is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,285 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::BulkRefundRepository;
+
+use crate::DbClient;
+
+pub struct StoreBulkRefundRepository {
+    db: DbClient,
+}
+
+impl StoreBulkRefundRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: Uuid,
+    flight_id: String,
+    departure_from: DateTime<Utc>,
+    departure_to: DateTime<Utc>,
+    reason: String,
+    status: String,
+    created_by: String,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    total: i64,
+    pending: i64,
+    processing: i64,
+    refunded: i64,
+    cancelled: i64,
+    skipped: i64,
+    failed: i64,
+    refunded_nuc: i64,
+}
+
+#[async_trait]
+impl BulkRefundRepository for StoreBulkRefundRepository {
+    async fn create_job(
+        &self,
+        flight_id: &str,
+        departure_from: DateTime<Utc>,
+        departure_to: DateTime<Utc>,
+        reason: &str,
+        created_by: &str,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let job_id = Uuid::new_v4();
+        let mut tx = self.db.writer().begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO bulk_refund_jobs (id, flight_id, departure_from, departure_to, reason, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(job_id)
+        .bind(flight_id)
+        .bind(departure_from)
+        .bind(departure_to)
+        .bind(reason)
+        .bind(created_by)
+        .execute(&mut *tx)
+        .await?;
+
+        // Items without a well-formed departure time can't be placed in the range
+        sqlx::query(
+            r#"
+            INSERT INTO bulk_refund_items (job_id, order_id)
+            SELECT DISTINCT $1::uuid, o.id
+            FROM orders o
+            JOIN order_items i ON i.order_id = o.id
+            CROSS JOIN LATERAL (
+                SELECT CASE WHEN i.metadata->>'departure_time' ~ '^\d{4}-\d{2}-\d{2}T'
+                            THEN (i.metadata->>'departure_time')::timestamptz END AS departs_at
+            ) d
+            WHERE i.metadata->>'flight_id' = $2
+              AND COALESCE(i.status, 'ACTIVE') NOT IN ('REFUNDED', 'CANCELLED')
+              AND o.status IN ('PROPOSED', 'PAYMENT_PENDING', 'PAID')
+              AND d.departs_at >= $3 AND d.departs_at < $4
+            "#,
+        )
+        .bind(job_id)
+        .bind(flight_id)
+        .bind(departure_from)
+        .bind(departure_to)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_job(job_id).await?.ok_or_else(|| "Bulk refund job vanished after creation".into())
+    }
+
+    async fn get_job(&self, job_id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // Progress is polled while the job writes, so read from the primary
+        let row = sqlx::query_as::<_, JobRow>(
+            r#"
+            SELECT j.id, j.flight_id, j.departure_from, j.departure_to, j.reason, j.status,
+                   j.created_by, j.created_at, j.completed_at,
+                   COUNT(i.order_id) AS total,
+                   COUNT(i.order_id) FILTER (WHERE i.status = 'PENDING') AS pending,
+                   COUNT(i.order_id) FILTER (WHERE i.status = 'PROCESSING') AS processing,
+                   COUNT(i.order_id) FILTER (WHERE i.status = 'REFUNDED') AS refunded,
+                   COUNT(i.order_id) FILTER (WHERE i.status = 'CANCELLED') AS cancelled,
+                   COUNT(i.order_id) FILTER (WHERE i.status = 'SKIPPED') AS skipped,
+                   COUNT(i.order_id) FILTER (WHERE i.status = 'FAILED') AS failed,
+                   COALESCE(SUM(i.refunded_nuc), 0)::BIGINT AS refunded_nuc
+            FROM bulk_refund_jobs j
+            LEFT JOIN bulk_refund_items i ON i.job_id = j.id
+            WHERE j.id = $1
+            GROUP BY j.id
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+
+        Ok(row.map(|row| {
+            let done = row.total - row.pending - row.processing;
+            serde_json::json!({
+                "id": row.id,
+                "flight_id": row.flight_id,
+                "departure_from": row.departure_from.to_rfc3339(),
+                "departure_to": row.departure_to.to_rfc3339(),
+                "reason": row.reason,
+                "status": row.status,
+                "created_by": row.created_by,
+                "created_at": row.created_at.to_rfc3339(),
+                "completed_at": row.completed_at.map(|t| t.to_rfc3339()),
+                "progress": {
+                    "total": row.total,
+                    "processed": done,
+                    "pending": row.pending,
+                    "processing": row.processing,
+                    "refunded": row.refunded,
+                    "cancelled": row.cancelled,
+                    "skipped": row.skipped,
+                    "failed": row.failed,
+                    "percent_complete": if row.total == 0 { 100 } else { done * 100 / row.total },
+                },
+                "refunded_nuc": row.refunded_nuc,
+            })
+        }))
+    }
+
+    async fn list_running_jobs(&self) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
+        let ids = sqlx::query_scalar("SELECT id FROM bulk_refund_jobs WHERE status = 'RUNNING' ORDER BY created_at")
+            .fetch_all(self.db.writer())
+            .await?;
+        Ok(ids)
+    }
+
+    async fn claim_items(
+        &self,
+        job_id: Uuid,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // SKIP LOCKED lets a resumed job and a retry share the queue safely
+        let rows = sqlx::query(
+            r#"
+            UPDATE bulk_refund_items
+            SET status = 'PROCESSING', attempts = attempts + 1, updated_at = NOW()
+            WHERE job_id = $1 AND order_id IN (
+                SELECT order_id FROM bulk_refund_items
+                WHERE job_id = $1
+                  AND (status = 'PENDING' OR (status = 'PROCESSING' AND updated_at < NOW() - make_interval(secs => $3)))
+                ORDER BY order_id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING order_id, attempts
+            "#,
+        )
+        .bind(job_id)
+        .bind(limit)
+        .bind(lease_seconds as f64)
+        .fetch_all(self.db.writer())
+        .await?;
+
+        Ok(rows.iter().map(|row| {
+            serde_json::json!({
+                "order_id": sqlx::Row::get::<Uuid, _>(row, "order_id"),
+                "attempts": sqlx::Row::get::<i32, _>(row, "attempts"),
+            })
+        }).collect())
+    }
+
+    async fn complete_item(
+        &self,
+        job_id: Uuid,
+        order_id: Uuid,
+        status: &str,
+        refunded_nuc: i64,
+        error: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            r#"
+            UPDATE bulk_refund_items
+            SET status = $3, refunded_nuc = $4, last_error = $5, updated_at = NOW()
+            WHERE job_id = $1 AND order_id = $2
+            "#,
+        )
+        .bind(job_id)
+        .bind(order_id)
+        .bind(status)
+        .bind(refunded_nuc)
+        .bind(error)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn finish_job(&self, job_id: Uuid) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let status = sqlx::query_scalar(
+            r#"
+            UPDATE bulk_refund_jobs j
+            SET status = CASE WHEN EXISTS (
+                    SELECT 1 FROM bulk_refund_items WHERE job_id = j.id AND status = 'FAILED'
+                ) THEN 'COMPLETED_WITH_FAILURES' ELSE 'COMPLETED' END,
+                completed_at = NOW()
+            WHERE j.id = $1 AND j.status = 'RUNNING'
+              AND NOT EXISTS (
+                SELECT 1 FROM bulk_refund_items WHERE job_id = j.id AND status IN ('PENDING', 'PROCESSING')
+              )
+            RETURNING j.status
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(status)
+    }
+
+    async fn list_failures(&self, job_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT i.order_id, o.total_nuc, o.currency, i.attempts, i.last_error, i.updated_at
+            FROM bulk_refund_items i
+            JOIN orders o ON o.id = i.order_id
+            WHERE i.job_id = $1 AND i.status = 'FAILED'
+            ORDER BY i.updated_at
+            "#,
+        )
+        .bind(job_id)
+        .fetch_all(self.db.writer())
+        .await?;
+
+        Ok(rows.iter().map(|row| {
+            serde_json::json!({
+                "order_id": sqlx::Row::get::<Uuid, _>(row, "order_id"),
+                "total_nuc": sqlx::Row::get::<i32, _>(row, "total_nuc"),
+                "currency": sqlx::Row::get::<Option<String>, _>(row, "currency"),
+                "attempts": sqlx::Row::get::<i32, _>(row, "attempts"),
+                "last_error": sqlx::Row::get::<Option<String>, _>(row, "last_error"),
+                "failed_at": sqlx::Row::get::<DateTime<Utc>, _>(row, "updated_at").to_rfc3339(),
+            })
+        }).collect())
+    }
+
+    async fn retry_failures(&self, job_id: Uuid) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let requeued = sqlx::query(
+            "UPDATE bulk_refund_items SET status = 'PENDING', attempts = 0, updated_at = NOW() WHERE job_id = $1 AND status = 'FAILED'",
+        )
+        .bind(job_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if requeued > 0 {
+            sqlx::query("UPDATE bulk_refund_jobs SET status = 'RUNNING', completed_at = NULL WHERE id = $1")
+                .bind(job_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(requeued)
+    }
+}
@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::CartRepository;
+
+use crate::DbClient;
+
+pub struct StoreCartRepository {
+    db: DbClient,
+}
+
+impl StoreCartRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CartRow {
+    id: Uuid,
+    customer_id: String,
+    status: String,
+    order_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct CartItemRow {
+    offer_id: Uuid,
+    offer_item_id: Uuid,
+    added_at: DateTime<Utc>,
+}
+
+impl CartRow {
+    fn into_json(self, items: Vec<CartItemRow>) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "customer_id": self.customer_id,
+            "status": self.status,
+            "order_id": self.order_id,
+            "created_at": self.created_at,
+            "updated_at": self.updated_at,
+            "items": items.into_iter().map(|item| serde_json::json!({
+                "offer_id": item.offer_id,
+                "offer_item_id": item.offer_item_id,
+                "added_at": item.added_at,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[async_trait]
+impl CartRepository for StoreCartRepository {
+    async fn create_cart(&self, customer_id: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let row: CartRow = sqlx::query_as(
+            r#"
+            INSERT INTO carts (customer_id) VALUES ($1)
+            RETURNING id, customer_id, status, order_id, created_at, updated_at
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(row.into_json(Vec::new()))
+    }
+
+    async fn get_cart(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // Carts are edited and read back straight away; the replica may not have the edit yet
+        let row: Option<CartRow> = sqlx::query_as(
+            "SELECT id, customer_id, status, order_id, created_at, updated_at FROM carts WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let items: Vec<CartItemRow> = sqlx::query_as(
+            "SELECT offer_id, offer_item_id, added_at FROM cart_items WHERE cart_id = $1 ORDER BY added_at, offer_item_id",
+        )
+        .bind(id)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(Some(row.into_json(items)))
+    }
+
+    async fn add_cart_items(&self, cart_id: Uuid, offer_id: Uuid, item_ids: &[Uuid]) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        // Locks the cart so a checkout can't run between the check and the insert
+        let open = sqlx::query("UPDATE carts SET updated_at = NOW() WHERE id = $1 AND status = 'OPEN'")
+            .bind(cart_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() > 0;
+        if !open {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO cart_items (cart_id, offer_id, offer_item_id)
+            SELECT $1, $2, UNNEST($3::UUID[])
+            ON CONFLICT (cart_id, offer_item_id) DO NOTHING
+            "#,
+        )
+        .bind(cart_id)
+        .bind(offer_id)
+        .bind(item_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn remove_cart_offer(&self, cart_id: Uuid, offer_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let open = sqlx::query("UPDATE carts SET updated_at = NOW() WHERE id = $1 AND status = 'OPEN'")
+            .bind(cart_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() > 0;
+        if !open {
+            return Ok(false);
+        }
+
+        sqlx::query("DELETE FROM cart_items WHERE cart_id = $1 AND offer_id = $2")
+            .bind(cart_id)
+            .bind(offer_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn checkout_cart(&self, cart_id: Uuid, order_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            "UPDATE carts SET status = 'CHECKED_OUT', order_id = $2, updated_at = NOW() WHERE id = $1 AND status = 'OPEN'",
+        )
+        .bind(cart_id)
+        .bind(order_id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn reopen_cart(&self, cart_id: Uuid) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE carts SET status = 'OPEN', order_id = NULL, updated_at = NOW() WHERE id = $1 AND status = 'CHECKED_OUT'")
+            .bind(cart_id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(())
+    }
+}
@@ -1,16 +1,58 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use uuid::Uuid;
-use sqlx::PgPool;
+use crate::DbClient;
 use serde_json::Value;
 use altis_core::repository::ProductRepository;
+use altis_core::tenant::TenantContext;
 
 pub struct StoreProductRepository {
-    pool: PgPool,
+    db: DbClient,
 }
 
 impl StoreProductRepository {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+
+    /// Loads a product, deleted or not; a tenant other than the platform
+    /// only sees its own airline's.
+    async fn fetch_product(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // Use query_as! for strict typing
+        let row = sqlx::query_as!(
+            ProductRow,
+            "SELECT id, airline_id, product_type, product_code, name, description, base_price_nuc, currency, is_active, margin_percentage::FLOAT8, metadata, version, created_at, updated_at, deleted_at FROM products WHERE id = $1 AND ($2::uuid IS NULL OR airline_id = $2)",
+            id,
+            tenant.airline_id()
+        )
+        .fetch_optional(self.db.reader())
+        .await?;
+
+        if let Some(row) = row {
+            let product_json = serde_json::json!({
+                "id": row.id,
+                "airline_id": row.airline_id,
+                "product_type": row.product_type,
+                "product_code": row.product_code,
+                "name": row.name,
+                "description": row.description,
+                "base_price_nuc": row.base_price_nuc,
+                "is_active": row.is_active,
+                "metadata": row.metadata,
+                "version": row.version,
+                "created_at": row.created_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339()),
+                "updated_at": row.updated_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339()),
+                "deleted_at": row.deleted_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339())
+            });
+            return Ok(Some(product_json));
+        }
+
+        Ok(None)
     }
 }
 
@@ -29,10 +71,126 @@ struct ProductRow {
     is_active: Option<bool>,
     margin_percentage: Option<f64>,
     metadata: Option<Value>,
+    version: i32,
     created_at: Option<chrono::DateTime<chrono::Utc>>,
     updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TaxCodeRow {
+    id: Uuid,
+    code: String,
+    name: String,
+    jurisdiction: String,
+    product_type: Option<String>,
+    origin_country: Option<String>,
+    destination_country: Option<String>,
+    market: Option<String>,
+    rate: Option<f64>,
+    amount_nuc: Option<i32>,
+}
+
+const VERSION_COLUMNS: &str = "product_id, version, name, description, base_price_nuc, metadata, is_active, effective_from, effective_to, created_at";
+
+/// SQL for the status a live campaign should have now, given its window
+fn campaign_status_now(starts_at: &str, ends_at: &str) -> String {
+    format!("CASE WHEN {} <= NOW() THEN 'ENDED' WHEN {} <= NOW() THEN 'ACTIVE' ELSE 'SCHEDULED' END", ends_at, starts_at)
+}
+
+fn campaign_product_types(campaign: &Value) -> Vec<String> {
+    campaign["product_types"].as_array().into_iter().flatten()
+        .filter_map(|t| t.as_str().map(str::to_string))
+        .collect()
+}
+
+fn campaign_time(campaign: &Value, field: &str) -> Result<chrono::DateTime<chrono::Utc>, Box<dyn std::error::Error + Send + Sync>> {
+    let time = campaign[field].as_str().ok_or_else(|| format!("Campaign without {}", field))?;
+    Ok(chrono::DateTime::parse_from_rfc3339(time)?.with_timezone(&chrono::Utc))
+}
+
+/// Snapshots each product whose row has drifted from the version it claims
+/// (a direct edit or a catalog import) as a new version in force from now.
+async fn record_product_versions(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    product_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        WITH changed AS (
+            SELECT p.id, p.name, p.description, p.base_price_nuc, p.metadata, p.is_active,
+                   (SELECT COALESCE(MAX(v.version), 0) + 1 FROM product_versions v WHERE v.product_id = p.id) AS next_version
+            FROM products p
+            WHERE p.id = ANY($1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM product_versions v
+                  WHERE v.product_id = p.id AND v.version = p.version
+                    AND v.name = p.name AND v.description IS NOT DISTINCT FROM p.description
+                    AND v.base_price_nuc = p.base_price_nuc AND v.metadata IS NOT DISTINCT FROM p.metadata
+                    AND v.is_active IS NOT DISTINCT FROM p.is_active
+              )
+        ),
+        inserted AS (
+            INSERT INTO product_versions (product_id, version, name, description, base_price_nuc, metadata, is_active, effective_from)
+            SELECT id, next_version, name, description, base_price_nuc, metadata, is_active, NOW() FROM changed
+            RETURNING product_id, version
+        )
+        UPDATE products p SET version = i.version FROM inserted i WHERE p.id = i.product_id
+        "#,
+    )
+    .bind(product_ids)
+    .execute(&mut **tx)
+    .await?;
+    refresh_version_windows(tx, product_ids).await
+}
+
+/// Closes each version at the next one's `effective_from`
+async fn refresh_version_windows(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    product_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE product_versions v SET effective_to = w.next_from
+        FROM (
+            SELECT id, LEAD(effective_from) OVER (PARTITION BY product_id ORDER BY effective_from, version) AS next_from
+            FROM product_versions WHERE product_id = ANY($1)
+        ) w
+        WHERE v.id = w.id AND v.effective_to IS DISTINCT FROM w.next_from
+        "#,
+    )
+    .bind(product_ids)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
 }
 
+/// An inventory rule as JSON, with the defaults the engine assumes filled in
+const INVENTORY_RULE_JSON: &str = "jsonb_build_object(\
+     'id', id, 'airline_id', airline_id, 'resource_type', resource_type, \
+     'hold_duration_seconds', COALESCE(hold_duration_seconds, 900), \
+     'overbooking_percentage', COALESCE(overbooking_percentage::FLOAT8, 0), \
+     'min_availability_threshold', COALESCE(min_availability_threshold, 0), \
+     'capacity', capacity, 'max_holds_per_customer', max_holds_per_customer, \
+     'blackout_dates', blackout_dates, 'is_active', is_active, 'updated_at', updated_at)";
+
+/// A route as JSON, with its airports' cities and countries and the market
+/// they make; selected from `r`, joined to its airports `o` and `d`
+const ROUTE_JSON: &str = "jsonb_build_object(\
+     'id', r.id, 'airline_id', r.airline_id, 'origin', r.origin, 'destination', r.destination, \
+     'origin_city', o.city_code, 'destination_city', d.city_code, \
+     'origin_country', o.country, 'destination_country', d.country, 'distance_km', r.distance_km, \
+     'market', CASE WHEN o.country = d.country THEN 'DOMESTIC' ELSE 'INTERNATIONAL' END, \
+     'sale_currency', r.sale_currency, 'is_active', r.is_active, 'created_at', r.created_at, 'updated_at', r.updated_at)";
+
+const ROUTE_AIRPORTS: &str = "JOIN airports o ON o.iata_code = r.origin JOIN airports d ON d.iata_code = r.destination";
+
+fn blackout_dates(rule: &Value) -> Result<Vec<chrono::NaiveDate>, Box<dyn std::error::Error + Send + Sync>> {
+    match rule.get("blackout_dates") {
+        Some(dates) if !dates.is_null() => Ok(serde_json::from_value(dates.clone())?),
+        _ => Ok(Vec::new()),
+    }
+}
 
 #[async_trait]
 impl ProductRepository for StoreProductRepository {
@@ -57,6 +215,7 @@ impl ProductRepository for StoreProductRepository {
         let is_active = product["is_active"].as_bool().unwrap_or(true);
         let metadata = &product["metadata"];
 
+        let mut tx = self.db.writer().begin().await?;
         sqlx::query!(
             r#"
             INSERT INTO products (id, airline_id, product_type, product_code, name, description, base_price_nuc, is_active, margin_percentage, metadata)
@@ -73,8 +232,10 @@ impl ProductRepository for StoreProductRepository {
             product["margin_percentage"].as_f64().unwrap_or(0.15),
             metadata
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        record_product_versions(&mut tx, &[product_id]).await?;
+        tx.commit().await?;
 
         Ok(product_id)
     }
@@ -83,33 +244,15 @@ impl ProductRepository for StoreProductRepository {
         &self,
         id: Uuid,
     ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
-        // Use query_as! for strict typing
-        let row = sqlx::query_as!(
-            ProductRow,
-            "SELECT id, airline_id, product_type, product_code, name, description, base_price_nuc, currency, is_active, margin_percentage::FLOAT8, metadata, created_at, updated_at FROM products WHERE id = $1",
-            id
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(row) = row {
-            let product_json = serde_json::json!({
-                "id": row.id,
-                "airline_id": row.airline_id,
-                "product_type": row.product_type,
-                "product_code": row.product_code,
-                "name": row.name,
-                "description": row.description,
-                "base_price_nuc": row.base_price_nuc,
-                "is_active": row.is_active,
-                "metadata": row.metadata,
-                "created_at": row.created_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339()),
-                "updated_at": row.updated_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339())
-            });
-            return Ok(Some(product_json));
-        }
+        self.fetch_product(&TenantContext::platform(), id).await
+    }
 
-        Ok(None)
+    async fn get_tenant_product(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_product(tenant, id).await
     }
 
     async fn list_products(
@@ -120,19 +263,19 @@ impl ProductRepository for StoreProductRepository {
         let products: Vec<ProductRow> = if let Some(pt) = product_type {
             sqlx::query_as!(
                 ProductRow,
-                "SELECT id, airline_id, product_type, product_code, name, description, base_price_nuc, currency, is_active, margin_percentage::FLOAT8, metadata, created_at, updated_at FROM products WHERE airline_id = $1 AND product_type = $2 ORDER BY name",
+                "SELECT id, airline_id, product_type, product_code, name, description, base_price_nuc, currency, is_active, margin_percentage::FLOAT8, metadata, version, created_at, updated_at, deleted_at FROM products WHERE airline_id = $1 AND product_type = $2 ORDER BY name",
                 airline_id,
                 pt
             )
-            .fetch_all(&self.pool)
+            .fetch_all(self.db.reader())
             .await?
         } else {
             sqlx::query_as!(
                 ProductRow,
-                "SELECT id, airline_id, product_type, product_code, name, description, base_price_nuc, currency, is_active, margin_percentage::FLOAT8, metadata, created_at, updated_at FROM products WHERE airline_id = $1 ORDER BY name",
+                "SELECT id, airline_id, product_type, product_code, name, description, base_price_nuc, currency, is_active, margin_percentage::FLOAT8, metadata, version, created_at, updated_at, deleted_at FROM products WHERE airline_id = $1 ORDER BY name",
                 airline_id
             )
-            .fetch_all(&self.pool)
+            .fetch_all(self.db.reader())
             .await?
         };
 
@@ -147,8 +290,10 @@ impl ProductRepository for StoreProductRepository {
                 "base_price_nuc": row.base_price_nuc,
                 "is_active": row.is_active,
                 "metadata": row.metadata,
+                "version": row.version,
                 "created_at": row.created_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339()),
-                "updated_at": row.updated_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339())
+                "updated_at": row.updated_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339()),
+                "deleted_at": row.deleted_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339())
             })
         }).collect();
 
@@ -166,11 +311,12 @@ impl ProductRepository for StoreProductRepository {
         let is_active = product["is_active"].as_bool().unwrap_or(true);
         let metadata = &product["metadata"];
 
+        let mut tx = self.db.writer().begin().await?;
         sqlx::query!(
             r#"
             UPDATE products 
             SET name = $1, description = $2, base_price_nuc = $3, is_active = $4, metadata = $5, updated_at = NOW()
-            WHERE id = $6
+            WHERE id = $6 AND deleted_at IS NULL
             "#,
             name,
             description,
@@ -179,8 +325,10 @@ impl ProductRepository for StoreProductRepository {
             metadata,
             id
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        record_product_versions(&mut tx, &[id]).await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -188,15 +336,438 @@ impl ProductRepository for StoreProductRepository {
     async fn delete_product(
         &self,
         id: Uuid,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        sqlx::query!(
-            "DELETE FROM products WHERE id = $1",
-            id
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        let deleted = sqlx::query("UPDATE products SET is_active = false, deleted_at = NOW(), updated_at = NOW() WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        record_product_versions(&mut tx, &[id]).await?;
+        tx.commit().await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn restore_product(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        let restored = sqlx::query("UPDATE products SET is_active = true, deleted_at = NULL, updated_at = NOW() WHERE id = $1 AND deleted_at IS NOT NULL")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        record_product_versions(&mut tx, &[id]).await?;
+        tx.commit().await?;
+        Ok(restored.rows_affected() > 0)
+    }
+
+    async fn count_product_references(
+        &self,
+        id: Uuid,
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        let references: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM order_items WHERE product_id = $1) + (SELECT COUNT(*) FROM offer_items WHERE product_id = $1)",
         )
-        .execute(&self.pool)
+        .bind(id)
+        .fetch_one(self.db.reader())
         .await?;
-        
-        Ok(())
+        Ok(references)
+    }
+
+    async fn purge_product(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let purged = sqlx::query(
+            r#"
+            DELETE FROM products
+            WHERE id = $1
+              AND NOT EXISTS (SELECT 1 FROM order_items WHERE product_id = $1)
+              AND NOT EXISTS (SELECT 1 FROM offer_items WHERE product_id = $1)
+            "#,
+        )
+        .bind(id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(purged.rows_affected() > 0)
+    }
+
+    async fn list_airline_product_content(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let content = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT to_jsonb(c) FROM product_content c
+            JOIN products p ON p.id = c.product_id
+            WHERE p.airline_id = $1 AND p.deleted_at IS NULL
+            "#,
+        )
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(content)
+    }
+
+    async fn list_product_content(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let content = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(c) FROM product_content c WHERE product_id = $1 ORDER BY locale",
+        )
+        .bind(product_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(content)
+    }
+
+    async fn upsert_product_content(
+        &self,
+        product_id: Uuid,
+        locale: &str,
+        content: &Value,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let saved = sqlx::query_scalar::<_, Value>(
+            r#"
+            INSERT INTO product_content (product_id, locale, name, description, media)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (product_id, locale) DO UPDATE
+            SET name = EXCLUDED.name, description = EXCLUDED.description, media = EXCLUDED.media, updated_at = NOW()
+            RETURNING to_jsonb(product_content)
+            "#,
+        )
+        .bind(product_id)
+        .bind(locale)
+        .bind(content["name"].as_str())
+        .bind(content["description"].as_str())
+        .bind(if content["media"].is_array() { content["media"].clone() } else { serde_json::json!([]) })
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(saved)
+    }
+
+    async fn delete_product_content(
+        &self,
+        product_id: Uuid,
+        locale: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let deleted = sqlx::query("DELETE FROM product_content WHERE product_id = $1 AND locale = $2")
+            .bind(product_id)
+            .bind(locale)
+            .execute(self.db.writer())
+            .await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn list_product_versions(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let versions: Vec<Value> = sqlx::query_scalar(&format!(
+            "SELECT to_jsonb(v) FROM (SELECT {} FROM product_versions WHERE product_id = $1 ORDER BY effective_from, version) v",
+            VERSION_COLUMNS
+        ))
+        .bind(product_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(versions)
+    }
+
+    async fn schedule_product_version(
+        &self,
+        product_id: Uuid,
+        version: &Value,
+        effective_from: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        // Lock the product so concurrent schedules don't take the same number
+        let exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM products WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+            .bind(product_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+
+        let scheduled: Value = sqlx::query_scalar(&format!(
+            r#"
+            WITH inserted AS (
+                INSERT INTO product_versions (product_id, version, name, description, base_price_nuc, metadata, is_active, effective_from)
+                SELECT p.id,
+                       (SELECT COALESCE(MAX(version), 0) + 1 FROM product_versions WHERE product_id = p.id),
+                       COALESCE($2, p.name), COALESCE($3, p.description), COALESCE($4, p.base_price_nuc),
+                       COALESCE($5, p.metadata), COALESCE($6, p.is_active), $7
+                FROM products p WHERE p.id = $1
+                RETURNING *
+            )
+            SELECT to_jsonb(v) FROM (SELECT {} FROM inserted) v
+            "#,
+            VERSION_COLUMNS
+        ))
+        .bind(product_id)
+        .bind(version["name"].as_str())
+        .bind(version["description"].as_str())
+        .bind(version["base_price_nuc"].as_i64().map(|price| price as i32))
+        .bind(version.get("metadata").filter(|m| !m.is_null()))
+        .bind(version["is_active"].as_bool())
+        .bind(effective_from)
+        .fetch_one(&mut *tx)
+        .await?;
+        refresh_version_windows(&mut tx, &[product_id]).await?;
+        tx.commit().await?;
+
+        Ok(Some(scheduled))
+    }
+
+    async fn cancel_product_version(
+        &self,
+        product_id: Uuid,
+        version: i32,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        let deleted = sqlx::query("DELETE FROM product_versions WHERE product_id = $1 AND version = $2 AND effective_from > NOW()")
+            .bind(product_id)
+            .bind(version)
+            .execute(&mut *tx)
+            .await?;
+        refresh_version_windows(&mut tx, &[product_id]).await?;
+        tx.commit().await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn apply_due_product_versions(
+        &self,
+    ) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
+        let changed: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            WITH in_force AS (
+                SELECT DISTINCT ON (product_id) product_id, version, name, description, base_price_nuc, metadata, is_active
+                FROM product_versions
+                WHERE effective_from <= NOW()
+                ORDER BY product_id, effective_from DESC, version DESC
+            )
+            UPDATE products p
+            SET version = v.version, name = v.name, description = v.description, base_price_nuc = v.base_price_nuc,
+                metadata = v.metadata, is_active = v.is_active, updated_at = NOW()
+            FROM in_force v
+            WHERE p.id = v.product_id AND p.version <> v.version AND p.deleted_at IS NULL
+            RETURNING p.id
+            "#,
+        )
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(changed)
+    }
+
+    async fn apply_bulk_price_update(
+        &self,
+        airline_id: Uuid,
+        request: &Value,
+        changes: &[(Uuid, i32, i32)],
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
+        let product_ids: Vec<Uuid> = changes.iter().map(|(id, _, _)| *id).collect();
+        let old_prices: Vec<i32> = changes.iter().map(|(_, old, _)| *old).collect();
+        let new_prices: Vec<i32> = changes.iter().map(|(_, _, new)| *new).collect();
+
+        let mut tx = self.db.writer().begin().await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE products p SET base_price_nuc = c.new_price, updated_at = NOW()
+            FROM UNNEST($2::uuid[], $3::int[], $4::int[]) AS c(product_id, old_price, new_price)
+            WHERE p.id = c.product_id AND p.airline_id = $1 AND p.base_price_nuc = c.old_price AND p.deleted_at IS NULL
+            "#,
+        )
+        .bind(airline_id)
+        .bind(&product_ids)
+        .bind(&old_prices)
+        .bind(&new_prices)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() != changes.len() as u64 {
+            return Ok(None);
+        }
+        record_product_versions(&mut tx, &product_ids).await?;
+
+        let recorded = serde_json::Value::Array(changes.iter().map(|(id, old, new)| serde_json::json!({
+            "product_id": id,
+            "old_price_nuc": old,
+            "new_price_nuc": new,
+        })).collect());
+        let id: Uuid = sqlx::query_scalar("INSERT INTO bulk_price_updates (airline_id, request, changes) VALUES ($1, $2, $3) RETURNING id")
+            .bind(airline_id)
+            .bind(request)
+            .bind(&recorded)
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(Some(id))
+    }
+
+    async fn rollback_bulk_price_update(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        let changes: Option<Value> = sqlx::query_scalar("SELECT changes FROM bulk_price_updates WHERE id = $1 AND rolled_back_at IS NULL FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(changes) = changes else {
+            return Ok(None);
+        };
+
+        let restored: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE products p SET base_price_nuc = c.old_price_nuc, updated_at = NOW()
+            FROM jsonb_to_recordset($1) AS c(product_id UUID, old_price_nuc INT, new_price_nuc INT)
+            WHERE p.id = c.product_id AND p.base_price_nuc = c.new_price_nuc AND p.deleted_at IS NULL
+            RETURNING p.id
+            "#,
+        )
+        .bind(&changes)
+        .fetch_all(&mut *tx)
+        .await?;
+        record_product_versions(&mut tx, &restored).await?;
+        sqlx::query("UPDATE bulk_price_updates SET rolled_back_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let skipped: Vec<Value> = changes.as_array().into_iter().flatten()
+            .map(|change| change["product_id"].clone())
+            .filter(|product_id| !restored.iter().any(|id| product_id.as_str() == Some(id.to_string().as_str())))
+            .collect();
+        Ok(Some(serde_json::json!({
+            "id": id,
+            "restored": restored,
+            "skipped": skipped,
+        })))
+    }
+
+    async fn list_campaigns(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let campaigns: Vec<Value> = sqlx::query_scalar("SELECT to_jsonb(c) FROM campaigns c WHERE airline_id = $1 ORDER BY starts_at DESC, created_at DESC")
+            .bind(airline_id)
+            .fetch_all(self.db.reader())
+            .await?;
+        Ok(campaigns)
+    }
+
+    async fn list_active_campaigns(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let campaigns: Vec<Value> = sqlx::query_scalar("SELECT to_jsonb(c) FROM campaigns c WHERE airline_id = $1 AND status = 'ACTIVE'")
+            .bind(airline_id)
+            .fetch_all(self.db.reader())
+            .await?;
+        Ok(campaigns)
+    }
+
+    async fn get_campaign(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let campaign: Option<Value> = sqlx::query_scalar("SELECT to_jsonb(c) FROM campaigns c WHERE id = $1")
+            .bind(id)
+            .fetch_optional(self.db.reader())
+            .await?;
+        Ok(campaign)
+    }
+
+    async fn create_campaign(
+        &self,
+        airline_id: Uuid,
+        campaign: &Value,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let created: Value = sqlx::query_scalar(&format!(
+            r#"
+            WITH c AS (
+                INSERT INTO campaigns (airline_id, name, description, discount_percentage, product_types, origin, destination, starts_at, ends_at, banner, status)
+                VALUES ($1, $2, $3, $4::FLOAT8::NUMERIC, $5, $6, $7, $8, $9, $10, {})
+                RETURNING *
+            )
+            SELECT to_jsonb(c) FROM c
+            "#,
+            campaign_status_now("$8", "$9")
+        ))
+        .bind(airline_id)
+        .bind(campaign["name"].as_str())
+        .bind(campaign["description"].as_str())
+        .bind(campaign["discount_percentage"].as_f64())
+        .bind(campaign_product_types(campaign))
+        .bind(campaign["origin"].as_str())
+        .bind(campaign["destination"].as_str())
+        .bind(campaign_time(campaign, "starts_at")?)
+        .bind(campaign_time(campaign, "ends_at")?)
+        .bind(campaign.get("banner").filter(|b| !b.is_null()).cloned().unwrap_or_else(|| serde_json::json!({})))
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(created)
+    }
+
+    async fn update_campaign(
+        &self,
+        id: Uuid,
+        campaign: &Value,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let updated: Option<Value> = sqlx::query_scalar(&format!(
+            r#"
+            WITH c AS (
+                UPDATE campaigns
+                SET name = $2, description = $3, discount_percentage = $4::FLOAT8::NUMERIC, product_types = $5,
+                    origin = $6, destination = $7, starts_at = $8, ends_at = $9, banner = $10,
+                    status = {}, updated_at = NOW()
+                WHERE id = $1 AND status IN ('SCHEDULED', 'ACTIVE')
+                RETURNING *
+            )
+            SELECT to_jsonb(c) FROM c
+            "#,
+            campaign_status_now("$8", "$9")
+        ))
+        .bind(id)
+        .bind(campaign["name"].as_str())
+        .bind(campaign["description"].as_str())
+        .bind(campaign["discount_percentage"].as_f64())
+        .bind(campaign_product_types(campaign))
+        .bind(campaign["origin"].as_str())
+        .bind(campaign["destination"].as_str())
+        .bind(campaign_time(campaign, "starts_at")?)
+        .bind(campaign_time(campaign, "ends_at")?)
+        .bind(campaign.get("banner").filter(|b| !b.is_null()).cloned().unwrap_or_else(|| serde_json::json!({})))
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(updated)
+    }
+
+    async fn cancel_campaign(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let cancelled = sqlx::query("UPDATE campaigns SET status = 'CANCELLED', updated_at = NOW() WHERE id = $1 AND status IN ('SCHEDULED', 'ACTIVE')")
+            .bind(id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(cancelled.rows_affected() > 0)
+    }
+
+    async fn sync_campaign_statuses(
+        &self,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let changed = sqlx::query(&format!(
+            r#"
+            UPDATE campaigns SET status = {0}, updated_at = NOW()
+            WHERE status IN ('SCHEDULED', 'ACTIVE') AND status <> {0}
+            "#,
+            campaign_status_now("starts_at", "ends_at")
+        ))
+        .execute(self.db.writer())
+        .await?;
+        Ok(changed.rows_affected())
     }
 
     async fn get_airline_by_code(
@@ -207,7 +778,7 @@ impl ProductRepository for StoreProductRepository {
             "SELECT id, code, name, country, status FROM airlines WHERE code = $1",
             code
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(self.db.writer())
         .await?;
 
         if let Some(row) = row {
@@ -233,7 +804,7 @@ impl ProductRepository for StoreProductRepository {
             airline_id,
             resource_type
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(self.db.writer())
         .await?;
 
         if let Some(row) = row {
@@ -252,4 +823,645 @@ impl ProductRepository for StoreProductRepository {
 
         Ok(None)
     }
+
+    async fn get_business_rule(&self, airline_id: Uuid, rule_type: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let config: Option<Value> = sqlx::query_scalar(
+            r#"
+            SELECT rule_config
+            FROM business_rules
+            WHERE airline_id = $1 AND rule_type = $2 AND is_active = true
+              AND (valid_from IS NULL OR valid_from <= NOW())
+              AND (valid_until IS NULL OR valid_until > NOW())
+            ORDER BY priority DESC NULLS LAST, created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(airline_id)
+        .bind(rule_type)
+        .fetch_optional(self.db.reader())
+        .await?;
+        Ok(config)
+    }
+
+    async fn put_airline_business_rules(&self, airline_id: Uuid, overrides: &Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            r#"
+            INSERT INTO business_rules (airline_id, rule_type, rule_name, rule_config)
+            VALUES ($1, 'CONFIG', 'Airline business rules', $2)
+            ON CONFLICT (airline_id) WHERE rule_type = 'CONFIG' AND is_active
+            DO UPDATE SET rule_config = EXCLUDED.rule_config, updated_at = NOW()
+            "#,
+        )
+        .bind(airline_id)
+        .bind(overrides)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn clear_airline_business_rules(&self, airline_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query("UPDATE business_rules SET is_active = false, updated_at = NOW() WHERE airline_id = $1 AND rule_type = 'CONFIG' AND is_active")
+            .bind(airline_id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_inventory_rules(&self, airline_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rules: Vec<Value> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT DISTINCT ON (resource_type) {}
+            FROM inventory_rules
+            WHERE airline_id = $1 AND is_active = true
+            ORDER BY resource_type, created_at DESC, id DESC
+            "#,
+            INVENTORY_RULE_JSON
+        ))
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(rules)
+    }
+
+    async fn get_inventory_rule_by_id(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rule: Option<Value> = sqlx::query_scalar(&format!("SELECT {} FROM inventory_rules WHERE id = $1", INVENTORY_RULE_JSON))
+            .bind(id)
+            .fetch_optional(self.db.reader())
+            .await?;
+        Ok(rule)
+    }
+
+    async fn create_inventory_rule(&self, airline_id: Uuid, rule: &Value) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let created: Option<Value> = sqlx::query_scalar(&format!(
+            r#"
+            INSERT INTO inventory_rules (airline_id, resource_type, hold_duration_seconds, overbooking_percentage,
+                                         min_availability_threshold, capacity, max_holds_per_customer, blackout_dates)
+            SELECT $1, $2, $3, $4::FLOAT8::NUMERIC, $5, $6, $7, $8
+            WHERE NOT EXISTS (SELECT 1 FROM inventory_rules WHERE airline_id = $1 AND resource_type = $2 AND is_active = true)
+            RETURNING {}
+            "#,
+            INVENTORY_RULE_JSON
+        ))
+        .bind(airline_id)
+        .bind(rule["resource_type"].as_str().ok_or("missing resource_type")?)
+        .bind(rule["hold_duration_seconds"].as_i64().unwrap_or(900) as i32)
+        .bind(rule["overbooking_percentage"].as_f64().unwrap_or(0.0))
+        .bind(rule["min_availability_threshold"].as_i64().unwrap_or(0) as i32)
+        .bind(rule["capacity"].as_i64().map(|c| c as i32))
+        .bind(rule["max_holds_per_customer"].as_i64().map(|m| m as i32))
+        .bind(blackout_dates(rule)?)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(created)
+    }
+
+    async fn update_inventory_rule(&self, id: Uuid, rule: &Value) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let updated: Option<Value> = sqlx::query_scalar(&format!(
+            r#"
+            UPDATE inventory_rules
+            SET hold_duration_seconds = $2, overbooking_percentage = $3::FLOAT8::NUMERIC, min_availability_threshold = $4,
+                capacity = $5, max_holds_per_customer = $6, blackout_dates = $7, updated_at = NOW()
+            WHERE id = $1 AND is_active = true
+            RETURNING {}
+            "#,
+            INVENTORY_RULE_JSON
+        ))
+        .bind(id)
+        .bind(rule["hold_duration_seconds"].as_i64().unwrap_or(900) as i32)
+        .bind(rule["overbooking_percentage"].as_f64().unwrap_or(0.0))
+        .bind(rule["min_availability_threshold"].as_i64().unwrap_or(0) as i32)
+        .bind(rule["capacity"].as_i64().map(|c| c as i32))
+        .bind(rule["max_holds_per_customer"].as_i64().map(|m| m as i32))
+        .bind(blackout_dates(rule)?)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(updated)
+    }
+
+    async fn deactivate_inventory_rule(&self, id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query("UPDATE inventory_rules SET is_active = false, updated_at = NOW() WHERE id = $1 AND is_active = true")
+            .bind(id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_tax_codes(&self) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, TaxCodeRow>(
+            r#"
+            SELECT id, code, name, jurisdiction, product_type, origin_country, destination_country, market,
+                   rate::FLOAT8 AS rate, amount_nuc
+            FROM tax_codes
+            WHERE is_active = true
+            ORDER BY code
+            "#,
+        )
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            serde_json::json!({
+                "id": row.id,
+                "code": row.code,
+                "name": row.name,
+                "jurisdiction": row.jurisdiction,
+                "product_type": row.product_type,
+                "origin_country": row.origin_country,
+                "destination_country": row.destination_country,
+                "market": row.market,
+                "rate": row.rate,
+                "amount_nuc": row.amount_nuc,
+            })
+        }).collect())
+    }
+
+    async fn create_tax_code(&self, tax_code: &Value) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>> {
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO tax_codes (code, name, jurisdiction, product_type, origin_country, destination_country, rate, amount_nuc, market)
+            VALUES ($1, $2, $3, $4, $5, $6, $7::FLOAT8::NUMERIC, $8, $9)
+            RETURNING id
+            "#,
+        )
+        .bind(tax_code["code"].as_str())
+        .bind(tax_code["name"].as_str())
+        .bind(tax_code["jurisdiction"].as_str())
+        .bind(tax_code["product_type"].as_str())
+        .bind(tax_code["origin_country"].as_str())
+        .bind(tax_code["destination_country"].as_str())
+        .bind(tax_code["rate"].as_f64())
+        .bind(tax_code["amount_nuc"].as_i64().map(|amount| amount as i32))
+        .bind(tax_code["market"].as_str())
+        .fetch_one(self.db.writer())
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn list_airport_countries(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT iata_code, country FROM airports")
+            .fetch_all(self.db.reader())
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn get_airport(&self, iata_code: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let row: Option<(String, String, Option<String>, Option<f64>, Option<f64>)> = sqlx::query_as(
+            "SELECT iata_code, country, city_code, latitude, longitude FROM airports WHERE iata_code = $1",
+        )
+        .bind(iata_code)
+        .fetch_optional(self.db.reader())
+        .await?;
+
+        Ok(row.map(|(iata_code, country, city_code, latitude, longitude)| serde_json::json!({
+            "iata_code": iata_code,
+            "country": country,
+            "city_code": city_code,
+            "latitude": latitude,
+            "longitude": longitude,
+        })))
+    }
+
+    async fn list_airports(&self) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let airports: Vec<Value> = sqlx::query_scalar(
+            "SELECT jsonb_build_object('iata_code', iata_code, 'name', name, 'city_code', city_code, 'country', country, \
+             'latitude', latitude, 'longitude', longitude) FROM airports ORDER BY iata_code",
+        )
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(airports)
+    }
+
+    async fn list_routes(&self, airline_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let routes: Vec<Value> = sqlx::query_scalar(&format!(
+            "SELECT {} FROM routes r {} WHERE r.airline_id = $1 ORDER BY r.origin, r.destination, r.created_at",
+            ROUTE_JSON, ROUTE_AIRPORTS
+        ))
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(routes)
+    }
+
+    async fn get_route(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let route: Option<Value> = sqlx::query_scalar(&format!("SELECT {} FROM routes r {} WHERE r.id = $1", ROUTE_JSON, ROUTE_AIRPORTS))
+            .bind(id)
+            .fetch_optional(self.db.reader())
+            .await?;
+        Ok(route)
+    }
+
+    async fn create_route(&self, airline_id: Uuid, route: &Value) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let created: Option<Value> = sqlx::query_scalar(&format!(
+            r#"
+            WITH r AS (
+                INSERT INTO routes (airline_id, origin, destination, distance_km, sale_currency)
+                SELECT $1, $2, $3, $4, $5
+                WHERE NOT EXISTS (SELECT 1 FROM routes WHERE airline_id = $1 AND origin = $2 AND destination = $3 AND is_active = true)
+                RETURNING *
+            )
+            SELECT {} FROM r {}
+            "#,
+            ROUTE_JSON, ROUTE_AIRPORTS
+        ))
+        .bind(airline_id)
+        .bind(route["origin"].as_str().ok_or("missing origin")?)
+        .bind(route["destination"].as_str().ok_or("missing destination")?)
+        .bind(route["distance_km"].as_i64().ok_or("missing distance_km")? as i32)
+        .bind(route["sale_currency"].as_str().ok_or("missing sale_currency")?)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(created)
+    }
+
+    async fn update_route(&self, id: Uuid, route: &Value) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let updated: Option<Value> = sqlx::query_scalar(&format!(
+            r#"
+            WITH r AS (
+                UPDATE routes SET distance_km = $2, sale_currency = $3, updated_at = NOW()
+                WHERE id = $1 AND is_active = true
+                RETURNING *
+            )
+            SELECT {} FROM r {}
+            "#,
+            ROUTE_JSON, ROUTE_AIRPORTS
+        ))
+        .bind(id)
+        .bind(route["distance_km"].as_i64().ok_or("missing distance_km")? as i32)
+        .bind(route["sale_currency"].as_str().ok_or("missing sale_currency")?)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(updated)
+    }
+
+    async fn deactivate_route(&self, id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query("UPDATE routes SET is_active = false, updated_at = NOW() WHERE id = $1 AND is_active = true")
+            .bind(id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_active_airlines(&self) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(Uuid, String, String, Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT id, code, name, country, accounting_code FROM airlines WHERE status = 'ACTIVE' ORDER BY code",
+        )
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|(id, code, name, country, accounting_code)| serde_json::json!({
+            "id": id,
+            "code": code,
+            "name": name,
+            "country": country,
+            "accounting_code": accounting_code,
+        })).collect())
+    }
+
+    async fn upsert_airline(&self, code: &str, name: &str) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>> {
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO airlines (code, name) VALUES ($1, $2)
+            ON CONFLICT (code) DO UPDATE SET name = EXCLUDED.name, status = 'ACTIVE', updated_at = NOW()
+            RETURNING id
+            "#,
+        )
+        .bind(code)
+        .bind(name)
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(id)
+    }
+
+    async fn list_pricing_rules(&self, airline_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(Uuid, Option<Uuid>, String, String, Value, Value, Option<i32>)> = sqlx::query_as(
+            r#"
+            SELECT id, product_id, rule_name, rule_type, conditions, adjustments, priority
+            FROM pricing_rules
+            WHERE airline_id = $1 AND is_active = true
+            ORDER BY priority DESC, rule_name
+            "#,
+        )
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|(id, product_id, rule_name, rule_type, conditions, adjustments, priority)| serde_json::json!({
+            "id": id,
+            "airline_id": airline_id,
+            "product_id": product_id,
+            "rule_name": rule_name,
+            "rule_type": rule_type,
+            "conditions": conditions,
+            "adjustments": adjustments,
+            "priority": priority.unwrap_or(0),
+            "is_active": true,
+        })).collect())
+    }
+
+    async fn export_catalog(&self, airline_id: Uuid) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let products: Vec<(String, String, String, Option<String>, i32, Option<Value>)> = sqlx::query_as(
+            r#"
+            SELECT product_type, product_code, name, description, base_price_nuc, metadata
+            FROM products
+            WHERE airline_id = $1 AND is_active = true
+            ORDER BY product_code
+            "#,
+        )
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        // Rules point at products by code; ids differ between environments
+        let pricing_rules: Vec<(String, String, Option<String>, Value, Value, Option<i32>)> = sqlx::query_as(
+            r#"
+            SELECT r.rule_name, r.rule_type, p.product_code, r.conditions, r.adjustments, r.priority
+            FROM pricing_rules r
+            LEFT JOIN products p ON p.id = r.product_id
+            WHERE r.airline_id = $1 AND r.is_active = true
+            ORDER BY r.rule_name
+            "#,
+        )
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        let bundles: Vec<(String, String, Value, Option<f64>, Option<i32>)> = sqlx::query_as(
+            r#"
+            SELECT bundle_name, bundle_type, product_types, discount_percentage::FLOAT8, priority
+            FROM bundle_templates
+            WHERE airline_id = $1 AND is_active = true
+            ORDER BY bundle_name
+            "#,
+        )
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        let inventory_rules: Vec<(String, Option<i32>, Option<f64>, Option<i32>, Option<bool>, Option<bool>, Option<i32>, Option<i32>, Vec<chrono::NaiveDate>)> = sqlx::query_as(
+            r#"
+            SELECT resource_type, hold_duration_seconds, overbooking_percentage::FLOAT8, min_availability_threshold,
+                   auto_release_on_expiry, notify_on_low_inventory, capacity, max_holds_per_customer, blackout_dates
+            FROM inventory_rules
+            WHERE airline_id = $1 AND is_active = true
+            ORDER BY resource_type
+            "#,
+        )
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(serde_json::json!({
+            "products": products.into_iter().map(|(product_type, product_code, name, description, base_price_nuc, metadata)| serde_json::json!({
+                "product_type": product_type,
+                "product_code": product_code,
+                "name": name,
+                "description": description,
+                "base_price_nuc": base_price_nuc,
+                "metadata": metadata.unwrap_or_else(|| serde_json::json!({})),
+            })).collect::<Vec<_>>(),
+            "pricing_rules": pricing_rules.into_iter().map(|(rule_name, rule_type, product_code, conditions, adjustments, priority)| serde_json::json!({
+                "rule_name": rule_name,
+                "rule_type": rule_type,
+                "product_code": product_code,
+                "conditions": conditions,
+                "adjustments": adjustments,
+                "priority": priority.unwrap_or(0),
+            })).collect::<Vec<_>>(),
+            "bundles": bundles.into_iter().map(|(bundle_name, bundle_type, product_types, discount_percentage, priority)| serde_json::json!({
+                "bundle_name": bundle_name,
+                "bundle_type": bundle_type,
+                "product_types": product_types,
+                "discount_percentage": discount_percentage.unwrap_or(0.0),
+                "priority": priority.unwrap_or(0),
+            })).collect::<Vec<_>>(),
+            "inventory_rules": inventory_rules.into_iter().map(|(resource_type, hold, overbooking, threshold, auto_release, notify, capacity, max_holds, blackout_dates)| serde_json::json!({
+                "resource_type": resource_type,
+                "hold_duration_seconds": hold.unwrap_or(900),
+                "overbooking_percentage": overbooking.unwrap_or(0.0),
+                "min_availability_threshold": threshold.unwrap_or(0),
+                "auto_release_on_expiry": auto_release.unwrap_or(true),
+                "notify_on_low_inventory": notify.unwrap_or(false),
+                "capacity": capacity,
+                "max_holds_per_customer": max_holds,
+                "blackout_dates": blackout_dates,
+            })).collect::<Vec<_>>(),
+        }))
+    }
+
+    async fn apply_catalog(&self, airline_id: Uuid, bundle: &Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let entries = |section: &str| bundle[section].as_array().cloned().unwrap_or_default();
+        let mut tx = self.db.writer().begin().await?;
+
+        // Products: upsert by code, retire the ones the bundle no longer has
+        let mut product_codes = Vec::new();
+        for product in entries("products") {
+            let code = product["product_code"].as_str().ok_or("Product without product_code")?.to_string();
+            sqlx::query(
+                r#"
+                INSERT INTO products (airline_id, product_type, product_code, name, description, base_price_nuc, metadata, is_active)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, true)
+                ON CONFLICT (airline_id, product_code) DO UPDATE
+                SET product_type = EXCLUDED.product_type, name = EXCLUDED.name, description = EXCLUDED.description,
+                    base_price_nuc = EXCLUDED.base_price_nuc, metadata = EXCLUDED.metadata, is_active = true, deleted_at = NULL, updated_at = NOW()
+                "#,
+            )
+            .bind(airline_id)
+            .bind(product["product_type"].as_str())
+            .bind(&code)
+            .bind(product["name"].as_str())
+            .bind(product["description"].as_str())
+            .bind(product["base_price_nuc"].as_i64().map(|price| price as i32))
+            .bind(&product["metadata"])
+            .execute(&mut *tx)
+            .await?;
+            product_codes.push(code);
+        }
+        sqlx::query("UPDATE products SET is_active = false, updated_at = NOW() WHERE airline_id = $1 AND is_active = true AND product_code <> ALL($2)")
+            .bind(airline_id)
+            .bind(&product_codes)
+            .execute(&mut *tx)
+            .await?;
+        let product_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM products WHERE airline_id = $1")
+            .bind(airline_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        record_product_versions(&mut tx, &product_ids).await?;
+
+        // Pricing rules have no unique key; the newest rule with a name is the one updated
+        let mut rule_names = Vec::new();
+        for rule in entries("pricing_rules") {
+            let name = rule["rule_name"].as_str().ok_or("Pricing rule without rule_name")?.to_string();
+            let product_id: Option<Uuid> = match rule["product_code"].as_str() {
+                Some(code) => Some(
+                    sqlx::query_scalar("SELECT id FROM products WHERE airline_id = $1 AND product_code = $2")
+                        .bind(airline_id)
+                        .bind(code)
+                        .fetch_optional(&mut *tx)
+                        .await?
+                        .ok_or_else(|| format!("Pricing rule '{}' references unknown product {}", name, code))?,
+                ),
+                None => None,
+            };
+            let updated = sqlx::query(
+                r#"
+                UPDATE pricing_rules
+                SET rule_type = $3, product_id = $4, conditions = $5, adjustments = $6, priority = $7, is_active = true, updated_at = NOW()
+                WHERE id = (SELECT id FROM pricing_rules WHERE airline_id = $1 AND rule_name = $2 ORDER BY created_at DESC, id DESC LIMIT 1)
+                "#,
+            )
+            .bind(airline_id)
+            .bind(&name)
+            .bind(rule["rule_type"].as_str())
+            .bind(product_id)
+            .bind(&rule["conditions"])
+            .bind(&rule["adjustments"])
+            .bind(rule["priority"].as_i64().unwrap_or(0) as i32)
+            .execute(&mut *tx)
+            .await?;
+            if updated.rows_affected() == 0 {
+                sqlx::query(
+                    r#"
+                    INSERT INTO pricing_rules (airline_id, rule_name, rule_type, product_id, conditions, adjustments, priority)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    "#,
+                )
+                .bind(airline_id)
+                .bind(&name)
+                .bind(rule["rule_type"].as_str())
+                .bind(product_id)
+                .bind(&rule["conditions"])
+                .bind(&rule["adjustments"])
+                .bind(rule["priority"].as_i64().unwrap_or(0) as i32)
+                .execute(&mut *tx)
+                .await?;
+            }
+            rule_names.push(name);
+        }
+        // Also retires older duplicates of a kept name
+        sqlx::query(
+            r#"
+            UPDATE pricing_rules SET is_active = false, updated_at = NOW()
+            WHERE airline_id = $1 AND is_active = true
+              AND (rule_name <> ALL($2) OR id <> (SELECT id FROM pricing_rules r WHERE r.airline_id = $1 AND r.rule_name = pricing_rules.rule_name ORDER BY created_at DESC, id DESC LIMIT 1))
+            "#,
+        )
+        .bind(airline_id)
+        .bind(&rule_names)
+        .execute(&mut *tx)
+        .await?;
+
+        // Bundle templates, keyed by name the same way
+        let mut bundle_names = Vec::new();
+        for template in entries("bundles") {
+            let name = template["bundle_name"].as_str().ok_or("Bundle without bundle_name")?.to_string();
+            let updated = sqlx::query(
+                r#"
+                UPDATE bundle_templates
+                SET bundle_type = $3, product_types = $4, discount_percentage = $5::FLOAT8::NUMERIC, priority = $6, is_active = true, updated_at = NOW()
+                WHERE id = (SELECT id FROM bundle_templates WHERE airline_id = $1 AND bundle_name = $2 ORDER BY created_at DESC, id DESC LIMIT 1)
+                "#,
+            )
+            .bind(airline_id)
+            .bind(&name)
+            .bind(template["bundle_type"].as_str())
+            .bind(&template["product_types"])
+            .bind(template["discount_percentage"].as_f64().unwrap_or(0.0))
+            .bind(template["priority"].as_i64().unwrap_or(0) as i32)
+            .execute(&mut *tx)
+            .await?;
+            if updated.rows_affected() == 0 {
+                sqlx::query(
+                    r#"
+                    INSERT INTO bundle_templates (airline_id, bundle_name, bundle_type, product_types, discount_percentage, priority)
+                    VALUES ($1, $2, $3, $4, $5::FLOAT8::NUMERIC, $6)
+                    "#,
+                )
+                .bind(airline_id)
+                .bind(&name)
+                .bind(template["bundle_type"].as_str())
+                .bind(&template["product_types"])
+                .bind(template["discount_percentage"].as_f64().unwrap_or(0.0))
+                .bind(template["priority"].as_i64().unwrap_or(0) as i32)
+                .execute(&mut *tx)
+                .await?;
+            }
+            bundle_names.push(name);
+        }
+        sqlx::query(
+            r#"
+            UPDATE bundle_templates SET is_active = false, updated_at = NOW()
+            WHERE airline_id = $1 AND is_active = true
+              AND (bundle_name <> ALL($2) OR id <> (SELECT id FROM bundle_templates b WHERE b.airline_id = $1 AND b.bundle_name = bundle_templates.bundle_name ORDER BY created_at DESC, id DESC LIMIT 1))
+            "#,
+        )
+        .bind(airline_id)
+        .bind(&bundle_names)
+        .execute(&mut *tx)
+        .await?;
+
+        // Inventory rules, one per resource type
+        let mut resource_types = Vec::new();
+        for rule in entries("inventory_rules") {
+            let resource_type = rule["resource_type"].as_str().ok_or("Inventory rule without resource_type")?.to_string();
+            let updated = sqlx::query(
+                r#"
+                UPDATE inventory_rules
+                SET hold_duration_seconds = $3, overbooking_percentage = $4::FLOAT8::NUMERIC, min_availability_threshold = $5,
+                    auto_release_on_expiry = $6, notify_on_low_inventory = $7, capacity = $8, max_holds_per_customer = $9,
+                    blackout_dates = $10, is_active = true, updated_at = NOW()
+                WHERE id = (SELECT id FROM inventory_rules WHERE airline_id = $1 AND resource_type = $2 ORDER BY created_at DESC, id DESC LIMIT 1)
+                "#,
+            )
+            .bind(airline_id)
+            .bind(&resource_type)
+            .bind(rule["hold_duration_seconds"].as_i64().unwrap_or(900) as i32)
+            .bind(rule["overbooking_percentage"].as_f64().unwrap_or(0.0))
+            .bind(rule["min_availability_threshold"].as_i64().unwrap_or(0) as i32)
+            .bind(rule["auto_release_on_expiry"].as_bool().unwrap_or(true))
+            .bind(rule["notify_on_low_inventory"].as_bool().unwrap_or(false))
+            .bind(rule["capacity"].as_i64().map(|c| c as i32))
+            .bind(rule["max_holds_per_customer"].as_i64().map(|m| m as i32))
+            .bind(blackout_dates(&rule)?)
+            .execute(&mut *tx)
+            .await?;
+            if updated.rows_affected() == 0 {
+                sqlx::query(
+                    r#"
+                    INSERT INTO inventory_rules (airline_id, resource_type, hold_duration_seconds, overbooking_percentage,
+                                                 min_availability_threshold, auto_release_on_expiry, notify_on_low_inventory, capacity,
+                                                 max_holds_per_customer, blackout_dates)
+                    VALUES ($1, $2, $3, $4::FLOAT8::NUMERIC, $5, $6, $7, $8, $9, $10)
+                    "#,
+                )
+                .bind(airline_id)
+                .bind(&resource_type)
+                .bind(rule["hold_duration_seconds"].as_i64().unwrap_or(900) as i32)
+                .bind(rule["overbooking_percentage"].as_f64().unwrap_or(0.0))
+                .bind(rule["min_availability_threshold"].as_i64().unwrap_or(0) as i32)
+                .bind(rule["auto_release_on_expiry"].as_bool().unwrap_or(true))
+                .bind(rule["notify_on_low_inventory"].as_bool().unwrap_or(false))
+                .bind(rule["capacity"].as_i64().map(|c| c as i32))
+                .bind(rule["max_holds_per_customer"].as_i64().map(|m| m as i32))
+                .bind(blackout_dates(&rule)?)
+                .execute(&mut *tx)
+                .await?;
+            }
+            resource_types.push(resource_type);
+        }
+        sqlx::query(
+            r#"
+            UPDATE inventory_rules SET is_active = false, updated_at = NOW()
+            WHERE airline_id = $1 AND is_active = true
+              AND (resource_type <> ALL($2) OR id <> (SELECT id FROM inventory_rules i WHERE i.airline_id = $1 AND i.resource_type = inventory_rules.resource_type ORDER BY created_at DESC, id DESC LIMIT 1))
+            "#,
+        )
+        .bind(airline_id)
+        .bind(&resource_types)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
 }
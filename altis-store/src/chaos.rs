@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::ChaosConfig;
+
+/// Dependencies faults can be injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChaosTarget {
+    Redis,
+    Postgres,
+    Payment,
+    Ml,
+}
+
+impl ChaosTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChaosTarget::Redis => "redis",
+            ChaosTarget::Postgres => "postgres",
+            ChaosTarget::Payment => "payment",
+            ChaosTarget::Ml => "ml",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "redis" => Some(ChaosTarget::Redis),
+            "postgres" => Some(ChaosTarget::Postgres),
+            "payment" => Some(ChaosTarget::Payment),
+            "ml" => Some(ChaosTarget::Ml),
+            _ => None,
+        }
+    }
+}
+
+/// Fault profile for one dependency. Latency and errors are rolled
+/// independently, so a call can be both slow and failed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChaosFault {
+    /// Chance (0..=1) that a call is delayed by `latency_ms`
+    #[serde(default)]
+    pub latency_probability: f64,
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Chance (0..=1) that a call fails
+    #[serde(default)]
+    pub error_probability: f64,
+}
+
+impl ChaosFault {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, p) in [("latency_probability", self.latency_probability), ("error_probability", self.error_probability)] {
+            if !(0.0..=1.0).contains(&p) {
+                return Err(format!("{} must be between 0 and 1", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A failure the injector decided on
+#[derive(Debug)]
+pub struct ChaosError(pub ChaosTarget);
+
+impl fmt::Display for ChaosError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fault injected into {} by chaos hook", self.0.as_str())
+    }
+}
+
+impl std::error::Error for ChaosError {}
+
+/// Injects latency and errors into dependency clients so circuit breakers,
+/// fallbacks and sagas can be exercised outside production. Faults are set
+/// at runtime by admins; with chaos disabled in config every call passes
+/// straight through and faults can't be set.
+#[derive(Debug, Default)]
+pub struct ChaosInjector {
+    enabled: bool,
+    faults: RwLock<HashMap<ChaosTarget, ChaosFault>>,
+}
+
+impl ChaosInjector {
+    pub fn new(config: &ChaosConfig) -> Self {
+        Self { enabled: config.enabled, faults: RwLock::new(HashMap::new()) }
+    }
+
+    /// An injector that never injects, for clients built without one
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_fault(&self, target: ChaosTarget, fault: ChaosFault) -> Result<(), String> {
+        if !self.enabled {
+            return Err("Chaos injection is disabled in this environment".to_string());
+        }
+        fault.validate()?;
+        self.faults.write().unwrap_or_else(|e| e.into_inner()).insert(target, fault);
+        Ok(())
+    }
+
+    /// Removes the fault on `target`, or every fault when None
+    pub fn clear(&self, target: Option<ChaosTarget>) {
+        let mut faults = self.faults.write().unwrap_or_else(|e| e.into_inner());
+        match target {
+            Some(target) => { faults.remove(&target); }
+            None => faults.clear(),
+        }
+    }
+
+    pub fn faults(&self) -> HashMap<ChaosTarget, ChaosFault> {
+        self.faults.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Rolls the dice for one call: the delay to apply and whether it fails.
+    fn roll(&self, target: ChaosTarget) -> (Option<Duration>, bool) {
+        if !self.enabled {
+            return (None, false);
+        }
+        let Some(fault) = self.faults.read().unwrap_or_else(|e| e.into_inner()).get(&target).cloned() else {
+            return (None, false);
+        };
+
+        let mut rng = rand::thread_rng();
+        let delay = (fault.latency_ms > 0 && rng.gen_bool(fault.latency_probability))
+            .then(|| Duration::from_millis(fault.latency_ms));
+        (delay, rng.gen_bool(fault.error_probability))
+    }
+
+    /// Called by a client before each operation on `target`
+    pub async fn inject(&self, target: ChaosTarget) -> Result<(), ChaosError> {
+        let (delay, fail) = self.roll(target);
+        if let Some(delay) = delay {
+            tracing::debug!("Chaos: delaying {} call by {:?}", target.as_str(), delay);
+            tokio::time::sleep(delay).await;
+        }
+        if fail {
+            tracing::debug!("Chaos: failing {} call", target.as_str());
+            return Err(ChaosError(target));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_faults_only_apply_when_enabled() {
+        let always_fail = ChaosFault { error_probability: 1.0, ..Default::default() };
+
+        let disabled = ChaosInjector::disabled();
+        assert!(disabled.set_fault(ChaosTarget::Redis, always_fail.clone()).is_err());
+        assert!(disabled.inject(ChaosTarget::Redis).await.is_ok());
+
+        let chaos = ChaosInjector::new(&ChaosConfig { enabled: true });
+        chaos.set_fault(ChaosTarget::Redis, always_fail).unwrap();
+        assert!(chaos.inject(ChaosTarget::Redis).await.is_err());
+        assert!(chaos.inject(ChaosTarget::Postgres).await.is_ok());
+
+        chaos.clear(None);
+        assert!(chaos.inject(ChaosTarget::Redis).await.is_ok());
+
+        let bad = ChaosFault { latency_probability: 1.5, latency_ms: 10, ..Default::default() };
+        assert!(chaos.set_fault(ChaosTarget::Ml, bad).is_err());
+    }
+}
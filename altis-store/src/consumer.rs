@@ -0,0 +1,237 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::{BorrowedMessage, Message};
+use tokio::sync::watch;
+use tracing::{debug, error, warn};
+
+use altis_core::events::{registry, VersionedEvent};
+
+use crate::app_config::KafkaConfig;
+use crate::events::EventProducer;
+
+/// Why a handler gave up on an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum HandlerError {
+    /// Worth trying again: a dependency was down or timed out
+    Retryable(String),
+    /// Will fail the same way every time; goes straight to the dead-letter topic
+    Permanent(String),
+}
+
+impl std::fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandlerError::Retryable(reason) => write!(f, "retryable: {}", reason),
+            HandlerError::Permanent(reason) => write!(f, "permanent: {}", reason),
+        }
+    }
+}
+
+/// Applies one kind of event read from a topic. The consumer opens the
+/// envelope, validates it and upcasts it to the current version, so handlers
+/// only see well-formed events of the shape they were written for.
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    type Event: VersionedEvent + Send + Sync;
+
+    async fn handle(&self, event: &Self::Event) -> Result<(), HandlerError>;
+}
+
+/// How often a failing event is retried before it is dead-lettered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in all, the first included
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &KafkaConfig) -> Self {
+        Self {
+            max_attempts: config.retry_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.retry_backoff_ms),
+            max_backoff: Duration::from_millis(config.retry_max_backoff_ms),
+        }
+    }
+
+    /// Wait before the attempt after `attempt` (1-based), doubling each time
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Topic that events failing on `topic` are published to
+pub fn dead_letter_topic(topic: &str) -> String {
+    format!("{}.dlq", topic)
+}
+
+/// What is published to the dead-letter topic: the original message as read,
+/// where it came from and why it was given up on
+fn dead_letter_envelope(topic: &str, partition: i32, offset: i64, key: Option<&[u8]>, payload: &[u8], error: &HandlerError, attempts: u32) -> serde_json::Value {
+    serde_json::json!({
+        "topic": topic,
+        "partition": partition,
+        "offset": offset,
+        "key": key.map(String::from_utf8_lossy),
+        "payload": String::from_utf8_lossy(payload),
+        "error": error.to_string(),
+        "attempts": attempts,
+        "failed_at": chrono::Utc::now(),
+    })
+}
+
+enum Outcome {
+    /// Handled or dead-lettered; the offset can be committed
+    Done,
+    /// Shutdown came first; the message is left for the next run
+    Interrupted,
+}
+
+/// Reads one topic in a consumer group and hands each event to a typed
+/// handler. Failures are retried with backoff, then published to
+/// `<topic>.dlq`; the offset is only committed once the event has been
+/// handled or dead-lettered, so nothing is lost across a restart.
+pub struct EventConsumer {
+    consumer: StreamConsumer,
+    topic: String,
+    retry: RetryPolicy,
+    dead_letters: Arc<EventProducer>,
+}
+
+impl EventConsumer {
+    pub fn new(config: &KafkaConfig, group: &str, topic: &str, dead_letters: Arc<EventProducer>) -> Result<Self, rdkafka::error::KafkaError> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", group)
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "latest")
+            .create()?;
+        consumer.subscribe(&[topic])?;
+
+        Ok(Self {
+            consumer,
+            topic: topic.to_string(),
+            retry: RetryPolicy::from_config(config),
+            dead_letters,
+        })
+    }
+
+    /// Consumes until `shutdown` turns true. An event being retried when it
+    /// does is left uncommitted and redelivered on the next start.
+    pub async fn run<H: EventHandler>(self, handler: H, mut shutdown: watch::Receiver<bool>) {
+        while !*shutdown.borrow() {
+            let received = tokio::select! {
+                _ = shutdown.changed() => break,
+                received = self.consumer.recv() => received,
+            };
+            let message = match received {
+                Ok(message) => message,
+                Err(e) => {
+                    error!("Consumer error on {}: {}", self.topic, e);
+                    if sleep_or_shutdown(Duration::from_secs(5), &mut shutdown).await {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            match self.process(&handler, &message, &mut shutdown).await {
+                Outcome::Done => {
+                    if let Err(e) = self.consumer.commit_message(&message, CommitMode::Async) {
+                        warn!("Failed to commit {} offset {}: {}", self.topic, message.offset(), e);
+                    }
+                }
+                Outcome::Interrupted => break,
+            }
+        }
+        debug!("Consumer of {} stopped", self.topic);
+    }
+
+    async fn process<H: EventHandler>(&self, handler: &H, message: &BorrowedMessage<'_>, shutdown: &mut watch::Receiver<bool>) -> Outcome {
+        let Some(payload) = message.payload() else {
+            return Outcome::Done;
+        };
+        let event = match registry().open::<H::Event>(payload) {
+            Ok(event) => event,
+            Err(e) => {
+                let error = HandlerError::Permanent(format!("unreadable event: {}", e));
+                return self.dead_letter(message, payload, &error, 0, shutdown).await;
+            }
+        };
+
+        let mut attempt = 1;
+        loop {
+            let error = match handler.handle(&event).await {
+                Ok(()) => return Outcome::Done,
+                Err(error) => error,
+            };
+            if matches!(error, HandlerError::Permanent(_)) || attempt >= self.retry.max_attempts {
+                return self.dead_letter(message, payload, &error, attempt, shutdown).await;
+            }
+            warn!("Attempt {} at {} offset {} failed ({}), retrying", attempt, self.topic, message.offset(), error);
+            if sleep_or_shutdown(self.retry.backoff(attempt), shutdown).await {
+                return Outcome::Interrupted;
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Publishes the message to the dead-letter topic, retrying the publish
+    /// itself until it goes through: committing past an event that reached
+    /// neither its handler nor the DLQ would lose it.
+    async fn dead_letter(&self, message: &BorrowedMessage<'_>, payload: &[u8], error: &HandlerError, attempts: u32, shutdown: &mut watch::Receiver<bool>) -> Outcome {
+        let dlq = dead_letter_topic(&self.topic);
+        let envelope = dead_letter_envelope(&self.topic, message.partition(), message.offset(), message.key(), payload, error, attempts);
+        let key = format!("{}:{}:{}", self.topic, message.partition(), message.offset());
+        error!("Dead-lettering {} to {} after {} attempt(s): {}", key, dlq, attempts, error);
+
+        let mut publish_attempt = 1;
+        while let Err(e) = self.dead_letters.publish(&dlq, &key, &envelope.to_string()).await {
+            error!("Failed to dead-letter {} ({}), retrying", key, e);
+            if sleep_or_shutdown(self.retry.backoff(publish_attempt), shutdown).await {
+                return Outcome::Interrupted;
+            }
+            publish_attempt += 1;
+        }
+        Outcome::Done
+    }
+}
+
+/// Sleeps for `wait` unless shutdown is signalled first; true if it was
+async fn sleep_or_shutdown(wait: Duration, shutdown: &mut watch::Receiver<bool>) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(wait) => *shutdown.borrow(),
+        _ = shutdown.changed() => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap_and_dead_letters_keep_the_original() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(3),
+        };
+        let waits: Vec<_> = (1..=5).map(|attempt| policy.backoff(attempt).as_millis()).collect();
+        assert_eq!(waits, vec![500, 1000, 2000, 3000, 3000]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(3));
+
+        let error = HandlerError::Retryable("redis down".to_string());
+        let envelope = dead_letter_envelope("flight.status.changed", 2, 41, Some(b"SQ318"), br#"{"status":"DELAYED"}"#, &error, 5);
+        assert_eq!(dead_letter_topic("flight.status.changed"), "flight.status.changed.dlq");
+        assert_eq!(envelope["payload"], r#"{"status":"DELAYED"}"#);
+        assert_eq!(envelope["key"], "SQ318");
+        assert_eq!(envelope["offset"], 41);
+        assert_eq!(envelope["error"], "retryable: redis down");
+    }
+}
@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use altis_core::repository::CustomerFeatureRepository;
+
+use crate::DbClient;
+
+pub struct StoreCustomerFeatureRepository {
+    db: DbClient,
+}
+
+impl StoreCustomerFeatureRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CustomerFeaturesRow {
+    customer_id: String,
+    order_count: i32,
+    preferred_cabin: Option<String>,
+    ancillary_attach_rate: f64,
+    price_sensitivity: f64,
+    avg_order_value_nuc: i32,
+    last_order_at: DateTime<Utc>,
+    refreshed_at: DateTime<Utc>,
+}
+
+#[async_trait]
+impl CustomerFeatureRepository for StoreCustomerFeatureRepository {
+    async fn get_customer_features(&self, customer_id: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let row: Option<CustomerFeaturesRow> = sqlx::query_as(
+            r#"
+            SELECT customer_id, order_count, preferred_cabin,
+                   ancillary_attach_rate::FLOAT8 AS ancillary_attach_rate,
+                   price_sensitivity::FLOAT8 AS price_sensitivity,
+                   avg_order_value_nuc, last_order_at, refreshed_at
+            FROM customer_features
+            WHERE customer_id = $1
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_optional(self.db.reader())
+        .await?;
+
+        Ok(row.map(|row| serde_json::json!({
+            "customer_id": row.customer_id,
+            "order_count": row.order_count,
+            "preferred_cabin": row.preferred_cabin,
+            "ancillary_attach_rate": row.ancillary_attach_rate,
+            "price_sensitivity": row.price_sensitivity,
+            "avg_order_value_nuc": row.avg_order_value_nuc,
+            "last_order_at": row.last_order_at,
+            "refreshed_at": row.refreshed_at,
+        })))
+    }
+
+    async fn refresh_customer_features(
+        &self,
+        since: Option<DateTime<Utc>>,
+        lookback_days: i32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        // Price sensitivity compares each flight against the median fare of
+        // all purchases in the window, so it stays comparable across customers
+        let written = sqlx::query(
+            r#"
+            WITH changed AS (
+                SELECT DISTINCT customer_id FROM orders
+                WHERE $1::TIMESTAMPTZ IS NULL OR updated_at > $1
+            ),
+            purchased AS (
+                SELECT o.id, o.customer_id, o.total_nuc, o.created_at,
+                       EXISTS (
+                           SELECT 1 FROM order_items i
+                           WHERE i.order_id = o.id AND UPPER(i.product_type) <> 'FLIGHT'
+                       ) AS has_ancillary
+                FROM orders o
+                JOIN changed c ON c.customer_id = o.customer_id
+                WHERE o.status IN ('PAID', 'FULFILLED', 'ARCHIVED')
+                  AND o.created_at > NOW() - make_interval(days => $2)
+            ),
+            median_fare AS (
+                SELECT percentile_cont(0.5) WITHIN GROUP (ORDER BY i.price_nuc) AS fare
+                FROM orders o
+                JOIN order_items i ON i.order_id = o.id
+                WHERE UPPER(i.product_type) = 'FLIGHT'
+                  AND o.status IN ('PAID', 'FULFILLED', 'ARCHIVED')
+                  AND o.created_at > NOW() - make_interval(days => $2)
+            ),
+            flights AS (
+                SELECT p.customer_id, i.price_nuc, i.metadata->>'cabin_class' AS cabin
+                FROM purchased p
+                JOIN order_items i ON i.order_id = p.id
+                WHERE UPPER(i.product_type) = 'FLIGHT'
+            )
+            INSERT INTO customer_features (
+                customer_id, order_count, preferred_cabin, ancillary_attach_rate,
+                price_sensitivity, avg_order_value_nuc, last_order_at, refreshed_at
+            )
+            SELECT p.customer_id,
+                   COUNT(*)::INTEGER,
+                   (SELECT mode() WITHIN GROUP (ORDER BY UPPER(f.cabin))
+                    FROM flights f WHERE f.customer_id = p.customer_id AND f.cabin IS NOT NULL),
+                   AVG(CASE WHEN p.has_ancillary THEN 1 ELSE 0 END)::NUMERIC(5, 4),
+                   COALESCE(
+                       (SELECT AVG(CASE WHEN f.price_nuc <= m.fare THEN 1 ELSE 0 END)
+                        FROM flights f CROSS JOIN median_fare m WHERE f.customer_id = p.customer_id),
+                       0.5
+                   )::NUMERIC(5, 4),
+                   AVG(p.total_nuc)::INTEGER,
+                   MAX(p.created_at),
+                   NOW()
+            FROM purchased p
+            GROUP BY p.customer_id
+            ON CONFLICT (customer_id) DO UPDATE SET
+                order_count = EXCLUDED.order_count,
+                preferred_cabin = EXCLUDED.preferred_cabin,
+                ancillary_attach_rate = EXCLUDED.ancillary_attach_rate,
+                price_sensitivity = EXCLUDED.price_sensitivity,
+                avg_order_value_nuc = EXCLUDED.avg_order_value_nuc,
+                last_order_at = EXCLUDED.last_order_at,
+                refreshed_at = EXCLUDED.refreshed_at
+            "#,
+        )
+        .bind(since)
+        .bind(lookback_days)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // Customers whose purchases were all refunded, cancelled or aged out.
+        // NOW() is the transaction start, so rows written above are kept.
+        sqlx::query(
+            r#"
+            DELETE FROM customer_features
+            WHERE refreshed_at < NOW()
+              AND (last_order_at <= NOW() - make_interval(days => $2)
+                   OR customer_id IN (
+                       SELECT customer_id FROM orders WHERE $1::TIMESTAMPTZ IS NULL OR updated_at > $1
+                   ))
+            "#,
+        )
+        .bind(since)
+        .bind(lookback_days)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(written)
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::postgres::{PgConnection, PgPool, PgPoolOptions};
+use tracing::info;
+
+use crate::app_config::DatabaseConfig;
+use crate::chaos::{ChaosInjector, ChaosTarget};
+use crate::deadline::{self, Deadline};
+use crate::migrations::{self, AppliedMigration, SchemaStatus, MIGRATOR};
+
+/// Postgres access split into a primary (writes) and an optional read replica.
+/// Without a replica, reads fall back to the primary pool.
+#[derive(Clone)]
+pub struct DbClient {
+    primary: PgPool,
+    replica: Option<PgPool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolHealth {
+    pub status: &'static str,
+    pub latency_ms: u128,
+    pub size: u32,
+    pub idle: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbHealth {
+    pub primary: PoolHealth,
+    pub replica: Option<PoolHealth>,
+}
+
+impl DbHealth {
+    /// A failed replica degrades reads but doesn't take the service down.
+    pub fn is_healthy(&self) -> bool {
+        self.primary.error.is_none()
+    }
+}
+
+impl DbClient {
+    pub async fn new(config: &DatabaseConfig, chaos: Arc<ChaosInjector>) -> Result<Self, sqlx::Error> {
+        let primary = Self::pool_options(config, &chaos).connect(&config.url).await?;
+        info!("Connected to Postgres primary (max {} connections)", config.max_connections);
+
+        let replica = match &config.replica_url {
+            Some(url) => {
+                let pool = Self::pool_options(config, &chaos).connect(url).await?;
+                info!("Connected to Postgres read replica");
+                Some(pool)
+            }
+            None => None,
+        };
+
+        Ok(Self { primary, replica })
+    }
+
+    /// Wraps an existing pool (no replica), e.g. for tests.
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { primary: pool, replica: None }
+    }
+
+    fn pool_options(config: &DatabaseConfig, chaos: &Arc<ChaosInjector>) -> PgPoolOptions {
+        let options = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout_seconds))
+            .idle_timeout(Some(Duration::from_secs(config.idle_timeout_seconds)));
+
+        let chaos = chaos.is_enabled().then(|| chaos.clone());
+        let deadlines = config.deadline_statement_timeout;
+        if chaos.is_none() && !deadlines {
+            return options;
+        }
+        // Faults hit connection checkout: an injected error drops the pooled
+        // connection (the pool reconnects), and latency beyond the acquire
+        // timeout surfaces to callers as PoolTimedOut.
+        // Checkout is also where statements get the caller's remaining
+        // deadline; new connections start at the server default.
+        options
+            .after_connect(move |conn, _meta| Box::pin(async move {
+                match deadline::current() {
+                    Some(deadline) if deadlines => set_statement_timeout(conn, Some(deadline)).await,
+                    _ => Ok(()),
+                }
+            }))
+            .before_acquire(move |conn, _meta| {
+                let chaos = chaos.clone();
+                Box::pin(async move {
+                    if let Some(chaos) = chaos {
+                        chaos.inject(ChaosTarget::Postgres).await
+                            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+                    }
+                    if deadlines {
+                        set_statement_timeout(conn, deadline::current()).await?;
+                    }
+                    Ok(true)
+                })
+            })
+    }
+
+    /// Pool for writes and read-your-writes queries.
+    pub fn writer(&self) -> &PgPool {
+        &self.primary
+    }
+
+    /// Pool for read-only queries that tolerate replication lag.
+    pub fn reader(&self) -> &PgPool {
+        self.replica.as_ref().unwrap_or(&self.primary)
+    }
+
+    pub async fn health(&self, timeout: Duration) -> DbHealth {
+        let replica = match &self.replica {
+            Some(pool) => Some(Self::probe(pool, timeout).await),
+            None => None,
+        };
+
+        DbHealth {
+            primary: Self::probe(&self.primary, timeout).await,
+            replica,
+        }
+    }
+
+    /// How the primary's schema compares with this build's migrations
+    pub async fn schema_status(&self) -> Result<SchemaStatus, sqlx::Error> {
+        let recorded: Option<String> = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations')::text")
+            .fetch_one(&self.primary)
+            .await?;
+        let applied: Vec<(i64, String, Vec<u8>, bool)> = match recorded {
+            Some(_) => sqlx::query_as("SELECT version, description, checksum, success FROM _sqlx_migrations ORDER BY version")
+                .fetch_all(&self.primary)
+                .await?,
+            // Nothing applied yet
+            None => Vec::new(),
+        };
+        let applied: Vec<AppliedMigration> = applied.into_iter()
+            .map(|(version, description, checksum, success)| AppliedMigration { version, description, checksum, success })
+            .collect();
+        Ok(migrations::compare(&MIGRATOR, &applied))
+    }
+
+    /// Applies pending migrations to the primary. A schema that has drifted
+    /// from them is left alone; the returned status says how.
+    pub async fn migrate(&self) -> Result<SchemaStatus, sqlx::Error> {
+        let status = self.schema_status().await?;
+        if status.pending.is_empty() || !status.drift.is_empty() {
+            return Ok(status);
+        }
+        MIGRATOR.run(&self.primary).await.map_err(|e| sqlx::Error::Migrate(Box::new(e)))?;
+        info!("Applied {} migration(s), schema now at {}", status.pending.len(), status.expected.unwrap_or_default());
+        self.schema_status().await
+    }
+
+    async fn probe(pool: &PgPool, timeout: Duration) -> PoolHealth {
+        let check = crate::health::probe(timeout, async {
+            sqlx::query("SELECT 1").execute(pool).await.map(|_| ())
+        }).await;
+
+        PoolHealth {
+            status: check.status,
+            latency_ms: check.latency_ms,
+            size: pool.size(),
+            idle: pool.num_idle(),
+            error: check.error,
+        }
+    }
+}
+
+/// Caps the connection's statements at the time left before `deadline`, or
+/// puts the server default back outside a request.
+async fn set_statement_timeout(conn: &mut PgConnection, deadline: Option<Deadline>) -> Result<(), sqlx::Error> {
+    match deadline {
+        // Never 0ms, which would turn the timeout off
+        Some(deadline) => sqlx::query("SELECT set_config('statement_timeout', $1, false)")
+            .bind(format!("{}ms", deadline.remaining().as_millis().max(1)))
+            .execute(conn)
+            .await?,
+        None => sqlx::query("RESET statement_timeout").execute(conn).await?,
+    };
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Per-request time budgets. The API scopes each request with a [`Deadline`];
+//! anything it calls reads it back with [`current`] to cap its own waiting at
+//! what is left, so a stalled dependency can't outlive the caller.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static DEADLINE: Deadline;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    at: Instant,
+}
+
+/// The current deadline passed before the operation finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineExceeded;
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Request deadline exceeded")
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+impl Deadline {
+    pub fn after(budget: Duration) -> Self {
+        Self { at: Instant::now() + budget }
+    }
+
+    pub fn at(&self) -> Instant {
+        self.at
+    }
+
+    /// Time left, zero once expired
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.at
+    }
+
+    /// Runs `fut` with this deadline visible to everything it awaits.
+    /// Work handed to `tokio::spawn` runs outside it.
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        DEADLINE.scope(self, fut).await
+    }
+}
+
+/// The deadline of the request being served, if any
+pub fn current() -> Option<Deadline> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// `limit`, shortened to what is left of the current deadline
+pub fn bounded(limit: Duration) -> Duration {
+    current().map_or(limit, |deadline| limit.min(deadline.remaining()))
+}
+
+/// Awaits `fut`, giving up once the current deadline passes
+pub async fn run<F: Future>(fut: F) -> Result<F::Output, DeadlineExceeded> {
+    match current() {
+        Some(deadline) => tokio::time::timeout_at(deadline.at(), fut).await.map_err(|_| DeadlineExceeded),
+        None => Ok(fut.await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scope_bounds_work() {
+        assert_eq!(current(), None);
+        assert_eq!(bounded(Duration::from_secs(1)), Duration::from_secs(1));
+
+        let deadline = Deadline::after(Duration::from_millis(100));
+        deadline.scope(async {
+            assert_eq!(current(), Some(deadline));
+            assert!(bounded(Duration::from_secs(1)) <= Duration::from_millis(100));
+            assert_eq!(run(async { 7 }).await, Ok(7));
+            assert_eq!(run(tokio::time::sleep(Duration::from_secs(1))).await, Err(DeadlineExceeded));
+            assert!(current().unwrap().is_expired());
+        }).await;
+    }
+}
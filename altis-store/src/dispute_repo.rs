@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::DisputeRepository;
+
+use crate::DbClient;
+
+pub struct StoreDisputeRepository {
+    db: DbClient,
+}
+
+impl StoreDisputeRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl DisputeRepository for StoreDisputeRepository {
+    async fn record_dispute(&self, dispute: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let order_id = Uuid::parse_str(dispute["order_id"].as_str().ok_or("missing order_id")?)?;
+        let evidence_due_by: Option<DateTime<Utc>> = dispute["evidence_due_by"].as_str().map(str::parse).transpose()?;
+
+        let recorded = sqlx::query_scalar::<_, Value>(
+            r#"
+            INSERT INTO payment_disputes (order_id, provider, provider_dispute_id, payment_intent_id, amount_nuc, currency, reason, evidence_due_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (provider, provider_dispute_id) DO UPDATE
+            SET evidence_due_by = COALESCE(EXCLUDED.evidence_due_by, payment_disputes.evidence_due_by), updated_at = NOW()
+            RETURNING to_jsonb(payment_disputes) || jsonb_build_object('inserted', xmax = 0)
+            "#,
+        )
+        .bind(order_id)
+        .bind(dispute["provider"].as_str().ok_or("missing provider")?)
+        .bind(dispute["provider_dispute_id"].as_str().ok_or("missing provider_dispute_id")?)
+        .bind(dispute["payment_intent_id"].as_str().ok_or("missing payment_intent_id")?)
+        .bind(dispute["amount_nuc"].as_i64().ok_or("missing amount_nuc")? as i32)
+        .bind(dispute["currency"].as_str().unwrap_or("NUC"))
+        .bind(dispute["reason"].as_str())
+        .bind(evidence_due_by)
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(recorded)
+    }
+
+    async fn get_dispute(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let dispute = sqlx::query_scalar::<_, Value>("SELECT to_jsonb(d) FROM payment_disputes d WHERE id = $1")
+            .bind(id)
+            .fetch_optional(self.db.writer())
+            .await?;
+        Ok(dispute)
+    }
+
+    async fn list_disputes(&self, status: Option<&str>, limit: i64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let disputes = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT to_jsonb(d) FROM payment_disputes d
+            WHERE $1::varchar IS NULL OR status = $1
+            ORDER BY evidence_due_by NULLS LAST, created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(status)
+        .bind(limit)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(disputes)
+    }
+
+    async fn update_dispute_status(&self, id: Uuid, from: &str, to: &str, note: Option<&str>) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE payment_disputes
+            SET status = $3, note = COALESCE($4, note), updated_at = NOW(),
+                evidence_submitted_at = CASE WHEN $3 = 'EVIDENCE_SUBMITTED' THEN NOW() ELSE evidence_submitted_at END,
+                closed_at = CASE WHEN $3 IN ('WON', 'LOST') THEN NOW() ELSE closed_at END
+            WHERE id = $1 AND status = $2
+            "#,
+        )
+        .bind(id)
+        .bind(from)
+        .bind(to)
+        .bind(note)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -0,0 +1,106 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::DisruptionRepository;
+
+use crate::DbClient;
+
+pub struct StoreDisruptionRepository {
+    db: DbClient,
+}
+
+impl StoreDisruptionRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl DisruptionRepository for StoreDisruptionRepository {
+    async fn create_disruption(&self, disruption: &Value) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>> {
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO disruptions (flight_id, airline_id, new_status, delay_minutes, cause, reaccommodate, source, reason)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(disruption["flight_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+        .bind(disruption["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+        .bind(disruption["new_status"].as_str())
+        .bind(disruption["delay_minutes"].as_i64().map(|minutes| minutes as i32))
+        .bind(disruption["cause"].as_str())
+        .bind(disruption["reaccommodate"].as_bool().unwrap_or(false))
+        .bind(disruption["source"].as_str())
+        .bind(disruption["reason"].as_str())
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(id)
+    }
+
+    async fn complete_disruption(&self, id: Uuid, affected_orders: i32, proposals_made: i32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE disruptions SET affected_orders = $2, proposals_made = $3, completed_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(affected_orders)
+            .bind(proposals_made)
+            .execute(self.db.writer())
+            .await?;
+        Ok(())
+    }
+
+    async fn list_disruptions(
+        &self,
+        airline_id: Option<Uuid>,
+        flight_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let disruptions = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT to_jsonb(d) FROM disruptions d
+            WHERE ($1::uuid IS NULL OR airline_id = $1) AND ($2::uuid IS NULL OR flight_id = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(airline_id)
+        .bind(flight_id)
+        .bind(limit)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(disruptions)
+    }
+
+    async fn get_disruption(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let disruption = sqlx::query_scalar::<_, Value>("SELECT to_jsonb(d) FROM disruptions d WHERE id = $1")
+            .bind(id)
+            .fetch_optional(self.db.reader())
+            .await?;
+        Ok(disruption)
+    }
+
+    async fn list_disrupted_bookings(&self, disruption_ids: &[Uuid]) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let ids: Vec<String> = disruption_ids.iter().map(|id| id.to_string()).collect();
+        let bookings = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT jsonb_build_object(
+                'disruption_id', p.metadata->>'disruption_id',
+                'order_id', p.order_id,
+                'item_id', p.metadata->>'disrupted_item_id',
+                'order_status', o.status,
+                'proposals', COUNT(*),
+                'open_proposals', COUNT(*) FILTER (WHERE p.status = 'REACCOMMODATED'),
+                'accepted', BOOL_OR(p.metadata->>'proposal_status' = 'ACCEPTED'),
+                'hold_expires_at', MIN(p.metadata->>'hold_expires_at') FILTER (WHERE p.status = 'REACCOMMODATED')
+            )
+            FROM order_items p JOIN orders o ON o.id = p.order_id
+            WHERE p.metadata->>'disruption_id' = ANY($1)
+            GROUP BY p.metadata->>'disruption_id', p.order_id, p.metadata->>'disrupted_item_id', o.status
+            ORDER BY p.order_id
+            "#,
+        )
+        .bind(&ids)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(bookings)
+    }
+}
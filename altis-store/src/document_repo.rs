@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+use serde_json::Value;
+use altis_core::repository::DocumentRepository;
+
+use crate::sequences::{DocumentType, SequenceAllocator};
+use crate::DbClient;
+
+pub struct StoreDocumentRepository {
+    db: DbClient,
+    sequences: Arc<SequenceAllocator>,
+}
+
+impl StoreDocumentRepository {
+    pub fn new(db: DbClient, sequences: Arc<SequenceAllocator>) -> Self {
+        Self { db, sequences }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DocumentRow {
+    id: Uuid,
+    airline_id: Uuid,
+    document_type: String,
+    fiscal_year: i32,
+    sequence_no: i64,
+    document_number: String,
+    order_id: Option<Uuid>,
+    reference_id: Option<Uuid>,
+    amount_nuc: i64,
+    currency: String,
+    issued_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct InvoiceRow {
+    id: Uuid,
+    airline_id: Uuid,
+    document_number: String,
+    amount_nuc: i64,
+    currency: String,
+    issued_at: chrono::DateTime<chrono::Utc>,
+    seller: String,
+    blob_key: Option<String>,
+    content_type: Option<String>,
+    sha256: Option<String>,
+    breakdown: Option<Value>,
+}
+
+fn parse_type(document_type: &str) -> Result<DocumentType, Box<dyn std::error::Error + Send + Sync>> {
+    DocumentType::parse(document_type).ok_or_else(|| format!("Unknown document type: {}", document_type).into())
+}
+
+#[async_trait]
+impl DocumentRepository for StoreDocumentRepository {
+    async fn issue_order_document(
+        &self,
+        order_id: Uuid,
+        document_type: &str,
+        amount_nuc: i64,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let document_type = parse_type(document_type)?;
+        let mut tx = self.db.writer().begin().await?;
+
+        let order: Option<(Option<Uuid>, Option<String>)> = sqlx::query_as("SELECT airline_id, currency FROM orders WHERE id = $1")
+            .bind(order_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some((Some(airline_id), currency)) = order else {
+            return Ok(None);
+        };
+
+        let document = self.sequences.issue(
+            &mut tx,
+            airline_id,
+            document_type,
+            Some(order_id),
+            None,
+            amount_nuc,
+            currency.as_deref().unwrap_or("NUC"),
+            chrono::Utc::now(),
+        ).await?;
+
+        tx.commit().await?;
+        Ok(Some(serde_json::to_value(document)?))
+    }
+
+    async fn list_documents(
+        &self,
+        airline_id: Uuid,
+        document_type: Option<&str>,
+        fiscal_year: Option<i32>,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, DocumentRow>(
+            r#"
+            SELECT id, airline_id, document_type, fiscal_year, sequence_no, document_number, order_id, reference_id, amount_nuc, currency, issued_at
+            FROM accounting_documents
+            WHERE airline_id = $1
+              AND ($2::varchar IS NULL OR document_type = $2)
+              AND ($3::int IS NULL OR fiscal_year = $3)
+            ORDER BY document_type, fiscal_year, sequence_no
+            "#,
+        )
+        .bind(airline_id)
+        .bind(document_type)
+        .bind(fiscal_year)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            serde_json::json!({
+                "id": row.id,
+                "airline_id": row.airline_id,
+                "document_type": row.document_type,
+                "fiscal_year": row.fiscal_year,
+                "sequence_no": row.sequence_no,
+                "document_number": row.document_number,
+                "order_id": row.order_id,
+                "reference_id": row.reference_id,
+                "amount_nuc": row.amount_nuc,
+                "currency": row.currency,
+                "issued_at": row.issued_at.to_rfc3339(),
+            })
+        }).collect())
+    }
+
+    async fn find_sequence_gaps(
+        &self,
+        airline_id: Uuid,
+        document_type: &str,
+        fiscal_year: i32,
+    ) -> Result<Vec<i64>, Box<dyn std::error::Error + Send + Sync>> {
+        let document_type = parse_type(document_type)?;
+        // Read from the primary: a lagging replica would report fresh numbers as gaps
+        let mut conn = self.db.writer().acquire().await?;
+        SequenceAllocator::find_gaps(&mut conn, airline_id, document_type, fiscal_year).await
+    }
+
+    async fn get_order_invoice(&self, order_id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let row = sqlx::query_as::<_, InvoiceRow>(
+            r#"
+            SELECT d.id, d.airline_id, d.document_number, d.amount_nuc, d.currency, d.issued_at,
+                   a.name AS seller, f.blob_key, f.content_type, f.sha256, f.breakdown
+            FROM accounting_documents d
+            JOIN airlines a ON a.id = d.airline_id
+            LEFT JOIN document_files f ON f.document_id = d.id
+            WHERE d.order_id = $1 AND d.document_type = 'INVOICE'
+            ORDER BY d.issued_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(order_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+
+        Ok(row.map(|row| {
+            let file = row.blob_key.map(|blob_key| serde_json::json!({
+                "blob_key": blob_key,
+                "content_type": row.content_type,
+                "sha256": row.sha256,
+                "breakdown": row.breakdown,
+            }));
+            serde_json::json!({
+                "id": row.id,
+                "airline_id": row.airline_id,
+                "document_number": row.document_number,
+                "amount_nuc": row.amount_nuc,
+                "currency": row.currency,
+                "issued_at": row.issued_at.to_rfc3339(),
+                "seller": row.seller,
+                "file": file,
+            })
+        }))
+    }
+
+    async fn save_document_file(
+        &self,
+        document_id: Uuid,
+        blob_key: &str,
+        content_type: &str,
+        sha256: &str,
+        breakdown: &Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            r#"
+            INSERT INTO document_files (document_id, blob_key, content_type, sha256, breakdown)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (document_id) DO NOTHING
+            "#,
+        )
+        .bind(document_id)
+        .bind(blob_key)
+        .bind(content_type)
+        .bind(sha256)
+        .bind(breakdown)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use altis_core::email::{EmailError, EmailMessage, EmailSender};
+
+use crate::app_config::EmailConfig;
+
+/// The configured provider's sender. SES is reached over its SMTP
+/// interface, so both real providers share one transport.
+pub fn email_sender(config: &EmailConfig) -> Result<Arc<dyn EmailSender>, String> {
+    let from: Mailbox = config.from.parse().map_err(|e| format!("email.from '{}': {}", config.from, e))?;
+    match config.provider.as_str() {
+        "log" => Ok(Arc::new(LogEmailSender)),
+        "smtp" => Ok(Arc::new(SmtpEmailSender::new(&config.smtp_host, config.smtp_port, config, from)?)),
+        "ses" => {
+            let host = format!("email-smtp.{}.amazonaws.com", config.ses_region);
+            Ok(Arc::new(SmtpEmailSender::new(&host, 587, config, from)?))
+        }
+        other => Err(format!("unknown email provider '{}'", other)),
+    }
+}
+
+/// Sends over SMTP with STARTTLS
+pub struct SmtpEmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpEmailSender {
+    fn new(host: &str, port: u16, config: &EmailConfig, from: Mailbox) -> Result<Self, String> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|e| format!("SMTP relay {}: {}", host, e))?
+            .port(port)
+            .credentials(Credentials::new(config.username.clone(), config.password.clone()))
+            .build();
+        Ok(Self { transport, from })
+    }
+}
+
+/// The message as sent: a plain text part with an HTML alternative
+fn build_message(from: &Mailbox, message: &EmailMessage) -> Result<Message, EmailError> {
+    let to: Mailbox = message.to.parse().map_err(|e| EmailError::Rejected(format!("recipient '{}': {}", message.to, e)))?;
+    Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(message.subject.clone())
+        .multipart(MultiPart::alternative()
+            .singlepart(SinglePart::builder().header(ContentType::TEXT_PLAIN).body(message.text.clone()))
+            .singlepart(SinglePart::builder().header(ContentType::TEXT_HTML).body(message.html.clone())))
+        .map_err(|e| EmailError::Rejected(e.to_string()))
+}
+
+#[async_trait]
+impl EmailSender for SmtpEmailSender {
+    async fn send(&self, message: &EmailMessage) -> Result<String, EmailError> {
+        let email = build_message(&self.from, message)?;
+        match self.transport.send(email).await {
+            Ok(response) => Ok(response.message().collect::<Vec<_>>().join(" ")),
+            Err(e) if e.is_permanent() => Err(EmailError::Rejected(e.to_string())),
+            Err(e) => Err(EmailError::Unavailable(e.to_string())),
+        }
+    }
+}
+
+/// Writes messages to the log instead of sending them, for development
+pub struct LogEmailSender;
+
+#[async_trait]
+impl EmailSender for LogEmailSender {
+    async fn send(&self, message: &EmailMessage) -> Result<String, EmailError> {
+        tracing::info!("Email '{}' not sent (log provider)", message.subject);
+        tracing::debug!("Email to {}:\n{}", message.to, message.text);
+        Ok(format!("log-{}", uuid::Uuid::new_v4()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_providers_and_message_building() {
+        let mut config = EmailConfig::default();
+        assert!(email_sender(&config).is_ok());
+        config.provider = "ses".to_string();
+        assert!(email_sender(&config).is_ok());
+        config.provider = "pigeon".to_string();
+        assert!(email_sender(&config).is_err());
+
+        let from: Mailbox = config.from.parse().unwrap();
+        let message = EmailMessage {
+            to: "ada@example.com".to_string(),
+            subject: "Your trip".to_string(),
+            text: "Booking reference ABC123".to_string(),
+            html: "<p>Booking reference ABC123</p>".to_string(),
+        };
+        let built = String::from_utf8(build_message(&from, &message).unwrap().formatted()).unwrap();
+        assert!(built.contains("To: ada@example.com"));
+        assert!(built.contains("multipart/alternative"));
+
+        let bad = EmailMessage { to: "not an address".to_string(), ..message };
+        assert!(matches!(build_message(&from, &bad), Err(EmailError::Rejected(_))));
+    }
+}
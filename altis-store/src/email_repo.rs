@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::EmailRepository;
+
+use crate::DbClient;
+
+pub struct StoreEmailRepository {
+    db: DbClient,
+}
+
+impl StoreEmailRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl EmailRepository for StoreEmailRepository {
+    async fn get_order_email(&self, order_id: Uuid, kind: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // From the primary: a redelivered event must see the send just recorded
+        let email = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(e) FROM order_emails e WHERE order_id = $1 AND kind = $2",
+        )
+        .bind(order_id)
+        .bind(kind)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(email)
+    }
+
+    async fn record_order_email(&self, email: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let order_id = email["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or("email without order_id")?;
+        let saved = sqlx::query_scalar::<_, Value>(
+            r#"
+            INSERT INTO order_emails (order_id, kind, recipient, status, provider, provider_message_id, error, sent_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, CASE WHEN $4 = 'SENT' THEN NOW() END)
+            ON CONFLICT (order_id, kind) DO UPDATE SET
+                recipient = EXCLUDED.recipient,
+                status = EXCLUDED.status,
+                provider = EXCLUDED.provider,
+                provider_message_id = EXCLUDED.provider_message_id,
+                error = EXCLUDED.error,
+                sent_at = EXCLUDED.sent_at,
+                attempts = order_emails.attempts + 1,
+                updated_at = NOW()
+            RETURNING to_jsonb(order_emails)
+            "#,
+        )
+        .bind(order_id)
+        .bind(email["kind"].as_str().ok_or("email without kind")?)
+        .bind(email["recipient"].as_str())
+        .bind(email["status"].as_str().ok_or("email without status")?)
+        .bind(email["provider"].as_str().unwrap_or_default())
+        .bind(email["provider_message_id"].as_str())
+        .bind(email["error"].as_str())
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(saved)
+    }
+
+    async fn list_order_emails(&self, order_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let emails = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(e) FROM order_emails e WHERE order_id = $1 ORDER BY created_at",
+        )
+        .bind(order_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(emails)
+    }
+}
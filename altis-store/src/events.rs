@@ -1,5 +1,5 @@
 use rdkafka::config::ClientConfig;
-use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
 use rdkafka::util::Timeout;
 use std::time::Duration;
 use tracing::{info, error};
@@ -21,6 +21,17 @@ impl EventProducer {
         Ok(Self { producer })
     }
 
+    /// Checks a broker answers a metadata request. librdkafka blocks, so the
+    /// call runs on the blocking pool.
+    pub async fn ping(&self, timeout: Duration) -> Result<(), rdkafka::error::KafkaError> {
+        let producer = self.producer.clone();
+        tokio::task::spawn_blocking(move || {
+            producer.client().fetch_metadata(None, Timeout::After(timeout)).map(|_| ())
+        })
+        .await
+        .unwrap_or(Err(rdkafka::error::KafkaError::Canceled))
+    }
+
     pub async fn publish(&self, topic: &str, key: &str, payload: &str) -> Result<(), rdkafka::error::KafkaError> {
         let record = FutureRecord::to(topic)
             .key(key)
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::ExperimentRepository;
+
+use crate::DbClient;
+
+pub struct StoreExperimentRepository {
+    db: DbClient,
+}
+
+impl StoreExperimentRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ExperimentRow {
+    id: Uuid,
+    name: String,
+    strategy: String,
+    traffic_percentage: f64,
+    starts_at: DateTime<Utc>,
+    ends_at: Option<DateTime<Utc>>,
+    created_by: String,
+    created_at: DateTime<Utc>,
+}
+
+impl ExperimentRow {
+    fn to_json(&self) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "strategy": self.strategy,
+            "traffic_percentage": self.traffic_percentage,
+            "starts_at": self.starts_at,
+            "ends_at": self.ends_at,
+            "created_by": self.created_by,
+            "created_at": self.created_at,
+        })
+    }
+}
+
+const COLUMNS: &str = "id, name, strategy, traffic_percentage::FLOAT8 AS traffic_percentage, starts_at, ends_at, created_by, created_at";
+
+#[async_trait]
+impl ExperimentRepository for StoreExperimentRepository {
+    async fn list_experiments(&self) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<ExperimentRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM ranking_experiments ORDER BY starts_at DESC, name", COLUMNS
+        ))
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.iter().map(ExperimentRow::to_json).collect())
+    }
+
+    async fn create_experiment(&self, experiment: &Value, created_by: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let starts_at = experiment["starts_at"].as_str()
+            .map(|t| DateTime::parse_from_rfc3339(t).map(|t| t.with_timezone(&Utc)))
+            .transpose()?;
+        let ends_at = experiment["ends_at"].as_str()
+            .map(|t| DateTime::parse_from_rfc3339(t).map(|t| t.with_timezone(&Utc)))
+            .transpose()?;
+
+        let row: ExperimentRow = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO ranking_experiments (name, strategy, traffic_percentage, starts_at, ends_at, created_by)
+            VALUES ($1, $2, $3::FLOAT8::NUMERIC, COALESCE($4, NOW()), $5, $6)
+            RETURNING {}
+            "#,
+            COLUMNS
+        ))
+        .bind(experiment["name"].as_str())
+        .bind(experiment["strategy"].as_str())
+        .bind(experiment["traffic_percentage"].as_f64())
+        .bind(starts_at)
+        .bind(ends_at)
+        .bind(created_by)
+        .fetch_one(self.db.writer())
+        .await?;
+
+        Ok(row.to_json())
+    }
+
+    async fn end_experiment(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // A scheduled experiment is ended at its start, so it never runs
+        let row: Option<ExperimentRow> = sqlx::query_as(&format!(
+            r#"
+            UPDATE ranking_experiments
+            SET ends_at = GREATEST(starts_at, NOW())
+            WHERE id = $1 AND (ends_at IS NULL OR ends_at > NOW())
+            RETURNING {}
+            "#,
+            COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(self.db.writer())
+        .await?;
+
+        Ok(row.as_ref().map(ExperimentRow::to_json))
+    }
+}
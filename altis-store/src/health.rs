@@ -0,0 +1,41 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Result of probing one dependency.
+#[derive(Debug, Serialize)]
+pub struct ComponentHealth {
+    pub status: &'static str,
+    pub latency_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ComponentHealth {
+    pub fn is_up(&self) -> bool {
+        self.status == "up"
+    }
+}
+
+/// Runs a probe under a deadline; a probe that hangs counts as down rather than
+/// stalling the health endpoint.
+pub async fn probe<F, E>(timeout: Duration, check: F) -> ComponentHealth
+where
+    F: Future<Output = Result<(), E>>,
+    E: Display,
+{
+    let started = Instant::now();
+    let error = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("timed out after {}ms", timeout.as_millis())),
+    };
+
+    ComponentHealth {
+        status: if error.is_none() { "up" } else { "down" },
+        latency_ms: started.elapsed().as_millis(),
+        error,
+    }
+}
@@ -0,0 +1,213 @@
+//! Product inventory held in Redis, so every API instance sells from the same
+//! counts. Each product is a hash at `inventory:{product_id}` with
+//! `capacity`, `available` and `reserved` fields (plus `closed` once taken
+//! off sale); every change runs as one Lua script, so concurrent bookings
+//! can't oversell.
+
+use std::collections::HashMap;
+
+use altis_catalog::{InventoryError, InventoryItem};
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+use crate::RedisClient;
+
+/// Script results: status code, then the field the status is about
+const OK: i64 = 0;
+const MISSING: i64 = 1;
+const INSUFFICIENT: i64 = 2;
+
+const RESERVE: &str = r#"
+    if redis.call("EXISTS", KEYS[1]) == 0 then return {1, 0} end
+    local qty = tonumber(ARGV[1])
+    local available = tonumber(redis.call("HGET", KEYS[1], "available"))
+    if available < qty then return {2, available} end
+    redis.call("HINCRBY", KEYS[1], "reserved", qty)
+    return {0, redis.call("HINCRBY", KEYS[1], "available", -qty)}
+"#;
+
+const COMMIT: &str = r#"
+    if redis.call("EXISTS", KEYS[1]) == 0 then return {1, 0} end
+    local qty = tonumber(ARGV[1])
+    local reserved = tonumber(redis.call("HGET", KEYS[1], "reserved"))
+    if reserved < qty then return {2, reserved} end
+    redis.call("HINCRBY", KEYS[1], "reserved", -qty)
+    return {0, tonumber(redis.call("HGET", KEYS[1], "available"))}
+"#;
+
+// ARGV[2] is 1 when the units come back from `reserved`, 0 when they were sold
+const RELEASE: &str = r#"
+    if redis.call("EXISTS", KEYS[1]) == 0 then return {1, 0} end
+    local qty = tonumber(ARGV[1])
+    local capacity = tonumber(redis.call("HGET", KEYS[1], "capacity"))
+    local available = tonumber(redis.call("HGET", KEYS[1], "available"))
+    local reserved = tonumber(redis.call("HGET", KEYS[1], "reserved"))
+    if ARGV[2] == "1" then
+        reserved = math.max(0, reserved - qty)
+        redis.call("HSET", KEYS[1], "reserved", reserved)
+    end
+    -- A closed product stays off sale
+    if redis.call("HEXISTS", KEYS[1], "closed") == 0 then
+        available = math.min(capacity - reserved, available + qty)
+        redis.call("HSET", KEYS[1], "available", available)
+    end
+    return {0, available}
+"#;
+
+/// The single way the engine reads and changes inventory.
+///
+/// Products without a record are untracked: every call on them returns
+/// [`InventoryError::NotFound`], which callers treat as "not limited".
+pub struct InventoryManager {
+    redis: RedisClient,
+}
+
+fn key(product_id: Uuid) -> String {
+    format!("inventory:{}", product_id)
+}
+
+fn unavailable(e: redis::RedisError) -> InventoryError {
+    InventoryError::Unavailable(e.to_string())
+}
+
+impl InventoryManager {
+    pub fn new(redis: RedisClient) -> Self {
+        Self { redis }
+    }
+
+    /// Starts tracking a product with all of `total_capacity` on sale. A product
+    /// that is already tracked keeps its live counts; returns whether it was new.
+    pub async fn initialize(&self, product_id: Uuid, total_capacity: i32) -> Result<bool, InventoryError> {
+        let mut conn = self.redis.connection();
+        let script = redis::Script::new(r#"
+            if redis.call("EXISTS", KEYS[1]) == 1 then return 0 end
+            redis.call("HSET", KEYS[1], "capacity", ARGV[1], "available", ARGV[1], "reserved", 0)
+            return 1
+        "#);
+        let created: i64 = self.redis.timed("inventory_initialize", script.key(key(product_id)).arg(total_capacity).invoke_async(&mut conn))
+            .await
+            .map_err(unavailable)?;
+        if created == 1 {
+            self.redis.invalidate_search_cache().await.map_err(unavailable)?;
+        }
+        Ok(created == 1)
+    }
+
+    /// Puts all of `total_capacity` back on sale, dropping live reservations
+    /// and any closure. Only for synthetic inventory, e.g. a sandbox reset;
+    /// cached searches are left for the caller to invalidate.
+    pub async fn reset(&self, product_id: Uuid, total_capacity: i32) -> Result<(), InventoryError> {
+        let mut conn = self.redis.connection();
+        let script = redis::Script::new(r#"
+            redis.call("DEL", KEYS[1])
+            redis.call("HSET", KEYS[1], "capacity", ARGV[1], "available", ARGV[1], "reserved", 0)
+            return 1
+        "#);
+        let _: i64 = self.redis.timed("inventory_reset", script.key(key(product_id)).arg(total_capacity).invoke_async(&mut conn))
+            .await
+            .map_err(unavailable)?;
+        Ok(())
+    }
+
+    /// Current counts; None when the product isn't tracked
+    pub async fn get(&self, product_id: Uuid) -> Result<Option<InventoryItem>, InventoryError> {
+        let mut conn = self.redis.connection();
+        let fields: HashMap<String, i32> = self.redis.timed("inventory_get", conn.hgetall(key(product_id))).await.map_err(unavailable)?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        let field = |name: &str| fields.get(name).copied().unwrap_or(0);
+        Ok(Some(InventoryItem {
+            product_id,
+            available_quantity: field("available"),
+            total_capacity: field("capacity"),
+            reserved_quantity: field("reserved"),
+        }))
+    }
+
+    /// Share of capacity reserved or sold; None when the product isn't tracked
+    pub async fn utilization(&self, product_id: Uuid) -> Result<Option<f64>, InventoryError> {
+        Ok(self.get(product_id).await?.map(|item| item.utilization()))
+    }
+
+    /// Holds `quantity` units for an unpaid order
+    pub async fn reserve(&self, product_id: Uuid, quantity: i32) -> Result<(), InventoryError> {
+        let (status, available) = self.run("inventory_reserve", RESERVE, product_id, quantity, None).await?;
+        match status {
+            MISSING => Err(InventoryError::NotFound(product_id.to_string())),
+            INSUFFICIENT => Err(InventoryError::InsufficientInventory { requested: quantity, available: available as i32 }),
+            _ => {
+                // Selling out changes what search can offer
+                if available <= 0 {
+                    self.redis.invalidate_search_cache().await.map_err(unavailable)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Turns `quantity` reserved units into sold ones once the order is paid
+    pub async fn commit(&self, product_id: Uuid, quantity: i32) -> Result<(), InventoryError> {
+        let (status, reserved) = self.run("inventory_commit", COMMIT, product_id, quantity, None).await?;
+        match status {
+            MISSING => Err(InventoryError::NotFound(product_id.to_string())),
+            INSUFFICIENT => Err(InventoryError::InsufficientReserved { requested: quantity, reserved: reserved as i32 }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Puts reserved units back on sale (unpaid order cancelled or expired)
+    pub async fn release(&self, product_id: Uuid, quantity: i32) -> Result<(), InventoryError> {
+        self.put_back("inventory_release", product_id, quantity, true).await
+    }
+
+    /// Puts sold units back on sale (paid order cancelled or refunded)
+    pub async fn restock(&self, product_id: Uuid, quantity: i32) -> Result<(), InventoryError> {
+        self.put_back("inventory_restock", product_id, quantity, false).await
+    }
+
+    /// Stops selling a product while keeping its record, e.g. a cancelled
+    /// flight. Reservations already made are left to run their course.
+    pub async fn close(&self, product_id: Uuid) -> Result<(), InventoryError> {
+        let mut conn = self.redis.connection();
+        let script = redis::Script::new(r#"
+            if redis.call("EXISTS", KEYS[1]) == 0 then return 0 end
+            redis.call("HSET", KEYS[1], "available", 0, "closed", 1)
+            return 1
+        "#);
+        let closed: i64 = self.redis.timed("inventory_close", script.key(key(product_id)).invoke_async(&mut conn))
+            .await
+            .map_err(unavailable)?;
+        if closed == 0 {
+            return Err(InventoryError::NotFound(product_id.to_string()));
+        }
+        self.redis.invalidate_search_cache().await.map_err(unavailable)
+    }
+
+    async fn put_back(&self, op: &'static str, product_id: Uuid, quantity: i32, from_reserved: bool) -> Result<(), InventoryError> {
+        let (status, available) = self.run(op, RELEASE, product_id, quantity, Some(from_reserved)).await?;
+        if status == MISSING {
+            return Err(InventoryError::NotFound(product_id.to_string()));
+        }
+        // Back on sale after selling out
+        if available > 0 && available <= i64::from(quantity) {
+            self.redis.invalidate_search_cache().await.map_err(unavailable)?;
+        }
+        Ok(())
+    }
+
+    async fn run(&self, op: &'static str, source: &str, product_id: Uuid, quantity: i32, flag: Option<bool>) -> Result<(i64, i64), InventoryError> {
+        let mut conn = self.redis.connection();
+        let script = redis::Script::new(source);
+        let mut invocation = script.key(key(product_id));
+        invocation.arg(quantity);
+        if let Some(flag) = flag {
+            invocation.arg(i32::from(flag));
+        }
+        let (status, value): (i64, i64) = self.redis.timed(op, invocation.invoke_async(&mut conn)).await.map_err(unavailable)?;
+        if status != OK && status != MISSING && status != INSUFFICIENT {
+            return Err(InventoryError::Unavailable(format!("unexpected script status {}", status)));
+        }
+        Ok((status, value))
+    }
+}
@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::LedgerRepository;
+
+use crate::DbClient;
+
+pub struct StoreLedgerRepository {
+    db: DbClient,
+}
+
+impl StoreLedgerRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(Deserialize)]
+struct NewTransaction {
+    id: Uuid,
+    order_id: Uuid,
+    order_item_id: Option<Uuid>,
+    kind: String,
+    description: Option<String>,
+    postings: Vec<NewPosting>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct NewPosting {
+    account: String,
+    debit_nuc: i64,
+    credit_nuc: i64,
+    counterparty_id: Option<Uuid>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TrialBalanceRow {
+    account: String,
+    debit_nuc: i64,
+    credit_nuc: i64,
+}
+
+#[async_trait]
+impl LedgerRepository for StoreLedgerRepository {
+    async fn post_transaction(&self, transaction: &Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let transaction: NewTransaction = serde_json::from_value(transaction.clone())?;
+        let mut tx = self.db.writer().begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO journal_transactions (id, order_id, order_item_id, kind, description, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(transaction.id)
+        .bind(transaction.order_id)
+        .bind(transaction.order_item_id)
+        .bind(&transaction.kind)
+        .bind(&transaction.description)
+        .bind(transaction.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for posting in &transaction.postings {
+            sqlx::query(
+                r#"
+                INSERT INTO journal_postings (transaction_id, account, debit_nuc, credit_nuc, counterparty_id)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(transaction.id)
+            .bind(&posting.account)
+            .bind(posting.debit_nuc)
+            .bind(posting.credit_nuc)
+            .bind(posting.counterparty_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // The balance trigger is deferred, so an unbalanced transaction fails here
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_order_journal(&self, order_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<Value> = sqlx::query_scalar(
+            r#"
+            SELECT jsonb_build_object(
+                'id', t.id,
+                'order_id', t.order_id,
+                'order_item_id', t.order_item_id,
+                'kind', t.kind,
+                'description', t.description,
+                'created_at', t.created_at,
+                'postings', (
+                    SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                        'account', p.account,
+                        'debit_nuc', p.debit_nuc,
+                        'credit_nuc', p.credit_nuc,
+                        'counterparty_id', p.counterparty_id
+                    ) ORDER BY p.debit_nuc DESC, p.account), '[]'::jsonb)
+                    FROM journal_postings p WHERE p.transaction_id = t.id
+                )
+            )
+            FROM journal_transactions t
+            WHERE t.order_id = $1
+            ORDER BY t.created_at, t.id
+            "#,
+        )
+        .bind(order_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn trial_balance(
+        &self,
+        as_of: DateTime<Utc>,
+        airline_id: Option<Uuid>,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, TrialBalanceRow>(
+            r#"
+            SELECT p.account,
+                   COALESCE(SUM(p.debit_nuc), 0)::BIGINT AS debit_nuc,
+                   COALESCE(SUM(p.credit_nuc), 0)::BIGINT AS credit_nuc
+            FROM journal_postings p
+            JOIN journal_transactions t ON t.id = p.transaction_id
+            JOIN orders o ON o.id = t.order_id
+            WHERE t.created_at <= $1 AND NOT o.test
+              AND ($2::uuid IS NULL OR o.airline_id = $2)
+            GROUP BY p.account
+            ORDER BY p.account
+            "#,
+        )
+        .bind(as_of)
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            serde_json::json!({
+                "account": row.account,
+                "debit_nuc": row.debit_nuc,
+                "credit_nuc": row.credit_nuc,
+                "balance_nuc": row.debit_nuc - row.credit_nuc,
+            })
+        }).collect())
+    }
+}
@@ -1,13 +1,82 @@
 pub mod app_config;
+pub mod db;
+pub mod migrations;
+pub mod health;
 pub mod redis_repo;
 pub mod events;
+pub mod consumer;
 pub mod offer_repo;
 pub mod order_repo;
 pub mod catalog_repo;
+pub mod settlement_repo;
+pub mod search_cache;
+pub mod inventory;
+pub mod blob_store;
+pub mod email;
+pub mod sequences;
+pub mod document_repo;
+pub mod ledger_repo;
+pub mod bulk_refund_repo;
+pub mod chaos;
+pub mod circuit_breaker;
+pub mod deadline;
+pub mod experiment_repo;
+pub mod customer_feature_repo;
+pub mod attribution_repo;
+pub mod price_watch_repo;
+pub mod webhook_delivery_repo;
+pub mod cart_repo;
+pub mod baggage_repo;
+pub mod disruption_repo;
+pub mod low_fare_repo;
+pub mod payment_schedule_repo;
+pub mod payment_method_repo;
+pub mod profile_repo;
+pub mod note_repo;
+pub mod dispute_repo;
+pub mod analytics_repo;
+pub mod email_repo;
+pub mod message_repo;
+pub mod upsell_repo;
+pub mod waitlist_repo;
+pub mod archive_repo;
+pub mod seed;
 
 // Re-export specific structs for easier access
+pub use db::DbClient;
 pub use redis_repo::RedisClient;
 pub use events::EventProducer;
+pub use consumer::{EventConsumer, EventHandler, HandlerError};
 pub use offer_repo::StoreOfferRepository;
 pub use order_repo::StoreOrderRepository;
 pub use catalog_repo::StoreProductRepository;
+pub use settlement_repo::StoreSettlementRepository;
+pub use search_cache::SearchCache;
+pub use inventory::InventoryManager;
+pub use blob_store::FsBlobStore;
+pub use sequences::SequenceAllocator;
+pub use document_repo::StoreDocumentRepository;
+pub use ledger_repo::StoreLedgerRepository;
+pub use bulk_refund_repo::StoreBulkRefundRepository;
+pub use chaos::ChaosInjector;
+pub use circuit_breaker::CircuitBreaker;
+pub use experiment_repo::StoreExperimentRepository;
+pub use customer_feature_repo::StoreCustomerFeatureRepository;
+pub use attribution_repo::StoreAttributionRepository;
+pub use price_watch_repo::StorePriceWatchRepository;
+pub use webhook_delivery_repo::StoreWebhookDeliveryRepository;
+pub use cart_repo::StoreCartRepository;
+pub use baggage_repo::StoreBaggageRepository;
+pub use disruption_repo::StoreDisruptionRepository;
+pub use low_fare_repo::StoreLowFareRepository;
+pub use payment_schedule_repo::StorePaymentScheduleRepository;
+pub use payment_method_repo::StorePaymentMethodRepository;
+pub use profile_repo::StoreProfileRepository;
+pub use note_repo::StoreNoteRepository;
+pub use dispute_repo::StoreDisputeRepository;
+pub use analytics_repo::StoreAnalyticsRepository;
+pub use email_repo::StoreEmailRepository;
+pub use message_repo::StoreMessageRepository;
+pub use upsell_repo::StoreUpsellRepository;
+pub use waitlist_repo::StoreWaitlistRepository;
+pub use archive_repo::StoreArchiveRepository;
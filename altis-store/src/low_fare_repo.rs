@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use altis_core::repository::LowFareRepository;
+
+use crate::DbClient;
+
+pub struct StoreLowFareRepository {
+    db: DbClient,
+}
+
+impl StoreLowFareRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+const LOW_FARE_COLUMNS: &str = "origin, destination, month, lowest_price_nuc, lowest_price_date, priced_at";
+
+#[derive(sqlx::FromRow)]
+struct LowFareRow {
+    origin: String,
+    destination: String,
+    month: NaiveDate,
+    lowest_price_nuc: Option<i32>,
+    lowest_price_date: Option<NaiveDate>,
+    priced_at: Option<DateTime<Utc>>,
+}
+
+impl LowFareRow {
+    fn into_json(self) -> Value {
+        serde_json::json!({
+            "origin": self.origin,
+            "destination": self.destination,
+            "month": self.month,
+            "lowest_price_nuc": self.lowest_price_nuc,
+            "lowest_price_date": self.lowest_price_date,
+            "priced_at": self.priced_at,
+        })
+    }
+}
+
+#[async_trait]
+impl LowFareRepository for StoreLowFareRepository {
+    async fn request_low_fares(
+        &self,
+        routes: &[(String, String, NaiveDate)],
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let origins: Vec<&str> = routes.iter().map(|(origin, _, _)| origin.as_str()).collect();
+        let destinations: Vec<&str> = routes.iter().map(|(_, destination, _)| destination.as_str()).collect();
+        let months: Vec<NaiveDate> = routes.iter().map(|(_, _, month)| *month).collect();
+
+        let rows: Vec<LowFareRow> = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO low_fares (origin, destination, month)
+            SELECT DISTINCT * FROM UNNEST($1::varchar[], $2::varchar[], $3::date[])
+            ON CONFLICT (origin, destination, month) DO UPDATE SET requested_at = NOW()
+            RETURNING {}
+            "#,
+            LOW_FARE_COLUMNS
+        ))
+        .bind(&origins)
+        .bind(&destinations)
+        .bind(&months)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(rows.into_iter().map(LowFareRow::into_json).collect())
+    }
+
+    async fn claim_stale_low_fares(
+        &self,
+        limit: i64,
+        refresh_seconds: i64,
+        retain_days: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("DELETE FROM low_fares WHERE month < date_trunc('month', CURRENT_DATE)::date")
+            .execute(self.db.writer())
+            .await?;
+
+        let rows: Vec<LowFareRow> = sqlx::query_as(&format!(
+            r#"
+            UPDATE low_fares SET checked_at = NOW()
+            WHERE (origin, destination, month) IN (
+                SELECT origin, destination, month FROM low_fares
+                WHERE requested_at > NOW() - make_interval(days => $3)
+                  AND (checked_at IS NULL OR checked_at < NOW() - make_interval(secs => $2))
+                ORDER BY checked_at NULLS FIRST
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            LOW_FARE_COLUMNS
+        ))
+        .bind(limit)
+        .bind(refresh_seconds as f64)
+        .bind(retain_days as i32)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(rows.into_iter().map(LowFareRow::into_json).collect())
+    }
+
+    async fn record_low_fare(
+        &self,
+        origin: &str,
+        destination: &str,
+        month: NaiveDate,
+        lowest: Option<(i32, NaiveDate)>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            r#"
+            UPDATE low_fares SET lowest_price_nuc = $4, lowest_price_date = $5, priced_at = NOW()
+            WHERE origin = $1 AND destination = $2 AND month = $3
+            "#,
+        )
+        .bind(origin)
+        .bind(destination)
+        .bind(month)
+        .bind(lowest.map(|(price, _)| price))
+        .bind(lowest.map(|(_, date)| date))
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::MessageRepository;
+
+use crate::DbClient;
+
+pub struct StoreMessageRepository {
+    db: DbClient,
+}
+
+impl StoreMessageRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl MessageRepository for StoreMessageRepository {
+    async fn claim_message(&self, order_id: Uuid, alert_key: &str, channel: &str, recipient: Option<&str>) -> Result<Option<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO order_messages (order_id, alert_key, channel, recipient)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (order_id, alert_key, channel) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(order_id)
+        .bind(alert_key)
+        .bind(channel)
+        .bind(recipient)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(id)
+    }
+
+    async fn finish_message(&self, id: Uuid, status: &str, provider_message_id: Option<&str>, error: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            "UPDATE order_messages SET status = $2, provider_message_id = $3, error = $4, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(id)
+        .bind(status)
+        .bind(provider_message_id)
+        .bind(error)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn list_order_messages(&self, order_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let messages = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(m) FROM order_messages m WHERE order_id = $1 ORDER BY created_at",
+        )
+        .bind(order_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(messages)
+    }
+
+    async fn due_check_in_alerts(&self, until: chrono::DateTime<chrono::Utc>, limit: i64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let due = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT jsonb_build_object('order_id', o.id, 'order_item_id', oi.id)
+            FROM order_items oi
+            JOIN orders o ON o.id = oi.order_id
+            WHERE oi.product_type = 'FLIGHT'
+              AND oi.status = 'ACTIVE'
+              AND o.status IN ('PAID', 'FULFILLED')
+              AND NOT o.test
+              AND (oi.metadata->>'departure_time')::timestamptz BETWEEN NOW() AND $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM order_messages m
+                  WHERE m.order_id = o.id AND m.alert_key = 'CHECK_IN:' || oi.id::text
+              )
+            ORDER BY (oi.metadata->>'departure_time')::timestamptz
+            LIMIT $2
+            "#,
+        )
+        .bind(until)
+        .bind(limit)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(due)
+    }
+}
@@ -0,0 +1,148 @@
+//! The store's schema: the migrations in `altis-store/migrations`, built
+//! into the binary, and how a database compares with them.
+
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+
+use crate::app_config::ConfigProblem;
+
+/// Every migration this build knows, in version order
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Values of `database.migrations`: apply pending migrations at startup, or
+/// only check the schema and refuse to start when it doesn't match
+pub const MIGRATION_MODES: &[&str] = &["apply", "verify"];
+
+/// A migration as recorded in `_sqlx_migrations`
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub checksum: Vec<u8>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DriftKind {
+    /// Applied, but its file has been edited since
+    Modified,
+    /// Applied, with no file for it in this build, though newer ones have
+    Unknown,
+    /// Started and never finished
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaDrift {
+    pub version: i64,
+    pub description: String,
+    pub kind: DriftKind,
+}
+
+/// The database's schema against this build's migrations
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SchemaStatus {
+    /// Latest migration applied
+    pub version: Option<i64>,
+    /// Latest migration in this build
+    pub expected: Option<i64>,
+    pub pending: Vec<i64>,
+    /// Applied by a newer build, e.g. mid-way through a rolling deploy. Not drift.
+    pub ahead: Vec<i64>,
+    pub drift: Vec<SchemaDrift>,
+}
+
+impl SchemaStatus {
+    /// Every migration applied as this build has it, and nothing else wrong
+    pub fn is_current(&self) -> bool {
+        self.pending.is_empty() && self.drift.is_empty()
+    }
+
+    pub fn problems(&self) -> Vec<ConfigProblem> {
+        let mut problems: Vec<ConfigProblem> = self.drift.iter()
+            .map(|drift| ConfigProblem {
+                setting: "database.schema".to_string(),
+                message: format!("migration {} ({}) {}", drift.version, drift.description, match drift.kind {
+                    DriftKind::Modified => "was edited after it was applied; restore the applied file",
+                    DriftKind::Unknown => "is applied but missing from this build",
+                    DriftKind::Failed => "failed part-way and must be repaired by hand",
+                }),
+            })
+            .collect();
+        if let (Some(first), Some(last)) = (self.pending.first(), self.pending.last()) {
+            problems.push(ConfigProblem {
+                setting: "database.schema".to_string(),
+                message: format!("{} migration(s) not applied ({} to {})", self.pending.len(), first, last),
+            });
+        }
+        problems
+    }
+}
+
+/// Compares the migrations applied to a database with this build's
+pub fn compare(migrator: &Migrator, applied: &[AppliedMigration]) -> SchemaStatus {
+    let known: Vec<_> = migrator.iter().filter(|m| m.migration_type.is_up_migration()).collect();
+    let expected = known.iter().map(|m| m.version).max();
+
+    let mut status = SchemaStatus {
+        version: applied.iter().filter(|m| m.success).map(|m| m.version).max(),
+        expected,
+        ..SchemaStatus::default()
+    };
+    let drift = |migration: &AppliedMigration, kind| SchemaDrift { version: migration.version, description: migration.description.clone(), kind };
+    for migration in applied {
+        match known.iter().find(|m| m.version == migration.version) {
+            _ if !migration.success => status.drift.push(drift(migration, DriftKind::Failed)),
+            Some(known) if *known.checksum != *migration.checksum => status.drift.push(drift(migration, DriftKind::Modified)),
+            Some(_) => {}
+            None if expected.is_none_or(|latest| migration.version > latest) => status.ahead.push(migration.version),
+            None => status.drift.push(drift(migration, DriftKind::Unknown)),
+        }
+    }
+    status.pending = known.iter()
+        .map(|m| m.version)
+        .filter(|version| !applied.iter().any(|m| m.version == *version))
+        .collect();
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let known: Vec<_> = MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration()).collect();
+        let applied = |m: &sqlx::migrate::Migration| AppliedMigration {
+            version: m.version,
+            description: m.description.to_string(),
+            checksum: m.checksum.to_vec(),
+            success: true,
+        };
+        let mut all: Vec<AppliedMigration> = known.iter().map(|m| applied(m)).collect();
+        let latest = known.last().unwrap().version;
+
+        let current = compare(&MIGRATOR, &all);
+        assert!(current.is_current());
+        assert_eq!(current.version, Some(latest));
+        assert!(current.problems().is_empty());
+
+        // A newer build got there first
+        all.push(AppliedMigration { version: latest + 1, description: "newer".to_string(), checksum: vec![], success: true });
+        let ahead = compare(&MIGRATOR, &all);
+        assert!(ahead.is_current());
+        assert_eq!(ahead.ahead, vec![latest + 1]);
+
+        // An edited file, a failed run, one this build never had, and the last two pending
+        all.truncate(known.len() - 2);
+        all[0].checksum = vec![0];
+        all[1].success = false;
+        all[2].version += 500_000;
+        let drifted = compare(&MIGRATOR, &all);
+        let kinds: Vec<DriftKind> = drifted.drift.iter().map(|d| d.kind).collect();
+        assert_eq!(kinds, vec![DriftKind::Modified, DriftKind::Failed, DriftKind::Unknown]);
+        assert_eq!(drifted.pending, vec![known[2].version, known[known.len() - 2].version, latest]);
+        assert_eq!(drifted.problems().len(), 4);
+    }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::NoteRepository;
+
+use crate::DbClient;
+
+pub struct StoreNoteRepository {
+    db: DbClient,
+}
+
+impl StoreNoteRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl NoteRepository for StoreNoteRepository {
+    async fn add_note(&self, note: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let uuid = |value: &Value, field: &str| value[field].as_str().and_then(|v| Uuid::parse_str(v).ok());
+        let order_id = uuid(note, "order_id").ok_or("note without order_id")?;
+        let mut tx = self.db.writer().begin().await?;
+
+        let mut saved: Value = sqlx::query_scalar(
+            r#"
+            INSERT INTO order_notes (order_id, author, author_role, visibility, body)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING to_jsonb(order_notes)
+            "#,
+        )
+        .bind(order_id)
+        .bind(note["author"].as_str().ok_or("note without author")?)
+        .bind(note["author_role"].as_str().unwrap_or_default())
+        .bind(note["visibility"].as_str().unwrap_or("INTERNAL"))
+        .bind(note["body"].as_str().ok_or("note without body")?)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let waiver = &note["waiver"];
+        saved["waiver"] = Value::Null;
+        if !waiver.is_null() {
+            let entry = &waiver["ledger_entry"];
+            let ledger_entry_id = match uuid(entry, "id") {
+                Some(entry_id) => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO order_ledger (id, order_id, order_item_id, transaction_type, amount_nuc, currency, description, tax_nuc)
+                        VALUES ($1, $2, $3, $4, $5, COALESCE($6, 'NUC'), $7, $8)
+                        "#,
+                    )
+                    .bind(entry_id)
+                    .bind(order_id)
+                    .bind(uuid(entry, "order_item_id").ok_or("ledger entry without order_item_id")?)
+                    .bind(entry["transaction_type"].as_str().ok_or("ledger entry without transaction_type")?)
+                    .bind(entry["amount_nuc"].as_i64().unwrap_or(0) as i32)
+                    .bind(entry["currency"].as_str())
+                    .bind(entry["description"].as_str())
+                    .bind(entry["tax_nuc"].as_i64().unwrap_or(0) as i32)
+                    .execute(&mut *tx)
+                    .await?;
+                    Some(entry_id)
+                }
+                None => None,
+            };
+
+            saved["waiver"] = sqlx::query_scalar(
+                r#"
+                INSERT INTO order_waivers (note_id, order_id, order_item_id, kind, amount_nuc, reason, ledger_entry_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING to_jsonb(order_waivers)
+                "#,
+            )
+            .bind(uuid(&saved, "id").ok_or("note stored without id")?)
+            .bind(order_id)
+            .bind(uuid(waiver, "order_item_id").ok_or("waiver without order_item_id")?)
+            .bind(waiver["kind"].as_str().ok_or("waiver without kind")?)
+            .bind(waiver["amount_nuc"].as_i64().unwrap_or(0) as i32)
+            .bind(waiver["reason"].as_str().unwrap_or_default())
+            .bind(ledger_entry_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(saved)
+    }
+
+    async fn list_notes(&self, order_id: Uuid, customer_only: bool) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let notes = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT to_jsonb(n) || jsonb_build_object('waiver', to_jsonb(w))
+            FROM order_notes n
+            LEFT JOIN order_waivers w ON w.note_id = n.id
+            WHERE n.order_id = $1 AND (NOT $2 OR n.visibility = 'CUSTOMER')
+            ORDER BY n.created_at
+            "#,
+        )
+        .bind(order_id)
+        .bind(customer_only)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(notes)
+    }
+}
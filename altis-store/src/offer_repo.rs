@@ -1,19 +1,92 @@
 use async_trait::async_trait;
 use uuid::Uuid;
-use sqlx::PgPool;
+use crate::DbClient;
 use redis::AsyncCommands;
 use serde_json::Value;
-use std::sync::Arc;
 use altis_core::repository::OfferRepository;
+use altis_core::tenant::TenantContext;
+use crate::RedisClient;
 
 pub struct StoreOfferRepository {
-    pool: PgPool,
-    redis: Arc<redis::Client>,
+    db: DbClient,
+    redis: RedisClient,
 }
 
 impl StoreOfferRepository {
-    pub fn new(pool: PgPool, redis: Arc<redis::Client>) -> Self {
-        Self { pool, redis }
+    pub fn new(db: DbClient, redis: RedisClient) -> Self {
+        Self { db, redis }
+    }
+
+    /// Loads an offer, cached or not; a tenant other than the platform only
+    /// sees its own airline's.
+    async fn fetch_offer(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // 1. Try Redis first
+        let mut conn = self.redis.connection();
+        let cached: Option<String> = self.redis.timed("get_offer", conn.get(format!("offer:{}", id))).await?;
+        
+        if let Some(json_str) = cached {
+            let offer: Value = serde_json::from_str(&json_str)?;
+            let owner = offer["airline_id"].as_str().and_then(|id| Uuid::parse_str(id).ok());
+            return Ok(tenant.permits(owner).then_some(offer));
+        }
+
+        // 2. Fallback to Postgres
+        let offer_row = sqlx::query!(
+            "SELECT * FROM offers WHERE id = $1 AND ($2::uuid IS NULL OR airline_id = $2)",
+            id,
+            tenant.airline_id()
+        )
+        .fetch_optional(self.db.writer())
+        .await?;
+
+        if let Some(row) = offer_row {
+            // Fetch items
+            let items: Vec<OfferItemRow> = sqlx::query_as(
+                "SELECT id, offer_id, product_id, product_type, product_code, name, description, price_nuc, quantity, metadata, tax_nuc, taxes, created_at FROM offer_items WHERE offer_id = $1",
+            )
+            .bind(id)
+            .fetch_all(self.db.writer())
+            .await?;
+
+            let items_json: Vec<Value> = items.into_iter().map(|item| {
+                serde_json::json!({
+                    "id": item.id,
+                    "product_id": item.product_id,
+                    "product_type": item.product_type,
+                    "product_code": item.product_code,
+                    "name": item.name,
+                    "description": item.description,
+                    "price_nuc": item.price_nuc,
+                    "quantity": item.quantity,
+                    "metadata": item.metadata,
+                    "tax_nuc": item.tax_nuc,
+                    "taxes": item.taxes,
+                    // No created_at needed in OfferItem JSON usually, but we can include if needed
+                    // "created_at": item.created_at.map(|t| t.to_rfc3339())
+                })
+            }).collect();
+
+            let offer_json = serde_json::json!({
+                "id": row.id,
+                "customer_id": row.customer_id,
+                "airline_id": row.airline_id,
+                "search_context": row.search_context,
+                "items": items_json,
+                "total_nuc": row.total_nuc,
+                "currency": row.currency,
+                "status": row.status,
+                "expires_at": row.expires_at.to_rfc3339(),
+                "created_at": row.created_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339()),
+            });
+
+            return Ok(Some(offer_json));
+        }
+
+        Ok(None)
     }
 }
 
@@ -31,6 +104,8 @@ struct OfferItemRow {
     price_nuc: i32,
     quantity: Option<i32>,
     metadata: Option<Value>,
+    tax_nuc: i32,
+    taxes: Value,
     #[allow(dead_code)] // schema has it
     created_at: Option<chrono::DateTime<chrono::Utc>>,
 }
@@ -62,15 +137,15 @@ impl OfferRepository for StoreOfferRepository {
         let expires_at = chrono::DateTime::parse_from_rfc3339(expires_at_str)?.with_timezone(&chrono::Utc);
 
         // 1. Save to Redis (Cache) - 15 minutes TTL
-        let mut conn = self.redis.get_multiplexed_async_connection().await?;
-        let _: () = conn.set_ex(
+        let mut conn = self.redis.connection();
+        let _: () = self.redis.timed("cache_offer", conn.set_ex(
             format!("offer:{}", offer_id),
             offer.to_string(),
             900
-        ).await?;
+        )).await?;
 
         // 2. Save to Postgres (Persistent)
-        let mut tx = self.pool.begin().await?;
+        let mut tx = self.db.writer().begin().await?;
 
         sqlx::query!(
             r#"
@@ -102,23 +177,27 @@ impl OfferRepository for StoreOfferRepository {
                 let price_nuc = item["price_nuc"].as_i64().unwrap_or(0) as i32;
                 let quantity = item["quantity"].as_i64().unwrap_or(1) as i32;
                 let metadata = &item["metadata"];
+                let tax_nuc = item["tax_nuc"].as_i64().unwrap_or(0) as i32;
+                let taxes = item.get("taxes").cloned().unwrap_or_else(|| serde_json::json!([]));
 
-                sqlx::query!(
+                sqlx::query(
                     r#"
-                    INSERT INTO offer_items (id, offer_id, product_id, product_type, product_code, name, description, price_nuc, quantity, metadata)
-                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                    INSERT INTO offer_items (id, offer_id, product_id, product_type, product_code, name, description, price_nuc, quantity, metadata, tax_nuc, taxes)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
                     "#,
-                    item_id,
-                    offer_id,
-                    product_id,
-                    product_type,
-                    product_code,
-                    name,
-                    description,
-                    price_nuc,
-                    quantity,
-                    metadata
                 )
+                .bind(item_id)
+                .bind(offer_id)
+                .bind(product_id)
+                .bind(product_type)
+                .bind(product_code)
+                .bind(name)
+                .bind(description)
+                .bind(price_nuc)
+                .bind(quantity)
+                .bind(metadata)
+                .bind(tax_nuc)
+                .bind(taxes)
                 .execute(&mut *tx)
                 .await?;
             }
@@ -129,69 +208,136 @@ impl OfferRepository for StoreOfferRepository {
         Ok(())
     }
 
-    async fn get_offer(
+    async fn save_offers_batch(
         &self,
-        id: Uuid,
-    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
-        // 1. Try Redis first
-        let mut conn = self.redis.get_multiplexed_async_connection().await?;
-        let cached: Option<String> = conn.get(format!("offer:{}", id)).await?;
-        
-        if let Some(json_str) = cached {
-            return Ok(Some(serde_json::from_str(&json_str)?));
+        offers: &[Value],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if offers.is_empty() {
+            return Ok(());
         }
 
-        // 2. Fallback to Postgres
-        let offer_row = sqlx::query!(
-            "SELECT * FROM offers WHERE id = $1",
-            id
+        // Column vectors for UNNEST-based multi-row inserts
+        let mut ids = Vec::with_capacity(offers.len());
+        let mut customer_ids = Vec::with_capacity(offers.len());
+        let mut airline_ids = Vec::with_capacity(offers.len());
+        let mut search_contexts = Vec::with_capacity(offers.len());
+        let mut totals = Vec::with_capacity(offers.len());
+        let mut currencies = Vec::with_capacity(offers.len());
+        let mut statuses = Vec::with_capacity(offers.len());
+        let mut expirations = Vec::with_capacity(offers.len());
+
+        let mut item_ids = Vec::new();
+        let mut item_offer_ids = Vec::new();
+        let mut item_product_ids = Vec::new();
+        let mut item_types = Vec::new();
+        let mut item_codes = Vec::new();
+        let mut item_names = Vec::new();
+        let mut item_descriptions = Vec::new();
+        let mut item_prices = Vec::new();
+        let mut item_quantities = Vec::new();
+        let mut item_metadata = Vec::new();
+        let mut item_tax_nuc = Vec::new();
+        let mut item_taxes = Vec::new();
+
+        for offer in offers {
+            let offer_id = Uuid::parse_str(offer["id"].as_str().ok_or("Missing offer ID")?)?;
+            let airline_id = offer["airline_id"].as_str().map(Uuid::parse_str).transpose()?;
+            let expires_at_str = offer["expires_at"].as_str().ok_or("Missing expires_at")?;
+
+            ids.push(offer_id);
+            customer_ids.push(offer["customer_id"].as_str().map(|s| s.to_string()));
+            airline_ids.push(airline_id);
+            search_contexts.push(offer["search_context"].clone());
+            totals.push(offer["total_nuc"].as_i64().ok_or("Missing total_nuc")? as i32);
+            currencies.push(offer["currency"].as_str().unwrap_or("NUC").to_string());
+            statuses.push(offer["status"].as_str().unwrap_or("ACTIVE").to_string());
+            expirations.push(chrono::DateTime::parse_from_rfc3339(expires_at_str)?.with_timezone(&chrono::Utc));
+
+            for item in offer["items"].as_array().into_iter().flatten() {
+                item_ids.push(Uuid::parse_str(item["id"].as_str().unwrap_or_default())?);
+                item_offer_ids.push(offer_id);
+                item_product_ids.push(item["product_id"].as_str().map(Uuid::parse_str).transpose()?);
+                item_types.push(item["product_type"].as_str().unwrap_or("UNKNOWN").to_string());
+                item_codes.push(item["product_code"].as_str().map(|s| s.to_string()));
+                item_names.push(item["name"].as_str().unwrap_or("Unknown Item").to_string());
+                item_descriptions.push(item["description"].as_str().map(|s| s.to_string()));
+                item_prices.push(item["price_nuc"].as_i64().unwrap_or(0) as i32);
+                item_quantities.push(item["quantity"].as_i64().unwrap_or(1) as i32);
+                item_metadata.push(item["metadata"].clone());
+                item_tax_nuc.push(item["tax_nuc"].as_i64().unwrap_or(0) as i32);
+                item_taxes.push(item.get("taxes").cloned().unwrap_or_else(|| serde_json::json!([])));
+            }
+        }
+
+        // 1. Save to Postgres: one INSERT for offers, one for all items
+        let mut tx = self.db.writer().begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO offers (id, customer_id, airline_id, search_context, total_nuc, currency, status, expires_at)
+            SELECT * FROM UNNEST($1::uuid[], $2::varchar[], $3::uuid[], $4::jsonb[], $5::int4[], $6::varchar[], $7::varchar[], $8::timestamptz[])
+            "#,
         )
-        .fetch_optional(&self.pool)
+        .bind(&ids)
+        .bind(&customer_ids)
+        .bind(&airline_ids)
+        .bind(&search_contexts)
+        .bind(&totals)
+        .bind(&currencies)
+        .bind(&statuses)
+        .bind(&expirations)
+        .execute(&mut *tx)
         .await?;
 
-        if let Some(row) = offer_row {
-            // Fetch items
-            let items: Vec<OfferItemRow> = sqlx::query_as!(
-                OfferItemRow,
-                "SELECT id, offer_id, product_id, product_type, product_code, name, description, price_nuc, quantity, metadata, created_at FROM offer_items WHERE offer_id = $1",
-                id
+        if !item_ids.is_empty() {
+            sqlx::query(
+                r#"
+                INSERT INTO offer_items (id, offer_id, product_id, product_type, product_code, name, description, price_nuc, quantity, metadata, tax_nuc, taxes)
+                SELECT * FROM UNNEST($1::uuid[], $2::uuid[], $3::uuid[], $4::varchar[], $5::varchar[], $6::varchar[], $7::text[], $8::int4[], $9::int4[], $10::jsonb[], $11::int4[], $12::jsonb[])
+                "#,
             )
-            .fetch_all(&self.pool)
+            .bind(&item_ids)
+            .bind(&item_offer_ids)
+            .bind(&item_product_ids)
+            .bind(&item_types)
+            .bind(&item_codes)
+            .bind(&item_names)
+            .bind(&item_descriptions)
+            .bind(&item_prices)
+            .bind(&item_quantities)
+            .bind(&item_metadata)
+            .bind(&item_tax_nuc)
+            .bind(&item_taxes)
+            .execute(&mut *tx)
             .await?;
+        }
 
-            let items_json: Vec<Value> = items.into_iter().map(|item| {
-                serde_json::json!({
-                    "id": item.id,
-                    "product_id": item.product_id,
-                    "product_type": item.product_type,
-                    "product_code": item.product_code,
-                    "name": item.name,
-                    "description": item.description,
-                    "price_nuc": item.price_nuc,
-                    "quantity": item.quantity,
-                    "metadata": item.metadata,
-                    // No created_at needed in OfferItem JSON usually, but we can include if needed
-                    // "created_at": item.created_at.map(|t| t.to_rfc3339())
-                })
-            }).collect();
-
-            let offer_json = serde_json::json!({
-                "id": row.id,
-                "customer_id": row.customer_id,
-                "airline_id": row.airline_id,
-                "search_context": row.search_context,
-                "items": items_json,
-                "total_nuc": row.total_nuc,
-                "currency": row.currency,
-                "status": row.status,
-                "expires_at": row.expires_at.to_rfc3339(),
-                "created_at": row.created_at.map(|t: chrono::DateTime<chrono::Utc>| t.to_rfc3339()),
-            });
+        tx.commit().await?;
 
-            return Ok(Some(offer_json));
+        // 2. Cache in Redis (15 minutes TTL) with a single pipelined round-trip
+        let mut pipe = redis::pipe();
+        for (offer_id, offer) in ids.iter().zip(offers) {
+            pipe.set_ex(format!("offer:{}", offer_id), offer.to_string(), 900).ignore();
         }
+        let mut conn = self.redis.connection();
+        let _: () = self.redis.timed("cache_offers_batch", pipe.query_async(&mut conn)).await?;
 
-        Ok(None)
+        Ok(())
+    }
+
+    async fn get_offer(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_offer(&TenantContext::platform(), id).await
+    }
+
+    async fn get_tenant_offer(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_offer(tenant, id).await
     }
 
     async fn list_active_offers(
@@ -202,7 +348,7 @@ impl OfferRepository for StoreOfferRepository {
             "SELECT id FROM offers WHERE customer_id = $1 AND status = 'ACTIVE' AND expires_at > NOW()",
             customer_id
         )
-        .fetch_all(&self.pool)
+        .fetch_all(self.db.reader())
         .await?;
 
         let mut offers = Vec::new();
@@ -223,12 +369,38 @@ impl OfferRepository for StoreOfferRepository {
             "UPDATE offers SET status = 'EXPIRED' WHERE id = $1",
             id
         )
-        .execute(&self.pool)
+        .execute(self.db.writer())
         .await?;
 
         // Remove from Redis
-        let mut conn = self.redis.get_multiplexed_async_connection().await?;
-        let _: () = conn.del(format!("offer:{}", id)).await?;
+        let mut conn = self.redis.connection();
+        let _: () = self.redis.timed("expire_offer", conn.del(format!("offer:{}", id))).await?;
+
+        Ok(())
+    }
+
+    async fn claim_offer(
+        &self,
+        id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let claimed = sqlx::query(
+            "UPDATE offers SET status = 'ACCEPTED' WHERE id = $1 AND status = 'ACTIVE' AND expires_at > NOW()",
+        )
+        .bind(id)
+        .execute(self.db.writer())
+        .await?;
+
+        Ok(claimed.rows_affected() == 1)
+    }
+
+    async fn release_offer(
+        &self,
+        id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE offers SET status = 'ACTIVE' WHERE id = $1 AND status = 'ACCEPTED'")
+            .bind(id)
+            .execute(self.db.writer())
+            .await?;
 
         Ok(())
     }
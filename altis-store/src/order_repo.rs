@@ -1,17 +1,173 @@
 use async_trait::async_trait;
 use uuid::Uuid;
-use sqlx::PgPool;
+use crate::DbClient;
 use serde_json::Value;
 use altis_core::repository::OrderRepository;
+use altis_core::tenant::TenantContext;
 
 pub struct StoreOrderRepository {
-    pool: PgPool,
+    db: DbClient,
 }
 
 impl StoreOrderRepository {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
     }
+
+    /// Loads an order with everything hanging off it; a tenant other than the
+    /// platform only sees its own airline's.
+    async fn fetch_order(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let order_row = sqlx::query_as::<_, OrderRow>(
+            &format!("SELECT {} FROM orders WHERE id = $1 AND ($2::uuid IS NULL OR airline_id = $2)", ORDER_COLUMNS)
+        )
+        .bind(id)
+        .bind(tenant.airline_id())
+        .fetch_optional(self.db.reader())
+        .await?;
+
+        if let Some(row) = order_row {
+            let items_rows = sqlx::query_as::<_, OrderItemRow>(
+                &format!("SELECT {} FROM order_items WHERE order_id = $1", ORDER_ITEM_COLUMNS)
+            )
+            .bind(id)
+            .fetch_all(self.db.reader())
+            .await?;
+
+            let items: Vec<Value> = items_rows.into_iter().map(item_json).collect();
+
+            let fulfillment_rows = sqlx::query_as::<_, FulfillmentRow>(
+                "SELECT id, order_id, order_item_id, traveler_id, traveler_index, unit_index, segment, fulfillment_type, barcode, qr_code_data, delivery_method, delivered_at, deliver_at, consumed_at, expired_at, created_at FROM fulfillment WHERE order_id = $1 AND voided_at IS NULL ORDER BY created_at, traveler_index, unit_index"
+            )
+            .bind(id)
+            .fetch_all(self.db.reader())
+            .await?;
+
+             let fulfillment: Vec<Value> = fulfillment_rows.into_iter().map(|f| {
+                serde_json::json!({
+                    "id": f.id,
+                    "order_item_id": f.order_item_id,
+                    "traveler_id": f.traveler_id,
+                    "traveler_index": f.traveler_index,
+                    "unit_index": f.unit_index,
+                    "segment": f.segment,
+                    "fulfillment_type": f.fulfillment_type,
+                    "barcode": f.barcode,
+                    "qr_code_data": f.qr_code_data,
+                    "delivery_method": f.delivery_method,
+                    "delivered_at": f.delivered_at.map(|t| t.to_rfc3339()),
+                    "deliver_at": f.deliver_at.map(|t| t.to_rfc3339()),
+                    "consumed_at": f.consumed_at.map(|t| t.to_rfc3339()),
+                    "expired_at": f.expired_at.map(|t| t.to_rfc3339()),
+                    "created_at": f.created_at.map(|t| t.to_rfc3339())
+                })
+            }).collect();
+
+            let traveler_rows = sqlx::query_as::<_, TravelerRow>(
+                &format!("SELECT {} FROM travelers WHERE order_id = $1", TRAVELER_COLUMNS)
+            )
+            .bind(id)
+            .fetch_all(self.db.reader())
+            .await?;
+
+            let travelers: Vec<Value> = traveler_rows.into_iter().map(traveler_json).collect();
+
+            let mut order_json = order_header_json(row);
+            order_json["items"] = serde_json::json!(items);
+            order_json["travelers"] = serde_json::json!(travelers);
+            order_json["fulfillment"] = serde_json::json!(fulfillment);
+
+            return Ok(Some(order_json));
+        }
+
+        Ok(None)
+    }
+}
+
+const ORDER_COLUMNS: &str = "id, customer_id, customer_email, offer_id, airline_id, status, total_nuc, currency, payment_method, payment_reference, customer_did, contact_phone, contact_first_name, contact_last_name, expires_at, group_size, names_due_at, test, payment_intent_id, payment_action_expires_at, created_at, updated_at";
+const ORDER_ITEM_COLUMNS: &str = "id, order_id, product_id, product_type, product_code, name, description, price_nuc, quantity, refunded_quantity, status, revenue_status, operating_carrier_id, net_rate_nuc, commission_nuc, metadata, tax_nuc, taxes, traveler_id, traveler_index, created_at, updated_at";
+const TRAVELER_COLUMNS: &str = "id, order_id, traveler_index, ptc, first_name, last_name, date_of_birth, gender, traveler_did, metadata";
+
+/// An order's own fields, without what hangs off it
+fn order_header_json(row: OrderRow) -> Value {
+    serde_json::json!({
+        "id": row.id,
+        "customer_id": row.customer_id,
+        "customer_email": row.customer_email,
+        "contact_info": {
+            "email": row.customer_email,
+            "phone": row.contact_phone,
+            "first_name": row.contact_first_name,
+            "last_name": row.contact_last_name,
+        },
+        "offer_id": row.offer_id,
+        "airline_id": row.airline_id,
+        "status": row.status,
+        "total_nuc": row.total_nuc,
+        "currency": row.currency,
+        "payment_method": row.payment_method,
+        "payment_reference": row.payment_reference,
+        "customer_did": row.customer_did,
+        "expires_at": row.expires_at.map(|t| t.to_rfc3339()),
+        "group_size": row.group_size,
+        "names_due_at": row.names_due_at.map(|t| t.to_rfc3339()),
+        "test": row.test,
+        "payment_intent_id": row.payment_intent_id,
+        "payment_action_expires_at": row.payment_action_expires_at.map(|t| t.to_rfc3339()),
+        "created_at": row.created_at.map(|t| t.to_rfc3339()),
+        "updated_at": row.updated_at.map(|t| t.to_rfc3339())
+    })
+}
+
+fn item_json(item: OrderItemRow) -> Value {
+    serde_json::json!({
+        "id": item.id,
+        "product_id": item.product_id,
+        "product_type": item.product_type,
+        "product_code": item.product_code,
+        "name": item.name,
+        "description": item.description,
+        "price_nuc": item.price_nuc,
+        "quantity": item.quantity,
+        "refunded_quantity": item.refunded_quantity,
+        "status": item.status,
+        "revenue_status": item.revenue_status,
+        "operating_carrier_id": item.operating_carrier_id,
+        "net_rate_nuc": item.net_rate_nuc,
+        "commission_nuc": item.commission_nuc,
+        "metadata": item.metadata,
+        "tax_nuc": item.tax_nuc,
+        "taxes": item.taxes,
+        "traveler_id": item.traveler_id,
+        "traveler_index": item.traveler_index,
+        "created_at": item.created_at.map(|t| t.to_rfc3339()),
+        "updated_at": item.updated_at.map(|t| t.to_rfc3339())
+    })
+}
+
+/// The traveler an item being added is for; items written before the
+/// column only had it in their metadata
+fn traveler_index(item: &Value) -> Option<i32> {
+    item["traveler_index"].as_i64()
+        .or_else(|| item["metadata"]["traveler_index"].as_i64())
+        .map(|index| index as i32)
+}
+
+fn traveler_json(t: TravelerRow) -> Value {
+    serde_json::json!({
+        "id": t.id,
+        "traveler_index": t.traveler_index,
+        "ptc": t.ptc,
+        "first_name": t.first_name,
+        "last_name": t.last_name,
+        "date_of_birth": t.date_of_birth.map(|d| d.format("%Y-%m-%d").to_string()),
+        "gender": t.gender,
+        "traveler_did": t.traveler_did,
+        "metadata": t.metadata
+    })
 }
 
 // Internal structs for type-safe querying
@@ -32,6 +188,11 @@ struct OrderRow {
     contact_first_name: Option<String>,
     contact_last_name: Option<String>,
     expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    group_size: Option<i32>,
+    names_due_at: Option<chrono::DateTime<chrono::Utc>>,
+    test: bool,
+    payment_intent_id: Option<String>,
+    payment_action_expires_at: Option<chrono::DateTime<chrono::Utc>>,
     created_at: Option<chrono::DateTime<chrono::Utc>>,
     updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
@@ -39,6 +200,7 @@ struct OrderRow {
 #[derive(sqlx::FromRow)]
 struct TravelerRow {
     id: Uuid,
+    order_id: Option<Uuid>,
     traveler_index: i32,
     ptc: String,
     first_name: String,
@@ -52,7 +214,6 @@ struct TravelerRow {
 #[derive(sqlx::FromRow)]
 struct OrderItemRow {
     id: Uuid,
-    #[allow(dead_code)]
     order_id: Option<Uuid>,
     product_id: Option<Uuid>,
     product_type: String,
@@ -61,12 +222,17 @@ struct OrderItemRow {
     description: Option<String>,
     price_nuc: i32,
     quantity: Option<i32>,
+    refunded_quantity: i32,
     status: Option<String>,
     revenue_status: Option<String>,
     operating_carrier_id: Option<Uuid>,
     net_rate_nuc: Option<i32>,
     commission_nuc: Option<i32>,
     metadata: Option<Value>,
+    tax_nuc: i32,
+    taxes: Value,
+    traveler_id: Option<Uuid>,
+    traveler_index: Option<i32>,
     created_at: Option<chrono::DateTime<chrono::Utc>>,
     updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
@@ -81,6 +247,8 @@ struct LedgerRow {
     currency: Option<String>,
     description: Option<String>,
     created_at: Option<chrono::DateTime<chrono::Utc>>,
+    counterparty_id: Option<Uuid>,
+    tax_nuc: i32,
 }
 
 #[derive(sqlx::FromRow)]
@@ -89,11 +257,18 @@ struct FulfillmentRow {
     #[allow(dead_code)]
     order_id: Option<Uuid>,
     order_item_id: Option<Uuid>,
+    traveler_id: Option<Uuid>,
+    traveler_index: Option<i32>,
+    unit_index: i32,
+    segment: Option<Value>,
     fulfillment_type: String,
     barcode: Option<String>,
     qr_code_data: Option<String>,
     delivery_method: Option<String>,
     delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+    deliver_at: Option<chrono::DateTime<chrono::Utc>>,
+    consumed_at: Option<chrono::DateTime<chrono::Utc>>,
+    expired_at: Option<chrono::DateTime<chrono::Utc>>,
     created_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
@@ -130,13 +305,16 @@ impl OrderRepository for StoreOrderRepository {
         let contact_last_name = order["contact_last_name"].as_str();
         let expires_at_str = order["expires_at"].as_str();
         let expires_at = expires_at_str.and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&chrono::Utc)));
+        let group_size = order["group_size"].as_i64().map(|n| n as i32);
+        let names_due_at = order["names_due_at"].as_str().and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&chrono::Utc)));
+        let test = order["test"].as_bool().unwrap_or(false);
 
-        let mut tx = self.pool.begin().await?;
+        let mut tx = self.db.writer().begin().await?;
 
         sqlx::query(
             r#"
-            INSERT INTO orders (id, customer_id, customer_email, offer_id, airline_id, status, total_nuc, currency, payment_method, payment_reference, customer_did, contact_phone, contact_first_name, contact_last_name, expires_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            INSERT INTO orders (id, customer_id, customer_email, offer_id, airline_id, status, total_nuc, currency, payment_method, payment_reference, customer_did, contact_phone, contact_first_name, contact_last_name, expires_at, group_size, names_due_at, test)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
             "#,
         )
         .bind(order_id)
@@ -154,6 +332,9 @@ impl OrderRepository for StoreOrderRepository {
         .bind(contact_first_name)
         .bind(contact_last_name)
         .bind(expires_at)
+        .bind(group_size)
+        .bind(names_due_at)
+        .bind(test)
         .execute(&mut *tx)
         .await?;
 
@@ -212,11 +393,14 @@ impl OrderRepository for StoreOrderRepository {
                 let net_rate_nuc = item["net_rate_nuc"].as_i64().map(|v| v as i32);
                 let commission_nuc = item["commission_nuc"].as_i64().map(|v| v as i32);
                 let metadata = &item["metadata"];
+                let tax_nuc = item["tax_nuc"].as_i64().unwrap_or(0) as i32;
+                let taxes = item.get("taxes").cloned().unwrap_or_else(|| serde_json::json!([]));
 
                 sqlx::query(
                     r#"
-                    INSERT INTO order_items (id, order_id, product_id, product_type, product_code, name, description, price_nuc, quantity, status, revenue_status, operating_carrier_id, net_rate_nuc, commission_nuc, metadata)
-                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                    INSERT INTO order_items (id, order_id, product_id, product_type, product_code, name, description, price_nuc, quantity, status, revenue_status, operating_carrier_id, net_rate_nuc, commission_nuc, metadata, tax_nuc, taxes, traveler_index, traveler_id)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                            (SELECT id FROM travelers WHERE order_id = $2 AND traveler_index = $18))
                     "#,
                 )
                 .bind(item_id)
@@ -234,6 +418,9 @@ impl OrderRepository for StoreOrderRepository {
                 .bind(net_rate_nuc)
                 .bind(commission_nuc)
                 .bind(metadata)
+                .bind(tax_nuc)
+                .bind(taxes)
+                .bind(traveler_index(item))
                 .execute(&mut *tx)
                 .await?;
             }
@@ -248,113 +435,15 @@ impl OrderRepository for StoreOrderRepository {
         &self,
         id: Uuid,
     ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
-        let order_row = sqlx::query_as::<_, OrderRow>(
-            "SELECT id, customer_id, customer_email, offer_id, airline_id, status, total_nuc, currency, payment_method, payment_reference, customer_did, contact_phone, contact_first_name, contact_last_name, expires_at, created_at, updated_at FROM orders WHERE id = $1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(row) = order_row {
-            let items_rows = sqlx::query_as::<_, OrderItemRow>(
-                "SELECT id, order_id, product_id, product_type, product_code, name, description, price_nuc, quantity, status, revenue_status, operating_carrier_id, net_rate_nuc, commission_nuc, metadata, created_at, updated_at FROM order_items WHERE order_id = $1"
-            )
-            .bind(id)
-            .fetch_all(&self.pool)
-            .await?;
-
-            let items: Vec<Value> = items_rows.into_iter().map(|item| {
-                serde_json::json!({
-                    "id": item.id,
-                    "product_id": item.product_id,
-                    "product_type": item.product_type,
-                    "product_code": item.product_code,
-                    "name": item.name,
-                    "description": item.description,
-                    "price_nuc": item.price_nuc,
-                    "quantity": item.quantity,
-                    "status": item.status,
-                    "revenue_status": item.revenue_status,
-                    "operating_carrier_id": item.operating_carrier_id,
-                    "net_rate_nuc": item.net_rate_nuc,
-                    "commission_nuc": item.commission_nuc,
-                    "metadata": item.metadata,
-                    "created_at": item.created_at.map(|t| t.to_rfc3339()),
-                    "updated_at": item.updated_at.map(|t| t.to_rfc3339())
-                })
-            }).collect();
-
-            let fulfillment_rows = sqlx::query_as::<_, FulfillmentRow>(
-                "SELECT id, order_id, order_item_id, fulfillment_type, barcode, qr_code_data, delivery_method, delivered_at, created_at FROM fulfillment WHERE order_id = $1"
-            )
-            .bind(id)
-            .fetch_all(&self.pool)
-            .await?;
-
-             let fulfillment: Vec<Value> = fulfillment_rows.into_iter().map(|f| {
-                serde_json::json!({
-                    "id": f.id,
-                    "order_item_id": f.order_item_id,
-                    "fulfillment_type": f.fulfillment_type,
-                    "barcode": f.barcode,
-                    "qr_code_data": f.qr_code_data,
-                    "delivery_method": f.delivery_method,
-                    "delivered_at": f.delivered_at.map(|t| t.to_rfc3339()),
-                    "created_at": f.created_at.map(|t| t.to_rfc3339())
-                })
-            }).collect();
-
-            let traveler_rows = sqlx::query_as::<_, TravelerRow>(
-                "SELECT id, traveler_index, ptc, first_name, last_name, date_of_birth, gender, traveler_did, metadata FROM travelers WHERE order_id = $1"
-            )
-            .bind(id)
-            .fetch_all(&self.pool)
-            .await?;
-
-            let travelers: Vec<Value> = traveler_rows.into_iter().map(|t| {
-                serde_json::json!({
-                    "id": t.id,
-                    "traveler_index": t.traveler_index,
-                    "ptc": t.ptc,
-                    "first_name": t.first_name,
-                    "last_name": t.last_name,
-                    "date_of_birth": t.date_of_birth.map(|d| d.format("%Y-%m-%d").to_string()),
-                    "gender": t.gender,
-                    "traveler_did": t.traveler_did,
-                    "metadata": t.metadata
-                })
-            }).collect();
-
-            let order_json = serde_json::json!({
-                "id": row.id,
-                "customer_id": row.customer_id,
-                "customer_email": row.customer_email,
-                "contact_info": {
-                    "email": row.customer_email,
-                    "phone": row.contact_phone,
-                    "first_name": row.contact_first_name,
-                    "last_name": row.contact_last_name,
-                },
-                "offer_id": row.offer_id,
-                "airline_id": row.airline_id,
-                "status": row.status,
-                "total_nuc": row.total_nuc,
-                "currency": row.currency,
-                "payment_method": row.payment_method,
-                "payment_reference": row.payment_reference,
-                "customer_did": row.customer_did,
-                "expires_at": row.expires_at.map(|t| t.to_rfc3339()),
-                "items": items,
-                "travelers": travelers,
-                "fulfillment": fulfillment,
-                "created_at": row.created_at.map(|t| t.to_rfc3339()),
-                "updated_at": row.updated_at.map(|t| t.to_rfc3339())
-            });
-
-            return Ok(Some(order_json));
-        }
+        self.fetch_order(&TenantContext::platform(), id).await
+    }
 
-        Ok(None)
+    async fn get_tenant_order(
+        &self,
+        tenant: &TenantContext,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_order(tenant, id).await
     }
 
     async fn update_order_status(
@@ -367,7 +456,7 @@ impl OrderRepository for StoreOrderRepository {
         )
         .bind(status)
         .bind(id)
-        .execute(&self.pool)
+        .execute(self.db.writer())
         .await?;
         Ok(())
     }
@@ -388,17 +477,20 @@ impl OrderRepository for StoreOrderRepository {
         let description = item["description"].as_str();
         let price_nuc = item["price_nuc"].as_i64().unwrap_or(0) as i32;
         let quantity = item["quantity"].as_i64().unwrap_or(1) as i32;
-        let status = String::from("ACTIVE");
+        let status = item["status"].as_str().unwrap_or("ACTIVE");
         let operating_carrier_id_str = item["operating_carrier_id"].as_str();
         let operating_carrier_id = if let Some(id) = operating_carrier_id_str { Some(Uuid::parse_str(id)?) } else { None };
         let net_rate_nuc = item["net_rate_nuc"].as_i64().map(|v| v as i32);
         let commission_nuc = item["commission_nuc"].as_i64().map(|v| v as i32);
         let metadata = &item["metadata"];
+        let tax_nuc = item["tax_nuc"].as_i64().unwrap_or(0) as i32;
+        let taxes = item.get("taxes").cloned().unwrap_or_else(|| serde_json::json!([]));
 
         sqlx::query(
             r#"
-            INSERT INTO order_items (id, order_id, product_id, product_type, product_code, name, description, price_nuc, quantity, status, revenue_status, operating_carrier_id, net_rate_nuc, commission_nuc, metadata)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            INSERT INTO order_items (id, order_id, product_id, product_type, product_code, name, description, price_nuc, quantity, status, revenue_status, operating_carrier_id, net_rate_nuc, commission_nuc, metadata, tax_nuc, taxes, traveler_index, traveler_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                    (SELECT id FROM travelers WHERE order_id = $2 AND traveler_index = $18))
             "#,
         )
         .bind(item_id)
@@ -416,7 +508,10 @@ impl OrderRepository for StoreOrderRepository {
         .bind(net_rate_nuc)
         .bind(commission_nuc)
         .bind(metadata)
-        .execute(&self.pool)
+        .bind(tax_nuc)
+        .bind(taxes)
+        .bind(traveler_index(item))
+        .execute(self.db.writer())
         .await?;
 
         Ok(item_id)
@@ -430,7 +525,7 @@ impl OrderRepository for StoreOrderRepository {
             "SELECT id FROM orders WHERE customer_id = $1 ORDER BY created_at DESC",
         )
         .bind(customer_id)
-        .fetch_all(&self.pool)
+        .fetch_all(self.db.reader())
         .await?;
 
         let mut orders = Vec::new();
@@ -443,10 +538,60 @@ impl OrderRepository for StoreOrderRepository {
         Ok(orders)
     }
 
+    async fn get_order_headers(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, OrderRow>(&format!("SELECT {} FROM orders WHERE id = ANY($1)", ORDER_COLUMNS))
+            .bind(ids)
+            .fetch_all(self.db.reader())
+            .await?;
+        Ok(rows.into_iter().map(order_header_json).collect())
+    }
+
+    async fn list_order_items(
+        &self,
+        order_ids: &[Uuid],
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, OrderItemRow>(
+            &format!("SELECT {} FROM order_items WHERE order_id = ANY($1) ORDER BY created_at", ORDER_ITEM_COLUMNS)
+        )
+        .bind(order_ids)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            let order_id = row.order_id;
+            let mut item = item_json(row);
+            item["order_id"] = serde_json::json!(order_id);
+            item
+        }).collect())
+    }
+
+    async fn list_order_travelers(
+        &self,
+        order_ids: &[Uuid],
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, TravelerRow>(
+            &format!("SELECT {} FROM travelers WHERE order_id = ANY($1) ORDER BY traveler_index", TRAVELER_COLUMNS)
+        )
+        .bind(order_ids)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            let order_id = row.order_id;
+            let mut traveler = traveler_json(row);
+            traveler["order_id"] = serde_json::json!(order_id);
+            traveler
+        }).collect())
+    }
+
     async fn create_fulfillment(
         &self,
         order_id: Uuid,
         order_item_id: Uuid,
+        holder: &Value,
         fulfillment_type: &str,
         barcode: &str,
     ) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>> {
@@ -454,43 +599,192 @@ impl OrderRepository for StoreOrderRepository {
         
         sqlx::query(
             r#"
-            INSERT INTO fulfillment (id, order_id, order_item_id, fulfillment_type, barcode)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO fulfillment (id, order_id, order_item_id, traveler_id, traveler_index, unit_index, segment, fulfillment_type, barcode)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             "#,
         )
         .bind(fulfillment_id)
         .bind(order_id)
         .bind(order_item_id)
+        .bind(holder["traveler_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+        .bind(holder["traveler_index"].as_i64().map(|index| index as i32))
+        .bind(holder["unit_index"].as_i64().unwrap_or(0) as i32)
+        .bind(holder.get("segment").filter(|segment| !segment.is_null()))
         .bind(fulfillment_type)
         .bind(barcode)
-        .execute(&self.pool)
+        .execute(self.db.writer())
+        .await?;
+
+        Ok(fulfillment_id)
+    }
+
+    async fn schedule_fulfillment(
+        &self,
+        order_id: Uuid,
+        order_item_id: Uuid,
+        holder: &Value,
+        fulfillment_type: &str,
+        deliver_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Uuid, Box<dyn std::error::Error + Send + Sync>> {
+        let fulfillment_id = Uuid::new_v4();
+
+        sqlx::query(
+            r#"
+            INSERT INTO fulfillment (id, order_id, order_item_id, traveler_id, traveler_index, unit_index, segment, fulfillment_type, deliver_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(fulfillment_id)
+        .bind(order_id)
+        .bind(order_item_id)
+        .bind(holder["traveler_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+        .bind(holder["traveler_index"].as_i64().map(|index| index as i32))
+        .bind(holder["unit_index"].as_i64().unwrap_or(0) as i32)
+        .bind(holder.get("segment").filter(|segment| !segment.is_null()))
+        .bind(fulfillment_type)
+        .bind(deliver_at)
+        .execute(self.db.writer())
         .await?;
 
         Ok(fulfillment_id)
     }
 
+    async fn claim_due_deliveries(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+        max_attempts: i32,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // SKIP LOCKED lets several API instances run the worker without double-sending
+        let rows = sqlx::query(
+            r#"
+            UPDATE fulfillment
+            SET deliver_at = NOW() + make_interval(secs => $2), delivery_attempts = delivery_attempts + 1
+            WHERE id IN (
+                SELECT id FROM fulfillment
+                WHERE delivered_at IS NULL AND voided_at IS NULL AND expired_at IS NULL AND deliver_at <= NOW()
+                  AND delivery_attempts < $3
+                ORDER BY deliver_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, order_id, order_item_id, traveler_index, unit_index, fulfillment_type, delivery_attempts
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_seconds as f64)
+        .bind(max_attempts)
+        .fetch_all(self.db.writer())
+        .await?;
+
+        Ok(rows.iter().map(|row| {
+            serde_json::json!({
+                "id": sqlx::Row::get::<Uuid, _>(row, "id"),
+                "order_id": sqlx::Row::get::<Option<Uuid>, _>(row, "order_id"),
+                "order_item_id": sqlx::Row::get::<Option<Uuid>, _>(row, "order_item_id"),
+                "traveler_index": sqlx::Row::get::<Option<i32>, _>(row, "traveler_index"),
+                "unit_index": sqlx::Row::get::<i32, _>(row, "unit_index"),
+                "fulfillment_type": sqlx::Row::get::<String, _>(row, "fulfillment_type"),
+                "delivery_attempts": sqlx::Row::get::<i32, _>(row, "delivery_attempts"),
+            })
+        }).collect())
+    }
+
+    async fn complete_delivery(
+        &self,
+        fulfillment_id: Uuid,
+        barcode: &str,
+        delivery_method: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            "UPDATE fulfillment SET barcode = $2, delivery_method = $3, delivered_at = NOW(), last_delivery_error = NULL WHERE id = $1",
+        )
+        .bind(fulfillment_id)
+        .bind(barcode)
+        .bind(delivery_method)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn fail_delivery(
+        &self,
+        fulfillment_id: Uuid,
+        error: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE fulfillment SET last_delivery_error = $2 WHERE id = $1")
+            .bind(fulfillment_id)
+            .bind(error)
+            .execute(self.db.writer())
+            .await?;
+        Ok(())
+    }
+
+    async fn find_fulfillment(
+        &self,
+        barcode: &str,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let found: Option<serde_json::Value> = sqlx::query_scalar(
+            r#"
+            SELECT jsonb_build_object(
+                'id', f.id,
+                'order_id', f.order_id,
+                'order_item_id', f.order_item_id,
+                'traveler_index', f.traveler_index,
+                'unit_index', f.unit_index,
+                'consumed_at', f.consumed_at,
+                'consumption_location', f.consumption_location,
+                'consumed_by', f.consumed_by,
+                'voided_at', f.voided_at,
+                'expired_at', f.expired_at,
+                'airline_id', o.airline_id,
+                'airport', coalesce(
+                    f.segment->>'origin',
+                    i.metadata->>'origin',
+                    (SELECT fi.metadata->>'origin' FROM order_items fi
+                     WHERE fi.order_id = f.order_id AND fi.product_type = 'FLIGHT'
+                       AND fi.metadata->>'flight_id' = i.metadata->>'flight_id'
+                     LIMIT 1)
+                )
+            )
+            FROM fulfillment f
+            JOIN orders o ON o.id = f.order_id
+            JOIN order_items i ON i.id = f.order_item_id
+            WHERE f.barcode = $1
+            "#,
+        )
+        .bind(barcode)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(found)
+    }
+
     async fn consume_fulfillment(
         &self,
         barcode: &str,
         location: &str,
-    ) -> Result<(Uuid, Uuid), Box<dyn std::error::Error + Send + Sync>> {
+        consumed_by: &str,
+        consumed_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<(Uuid, Uuid)>, Box<dyn std::error::Error + Send + Sync>> {
         let row = sqlx::query(
             r#"
             UPDATE fulfillment 
-            SET consumed_at = NOW(), consumption_location = $2
-            WHERE barcode = $1
+            SET consumed_at = $4, consumption_location = $2, consumed_by = $3
+            WHERE barcode = $1 AND voided_at IS NULL AND expired_at IS NULL AND consumed_at IS NULL
             RETURNING order_id, order_item_id
             "#,
         )
         .bind(barcode)
         .bind(location)
-        .fetch_one(&self.pool)
+        .bind(consumed_by)
+        .bind(consumed_at)
+        .fetch_optional(self.db.writer())
         .await?;
 
-        Ok((
+        Ok(row.map(|row| (
             sqlx::Row::get(&row, "order_id"),
             sqlx::Row::get(&row, "order_item_id"),
-        ))
+        )))
     }
 
     async fn add_order_change(
@@ -514,7 +808,7 @@ impl OrderRepository for StoreOrderRepository {
         .bind(new_value)
         .bind(changed_by)
         .bind(reason)
-        .execute(&self.pool)
+        .execute(self.db.writer())
         .await?;
 
         Ok(())
@@ -525,10 +819,10 @@ impl OrderRepository for StoreOrderRepository {
         flight_id: &str,
     ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
         let rows = sqlx::query_as::<_, OrderRow>(
-            "SELECT id, customer_id, customer_email, offer_id, airline_id, status, total_nuc, currency, payment_method, payment_reference, created_at, updated_at FROM orders WHERE id IN (SELECT order_id FROM order_items WHERE metadata->>'flight_id' = $1)"
+            "SELECT id, customer_id, customer_email, offer_id, airline_id, status, total_nuc, currency, payment_method, payment_reference, customer_did, contact_phone, contact_first_name, contact_last_name, expires_at, group_size, names_due_at, test, payment_intent_id, payment_action_expires_at, created_at, updated_at FROM orders WHERE id IN (SELECT order_id FROM order_items WHERE metadata->>'flight_id' = $1)"
         )
         .bind(flight_id)
-        .fetch_all(&self.pool)
+        .fetch_all(self.db.reader())
         .await?;
 
         let mut orders = Vec::new();
@@ -540,6 +834,110 @@ impl OrderRepository for StoreOrderRepository {
         Ok(orders)
     }
 
+    async fn find_orders_by_reference(
+        &self,
+        reference: &str,
+        last_name: &str,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT o.id FROM orders o
+            WHERE upper(left(o.id::text, 6)) = upper($1)
+              AND (lower(o.contact_last_name) = lower($2)
+                   OR EXISTS (SELECT 1 FROM travelers t WHERE t.order_id = o.id AND lower(t.last_name) = lower($2)))
+            ORDER BY o.created_at DESC
+            "#,
+        )
+        .bind(reference)
+        .bind(last_name)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        let mut orders = Vec::new();
+        for id in ids {
+            if let Some(order) = self.get_order(id).await? {
+                orders.push(order);
+            }
+        }
+        Ok(orders)
+    }
+
+    async fn save_travelers(
+        &self,
+        order_id: Uuid,
+        travelers: &[Value],
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        for traveler in travelers {
+            let dob = traveler["date_of_birth"].as_str()
+                .and_then(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok());
+
+            sqlx::query(
+                r#"
+                INSERT INTO travelers (order_id, traveler_index, ptc, first_name, last_name, date_of_birth, gender)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (order_id, traveler_index) DO UPDATE
+                SET ptc = EXCLUDED.ptc,
+                    first_name = EXCLUDED.first_name,
+                    last_name = EXCLUDED.last_name,
+                    date_of_birth = EXCLUDED.date_of_birth,
+                    gender = EXCLUDED.gender
+                "#,
+            )
+            .bind(order_id)
+            .bind(traveler["traveler_index"].as_i64().unwrap_or(0) as i32)
+            .bind(traveler["ptc"].as_str().unwrap_or("ADT"))
+            .bind(traveler["first_name"].as_str().unwrap_or_default())
+            .bind(traveler["last_name"].as_str().unwrap_or_default())
+            .bind(dob)
+            .bind(traveler["gender"].as_str())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Items bought for a traveler before their name was in
+        sqlx::query(
+            r#"
+            UPDATE order_items i
+            SET traveler_id = t.id, updated_at = NOW()
+            FROM travelers t
+            WHERE i.order_id = $1 AND t.order_id = $1 AND t.traveler_index = i.traveler_index
+              AND i.traveler_id IS DISTINCT FROM t.id
+            "#,
+        )
+        .bind(order_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(travelers.len())
+    }
+
+    async fn customer_has_flight_order(
+        &self,
+        owner: &str,
+        flight_id: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let exists: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM orders o
+                JOIN order_items oi ON oi.order_id = o.id
+                WHERE (o.customer_id = $1 OR o.customer_did = $1)
+                  AND oi.metadata->>'flight_id' = $2
+                  AND o.status NOT IN ('CANCELLED', 'EXPIRED')
+            )
+            "#,
+        )
+        .bind(owner)
+        .bind(flight_id)
+        .fetch_one(self.db.writer())
+        .await?;
+
+        Ok(exists)
+    }
+
     async fn add_order_ledger_entry(
         &self,
         order_id: Uuid,
@@ -561,11 +959,128 @@ impl OrderRepository for StoreOrderRepository {
         .bind(transaction_type)
         .bind(amount_nuc)
         .bind(description)
-        .execute(&self.pool)
+        .execute(self.db.writer())
         .await?;
         Ok(entry_id)
     }
 
+    async fn add_ledger_entries(
+        &self,
+        entries: &[Value],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        for entry in entries {
+            let uuid = |field: &str| entry[field].as_str().and_then(|v| Uuid::parse_str(v).ok());
+            sqlx::query(
+                r#"
+                INSERT INTO order_ledger (id, order_id, order_item_id, transaction_type, amount_nuc, currency, description, counterparty_id, tax_nuc)
+                VALUES ($1, $2, $3, $4, $5, COALESCE($6, 'NUC'), $7, $8, $9)
+                "#,
+            )
+            .bind(uuid("id").unwrap_or_else(Uuid::new_v4))
+            .bind(uuid("order_id").ok_or("ledger entry without order_id")?)
+            .bind(uuid("order_item_id").ok_or("ledger entry without order_item_id")?)
+            .bind(entry["transaction_type"].as_str().ok_or("ledger entry without transaction_type")?)
+            .bind(entry["amount_nuc"].as_i64().unwrap_or(0) as i32)
+            .bind(entry["currency"].as_str())
+            .bind(entry["description"].as_str())
+            .bind(uuid("counterparty_id"))
+            .bind(entry["tax_nuc"].as_i64().unwrap_or(0) as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn transfer_order_item(
+        &self,
+        item_id: Uuid,
+        from_order_id: Uuid,
+        to_order_id: Uuid,
+        traveler_index: Option<i32>,
+        new_barcode: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        // Revenue stays with the order that paid for the item, however many times it moves
+        let moved = sqlx::query_scalar::<_, i32>(
+            r#"
+            UPDATE order_items
+            SET order_id = $3,
+                metadata = COALESCE(metadata, '{}'::jsonb) || jsonb_build_object(
+                    'original_order_id', COALESCE(metadata->>'original_order_id', $2::text),
+                    'traveler_index', $4::int,
+                    'transferred_at', NOW()
+                ),
+                traveler_index = $4,
+                traveler_id = (SELECT id FROM travelers WHERE order_id = $3 AND traveler_index = $4),
+                updated_at = NOW()
+            WHERE id = $1 AND order_id = $2
+              AND product_type <> 'FLIGHT'
+              AND UPPER(status) = 'ACTIVE'
+              AND UPPER(revenue_status) = 'UNEARNED'
+              AND NOT EXISTS (
+                  SELECT 1 FROM fulfillment WHERE order_item_id = $1 AND consumed_at IS NOT NULL
+              )
+            RETURNING price_nuc * (GREATEST(COALESCE(quantity, 1), 1) - refunded_quantity)
+            "#,
+        )
+        .bind(item_id)
+        .bind(from_order_id)
+        .bind(to_order_id)
+        .bind(traveler_index)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(price_nuc) = moved else {
+            return Ok(false);
+        };
+
+        sqlx::query("UPDATE fulfillment SET voided_at = NOW() WHERE order_item_id = $1 AND voided_at IS NULL")
+            .bind(item_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // Issued to the traveler it now belongs to, if any, one per unit still held
+        sqlx::query(
+            r#"
+            INSERT INTO fulfillment (id, order_id, order_item_id, traveler_id, traveler_index, unit_index, fulfillment_type, barcode)
+            SELECT gen_random_uuid(), $1, $2, (SELECT id FROM travelers WHERE order_id = $1 AND traveler_index = $4), $4, unit, 'BARCODE',
+                   CASE WHEN unit = 0 THEN $3 ELSE $3 || '-U' || unit END
+            FROM order_items i
+            CROSS JOIN generate_series(0, GREATEST(COALESCE(i.quantity, 1), 1) - i.refunded_quantity - 1) unit
+            WHERE i.id = $2
+            "#,
+        )
+        .bind(to_order_id)
+        .bind(item_id)
+        .bind(new_barcode)
+        .bind(traveler_index)
+        .execute(&mut *tx)
+        .await?;
+
+        // Zero-amount memos: the transfer moves no money
+        let memos = [
+            (from_order_id, "TRANSFER_OUT", format!("Item transferred to order {} (value {} NUC)", to_order_id, price_nuc)),
+            (to_order_id, "TRANSFER_IN", format!("Item received from order {} (value {} NUC)", from_order_id, price_nuc)),
+        ];
+        for (order_id, transaction_type, description) in memos {
+            sqlx::query(
+                "INSERT INTO order_ledger (order_id, order_item_id, transaction_type, amount_nuc, description) VALUES ($1, $2, $3, 0, $4)",
+            )
+            .bind(order_id)
+            .bind(item_id)
+            .bind(transaction_type)
+            .bind(description)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
     async fn update_item_revenue_status(
         &self,
         item_id: Uuid,
@@ -576,7 +1091,293 @@ impl OrderRepository for StoreOrderRepository {
         )
         .bind(status)
         .bind(item_id)
-        .execute(&self.pool)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn update_item_status(
+        &self,
+        item_id: Uuid,
+        status: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            "UPDATE order_items SET status = $1, updated_at = NOW() WHERE id = $2",
+        )
+        .bind(status)
+        .bind(item_id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn schedule_revenue_recognition(
+        &self,
+        item_id: Uuid,
+        recognize_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE order_items SET recognize_at = $2 WHERE id = $1")
+            .bind(item_id)
+            .bind(recognize_at)
+            .execute(self.db.writer())
+            .await?;
+        Ok(())
+    }
+
+    async fn claim_due_recognitions(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // SKIP LOCKED lets several API instances run the worker without double-posting
+        let rows: Vec<(Uuid, Option<Uuid>)> = sqlx::query_as(
+            r#"
+            UPDATE order_items
+            SET recognize_at = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT i.id FROM order_items i JOIN orders o ON o.id = i.order_id
+                WHERE i.recognize_at <= NOW()
+                  AND UPPER(i.status) = 'ACTIVE' AND UPPER(i.revenue_status) = 'UNEARNED'
+                  AND o.status IN ('PAID', 'FULFILLED')
+                ORDER BY i.recognize_at
+                LIMIT $1
+                FOR UPDATE OF i SKIP LOCKED
+            )
+            RETURNING id, order_id
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_seconds as f64)
+        .fetch_all(self.db.writer())
+        .await?;
+
+        Ok(rows.into_iter().map(|(id, order_id)| serde_json::json!({
+            "id": id,
+            "order_id": order_id,
+        })).collect())
+    }
+
+    async fn complete_scheduled_recognition(
+        &self,
+        item_id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            "UPDATE order_items SET revenue_status = 'EARNED', recognize_at = NULL, updated_at = NOW() WHERE id = $1 AND UPPER(revenue_status) = 'UNEARNED'",
+        )
+        .bind(item_id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn expire_unconsumed_fulfillment(
+        &self,
+        item_id: Uuid,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            "UPDATE fulfillment SET expired_at = NOW() WHERE order_item_id = $1 AND voided_at IS NULL AND consumed_at IS NULL AND expired_at IS NULL",
+        )
+        .bind(item_id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn transition_item_status(
+        &self,
+        item_id: Uuid,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            "UPDATE order_items SET status = $1, updated_at = NOW() WHERE id = $2 AND status = $3",
+        )
+        .bind(to)
+        .bind(item_id)
+        .bind(from)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn refund_item_units(
+        &self,
+        item_id: Uuid,
+        units: i32,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let held: Option<(i32, i64)> = sqlx::query_as(
+            r#"
+            SELECT GREATEST(COALESCE(i.quantity, 1), 1) - i.refunded_quantity,
+                   (SELECT count(*) FROM fulfillment f WHERE f.order_item_id = i.id AND f.voided_at IS NULL AND f.consumed_at IS NOT NULL)
+            FROM order_items i
+            WHERE i.id = $1 AND UPPER(i.status) = 'ACTIVE'
+            FOR UPDATE
+            "#,
+        )
+        .bind(item_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((held, consumed)) = held else { return Ok(false) };
+        if units < 1 || (held as i64 - consumed) < units as i64 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE fulfillment SET voided_at = NOW()
+            WHERE id IN (
+                SELECT id FROM fulfillment
+                WHERE order_item_id = $1 AND voided_at IS NULL AND consumed_at IS NULL
+                ORDER BY unit_index DESC
+                LIMIT $2
+                FOR UPDATE
+            )
+            "#,
+        )
+        .bind(item_id)
+        .bind(units as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        // What's left is earned if every unit still held has been used
+        sqlx::query(
+            r#"
+            UPDATE order_items
+            SET refunded_quantity = refunded_quantity + $2,
+                status = CASE WHEN $3 = 0 THEN 'REFUNDED' ELSE status END,
+                revenue_status = CASE WHEN $3 = 0 THEN 'REFUNDED' WHEN $4 >= $3 THEN 'EARNED' ELSE revenue_status END,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(item_id)
+        .bind(units)
+        .bind(held - units)
+        .bind(consumed as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn expire_reaccommodation_proposals(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(Uuid, Option<Uuid>, Option<Uuid>, Option<i32>)> = sqlx::query_as(
+            r#"
+            UPDATE order_items
+            SET status = 'CANCELLED', metadata = COALESCE(metadata, '{}'::jsonb) || '{"proposal_status": "EXPIRED"}'::jsonb, updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM order_items
+                WHERE status = 'REACCOMMODATED' AND (metadata->>'hold_expires_at')::timestamptz < NOW()
+                ORDER BY id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, order_id, product_id, quantity
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(self.db.writer())
+        .await?;
+
+        Ok(rows.into_iter().map(|(id, order_id, product_id, quantity)| serde_json::json!({
+            "id": id,
+            "order_id": order_id,
+            "product_id": product_id,
+            "quantity": quantity.unwrap_or(1),
+        })).collect())
+    }
+
+    async fn await_payment_action(
+        &self,
+        order_id: Uuid,
+        intent_id: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            "UPDATE orders SET payment_intent_id = $2, payment_action_expires_at = $3, updated_at = NOW() WHERE id = $1 AND status = 'PAYMENT_PENDING'",
+        )
+        .bind(order_id)
+        .bind(intent_id)
+        .bind(expires_at)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn transition_order_status(
+        &self,
+        order_id: Uuid,
+        from: &[&str],
+        to: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            "UPDATE orders SET status = $2, payment_action_expires_at = NULL, updated_at = NOW() WHERE id = $1 AND status = ANY($3)",
+        )
+        .bind(order_id)
+        .bind(to)
+        .bind(from)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn claim_expired_payment_actions(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let claimed = sqlx::query_scalar::<_, Value>(
+            r#"
+            UPDATE orders SET payment_action_expires_at = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM orders
+                WHERE status = 'PAYMENT_PENDING' AND payment_action_expires_at <= NOW()
+                ORDER BY payment_action_expires_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING jsonb_build_object('id', id, 'payment_intent_id', payment_intent_id, 'test', test)
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_seconds as f64)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(claimed)
+    }
+
+    async fn merge_item_metadata(
+        &self,
+        item_id: Uuid,
+        patch: &Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            "UPDATE order_items SET metadata = COALESCE(metadata, '{}'::jsonb) || $1, updated_at = NOW() WHERE id = $2",
+        )
+        .bind(patch)
+        .bind(item_id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn adjust_order_total(
+        &self,
+        order_id: Uuid,
+        delta_nuc: i32,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            "UPDATE orders SET total_nuc = total_nuc + $1, updated_at = NOW() WHERE id = $2",
+        )
+        .bind(delta_nuc)
+        .bind(order_id)
+        .execute(self.db.writer())
         .await?;
         Ok(())
     }
@@ -586,10 +1387,10 @@ impl OrderRepository for StoreOrderRepository {
         order_id: Uuid,
     ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
         let rows = sqlx::query_as::<_, LedgerRow>(
-            "SELECT id, order_id, order_item_id, transaction_type, amount_nuc, currency, description, created_at FROM order_ledger WHERE order_id = $1 ORDER BY created_at"
+            "SELECT id, order_id, order_item_id, transaction_type, amount_nuc, currency, description, created_at, counterparty_id, tax_nuc FROM order_ledger WHERE order_id = $1 ORDER BY created_at"
         )
         .bind(order_id)
-        .fetch_all(&self.pool)
+        .fetch_all(self.db.reader())
         .await?;
 
         let ledger = rows.into_iter().map(|row| {
@@ -601,10 +1402,224 @@ impl OrderRepository for StoreOrderRepository {
                 "amount_nuc": row.amount_nuc,
                 "currency": row.currency,
                 "description": row.description,
-                "created_at": row.created_at.as_ref().map(|t| t.to_rfc3339())
+                "created_at": row.created_at.as_ref().map(|t| t.to_rfc3339()),
+                "counterparty_id": row.counterparty_id,
+                "tax_nuc": row.tax_nuc
             })
         }).collect();
 
         Ok(ledger)
     }
+
+    async fn get_order_changes(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let changes = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(c) FROM order_changes c WHERE order_id = $1 ORDER BY created_at"
+        )
+        .bind(order_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(changes)
+    }
+
+    async fn get_fulfillment_history(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let records = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(f) FROM fulfillment f WHERE order_id = $1 ORDER BY created_at"
+        )
+        .bind(order_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn create_compensation_payout(
+        &self,
+        payout: &Value,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let created = sqlx::query_scalar::<_, Value>(
+            r#"
+            INSERT INTO compensation_payouts (order_id, order_item_id, scheme, amount_nuc, payout_method, voucher_code)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (order_item_id, scheme) DO NOTHING
+            RETURNING to_jsonb(compensation_payouts)
+            "#,
+        )
+        .bind(payout["order_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+        .bind(payout["order_item_id"].as_str().and_then(|id| Uuid::parse_str(id).ok()))
+        .bind(payout["scheme"].as_str())
+        .bind(payout["amount_nuc"].as_i64().map(|amount| amount as i32))
+        .bind(payout["payout_method"].as_str())
+        .bind(payout["voucher_code"].as_str())
+        .fetch_optional(self.db.writer())
+        .await?;
+
+        Ok(created)
+    }
+
+    async fn list_compensation_payouts(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let payouts = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(p) FROM compensation_payouts p WHERE order_id = $1 ORDER BY created_at"
+        )
+        .bind(order_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(payouts)
+    }
+
+    async fn count_customer_holds(
+        &self,
+        customer_id: &str,
+        airline_id: Uuid,
+        product_type: &str,
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        let holds: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM orders o
+            WHERE o.customer_id = $1 AND o.airline_id = $2 AND o.status = 'PROPOSED' AND o.expires_at > NOW()
+              AND EXISTS (
+                  SELECT 1 FROM order_items i
+                  WHERE i.order_id = o.id AND i.status = 'ACTIVE'
+                    AND UPPER(REPLACE(i.product_type, '_', '')) = UPPER(REPLACE($3, '_', ''))
+              )
+            "#,
+        )
+        .bind(customer_id)
+        .bind(airline_id)
+        .bind(product_type)
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(holds)
+    }
+
+    async fn save_order_snapshot(
+        &self,
+        order_id: Uuid,
+        snapshot: &serde_json::Value,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO order_snapshots (order_id, offers, pricing, business_rules, sha256)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (order_id) DO NOTHING
+            "#,
+        )
+        .bind(order_id)
+        .bind(&snapshot["offers"])
+        .bind(&snapshot["pricing"])
+        .bind(&snapshot["business_rules"])
+        .bind(snapshot["sha256"].as_str().ok_or("Snapshot without sha256")?)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn get_order_snapshot(
+        &self,
+        order_id: Uuid,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let snapshot: Option<serde_json::Value> = sqlx::query_scalar(
+            "SELECT to_jsonb(s) FROM order_snapshots s WHERE order_id = $1",
+        )
+        .bind(order_id)
+        .fetch_optional(self.db.reader())
+        .await?;
+        Ok(snapshot)
+    }
+
+    async fn seats_taken(
+        &self,
+        flight_id: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let seats: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT metadata->>'seat_number' FROM order_items
+            WHERE product_type = 'SEAT' AND status = 'ACTIVE'
+              AND metadata->>'flight_id' = $1 AND metadata ? 'seat_number'
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(seats)
+    }
+
+    async fn due_seat_assignments(
+        &self,
+        until: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let due: Vec<Value> = sqlx::query_scalar(
+            r#"
+            SELECT jsonb_build_object('order_id', o.id, 'order_item_id', oi.id)
+            FROM order_items oi
+            JOIN orders o ON o.id = oi.order_id
+            WHERE oi.product_type = 'FLIGHT'
+              AND oi.status = 'ACTIVE'
+              AND o.status IN ('PAID', 'FULFILLED')
+              AND (oi.metadata->>'departure_time')::timestamptz BETWEEN NOW() AND $1
+              AND NOT oi.metadata ? 'seats_assigned_at'
+            ORDER BY (oi.metadata->>'departure_time')::timestamptz
+            LIMIT $2
+            "#,
+        )
+        .bind(until)
+        .bind(limit)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(due)
+    }
+
+    async fn due_dcs_exports(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let due: Vec<Value> = sqlx::query_scalar(
+            r#"
+            SELECT jsonb_build_object('order_id', order_id, 'order_item_id', id)
+            FROM order_items
+            WHERE product_type = 'FLIGHT' AND status = 'ACTIVE'
+              AND metadata ? 'checked_in_at' AND NOT metadata ? 'dcs_exported_at'
+            ORDER BY metadata->>'checked_in_at'
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(due)
+    }
+
+    async fn confirm_service_delivery(
+        &self,
+        order_id: Uuid,
+        order_item_id: Uuid,
+        barcode: Option<&str>,
+        delivered_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE fulfillment SET delivered_at = $4, delivery_method = 'DCS'
+            WHERE order_id = $1 AND order_item_id = $2 AND voided_at IS NULL
+              AND ($3::text IS NULL OR barcode = $3)
+            "#,
+        )
+        .bind(order_id)
+        .bind(order_item_id)
+        .bind(barcode)
+        .bind(delivered_at)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected())
+    }
 }
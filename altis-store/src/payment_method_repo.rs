@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::PaymentMethodRepository;
+
+use crate::DbClient;
+
+pub struct StorePaymentMethodRepository {
+    db: DbClient,
+}
+
+impl StorePaymentMethodRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PaymentMethodRepository for StorePaymentMethodRepository {
+    async fn create_payment_method(&self, method: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let customer_id = method["customer_id"].as_str().ok_or("missing customer_id")?;
+        let provider = method["provider"].as_str().ok_or("missing provider")?;
+        let test = method["test"].as_bool().unwrap_or(false);
+        let mut tx = self.db.writer().begin().await?;
+
+        let created: Option<Value> = sqlx::query_scalar(
+            r#"
+            INSERT INTO payment_methods (customer_id, provider, provider_token, brand, last4, exp_month, exp_year, fingerprint, test, is_default)
+            SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, NOT EXISTS (
+                SELECT 1 FROM payment_methods
+                WHERE customer_id = $1 AND provider = $2 AND test = $9 AND status = 'ACTIVE'
+            )
+            ON CONFLICT (customer_id, provider, test, fingerprint) WHERE status = 'ACTIVE' DO NOTHING
+            RETURNING to_jsonb(payment_methods)
+            "#,
+        )
+        .bind(customer_id)
+        .bind(provider)
+        .bind(method["provider_token"].as_str().ok_or("missing provider_token")?)
+        .bind(method["brand"].as_str())
+        .bind(method["last4"].as_str())
+        .bind(method["exp_month"].as_i64().map(|month| month as i16))
+        .bind(method["exp_year"].as_i64().map(|year| year as i16))
+        .bind(method["fingerprint"].as_str())
+        .bind(test)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let saved = match created {
+            Some(created) => created,
+            None => sqlx::query_scalar(
+                r#"
+                SELECT to_jsonb(m) FROM payment_methods m
+                WHERE customer_id = $1 AND provider = $2 AND test = $3 AND fingerprint = $4 AND status = 'ACTIVE'
+                "#,
+            )
+            .bind(customer_id)
+            .bind(provider)
+            .bind(test)
+            .bind(method["fingerprint"].as_str())
+            .fetch_one(&mut *tx)
+            .await?,
+        };
+
+        tx.commit().await?;
+        Ok(saved)
+    }
+
+    async fn list_payment_methods(&self, customer_id: &str, provider: &str, test: bool) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let methods = sqlx::query_scalar::<_, Value>(
+            r#"
+            SELECT to_jsonb(m) FROM payment_methods m
+            WHERE customer_id = $1 AND provider = $2 AND test = $3 AND status = 'ACTIVE'
+            ORDER BY is_default DESC, created_at DESC
+            "#,
+        )
+        .bind(customer_id)
+        .bind(provider)
+        .bind(test)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(methods)
+    }
+
+    async fn get_payment_method(&self, id: Uuid, customer_id: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // From the primary: a card is often saved and paid with straight away
+        let method = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(m) FROM payment_methods m WHERE id = $1 AND customer_id = $2 AND status = 'ACTIVE'",
+        )
+        .bind(id)
+        .bind(customer_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(method)
+    }
+
+    async fn remove_payment_method(&self, id: Uuid, customer_id: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let removed: Option<Value> = sqlx::query_scalar(
+            r#"
+            UPDATE payment_methods SET status = 'REMOVED', removed_at = NOW()
+            WHERE id = $1 AND customer_id = $2 AND status = 'ACTIVE'
+            RETURNING to_jsonb(payment_methods)
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(removed) = removed.as_ref().filter(|removed| removed["is_default"] == true) {
+            sqlx::query(
+                r#"
+                UPDATE payment_methods SET is_default = TRUE
+                WHERE id = (
+                    SELECT id FROM payment_methods
+                    WHERE customer_id = $1 AND provider = $2 AND test = $3 AND status = 'ACTIVE'
+                    ORDER BY created_at DESC LIMIT 1
+                )
+                "#,
+            )
+            .bind(customer_id)
+            .bind(removed["provider"].as_str())
+            .bind(removed["test"].as_bool().unwrap_or(false))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(removed)
+    }
+}
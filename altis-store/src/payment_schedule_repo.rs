@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::PaymentScheduleRepository;
+
+use crate::DbClient;
+
+pub struct StorePaymentScheduleRepository {
+    db: DbClient,
+}
+
+impl StorePaymentScheduleRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+/// A plan row with its installments folded in, in sequence order
+const SCHEDULE_WITH_INSTALLMENTS: &str = r#"
+    SELECT to_jsonb(s) || jsonb_build_object('installments', COALESCE(
+        (SELECT jsonb_agg(to_jsonb(i) - 'schedule_id' ORDER BY i.sequence) FROM payment_installments i WHERE i.schedule_id = s.id),
+        '[]'::jsonb))
+    FROM payment_schedules s
+"#;
+
+#[async_trait]
+impl PaymentScheduleRepository for StorePaymentScheduleRepository {
+    async fn create_schedule(
+        &self,
+        order_id: Uuid,
+        payment_reference: Option<&str>,
+        installments: &[Value],
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let id: Uuid = sqlx::query_scalar("INSERT INTO payment_schedules (order_id, payment_reference) VALUES ($1, $2) RETURNING id")
+            .bind(order_id)
+            .bind(payment_reference)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        for installment in installments {
+            let due_at: DateTime<Utc> = installment["due_at"].as_str().ok_or("missing due_at")?.parse()?;
+            sqlx::query(
+                "INSERT INTO payment_installments (schedule_id, sequence, due_at, amount_nuc, next_attempt_at) VALUES ($1, $2, $3, $4, $3)",
+            )
+            .bind(id)
+            .bind(installment["sequence"].as_i64().ok_or("missing sequence")? as i32)
+            .bind(due_at)
+            .bind(installment["amount_nuc"].as_i64().ok_or("missing amount_nuc")? as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let created: Value = sqlx::query_scalar(&format!("{} WHERE s.id = $1", SCHEDULE_WITH_INSTALLMENTS))
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    async fn get_schedule(&self, order_id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let schedule = sqlx::query_scalar::<_, Value>(&format!(
+            "{} WHERE s.order_id = $1 AND s.status <> 'CANCELLED' ORDER BY s.created_at DESC LIMIT 1",
+            SCHEDULE_WITH_INSTALLMENTS
+        ))
+        .bind(order_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(schedule)
+    }
+
+    async fn claim_due_installments(&self, limit: i64, lease_seconds: i64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let claimed = sqlx::query_scalar::<_, Value>(
+            r#"
+            UPDATE payment_installments i SET next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM payment_schedules s
+            WHERE s.id = i.schedule_id AND i.id IN (
+                SELECT pi.id FROM payment_installments pi
+                JOIN payment_schedules ps ON ps.id = pi.schedule_id
+                WHERE pi.status = 'PENDING' AND pi.next_attempt_at <= NOW() AND ps.status = 'ACTIVE'
+                ORDER BY pi.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF pi SKIP LOCKED
+            )
+            RETURNING to_jsonb(i) || jsonb_build_object('order_id', s.order_id, 'payment_reference', s.payment_reference)
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_seconds as f64)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(claimed)
+    }
+
+    async fn mark_installment_paid(&self, installment_id: Uuid) -> Result<Option<i64>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let schedule_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE payment_installments SET status = 'PAID', attempts = attempts + 1, paid_at = NOW(), last_error = NULL
+            WHERE id = $1 AND status = 'PENDING'
+            RETURNING schedule_id
+            "#,
+        )
+        .bind(installment_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(schedule_id) = schedule_id else {
+            return Ok(None);
+        };
+
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payment_installments WHERE schedule_id = $1 AND status = 'PENDING'")
+            .bind(schedule_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if remaining == 0 {
+            sqlx::query("UPDATE payment_schedules SET status = 'COMPLETED', updated_at = NOW() WHERE id = $1 AND status = 'ACTIVE'")
+                .bind(schedule_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(remaining))
+    }
+
+    async fn mark_installment_failed(
+        &self,
+        installment_id: Uuid,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            r#"
+            UPDATE payment_installments
+            SET attempts = attempts + 1, last_error = $2,
+                status = CASE WHEN $3::timestamptz IS NULL THEN 'FAILED' ELSE status END,
+                next_attempt_at = COALESCE($3, next_attempt_at)
+            WHERE id = $1 AND status = 'PENDING'
+            "#,
+        )
+        .bind(installment_id)
+        .bind(error)
+        .bind(retry_at)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+
+    async fn close_schedule(&self, schedule_id: Uuid, status: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        let closed = sqlx::query("UPDATE payment_schedules SET status = $2, updated_at = NOW() WHERE id = $1 AND status = 'ACTIVE'")
+            .bind(schedule_id)
+            .bind(status)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() > 0;
+        if closed {
+            sqlx::query("UPDATE payment_installments SET status = 'CANCELLED' WHERE schedule_id = $1 AND status = 'PENDING'")
+                .bind(schedule_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(closed)
+    }
+}
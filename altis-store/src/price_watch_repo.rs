@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::PriceWatchRepository;
+
+use crate::DbClient;
+
+pub struct StorePriceWatchRepository {
+    db: DbClient,
+}
+
+impl StorePriceWatchRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+const WATCH_COLUMNS: &str = "id, customer_id, customer_email, origin, destination, depart_from, depart_to, \
+     passengers, target_price_nuc, status, lowest_price_nuc, lowest_price_date, last_checked_at, triggered_at, created_at";
+
+#[derive(sqlx::FromRow)]
+struct PriceWatchRow {
+    id: Uuid,
+    customer_id: String,
+    customer_email: Option<String>,
+    origin: String,
+    destination: String,
+    depart_from: NaiveDate,
+    depart_to: NaiveDate,
+    passengers: i32,
+    target_price_nuc: i32,
+    status: String,
+    lowest_price_nuc: Option<i32>,
+    lowest_price_date: Option<NaiveDate>,
+    last_checked_at: Option<DateTime<Utc>>,
+    triggered_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl PriceWatchRow {
+    fn into_json(self) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "customer_id": self.customer_id,
+            "customer_email": self.customer_email,
+            "origin": self.origin,
+            "destination": self.destination,
+            "depart_from": self.depart_from,
+            "depart_to": self.depart_to,
+            "passengers": self.passengers,
+            "target_price_nuc": self.target_price_nuc,
+            "status": self.status,
+            "lowest_price_nuc": self.lowest_price_nuc,
+            "lowest_price_date": self.lowest_price_date,
+            "last_checked_at": self.last_checked_at,
+            "triggered_at": self.triggered_at,
+            "created_at": self.created_at,
+        })
+    }
+}
+
+#[async_trait]
+impl PriceWatchRepository for StorePriceWatchRepository {
+    async fn create_watch(&self, watch: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let date = |key: &str| -> Result<NaiveDate, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(watch[key].as_str().ok_or_else(|| format!("missing {}", key))?.parse()?)
+        };
+        let row: PriceWatchRow = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO price_watches (customer_id, customer_email, origin, destination, depart_from, depart_to, passengers, target_price_nuc)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {}
+            "#,
+            WATCH_COLUMNS
+        ))
+        .bind(watch["customer_id"].as_str().ok_or("missing customer_id")?)
+        .bind(watch["customer_email"].as_str())
+        .bind(watch["origin"].as_str().ok_or("missing origin")?)
+        .bind(watch["destination"].as_str().ok_or("missing destination")?)
+        .bind(date("depart_from")?)
+        .bind(date("depart_to")?)
+        .bind(watch["passengers"].as_i64().unwrap_or(1) as i32)
+        .bind(watch["target_price_nuc"].as_i64().ok_or("missing target_price_nuc")? as i32)
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(row.into_json())
+    }
+
+    async fn list_watches(&self, customer_id: &str) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<PriceWatchRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM price_watches WHERE customer_id = $1 ORDER BY created_at DESC",
+            WATCH_COLUMNS
+        ))
+        .bind(customer_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(rows.into_iter().map(PriceWatchRow::into_json).collect())
+    }
+
+    async fn get_watch(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let row: Option<PriceWatchRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM price_watches WHERE id = $1",
+            WATCH_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(self.db.reader())
+        .await?;
+        Ok(row.map(PriceWatchRow::into_json))
+    }
+
+    async fn cancel_watch(&self, id: Uuid, customer_id: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let row: Option<PriceWatchRow> = sqlx::query_as(&format!(
+            r#"
+            UPDATE price_watches SET status = 'CANCELLED'
+            WHERE id = $1 AND customer_id = $2 AND status = 'ACTIVE'
+            RETURNING {}
+            "#,
+            WATCH_COLUMNS
+        ))
+        .bind(id)
+        .bind(customer_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(row.map(PriceWatchRow::into_json))
+    }
+
+    async fn claim_due_watches(&self, limit: i64, recheck_seconds: i64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE price_watches SET status = 'EXPIRED' WHERE status = 'ACTIVE' AND depart_to < CURRENT_DATE")
+            .execute(self.db.writer())
+            .await?;
+
+        let rows: Vec<PriceWatchRow> = sqlx::query_as(&format!(
+            r#"
+            UPDATE price_watches SET last_checked_at = NOW()
+            WHERE id IN (
+                SELECT id FROM price_watches
+                WHERE status = 'ACTIVE'
+                  AND (last_checked_at IS NULL OR last_checked_at < NOW() - make_interval(secs => $2))
+                ORDER BY last_checked_at NULLS FIRST
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            WATCH_COLUMNS
+        ))
+        .bind(limit)
+        .bind(recheck_seconds as f64)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(rows.into_iter().map(PriceWatchRow::into_json).collect())
+    }
+
+    async fn record_price(
+        &self,
+        id: Uuid,
+        lowest_price_nuc: i32,
+        lowest_price_date: NaiveDate,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE price_watches SET lowest_price_nuc = $2, lowest_price_date = $3 WHERE id = $1")
+            .bind(id)
+            .bind(lowest_price_nuc)
+            .bind(lowest_price_date)
+            .execute(self.db.writer())
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_triggered(&self, id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            "UPDATE price_watches SET status = 'TRIGGERED', triggered_at = NOW() WHERE id = $1 AND status = 'ACTIVE'",
+        )
+        .bind(id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
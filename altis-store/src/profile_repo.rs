@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::ProfileRepository;
+
+use crate::DbClient;
+
+pub struct StoreProfileRepository {
+    db: DbClient,
+}
+
+impl StoreProfileRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+/// `YYYY-MM-DD` field of a traveler, if set and well formed
+fn date(traveler: &Value, field: &str) -> Option<chrono::NaiveDate> {
+    traveler[field].as_str().and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+#[async_trait]
+impl ProfileRepository for StoreProfileRepository {
+    async fn get_profile(&self, customer_id: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let profile = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(p) FROM customer_profiles p WHERE customer_id = $1",
+        )
+        .bind(customer_id)
+        .fetch_optional(self.db.reader())
+        .await?;
+        Ok(profile)
+    }
+
+    async fn save_profile(&self, customer_id: &str, profile: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let saved = sqlx::query_scalar::<_, Value>(
+            r#"
+            INSERT INTO customer_profiles (customer_id, email, phone, first_name, last_name, marketing_opt_in, marketing_opt_in_at, alert_channels)
+            VALUES ($1, $2, $3, $4, $5, $6, CASE WHEN $6 THEN NOW() END, $7)
+            ON CONFLICT (customer_id) DO UPDATE SET
+                email = EXCLUDED.email,
+                phone = EXCLUDED.phone,
+                first_name = EXCLUDED.first_name,
+                last_name = EXCLUDED.last_name,
+                alert_channels = EXCLUDED.alert_channels,
+                marketing_opt_in = EXCLUDED.marketing_opt_in,
+                marketing_opt_in_at = CASE
+                    WHEN NOT EXCLUDED.marketing_opt_in THEN NULL
+                    WHEN customer_profiles.marketing_opt_in THEN customer_profiles.marketing_opt_in_at
+                    ELSE NOW()
+                END,
+                updated_at = NOW()
+            RETURNING to_jsonb(customer_profiles)
+            "#,
+        )
+        .bind(customer_id)
+        .bind(profile["email"].as_str())
+        .bind(profile["phone"].as_str())
+        .bind(profile["first_name"].as_str())
+        .bind(profile["last_name"].as_str())
+        .bind(profile["marketing_opt_in"].as_bool().unwrap_or(false))
+        .bind(profile["alert_channels"].as_array().map(|channels| {
+            channels.iter().filter_map(|c| c.as_str().map(str::to_string)).collect::<Vec<_>>()
+        }))
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(saved)
+    }
+
+    async fn list_saved_travelers(&self, customer_id: &str) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let travelers = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(t) FROM saved_travelers t WHERE customer_id = $1 ORDER BY created_at",
+        )
+        .bind(customer_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(travelers)
+    }
+
+    async fn get_saved_travelers(&self, customer_id: &str, ids: &[Uuid]) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // From the primary: a traveler is often saved and booked straight away
+        let travelers = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(t) FROM saved_travelers t WHERE customer_id = $1 AND id = ANY($2)",
+        )
+        .bind(customer_id)
+        .bind(ids)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(travelers)
+    }
+
+    async fn create_saved_traveler(&self, customer_id: &str, traveler: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let saved = sqlx::query_scalar::<_, Value>(
+            r#"
+            INSERT INTO saved_travelers (customer_id, ptc, first_name, last_name, date_of_birth, gender, document_type, document_number, document_expiry, nationality)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING to_jsonb(saved_travelers)
+            "#,
+        )
+        .bind(customer_id)
+        .bind(traveler["ptc"].as_str().unwrap_or("ADT"))
+        .bind(traveler["first_name"].as_str().ok_or("missing first_name")?)
+        .bind(traveler["last_name"].as_str().ok_or("missing last_name")?)
+        .bind(date(traveler, "date_of_birth"))
+        .bind(traveler["gender"].as_str())
+        .bind(traveler["document_type"].as_str())
+        .bind(traveler["document_number"].as_str())
+        .bind(date(traveler, "document_expiry"))
+        .bind(traveler["nationality"].as_str())
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(saved)
+    }
+
+    async fn update_saved_traveler(&self, id: Uuid, customer_id: &str, traveler: &Value) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let updated = sqlx::query_scalar::<_, Value>(
+            r#"
+            UPDATE saved_travelers SET
+                ptc = $3, first_name = $4, last_name = $5, date_of_birth = $6, gender = $7,
+                document_type = $8, document_number = $9, document_expiry = $10, nationality = $11,
+                updated_at = NOW()
+            WHERE id = $1 AND customer_id = $2
+            RETURNING to_jsonb(saved_travelers)
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .bind(traveler["ptc"].as_str().unwrap_or("ADT"))
+        .bind(traveler["first_name"].as_str().ok_or("missing first_name")?)
+        .bind(traveler["last_name"].as_str().ok_or("missing last_name")?)
+        .bind(date(traveler, "date_of_birth"))
+        .bind(traveler["gender"].as_str())
+        .bind(traveler["document_type"].as_str())
+        .bind(traveler["document_number"].as_str())
+        .bind(date(traveler, "document_expiry"))
+        .bind(traveler["nationality"].as_str())
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(updated)
+    }
+
+    async fn delete_saved_traveler(&self, id: Uuid, customer_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let deleted = sqlx::query("DELETE FROM saved_travelers WHERE id = $1 AND customer_id = $2")
+            .bind(id)
+            .bind(customer_id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+}
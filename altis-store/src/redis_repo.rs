@@ -1,124 +1,316 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts};
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
 use redis::{AsyncCommands, RedisResult};
 use tracing::info;
 
+use crate::chaos::{ChaosInjector, ChaosTarget};
+use crate::deadline;
+
+/// Per-operation Redis latency and error counters, exposed on /metrics.
+#[derive(Clone)]
+pub struct RedisMetrics {
+    pub latency: HistogramVec,
+    pub errors: IntCounterVec,
+}
 
+impl RedisMetrics {
+    fn new() -> Self {
+        let latency = HistogramVec::new(
+            HistogramOpts::new("altis_redis_command_seconds", "Redis command latency by operation")
+                .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25]),
+            &["op"],
+        ).expect("valid histogram definition");
+        let errors = IntCounterVec::new(
+            Opts::new("altis_redis_errors_total", "Redis command failures by operation"),
+            &["op"],
+        ).expect("valid counter definition");
+
+        Self { latency, errors }
+    }
+}
+
+fn seat_lock_key(flight_id: &str, seat_number: &str) -> String {
+    format!("seat:{}:{}", flight_id, seat_number)
+}
+
+/// Seat locks taken by a trip, kept beside the `trip:{id}` session key
+fn trip_seats_key(trip_id: &str) -> String {
+    format!("trip:{}:seats", trip_id)
+}
+
+/// The trip id of an expired `trip:{id}` session key. The trip's own
+/// `trip:{id}:seats` set and other trip-scoped keys don't count.
+pub fn expired_trip_id(key: &str) -> Option<&str> {
+    key.strip_prefix("trip:").filter(|id| !id.is_empty() && !id.contains(':'))
+}
 
+/// Shares one auto-reconnecting multiplexed connection across all callers
+/// instead of dialing Redis per command.
 #[derive(Clone)]
 pub struct RedisClient {
     client: redis::Client,
+    manager: ConnectionManager,
+    metrics: RedisMetrics,
+    chaos: Arc<ChaosInjector>,
 }
 
 impl RedisClient {
     pub async fn new(connection_string: &str) -> Result<Self, redis::RedisError> {
         let client = redis::Client::open(connection_string)?;
-        Ok(Self { client })
+
+        // Reconnect with exponential backoff (100ms .. 5s); fail commands fast
+        // rather than stalling request handlers while Redis is away.
+        let config = ConnectionManagerConfig::new()
+            .set_min_delay(Duration::from_millis(100))
+            .set_max_delay(Duration::from_secs(5))
+            .set_number_of_retries(6)
+            .set_connection_timeout(Some(Duration::from_secs(2)))
+            .set_response_timeout(Some(Duration::from_secs(1)));
+        let manager = ConnectionManager::new_with_config(client.clone(), config).await?;
+        info!("Redis connection manager established");
+
+        Ok(Self { client, manager, metrics: RedisMetrics::new(), chaos: Arc::new(ChaosInjector::disabled()) })
+    }
+
+    pub fn with_chaos(mut self, chaos: Arc<ChaosInjector>) -> Self {
+        self.chaos = chaos;
+        self
     }
 
     pub fn get_client(&self) -> redis::Client {
         self.client.clone()
     }
 
+    /// Cheap handle on the shared connection (clones share the same socket).
+    pub fn connection(&self) -> ConnectionManager {
+        self.manager.clone()
+    }
+
+    pub fn metrics(&self) -> &RedisMetrics {
+        &self.metrics
+    }
+
+    pub async fn ping(&self) -> RedisResult<()> {
+        let mut conn = self.connection();
+        self.timed("ping", redis::cmd("PING").query_async::<()>(&mut conn)).await
+    }
+
+    /// Runs a Redis operation, recording its latency and failures under `op`.
+    /// Inside a request it is abandoned once the request's deadline passes.
+    pub async fn timed<T, F>(&self, op: &'static str, fut: F) -> RedisResult<T>
+    where
+        F: Future<Output = RedisResult<T>>,
+    {
+        let started = Instant::now();
+        let result = match self.chaos.inject(ChaosTarget::Redis).await {
+            Ok(()) => deadline::run(fut).await
+                .unwrap_or_else(|e| Err(redis::RedisError::from((redis::ErrorKind::Io, "Command abandoned", e.to_string())))),
+            Err(_) => Err(redis::RedisError::from((redis::ErrorKind::Io, "Fault injected by chaos hook"))),
+        };
+        self.metrics.latency.with_label_values(&[op]).observe(started.elapsed().as_secs_f64());
+        if result.is_err() {
+            self.metrics.errors.with_label_values(&[op]).inc();
+        }
+        result
+    }
+
     pub async fn set_trip_hold(&self, trip_id: &str, flight_id: &str, ttl_seconds: u64) -> Result<(), redis::RedisError> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut conn = self.connection();
         let key = format!("trip:{}", trip_id);
-        conn.set_ex::<_, _, ()>(key, flight_id, ttl_seconds).await?;
+        self.timed("set_trip_hold", conn.set_ex::<_, _, ()>(key, flight_id, ttl_seconds)).await?;
         info!("Trip hold set: {} -> {}", trip_id, flight_id);
         Ok(())
     }
 
     pub async fn get_trip_flight(&self, trip_id: &str) -> Result<Option<String>, redis::RedisError> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut conn = self.connection();
         let key = format!("trip:{}", trip_id);
-        let flight_id: Option<String> = conn.get(key).await?;
-        Ok(flight_id)
+        self.timed("get_trip_flight", conn.get(key)).await
     }
 
+    /// Locks a seat for a trip. The seat is also recorded against the trip so
+    /// [`Self::release_trip_seats`] can let it go when the trip ends early.
     pub async fn acquire_seat_lock(&self, flight_id: &str, seat_number: &str, trip_id: &str, ttl_seconds: u64) -> Result<bool, redis::RedisError> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let key = format!("seat:{}:{}", flight_id, seat_number);
+        let mut conn = self.connection();
+        let key = seat_lock_key(flight_id, seat_number);
         
         // SET NX: Only set if key does not exist
-        let result: Option<String> = redis::cmd("SET")
+        let result: Option<String> = self.timed("acquire_seat_lock", redis::cmd("SET")
             .arg(&key)
             .arg(trip_id)
             .arg("NX")
             .arg("EX")
             .arg(ttl_seconds)
             .query_async(&mut conn)
-            .await?;
+        ).await?;
+
+        if result.is_some() {
+            let seats = trip_seats_key(trip_id);
+            self.timed("track_trip_seat", redis::pipe()
+                .sadd(&seats, &key)
+                .expire(&seats, ttl_seconds as i64)
+                .query_async::<()>(&mut conn)
+            ).await?;
+        }
 
         Ok(result.is_some())
     }
 
-    pub async fn decr_flight_availability(&self, flight_id: &str) -> RedisResult<Option<i64>> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let key = format!("flight:{}:availability", flight_id);
-        // Enterprise Upgrade: Use Lua script to ensuring we don't seed negative values on cache miss.
-        // If key exists, DECR it. If not, return nil (and let the next Search re-seed it from DB).
+    /// Releases a seat lock if `trip_id` holds it. Returns false when the
+    /// seat is free or locked by another trip.
+    pub async fn release_seat_lock(&self, flight_id: &str, seat_number: &str, trip_id: &str) -> RedisResult<bool> {
+        let mut conn = self.connection();
+        // Compare-and-delete, so a lock that expired and was re-taken by
+        // someone else in between is left alone
         let script = redis::Script::new(r#"
-            if redis.call("EXISTS", KEYS[1]) == 1 then
-                return redis.call("DECR", KEYS[1])
-            else
-                return nil
+            redis.call("SREM", KEYS[2], KEYS[1])
+            if redis.call("GET", KEYS[1]) == ARGV[1] then
+                return redis.call("DEL", KEYS[1])
             end
+            return 0
         "#);
-        
-        script.key(key).invoke_async(&mut conn).await
+        let released: i64 = self.timed("release_seat_lock", script
+            .key(seat_lock_key(flight_id, seat_number))
+            .key(trip_seats_key(trip_id))
+            .arg(trip_id)
+            .invoke_async(&mut conn)
+        ).await?;
+        Ok(released == 1)
     }
 
-    pub async fn get_flight_availability(&self, flight_id: &str) -> RedisResult<Option<i32>> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let key = format!("flight:{}:availability", flight_id);
-        conn.get(key).await
+    /// Releases every seat lock still held by `trip_id`, returning how many
+    /// were freed.
+    pub async fn release_trip_seats(&self, trip_id: &str) -> RedisResult<u64> {
+        let mut conn = self.connection();
+        let script = redis::Script::new(r#"
+            local released = 0
+            for _, seat in ipairs(redis.call("SMEMBERS", KEYS[1])) do
+                if redis.call("GET", seat) == ARGV[1] then
+                    released = released + redis.call("DEL", seat)
+                end
+            end
+            redis.call("DEL", KEYS[1])
+            return released
+        "#);
+        self.timed("release_trip_seats", script.key(trip_seats_key(trip_id)).arg(trip_id).invoke_async(&mut conn)).await
     }
 
-    pub async fn set_flight_availability(&self, flight_id: &str, count: i32) -> RedisResult<()> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let key = format!("flight:{}:availability", flight_id);
-        conn.set(key, count).await
+    /// Turns on expired-key events (`E` and `x` in notify-keyspace-events),
+    /// keeping whatever classes the server already publishes.
+    pub async fn enable_expiry_notifications(&self) -> RedisResult<()> {
+        let mut conn = self.connection();
+        let (_, flags): (String, String) = self.timed("config_get", redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("notify-keyspace-events")
+            .query_async(&mut conn)
+        ).await?;
+        if flags.contains('E') && (flags.contains('x') || flags.contains('A')) {
+            return Ok(());
+        }
+        self.timed("config_set", redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("notify-keyspace-events")
+            .arg(format!("{}Ex", flags))
+            .query_async::<()>(&mut conn)
+        ).await
     }
-        pub async fn delete_flight_availability(&self, flight_id: &str) -> RedisResult<()> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let key = format!("flight:{}:availability", flight_id);
-        conn.del(key).await
+
+    /// A dedicated connection subscribed to expired-key events on every
+    /// database. Pub/sub can't share the multiplexed connection.
+    pub async fn subscribe_expired_keys(&self) -> RedisResult<redis::aio::PubSub> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.psubscribe("__keyevent@*__:expired").await?;
+        Ok(pubsub)
     }
-        pub async fn del_trip_key(&self, trip_id: &str) -> RedisResult<()> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+
+    /// Claims `key` for `ttl_seconds`; false when someone already holds it.
+    /// Used to handle a message once across instances and redeliveries.
+    pub async fn try_claim(&self, key: &str, ttl_seconds: u64) -> RedisResult<bool> {
+        let mut conn = self.connection();
+        let result: Option<String> = self.timed("try_claim", redis::cmd("SET")
+            .arg(key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query_async(&mut conn)
+        ).await?;
+        Ok(result.is_some())
+    }
+
+    /// Gives up a claim so the work can be retried
+    pub async fn release_claim(&self, key: &str) -> RedisResult<()> {
+        let mut conn = self.connection();
+        self.timed("release_claim", conn.del(key)).await
+    }
+
+    /// Invalidates every cached search result by bumping the cache version.
+    pub async fn invalidate_search_cache(&self) -> RedisResult<()> {
+        let mut conn = self.connection();
+        self.timed("invalidate_search_cache", conn.incr::<_, _, ()>(crate::search_cache::SEARCH_VERSION_KEY, 1)).await
+    }
+
+    pub async fn del_trip_key(&self, trip_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection();
         let key = format!("trip:{}", trip_id);
-        conn.del(key).await
+        self.timed("del_trip_key", conn.del(key)).await
     }
 
 
     // Hash Operations for Sessions
     pub async fn hset_trip_field(&self, trip_id: &str, field: &str, value: &str) -> RedisResult<()> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut conn = self.connection();
         let key = format!("trip:{}", trip_id);
-        conn.hset(key, field, value).await
+        self.timed("hset_trip_field", conn.hset(key, field, value)).await
     }
 
     pub async fn hget_trip_field(&self, trip_id: &str, field: &str) -> RedisResult<Option<String>> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut conn = self.connection();
         let key = format!("trip:{}", trip_id);
-        conn.hget(key, field).await
+        self.timed("hget_trip_field", conn.hget(key, field)).await
+    }
+
+    /// Every field of the trip session hash; empty once the trip has expired
+    pub async fn hgetall_trip(&self, trip_id: &str) -> RedisResult<HashMap<String, String>> {
+        let mut conn = self.connection();
+        let key = format!("trip:{}", trip_id);
+        self.timed("hgetall_trip", conn.hgetall(key)).await
     }
 
     pub async fn exp_trip_key(&self, trip_id: &str, ttl_seconds: usize) -> RedisResult<()> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut conn = self.connection();
         let key = format!("trip:{}", trip_id);
-        conn.expire(key, ttl_seconds as i64).await
+        self.timed("exp_trip_key", conn.expire(key, ttl_seconds as i64)).await
     }
 
     pub async fn check_rate_limit(&self, key: &str, limit: i64, window_seconds: i64) -> RedisResult<bool> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut conn = self.connection();
         
-        let (count,): (i64,) = redis::pipe()
+        let (count,): (i64,) = self.timed("check_rate_limit", redis::pipe()
             .atomic()
             .incr(key, 1)
             .expire(key, window_seconds)
             .query_async(&mut conn)
-            .await?;
+        ).await?;
         
         Ok(count <= limit)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_trip_id() {
+        assert_eq!(expired_trip_id("trip:3f2a"), Some("3f2a"));
+        assert_eq!(expired_trip_id("trip:3f2a:seats"), None);
+        assert_eq!(expired_trip_id("trip:"), None);
+        assert_eq!(expired_trip_id("seat:f1:12A"), None);
+    }
+}
@@ -0,0 +1,121 @@
+use prometheus::{IntCounterVec, Opts};
+use redis::AsyncCommands;
+use serde_json::Value;
+
+use crate::RedisClient;
+
+/// Bumped on any availability or pricing change; part of every cache key, so
+/// one INCR invalidates all cached searches at once.
+pub const SEARCH_VERSION_KEY: &str = "search:version";
+
+/// Caches search results for a short window, keyed on normalized criteria.
+pub struct SearchCache {
+    redis: RedisClient,
+    ttl_seconds: u64,
+    requests: IntCounterVec,
+}
+
+impl SearchCache {
+    pub fn new(redis: RedisClient, ttl_seconds: u64) -> Self {
+        let requests = IntCounterVec::new(
+            Opts::new("altis_search_cache_requests_total", "Search cache lookups by result"),
+            &["result"],
+        ).expect("valid counter definition");
+
+        Self { redis, ttl_seconds, requests }
+    }
+
+    /// Normalizes criteria so equivalent searches ("sin" vs "SIN ", missing vs
+    /// "economy" cabin) share an entry.
+    pub fn key(
+        origin: &str,
+        destination: &str,
+        departure_date: &str,
+        return_date: Option<&str>,
+        passengers: u32,
+        cabin_class: Option<&str>,
+        user_segment: Option<&str>,
+    ) -> String {
+        let normalize = |v: Option<&str>, default: &str| {
+            v.map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| default.to_string())
+        };
+
+        format!(
+            "{}:{}:{}:{}:{}:{}:{}",
+            origin.trim().to_uppercase(),
+            destination.trim().to_uppercase(),
+            departure_date.trim(),
+            return_date.map(str::trim).unwrap_or("-"),
+            passengers,
+            normalize(cabin_class, "economy"),
+            normalize(user_segment, "default"),
+        )
+    }
+
+    pub fn metrics(&self) -> &IntCounterVec {
+        &self.requests
+    }
+
+    /// Cache misses and Redis errors both fall through to a fresh search.
+    pub async fn get(&self, key: &str) -> Option<Value> {
+        if self.ttl_seconds == 0 {
+            return None;
+        }
+
+        let cached = match self.versioned_key(key).await {
+            Some(versioned) => {
+                let mut conn = self.redis.connection();
+                self.redis.timed("search_cache_get", conn.get::<_, Option<String>>(versioned)).await.ok().flatten()
+            }
+            None => None,
+        };
+
+        let hit = cached.and_then(|json| serde_json::from_str(&json).ok());
+        self.requests.with_label_values(&[if hit.is_some() { "hit" } else { "miss" }]).inc();
+        hit
+    }
+
+    pub async fn put(&self, key: &str, results: &Value) {
+        if self.ttl_seconds == 0 {
+            return;
+        }
+
+        let Some(versioned) = self.versioned_key(key).await else {
+            return;
+        };
+
+        let mut conn = self.redis.connection();
+        if let Err(e) = self.redis.timed("search_cache_put", conn.set_ex::<_, _, ()>(versioned, results.to_string(), self.ttl_seconds)).await {
+            tracing::warn!("Failed to cache search results: {}", e);
+        }
+    }
+
+    pub async fn invalidate(&self) {
+        if let Err(e) = self.redis.invalidate_search_cache().await {
+            tracing::warn!("Failed to invalidate search cache: {}", e);
+        }
+    }
+
+    async fn versioned_key(&self, key: &str) -> Option<String> {
+        let mut conn = self.redis.connection();
+        let version: Option<u64> = self.redis.timed("search_cache_version", conn.get(SEARCH_VERSION_KEY)).await.ok()?;
+        Some(format!("search:v{}:{}", version.unwrap_or(0), key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_normalization() {
+        let a = SearchCache::key(" sin", "bkk ", "2026-03-01", None, 2, None, Some(""));
+        let b = SearchCache::key("SIN", "BKK", "2026-03-01", None, 2, Some("Economy"), Some("DEFAULT"));
+        assert_eq!(a, b);
+
+        let c = SearchCache::key("SIN", "BKK", "2026-03-01", None, 3, None, None);
+        assert_ne!(a, c);
+    }
+}
@@ -0,0 +1,468 @@
+//! Synthetic data for load tests and benchmarks. Everything, ids included,
+//! is drawn from one seeded RNG, so the same options give the same rows and
+//! a rerun against a seeded database inserts nothing new.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// Airports routes are drawn between: code and position (lat, lon)
+const AIRPORTS: &[(&str, f64, f64)] = &[
+    ("SIN", 1.36, 103.99), ("BKK", 13.69, 100.75), ("KUL", 2.75, 101.71), ("CGK", -6.13, 106.66),
+    ("MNL", 14.51, 121.02), ("SGN", 10.82, 106.66), ("HKG", 22.31, 113.92), ("NRT", 35.77, 140.39),
+    ("ICN", 37.46, 126.44), ("PEK", 40.08, 116.58), ("SYD", -33.95, 151.18), ("DEL", 28.56, 77.10),
+    ("DXB", 25.25, 55.36), ("DOH", 25.27, 51.61), ("LHR", 51.47, -0.45), ("CDG", 49.01, 2.55),
+    ("FRA", 50.03, 8.56), ("AMS", 52.31, 4.76), ("MAD", 40.47, -3.57), ("IST", 41.26, 28.74),
+    ("JFK", 40.64, -73.78), ("LAX", 33.94, -118.41), ("ORD", 41.98, -87.90), ("YYZ", 43.68, -79.63),
+];
+
+/// Ancillaries every seeded airline sells: code, type, name and price
+const ANCILLARIES: &[(&str, &str, &str, i32)] = &[
+    ("BAG_20", "BAG", "20kg checked bag", 3500),
+    ("SEAT_XL", "SEAT", "Extra legroom seat", 2500),
+    ("MEAL_HOT", "MEAL", "Hot meal", 1500),
+];
+
+/// Departure banks (hour of day) and how much of the schedule each gets
+const DEPARTURE_HOURS: &[(u32, u32)] = &[(6, 3), (8, 5), (10, 3), (13, 2), (16, 3), (18, 5), (21, 3), (23, 1)];
+
+/// Final order statuses and their share of bookings
+const ORDER_STATUSES: &[(&str, u32)] = &[("PAID", 80), ("EXPIRED", 7), ("CANCELLED", 8), ("REFUNDED", 5)];
+
+/// Airline codes are a digit and a letter, which keeps them clear of real carriers' two-letter codes
+const MAX_AIRLINES: usize = 260;
+
+/// Rows per INSERT; keeps the bind count well under Postgres' 65535
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct SeedConfig {
+    pub seed: u64,
+    pub airlines: usize,
+    pub routes_per_airline: usize,
+    /// Days of schedule from `start_date`
+    pub days: i64,
+    pub orders: usize,
+    /// Distinct customers the orders are spread over
+    pub customers: usize,
+    pub start_date: NaiveDate,
+}
+
+impl SeedConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.airlines == 0 || self.airlines > MAX_AIRLINES {
+            return Err(format!("airlines must be between 1 and {}", MAX_AIRLINES));
+        }
+        let max_routes = AIRPORTS.len() * (AIRPORTS.len() - 1);
+        if self.routes_per_airline == 0 || self.routes_per_airline > max_routes {
+            return Err(format!("routes per airline must be between 1 and {}", max_routes));
+        }
+        if self.days < 1 {
+            return Err("days must be at least 1".to_string());
+        }
+        if self.orders > 0 && self.customers == 0 {
+            return Err("orders need at least one customer".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedAirline {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedProduct {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    pub product_type: &'static str,
+    pub product_code: String,
+    pub name: String,
+    pub base_price_nuc: i32,
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedOrderItem {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub product_type: &'static str,
+    pub product_code: String,
+    pub name: String,
+    pub price_nuc: i32,
+    pub quantity: i32,
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedOrder {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    pub customer_id: String,
+    pub customer_email: String,
+    pub status: &'static str,
+    pub total_nuc: i32,
+    pub created_at: DateTime<Utc>,
+    pub items: Vec<SeedOrderItem>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dataset {
+    pub airlines: Vec<SeedAirline>,
+    pub products: Vec<SeedProduct>,
+    pub orders: Vec<SeedOrder>,
+}
+
+impl Dataset {
+    pub fn flights(&self) -> usize {
+        self.products.iter().filter(|p| p.product_type == "FLIGHT").count()
+    }
+}
+
+/// Rows inserted by `write`; lower than the dataset's counts on a rerun
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeedCounts {
+    pub airlines: u64,
+    pub products: u64,
+    pub orders: u64,
+    pub order_items: u64,
+}
+
+fn seeded_uuid(rng: &mut StdRng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.gen()).into_uuid()
+}
+
+fn distance_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    6371.0 * 2.0 * a.sqrt().asin()
+}
+
+struct Flight {
+    product: usize,
+    departs: DateTime<Utc>,
+    capacity: i32,
+    sold: i32,
+}
+
+/// Builds the dataset for `config`. Route popularity and customer activity
+/// both follow a Zipf-like curve, so a few routes and regulars carry most of
+/// the bookings, as they do in real sales data.
+pub fn generate(config: &SeedConfig) -> Dataset {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut dataset = Dataset::default();
+    let hours = WeightedIndex::new(DEPARTURE_HOURS.iter().map(|(_, weight)| weight)).expect("departure weights");
+    // (airline, flights of each of its routes), routes in popularity order
+    let mut schedules: Vec<(usize, Vec<Vec<Flight>>)> = Vec::new();
+    let mut ancillaries: Vec<Vec<usize>> = Vec::new();
+
+    for index in 0..config.airlines {
+        let code = format!("{}{}", index / 26, (b'A' + (index % 26) as u8) as char);
+        let airline = SeedAirline { id: seeded_uuid(&mut rng), name: format!("Seed Airways {}", code), code };
+
+        let mut pairs: Vec<(usize, usize)> = (0..AIRPORTS.len())
+            .flat_map(|from| (0..AIRPORTS.len()).filter(move |to| *to != from).map(move |to| (from, to)))
+            .collect();
+        let mut routes = Vec::new();
+        for number in 0..config.routes_per_airline {
+            let (from, to) = pairs.swap_remove(rng.gen_range(0..pairs.len()));
+            let (origin, destination) = (AIRPORTS[from], AIRPORTS[to]);
+            let km = distance_km((origin.1, origin.2), (destination.1, destination.2));
+            let block_minutes = (30.0 + km / 800.0 * 60.0) as i64;
+            let widebody = km > 4000.0;
+            let fare = (3000.0 + km * 9.0 * rng.gen_range(0.8..1.2)) as i32;
+            // Busier routes are flown more often
+            let daily = if number < config.routes_per_airline / 5 { rng.gen_range(2..=4) } else { rng.gen_range(1..=2) };
+
+            let mut flights = Vec::new();
+            for day in 0..config.days {
+                let date = config.start_date + Duration::days(day);
+                let mut slots: Vec<u32> = (0..daily).map(|_| DEPARTURE_HOURS[hours.sample(&mut rng)].0).collect();
+                slots.sort_unstable();
+                slots.dedup();
+                for (slot, hour) in slots.into_iter().enumerate() {
+                    let minute = rng.gen_range(0..12) * 5;
+                    let departs = date.and_hms_opt(hour, minute, 0).unwrap_or_default().and_utc();
+                    let arrives = departs + Duration::minutes(block_minutes);
+                    let capacity = if widebody { 300 } else { 180 };
+                    let flight_number = format!("{}{}", airline.code, 100 + number * 5 + slot);
+                    dataset.products.push(SeedProduct {
+                        id: seeded_uuid(&mut rng),
+                        airline_id: airline.id,
+                        product_type: "FLIGHT",
+                        product_code: format!("{}-{}", flight_number, date.format("%Y%m%d")),
+                        name: format!("{} {}-{}", flight_number, origin.0, destination.0),
+                        base_price_nuc: fare,
+                        metadata: serde_json::json!({
+                            "flight_number": flight_number,
+                            "origin": origin.0,
+                            "destination": destination.0,
+                            "departure_date": date.to_string(),
+                            "departure_time": departs.to_rfc3339(),
+                            "arrival_time": arrives.to_rfc3339(),
+                            "available_seats": capacity,
+                            "cabin_class": "ECONOMY",
+                            "distance_km": km.round(),
+                        }),
+                    });
+                    flights.push(Flight { product: dataset.products.len() - 1, departs, capacity, sold: 0 });
+                }
+            }
+            routes.push(flights);
+        }
+
+        let mut extras = Vec::new();
+        for (code, product_type, name, price) in ANCILLARIES {
+            dataset.products.push(SeedProduct {
+                id: seeded_uuid(&mut rng),
+                airline_id: airline.id,
+                product_type,
+                product_code: code.to_string(),
+                name: name.to_string(),
+                base_price_nuc: *price,
+                metadata: serde_json::json!({}),
+            });
+            extras.push(dataset.products.len() - 1);
+        }
+        ancillaries.push(extras);
+        schedules.push((dataset.airlines.len(), routes));
+        dataset.airlines.push(airline);
+    }
+
+    if config.orders == 0 {
+        return dataset;
+    }
+    let zipf = |n: usize| WeightedIndex::new((1..=n).map(|rank| 1.0 / rank as f64)).expect("zipf weights");
+    let airline_pick = zipf(schedules.len());
+    let route_pick = zipf(config.routes_per_airline);
+    let customer_pick = zipf(config.customers);
+    let statuses = WeightedIndex::new(ORDER_STATUSES.iter().map(|(_, weight)| weight)).expect("status weights");
+    let passengers = WeightedIndex::new([60, 25, 10, 5]).expect("party weights");
+
+    for _ in 0..config.orders {
+        let (airline, routes) = &mut schedules[airline_pick.sample(&mut rng)];
+        let flights = &mut routes[route_pick.sample(&mut rng)];
+        let pick = rng.gen_range(0..flights.len());
+        let flight = &mut flights[pick];
+        let party = passengers.sample(&mut rng) as i32 + 1;
+        let status = ORDER_STATUSES[statuses.sample(&mut rng)].0;
+        // Lead times are skewed short: most book within a few weeks of departure
+        let lead_hours = (-rng.gen::<f64>().max(f64::MIN_POSITIVE).ln() * 21.0 * 24.0).clamp(2.0, 330.0 * 24.0);
+        let created_at = flight.departs - Duration::minutes((lead_hours * 60.0) as i64);
+        let customer = customer_pick.sample(&mut rng) + 1;
+        let sold_out = flight.sold + party > flight.capacity;
+
+        let product = &dataset.products[flight.product];
+        let mut items = vec![SeedOrderItem {
+            id: seeded_uuid(&mut rng),
+            product_id: product.id,
+            product_type: product.product_type,
+            product_code: product.product_code.clone(),
+            name: product.name.clone(),
+            price_nuc: product.base_price_nuc,
+            quantity: party,
+            metadata: serde_json::json!({
+                "flight_number": product.metadata["flight_number"],
+                "origin": product.metadata["origin"],
+                "destination": product.metadata["destination"],
+                "departure_time": product.metadata["departure_time"],
+                "passengers": party,
+            }),
+        }];
+        for extra in &ancillaries[*airline] {
+            if rng.gen_bool(0.3) {
+                let product = &dataset.products[*extra];
+                items.push(SeedOrderItem {
+                    id: seeded_uuid(&mut rng),
+                    product_id: product.id,
+                    product_type: product.product_type,
+                    product_code: product.product_code.clone(),
+                    name: product.name.clone(),
+                    price_nuc: product.base_price_nuc,
+                    quantity: party,
+                    metadata: serde_json::json!({}),
+                });
+            }
+        }
+        // A full flight still takes the order; it just didn't get paid
+        let status = if sold_out && status == "PAID" { "EXPIRED" } else { status };
+        if status == "PAID" {
+            flight.sold += party;
+        }
+
+        dataset.orders.push(SeedOrder {
+            id: seeded_uuid(&mut rng),
+            airline_id: dataset.airlines[*airline].id,
+            customer_id: format!("seed-customer-{:06}", customer),
+            customer_email: format!("customer{}@seed.example", customer),
+            status,
+            total_nuc: items.iter().map(|item| item.price_nuc * item.quantity).sum(),
+            created_at,
+            items,
+        });
+    }
+
+    // Seats sold by paid orders are off sale when inventory is first loaded
+    for (_, routes) in &schedules {
+        for flight in routes.iter().flatten() {
+            dataset.products[flight.product].metadata["available_seats"] = (flight.capacity - flight.sold).into();
+        }
+    }
+    dataset
+}
+
+/// Inserts the dataset in one transaction. Rows already there from a run
+/// with the same options are left as they are; airline codes taken by
+/// another seed are refused, as that run's flights would be mixed in.
+pub async fn write(pool: &PgPool, dataset: &Dataset) -> Result<SeedCounts, Box<dyn std::error::Error + Send + Sync>> {
+    let mut tx = pool.begin().await?;
+    let mut counts = SeedCounts::default();
+
+    let codes: Vec<&str> = dataset.airlines.iter().map(|airline| airline.code.as_str()).collect();
+    let ids: Vec<Uuid> = dataset.airlines.iter().map(|airline| airline.id).collect();
+    let taken: Vec<String> = sqlx::query_scalar("SELECT code FROM airlines WHERE code = ANY($1) AND id <> ALL($2) ORDER BY code")
+        .bind(&codes)
+        .bind(&ids)
+        .fetch_all(&mut *tx)
+        .await?;
+    if !taken.is_empty() {
+        return Err(format!("airline codes {} are already used by other data; seed a fresh database", taken.join(", ")).into());
+    }
+
+    for chunk in dataset.airlines.chunks(BATCH_SIZE) {
+        let mut query = QueryBuilder::<Postgres>::new("INSERT INTO airlines (id, code, name) ");
+        query.push_values(chunk, |mut row, airline| {
+            row.push_bind(airline.id).push_bind(&airline.code).push_bind(&airline.name);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        counts.airlines += query.build().execute(&mut *tx).await?.rows_affected();
+    }
+
+    for chunk in dataset.products.chunks(BATCH_SIZE) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO products (id, airline_id, product_type, product_code, name, description, base_price_nuc, metadata, is_active) ",
+        );
+        query.push_values(chunk, |mut row, product| {
+            row.push_bind(product.id)
+                .push_bind(product.airline_id)
+                .push_bind(product.product_type)
+                .push_bind(&product.product_code)
+                .push_bind(&product.name)
+                .push_bind("Seeded for load testing")
+                .push_bind(product.base_price_nuc)
+                .push_bind(&product.metadata)
+                .push_bind(true);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        counts.products += query.build().execute(&mut *tx).await?.rows_affected();
+    }
+
+    for chunk in dataset.orders.chunks(BATCH_SIZE) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO orders (id, customer_id, customer_email, airline_id, status, total_nuc, currency, payment_method, created_at, updated_at) ",
+        );
+        query.push_values(chunk, |mut row, order| {
+            row.push_bind(order.id)
+                .push_bind(&order.customer_id)
+                .push_bind(&order.customer_email)
+                .push_bind(order.airline_id)
+                .push_bind(order.status)
+                .push_bind(order.total_nuc)
+                .push_bind("NUC")
+                .push_bind((order.status != "EXPIRED").then_some("CARD"))
+                .push_bind(order.created_at)
+                .push_bind(order.created_at);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        counts.orders += query.build().execute(&mut *tx).await?.rows_affected();
+    }
+
+    let items: Vec<(Uuid, &SeedOrderItem, &SeedOrder)> = dataset.orders.iter()
+        .flat_map(|order| order.items.iter().map(move |item| (order.id, item, order)))
+        .collect();
+    for chunk in items.chunks(BATCH_SIZE) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO order_items (id, order_id, product_id, product_type, product_code, name, price_nuc, quantity, status, metadata, created_at, updated_at) ",
+        );
+        query.push_values(chunk, |mut row, (order_id, item, order)| {
+            let status = match order.status {
+                "PAID" => "ACTIVE",
+                _ => "CANCELLED",
+            };
+            row.push_bind(item.id)
+                .push_bind(*order_id)
+                .push_bind(item.product_id)
+                .push_bind(item.product_type)
+                .push_bind(&item.product_code)
+                .push_bind(&item.name)
+                .push_bind(item.price_nuc)
+                .push_bind(item.quantity)
+                .push_bind(status)
+                .push_bind(&item.metadata)
+                .push_bind(order.created_at)
+                .push_bind(order.created_at);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        counts.order_items += query.build().execute(&mut *tx).await?.rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64) -> SeedConfig {
+        SeedConfig {
+            seed,
+            airlines: 3,
+            routes_per_airline: 10,
+            days: 7,
+            orders: 2000,
+            customers: 300,
+            start_date: NaiveDate::from_ymd_opt(2026, 5, 1).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_same_seed_gives_the_same_dataset() {
+        let first = generate(&config(42));
+        assert_eq!(first, generate(&config(42)));
+        assert_ne!(first.orders[0].id, generate(&config(43)).orders[0].id);
+
+        assert_eq!(first.airlines.iter().map(|a| a.code.as_str()).collect::<Vec<_>>(), vec!["0A", "0B", "0C"]);
+        assert_eq!(first.orders.len(), 2000);
+        assert_eq!(first.products.len() - first.flights(), 3 * ANCILLARIES.len());
+        assert!(first.flights() >= 3 * 10 * 7);
+
+        // Paid seats come off the flight's availability, and never oversell it
+        let sold: i32 = first.orders.iter()
+            .filter(|order| order.status == "PAID")
+            .map(|order| order.items[0].quantity)
+            .sum();
+        let remaining: i64 = first.products.iter()
+            .filter(|p| p.product_type == "FLIGHT")
+            .map(|p| p.metadata["available_seats"].as_i64().unwrap())
+            .sum();
+        let capacity: i64 = first.products.iter()
+            .filter(|p| p.product_type == "FLIGHT")
+            .map(|p| if p.metadata["distance_km"].as_f64().unwrap() > 4000.0 { 300 } else { 180 })
+            .sum();
+        assert_eq!(remaining, capacity - sold as i64);
+        assert!(first.products.iter().all(|p| p.metadata["available_seats"].as_i64().is_none_or(|seats| seats >= 0)));
+        assert!(first.orders.iter().all(|order| order.total_nuc > 0));
+
+        assert!(config(1).validate().is_ok());
+        assert!(SeedConfig { airlines: 0, ..config(1) }.validate().is_err());
+    }
+}
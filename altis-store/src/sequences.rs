@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::Serialize;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::app_config::DocumentsConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentType {
+    Invoice,
+    CreditNote,
+    Settlement,
+}
+
+impl DocumentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::Invoice => "INVOICE",
+            DocumentType::CreditNote => "CREDIT_NOTE",
+            DocumentType::Settlement => "SETTLEMENT",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "INVOICE" => Some(DocumentType::Invoice),
+            "CREDIT_NOTE" => Some(DocumentType::CreditNote),
+            "SETTLEMENT" => Some(DocumentType::Settlement),
+            _ => None,
+        }
+    }
+
+    /// Short code used inside document numbers
+    fn code(&self) -> &'static str {
+        match self {
+            DocumentType::Invoice => "INV",
+            DocumentType::CreditNote => "CN",
+            DocumentType::Settlement => "STL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedDocument {
+    pub id: Uuid,
+    pub airline_id: Uuid,
+    pub document_type: &'static str,
+    pub fiscal_year: i32,
+    pub sequence_no: i64,
+    pub document_number: String,
+}
+
+/// Hands out gapless document numbers per airline, document type and fiscal
+/// year. Numbers are allocated and recorded inside the caller's transaction:
+/// the sequence row stays locked until it commits, and a rollback returns the
+/// number, so committed documents never skip one.
+pub struct SequenceAllocator {
+    fiscal_year_start_month: u32,
+    prefixes: HashMap<String, String>,
+}
+
+impl SequenceAllocator {
+    pub fn new(config: &DocumentsConfig) -> Self {
+        Self {
+            fiscal_year_start_month: config.fiscal_year_start_month.clamp(1, 12),
+            prefixes: config.prefixes.iter().map(|(code, prefix)| (code.to_uppercase(), prefix.clone())).collect(),
+        }
+    }
+
+    /// Fiscal years are named after the calendar year they start in.
+    pub fn fiscal_year(&self, at: DateTime<Utc>) -> i32 {
+        if at.month() >= self.fiscal_year_start_month {
+            at.year()
+        } else {
+            at.year() - 1
+        }
+    }
+
+    fn document_number(&self, airline_code: &str, document_type: DocumentType, fiscal_year: i32, sequence_no: i64) -> String {
+        let prefix = self.prefixes.get(&airline_code.to_uppercase()).map(String::as_str).unwrap_or(airline_code);
+        format!("{}-{}-{}-{:06}", prefix, document_type.code(), fiscal_year, sequence_no)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn issue(
+        &self,
+        conn: &mut PgConnection,
+        airline_id: Uuid,
+        document_type: DocumentType,
+        order_id: Option<Uuid>,
+        reference_id: Option<Uuid>,
+        amount_nuc: i64,
+        currency: &str,
+        issued_at: DateTime<Utc>,
+    ) -> Result<IssuedDocument, Box<dyn std::error::Error + Send + Sync>> {
+        let airline_code: String = sqlx::query_scalar("SELECT code FROM airlines WHERE id = $1")
+            .bind(airline_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        let fiscal_year = self.fiscal_year(issued_at);
+        let sequence_no: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO document_sequences (airline_id, document_type, fiscal_year, last_value)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (airline_id, document_type, fiscal_year)
+            DO UPDATE SET last_value = document_sequences.last_value + 1, updated_at = NOW()
+            RETURNING last_value
+            "#,
+        )
+        .bind(airline_id)
+        .bind(document_type.as_str())
+        .bind(fiscal_year)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let document = IssuedDocument {
+            id: Uuid::new_v4(),
+            airline_id,
+            document_type: document_type.as_str(),
+            fiscal_year,
+            sequence_no,
+            document_number: self.document_number(&airline_code, document_type, fiscal_year, sequence_no),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO accounting_documents (id, airline_id, document_type, fiscal_year, sequence_no, document_number, order_id, reference_id, amount_nuc, currency, issued_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(document.id)
+        .bind(airline_id)
+        .bind(document.document_type)
+        .bind(fiscal_year)
+        .bind(sequence_no)
+        .bind(&document.document_number)
+        .bind(order_id)
+        .bind(reference_id)
+        .bind(amount_nuc)
+        .bind(currency)
+        .bind(issued_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(document)
+    }
+
+    /// Sequence numbers handed out but missing from the document register.
+    /// Always empty unless documents were deleted or written around the allocator.
+    pub async fn find_gaps(
+        conn: &mut PgConnection,
+        airline_id: Uuid,
+        document_type: DocumentType,
+        fiscal_year: i32,
+    ) -> Result<Vec<i64>, Box<dyn std::error::Error + Send + Sync>> {
+        let gaps = sqlx::query_scalar(
+            r#"
+            SELECT n FROM document_sequences s, generate_series(1::bigint, s.last_value) AS n
+            WHERE s.airline_id = $1 AND s.document_type = $2 AND s.fiscal_year = $3
+              AND NOT EXISTS (
+                SELECT 1 FROM accounting_documents d
+                WHERE d.airline_id = s.airline_id AND d.document_type = s.document_type
+                  AND d.fiscal_year = s.fiscal_year AND d.sequence_no = n
+              )
+            ORDER BY n
+            "#,
+        )
+        .bind(airline_id)
+        .bind(document_type.as_str())
+        .bind(fiscal_year)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(gaps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_fiscal_year_and_numbering() {
+        let mut prefixes = HashMap::new();
+        prefixes.insert("sq".to_string(), "SIA".to_string());
+        let allocator = SequenceAllocator::new(&DocumentsConfig { fiscal_year_start_month: 4, prefixes });
+
+        // April fiscal year: March still belongs to the previous one
+        assert_eq!(allocator.fiscal_year(Utc.with_ymd_and_hms(2026, 3, 31, 23, 0, 0).unwrap()), 2025);
+        assert_eq!(allocator.fiscal_year(Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap()), 2026);
+
+        assert_eq!(allocator.document_number("SQ", DocumentType::Invoice, 2026, 42), "SIA-INV-2026-000042");
+        assert_eq!(allocator.document_number("MH", DocumentType::CreditNote, 2026, 7), "MH-CN-2026-000007");
+    }
+}
@@ -0,0 +1,391 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::DbClient;
+use serde_json::Value;
+use altis_core::repository::SettlementRepository;
+use std::sync::Arc;
+use crate::sequences::{DocumentType, SequenceAllocator};
+
+/// Any fixed key works; it only has to be the same for every batch run.
+const BATCH_RUN_LOCK: i64 = 0x5e77_1e00;
+
+pub struct StoreSettlementRepository {
+    db: DbClient,
+    sequences: Arc<SequenceAllocator>,
+}
+
+impl StoreSettlementRepository {
+    pub fn new(db: DbClient, sequences: Arc<SequenceAllocator>) -> Self {
+        Self { db, sequences }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct BatchRow {
+    id: Uuid,
+    airline_id: Uuid,
+    period_start: Option<chrono::DateTime<chrono::Utc>>,
+    period_end: chrono::DateTime<chrono::Utc>,
+    status: String,
+    entry_count: i32,
+    total_nuc: i64,
+    currency: String,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    submitted_at: Option<chrono::DateTime<chrono::Utc>>,
+    confirmed_at: Option<chrono::DateTime<chrono::Utc>>,
+    document_number: Option<String>,
+}
+
+impl BatchRow {
+    fn to_json(&self) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "airline_id": self.airline_id,
+            "period_start": self.period_start.map(|t| t.to_rfc3339()),
+            "period_end": self.period_end.to_rfc3339(),
+            "status": self.status,
+            "entry_count": self.entry_count,
+            "total_nuc": self.total_nuc,
+            "currency": self.currency,
+            "created_at": self.created_at.map(|t| t.to_rfc3339()),
+            "submitted_at": self.submitted_at.map(|t| t.to_rfc3339()),
+            "confirmed_at": self.confirmed_at.map(|t| t.to_rfc3339()),
+            "document_number": self.document_number,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ReportRow {
+    group_key: String,
+    earned_nuc: i64,
+    unearned_nuc: i64,
+    payable_nuc: i64,
+    commission_nuc: i64,
+    refunded_nuc: i64,
+    tax_nuc: i64,
+    breakage_nuc: i64,
+    processed_items: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct InterlineRow {
+    airline_id: Uuid,
+    operating_carrier_id: Uuid,
+    payable_nuc: i64,
+    commission_nuc: i64,
+    entry_count: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct BatchEntryRow {
+    id: Uuid,
+    order_id: Uuid,
+    order_item_id: Uuid,
+    transaction_type: String,
+    amount_nuc: i32,
+    currency: Option<String>,
+    description: Option<String>,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+const BATCH_COLUMNS: &str = "id, airline_id, period_start, period_end, status, entry_count, total_nuc, currency, created_at, submitted_at, confirmed_at, document_number";
+
+#[async_trait]
+impl SettlementRepository for StoreSettlementRepository {
+    async fn create_batches(
+        &self,
+        period_end: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+
+        // One run at a time, so no entry can land in two batches.
+        // Interline postings (with a counterparty) are billed between carriers
+        // and stay out of the airline's own settlement batches; sandbox orders
+        // are never settled.
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(BATCH_RUN_LOCK)
+            .execute(&mut *tx)
+            .await?;
+
+        let airlines: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT o.airline_id
+            FROM order_ledger l JOIN orders o ON o.id = l.order_id
+            WHERE l.settlement_batch_id IS NULL AND l.created_at < $1 AND o.airline_id IS NOT NULL
+              AND l.counterparty_id IS NULL AND NOT o.test
+            "#,
+        )
+        .bind(period_end)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut batches = Vec::new();
+        for airline_id in airlines {
+            let batch_id = Uuid::new_v4();
+            sqlx::query("INSERT INTO settlement_batches (id, airline_id, period_end) VALUES ($1, $2, $3)")
+                .bind(batch_id)
+                .bind(airline_id)
+                .bind(period_end)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE order_ledger l SET settlement_batch_id = $1
+                FROM orders o
+                WHERE o.id = l.order_id AND o.airline_id = $2
+                  AND l.settlement_batch_id IS NULL AND l.created_at < $3
+                  AND l.counterparty_id IS NULL AND NOT o.test
+                "#,
+            )
+            .bind(batch_id)
+            .bind(airline_id)
+            .bind(period_end)
+            .execute(&mut *tx)
+            .await?;
+
+            let total_nuc: i64 = sqlx::query_scalar(
+                "SELECT COALESCE(SUM(amount_nuc), 0)::bigint FROM order_ledger WHERE settlement_batch_id = $1",
+            )
+            .bind(batch_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // Numbered in the same transaction, so a failed run leaves no gap
+            let statement = self.sequences.issue(
+                &mut tx,
+                airline_id,
+                DocumentType::Settlement,
+                None,
+                Some(batch_id),
+                total_nuc,
+                "NUC",
+                chrono::Utc::now(),
+            ).await?;
+
+            let row = sqlx::query_as::<_, BatchRow>(
+                r#"
+                UPDATE settlement_batches b SET
+                    period_start = agg.period_start,
+                    entry_count = agg.entry_count,
+                    total_nuc = agg.total_nuc,
+                    document_number = $2
+                FROM (
+                    SELECT MIN(created_at) AS period_start, COUNT(*)::int AS entry_count, COALESCE(SUM(amount_nuc), 0)::bigint AS total_nuc
+                    FROM order_ledger WHERE settlement_batch_id = $1
+                ) agg
+                WHERE b.id = $1
+                RETURNING b.id, b.airline_id, b.period_start, b.period_end, b.status, b.entry_count, b.total_nuc, b.currency, b.created_at, b.submitted_at, b.confirmed_at, b.document_number
+                "#,
+            )
+            .bind(batch_id)
+            .bind(&statement.document_number)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            batches.push(row.to_json());
+        }
+
+        tx.commit().await?;
+        Ok(batches)
+    }
+
+    async fn list_batches(
+        &self,
+        airline_id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, BatchRow>(&format!(
+            "SELECT {} FROM settlement_batches WHERE airline_id = $1 ORDER BY period_end DESC, created_at DESC",
+            BATCH_COLUMNS,
+        ))
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.iter().map(BatchRow::to_json).collect())
+    }
+
+    async fn get_batch(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let row = sqlx::query_as::<_, BatchRow>(&format!(
+            "SELECT {} FROM settlement_batches WHERE id = $1",
+            BATCH_COLUMNS,
+        ))
+        .bind(id)
+        .fetch_optional(self.db.writer())
+        .await?;
+
+        Ok(row.as_ref().map(BatchRow::to_json))
+    }
+
+    async fn get_batch_entries(
+        &self,
+        id: Uuid,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, BatchEntryRow>(
+            "SELECT id, order_id, order_item_id, transaction_type, amount_nuc, currency, description, created_at FROM order_ledger WHERE settlement_batch_id = $1 ORDER BY order_id, created_at"
+        )
+        .bind(id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            serde_json::json!({
+                "id": row.id,
+                "order_id": row.order_id,
+                "order_item_id": row.order_item_id,
+                "transaction_type": row.transaction_type,
+                "amount_nuc": row.amount_nuc,
+                "currency": row.currency.unwrap_or_else(|| "NUC".to_string()),
+                "description": row.description,
+                "created_at": row.created_at.unwrap_or_else(chrono::Utc::now).to_rfc3339(),
+            })
+        }).collect())
+    }
+
+    async fn update_batch_status(
+        &self,
+        id: Uuid,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE settlement_batches SET
+                status = $3::varchar,
+                submitted_at = CASE WHEN $3::varchar = 'SUBMITTED' THEN NOW() ELSE submitted_at END,
+                confirmed_at = CASE WHEN $3::varchar = 'CONFIRMED' THEN NOW() ELSE confirmed_at END
+            WHERE id = $1 AND status = $2
+            "#,
+        )
+        .bind(id)
+        .bind(from)
+        .bind(to)
+        .execute(self.db.writer())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn settlement_report(
+        &self,
+        airline_id: Uuid,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        group_by: &str,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // Sales figures come from items sold in the period; earned and refunded
+        // come from ledger entries posted in the period, whenever the sale was.
+        let rows = sqlx::query_as::<_, ReportRow>(
+            r#"
+            WITH sales AS (
+                SELECT
+                    CASE $4::text
+                        WHEN 'product_type' THEN i.product_type::text
+                        WHEN 'day' THEN to_char(o.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD')
+                        ELSE 'ALL'
+                    END AS group_key,
+                    SUM(i.price_nuc * (COALESCE(i.quantity, 1) - i.refunded_quantity)) FILTER (WHERE i.revenue_status = 'unearned' AND i.status = 'ACTIVE') AS unearned_nuc,
+                    SUM(COALESCE(i.net_rate_nuc, i.price_nuc * (COALESCE(i.quantity, 1) - i.refunded_quantity) - COALESCE(i.commission_nuc, 0))) AS payable_nuc,
+                    SUM(COALESCE(i.commission_nuc, 0)) AS commission_nuc,
+                    COUNT(*) AS processed_items
+                FROM order_items i JOIN orders o ON o.id = i.order_id
+                WHERE o.airline_id = $1 AND o.status IN ('PAID', 'FULFILLED', 'ARCHIVED') AND NOT o.test
+                  AND o.created_at >= $2 AND o.created_at < $3
+                GROUP BY 1
+            ),
+            ledger AS (
+                SELECT
+                    CASE $4::text
+                        WHEN 'product_type' THEN i.product_type::text
+                        WHEN 'day' THEN to_char(l.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD')
+                        ELSE 'ALL'
+                    END AS group_key,
+                    SUM(l.amount_nuc) FILTER (WHERE l.transaction_type IN ('REVENUE_RECOGNITION', 'BREAKAGE')) AS earned_nuc,
+                    SUM(l.amount_nuc) FILTER (WHERE l.transaction_type = 'REFUND') AS refunded_nuc,
+                    SUM(l.tax_nuc) FILTER (WHERE l.transaction_type IN ('REVENUE_RECOGNITION', 'BREAKAGE')) AS tax_nuc,
+                    SUM(l.amount_nuc) FILTER (WHERE l.transaction_type = 'BREAKAGE') AS breakage_nuc
+                FROM order_ledger l
+                JOIN orders o ON o.id = l.order_id
+                JOIN order_items i ON i.id = l.order_item_id
+                WHERE o.airline_id = $1 AND NOT o.test AND l.created_at >= $2 AND l.created_at < $3
+                GROUP BY 1
+            )
+            SELECT
+                COALESCE(s.group_key, l.group_key) AS group_key,
+                COALESCE(l.earned_nuc, 0)::bigint AS earned_nuc,
+                COALESCE(s.unearned_nuc, 0)::bigint AS unearned_nuc,
+                COALESCE(s.payable_nuc, 0)::bigint AS payable_nuc,
+                COALESCE(s.commission_nuc, 0)::bigint AS commission_nuc,
+                COALESCE(l.refunded_nuc, 0)::bigint AS refunded_nuc,
+                COALESCE(l.tax_nuc, 0)::bigint AS tax_nuc,
+                COALESCE(l.breakage_nuc, 0)::bigint AS breakage_nuc,
+                COALESCE(s.processed_items, 0)::bigint AS processed_items
+            FROM sales s FULL OUTER JOIN ledger l ON l.group_key = s.group_key
+            ORDER BY 1
+            "#,
+        )
+        .bind(airline_id)
+        .bind(from)
+        .bind(to)
+        .bind(group_by)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            serde_json::json!({
+                "group": row.group_key,
+                "earned_nuc": row.earned_nuc,
+                "unearned_nuc": row.unearned_nuc,
+                "payable_nuc": row.payable_nuc,
+                "commission_nuc": row.commission_nuc,
+                "refunded_nuc": row.refunded_nuc,
+                "tax_nuc": row.tax_nuc,
+                "breakage_nuc": row.breakage_nuc,
+                "processed_items": row.processed_items,
+            })
+        }).collect())
+    }
+
+    async fn interline_report(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        carrier_id: Option<Uuid>,
+    ) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, InterlineRow>(
+            r#"
+            SELECT
+                o.airline_id,
+                l.counterparty_id AS operating_carrier_id,
+                COALESCE(SUM(l.amount_nuc) FILTER (WHERE l.transaction_type = 'INTERLINE_PAYABLE'), 0)::bigint AS payable_nuc,
+                COALESCE(SUM(l.amount_nuc) FILTER (WHERE l.transaction_type = 'INTERLINE_COMMISSION'), 0)::bigint AS commission_nuc,
+                COUNT(*) AS entry_count
+            FROM order_ledger l JOIN orders o ON o.id = l.order_id
+            WHERE l.counterparty_id IS NOT NULL AND o.airline_id IS NOT NULL AND NOT o.test
+              AND l.created_at >= $1 AND l.created_at < $2
+              AND ($3::uuid IS NULL OR l.counterparty_id = $3)
+            GROUP BY o.airline_id, l.counterparty_id
+            ORDER BY o.airline_id, l.counterparty_id
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(carrier_id)
+        .fetch_all(self.db.reader())
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            serde_json::json!({
+                "airline_id": row.airline_id,
+                "operating_carrier_id": row.operating_carrier_id,
+                "payable_nuc": row.payable_nuc,
+                "commission_nuc": row.commission_nuc,
+                "entry_count": row.entry_count,
+            })
+        }).collect())
+    }
+}
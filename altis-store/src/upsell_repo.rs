@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::UpsellRepository;
+
+use crate::DbClient;
+
+pub struct StoreUpsellRepository {
+    db: DbClient,
+}
+
+impl StoreUpsellRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl UpsellRepository for StoreUpsellRepository {
+    async fn open_upsell_offers(&self, order_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // From the primary: the offers just made must be found again
+        let offers = sqlx::query_scalar::<_, Value>(
+            "SELECT to_jsonb(u) FROM upsell_offers u WHERE order_id = $1 AND status = 'OPEN' AND expires_at > NOW() ORDER BY rank",
+        )
+        .bind(order_id)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(offers)
+    }
+
+    async fn save_upsell_offers(&self, order_id: Uuid, offers: &[Value]) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut tx = self.db.writer().begin().await?;
+        let mut saved = Vec::with_capacity(offers.len());
+        for offer in offers {
+            let id = offer["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()).ok_or("upsell offer without id")?;
+            let expires_at = offer["expires_at"].as_str()
+                .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                .ok_or("upsell offer without expires_at")?;
+            let row = sqlx::query_scalar::<_, Value>(
+                r#"
+                INSERT INTO upsell_offers (id, order_id, rank, reason, item, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING to_jsonb(upsell_offers)
+                "#,
+            )
+            .bind(id)
+            .bind(order_id)
+            .bind(offer["rank"].as_i64().unwrap_or(0) as i32)
+            .bind(offer["reason"].as_str().unwrap_or("AVAILABLE"))
+            .bind(&offer["item"])
+            .bind(expires_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            saved.push(row);
+        }
+        tx.commit().await?;
+        Ok(saved)
+    }
+
+    async fn claim_upsell_offer(&self, id: Uuid, order_id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let offer = sqlx::query_scalar::<_, Value>(
+            r#"
+            UPDATE upsell_offers SET status = 'ACCEPTING', updated_at = NOW()
+            WHERE id = $1 AND order_id = $2 AND status = 'OPEN' AND expires_at > NOW()
+            RETURNING to_jsonb(upsell_offers)
+            "#,
+        )
+        .bind(id)
+        .bind(order_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(offer)
+    }
+
+    async fn release_upsell_offer(&self, id: Uuid) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("UPDATE upsell_offers SET status = 'OPEN', updated_at = NOW() WHERE id = $1 AND status = 'ACCEPTING'")
+            .bind(id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(())
+    }
+
+    async fn complete_upsell_offer(&self, id: Uuid, order_item_id: Uuid) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            "UPDATE upsell_offers SET status = 'ACCEPTED', order_item_id = $2, updated_at = NOW() WHERE id = $1 AND status = 'ACCEPTING'",
+        )
+        .bind(id)
+        .bind(order_item_id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(())
+    }
+}
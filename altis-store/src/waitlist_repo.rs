@@ -0,0 +1,209 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::WaitlistRepository;
+
+use crate::DbClient;
+
+pub struct StoreWaitlistRepository {
+    db: DbClient,
+}
+
+impl StoreWaitlistRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+/// An entry with its place among those still waiting for the flight
+const ENTRY_JSON: &str = "to_jsonb(w) || jsonb_build_object('position', CASE WHEN w.status = 'WAITING' THEN (\
+     SELECT count(*) FROM waitlist_entries q \
+     WHERE q.flight_id = w.flight_id AND q.status = 'WAITING' AND (q.created_at, q.id) <= (w.created_at, w.id)) END)";
+
+#[async_trait]
+impl WaitlistRepository for StoreWaitlistRepository {
+    async fn join_waitlist(&self, entry: &Value) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let id = |key: &str| entry[key].as_str().and_then(|id| Uuid::parse_str(id).ok());
+        let joined: Option<Value> = sqlx::query_scalar(&format!(
+            r#"
+            WITH w AS (
+                INSERT INTO waitlist_entries (flight_id, airline_id, customer_id, customer_email, seats)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT DO NOTHING
+                RETURNING *
+            )
+            SELECT {} FROM w
+            "#,
+            ENTRY_JSON
+        ))
+        .bind(id("flight_id").ok_or("missing flight_id")?)
+        .bind(id("airline_id"))
+        .bind(entry["customer_id"].as_str().ok_or("missing customer_id")?)
+        .bind(entry["customer_email"].as_str())
+        .bind(entry["seats"].as_i64().unwrap_or(1) as i32)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(joined)
+    }
+
+    async fn find_waitlist_entry(&self, flight_id: Uuid, customer_id: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let entry: Option<Value> = sqlx::query_scalar(&format!(
+            "SELECT {} FROM waitlist_entries w WHERE w.flight_id = $1 AND w.customer_id = $2 AND w.status IN ('WAITING', 'OFFERED')",
+            ENTRY_JSON
+        ))
+        .bind(flight_id)
+        .bind(customer_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(entry)
+    }
+
+    async fn get_waitlist_entry(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let entry: Option<Value> = sqlx::query_scalar(&format!("SELECT {} FROM waitlist_entries w WHERE w.id = $1", ENTRY_JSON))
+            .bind(id)
+            .fetch_optional(self.db.writer())
+            .await?;
+        Ok(entry)
+    }
+
+    async fn leave_waitlist(&self, flight_id: Uuid, customer_id: &str) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        // The old row: an OFFERED entry still holds seats to give back
+        let left: Option<Value> = sqlx::query_scalar(
+            r#"
+            WITH old AS (
+                SELECT * FROM waitlist_entries
+                WHERE flight_id = $1 AND customer_id = $2 AND status IN ('WAITING', 'OFFERED')
+                FOR UPDATE
+            )
+            UPDATE waitlist_entries w SET status = 'CANCELLED', updated_at = NOW()
+            FROM old WHERE w.id = old.id
+            RETURNING to_jsonb(old)
+            "#,
+        )
+        .bind(flight_id)
+        .bind(customer_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(left)
+    }
+
+    async fn waitlisted_flights(&self, limit: i64) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
+        let flights: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT flight_id FROM waitlist_entries WHERE status = 'WAITING'
+            GROUP BY flight_id ORDER BY min(created_at) LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(flights)
+    }
+
+    async fn next_waiting(&self, flight_id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let entry: Option<Value> = sqlx::query_scalar(&format!(
+            "SELECT {} FROM waitlist_entries w WHERE w.flight_id = $1 AND w.status = 'WAITING' ORDER BY w.created_at, w.id LIMIT 1",
+            ENTRY_JSON
+        ))
+        .bind(flight_id)
+        .fetch_optional(self.db.writer())
+        .await?;
+        Ok(entry)
+    }
+
+    async fn mark_offered(&self, id: Uuid, offer_id: Uuid, expires_at: DateTime<Utc>) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE waitlist_entries SET status = 'OFFERED', offer_id = $2, offered_at = NOW(), offer_expires_at = $3, updated_at = NOW()
+            WHERE id = $1 AND status = 'WAITING'
+            "#,
+        )
+        .bind(id)
+        .bind(offer_id)
+        .bind(expires_at)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn book_waitlist_entry(&self, id: Uuid, customer_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE waitlist_entries SET status = 'BOOKED', updated_at = NOW()
+            WHERE id = $1 AND customer_id = $2 AND status = 'OFFERED' AND offer_expires_at > NOW()
+            "#,
+        )
+        .bind(id)
+        .bind(customer_id)
+        .execute(self.db.writer())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn expire_waitlist_offers(&self, limit: i64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let expired: Vec<Value> = sqlx::query_scalar(
+            r#"
+            UPDATE waitlist_entries SET status = 'EXPIRED', updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM waitlist_entries
+                WHERE status = 'OFFERED' AND offer_expires_at <= NOW()
+                ORDER BY offer_expires_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING to_jsonb(waitlist_entries)
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(self.db.writer())
+        .await?;
+        Ok(expired)
+    }
+
+    async fn close_waitlist(&self, flight_id: Uuid) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let result = sqlx::query("UPDATE waitlist_entries SET status = 'EXPIRED', updated_at = NOW() WHERE flight_id = $1 AND status = 'WAITING'")
+            .bind(flight_id)
+            .execute(self.db.writer())
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn waitlist_depth(&self, airline_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let depth: Vec<Value> = sqlx::query_scalar(
+            r#"
+            SELECT jsonb_build_object(
+                'flight_id', w.flight_id,
+                'product_code', p.product_code,
+                'departure_time', p.metadata->>'departure_time',
+                'waiting', count(*) FILTER (WHERE w.status = 'WAITING'),
+                'waiting_seats', coalesce(sum(w.seats) FILTER (WHERE w.status = 'WAITING'), 0),
+                'offered', count(*) FILTER (WHERE w.status = 'OFFERED'),
+                'oldest_waiting_since', min(w.created_at) FILTER (WHERE w.status = 'WAITING'))
+            FROM waitlist_entries w JOIN products p ON p.id = w.flight_id
+            WHERE w.airline_id = $1 AND w.status IN ('WAITING', 'OFFERED')
+            GROUP BY w.flight_id, p.product_code, p.metadata->>'departure_time'
+            ORDER BY count(*) FILTER (WHERE w.status = 'WAITING') DESC, w.flight_id
+            "#,
+        )
+        .bind(airline_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(depth)
+    }
+
+    async fn list_waitlist(&self, flight_id: Uuid) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let entries: Vec<Value> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT {} FROM waitlist_entries w
+            WHERE w.flight_id = $1 AND w.status IN ('WAITING', 'OFFERED')
+            ORDER BY w.status = 'WAITING', w.created_at, w.id
+            "#,
+            ENTRY_JSON
+        ))
+        .bind(flight_id)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(entries)
+    }
+}
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use altis_core::repository::WebhookDeliveryRepository;
+
+use crate::DbClient;
+
+pub struct StoreWebhookDeliveryRepository {
+    db: DbClient,
+}
+
+impl StoreWebhookDeliveryRepository {
+    pub fn new(db: DbClient) -> Self {
+        Self { db }
+    }
+}
+
+const DELIVERY_COLUMNS: &str = "id, partner, event_id, event_type, is_test, redelivery_of, url, request_headers, request_body, \
+     response_status, response_headers, response_body, error, duration_ms, created_at";
+
+#[derive(sqlx::FromRow)]
+struct WebhookDeliveryRow {
+    id: Uuid,
+    partner: String,
+    event_id: Uuid,
+    event_type: String,
+    is_test: bool,
+    redelivery_of: Option<Uuid>,
+    url: String,
+    request_headers: Value,
+    request_body: String,
+    response_status: Option<i32>,
+    response_headers: Option<Value>,
+    response_body: Option<String>,
+    error: Option<String>,
+    duration_ms: i32,
+    created_at: DateTime<Utc>,
+}
+
+impl WebhookDeliveryRow {
+    fn into_json(self) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "partner": self.partner,
+            "event_id": self.event_id,
+            "event_type": self.event_type,
+            "is_test": self.is_test,
+            "redelivery_of": self.redelivery_of,
+            "url": self.url,
+            "request_headers": self.request_headers,
+            "request_body": self.request_body,
+            "response_status": self.response_status,
+            "response_headers": self.response_headers,
+            "response_body": self.response_body,
+            "error": self.error,
+            "duration_ms": self.duration_ms,
+            "created_at": self.created_at,
+        })
+    }
+}
+
+#[async_trait]
+impl WebhookDeliveryRepository for StoreWebhookDeliveryRepository {
+    async fn record_delivery(&self, delivery: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let uuid = |key: &str| delivery[key].as_str().map(Uuid::parse_str).transpose();
+        let row: WebhookDeliveryRow = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO webhook_deliveries (
+                partner, event_id, event_type, is_test, redelivery_of, url, request_headers, request_body,
+                response_status, response_headers, response_body, error, duration_ms
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING {}
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(delivery["partner"].as_str().ok_or("missing partner")?)
+        .bind(uuid("event_id")?.ok_or("missing event_id")?)
+        .bind(delivery["event_type"].as_str().ok_or("missing event_type")?)
+        .bind(delivery["is_test"].as_bool().unwrap_or(false))
+        .bind(uuid("redelivery_of")?)
+        .bind(delivery["url"].as_str().ok_or("missing url")?)
+        .bind(&delivery["request_headers"])
+        .bind(delivery["request_body"].as_str().ok_or("missing request_body")?)
+        .bind(delivery["response_status"].as_i64().map(|s| s as i32))
+        .bind(Some(&delivery["response_headers"]).filter(|h| !h.is_null()))
+        .bind(delivery["response_body"].as_str())
+        .bind(delivery["error"].as_str())
+        .bind(delivery["duration_ms"].as_i64().unwrap_or(0) as i32)
+        .fetch_one(self.db.writer())
+        .await?;
+        Ok(row.into_json())
+    }
+
+    async fn list_deliveries(&self, partner: &str, limit: i64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<WebhookDeliveryRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE partner = $1 ORDER BY created_at DESC LIMIT $2",
+            DELIVERY_COLUMNS
+        ))
+        .bind(partner)
+        .bind(limit)
+        .fetch_all(self.db.reader())
+        .await?;
+        Ok(rows.into_iter().map(WebhookDeliveryRow::into_json).collect())
+    }
+
+    async fn get_delivery(&self, id: Uuid) -> Result<Option<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let row: Option<WebhookDeliveryRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE id = $1",
+            DELIVERY_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(self.db.reader())
+        .await?;
+        Ok(row.map(WebhookDeliveryRow::into_json))
+    }
+}